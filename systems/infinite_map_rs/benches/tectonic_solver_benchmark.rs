@@ -0,0 +1,63 @@
+// Tectonic Solver Performance Benchmark
+//
+// Compares the two repulsion modes of the force-directed layout solver on
+// the same random tile graphs:
+// - Exact: every pair of tiles, O(n²) per iteration
+// - Barnes-Hut (theta 0.5): quadtree approximation, O(n log n)
+//
+// Target: Barnes-Hut faster than Exact from 500 tiles up, widening with size
+
+use std::collections::HashMap;
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use infinite_map_rs::tectonic::{
+    BondType, CognitiveBond, Coord, ForceDirectedSolver, HilbertConstraint, SolverMode, TileId,
+};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+/// Deterministic random layout of `count` tiles with about two bonds per tile
+fn random_graph(count: u64, seed: u64) -> (HashMap<TileId, Coord>, Vec<CognitiveBond>) {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let extent = (count as f64).sqrt() * 150.0;
+    let positions = (0..count)
+        .map(|id| (id, (rng.gen_range(0.0..extent), rng.gen_range(0.0..extent))))
+        .collect();
+    let bonds = (0..count * 2)
+        .map(|_| CognitiveBond {
+            source: rng.gen_range(0..count),
+            dest: rng.gen_range(0..count),
+            strength: rng.gen_range(0.1..1.0),
+            bond_type: BondType::Cognitive,
+            pulse_count: 1,
+        })
+        .filter(|bond| bond.source != bond.dest)
+        .collect();
+    (positions, bonds)
+}
+
+fn bench_solver_modes(c: &mut Criterion) {
+    let mut group = c.benchmark_group("tectonic_solve");
+    group.sample_size(10);
+    let constraint = HilbertConstraint::new(0.0);
+
+    for &tiles in &[500u64, 1000, 2000] {
+        let (positions, bonds) = random_graph(tiles, 42);
+        group.throughput(Throughput::Elements(tiles));
+
+        for (name, mode) in [
+            ("exact", SolverMode::Exact),
+            ("barnes_hut", SolverMode::BarnesHut),
+        ] {
+            let solver = ForceDirectedSolver::new(100.0, 50.0).with_mode(mode, 0.5);
+            group.bench_with_input(BenchmarkId::new(name, tiles), &tiles, |b, _| {
+                b.iter(|| solver.solve(black_box(&positions), black_box(&bonds), &constraint))
+            });
+        }
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_solver_modes);
+criterion_main!(benches);
@@ -33,7 +33,7 @@ use crate::{
 };
 
 use crate::evolution_protocol::DaemonFrequencyBand;
-use crate::visual_shell::{DaemonId, FrequencyBand, VisualShell};
+use crate::visual_shell::{DaemonId, FrequencyBand, VisualShell, DEFAULT_HILBERT_ORDER};
 
 use crate::antigravity_watcher::AntigravityWatcher;
 use crate::cartridge_texture_manager::CartridgeTextureManager;
@@ -157,6 +157,8 @@ pub struct InfiniteMapApp<'a> {
     pub qemu_bridge: Option<crate::qemu::SharedMemoryBridge>,
     // Phase 36.2: QMP Control Channel
     pub qmp_tx: Option<tokio::sync::mpsc::Sender<crate::qemu::QmpCommand>>,
+    pub qmp_status: Option<tokio::sync::watch::Receiver<crate::qemu::QmpStatus>>,
+    pub qmp_events: Option<tokio::sync::mpsc::Receiver<crate::qemu::QmpEvent>>,
     // Phase 37.1: Neural Introspection - Hover-to-Query
     pub hovered_memory_address: Option<usize>,
     // Phase 38: Antigravity Bridge (Tectonic)
@@ -294,8 +296,7 @@ pub struct InfiniteMapApp<'a> {
     // Phase 45 / Horizon 1.2: Filesystem Hilbert Mapper
     pub filesystem_hilbert_manager: Option<crate::filesystem_hilbert::FilesystemHilbertManager>,
     // Phase 45 / Horizon 1.3: Terminal Tiles
-    pub terminal_tiles: Vec<crate::terminal_tile::TerminalTile>,
-    #[cfg(feature = "hypervisor")]
+    pub terminal_tiles: crate::terminal_tile::TerminalTilePool,
     pub terminal_clone_manager: Option<crate::terminal_clone::TerminalCloneManager>,
 
     // Phase 50: Visual Kernel - Sovereign GPU-Native Windowing
@@ -401,6 +402,8 @@ impl<'a> InfiniteMapApp<'a> {
             // Phase 36: QEMU Shared Memory Bridge
             qemu_bridge: None,
             qmp_tx: None,
+            qmp_status: None,
+            qmp_events: None,
             introspection_rx: None, // Initialized below
             introspection_tx: tokio::sync::mpsc::channel(1).0, // Use dummy, overwritten below
 
@@ -531,8 +534,7 @@ impl<'a> InfiniteMapApp<'a> {
                     "/home/jericho/zion/projects/geometry_os/geometry_os",
                 )),
             ),
-            terminal_tiles: Vec::new(),
-            #[cfg(feature = "hypervisor")]
+            terminal_tiles: crate::terminal_tile::TerminalTilePool::new(),
             terminal_clone_manager: Some(crate::terminal_clone::TerminalCloneManager::new()),
             // Phase 50: Visual Kernel - Sovereign GPU-Native Windowing
             visual_kernel: None,
@@ -543,6 +545,10 @@ impl<'a> InfiniteMapApp<'a> {
                 supports_i64: true, // Assume native support initially
                 vendor_name: "Unknown".to_string(),
                 device_name: "Unknown".to_string(),
+                features: wgpu::Features::empty(),
+                format_features: std::collections::HashMap::new(),
+                limits: wgpu::Limits::default(),
+                vram_bytes: None,
             },
             // Phase 49: Morph visual effect - initially inactive
             morph_effect_until: None,
@@ -625,6 +631,7 @@ impl<'a> InfiniteMapApp<'a> {
     pub async fn initialize_gpu_capabilities(&mut self, adapter: &wgpu::Adapter) {
         self.gpu_caps = crate::gpu_capabilities::GpuCapabilities::new(adapter).await;
         self.log_gpu_info();
+        self.apply_vram_limit();
 
         // Phase 46.4: Initialize WLU GPU backend if configured
         if CONFIG.use_wlu_gpu {
@@ -650,6 +657,22 @@ impl<'a> InfiniteMapApp<'a> {
     pub fn initialize_gpu_capabilities_sync(&mut self, adapter: &wgpu::Adapter) {
         self.gpu_caps = pollster::block_on(crate::gpu_capabilities::GpuCapabilities::new(adapter));
         self.log_gpu_info();
+        self.apply_vram_limit();
+    }
+
+    /// Measure System health against the adapter's real VRAM (4GB if unknown)
+    fn apply_vram_limit(&mut self) {
+        match self.gpu_caps.vram_bytes {
+            Some(vram) => {
+                log::info!("VRAM: {} MiB", vram / (1024 * 1024));
+                self.diagnostic_overlay.set_vram_limit(vram);
+            },
+            None => {
+                log::info!("VRAM size unknown, assuming default budget");
+                self.diagnostic_overlay
+                    .set_vram_limit(crate::diagnostic::DEFAULT_VRAM_LIMIT_BYTES);
+            },
+        }
     }
 
     pub fn set_visual_command_rx(
@@ -1350,6 +1373,77 @@ impl<'a> InfiniteMapApp<'a> {
         }
     }
 
+    // Reflect QMP events (pause, reset, shutdown) on the QEMU memory windows
+    pub fn handle_qmp_events(&mut self) {
+        let Some(events) = self.qmp_events.as_mut() else {
+            return;
+        };
+        let mut border = None;
+        while let Ok(event) = events.try_recv() {
+            log::info!("🔔 QMP: {:?}", event);
+            border = Some(match event {
+                crate::qemu::QmpEvent::Stop | crate::qemu::QmpEvent::PowerdownRequested => {
+                    Some([1.0, 0.7, 0.0, 1.0]) // Amber: paused or shutting down
+                },
+                crate::qemu::QmpEvent::Shutdown { .. } => Some([1.0, 0.0, 0.0, 1.0]), // Red: VM gone
+                crate::qemu::QmpEvent::Resume | crate::qemu::QmpEvent::Reset { .. } => None,
+            });
+        }
+
+        let Some(border) = border else {
+            return;
+        };
+        for window in self.window_manager.get_windows_mut() {
+            if window.has_memory_texture {
+                window.custom_border_color = border;
+            }
+        }
+    }
+
+    // Handle RISC-V debugger commands (F9 = toggle single-step, F10 = step)
+    pub fn handle_riscv_debug_commands(&mut self) {
+        let Some(commands) = self.input_manager.get_riscv_debug_commands() else {
+            return;
+        };
+        let Some(ref executor_arc) = self.riscv_executor else {
+            log::warn!("RISC-V debug command ignored: no executor running");
+            return;
+        };
+        let Ok(mut executor) = executor_arc.lock() else {
+            return;
+        };
+        for cmd in commands {
+            match cmd {
+                157 => {
+                    let enabled = !executor.single_step();
+                    executor.set_single_step(enabled);
+                },
+                158 => match executor.step() {
+                    Ok(step) => {
+                        let reg = step
+                            .reg_write
+                            .map(|(reg, value)| format!(" x{} = 0x{:x}", reg, value))
+                            .unwrap_or_default();
+                        let mem = step
+                            .mem_write
+                            .map(|w| format!(" [0x{:x}] = 0x{:x}", w.addr, w.value))
+                            .unwrap_or_default();
+                        log::info!(
+                            "🐞 0x{:08x}: {:08x} {}{}{}",
+                            step.pc,
+                            step.word,
+                            step.mnemonic,
+                            reg,
+                            mem
+                        );
+                    },
+                    Err(e) => log::warn!("🐞 RISC-V step failed: {}", e),
+                },
+                _ => {},
+            }
+        }
+    }
+
     // Phase 44: Handle Multi-VM commands (Ctrl+Shift+M)
     pub fn handle_multi_vm_commands(&mut self) {
         if let Some(commands) = self.input_manager.get_multi_vm_commands() {
@@ -1399,6 +1493,19 @@ impl<'a> InfiniteMapApp<'a> {
         let rts = rts_result.unwrap();
 
         // Create a temporary texture for the compiler
+        // Need Unorm for data access in compiler, there is no stand-in
+        let usage = wgpu::TextureUsages::TEXTURE_BINDING
+            | wgpu::TextureUsages::STORAGE_BINDING
+            | wgpu::TextureUsages::COPY_DST;
+        let Some(format) = self
+            .gpu_caps
+            .best_supported_format(&[wgpu::TextureFormat::Rgba8Unorm], usage)
+        else {
+            log::error!("Adapter cannot bind Rgba8Unorm as storage, cannot compile tile");
+            self.compilation_status = CompilationStatus::Error;
+            return;
+        };
+
         let device = self.renderer.get_device();
         let queue = self.renderer.get_queue();
 
@@ -1412,10 +1519,8 @@ impl<'a> InfiniteMapApp<'a> {
             mip_level_count: 1,
             sample_count: 1,
             dimension: wgpu::TextureDimension::D2,
-            format: wgpu::TextureFormat::Rgba8Unorm, // Need Unorm for data access in compiler
-            usage: wgpu::TextureUsages::TEXTURE_BINDING
-                | wgpu::TextureUsages::STORAGE_BINDING
-                | wgpu::TextureUsages::COPY_DST,
+            format,
+            usage,
             view_formats: &[],
         });
 
@@ -2043,10 +2148,15 @@ impl<'a> InfiniteMapApp<'a> {
     // Phase 40.5: Update all loaded modules (call periodically)
     pub fn update_qemu_shm_process(&mut self) {
         if let Some(proc) = &mut self.qemu_shm_process {
+            // start() doesn't wait for QEMU to come up; report an early exit here
+            if let Err(e) = proc.check_startup() {
+                log::error!("Failed to start QEMU: {}", e);
+                log::error!("{}", e.remediation());
+            }
+
             if proc.is_running() {
-                // Update test pattern (Phase 47 Task 2 simulation)
-                // In production this would just read the SHM that QEMU wrote to
-                proc.update_test_pattern();
+                // Phase 47 Task 2: Pull the guest display out of SHM
+                proc.read_framebuffer_from_shm();
 
                 // Phase 47 Task 4: Forward Input
                 if let Some(input_data) = self.input_manager.get_console_input() {
@@ -2103,9 +2213,12 @@ impl<'a> InfiniteMapApp<'a> {
             vcpu_count: 2,
             width: 640,
             height: 480,
-            enable_kvm: false,
+            // Falls back to TCG when /dev/kvm is unusable
+            enable_kvm: true,
             ..Default::default()
-        };
+        }
+        // Expose RAM + QMP so try_connect_qemu_shared_memory can attach
+        .with_geometry_integration("default");
 
         let mut proc = crate::virtual_machine::QemuProcessWithShm::new(config);
 
@@ -2146,7 +2259,7 @@ impl<'a> InfiniteMapApp<'a> {
             },
             Err(e) => {
                 log::error!("Failed to start QEMU: {}", e);
-                log::error!("Ensure /dev/kvm exists and is accessible.");
+                log::error!("{}", e.remediation());
             },
         }
     }
@@ -2280,8 +2393,19 @@ impl<'a> InfiniteMapApp<'a> {
                 log::info!("🧬 Loading RISC-V program from (RTS-PNG): {}", path);
                 executor.load_program_from_file(&path)
             } else {
-                log::info!("💾 Loading RISC-V kernel from (Raw Binary): {}", path);
-                executor.load_program_raw(&path, 0)
+                match std::fs::read(&path) {
+                    Ok(bytes) if bytes.starts_with(crate::riscv::elf::ELF_MAGIC) => {
+                        log::info!("🧩 Loading RISC-V program from (ELF): {}", path);
+                        executor
+                            .load_elf(&bytes)
+                            .map(|_| ())
+                            .map_err(|e| e.to_string())
+                    },
+                    _ => {
+                        log::info!("💾 Loading RISC-V kernel from (Raw Binary): {}", path);
+                        executor.load_program_raw(&path, 0)
+                    },
+                }
             };
 
             if let Err(e) = res {
@@ -2372,11 +2496,16 @@ impl<'a> InfiniteMapApp<'a> {
                                 let mut sorted = entries.clone();
                                 sorted.sort_by(|a, b| b.count.cmp(&a.count));
                                 for entry in sorted.iter().take(5) {
-                                    log::info!(
-                                        "  Block 0x{:04x}: {} executions",
-                                        entry.pc,
-                                        entry.count
-                                    );
+                                    match executor.read_instruction(entry.pc as u64) {
+                                        Some(word) => {
+                                            log::info!("  Block {}", entry.describe(word))
+                                        },
+                                        None => log::info!(
+                                            "  Block 0x{:04x}: {} executions",
+                                            entry.pc,
+                                            entry.count
+                                        ),
+                                    }
                                 }
                             }
                             self.profiler_last_poll = now;
@@ -3590,7 +3719,7 @@ impl<'a> InfiniteMapApp<'a> {
                     if let Some(manager) = &mut self.memory_texture_manager {
                         let texture_name = format!("qemu_ram_{}", vm_id);
                         // 1024x1024 = 1MB viewport (visualizing the first 1MB is usually enough to see chaos/boot)
-                        if let Err(e) = manager.create_qemu_texture(&texture_name, 1024, 1024) {
+                        if let Err(e) = manager.create_qemu_texture(&texture_name, 1024, 1024, &self.gpu_caps) {
                             log::warn!("Failed to create QEMU texture: {}", e);
                         } else {
                             // Create visual window
@@ -3619,45 +3748,69 @@ impl<'a> InfiniteMapApp<'a> {
                     // Phase 36.2: Start QMP Control Background Task
                     let (tx, mut rx) = tokio::sync::mpsc::channel(32);
                     self.qmp_tx = Some(tx);
-                    let qmp_id = vm_id.clone();
+                    let mut client = crate::qemu::QmpClient::for_vm(&vm_id);
+                    self.qmp_status = Some(client.status_watch());
+                    self.qmp_events = Some(client.events());
 
                     tokio::spawn(async move {
-                        log::info!("🔄 QMP: Background task started for {}", qmp_id);
-                        match crate::qemu::QmpClient::connect(&qmp_id).await {
-                            Ok(mut client) => {
-                                log::info!("✅ QMP: Connected and ready for commands");
-                                while let Some(cmd) = rx.recv().await {
-                                    match cmd {
-                                        crate::qemu::QmpCommand::Pause => {
-                                            if let Err(e) = client.stop().await {
-                                                log::error!("QMP Pause Failed: {}", e);
-                                            }
-                                        },
-                                        crate::qemu::QmpCommand::Resume => {
-                                            if let Err(e) = client.resume().await {
-                                                log::error!("QMP Resume Failed: {}", e);
-                                            }
-                                        },
-                                        crate::qemu::QmpCommand::Reset => {
-                                            if let Err(e) = client.system_reset().await {
-                                                log::error!("QMP Reset Failed: {}", e);
-                                            }
-                                        },
-                                        crate::qemu::QmpCommand::QueryStatus => {
-                                            match client.query_status().await {
-                                                Ok(status) => log::info!("QMP Status: {}", status),
-                                                Err(e) => log::error!("QMP Query Failed: {}", e),
-                                            }
-                                        },
+                        log::info!(
+                            "🔄 QMP: Background task started for {}",
+                            client.socket_path()
+                        );
+                        match client.ensure_connected().await {
+                            Ok(()) => log::info!("✅ QMP: Connected and ready for commands"),
+                            Err(e) => log::warn!(
+                                "⚠️ QMP Connection failed: {} (Is the VM running with -qmp?)",
+                                e
+                            ),
+                        }
+
+                        // Periodic status query so a dropped socket (VM reset) is
+                        // reconnected before the next user command
+                        let mut health_check =
+                            tokio::time::interval(std::time::Duration::from_secs(5));
+                        loop {
+                            let cmd = tokio::select! {
+                                cmd = rx.recv() => match cmd {
+                                    Some(cmd) => cmd,
+                                    None => break,
+                                },
+                                _ = health_check.tick() => {
+                                    if let Err(e) = client.query_status().await {
+                                        log::debug!("QMP health check failed: {}", e);
                                     }
-                                }
-                            },
-                            Err(e) => {
-                                log::warn!(
-                                    "⚠️ QMP Connection failed: {} (Is the VM running with -qmp?)",
-                                    e
-                                );
-                            },
+                                    continue;
+                                },
+                                result = client.pump_events() => {
+                                    if let Err(e) = result {
+                                        log::debug!("QMP event read failed: {}", e);
+                                    }
+                                    continue;
+                                },
+                            };
+                            match cmd {
+                                crate::qemu::QmpCommand::Pause => {
+                                    if let Err(e) = client.stop().await {
+                                        log::error!("QMP Pause Failed: {}", e);
+                                    }
+                                },
+                                crate::qemu::QmpCommand::Resume => {
+                                    if let Err(e) = client.resume().await {
+                                        log::error!("QMP Resume Failed: {}", e);
+                                    }
+                                },
+                                crate::qemu::QmpCommand::Reset => {
+                                    if let Err(e) = client.system_reset().await {
+                                        log::error!("QMP Reset Failed: {}", e);
+                                    }
+                                },
+                                crate::qemu::QmpCommand::QueryStatus => {
+                                    match client.query_status().await {
+                                        Ok(status) => log::info!("QMP Status: {}", status),
+                                        Err(e) => log::error!("QMP Query Failed: {}", e),
+                                    }
+                                },
+                            }
                         }
                     });
                 },
@@ -3723,13 +3876,19 @@ impl<'a> InfiniteMapApp<'a> {
 
         let mut tool_manager = crate::tool_manager::ToolManager::new();
 
-        // Register BtopAdapter for system metrics
-        tool_manager.register_adapter(std::sync::Arc::new(crate::tool_adapter::BtopAdapter::new()));
-
-        // Register BpftraceAdapter for kernel tracing
-        tool_manager.register_adapter(std::sync::Arc::new(
-            crate::tool_adapter::BpftraceAdapter::new(),
-        ));
+        let adapters: [std::sync::Arc<dyn crate::tool_adapter::ToolAdapter + Send + Sync>; 3] = [
+            // BtopAdapter for system metrics
+            std::sync::Arc::new(crate::tool_adapter::BtopAdapter::new()),
+            // BpftraceAdapter for kernel tracing
+            std::sync::Arc::new(crate::tool_adapter::BpftraceAdapter::new()),
+            // GpuMemoryAdapter for real VRAM pressure
+            std::sync::Arc::new(crate::tool_adapter::GpuMemoryAdapter::new()),
+        ];
+        for adapter in adapters {
+            if let Err(e) = tool_manager.register_adapter(adapter) {
+                log::warn!("🔧 Tool Manager: {}", e);
+            }
+        }
 
         log::info!(
             "🔧 Tool Manager initialized with {} adapters",
@@ -3756,8 +3915,9 @@ impl<'a> InfiniteMapApp<'a> {
                     window.height = 350.0;
 
                     let mut content = format!(
-                        "PAS Score: {:.2}\n\nPerformance: {:.2}\nAesthetic: {:.2}\nSystem: {:.2}\n\nVRAM: {} MB / {} MB",
+                        "PAS Score: {:.2} ({:?})\n\nPerformance: {:.2}\nAesthetic: {:.2}\nSystem: {:.2}\n\nVRAM: {} MB / {} MB",
                         score,
+                        self.diagnostic_overlay.trend(),
                         pas.p,
                         pas.a,
                         pas.s,
@@ -3779,13 +3939,7 @@ impl<'a> InfiniteMapApp<'a> {
                     window.content = Some(format!(
                         "PAS Score: {:.2} [ {} ]",
                         score,
-                        if score > 0.8 {
-                            "OPTIMAL"
-                        } else if score > 0.5 {
-                            "CAUTION"
-                        } else {
-                            "CRITICAL"
-                        }
+                        pas.get_state_name()
                     ));
                 }
             }
@@ -3922,28 +4076,48 @@ impl<'a> InfiniteMapApp<'a> {
 
     // Phase 45 / Horizon 1.3: Terminal Tiles - Create a new terminal tile
     pub fn create_terminal_tile(&mut self, x: f32, y: f32, title: String) -> usize {
-        let id = self.terminal_tiles.len();
-        let tile = crate::terminal_tile::TerminalTile::new(id, title, 80, 24);
-        self.terminal_tiles.push(tile);
+        let id = self.terminal_tiles.create(title, 80, 24);
+        let tile = self
+            .terminal_tiles
+            .get_mut(id)
+            .expect("terminal tile was just created");
 
         // Also create a window to represent it visually
         let window_id = self.window_manager.create_demo_window(
-            self.terminal_tiles[id].title.clone(),
+            tile.title.clone(),
             "".to_string(), // Content handled by custom texture
             x,
             y,
             crate::window::WindowType::Default,
         );
+        tile.window_id = Some(window_id);
 
         if let Some(window) = self.window_manager.get_window_mut(window_id) {
-            window.width = self.terminal_tiles[id].texture_width as f32;
-            window.height = self.terminal_tiles[id].texture_height as f32;
+            window.width = tile.texture_width as f32;
+            window.height = tile.texture_height as f32;
             window.custom_border_color = Some([1.0, 0.7, 0.0, 1.0]); // Gold border for native tools
         }
 
         window_id
     }
 
+    /// Remove a terminal tile, its window and its PTY (if any)
+    pub fn remove_terminal_tile(&mut self, id: crate::terminal_tile::TerminalTileId) -> bool {
+        let Some(tile) = self.terminal_tiles.remove(id) else {
+            return false;
+        };
+
+        if let Some(window_id) = tile.window_id {
+            self.window_manager.remove_window(window_id);
+        }
+        if let (Some(pty_id), Some(manager)) = (tile.pty_id, &mut self.terminal_clone_manager) {
+            manager.remove_terminal(pty_id);
+        }
+
+        log::info!("🗑️  Removed terminal tile {}", id);
+        true
+    }
+
     /// Spawn an Evolution Zone window for autonomous execution interactions
     pub fn spawn_evolution_zone(&mut self) {
         // Initial spawning of the evolution zone window
@@ -3963,19 +4137,20 @@ impl<'a> InfiniteMapApp<'a> {
         log::info!("🧬 Spawning Evolution Zone (ID: {})", window_id);
     }
 
-    #[cfg(feature = "hypervisor")]
     pub fn spawn_terminal_clone(&mut self, req: crate::api_server::TerminalSpawnRequest) {
         if let Some(ref mut manager) = self.terminal_clone_manager {
             match manager.create_terminal(req.rows as u16, req.cols as u16, &req.shell) {
                 Ok(pty_id) => {
                     // Create the tile
-                    let tile_idx = self.terminal_tiles.len();
-                    let mut tile = crate::terminal_tile::TerminalTile::new(
-                        tile_idx,
+                    let tile_id = self.terminal_tiles.create(
                         format!("Terminal {}", pty_id),
                         req.cols,
                         req.rows,
                     );
+                    let tile = self
+                        .terminal_tiles
+                        .get_mut(tile_id)
+                        .expect("terminal tile was just created");
                     tile.pty_id = Some(pty_id);
 
                     // Create the window
@@ -3988,19 +4163,18 @@ impl<'a> InfiniteMapApp<'a> {
                     );
 
                     tile.window_id = Some(window_id);
-                    self.terminal_tiles.push(tile);
 
                     if let Some(window) = self.window_manager.get_window_mut(window_id) {
-                        window.width = self.terminal_tiles[tile_idx].texture_width as f32;
-                        window.height = self.terminal_tiles[tile_idx].texture_height as f32;
+                        window.width = tile.texture_width as f32;
+                        window.height = tile.texture_height as f32;
                         window.custom_border_color = Some([0.0, 1.0, 1.0, 1.0]); // Cyan for PTY tools
                         window.has_terminal_texture = true;
-                        window.terminal_tile_id = Some(tile_idx);
+                        window.terminal_tile_id = Some(tile_id);
                     }
 
                     log::info!(
                         "🚀 Spawned terminal clone {} (PTY: {}) at ({}, {})",
-                        tile_idx,
+                        tile_id,
                         pty_id,
                         req.x,
                         req.y
@@ -4014,7 +4188,6 @@ impl<'a> InfiniteMapApp<'a> {
     // Phase 45 / Horizon 1.3: Update Terminal Tiles
     pub fn update_terminal_tiles(&mut self) {
         // 1. Process pending requests from API
-        #[cfg(feature = "hypervisor")]
         {
             let mut spawns = Vec::new();
             let mut resizes = Vec::new();
@@ -4036,43 +4209,66 @@ impl<'a> InfiniteMapApp<'a> {
                 self.spawn_terminal_clone(req);
             }
 
+            for tile_id in destroys {
+                if tile_id < 0 || !self.remove_terminal_tile(tile_id as usize) {
+                    log::warn!("Terminal destroy: no tile with id {}", tile_id);
+                }
+            }
+
             for req in resizes {
-                // Handle resize logic
-                if let Some(ref mut manager) = self.terminal_clone_manager {
-                    // Find the tile with this pty_id or matching tile_id
-                    // Note: API tile_id might not match internal tile index
-                    let _ = manager.resize_terminal(
-                        req.tile_id as usize,
-                        req.rows as u16,
-                        req.cols as u16,
+                // The API addresses tiles; the clone manager addresses PTYs
+                let pty_id = usize::try_from(req.tile_id)
+                    .ok()
+                    .and_then(|id| self.terminal_tiles.get(id))
+                    .and_then(|tile| tile.pty_id);
+                let Some(pty_id) = pty_id else {
+                    log::warn!(
+                        "Terminal resize: no PTY-backed tile with id {}",
+                        req.tile_id
                     );
+                    continue;
+                };
+                if let Some(ref mut manager) = self.terminal_clone_manager {
+                    let _ = manager.resize_terminal(pty_id, req.rows as u16, req.cols as u16);
                 }
             }
         }
 
         // 2. Update existing clones from PTY
-        #[cfg(feature = "hypervisor")]
         if let Some(ref mut manager) = self.terminal_clone_manager {
-            manager.update();
+            let bytes_read = manager.update();
 
-            // Sync emulator buffers to tiles
-            for tile in &mut self.terminal_tiles {
+            // Sync emulator buffers to tiles: only tiles whose PTY produced
+            // output need a redraw
+            for tile in self.terminal_tiles.iter_mut() {
                 if let Some(pty_id) = tile.pty_id {
-                    if let Some(emulator) = manager.get_emulator(pty_id) {
-                        // Check if emulator buffer has changed (rough heuristic)
-                        // For now just always update if needs_render is true or periodically
-                        // In a real system we'd check a 'dirty' flag
-                        tile.needs_render = true;
+                    if let Some(&bytes) = bytes_read.get(&pty_id) {
+                        tile.record_bytes(bytes);
                     }
                 }
             }
+
+            if let Some(busiest) = self
+                .terminal_tiles
+                .iter()
+                .filter(|tile| tile.metrics().bytes_processed > 0)
+                .max_by_key(|tile| tile.metrics().bytes_processed)
+            {
+                let metrics = busiest.metrics();
+                log::debug!(
+                    "Busiest terminal tile {}: {} bytes, {} cells, {} rebuilds",
+                    busiest.id,
+                    metrics.bytes_processed,
+                    metrics.cells_updated,
+                    metrics.texture_rebuilds
+                );
+            }
         }
 
         // 3. Render tiles to textures
-        for tile in &mut self.terminal_tiles {
+        for tile in self.terminal_tiles.iter_mut() {
             if tile.needs_render {
-                let external_emu = None;
-                #[cfg(feature = "hypervisor")]
+                let mut external_emu = None;
                 if let Some(pty_id) = tile.pty_id {
                     if let Some(ref manager) = self.terminal_clone_manager {
                         external_emu = manager.get_emulator(pty_id);
@@ -4332,9 +4528,11 @@ impl<'a> InfiniteMapApp<'a> {
 
     fn initialize_visual_shell(&mut self) {
         // Assume tokens are in sibling directory
-        let _tokens_path = "../visual_shell/tokens.json";
+        let tokens_path = "../visual_shell/tokens.json";
+        // No neural state has arrived yet, so size for the default grid
+        let neural_len = 1usize << (2 * DEFAULT_HILBERT_ORDER);
 
-        match VisualShell::new() {
+        match VisualShell::new_auto(tokens_path, neural_len) {
             Ok(mut shell) => {
                 let device = self.renderer.get_device();
                 let queue = self.renderer.get_queue();
@@ -4351,10 +4549,7 @@ impl<'a> InfiniteMapApp<'a> {
                 }
             },
             Err(e) => {
-                log::warn!(
-                    "Failed to initialize Visual Shell (tokens not found?): {}",
-                    e
-                );
+                log::warn!("Failed to initialize Visual Shell: {}", e);
             },
         }
 
@@ -5112,6 +5307,12 @@ impl<'a> InfiniteMapApp<'a> {
         // Phase 44: Handle Multi-VM commands (Ctrl+Shift+M)
         self.handle_multi_vm_commands();
 
+        // RISC-V debugger commands (F9 / F10)
+        self.handle_riscv_debug_commands();
+
+        // Phase 36.2: VM state changes reported over QMP
+        self.handle_qmp_events();
+
         // Phase Mode B.2: Handle spatial auto-save
         self.handle_spatial_auto_save();
 
@@ -5500,7 +5701,12 @@ impl<'a> InfiniteMapApp<'a> {
                                 DaemonFrequencyBand::Mid => FrequencyBand::Mid,
                                 DaemonFrequencyBand::High => FrequencyBand::High,
                                 DaemonFrequencyBand::Custom(millihz) => {
-                                    FrequencyBand::Custom(millihz as f32 / 1000.0)
+                                    // Narrow band around the requested tone
+                                    let hz = millihz as f32 / 1000.0;
+                                    FrequencyBand::Custom {
+                                        low_hz: hz * 0.95,
+                                        high_hz: hz * 1.05,
+                                    }
                                 },
                             };
                             if let Err(e) = shell.register_daemon(id, band, reg.initial_amplitude) {
@@ -6558,10 +6764,17 @@ impl<'a> InfiniteMapApp<'a> {
                     // Phase 47: Route to QEMU SHM Window (Possessed)
                     if Some(possessed_id) == self.qemu_shm_window_id {
                         if key_state == smithay::backend::input::KeyState::Pressed {
-                            if let Some(byte) = self.input_manager.map_scancode_to_ascii(key_code) {
-                                if let Ok(input_str) = std::str::from_utf8(&[byte]) {
-                                    if let Some(proc) = &self.qemu_shm_process {
-                                        proc.send_input(input_str);
+                            let modifiers = crate::qemu::Modifiers {
+                                shift: self.input_manager.is_shift_pressed(),
+                                ctrl: self.input_manager.is_ctrl_pressed(),
+                                alt: self.input_manager.is_alt_pressed(),
+                            };
+                            if let Some(event) =
+                                crate::qemu::KeyEvent::from_scancode(key_code, modifiers)
+                            {
+                                if let Some(proc) = &self.qemu_shm_process {
+                                    if let Err(e) = proc.send_key(event) {
+                                        log::debug!("QEMU key dropped: {}", e);
                                     }
                                 }
                             }
@@ -7173,6 +7386,15 @@ impl<'a> InfiniteMapApp<'a> {
                                 if window.has_memory_texture {
                                     // Visual Feedback: Check if we have a QMP channel
                                     if self.qmp_tx.is_some() {
+                                        let qmp_status =
+                                            self.qmp_status.as_ref().map(|status| *status.borrow());
+                                        if qmp_status != Some(crate::qemu::QmpStatus::Connected) {
+                                            log::warn!(
+                                                "⚠️ VM control unavailable (QMP {:?})",
+                                                qmp_status
+                                            );
+                                            return;
+                                        }
                                         log::info!("🛑 Visual Interrupt: Pausing VM via Middle Click on Window {}", window.id);
                                         // Send Pause Command (Non-blocking)
                                         if let Some(ref tx) = self.qmp_tx {
@@ -7405,6 +7627,10 @@ impl<'a> InfiniteMapApp<'a> {
 
     // Phase 46: Cognitive System Update Loop
     pub fn update_cognitive_system(&mut self) {
+        // Publish system health so entities can read it via host functions
+        self.diagnostic_overlay.publish_snapshot();
+        self.diagnostic_overlay.sample_pas();
+
         // 1. Tick ACE Entities (WASM thinking)
         if let Some(manager) = &self.cognitive_manager {
             let entities = manager.list_entities(); // Returns Vec<ACEEntity>
@@ -7421,7 +7647,11 @@ impl<'a> InfiniteMapApp<'a> {
                                 entity.id.clone(),
                                 &entity.texture_path,
                             ) {
-                                Ok(runtime) => {
+                                Ok(mut runtime) => {
+                                    runtime.attach_diagnostics(
+                                        self.diagnostic_overlay.shared_snapshot(),
+                                    );
+                                    runtime.attach_mailboxes(manager.mailboxes());
                                     self.cognitive_runtimes.insert(entity.id.clone(), runtime);
                                     manager.set_state(
                                         &entity.id,
@@ -7442,7 +7672,9 @@ impl<'a> InfiniteMapApp<'a> {
                         } else {
                             // Runtime exists, tick it
                             if let Some(runtime) = self.cognitive_runtimes.get_mut(&entity.id) {
-                                if let Err(e) = runtime.think() {
+                                let result = runtime.think();
+                                manager.set_thinking(&entity.id, result.is_ok());
+                                if let Err(e) = result {
                                     log::error!(
                                         "❌ Error in entity {} during thought process: {}",
                                         entity.id,
@@ -7456,6 +7688,9 @@ impl<'a> InfiniteMapApp<'a> {
                     _ => {
                         // If suspended or dormant, do nothing or unload runtime?
                         // For now we keep runtime in memory but don't tick
+                        if entity.thinking {
+                            manager.set_thinking(&entity.id, false);
+                        }
                     },
                 }
             }
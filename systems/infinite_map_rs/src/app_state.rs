@@ -216,7 +216,6 @@ impl CognitiveState {
 pub struct TileState {
     pub pid_to_window: HashMap<u32, usize>,
 
-    #[cfg(feature = "hypervisor")]
     pub terminal_clone_manager: Option<crate::terminal_clone::TerminalCloneManager>,
 
     // Shader Execution Zone
@@ -227,7 +226,6 @@ impl Default for TileState {
     fn default() -> Self {
         Self {
             pid_to_window: HashMap::new(),
-            #[cfg(feature = "hypervisor")]
             terminal_clone_manager: Some(crate::terminal_clone::TerminalCloneManager::new()),
             compositor: None,
         }
@@ -263,6 +261,10 @@ impl Default for PerformanceState {
                 supports_i64: true,
                 vendor_name: "Unknown".to_string(),
                 device_name: "Unknown".to_string(),
+                features: wgpu::Features::empty(),
+                format_features: HashMap::new(),
+                limits: wgpu::Limits::default(),
+                vram_bytes: None,
             },
         }
     }
@@ -1,9 +1,35 @@
 use crate::cognitive::binary_extractor::ACEBinaryExtractor;
+use crate::cognitive::entity_manager::EntityMailboxes;
+use crate::diagnostic::{DiagnosticSnapshot, SharedDiagnosticSnapshot};
 use anyhow::{anyhow, Result};
 use image::DynamicImage;
 use std::path::Path;
 use wasmtime::*;
 
+/// WASM page size in bytes
+const WASM_PAGE_SIZE: usize = 64 * 1024;
+
+/// Sandbox limits applied to every ACE runtime
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ACERuntimeConfig {
+    /// Maximum linear memory, in 64 KiB WASM pages
+    pub max_memory_pages: usize,
+    /// Maximum number of elements in any table
+    pub max_table_elements: usize,
+    /// Fuel granted before each `think()` call (`None` disables metering)
+    pub fuel_per_tick: Option<u64>,
+}
+
+impl Default for ACERuntimeConfig {
+    fn default() -> Self {
+        Self {
+            max_memory_pages: 256, // 16 MiB
+            max_table_elements: 10_000,
+            fuel_per_tick: Some(10_000_000),
+        }
+    }
+}
+
 pub struct ACERuntime {
     /// WASM engine (scaffolding: future runtime inspection)
     #[allow(dead_code)]
@@ -13,14 +39,36 @@ pub struct ACERuntime {
     module: Module,
     store: Store<ACEState>,
     instance: Instance,
+    config: ACERuntimeConfig,
 }
 
 pub struct ACEState {
     pub id: String,
     pub texture_path: std::path::PathBuf,
+    /// Shared diagnostic snapshot published by the compositor (if attached)
+    pub diagnostics: Option<SharedDiagnosticSnapshot>,
+    /// Last snapshot successfully read, returned when the lock is contended
+    pub last_diagnostics: DiagnosticSnapshot,
+    /// Memory/table limits enforced by the store limiter
+    pub limits: StoreLimits,
+    /// Inter-entity mailboxes (if attached)
+    pub mailboxes: Option<EntityMailboxes>,
     // Add other state fields as needed
 }
 
+impl ACEState {
+    pub fn new(id: String, texture_path: std::path::PathBuf) -> Self {
+        Self {
+            id,
+            texture_path,
+            diagnostics: None,
+            last_diagnostics: DiagnosticSnapshot::default(),
+            limits: StoreLimits::default(),
+            mailboxes: None,
+        }
+    }
+}
+
 impl ACERuntime {
     pub fn boot_from_texture(id: String, texture_path: &Path) -> Result<Self> {
         // 1. Load PNG
@@ -33,20 +81,35 @@ impl ACERuntime {
             return Err(anyhow!("Failed to extract ACE binary from texture"));
         }
 
-        // 3. Initialize WASM runtime
-        let engine = Engine::default();
-        let module = Module::new(&engine, &binary)?;
-
-        // 4. Create store with ACE state
-        let mut store = Store::new(
-            &engine,
-            ACEState {
-                id,
-                texture_path: texture_path.to_path_buf(),
-            },
-        );
+        Self::new(id, texture_path, &binary, ACERuntimeConfig::default())
+    }
+
+    /// Instantiate a sandboxed runtime directly from a WASM binary
+    pub fn new(
+        id: String,
+        texture_path: &Path,
+        binary: &[u8],
+        config: ACERuntimeConfig,
+    ) -> Result<Self> {
+        // 1. Initialize WASM runtime
+        let mut engine_config = Config::new();
+        engine_config.consume_fuel(config.fuel_per_tick.is_some());
+        let engine = Engine::new(&engine_config)?;
+        let module = Module::new(&engine, binary)?;
 
-        // 5. Instantiate with host functions (linker setup)
+        // 2. Create store with ACE state and resource limits
+        let mut state = ACEState::new(id, texture_path.to_path_buf());
+        state.limits = StoreLimitsBuilder::new()
+            .memory_size(config.max_memory_pages.saturating_mul(WASM_PAGE_SIZE))
+            .table_elements(config.max_table_elements)
+            .build();
+        let mut store = Store::new(&engine, state);
+        store.limiter(|state| &mut state.limits);
+        if let Some(fuel) = config.fuel_per_tick {
+            store.set_fuel(fuel)?;
+        }
+
+        // 3. Instantiate with host functions (linker setup)
         let mut linker = Linker::new(&engine);
         crate::cognitive::host_functions::register_host_functions(&mut linker)?;
 
@@ -57,9 +120,25 @@ impl ACERuntime {
             module,
             store,
             instance,
+            config,
         })
     }
 
+    /// Attach the shared mailboxes so the entity can receive messages
+    pub fn attach_mailboxes(&mut self, mailboxes: EntityMailboxes) {
+        self.store.data_mut().mailboxes = Some(mailboxes);
+    }
+
+    /// Get the sandbox configuration
+    pub fn config(&self) -> &ACERuntimeConfig {
+        &self.config
+    }
+
+    /// Attach a shared diagnostic snapshot so the entity can observe system health
+    pub fn attach_diagnostics(&mut self, diagnostics: SharedDiagnosticSnapshot) {
+        self.store.data_mut().diagnostics = Some(diagnostics);
+    }
+
     /// Extract ACE binary from texture (scaffolding: future standalone extraction)
     #[allow(dead_code)]
     fn extract_ace_binary(img: &DynamicImage) -> Result<Vec<u8>> {
@@ -79,6 +158,11 @@ impl ACERuntime {
     }
 
     pub fn think(&mut self) -> Result<()> {
+        // Refill the fuel budget so a runaway loop traps instead of hanging the compositor
+        if let Some(fuel) = self.config.fuel_per_tick {
+            self.store.set_fuel(fuel)?;
+        }
+
         if let Ok(think_fn) = self
             .instance
             .get_typed_func::<(), ()>(&mut self.store, "think")
@@ -88,3 +172,56 @@ impl ACERuntime {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SANDBOX_WAT: &str = r#"
+        (module
+            (memory 1)
+            (func (export "grow") (param i32) (result i32)
+                local.get 0
+                memory.grow)
+            (func (export "think")
+                (loop $spin br $spin)))
+    "#;
+
+    fn sandboxed_runtime() -> ACERuntime {
+        let config = ACERuntimeConfig {
+            max_memory_pages: 4,
+            max_table_elements: 16,
+            fuel_per_tick: Some(100_000),
+        };
+        ACERuntime::new(
+            "sandbox".to_string(),
+            Path::new("sandbox.rts.png"),
+            SANDBOX_WAT.as_bytes(),
+            config,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_memory_growth_past_limit_fails_gracefully() {
+        let mut runtime = sandboxed_runtime();
+        let grow = runtime
+            .instance
+            .get_typed_func::<i32, i32>(&mut runtime.store, "grow")
+            .unwrap();
+
+        // 1 GiB request is refused with -1 rather than allocated
+        assert_eq!(grow.call(&mut runtime.store, 16 * 1024).unwrap(), -1);
+        // Growth within the limit still succeeds (returns previous size)
+        assert_eq!(grow.call(&mut runtime.store, 3).unwrap(), 1);
+        assert_eq!(grow.call(&mut runtime.store, 1).unwrap(), -1);
+    }
+
+    #[test]
+    fn test_fuel_limit_stops_runaway_think() {
+        let mut runtime = sandboxed_runtime();
+        assert!(runtime.think().is_err());
+        // Fuel is refilled each tick, so the runtime stays usable
+        assert!(runtime.think().is_err());
+    }
+}
@@ -14,6 +14,7 @@
 //! Agents exist at Hilbert coordinates (1D) but render in 2D spatial positions.
 //! They maintain goals, memory (via Vat), and communicate via Synaptic Layer.
 
+use crate::cognitive::hilbert_pathfinder::{HilbertPathfinder, PathStrategy};
 use crate::hot_swap::{VatBuffer, VatId, VatState};
 use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, VecDeque};
@@ -29,6 +30,27 @@ pub enum AgentRole {
     Archivist, // Manages Vat persistence
 }
 
+/// Behavior tuning for all agents of one role
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct AgentParams {
+    /// Movement speed in world units per second (default 100.0)
+    pub move_speed: f32,
+    /// Hilbert distance within which other agents are sensed (default 256)
+    pub sensing_radius: u32,
+    /// Per-tick chance of abandoning a wander goal for a new one (default 0.0)
+    pub goal_switch_probability: f32,
+}
+
+impl Default for AgentParams {
+    fn default() -> Self {
+        Self {
+            move_speed: 100.0,
+            sensing_radius: 256,
+            goal_switch_probability: 0.0,
+        }
+    }
+}
+
 /// Agent state machine
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum AgentState {
@@ -76,6 +98,11 @@ pub enum GoalType {
     Archive { vat_id: VatId },
     /// Scout for code rot
     ScoutDistrict { district: String },
+    /// Visit grid waypoints in order, `loop_count` times (forever if `None`)
+    Patrol {
+        waypoints: Vec<(u32, u32)>,
+        loop_count: Option<u32>,
+    },
 }
 
 /// Memory entry for agent learning
@@ -166,6 +193,14 @@ impl CityAgent {
         self.goals.front()
     }
 
+    /// Whether the current goal is a self-chosen wander target
+    pub fn is_wandering(&self) -> bool {
+        matches!(
+            self.current_goal().map(|goal| &goal.goal_type),
+            Some(GoalType::Navigate { destination }) if destination == "Wandering"
+        )
+    }
+
     /// Complete current goal
     pub fn complete_goal(&mut self, result: String) {
         if self.goals.pop_front().is_some() {
@@ -270,15 +305,33 @@ impl CityAgent {
 
     /// Process active goals
     pub fn tick(&mut self, requests: &mut VecDeque<AgentRequest>, grid_size: u32) {
+        self.tick_with(requests, grid_size, |bound| {
+            use rand::Rng;
+            rand::thread_rng().gen_range(0..bound)
+        });
+    }
+
+    /// Process active goals, using `pick_wander` to choose wander targets
+    ///
+    /// `pick_wander` receives the exclusive upper bound of the Hilbert range.
+    /// Returns the wander target chosen this tick (if any) so the decision can
+    /// be logged and replayed.
+    pub fn tick_with<F: FnOnce(u32) -> u32>(
+        &mut self,
+        requests: &mut VecDeque<AgentRequest>,
+        grid_size: u32,
+        pick_wander: F,
+    ) -> Option<u32> {
+        let mut wander_target = None;
+
         if self.state == AgentState::Idle {
             // Wander: Pick a random Hilbert location
             if !self.goals.is_empty() {
                 self.state = AgentState::Navigating;
             } else {
                 // Pick a random target
-                use rand::Rng;
-                let mut rng = rand::thread_rng();
-                let target = rng.gen_range(0..grid_size * grid_size);
+                let target = pick_wander(grid_size * grid_size);
+                wander_target = Some(target);
 
                 self.add_goal(AgentGoal {
                     id: Uuid::new_v4().to_string(),
@@ -297,7 +350,9 @@ impl CityAgent {
         if self.state == AgentState::Navigating {
             if self.target_pos.is_none() {
                 if let Some(goal) = self.goals.front() {
-                    if let Some(th) = goal.target_hilbert {
+                    if matches!(goal.goal_type, GoalType::Patrol { .. }) {
+                        // Patrol legs are planned by CityAgentManager
+                    } else if let Some(th) = goal.target_hilbert {
                         let world_target = Self::hilbert_to_world(th, grid_size);
                         self.target_pos = Some(world_target);
                     } else {
@@ -334,12 +389,17 @@ impl CityAgent {
                         self.remember(format!("District {} looks healthy.", district), 0.8);
                         self.complete_goal("Scouting done".to_string());
                     },
+                    GoalType::Patrol { .. } => {
+                        // Waypoint arrival is handled by CityAgentManager
+                    },
                     _ => {
                         self.complete_goal("Task finished".to_string());
                     },
                 }
             }
         }
+
+        wander_target
     }
 }
 
@@ -378,6 +438,7 @@ impl VatState for CityAgent {
                 GoalType::Rebuild { .. } => 2u8,
                 GoalType::Archive { .. } => 3u8,
                 GoalType::ScoutDistrict { .. } => 4u8,
+                GoalType::Patrol { .. } => 5u8,
             };
             vat.write_u8(type_id)?;
             vat.write_u8(goal.priority)?;
@@ -390,6 +451,20 @@ impl VatState for CityAgent {
                 vat.write_u32(0)?;
                 vat.write_u8(0u8)?; // No target
             }
+            // Patrol route (waypoint count + each waypoint, optional loop count)
+            if let GoalType::Patrol {
+                waypoints,
+                loop_count,
+            } = &goal.goal_type
+            {
+                vat.write_u32(waypoints.len() as u32)?;
+                for &(x, y) in waypoints {
+                    vat.write_u32(x)?;
+                    vat.write_u32(y)?;
+                }
+                vat.write_u32(loop_count.unwrap_or(0))?;
+                vat.write_u8(loop_count.is_some() as u8)?;
+            }
         }
 
         // Memory (count + recent entries, max 100)
@@ -457,6 +532,19 @@ impl VatState for CityAgent {
                 4 => GoalType::ScoutDistrict {
                     district: "restored".to_string(),
                 },
+                5 => {
+                    let waypoint_count = vat.read_u32()?;
+                    let mut waypoints = Vec::with_capacity(waypoint_count as usize);
+                    for _ in 0..waypoint_count {
+                        waypoints.push((vat.read_u32()?, vat.read_u32()?));
+                    }
+                    let loop_count_raw = vat.read_u32()?;
+                    let has_loop_count = vat.read_u8()? == 1;
+                    GoalType::Patrol {
+                        waypoints,
+                        loop_count: has_loop_count.then_some(loop_count_raw),
+                    }
+                },
                 _ => GoalType::Navigate {
                     destination: "Unknown".to_string(),
                 },
@@ -493,6 +581,94 @@ impl VatState for CityAgent {
     }
 }
 
+/// Default number of records retained by the action log
+pub const DEFAULT_ACTION_LOG_CAPACITY: usize = 16_384;
+
+/// A manager-level decision recorded for deterministic replay
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum AgentAction {
+    /// Agent spawned with a role at a Hilbert coordinate
+    Spawn { role: AgentRole, hilbert_pos: u32 },
+    /// Goal assigned to the agent
+    AssignGoal { goal: AgentGoal },
+    /// Agent stepped by `dt`, recording any wander target it picked and
+    /// whether it abandoned its previous wander goal first
+    Step {
+        dt: f32,
+        wander_target: Option<u32>,
+        #[serde(default)]
+        goal_switched: bool,
+    },
+    /// Agent removed
+    Despawn,
+}
+
+/// One entry in the action log
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActionRecord {
+    pub tick: u64,
+    pub agent_id: String,
+    pub action: AgentAction,
+}
+
+/// Bounded ring of agent actions, oldest evicted first
+#[derive(Debug, Clone)]
+pub struct ActionLog {
+    records: VecDeque<ActionRecord>,
+    capacity: usize,
+}
+
+impl ActionLog {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            records: VecDeque::new(),
+            capacity: capacity.max(1),
+        }
+    }
+
+    /// Append a record, evicting the oldest when full
+    pub fn record(&mut self, tick: u64, agent_id: &str, action: AgentAction) {
+        if self.records.len() >= self.capacity {
+            self.records.pop_front();
+        }
+        self.records.push_back(ActionRecord {
+            tick,
+            agent_id: agent_id.to_string(),
+            action,
+        });
+    }
+
+    /// Copy out all retained records, oldest first
+    pub fn export(&self) -> Vec<ActionRecord> {
+        self.records.iter().cloned().collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.records.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.records.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.records.clear();
+    }
+}
+
+/// Progress of an agent through a `GoalType::Patrol` goal
+#[derive(Debug, Clone, Default)]
+pub struct PatrolProgress {
+    /// Index of the waypoint currently being approached
+    pub waypoint_index: usize,
+    /// Completed passes over the full waypoint list
+    pub loops_completed: u32,
+    /// Whether a leg toward `waypoint_index` has been planned
+    en_route: bool,
+    /// Remaining world-space steps of the current leg
+    leg: VecDeque<(f32, f32)>,
+}
+
 /// Manager for autonomous agents in Source City
 pub struct CityAgentManager {
     agents: HashMap<String, CityAgent>,
@@ -501,6 +677,16 @@ pub struct CityAgentManager {
     pub requests: VecDeque<AgentRequest>,
     pub total_tasks_completed: u64,
     pub last_telemetry_report: f64,
+    /// Number of `update` calls so far
+    tick: u64,
+    /// Ordered record of decisions for replay/debugging
+    action_log: ActionLog,
+    /// Behavior tuning per role
+    role_params: HashMap<AgentRole, AgentParams>,
+    /// Route planner for patrol legs
+    pathfinder: HilbertPathfinder,
+    /// Patrol progress by agent ID
+    patrols: HashMap<String, PatrolProgress>,
 }
 
 impl CityAgentManager {
@@ -512,9 +698,27 @@ impl CityAgentManager {
             requests: VecDeque::new(),
             total_tasks_completed: 0,
             last_telemetry_report: CityAgent::now(),
+            tick: 0,
+            action_log: ActionLog::new(DEFAULT_ACTION_LOG_CAPACITY),
+            role_params: [AgentRole::Scout, AgentRole::Engineer, AgentRole::Archivist]
+                .into_iter()
+                .map(|role| (role, AgentParams::default()))
+                .collect(),
+            pathfinder: HilbertPathfinder::new(grid_size),
+            patrols: HashMap::new(),
         }
     }
 
+    /// Set the behavior tuning for every agent of `role`
+    pub fn set_role_params(&mut self, role: AgentRole, params: AgentParams) {
+        self.role_params.insert(role, params);
+    }
+
+    /// Get the behavior tuning for `role`
+    pub fn role_params(&self, role: AgentRole) -> AgentParams {
+        self.role_params.get(&role).copied().unwrap_or_default()
+    }
+
     pub fn set_vat_registry(
         &mut self,
         registry: std::sync::Arc<std::sync::Mutex<crate::hot_swap::VatRegistry>>,
@@ -524,9 +728,25 @@ impl CityAgentManager {
 
     /// Spawn a new agent
     pub fn spawn_agent(&mut self, role: AgentRole, hilbert_pos: u32) -> String {
+        self.spawn_agent_with_id(role, hilbert_pos, None)
+    }
+
+    /// Spawn an agent, optionally reusing a known ID (for replay)
+    fn spawn_agent_with_id(
+        &mut self,
+        role: AgentRole,
+        hilbert_pos: u32,
+        id: Option<String>,
+    ) -> String {
         let mut agent = CityAgent::new(role, hilbert_pos);
+        if let Some(id) = id {
+            agent.vat_id = VatId::new(&format!("agent_{}", id));
+            agent.id = id;
+        }
         agent.world_pos = CityAgent::hilbert_to_world(hilbert_pos, self.grid_size);
         let id = agent.id.clone();
+        self.action_log
+            .record(self.tick, &id, AgentAction::Spawn { role, hilbert_pos });
 
         // Persist to Vat before inserting
         if let Some(ref registry) = self.vat_registry {
@@ -555,15 +775,50 @@ impl CityAgentManager {
     pub fn update(&mut self, dt: f32) {
         let mut to_persist = Vec::new();
         let mut completed_this_tick = 0;
+        self.tick += 1;
 
         for agent in self.agents.values_mut() {
-            let prev_goals = agent.goals.len();
-            agent.update_position(dt, self.grid_size);
-            agent.tick(&mut self.requests, self.grid_size);
+            if Self::drive_patrol(
+                agent,
+                &mut self.patrols,
+                &mut self.pathfinder,
+                self.grid_size,
+            ) {
+                completed_this_tick += 1;
+            }
 
-            if prev_goals > agent.goals.len() {
+            use rand::Rng;
+            let params = self
+                .role_params
+                .get(&agent.role)
+                .copied()
+                .unwrap_or_default();
+            let goal_switched = agent.is_wandering()
+                && params.goal_switch_probability > 0.0
+                && rand::thread_rng().gen::<f32>() < params.goal_switch_probability;
+
+            let (completed, wander_target) = Self::step_agent(
+                agent,
+                dt,
+                self.grid_size,
+                &params,
+                goal_switched,
+                &mut self.requests,
+                |bound| rand::thread_rng().gen_range(0..bound),
+            );
+
+            if completed {
                 completed_this_tick += 1;
             }
+            self.action_log.record(
+                self.tick,
+                &agent.id,
+                AgentAction::Step {
+                    dt,
+                    wander_target,
+                    goal_switched,
+                },
+            );
             to_persist.push(agent.id.clone());
         }
 
@@ -581,6 +836,197 @@ impl CityAgentManager {
         }
     }
 
+    /// Advance one agent; returns (goal completed, wander target picked)
+    fn step_agent<F: FnOnce(u32) -> u32>(
+        agent: &mut CityAgent,
+        dt: f32,
+        grid_size: u32,
+        params: &AgentParams,
+        goal_switched: bool,
+        requests: &mut VecDeque<AgentRequest>,
+        pick_wander: F,
+    ) -> (bool, Option<u32>) {
+        if goal_switched {
+            // Abandon the wander goal; tick picks a fresh one
+            agent.goals.pop_front();
+            agent.target_pos = None;
+            agent.state = AgentState::Idle;
+        }
+
+        let prev_goals = agent.goals.len();
+        agent.speed = params.move_speed;
+        agent.update_position(dt, grid_size);
+        let wander_target = agent.tick_with(requests, grid_size, pick_wander);
+        (prev_goals > agent.goals.len(), wander_target)
+    }
+
+    /// Plan the next patrol step for an agent whose current goal is a patrol
+    ///
+    /// Each waypoint leg is routed with the `HilbertPathfinder` and fed to the
+    /// agent one step at a time. Returns `true` when the patrol completes.
+    fn drive_patrol(
+        agent: &mut CityAgent,
+        patrols: &mut HashMap<String, PatrolProgress>,
+        pathfinder: &mut HilbertPathfinder,
+        grid_size: u32,
+    ) -> bool {
+        let Some(GoalType::Patrol {
+            waypoints,
+            loop_count,
+        }) = agent.current_goal().map(|goal| goal.goal_type.clone())
+        else {
+            patrols.remove(&agent.id);
+            return false;
+        };
+
+        // Still walking toward the current step
+        if agent.target_pos.is_some() {
+            return false;
+        }
+
+        let progress = patrols.entry(agent.id.clone()).or_default();
+        if let Some(step) = progress.leg.pop_front() {
+            agent.target_pos = Some(step);
+            agent.state = AgentState::Navigating;
+            return false;
+        }
+
+        let finished = if waypoints.is_empty() || loop_count == Some(0) {
+            true
+        } else {
+            if progress.en_route {
+                // Leg exhausted: the agent is at the current waypoint
+                let (x, y) = waypoints[progress.waypoint_index];
+                agent.remember(
+                    format!(
+                        "Patrol waypoint {} reached at ({}, {})",
+                        progress.waypoint_index, x, y
+                    ),
+                    1.0,
+                );
+                progress.waypoint_index += 1;
+                if progress.waypoint_index == waypoints.len() {
+                    progress.waypoint_index = 0;
+                    progress.loops_completed += 1;
+                }
+            }
+            loop_count.is_some_and(|loops| progress.loops_completed >= loops)
+        };
+
+        if finished {
+            patrols.remove(&agent.id);
+            agent.complete_goal("Patrol complete".to_string());
+            return true;
+        }
+
+        // Route the next leg along the Hilbert curve
+        let max = grid_size.saturating_sub(1);
+        let (x, y) = waypoints[progress.waypoint_index];
+        let destination = crate::hilbert::xy2d(grid_size, x.min(max), y.min(max)) as u32;
+        let path = pathfinder.find_path(agent.hilbert_pos, destination, PathStrategy::Direct);
+        progress.leg = path.waypoints.iter().skip(1).map(|w| (w.x, w.y)).collect();
+        progress.en_route = true;
+
+        if let Some(step) = progress.leg.pop_front() {
+            agent.target_pos = Some(step);
+            agent.state = AgentState::Navigating;
+        }
+        false
+    }
+
+    /// Get patrol progress for an agent (if it is patrolling)
+    pub fn patrol_progress(&self, id: &str) -> Option<&PatrolProgress> {
+        self.patrols.get(id)
+    }
+
+    /// Get the action log
+    pub fn action_log(&self) -> &ActionLog {
+        &self.action_log
+    }
+
+    /// Re-drive agents from a recorded action log
+    ///
+    /// Intended for a fresh manager: agents are spawned with their recorded
+    /// IDs and every wander decision is taken from the log instead of the RNG,
+    /// so the final agent states match the original run. Records for agents
+    /// whose spawn was evicted from the ring are skipped.
+    pub fn replay(&mut self, log: &[ActionRecord]) {
+        for record in log {
+            self.tick = record.tick;
+            match &record.action {
+                AgentAction::Spawn { role, hilbert_pos } => {
+                    self.spawn_agent_with_id(*role, *hilbert_pos, Some(record.agent_id.clone()));
+                },
+                AgentAction::AssignGoal { goal } => {
+                    if let Some(agent) = self.agents.get_mut(&record.agent_id) {
+                        agent.add_goal(goal.clone());
+                        self.action_log.record(
+                            self.tick,
+                            &record.agent_id,
+                            AgentAction::AssignGoal { goal: goal.clone() },
+                        );
+                    }
+                },
+                AgentAction::Step {
+                    dt,
+                    wander_target,
+                    goal_switched,
+                } => {
+                    let Some(agent) = self.agents.get_mut(&record.agent_id) else {
+                        continue;
+                    };
+                    if Self::drive_patrol(
+                        agent,
+                        &mut self.patrols,
+                        &mut self.pathfinder,
+                        self.grid_size,
+                    ) {
+                        self.total_tasks_completed += 1;
+                    }
+                    let params = self
+                        .role_params
+                        .get(&agent.role)
+                        .copied()
+                        .unwrap_or_default();
+                    let recorded = *wander_target;
+                    let (completed, picked) = Self::step_agent(
+                        agent,
+                        *dt,
+                        self.grid_size,
+                        &params,
+                        *goal_switched,
+                        &mut self.requests,
+                        |bound| {
+                            recorded.unwrap_or_else(|| {
+                                log::warn!(
+                                    "Replay diverged: agent {} wandered without a recorded target",
+                                    record.agent_id
+                                );
+                                use rand::Rng;
+                                rand::thread_rng().gen_range(0..bound)
+                            })
+                        },
+                    );
+                    if completed {
+                        self.total_tasks_completed += 1;
+                    }
+                    self.action_log.record(
+                        self.tick,
+                        &record.agent_id,
+                        AgentAction::Step {
+                            dt: *dt,
+                            wander_target: picked,
+                            goal_switched: *goal_switched,
+                        },
+                    );
+                },
+                AgentAction::Despawn => {
+                    self.despawn_agent(&record.agent_id);
+                },
+            }
+        }
+    }
+
     /// Find agents near a position
     pub fn agents_near(&self, hilbert_pos: u32, radius: u32) -> Vec<&CityAgent> {
         self.agents
@@ -589,6 +1035,18 @@ impl CityAgentManager {
             .collect()
     }
 
+    /// Find agents within the sensing radius of an agent's role
+    pub fn sensed_neighbors(&self, id: &str) -> Vec<&CityAgent> {
+        let Some(agent) = self.agents.get(id) else {
+            return Vec::new();
+        };
+        let radius = self.role_params(agent.role).sensing_radius;
+        self.agents_near(agent.hilbert_pos, radius)
+            .into_iter()
+            .filter(|other| other.id != id)
+            .collect()
+    }
+
     /// Assign goal to nearest agent of appropriate role
     pub fn assign_goal(&mut self, role: AgentRole, goal: AgentGoal) -> Option<String> {
         let goal_loc = goal.target_hilbert;
@@ -605,6 +1063,11 @@ impl CityAgentManager {
             });
 
         if let Some(agent) = nearest {
+            self.action_log.record(
+                self.tick,
+                &agent.id,
+                AgentAction::AssignGoal { goal: goal.clone() },
+            );
             agent.add_goal(goal);
             Some(agent.id.clone())
         } else {
@@ -619,7 +1082,10 @@ impl CityAgentManager {
 
     /// Remove an agent
     pub fn despawn_agent(&mut self, id: &str) -> Option<CityAgent> {
-        self.agents.remove(id)
+        let agent = self.agents.remove(id)?;
+        self.patrols.remove(id);
+        self.action_log.record(self.tick, id, AgentAction::Despawn);
+        Some(agent)
     }
 }
 
@@ -674,4 +1140,208 @@ mod tests {
         assert!(agent.is_some());
         assert_eq!(agent.unwrap().hilbert_pos, 100);
     }
+
+    #[test]
+    fn test_action_log_replay_matches_original() {
+        let mut original = CityAgentManager::new(256);
+        let scout = original.spawn_agent(AgentRole::Scout, 100);
+        original.spawn_agent(AgentRole::Engineer, 2000);
+        let archivist = original.spawn_agent(AgentRole::Archivist, 40000);
+
+        for _ in 0..30 {
+            original.update(0.016);
+        }
+        original.despawn_agent(&archivist);
+        for _ in 0..30 {
+            original.update(0.016);
+        }
+
+        let log = original.action_log().export();
+        assert!(log
+            .iter()
+            .any(|r| r.agent_id == scout && matches!(r.action, AgentAction::Spawn { .. })));
+
+        let mut replayed = CityAgentManager::new(256);
+        replayed.replay(&log);
+
+        assert_eq!(replayed.list_agents().len(), original.list_agents().len());
+        assert!(replayed.get_agent(&archivist).is_none());
+        for agent in original.list_agents() {
+            let copy = replayed.get_agent(&agent.id).expect("agent replayed");
+            assert_eq!(copy.role, agent.role);
+            assert_eq!(copy.hilbert_pos, agent.hilbert_pos);
+            assert_eq!(copy.world_pos, agent.world_pos);
+            assert_eq!(copy.target_pos, agent.target_pos);
+            assert_eq!(copy.state, agent.state);
+            assert_eq!(copy.goals.len(), agent.goals.len());
+        }
+        assert_eq!(
+            replayed.total_tasks_completed,
+            original.total_tasks_completed
+        );
+    }
+
+    #[test]
+    fn test_action_log_is_bounded() {
+        let mut log = ActionLog::new(2);
+        log.record(1, "a", AgentAction::Despawn);
+        log.record(2, "b", AgentAction::Despawn);
+        log.record(3, "c", AgentAction::Despawn);
+
+        let records = log.export();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].tick, 2);
+        assert_eq!(records[1].agent_id, "c");
+    }
+
+    #[test]
+    fn test_role_params_change_scout_speed_only() {
+        let mut manager = CityAgentManager::new(256);
+        let fast = AgentParams {
+            move_speed: 300.0,
+            ..AgentParams::default()
+        };
+        manager.set_role_params(AgentRole::Scout, fast);
+        assert_eq!(manager.role_params(AgentRole::Scout), fast);
+        assert_eq!(
+            manager.role_params(AgentRole::Engineer),
+            AgentParams::default()
+        );
+
+        let scout = manager.spawn_agent(AgentRole::Scout, 0);
+        let engineer = manager.spawn_agent(AgentRole::Engineer, 0);
+        for id in [&scout, &engineer] {
+            manager.get_agent_mut(id).unwrap().add_goal(AgentGoal {
+                id: "far".to_string(),
+                goal_type: GoalType::Navigate {
+                    destination: "far".to_string(),
+                },
+                target_path: None,
+                target_hilbert: Some(256 * 256 / 2),
+                priority: 100,
+                created_at: 0.0,
+                deadline: None,
+            });
+        }
+
+        // First tick picks the movement target, second tick moves
+        let dt = 0.0001;
+        manager.update(dt);
+        let start = |m: &CityAgentManager, id: &str| m.get_agent(id).unwrap().world_pos;
+        let (scout_start, engineer_start) = (start(&manager, &scout), start(&manager, &engineer));
+        manager.update(dt);
+
+        let moved = |m: &CityAgentManager, id: &str, from: (f32, f32)| {
+            let pos = m.get_agent(id).unwrap().world_pos;
+            ((pos.0 - from.0).powi(2) + (pos.1 - from.1).powi(2)).sqrt()
+        };
+        let scout_moved = moved(&manager, &scout, scout_start);
+        let engineer_moved = moved(&manager, &engineer, engineer_start);
+
+        assert!((engineer_moved - 100.0 * dt).abs() < 1e-4);
+        assert!((scout_moved - 300.0 * dt).abs() < 1e-4);
+        assert!(scout_moved > engineer_moved);
+    }
+
+    fn patrol_goal(waypoints: Vec<(u32, u32)>, loop_count: Option<u32>) -> AgentGoal {
+        AgentGoal {
+            id: "patrol".to_string(),
+            goal_type: GoalType::Patrol {
+                waypoints,
+                loop_count,
+            },
+            target_path: None,
+            target_hilbert: None,
+            priority: 50,
+            created_at: 0.0,
+            deadline: None,
+        }
+    }
+
+    #[test]
+    fn test_patrol_visits_waypoints_in_order_and_repeats() {
+        let mut manager = CityAgentManager::new(16);
+        let id = manager.spawn_agent(AgentRole::Scout, 0);
+        manager
+            .get_agent_mut(&id)
+            .unwrap()
+            .add_goal(patrol_goal(vec![(2, 2), (12, 3), (5, 13)], Some(2)));
+
+        for _ in 0..2000 {
+            manager.update(0.016);
+            if manager.total_tasks_completed > 0 {
+                break;
+            }
+        }
+
+        let agent = manager.get_agent(&id).unwrap();
+        let visits: Vec<&str> = agent
+            .memory
+            .iter()
+            .map(|m| m.observation.as_str())
+            .filter(|o| o.starts_with("Patrol waypoint"))
+            .collect();
+        assert_eq!(
+            visits,
+            vec![
+                "Patrol waypoint 0 reached at (2, 2)",
+                "Patrol waypoint 1 reached at (12, 3)",
+                "Patrol waypoint 2 reached at (5, 13)",
+                "Patrol waypoint 0 reached at (2, 2)",
+                "Patrol waypoint 1 reached at (12, 3)",
+                "Patrol waypoint 2 reached at (5, 13)",
+            ]
+        );
+        assert!(manager.patrol_progress(&id).is_none());
+        assert!(!matches!(
+            agent.current_goal().map(|g| &g.goal_type),
+            Some(GoalType::Patrol { .. })
+        ));
+    }
+
+    #[test]
+    fn test_patrol_route_survives_vat_round_trip() {
+        let mut agent = CityAgent::new(AgentRole::Scout, 0);
+        agent.add_goal(patrol_goal(vec![(2, 2), (12, 3)], Some(3)));
+        agent.add_goal(patrol_goal(vec![(1, 1)], None));
+
+        let mut vat = VatBuffer::new(agent.vat_id());
+        agent.serialize_to_vat(&mut vat).unwrap();
+        vat.finalize();
+
+        let mut restored = CityAgent::new(AgentRole::Engineer, 0);
+        restored.deserialize_from_vat(&mut vat).unwrap();
+        let routes: Vec<&GoalType> = restored.goals.iter().map(|g| &g.goal_type).collect();
+        assert_eq!(
+            routes,
+            vec![
+                &GoalType::Patrol {
+                    waypoints: vec![(2, 2), (12, 3)],
+                    loop_count: Some(3),
+                },
+                &GoalType::Patrol {
+                    waypoints: vec![(1, 1)],
+                    loop_count: None,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_patrol_without_loop_count_continues() {
+        let mut manager = CityAgentManager::new(16);
+        let id = manager.spawn_agent(AgentRole::Scout, 0);
+        manager
+            .get_agent_mut(&id)
+            .unwrap()
+            .add_goal(patrol_goal(vec![(1, 1), (3, 0)], None));
+
+        for _ in 0..500 {
+            manager.update(0.016);
+        }
+
+        let progress = manager.patrol_progress(&id).expect("still patrolling");
+        assert!(progress.loops_completed >= 2);
+        assert_eq!(manager.total_tasks_completed, 0);
+    }
 }
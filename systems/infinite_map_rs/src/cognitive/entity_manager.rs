@@ -1,12 +1,85 @@
 use crate::cognitive::entity_type::EntityType;
-use parking_lot::RwLock;
-use std::collections::HashMap;
+use parking_lot::{Mutex, RwLock};
+use std::collections::{HashMap, VecDeque};
 use std::path::PathBuf;
 use std::sync::Arc;
 
+/// Default number of messages queued per entity before the oldest is dropped
+pub const DEFAULT_MAILBOX_CAPACITY: usize = 64;
+
 /// Manages the lifecycle of cognitive entities
 pub struct CognitiveEntityManager {
     entities: Arc<RwLock<HashMap<String, ACEEntity>>>,
+    mailboxes: EntityMailboxes,
+}
+
+/// A message passed between cognitive entities
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EntityMessage {
+    pub from: String,
+    pub payload: Vec<u8>,
+}
+
+/// Bounded per-entity message queues, shared with ACE runtimes
+#[derive(Debug, Clone)]
+pub struct EntityMailboxes {
+    queues: Arc<Mutex<HashMap<String, VecDeque<EntityMessage>>>>,
+    capacity: usize,
+}
+
+impl EntityMailboxes {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            queues: Arc::new(Mutex::new(HashMap::new())),
+            capacity: capacity.max(1),
+        }
+    }
+
+    /// Queue a message for `to`, dropping its oldest message if the queue is full
+    ///
+    /// Returns `true` if a message had to be dropped.
+    pub fn send(&self, from: &str, to: &str, payload: Vec<u8>) -> bool {
+        let mut queues = self.queues.lock();
+        let queue = queues.entry(to.to_string()).or_default();
+        let dropped = queue.len() >= self.capacity;
+        if dropped {
+            queue.pop_front();
+        }
+        queue.push_back(EntityMessage {
+            from: from.to_string(),
+            payload,
+        });
+        dropped
+    }
+
+    /// Pop the oldest message queued for `id`
+    pub fn recv(&self, id: &str) -> Option<EntityMessage> {
+        self.queues.lock().get_mut(id)?.pop_front()
+    }
+
+    /// Pop the oldest message for `id` if its payload fits in `max_len` bytes
+    ///
+    /// A payload that does not fit stays queued and its length is returned
+    /// as the error, so the caller can retry with a larger buffer.
+    pub fn recv_within(&self, id: &str, max_len: usize) -> Option<Result<EntityMessage, usize>> {
+        let mut queues = self.queues.lock();
+        let queue = queues.get_mut(id)?;
+        let len = queue.front()?.payload.len();
+        if len > max_len {
+            return Some(Err(len));
+        }
+        queue.pop_front().map(Ok)
+    }
+
+    /// Number of messages waiting for `id`
+    pub fn pending(&self, id: &str) -> usize {
+        self.queues.lock().get(id).map_or(0, VecDeque::len)
+    }
+
+    /// Per-entity queue capacity
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -15,6 +88,8 @@ pub struct ACEEntity {
     pub texture_path: PathBuf,
     pub state: EntityState,
     pub entity_type: EntityType,
+    /// Whether the entity is mid-thought (drives the thinking indicator)
+    pub thinking: bool,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -28,8 +103,13 @@ pub enum EntityState {
 
 impl CognitiveEntityManager {
     pub fn new() -> Self {
+        Self::with_mailbox_capacity(DEFAULT_MAILBOX_CAPACITY)
+    }
+
+    pub fn with_mailbox_capacity(capacity: usize) -> Self {
         Self {
             entities: Arc::new(RwLock::new(HashMap::new())),
+            mailboxes: EntityMailboxes::new(capacity),
         }
     }
 
@@ -42,6 +122,7 @@ impl CognitiveEntityManager {
                 texture_path,
                 state: EntityState::Dormant,
                 entity_type,
+                thinking: false,
             },
         );
     }
@@ -53,6 +134,18 @@ impl CognitiveEntityManager {
         }
     }
 
+    pub fn set_thinking(&self, id: &str, thinking: bool) {
+        let mut entities = self.entities.write();
+        if let Some(entity) = entities.get_mut(id) {
+            entity.thinking = thinking;
+        }
+    }
+
+    pub fn is_thinking(&self, id: &str) -> bool {
+        let entities = self.entities.read();
+        entities.get(id).is_some_and(|e| e.thinking)
+    }
+
     pub fn get_entity_state(&self, id: &str) -> Option<EntityState> {
         let entities = self.entities.read();
         entities.get(id).map(|e| e.state.clone())
@@ -62,4 +155,88 @@ impl CognitiveEntityManager {
         let entities = self.entities.read();
         entities.values().cloned().collect()
     }
+
+    /// Send a message from one entity to another
+    ///
+    /// Returns `false` if the recipient is not registered. When the recipient's
+    /// queue is full the oldest message is dropped to make room.
+    pub fn send_message(&self, from: &str, to: &str, bytes: Vec<u8>) -> bool {
+        if !self.entities.read().contains_key(to) {
+            return false;
+        }
+        if self.mailboxes.send(from, to, bytes) {
+            log::debug!("📪 Mailbox for {} full, dropped oldest message", to);
+        }
+        true
+    }
+
+    /// Receive the oldest pending message for an entity
+    pub fn recv_message(&self, id: &str) -> Option<Vec<u8>> {
+        self.mailboxes.recv(id).map(|message| message.payload)
+    }
+
+    /// Shared handle to the mailboxes (for attaching to ACE runtimes)
+    pub fn mailboxes(&self) -> EntityMailboxes {
+        self.mailboxes.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn manager_with_pair(capacity: usize) -> CognitiveEntityManager {
+        let manager = CognitiveEntityManager::with_mailbox_capacity(capacity);
+        for id in ["alpha", "beta"] {
+            manager.register_entity(
+                id.to_string(),
+                PathBuf::from(format!("{}.rts.png", id)),
+                EntityType::CognitiveEntity,
+            );
+        }
+        manager
+    }
+
+    #[test]
+    fn test_message_passing_preserves_order() {
+        let manager = manager_with_pair(DEFAULT_MAILBOX_CAPACITY);
+
+        assert!(manager.send_message("alpha", "beta", b"first".to_vec()));
+        assert!(manager.send_message("alpha", "beta", b"second".to_vec()));
+        assert!(!manager.send_message("alpha", "nobody", b"lost".to_vec()));
+
+        assert_eq!(manager.recv_message("beta"), Some(b"first".to_vec()));
+        assert_eq!(manager.recv_message("beta"), Some(b"second".to_vec()));
+        assert_eq!(manager.recv_message("beta"), None);
+        assert_eq!(manager.recv_message("alpha"), None);
+    }
+
+    #[test]
+    fn test_mailbox_overflow_drops_oldest() {
+        let manager = manager_with_pair(2);
+
+        for payload in [b"m1", b"m2", b"m3"] {
+            manager.send_message("alpha", "beta", payload.to_vec());
+        }
+
+        assert_eq!(manager.mailboxes().pending("beta"), 2);
+        assert_eq!(manager.recv_message("beta"), Some(b"m2".to_vec()));
+        assert_eq!(manager.recv_message("beta"), Some(b"m3".to_vec()));
+    }
+
+    #[test]
+    fn test_recv_within_keeps_oversized_messages_queued() {
+        let mailboxes = EntityMailboxes::new(4);
+        mailboxes.send("alpha", "beta", b"hello".to_vec());
+
+        assert_eq!(mailboxes.recv_within("beta", 4), Some(Err(5)));
+        assert_eq!(mailboxes.pending("beta"), 1);
+        let message = mailboxes.recv_within("beta", 5).unwrap().unwrap();
+        assert_eq!(
+            (message.from.as_str(), message.payload),
+            ("alpha", b"hello".to_vec())
+        );
+        assert_eq!(mailboxes.recv_within("beta", 5), None);
+        assert_eq!(mailboxes.recv_within("nobody", 5), None);
+    }
 }
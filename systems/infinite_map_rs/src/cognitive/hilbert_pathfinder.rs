@@ -575,74 +575,12 @@ impl HilbertPathfinder {
 
     /// Convert Hilbert coordinate to (x, y)
     pub fn hilbert_to_xy(&self, d: u32) -> (u32, u32) {
-        let n = self.grid_size;
-        let mut x = 0u32;
-        let mut y = 0u32;
-        let mut s = 1u32;
-        let mut t = d;
-
-        while s < n {
-            let rx = 1 & (t / 2);
-            let ry = 1 & (t ^ rx);
-
-            if ry == 0 {
-                if rx == 1 {
-                    x = n.wrapping_sub(1).wrapping_sub(x);
-                    y = n.wrapping_sub(1).wrapping_sub(y);
-                }
-                std::mem::swap(&mut x, &mut y);
-            }
-
-            x = x.wrapping_add(s * rx);
-            y = y.wrapping_add(s * ry);
-            t /= 4;
-            s *= 2;
-        }
-
-        (x, y)
+        crate::hilbert::d2xy(self.grid_size, d as u64)
     }
 
     /// Convert (x, y) to Hilbert coordinate
     pub fn xy_to_hilbert(&self, x: u32, y: u32) -> u32 {
-        let mut d = 0u32;
-        let mut s = 1u32;
-        let mut cur_x = x;
-        let mut cur_y = y;
-
-        while s < self.grid_size {
-            let mask = s - 1;
-            let rx = cur_x & mask;
-            let ry = cur_y & mask;
-
-            // Rotate/flip quadrant
-            d += s
-                * s
-                * match (rx, ry) {
-                    (0, 0) => 0,
-                    (1, 0) => 1,
-                    (1, 1) => 2,
-                    (0, 1) => 3,
-                    _ => 0,
-                };
-
-            cur_x = if ry == 0 {
-                if rx == 1 {
-                    s - 1 - cur_x
-                } else {
-                    cur_x
-                }
-            } else {
-                cur_x
-            };
-
-            std::mem::swap(&mut cur_x, &mut cur_y);
-            cur_x += if ry == 0 && rx == 1 { s } else { 0 };
-            cur_y += if ry == 0 && rx == 1 { s } else { 0 };
-
-            s *= 2;
-        }
-
-        d
+        crate::hilbert::xy2d(self.grid_size, x, y) as u32
     }
 
     /// Normalize x coordinate to [-1, 1] range
@@ -1,4 +1,5 @@
 use crate::cognitive::ace_runtime::ACEState;
+use crate::diagnostic::DiagnosticSnapshot;
 use image::{GenericImage, GenericImageView};
 use std::io::Write;
 use std::os::unix::net::UnixStream;
@@ -73,5 +74,164 @@ pub fn register_host_functions(linker: &mut Linker<ACEState>) -> anyhow::Result<
         },
     )?;
 
+    linker.func_wrap(
+        "ace",
+        "get_pas_score",
+        |mut caller: Caller<'_, ACEState>| -> f32 { read_diagnostics(&mut caller).pas_score },
+    )?;
+
+    linker.func_wrap(
+        "ace",
+        "get_metabolic_state_name",
+        |mut caller: Caller<'_, ACEState>| -> i32 {
+            read_diagnostics(&mut caller).metabolic_state_code
+        },
+    )?;
+
+    // Copies the oldest pending message into guest memory at `ptr` and
+    // returns its length, or -1 if no message is pending. A return value
+    // larger than `capacity` means nothing was copied: the message stays
+    // queued and the guest can retry with a buffer of that size.
+    linker.func_wrap(
+        "ace",
+        "recv_message",
+        |mut caller: Caller<'_, ACEState>, ptr: u32, capacity: u32| -> i32 {
+            let Some(mailboxes) = caller.data().mailboxes.clone() else {
+                return -1;
+            };
+            let Some(memory) = caller
+                .get_export("memory")
+                .and_then(|export| export.into_memory())
+            else {
+                return -1;
+            };
+            // Only bytes inside linear memory count, so the write cannot fail
+            // after the message has been dequeued
+            let room = memory
+                .data_size(&caller)
+                .saturating_sub(ptr as usize)
+                .min(capacity as usize);
+            let id = caller.data().id.clone();
+            match mailboxes.recv_within(&id, room) {
+                None => -1,
+                Some(Err(len)) => len as i32,
+                Some(Ok(message)) => {
+                    memory.data_mut(&mut caller)[ptr as usize..][..message.payload.len()]
+                        .copy_from_slice(&message.payload);
+                    message.payload.len() as i32
+                },
+            }
+        },
+    )?;
+
     Ok(())
 }
+
+/// Read the shared diagnostic snapshot without blocking
+///
+/// Uses `try_read` so a host that calls into WASM while holding the write
+/// lock cannot deadlock; the last successfully read snapshot is returned
+/// instead.
+fn read_diagnostics(caller: &mut Caller<'_, ACEState>) -> DiagnosticSnapshot {
+    let state = caller.data_mut();
+    let latest = state
+        .diagnostics
+        .as_ref()
+        .and_then(|shared| shared.try_read().map(|snapshot| *snapshot));
+    if let Some(snapshot) = latest {
+        state.last_diagnostics = snapshot;
+    }
+    state.last_diagnostics
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cognitive::entity_manager::EntityMailboxes;
+    use crate::diagnostic::DiagnosticOverlay;
+
+    const PAS_PROBE_WAT: &str = r#"
+        (module
+            (import "ace" "get_pas_score" (func $pas (result f32)))
+            (import "ace" "get_metabolic_state_name" (func $state (result i32)))
+            (func (export "probe_pas") (result f32) call $pas)
+            (func (export "probe_state") (result i32) call $state))
+    "#;
+
+    fn instantiate(state: ACEState) -> (Store<ACEState>, Instance) {
+        let engine = Engine::default();
+        let module = Module::new(&engine, PAS_PROBE_WAT).unwrap();
+        let mut store = Store::new(&engine, state);
+        let mut linker = Linker::new(&engine);
+        register_host_functions(&mut linker).unwrap();
+        let instance = linker.instantiate(&mut store, &module).unwrap();
+        (store, instance)
+    }
+
+    #[test]
+    fn test_get_pas_score_reads_shared_snapshot() {
+        let mut overlay = DiagnosticOverlay::new();
+        overlay.current_pas.p = 0.5;
+        overlay.current_pas.a = 0.5;
+        overlay.current_pas.s = 0.0;
+        overlay.publish_snapshot();
+
+        let mut state = ACEState::new("probe".to_string(), "probe.rts.png".into());
+        state.diagnostics = Some(overlay.shared_snapshot());
+        let (mut store, instance) = instantiate(state);
+
+        let probe_pas = instance
+            .get_typed_func::<(), f32>(&mut store, "probe_pas")
+            .unwrap();
+        let probe_state = instance
+            .get_typed_func::<(), i32>(&mut store, "probe_state")
+            .unwrap();
+
+        let expected = overlay.current_pas.calculate();
+        assert!((probe_pas.call(&mut store, ()).unwrap() - expected).abs() < f32::EPSILON);
+        assert_eq!(probe_state.call(&mut store, ()).unwrap(), 0);
+
+        // While the writer holds the lock, the last known value is returned
+        let shared = overlay.shared_snapshot();
+        let _guard = shared.write();
+        assert!((probe_pas.call(&mut store, ()).unwrap() - expected).abs() < f32::EPSILON);
+    }
+
+    #[test]
+    fn test_recv_message_copies_payload_into_guest_memory() {
+        const RECV_WAT: &str = r#"
+            (module
+                (import "ace" "recv_message" (func $recv (param i32 i32) (result i32)))
+                (memory (export "memory") 1)
+                (func (export "probe_recv") (param i32) (result i32)
+                    i32.const 16
+                    local.get 0
+                    call $recv))
+        "#;
+
+        let mailboxes = EntityMailboxes::new(8);
+        mailboxes.send("alpha", "beta", b"hello".to_vec());
+
+        let mut state = ACEState::new("beta".to_string(), "beta.rts.png".into());
+        state.mailboxes = Some(mailboxes.clone());
+
+        let engine = Engine::default();
+        let module = Module::new(&engine, RECV_WAT).unwrap();
+        let mut store = Store::new(&engine, state);
+        let mut linker = Linker::new(&engine);
+        register_host_functions(&mut linker).unwrap();
+        let instance = linker.instantiate(&mut store, &module).unwrap();
+        let probe = instance
+            .get_typed_func::<i32, i32>(&mut store, "probe_recv")
+            .unwrap();
+
+        // Too small a buffer reports the needed size and leaves the message queued
+        assert_eq!(probe.call(&mut store, 2).unwrap(), 5);
+        assert_eq!(mailboxes.pending("beta"), 1);
+        assert_eq!(probe.call(&mut store, 5).unwrap(), 5);
+        assert_eq!(probe.call(&mut store, 64).unwrap(), -1);
+
+        let memory = instance.get_memory(&mut store, "memory").unwrap();
+        assert_eq!(&memory.data(&store)[16..21], b"hello");
+    }
+}
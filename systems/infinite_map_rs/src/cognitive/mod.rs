@@ -45,15 +45,20 @@ pub mod agents;
 pub mod hilbert_pathfinder;
 
 // Re-export common types
-pub use ace_runtime::{ACERuntime, ACEState};
+pub use ace_runtime::{ACERuntime, ACERuntimeConfig, ACEState};
 pub use binary_extractor::ACEBinaryExtractor;
-pub use entity_manager::{ACEEntity, CognitiveEntityManager, EntityState};
+pub use entity_manager::{
+    ACEEntity, CognitiveEntityManager, EntityMailboxes, EntityMessage, EntityState,
+};
 pub use entity_type::{EntityType, RTSMetadata};
 pub use host_functions::register_host_functions;
-pub use texture_updater::TextureUpdater;
+pub use texture_updater::{TextureQueue, TextureUpdater, ThinkingStyle};
 
 // Phase 46 exports
-pub use agents::{AgentGoal, AgentRole, AgentState, CityAgent, CityAgentManager, GoalType};
+pub use agents::{
+    ActionLog, ActionRecord, AgentAction, AgentGoal, AgentParams, AgentRole, AgentState, CityAgent,
+    CityAgentManager, GoalType, PatrolProgress,
+};
 pub use hilbert_pathfinder::{
     assign_navigation_goal, HilbertPath, HilbertPathfinder, PathStrategy, Waypoint,
 };
@@ -1,45 +1,500 @@
+use crate::damage_tracker::{self, DamageTracker, DirtyRect};
 use std::sync::Arc;
 use wgpu;
 
-pub struct TextureUpdater {
-    /// GPU device (scaffolding: future async texture ops)
-    #[allow(dead_code)]
-    device: Arc<wgpu::Device>,
-    queue: Arc<wgpu::Queue>,
+/// Bytes per RGBA8 texel (default format, and the only one the overlay tints)
+const BYTES_PER_PIXEL: u32 = 4;
+
+/// Edge length of the tiles compared by the diff path
+pub const DIFF_TILE_SIZE: u32 = 16;
+
+/// Pulsing border drawn over an entity texture while it is thinking
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ThinkingStyle {
+    /// RGBA tint blended into the border
+    pub color: [u8; 4],
+    /// Border thickness in pixels
+    pub border_width: u32,
+    /// Frames per full pulse (dim -> bright -> dim)
+    pub period_frames: u32,
+    /// Blend strength at the dimmest point of the pulse
+    pub min_intensity: f32,
+    /// Blend strength at the brightest point of the pulse
+    pub max_intensity: f32,
 }
 
-impl TextureUpdater {
-    pub fn new(device: Arc<wgpu::Device>, queue: Arc<wgpu::Queue>) -> Self {
-        Self { device, queue }
+impl Default for ThinkingStyle {
+    fn default() -> Self {
+        Self {
+            color: [0, 200, 255, 255],
+            border_width: 2,
+            period_frames: 60,
+            min_intensity: 0.2,
+            max_intensity: 0.9,
+        }
     }
+}
 
-    pub fn update_chunk(
+impl ThinkingStyle {
+    /// Blend strength for a given frame of the pulse
+    pub fn intensity(&self, frame: u64) -> f32 {
+        let period = self.period_frames.max(1) as u64;
+        let phase = (frame % period) as f32 / period as f32;
+        let wave = 0.5 - 0.5 * (phase * std::f32::consts::TAU).cos();
+        self.min_intensity + (self.max_intensity - self.min_intensity) * wave
+    }
+}
+
+/// Destination for texture uploads (wgpu queue, or a recorder in tests)
+pub trait TextureQueue {
+    type Texture: ?Sized;
+
+    /// Upload tightly packed texels of `bytes_per_pixel` bytes into `rect`
+    fn write_region(
+        &self,
+        texture: &Self::Texture,
+        data: &[u8],
+        rect: &DirtyRect,
+        bytes_per_pixel: u32,
+    );
+}
+
+impl TextureQueue for wgpu::Queue {
+    type Texture = wgpu::Texture;
+
+    fn write_region(
         &self,
         texture: &wgpu::Texture,
         data: &[u8],
-        x: u32,
-        y: u32,
-        width: u32,
-        height: u32,
+        rect: &DirtyRect,
+        bytes_per_pixel: u32,
     ) {
-        self.queue.write_texture(
+        // Packed data is its own full-width buffer
+        let layout = DirtyRect::new(0, 0, rect.width(), rect.height())
+            .upload_layout(rect.width(), bytes_per_pixel);
+        self.write_texture(
             wgpu::ImageCopyTexture {
                 texture,
                 mip_level: 0,
-                origin: wgpu::Origin3d { x, y, z: 0 },
+                origin: wgpu::Origin3d {
+                    x: rect.x1,
+                    y: rect.y1,
+                    z: 0,
+                },
                 aspect: wgpu::TextureAspect::All,
             },
             data,
-            wgpu::ImageDataLayout {
-                offset: 0,
-                bytes_per_row: Some(4 * width),
-                rows_per_image: Some(height),
-            },
+            layout.image_data_layout(),
             wgpu::Extent3d {
-                width,
-                height,
+                width: rect.width(),
+                height: rect.height(),
                 depth_or_array_layers: 1,
             },
         );
     }
 }
+
+pub struct TextureUpdater<Q: TextureQueue = wgpu::Queue> {
+    /// GPU device (scaffolding: future async texture ops)
+    #[allow(dead_code)]
+    device: Option<Arc<wgpu::Device>>,
+    queue: Arc<Q>,
+    /// Upload only changed regions in `update_texture`
+    diff_mode: bool,
+    /// Texel size of the textures being updated
+    bytes_per_pixel: u32,
+    /// Thinking indicator appearance
+    thinking_style: ThinkingStyle,
+    /// Frames since the indicator started pulsing
+    thinking_frame: u64,
+}
+
+impl TextureUpdater {
+    pub fn new(device: Arc<wgpu::Device>, queue: Arc<wgpu::Queue>) -> Self {
+        Self {
+            device: Some(device),
+            queue,
+            diff_mode: false,
+            bytes_per_pixel: BYTES_PER_PIXEL,
+            thinking_style: ThinkingStyle::default(),
+            thinking_frame: 0,
+        }
+    }
+}
+
+impl<Q: TextureQueue> TextureUpdater<Q> {
+    /// Create an updater that writes through an arbitrary queue
+    pub fn with_queue(queue: Arc<Q>) -> Self {
+        Self {
+            device: None,
+            queue,
+            diff_mode: false,
+            bytes_per_pixel: BYTES_PER_PIXEL,
+            thinking_style: ThinkingStyle::default(),
+            thinking_frame: 0,
+        }
+    }
+
+    /// Enable or disable diff-based uploads
+    pub fn set_diff_mode(&mut self, enabled: bool) {
+        self.diff_mode = enabled;
+    }
+
+    /// Whether diff-based uploads are enabled
+    pub fn diff_mode(&self) -> bool {
+        self.diff_mode
+    }
+
+    /// Set the format of the textures being updated (defaults to RGBA8)
+    pub fn set_texture_format(&mut self, format: wgpu::TextureFormat) -> Result<(), String> {
+        self.bytes_per_pixel = damage_tracker::bytes_per_pixel(format)
+            .ok_or_else(|| format!("Texture format {:?} has no per-texel size", format))?;
+        Ok(())
+    }
+
+    /// Bytes per texel of the configured texture format
+    pub fn bytes_per_pixel(&self) -> u32 {
+        self.bytes_per_pixel
+    }
+
+    /// Configure the thinking indicator
+    pub fn set_thinking_style(&mut self, style: ThinkingStyle) {
+        self.thinking_style = style;
+    }
+
+    /// Current thinking indicator style
+    pub fn thinking_style(&self) -> ThinkingStyle {
+        self.thinking_style
+    }
+
+    /// Upload an entity texture, pulsing its border while `thinking`
+    ///
+    /// The pulse advances one frame per call and restarts once thinking clears.
+    pub fn update_entity_texture(
+        &mut self,
+        texture: &Q::Texture,
+        previous: Option<&[u8]>,
+        current: &[u8],
+        width: u32,
+        height: u32,
+        thinking: bool,
+    ) -> Vec<DirtyRect> {
+        if !thinking || self.bytes_per_pixel != BYTES_PER_PIXEL {
+            self.thinking_frame = 0;
+            return self.update_texture(texture, previous, current, width, height);
+        }
+
+        let mut pixels = current.to_vec();
+        self.apply_thinking_overlay(&mut pixels, width, height, self.thinking_frame);
+        self.thinking_frame += 1;
+        self.update_texture(texture, previous, &pixels, width, height)
+    }
+
+    /// Blend the thinking border into an RGBA8 buffer for the given pulse frame
+    pub fn apply_thinking_overlay(&self, pixels: &mut [u8], width: u32, height: u32, frame: u64) {
+        let style = &self.thinking_style;
+        let alpha = style.intensity(frame).clamp(0.0, 1.0);
+        let border = style.border_width;
+
+        for y in 0..height {
+            for x in 0..width {
+                let on_border =
+                    x < border || y < border || x + border >= width || y + border >= height;
+                if !on_border {
+                    continue;
+                }
+                let i = ((y * width + x) * BYTES_PER_PIXEL) as usize;
+                if let Some(px) = pixels.get_mut(i..i + BYTES_PER_PIXEL as usize) {
+                    for (channel, tint) in px.iter_mut().zip(style.color) {
+                        *channel =
+                            (*channel as f32 * (1.0 - alpha) + tint as f32 * alpha).round() as u8;
+                    }
+                }
+            }
+        }
+    }
+
+    pub fn update_chunk(
+        &self,
+        texture: &Q::Texture,
+        data: &[u8],
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+    ) {
+        let rect = DirtyRect::new(x, y, x + width, y + height);
+        self.queue
+            .write_region(texture, data, &rect, self.bytes_per_pixel);
+    }
+
+    /// Upload a full `width` x `height` buffer of the configured format
+    ///
+    /// In diff mode only the regions that differ from `previous` are uploaded.
+    /// Without a usable `previous` buffer the whole texture is written.
+    /// Returns the uploaded regions in pixels.
+    pub fn update_texture(
+        &self,
+        texture: &Q::Texture,
+        previous: Option<&[u8]>,
+        current: &[u8],
+        width: u32,
+        height: u32,
+    ) -> Vec<DirtyRect> {
+        let bpp = self.bytes_per_pixel;
+        let expected = (width * height * bpp) as usize;
+        if current.len() != expected {
+            log::warn!(
+                "⚠️  Texture buffer size mismatch: expected {} bytes, got {}",
+                expected,
+                current.len()
+            );
+            return Vec::new();
+        }
+
+        let rects = match previous {
+            Some(previous) if self.diff_mode && previous.len() == expected => {
+                Self::diff_rects(previous, current, width, height, bpp)
+            },
+            _ => vec![DirtyRect::new(0, 0, width, height)],
+        };
+
+        for rect in &rects {
+            let data = Self::extract_region(current, width, rect, bpp);
+            self.update_chunk(
+                texture,
+                &data,
+                rect.x1,
+                rect.y1,
+                rect.width(),
+                rect.height(),
+            );
+        }
+
+        rects
+    }
+
+    /// Compute the pixel regions that differ between two texture buffers
+    pub fn diff_rects(
+        previous: &[u8],
+        current: &[u8],
+        width: u32,
+        height: u32,
+        bytes_per_pixel: u32,
+    ) -> Vec<DirtyRect> {
+        let tiles_x = width.div_ceil(DIFF_TILE_SIZE);
+        let tiles_y = height.div_ceil(DIFF_TILE_SIZE);
+        let mut tracker = DamageTracker::new(tiles_x, tiles_y);
+
+        let row_bytes = (width * bytes_per_pixel) as usize;
+        let tile_bytes = (DIFF_TILE_SIZE * bytes_per_pixel) as usize;
+        for y in 0..height {
+            let start = y as usize * row_bytes;
+            let prev_row = &previous[start..start + row_bytes];
+            let cur_row = &current[start..start + row_bytes];
+            for (tile_x, (a, b)) in prev_row
+                .chunks(tile_bytes)
+                .zip(cur_row.chunks(tile_bytes))
+                .enumerate()
+            {
+                if a != b {
+                    tracker.mark_dirty(tile_x as u32, y / DIFF_TILE_SIZE);
+                }
+            }
+        }
+
+        tracker
+            .compute_dirty_rects()
+            .into_iter()
+            .filter_map(|tiles| {
+                let pixels = DirtyRect::new(
+                    tiles.x1 * DIFF_TILE_SIZE,
+                    tiles.y1 * DIFF_TILE_SIZE,
+                    (tiles.x2 * DIFF_TILE_SIZE).min(width),
+                    (tiles.y2 * DIFF_TILE_SIZE).min(height),
+                );
+                Self::tighten(previous, current, width, &pixels, bytes_per_pixel)
+            })
+            .collect()
+    }
+
+    /// Shrink a pixel rect to the bounds of the changed pixels inside it
+    fn tighten(
+        previous: &[u8],
+        current: &[u8],
+        width: u32,
+        area: &DirtyRect,
+        bytes_per_pixel: u32,
+    ) -> Option<DirtyRect> {
+        let mut bounds: Option<DirtyRect> = None;
+        for y in area.y1..area.y2 {
+            for x in area.x1..area.x2 {
+                let i = ((y * width + x) * bytes_per_pixel) as usize;
+                let end = i + bytes_per_pixel as usize;
+                if previous[i..end] != current[i..end] {
+                    match bounds.as_mut() {
+                        Some(rect) => rect.expand_to_include(x, y),
+                        None => bounds = Some(DirtyRect::from_cell(x, y)),
+                    }
+                }
+            }
+        }
+        bounds
+    }
+
+    /// Copy a sub-rect of a full texture buffer into a tightly packed buffer
+    fn extract_region(
+        buffer: &[u8],
+        width: u32,
+        rect: &DirtyRect,
+        bytes_per_pixel: u32,
+    ) -> Vec<u8> {
+        let layout = rect.upload_layout(width, bytes_per_pixel);
+        let row_bytes = layout.row_bytes as usize;
+        let mut data = Vec::with_capacity(row_bytes * layout.rows as usize);
+        for row in 0..layout.rows as usize {
+            let start = layout.offset as usize + row * layout.bytes_per_row as usize;
+            data.extend_from_slice(&buffer[start..start + row_bytes]);
+        }
+        data
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use parking_lot::Mutex;
+
+    /// (x, y, width, height, data)
+    type WriteCall = (u32, u32, u32, u32, Vec<u8>);
+
+    #[derive(Default)]
+    struct RecordingQueue {
+        writes: Mutex<Vec<WriteCall>>,
+    }
+
+    impl TextureQueue for RecordingQueue {
+        type Texture = ();
+
+        fn write_region(&self, _: &(), data: &[u8], rect: &DirtyRect, _: u32) {
+            self.writes
+                .lock()
+                .push((rect.x1, rect.y1, rect.width(), rect.height(), data.to_vec()));
+        }
+    }
+
+    #[test]
+    fn test_diff_mode_uploads_only_changed_region() {
+        let queue = Arc::new(RecordingQueue::default());
+        let mut updater = TextureUpdater::with_queue(queue.clone());
+        updater.set_diff_mode(true);
+
+        let (width, height) = (64, 64);
+        let previous = vec![0u8; (width * height * 4) as usize];
+        let mut current = previous.clone();
+        // Change a 3x2 block at (20, 40)
+        for y in 40..42 {
+            for x in 20..23 {
+                let i = ((y * width + x) * 4) as usize;
+                current[i..i + 4].copy_from_slice(&[255, 128, 0, 255]);
+            }
+        }
+
+        let rects = updater.update_texture(&(), Some(&previous), &current, width, height);
+        assert_eq!(rects, vec![DirtyRect::new(20, 40, 23, 42)]);
+
+        let writes = queue.writes.lock();
+        assert_eq!(writes.len(), 1);
+        let (x, y, w, h, data) = &writes[0];
+        assert_eq!((*x, *y, *w, *h), (20, 40, 3, 2));
+        assert_eq!(data.len(), 3 * 2 * 4);
+        assert!(data.chunks(4).all(|px| px == [255, 128, 0, 255]));
+    }
+
+    #[test]
+    fn test_thinking_indicator_pulses_border_until_cleared() {
+        use crate::cognitive::entity_manager::CognitiveEntityManager;
+        use crate::cognitive::entity_type::EntityType;
+        use std::path::PathBuf;
+
+        let manager = CognitiveEntityManager::new();
+        manager.register_entity(
+            "thinker".to_string(),
+            PathBuf::from("thinker.rts.png"),
+            EntityType::CognitiveEntity,
+        );
+        manager.set_thinking("thinker", true);
+
+        let queue = Arc::new(RecordingQueue::default());
+        let mut updater = TextureUpdater::with_queue(queue.clone());
+        let style = ThinkingStyle {
+            color: [255, 255, 255, 255],
+            border_width: 1,
+            period_frames: 4,
+            min_intensity: 0.0,
+            max_intensity: 1.0,
+        };
+        updater.set_thinking_style(style);
+
+        let (width, height) = (8u32, 8u32);
+        let texture = vec![0u8; (width * height * 4) as usize];
+        let pixel = |data: &[u8], x: u32, y: u32| {
+            let i = ((y * width + x) * 4) as usize;
+            data[i]
+        };
+
+        // Border follows the pulse (0 -> half -> full -> half), centre untouched
+        for expected in [0u8, 128, 255, 128, 0] {
+            let thinking = manager.is_thinking("thinker");
+            updater.update_entity_texture(&(), None, &texture, width, height, thinking);
+            let writes = queue.writes.lock();
+            let data = &writes.last().unwrap().4;
+            assert_eq!(pixel(data, 0, 0), expected);
+            assert_eq!(pixel(data, 7, 4), expected);
+            assert_eq!(pixel(data, 4, 4), 0);
+        }
+
+        manager.set_thinking("thinker", false);
+        for _ in 0..3 {
+            let thinking = manager.is_thinking("thinker");
+            updater.update_entity_texture(&(), None, &texture, width, height, thinking);
+            assert_eq!(queue.writes.lock().last().unwrap().4, texture);
+        }
+    }
+
+    #[test]
+    fn test_diff_mode_r8_texture() {
+        let queue = Arc::new(RecordingQueue::default());
+        let mut updater = TextureUpdater::with_queue(queue.clone());
+        updater.set_diff_mode(true);
+        updater
+            .set_texture_format(wgpu::TextureFormat::R8Unorm)
+            .unwrap();
+        assert_eq!(updater.bytes_per_pixel(), 1);
+
+        let (width, height) = (40u32, 20u32);
+        let previous = vec![0u8; (width * height) as usize];
+        let mut current = previous.clone();
+        current[(3 * width + 17) as usize] = 9;
+        current[(4 * width + 18) as usize] = 9;
+
+        let rects = updater.update_texture(&(), Some(&previous), &current, width, height);
+        assert_eq!(rects, vec![DirtyRect::new(17, 3, 19, 5)]);
+        assert_eq!(queue.writes.lock()[0].4, vec![9, 0, 0, 9]);
+
+        assert!(updater
+            .set_texture_format(wgpu::TextureFormat::Bc1RgbaUnorm)
+            .is_err());
+    }
+
+    #[test]
+    fn test_full_upload_without_diff_mode() {
+        let queue = Arc::new(RecordingQueue::default());
+        let updater = TextureUpdater::with_queue(queue.clone());
+
+        let buffer = vec![7u8; 32 * 16 * 4];
+        let rects = updater.update_texture(&(), Some(&buffer), &buffer, 32, 16);
+        assert_eq!(rects, vec![DirtyRect::new(0, 0, 32, 16)]);
+        assert_eq!(queue.writes.lock()[0].4.len(), buffer.len());
+    }
+}
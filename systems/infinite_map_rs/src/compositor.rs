@@ -3,7 +3,8 @@
 //! This module provides the main compositor functionality for the infinite map,
 //! managing execution zones and their rendering.
 
-use crate::entities::{ExecutionZone, RTSParticle};
+use crate::camera::Camera;
+use crate::entities::{BlendMode, ExecutionZone, RTSParticle};
 use crate::input::drag_handler;
 use crate::rendering::execution_zone_renderer::ExecutionZoneRenderer;
 use glam::Vec2;
@@ -40,6 +41,10 @@ pub struct Compositor {
     rts_particles: Vec<RTSParticle>,
     /// Execution zone renderer
     zone_renderer: ExecutionZoneRenderer,
+    /// Color to clear the output to before drawing zones
+    ///
+    /// `None` preserves existing content (overlay use).
+    clear_color: Option<wgpu::Color>,
 }
 
 impl Compositor {
@@ -62,6 +67,7 @@ impl Compositor {
             execution_zones: Vec::new(),
             rts_particles: Vec::new(),
             zone_renderer: ExecutionZoneRenderer::new(device_clone, queue_clone),
+            clear_color: None,
         }
     }
 
@@ -198,14 +204,21 @@ impl Compositor {
         self.execution_zones.push(zone);
     }
 
-    /// Render all execution zones
+    /// Render the execution zones inside the camera's view
     ///
-    /// Iterates through all zones and renders them based on their active state.
+    /// Visible zones are dispatched and blitted through the encoder, then
+    /// their borders are drawn in one batched pass via
+    /// `ExecutionZoneRenderer::render_visible`.
     ///
     /// # Arguments
     ///
     /// * `encoder` - Command encoder for recording rendering commands
     /// * `output_texture` - Output texture to blit results to
+    /// * `camera` - Camera whose view is used for culling
+    ///
+    /// # Returns
+    ///
+    /// Number of zones rendered
     ///
     /// # Integration Note
     ///
@@ -214,8 +227,96 @@ impl Compositor {
     /// when calling Renderer::render() to enable execution zone rendering.
     /// The compositor renders after the main scene (compilation border) and before
     /// the final queue.submit().
-    pub fn render(&mut self, encoder: &mut CommandEncoder, output_texture: &wgpu::Texture) {
-        self.zone_renderer.render(encoder, output_texture);
+    pub fn render(
+        &mut self,
+        encoder: &mut CommandEncoder,
+        output_texture: &wgpu::Texture,
+        camera: &Camera,
+    ) -> usize {
+        let view = output_texture.create_view(&wgpu::TextureViewDescriptor::default());
+        if let Some(color) = self.clear_color {
+            encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Compositor Clear Pass"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: &view,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(color),
+                        store: wgpu::StoreOp::Store,
+                    },
+                })],
+                depth_stencil_attachment: None,
+                timestamp_writes: None,
+                occlusion_query_set: None,
+            });
+        }
+
+        let viewport = (
+            output_texture.width() as f32,
+            output_texture.height() as f32,
+        );
+        self.zone_renderer
+            .dispatch_visible(camera, viewport, encoder, output_texture);
+        self.zone_renderer
+            .set_target(output_texture.format(), viewport);
+
+        let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Compositor Zone Pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: &view,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Load,
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+        self.zone_renderer
+            .render_visible(&self.execution_zones, camera, &mut pass)
+    }
+
+    /// Set the color the output is cleared to before zones are drawn
+    ///
+    /// # Arguments
+    ///
+    /// * `color` - Clear color, or `None` to draw over existing content
+    pub fn set_clear_color(&mut self, color: Option<wgpu::Color>) {
+        self.clear_color = color;
+    }
+
+    /// Get the configured clear color
+    ///
+    /// # Returns
+    ///
+    /// The clear color, or `None` if the output is not cleared
+    pub fn clear_color(&self) -> Option<wgpu::Color> {
+        self.clear_color
+    }
+
+    /// Set how an execution zone is composited onto the map
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - Index of the zone in `execution_zones()`
+    /// * `mode` - Blend mode to apply
+    ///
+    /// # Returns
+    ///
+    /// `false` if no zone exists at `index`
+    pub fn set_zone_blend_mode(&mut self, index: usize, mode: BlendMode) -> bool {
+        let Some(zone) = self.execution_zones.get_mut(index) else {
+            return false;
+        };
+        zone.set_blend_mode(mode);
+
+        // Keep the renderer's copy in sync
+        if let Some(zone) = self.zone_renderer.zones_mut().get_mut(index) {
+            zone.set_blend_mode(mode);
+        }
+        true
     }
 
     /// Get reference to the WebGPU device
@@ -313,6 +414,7 @@ impl Compositor {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::tests::gpu::test_device;
 
     /// Create a mock WebGPU device for testing
     ///
@@ -335,6 +437,122 @@ mod tests {
         panic!("Mock queue creation not implemented - requires wgpu testing framework");
     }
 
+    #[test]
+    fn test_alpha_blend_zone_over_clear_color() {
+        let Some((device, queue)) = test_device() else {
+            return;
+        };
+
+        // 16x16 zone of red at 50% alpha
+        let zone_texture = Arc::new(device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Blend Test Zone"),
+            size: wgpu::Extent3d {
+                width: 16,
+                height: 16,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::Rgba8Unorm,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING
+                | wgpu::TextureUsages::COPY_DST
+                | wgpu::TextureUsages::COPY_SRC,
+            view_formats: &[],
+        }));
+        let red_half_alpha: Vec<u8> = [255u8, 0, 0, 128].repeat(16 * 16);
+        queue.write_texture(
+            wgpu::ImageCopyTexture {
+                texture: &zone_texture,
+                mip_level: 0,
+                origin: wgpu::Origin3d::ZERO,
+                aspect: wgpu::TextureAspect::All,
+            },
+            &red_half_alpha,
+            wgpu::ImageDataLayout {
+                offset: 0,
+                bytes_per_row: Some(16 * 4),
+                rows_per_image: Some(16),
+            },
+            wgpu::Extent3d {
+                width: 16,
+                height: 16,
+                depth_or_array_layers: 1,
+            },
+        );
+
+        let mut zone = ExecutionZone::new(
+            Vec2::new(0.0, 0.0),
+            "blend.wgsl".to_string(),
+            b"@compute @workgroup_size(1) fn main() {}".to_vec(),
+        );
+        zone.compile().unwrap();
+        zone.set_texture(zone_texture);
+
+        let mut compositor = Compositor::new(device.clone(), queue.clone());
+        compositor.add_execution_zone(zone);
+        assert!(compositor.set_zone_blend_mode(0, BlendMode::AlphaBlend));
+        compositor.set_clear_color(Some(wgpu::Color::BLUE));
+
+        // 64 pixels wide keeps the readback rows 256-byte aligned
+        let output = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Blend Test Output"),
+            size: wgpu::Extent3d {
+                width: 64,
+                height: 64,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::Rgba8Unorm,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+            view_formats: &[],
+        });
+        let readback = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Blend Test Readback"),
+            size: 64 * 64 * 4,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("Blend Test Encoder"),
+        });
+        compositor.render(&mut encoder, &output, &Camera::new(32.0, 32.0, 1.0));
+        encoder.copy_texture_to_buffer(
+            output.as_image_copy(),
+            wgpu::ImageCopyBuffer {
+                buffer: &readback,
+                layout: wgpu::ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: Some(64 * 4),
+                    rows_per_image: Some(64),
+                },
+            },
+            output.size(),
+        );
+        queue.submit(Some(encoder.finish()));
+
+        let slice = readback.slice(..);
+        slice.map_async(wgpu::MapMode::Read, |_| {});
+        device.poll(wgpu::Maintain::Wait);
+        let pixels = slice.get_mapped_range();
+
+        let pixel = |x: usize, y: usize| {
+            let i = (y * 64 + x) * 4;
+            [pixels[i], pixels[i + 1], pixels[i + 2], pixels[i + 3]]
+        };
+        let close = |a: [u8; 4], b: [u8; 4]| a.iter().zip(b).all(|(&a, b)| a.abs_diff(b) <= 2);
+
+        // Inside the zone: red over blue at 50%
+        let blended = pixel(8, 8);
+        assert!(close(blended, [128, 0, 127, 255]), "got {:?}", blended);
+        // Outside the zone: clear color only
+        let background = pixel(40, 40);
+        assert!(close(background, [0, 0, 255, 255]), "got {:?}", background);
+    }
+
     #[test]
     #[ignore = "Requires actual WebGPU device"]
     fn test_compositor_creation() {
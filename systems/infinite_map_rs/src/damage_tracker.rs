@@ -81,6 +81,92 @@ impl DirtyRect {
     pub fn area(&self) -> u32 {
         self.width() * self.height()
     }
+
+    /// Byte layout of this rect inside a tightly packed full-texture buffer
+    pub fn upload_layout(&self, texture_width: u32, bytes_per_pixel: u32) -> UploadLayout {
+        let bytes_per_row = texture_width * bytes_per_pixel;
+        UploadLayout {
+            offset: self.y1 as u64 * bytes_per_row as u64 + (self.x1 * bytes_per_pixel) as u64,
+            bytes_per_row,
+            row_bytes: self.width() * bytes_per_pixel,
+            rows: self.height(),
+        }
+    }
+}
+
+/// Where a dirty rect's texels live in a full-texture source buffer
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UploadLayout {
+    /// Byte offset of the rect's top-left texel
+    pub offset: u64,
+    /// Stride between source rows (full texture width)
+    pub bytes_per_row: u32,
+    /// Bytes covered by the rect in each row
+    pub row_bytes: u32,
+    /// Number of rows in the rect
+    pub rows: u32,
+}
+
+impl UploadLayout {
+    /// Layout for `Queue::write_texture` when passing the full source buffer
+    pub fn image_data_layout(&self) -> wgpu::ImageDataLayout {
+        wgpu::ImageDataLayout {
+            offset: self.offset,
+            bytes_per_row: Some(self.bytes_per_row),
+            rows_per_image: Some(self.rows),
+        }
+    }
+}
+
+/// Bytes per texel of an uncompressed color format
+///
+/// Returns `None` for block-compressed and combined depth-stencil formats,
+/// which cannot be updated per texel.
+pub fn bytes_per_pixel(format: wgpu::TextureFormat) -> Option<u32> {
+    if format.block_dimensions() != (1, 1) {
+        return None;
+    }
+    format.block_copy_size(None)
+}
+
+/// Area of the union of `rects`, counting overlapping cells once
+///
+/// Sweeps left to right over the rect edges; in each vertical strip the
+/// covered rows are the merged y-intervals of the rects spanning it.
+pub fn union_area(rects: &[DirtyRect]) -> u64 {
+    let mut edges: Vec<u32> = rects
+        .iter()
+        .filter(|rect| rect.area() > 0)
+        .flat_map(|rect| [rect.x1, rect.x2])
+        .collect();
+    edges.sort_unstable();
+    edges.dedup();
+
+    let mut area = 0u64;
+    let mut spans = Vec::new();
+    for strip in edges.windows(2) {
+        let (left, right) = (strip[0], strip[1]);
+        spans.clear();
+        spans.extend(
+            rects
+                .iter()
+                .filter(|rect| rect.x1 <= left && rect.x2 >= right && rect.y2 > rect.y1)
+                .map(|rect| (rect.y1, rect.y2)),
+        );
+        spans.sort_unstable();
+
+        let mut covered = 0u64;
+        let mut reach = 0u32;
+        for &(top, bottom) in &spans {
+            let top = top.max(reach);
+            if bottom > top {
+                covered += (bottom - top) as u64;
+                reach = bottom;
+            }
+        }
+        area += covered * (right - left) as u64;
+    }
+    area
 }
 
 /// Tracks dirty cells in a terminal grid and computes dirty rectangles
@@ -93,6 +179,8 @@ pub struct DamageTracker {
     dirty_cells: Vec<u64>,
     /// Flag indicating if any cells are dirty
     has_damage: bool,
+    /// Flag indicating the whole grid must be redrawn
+    full_redraw: bool,
 }
 
 impl DamageTracker {
@@ -108,6 +196,7 @@ impl DamageTracker {
             rows,
             dirty_cells: vec![0u64; num_words],
             has_damage: false,
+            full_redraw: false,
         }
     }
 
@@ -144,6 +233,27 @@ impl DamageTracker {
         }
     }
 
+    /// Mark the whole grid dirty as a single full-redraw rect
+    ///
+    /// Used after resizes or pipeline hot-swaps. Resizes the tracker if the
+    /// dimensions changed and replaces any accumulated incremental damage.
+    pub fn mark_all(&mut self, width: u32, height: u32) {
+        if width != self.cols || height != self.rows {
+            *self = Self::new(width, height);
+        }
+
+        for word in &mut self.dirty_cells {
+            *word = u64::MAX;
+        }
+        self.has_damage = width > 0 && height > 0;
+        self.full_redraw = self.has_damage;
+    }
+
+    /// Check if the whole grid is pending a redraw
+    pub fn is_full_redraw(&self) -> bool {
+        self.full_redraw
+    }
+
     /// Check if a specific cell is dirty
     pub fn is_dirty(&self, col: u32, row: u32) -> bool {
         if col >= self.cols || row >= self.rows {
@@ -168,6 +278,7 @@ impl DamageTracker {
             *word = 0;
         }
         self.has_damage = false;
+        self.full_redraw = false;
     }
 
     /// Compute dirty rectangles using a rect expansion algorithm
@@ -181,6 +292,10 @@ impl DamageTracker {
             return Vec::new();
         }
 
+        if self.full_redraw {
+            return vec![DirtyRect::new(0, 0, self.cols, self.rows)];
+        }
+
         // Collect all dirty cells
         let dirty_cells: Vec<(u32, u32)> = self.collect_dirty_cells();
 
@@ -204,6 +319,25 @@ impl DamageTracker {
         rects
     }
 
+    /// Area covered by the coalesced dirty rectangles
+    ///
+    /// This is the number of cells an incremental upload covers, not the
+    /// number of dirty cells: marks are merged into bounding boxes first,
+    /// which may include clean cells between nearby marks.
+    pub fn total_dirty_area(&self) -> u64 {
+        union_area(&self.compute_dirty_rects())
+    }
+
+    /// Fraction of a texture covered by dirty rectangles (0.0 to 1.0)
+    ///
+    /// Renderers can switch to a full redraw when this exceeds a threshold.
+    pub fn dirty_fraction(&self, texture_area: u64) -> f32 {
+        if texture_area == 0 {
+            return 0.0;
+        }
+        (self.total_dirty_area() as f64 / texture_area as f64).min(1.0) as f32
+    }
+
     /// Collect all dirty cell coordinates
     fn collect_dirty_cells(&self) -> Vec<(u32, u32)> {
         let mut cells = Vec::new();
@@ -413,6 +547,120 @@ mod tests {
         assert!(!tracker.is_dirty(5, 10));
     }
 
+    #[test]
+    fn test_mark_all_replaces_incremental_rects() {
+        let mut tracker = DamageTracker::new(80, 24);
+
+        tracker.mark_dirty(3, 3);
+        tracker.mark_rect_dirty(40, 10, 44, 12);
+        assert!(!tracker.is_full_redraw());
+        assert_eq!(tracker.compute_dirty_rects().len(), 2);
+
+        tracker.mark_all(80, 24);
+
+        assert!(tracker.is_full_redraw());
+        assert_eq!(
+            tracker.compute_dirty_rects(),
+            vec![DirtyRect::new(0, 0, 80, 24)]
+        );
+        assert!(tracker.is_dirty(79, 23));
+
+        tracker.clear();
+        assert!(!tracker.is_full_redraw());
+        assert!(tracker.compute_dirty_rects().is_empty());
+    }
+
+    #[test]
+    fn test_mark_all_resizes_tracker() {
+        let mut tracker = DamageTracker::new(80, 24);
+        tracker.mark_all(120, 40);
+
+        assert_eq!((tracker.cols(), tracker.rows()), (120, 40));
+        assert_eq!(
+            tracker.compute_dirty_rects(),
+            vec![DirtyRect::new(0, 0, 120, 40)]
+        );
+    }
+
+    #[test]
+    fn test_total_dirty_area_sums_coalesced_rects() {
+        let mut tracker = DamageTracker::new(80, 24);
+
+        // Two 4x4 rects sharing a 2x4 column, plus a disjoint 2x2 rect
+        tracker.mark_rect_dirty(0, 0, 4, 4);
+        tracker.mark_rect_dirty(2, 0, 6, 4);
+        tracker.mark_rect_dirty(40, 10, 42, 12);
+
+        // Overlapping marks merge into one 6x4 box: 6x4 + 2x2, not 16 + 16 + 4
+        assert_eq!(tracker.total_dirty_area(), 24 + 4);
+        assert!((tracker.dirty_fraction(80 * 24) - 28.0 / 1920.0).abs() < 1e-6);
+
+        // Diagonal neighbours merge into a 2x2 box that includes two clean cells
+        let mut diagonal = DamageTracker::new(80, 24);
+        diagonal.mark_dirty(0, 0);
+        diagonal.mark_dirty(1, 1);
+        assert_eq!(diagonal.total_dirty_area(), 4);
+
+        tracker.mark_all(80, 24);
+        assert_eq!(tracker.total_dirty_area(), 80 * 24);
+        assert_eq!(tracker.dirty_fraction(80 * 24), 1.0);
+        assert_eq!(tracker.dirty_fraction(0), 0.0);
+    }
+
+    #[test]
+    fn test_union_area_counts_overlaps_once() {
+        assert_eq!(union_area(&[]), 0);
+
+        // Two 4x4 squares overlapping in a 2x2 corner
+        let squares = [DirtyRect::new(0, 0, 4, 4), DirtyRect::new(2, 2, 6, 6)];
+        assert_eq!(union_area(&squares), 16 + 16 - 4);
+
+        // A rect nested inside another adds nothing; duplicates count once
+        let nested = [
+            DirtyRect::new(0, 0, 10, 10),
+            DirtyRect::new(2, 3, 5, 7),
+            DirtyRect::new(0, 0, 10, 10),
+        ];
+        assert_eq!(union_area(&nested), 100);
+
+        // A cross: 9x3 and 3x9 sharing the 3x3 center, plus a disjoint
+        // rect and an empty one
+        let cross = [
+            DirtyRect::new(0, 3, 9, 6),
+            DirtyRect::new(3, 0, 6, 9),
+            DirtyRect::new(20, 20, 22, 21),
+            DirtyRect::new(30, 30, 30, 40),
+        ];
+        assert_eq!(union_area(&cross), 27 + 27 - 9 + 2);
+    }
+
+    #[test]
+    fn test_upload_layout_for_r8_and_rgba16float() {
+        let rect = DirtyRect::new(10, 5, 30, 8);
+
+        let r8 = bytes_per_pixel(wgpu::TextureFormat::R8Unorm).unwrap();
+        assert_eq!(r8, 1);
+        let layout = rect.upload_layout(100, r8);
+        assert_eq!(layout.offset, 5 * 100 + 10);
+        assert_eq!(layout.bytes_per_row, 100);
+        assert_eq!(layout.row_bytes, 20);
+        assert_eq!(layout.rows, 3);
+
+        let rgba16f = bytes_per_pixel(wgpu::TextureFormat::Rgba16Float).unwrap();
+        assert_eq!(rgba16f, 8);
+        let layout = rect.upload_layout(100, rgba16f);
+        assert_eq!(layout.offset, (5 * 100 + 10) * 8);
+        assert_eq!(layout.bytes_per_row, 800);
+        assert_eq!(layout.row_bytes, 160);
+        assert_eq!(layout.image_data_layout().bytes_per_row, Some(800));
+
+        assert_eq!(bytes_per_pixel(wgpu::TextureFormat::Bc1RgbaUnorm), None);
+        assert_eq!(
+            bytes_per_pixel(wgpu::TextureFormat::Depth24PlusStencil8),
+            None
+        );
+    }
+
     #[test]
     fn test_compute_dirty_rects_single_cluster() {
         let mut tracker = DamageTracker::new(80, 24);
@@ -1,4 +1,8 @@
 use crate::cortex::Neuromodulator;
+use parking_lot::RwLock;
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::sync::Arc;
 use std::time::{Duration, Instant};
 
 /// Metabolic State - The biochemical state of the cognitive system
@@ -47,6 +51,63 @@ impl MetabolicState {
             "BASELINE"
         }
     }
+
+    /// Get a numeric code for the state name (for FFI/WASM consumers)
+    ///
+    /// BASELINE = 0, FOCUSED = 1, FLOW = 2, THROTTLED = 3, PANIC = 4
+    pub fn get_state_code(&self) -> i32 {
+        match self.get_state_name() {
+            "FOCUSED" => 1,
+            "FLOW" => 2,
+            "THROTTLED" => 3,
+            "PANIC" => 4,
+            _ => 0,
+        }
+    }
+}
+
+/// How far the PAS weights may sum from 1.0
+pub const PAS_WEIGHT_TOLERANCE: f32 = 1e-3;
+
+/// Share of each component in the combined PAS score
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PasWeights {
+    pub p: f32,
+    pub a: f32,
+    pub s: f32,
+}
+
+impl Default for PasWeights {
+    fn default() -> Self {
+        Self {
+            p: 0.4,
+            a: 0.4,
+            s: 0.2,
+        }
+    }
+}
+
+impl PasWeights {
+    /// Weights that are non-negative and sum to 1.0
+    pub fn new(p: f32, a: f32, s: f32) -> Result<Self, String> {
+        let weights = Self { p, a, s };
+        weights.validate()?;
+        Ok(weights)
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if [self.p, self.a, self.s]
+            .iter()
+            .any(|w| !w.is_finite() || *w < 0.0)
+        {
+            return Err(format!("PAS weights must be non-negative: {:?}", self));
+        }
+        let sum = self.p + self.a + self.s;
+        if (sum - 1.0).abs() > PAS_WEIGHT_TOLERANCE {
+            return Err(format!("PAS weights must sum to 1.0, got {}", sum));
+        }
+        Ok(())
+    }
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -54,35 +115,197 @@ pub struct PasScore {
     pub p: f32, // Performance (0.0 - 1.0)
     pub a: f32, // Aesthetic (0.0 - 1.0)
     pub s: f32, // System (0.0 - 1.0)
+    pub weights: PasWeights,
 }
 
 impl PasScore {
     pub fn calculate(&self) -> f32 {
-        (self.p * 0.4) + (self.a * 0.4) + (self.s * 0.2)
+        (self.p * self.weights.p) + (self.a * self.weights.a) + (self.s * self.weights.s)
     }
 
     pub fn get_color(&self) -> [f32; 4] {
-        let score = self.calculate();
-        if score > 0.8 {
-            [0.0, 1.0, 0.5, 1.0] // Crystalline Green
-        } else if score > 0.5 {
-            [1.0, 0.8, 0.0, 1.0] // Amber Caution
-        } else {
-            [1.0, 0.2, 0.2, 1.0] // Fracture Red
+        match self.get_state_name() {
+            "OPTIMAL" => [0.0, 1.0, 0.5, 1.0], // Crystalline Green
+            "CAUTION" => [1.0, 0.8, 0.0, 1.0], // Amber Caution
+            _ => [1.0, 0.2, 0.2, 1.0],         // Fracture Red
         }
     }
+
+    /// Name of the color band the score falls in
+    pub fn get_state_name(&self) -> &'static str {
+        pas_state_name(self.calculate())
+    }
 }
 
+/// Scores above this are OPTIMAL (Crystalline Green)
+pub const PAS_OPTIMAL_THRESHOLD: f32 = 0.8;
+
+/// Scores above this are CAUTION (Amber Caution); the rest are CRITICAL
+/// (Fracture Red)
+pub const PAS_CAUTION_THRESHOLD: f32 = 0.5;
+
+/// Default margin a score must clear past a band edge to change state
+pub const DEFAULT_PAS_HYSTERESIS: f32 = 0.02;
+
+fn pas_state_name(score: f32) -> &'static str {
+    if score > PAS_OPTIMAL_THRESHOLD {
+        "OPTIMAL"
+    } else if score > PAS_CAUTION_THRESHOLD {
+        "CAUTION"
+    } else {
+        "CRITICAL"
+    }
+}
+
+fn pas_state_rank(name: &str) -> u8 {
+    match name {
+        "OPTIMAL" => 2,
+        "CAUTION" => 1,
+        _ => 0,
+    }
+}
+
+/// Called with the score and the new state name when PAS changes band
+pub type PasThresholdCallback = Box<dyn FnMut(PasScore, &'static str)>;
+
+/// Frame time the Performance score aims for (60 FPS)
+pub const TARGET_FRAME_TIME: Duration = Duration::from_nanos(1_000_000_000 / 60);
+
+/// Frames longer than this multiple of the target count as jank
+pub const JANK_FACTOR: u32 = 2;
+
+/// Upper bounds of the histogram buckets, as multiples of the target frame
+/// time; the last bucket holds everything slower
+pub const FRAME_BUCKET_MULTIPLES: [f32; 5] = [0.5, 1.0, 2.0, 3.0, 6.0];
+
+/// Counts of frames by duration, bucketed against the target frame time
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FrameTimeHistogram {
+    counts: [u64; FRAME_BUCKET_MULTIPLES.len() + 1],
+}
+
+impl FrameTimeHistogram {
+    /// Bucket a frame falls into (frames on a bound go to the lower bucket)
+    pub fn bucket_for(frame_time: Duration) -> usize {
+        let ratio = frame_time.as_secs_f32() / TARGET_FRAME_TIME.as_secs_f32();
+        FRAME_BUCKET_MULTIPLES
+            .iter()
+            .position(|&bound| ratio <= bound)
+            .unwrap_or(FRAME_BUCKET_MULTIPLES.len())
+    }
+
+    pub fn record(&mut self, frame_time: Duration) {
+        self.counts[Self::bucket_for(frame_time)] += 1;
+    }
+
+    /// Frame counts per bucket, fastest first
+    pub fn counts(&self) -> &[u64] {
+        &self.counts
+    }
+
+    pub fn total(&self) -> u64 {
+        self.counts.iter().sum()
+    }
+}
+
+/// A signal feeding the Aesthetic (A) component of PAS
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AestheticSignal {
+    /// Visual disorder (0.0 calm - 1.0 chaotic)
+    Entropy,
+    /// How well on-screen colors work together (0.0 clashing - 1.0 harmonious)
+    ColorHarmony,
+    /// Amount of on-screen movement (0.0 still - 1.0 frantic)
+    Motion,
+}
+
+impl AestheticSignal {
+    /// Map a raw signal value (0.0 - 1.0) to its aesthetic quality,
+    /// where 1.0 is best
+    pub fn quality(&self, value: f32) -> f32 {
+        let value = value.clamp(0.0, 1.0);
+        match self {
+            AestheticSignal::ColorHarmony => value,
+            AestheticSignal::Entropy | AestheticSignal::Motion => 1.0 - value,
+        }
+    }
+}
+
+/// PAS samples kept for trend display
+pub const PAS_HISTORY_LEN: usize = 120;
+
+/// Minimum spacing between PAS history samples (120 samples ≈ 1 minute)
+pub const PAS_SAMPLE_INTERVAL: Duration = Duration::from_millis(500);
+
+/// PAS change per second below which the trend counts as stable
+pub const TREND_THRESHOLD: f32 = 0.001;
+
+/// Direction the PAS score is heading over the history window
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Trend {
+    Improving,
+    Stable,
+    Degrading,
+}
+
+/// Point-in-time copy of the overlay state, safe to share across threads
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DiagnosticSnapshot {
+    /// Combined PAS score (0.0 - 1.0)
+    pub pas_score: f32,
+    /// Metabolic state code (see `MetabolicState::get_state_code`)
+    pub metabolic_state_code: i32,
+    /// Frames slower than `JANK_FACTOR` × the target frame time
+    pub jank_count: u64,
+    /// Distribution of every recorded frame time
+    pub frame_histogram: FrameTimeHistogram,
+}
+
+impl Default for DiagnosticSnapshot {
+    fn default() -> Self {
+        Self {
+            pas_score: 1.0,
+            metabolic_state_code: 0,
+            jank_count: 0,
+            frame_histogram: FrameTimeHistogram::default(),
+        }
+    }
+}
+
+/// VRAM budget assumed when the GPU's real memory size is unknown
+pub const DEFAULT_VRAM_LIMIT_BYTES: u64 = 4 * 1024 * 1024 * 1024;
+
+/// Shared handle to the latest published diagnostic snapshot
+pub type SharedDiagnosticSnapshot = Arc<RwLock<DiagnosticSnapshot>>;
+
 pub struct DiagnosticOverlay {
     pub enabled: bool,
     pub expanded: bool,
     pub current_pas: PasScore,
     pub last_update: Instant,
     pub frame_times: Vec<Duration>,
+    /// Score Performance from the p95 frame time instead of the mean, so
+    /// occasional hitches lower it
+    pub performance_from_p95: bool,
+    /// Every frame since startup (or `reset_frame_stats`), bucketed
+    pub frame_histogram: FrameTimeHistogram,
+    /// Frames slower than `JANK_FACTOR` × the target frame time
+    pub jank_count: u64,
     pub vram_usage_bytes: u64,
     pub vram_limit_bytes: u64,
     /// Phase 47: Metabolic state from RISC-V executor
     pub metabolic_state: MetabolicState,
+    /// Recent PAS samples, oldest first
+    pas_history: Vec<(Instant, PasScore)>,
+    /// Blend weight of each aesthetic signal (1.0 unless configured)
+    aesthetic_weights: HashMap<AestheticSignal, f32>,
+    /// Latest snapshot published for cognitive entities
+    shared_snapshot: SharedDiagnosticSnapshot,
+    /// How far past a band edge the score must go before the state changes
+    pub pas_hysteresis: f32,
+    /// Band last reported to the threshold callbacks
+    pas_state: &'static str,
+    threshold_callbacks: Vec<PasThresholdCallback>,
 }
 
 impl DiagnosticOverlay {
@@ -94,30 +317,81 @@ impl DiagnosticOverlay {
                 p: 1.0,
                 a: 1.0,
                 s: 1.0,
+                weights: PasWeights::default(),
             },
             last_update: Instant::now(),
             frame_times: Vec::with_capacity(60),
+            performance_from_p95: false,
+            frame_histogram: FrameTimeHistogram::default(),
+            jank_count: 0,
             vram_usage_bytes: 0,
-            vram_limit_bytes: 4 * 1024 * 1024 * 1024, // Default 4GB
+            vram_limit_bytes: DEFAULT_VRAM_LIMIT_BYTES,
             metabolic_state: MetabolicState::default(),
+            pas_history: Vec::with_capacity(PAS_HISTORY_LEN),
+            aesthetic_weights: HashMap::new(),
+            shared_snapshot: Arc::new(RwLock::new(DiagnosticSnapshot::default())),
+            pas_hysteresis: DEFAULT_PAS_HYSTERESIS,
+            pas_state: "OPTIMAL",
+            threshold_callbacks: Vec::new(),
         }
     }
 
     pub fn update_performance(&mut self, frame_time: Duration) {
+        self.frame_histogram.record(frame_time);
+        if frame_time > TARGET_FRAME_TIME * JANK_FACTOR {
+            self.jank_count += 1;
+        }
+
         self.frame_times.push(frame_time);
         if self.frame_times.len() > 60 {
             self.frame_times.remove(0);
         }
 
-        let avg_frame_time =
-            self.frame_times.iter().sum::<Duration>().as_secs_f32() / self.frame_times.len() as f32;
-        let target_frame_time = 1.0 / 60.0;
+        // Frame time the Performance score is based on: p95 or mean of the
+        // recent window
+        let scored_frame_time = if self.performance_from_p95 {
+            self.frame_time_percentile(95.0).as_secs_f32()
+        } else {
+            self.frame_times.iter().sum::<Duration>().as_secs_f32() / self.frame_times.len() as f32
+        };
+        let target_frame_time = TARGET_FRAME_TIME.as_secs_f32();
 
-        if avg_frame_time <= target_frame_time {
+        if scored_frame_time <= target_frame_time {
             self.current_pas.p = 1.0;
         } else {
-            self.current_pas.p = (target_frame_time / avg_frame_time).max(0.0).min(1.0);
+            self.current_pas.p = (target_frame_time / scored_frame_time).max(0.0).min(1.0);
+        }
+        self.check_pas_thresholds();
+    }
+
+    /// Frame time at percentile `p` (0 - 100) of the recent frame window,
+    /// by nearest rank; zero before the first frame
+    pub fn frame_time_percentile(&self, p: f32) -> Duration {
+        if self.frame_times.is_empty() {
+            return Duration::ZERO;
         }
+        let mut sorted = self.frame_times.clone();
+        sorted.sort_unstable();
+
+        let rank = (p.clamp(0.0, 100.0) / 100.0 * sorted.len() as f32).ceil() as usize;
+        sorted[rank.clamp(1, sorted.len()) - 1]
+    }
+
+    /// Clear the frame histogram and jank counter
+    pub fn reset_frame_stats(&mut self) {
+        self.frame_histogram = FrameTimeHistogram::default();
+        self.jank_count = 0;
+    }
+
+    /// Set the VRAM budget System health is measured against
+    ///
+    /// A limit of 0 restores `DEFAULT_VRAM_LIMIT_BYTES`.
+    pub fn set_vram_limit(&mut self, bytes: u64) {
+        self.vram_limit_bytes = if bytes == 0 {
+            DEFAULT_VRAM_LIMIT_BYTES
+        } else {
+            bytes
+        };
     }
 
     pub fn update_system_health(&mut self, vram_usage: u64) {
@@ -125,10 +399,98 @@ impl DiagnosticOverlay {
         self.current_pas.s = (1.0 - (vram_usage as f32 / self.vram_limit_bytes as f32))
             .max(0.0)
             .min(1.0);
+        self.check_pas_thresholds();
     }
 
+    /// Set the A score from entropy alone (`1 - entropy`)
+    ///
+    /// A lone signal's weighted mean is its own quality, so the entropy
+    /// weight is not consulted; this still applies when it is 0.
     pub fn set_aesthetic_entropy(&mut self, entropy: f32) {
-        self.current_pas.a = (1.0 - entropy).max(0.0).min(1.0);
+        self.current_pas.a = AestheticSignal::Entropy.quality(entropy);
+        self.check_pas_thresholds();
+    }
+
+    /// Set how much a signal counts in `set_aesthetic_components`
+    pub fn set_aesthetic_weight(&mut self, signal: AestheticSignal, weight: f32) {
+        self.aesthetic_weights.insert(signal, weight.max(0.0));
+    }
+
+    pub fn aesthetic_weight(&self, signal: AestheticSignal) -> f32 {
+        self.aesthetic_weights.get(&signal).copied().unwrap_or(1.0)
+    }
+
+    /// Set the A score to the weighted mean quality of the given signals
+    ///
+    /// Leaves A unchanged if no component has a positive weight.
+    pub fn set_aesthetic_components(&mut self, components: &[(AestheticSignal, f32)]) {
+        let (sum, total_weight) =
+            components
+                .iter()
+                .fold((0.0, 0.0), |(sum, total_weight), &(signal, value)| {
+                    let weight = self.aesthetic_weight(signal);
+                    (sum + weight * signal.quality(value), total_weight + weight)
+                });
+        if total_weight > 0.0 {
+            self.current_pas.a = (sum / total_weight).clamp(0.0, 1.0);
+        }
+        self.check_pas_thresholds();
+    }
+
+    /// Reweight the PAS components, e.g. to favor System health on a
+    /// headless renderer; invalid weights are rejected and the old ones kept
+    pub fn set_pas_weights(&mut self, weights: PasWeights) -> Result<(), String> {
+        weights.validate()?;
+        self.current_pas.weights = weights;
+        self.check_pas_thresholds();
+        Ok(())
+    }
+
+    /// Call `callback` whenever the PAS state (OPTIMAL, CAUTION, CRITICAL)
+    /// changes, e.g. to page an operator on entering Fracture Red
+    ///
+    /// The score must move `pas_hysteresis` past a band edge before the
+    /// state changes, so a score hovering on the edge fires once.
+    pub fn on_threshold_cross(&mut self, callback: PasThresholdCallback) {
+        self.threshold_callbacks.push(callback);
+    }
+
+    /// Current PAS state, as last reported to threshold callbacks
+    pub fn pas_state(&self) -> &'static str {
+        self.pas_state
+    }
+
+    /// Re-evaluate the PAS state and notify callbacks on a change
+    ///
+    /// The overlay's own update methods call this; call it after changing
+    /// `current_pas` directly.
+    pub fn check_pas_thresholds(&mut self) {
+        let score = self.current_pas.calculate();
+        let raw = pas_state_name(score);
+        if raw == self.pas_state {
+            return;
+        }
+
+        // Require the score to clear the band edge by the hysteresis margin
+        let rising = pas_state_rank(raw) > pas_state_rank(self.pas_state);
+        let margin = if rising {
+            -self.pas_hysteresis
+        } else {
+            self.pas_hysteresis
+        };
+        let state = pas_state_name(score + margin);
+        if state == self.pas_state {
+            return;
+        }
+
+        self.pas_state = state;
+        for callback in &mut self.threshold_callbacks {
+            callback(self.current_pas, state);
+        }
+    }
+
+    pub fn pas_weights(&self) -> PasWeights {
+        self.current_pas.weights
     }
 
     pub fn toggle_expansion(&mut self) -> bool {
@@ -156,6 +518,7 @@ impl DiagnosticOverlay {
         let blended_health = (tool_health_score * weight) + (vram_health * (1.0 - weight));
 
         self.current_pas.s = blended_health.max(0.0).min(1.0);
+        self.check_pas_thresholds();
 
         log::debug!(
             "🔧 Diagnostic: Updated System health - Tool: {:.2}, VRAM: {:.2}, Blended: {:.2}",
@@ -165,6 +528,65 @@ impl DiagnosticOverlay {
         );
     }
 
+    /// Record the current PAS score in the history
+    pub fn sample_pas(&mut self) {
+        self.sample_pas_at(Instant::now());
+    }
+
+    /// Record the current PAS score as of `now`, at most once per
+    /// `PAS_SAMPLE_INTERVAL`
+    pub fn sample_pas_at(&mut self, now: Instant) {
+        if let Some((last, _)) = self.pas_history.last() {
+            if now.saturating_duration_since(*last) < PAS_SAMPLE_INTERVAL {
+                return;
+            }
+        }
+        if self.pas_history.len() >= PAS_HISTORY_LEN {
+            self.pas_history.remove(0);
+        }
+        self.pas_history.push((now, self.current_pas));
+    }
+
+    /// Recent PAS samples, oldest first
+    pub fn pas_history(&self) -> &[(Instant, PasScore)] {
+        &self.pas_history
+    }
+
+    /// Trend of the combined PAS score, from a least-squares fit over the
+    /// history window
+    pub fn trend(&self) -> Trend {
+        let Some((start, _)) = self.pas_history.first() else {
+            return Trend::Stable;
+        };
+        let points: Vec<(f32, f32)> = self
+            .pas_history
+            .iter()
+            .map(|(at, pas)| (at.duration_since(*start).as_secs_f32(), pas.calculate()))
+            .collect();
+
+        let n = points.len() as f32;
+        let mean_t = points.iter().map(|(t, _)| t).sum::<f32>() / n;
+        let mean_v = points.iter().map(|(_, v)| v).sum::<f32>() / n;
+        let (cov, var) = points.iter().fold((0.0, 0.0), |(cov, var), (t, v)| {
+            (
+                cov + (t - mean_t) * (v - mean_v),
+                var + (t - mean_t) * (t - mean_t),
+            )
+        });
+        if var <= 0.0 {
+            return Trend::Stable;
+        }
+
+        let slope = cov / var;
+        if slope > TREND_THRESHOLD {
+            Trend::Improving
+        } else if slope < -TREND_THRESHOLD {
+            Trend::Degrading
+        } else {
+            Trend::Stable
+        }
+    }
+
     /// Phase 47: Update metabolic state from RISC-V executor
     pub fn update_metabolic_state(&mut self, metabolic: MetabolicState) {
         self.metabolic_state = metabolic;
@@ -174,4 +596,350 @@ impl DiagnosticOverlay {
     pub fn get_metabolic_state(&self) -> MetabolicState {
         self.metabolic_state
     }
+
+    /// Capture the current PAS score and metabolic state
+    pub fn snapshot(&self) -> DiagnosticSnapshot {
+        DiagnosticSnapshot {
+            pas_score: self.current_pas.calculate(),
+            metabolic_state_code: self.metabolic_state.get_state_code(),
+            jank_count: self.jank_count,
+            frame_histogram: self.frame_histogram,
+        }
+    }
+
+    /// Current health as Prometheus text exposition format, for serving on
+    /// a `/metrics` endpoint
+    pub fn export_prometheus(&self) -> String {
+        let gauges: [(&str, &str, f64); 6] = [
+            (
+                "geometry_pas_score",
+                "Combined PAS health score (0-1)",
+                self.current_pas.calculate() as f64,
+            ),
+            (
+                "geometry_pas_performance",
+                "PAS Performance component (0-1)",
+                self.current_pas.p as f64,
+            ),
+            (
+                "geometry_pas_aesthetic",
+                "PAS Aesthetic component (0-1)",
+                self.current_pas.a as f64,
+            ),
+            (
+                "geometry_pas_system",
+                "PAS System component (0-1)",
+                self.current_pas.s as f64,
+            ),
+            (
+                "geometry_vram_bytes",
+                "VRAM in use, in bytes",
+                self.vram_usage_bytes as f64,
+            ),
+            (
+                "geometry_instruction_budget",
+                "RISC-V instruction budget per frame",
+                self.metabolic_state.instruction_budget as f64,
+            ),
+        ];
+
+        let mut out = String::new();
+        for (name, help, value) in gauges {
+            let _ = writeln!(out, "# HELP {} {}", name, help);
+            let _ = writeln!(out, "# TYPE {} gauge", name);
+            let _ = writeln!(out, "{} {}", name, value);
+        }
+        out
+    }
+
+    /// Publish the current snapshot to all shared readers
+    pub fn publish_snapshot(&self) {
+        *self.shared_snapshot.write() = self.snapshot();
+    }
+
+    /// Get a handle to the shared snapshot (updated by `publish_snapshot`)
+    pub fn shared_snapshot(&self) -> SharedDiagnosticSnapshot {
+        self.shared_snapshot.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_system_health_uses_vram_limit() {
+        const GIB: u64 = 1024 * 1024 * 1024;
+        let mut overlay = DiagnosticOverlay::new();
+        assert_eq!(overlay.vram_limit_bytes, DEFAULT_VRAM_LIMIT_BYTES);
+
+        overlay.update_system_health(2 * GIB);
+        assert!((overlay.current_pas.s - 0.5).abs() < 1e-6);
+
+        overlay.set_vram_limit(16 * GIB);
+        overlay.update_system_health(2 * GIB);
+        assert!((overlay.current_pas.s - 0.875).abs() < 1e-6);
+
+        overlay.update_system_health(20 * GIB);
+        assert_eq!(overlay.current_pas.s, 0.0);
+
+        overlay.set_vram_limit(0);
+        assert_eq!(overlay.vram_limit_bytes, DEFAULT_VRAM_LIMIT_BYTES);
+    }
+
+    #[test]
+    fn test_frame_histogram_and_jank_count() {
+        let mut overlay = DiagnosticOverlay::new();
+        let ms = Duration::from_millis;
+        // 50 smooth frames, 5 slightly late, 3 janky, 2 hitches
+        let frames = [(ms(5), 50), (ms(20), 5), (ms(40), 3), (ms(120), 2)];
+        for (frame_time, count) in frames {
+            for _ in 0..count {
+                overlay.update_performance(frame_time);
+            }
+        }
+
+        assert_eq!(overlay.jank_count, 5);
+        assert_eq!(overlay.frame_histogram.counts(), &[50, 0, 5, 3, 0, 2]);
+        assert_eq!(overlay.frame_histogram.total(), 60);
+
+        // Exactly 2× the target is slow but not jank
+        overlay.update_performance(TARGET_FRAME_TIME * JANK_FACTOR);
+        assert_eq!(overlay.jank_count, 5);
+
+        let snapshot = overlay.snapshot();
+        assert_eq!(snapshot.jank_count, 5);
+        assert_eq!(snapshot.frame_histogram.counts(), &[50, 0, 6, 3, 0, 2]);
+
+        overlay.reset_frame_stats();
+        assert_eq!(overlay.snapshot().frame_histogram.total(), 0);
+        assert_eq!(overlay.snapshot().jank_count, 0);
+    }
+
+    #[test]
+    fn test_pas_trend_follows_history() {
+        let mut overlay = DiagnosticOverlay::new();
+        let start = Instant::now();
+        assert_eq!(overlay.trend(), Trend::Stable);
+
+        // System health falls steadily over 20 s
+        for i in 0..40 {
+            overlay.current_pas.s = 1.0 - i as f32 * 0.02;
+            overlay.sample_pas_at(start + PAS_SAMPLE_INTERVAL * i);
+            // Samples closer together than the interval are dropped
+            overlay.sample_pas_at(start + PAS_SAMPLE_INTERVAL * i + Duration::from_millis(1));
+        }
+        assert_eq!(overlay.pas_history().len(), 40);
+        assert_eq!(overlay.trend(), Trend::Degrading);
+
+        // Recovery overtakes the decline, and the ring stays bounded
+        for i in 40..(40 + PAS_HISTORY_LEN as u32) {
+            overlay.current_pas.s = (i - 40) as f32 / PAS_HISTORY_LEN as f32;
+            overlay.sample_pas_at(start + PAS_SAMPLE_INTERVAL * i);
+        }
+        assert_eq!(overlay.pas_history().len(), PAS_HISTORY_LEN);
+        assert_eq!(overlay.trend(), Trend::Improving);
+
+        // Flat scores are stable
+        for i in 0..PAS_HISTORY_LEN as u32 {
+            overlay.sample_pas_at(start + PAS_SAMPLE_INTERVAL * (200 + i));
+        }
+        assert_eq!(overlay.trend(), Trend::Stable);
+    }
+
+    #[test]
+    fn test_aesthetic_components_blend_by_weight() {
+        let mut overlay = DiagnosticOverlay::new();
+        overlay.set_aesthetic_weight(AestheticSignal::Entropy, 1.0);
+        overlay.set_aesthetic_weight(AestheticSignal::ColorHarmony, 3.0);
+
+        // Entropy 0.6 -> quality 0.4; harmony 0.8 -> quality 0.8
+        overlay.set_aesthetic_components(&[
+            (AestheticSignal::Entropy, 0.6),
+            (AestheticSignal::ColorHarmony, 0.8),
+        ]);
+        let expected = (1.0 * 0.4 + 3.0 * 0.8) / 4.0;
+        assert!((overlay.current_pas.a - expected).abs() < 1e-6);
+
+        // The entropy shortcut still maps straight to 1 - entropy
+        overlay.set_aesthetic_entropy(0.25);
+        assert!((overlay.current_pas.a - 0.75).abs() < 1e-6);
+
+        // Zero-weight signals are ignored
+        overlay.set_aesthetic_weight(AestheticSignal::Motion, 0.0);
+        overlay.set_aesthetic_components(&[(AestheticSignal::Motion, 1.0)]);
+        assert!((overlay.current_pas.a - 0.75).abs() < 1e-6);
+
+        // ...but the entropy shortcut works whatever the entropy weight
+        overlay.set_aesthetic_weight(AestheticSignal::Entropy, 0.0);
+        overlay.set_aesthetic_entropy(0.5);
+        assert!((overlay.current_pas.a - 0.5).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_pas_weights_change_score_and_color() {
+        let mut overlay = DiagnosticOverlay::new();
+        overlay.current_pas.p = 1.0;
+        overlay.current_pas.a = 1.0;
+        overlay.current_pas.s = 0.3;
+
+        // Default weights: 0.4 + 0.4 + 0.06
+        assert_eq!(overlay.pas_weights(), PasWeights::default());
+        assert!((overlay.current_pas.calculate() - 0.86).abs() < 1e-6);
+        assert_eq!(overlay.current_pas.get_color(), [0.0, 1.0, 0.5, 1.0]);
+
+        // System-heavy weights: 0.1 + 0.1 + 0.24
+        let headless = PasWeights::new(0.1, 0.1, 0.8).unwrap();
+        overlay.set_pas_weights(headless).unwrap();
+        assert!((overlay.current_pas.calculate() - 0.44).abs() < 1e-6);
+        assert_eq!(overlay.current_pas.get_color(), [1.0, 0.2, 0.2, 1.0]);
+        assert!((overlay.snapshot().pas_score - 0.44).abs() < 1e-6);
+
+        // Bad weights are refused and the current ones stay
+        assert!(PasWeights::new(0.5, 0.5, 0.5).is_err());
+        assert!(overlay
+            .set_pas_weights(PasWeights {
+                p: 1.2,
+                a: -0.2,
+                s: 0.0,
+            })
+            .is_err());
+        assert_eq!(overlay.pas_weights(), headless);
+    }
+
+    #[test]
+    fn test_frame_time_percentiles_expose_hitches() {
+        let mut overlay = DiagnosticOverlay::new();
+        assert_eq!(overlay.frame_time_percentile(99.0), Duration::ZERO);
+
+        // 56 smooth frames and 4 hitches
+        let ms = Duration::from_millis;
+        for i in 0..60 {
+            overlay.update_performance(if i % 15 == 14 { ms(100) } else { ms(10) });
+        }
+
+        let mean = overlay.frame_times.iter().sum::<Duration>() / 60;
+        assert_eq!(overlay.frame_time_percentile(50.0), ms(10));
+        assert_eq!(overlay.frame_time_percentile(95.0), ms(100));
+        assert_eq!(overlay.frame_time_percentile(99.0), ms(100));
+        assert!(overlay.frame_time_percentile(99.0) > mean * 6);
+
+        // The mean hides the hitches; p95 does not
+        let from_mean = overlay.current_pas.p;
+        overlay.performance_from_p95 = true;
+        overlay.update_performance(ms(10));
+        let from_p95 = overlay.current_pas.p;
+        assert_eq!(from_mean, 1.0);
+        assert!(from_p95 < 0.2, "{}", from_p95);
+    }
+
+    #[test]
+    fn test_export_prometheus_is_valid_exposition() {
+        let mut overlay = DiagnosticOverlay::new();
+        overlay.update_system_health(1024 * 1024 * 1024);
+        overlay.current_pas.a = 0.5;
+        let text = overlay.export_prometheus();
+
+        // Every sample needs HELP and TYPE first, a valid name and a number
+        let valid_name = |name: &str| {
+            !name.is_empty()
+                && !name.starts_with(|c: char| c.is_ascii_digit())
+                && name
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == ':')
+        };
+        let mut helped = Vec::new();
+        let mut typed = Vec::new();
+        let mut samples = HashMap::new();
+        for line in text.lines() {
+            if let Some(rest) = line.strip_prefix("# HELP ") {
+                let (name, help) = rest.split_once(' ').unwrap();
+                assert!(valid_name(name) && !help.is_empty(), "{}", line);
+                helped.push(name);
+            } else if let Some(rest) = line.strip_prefix("# TYPE ") {
+                let (name, kind) = rest.split_once(' ').unwrap();
+                assert!(valid_name(name), "{}", line);
+                assert_eq!(kind, "gauge");
+                typed.push(name);
+            } else {
+                let (name, value) = line.split_once(' ').unwrap();
+                assert!(valid_name(name), "{}", line);
+                assert!(helped.contains(&name) && typed.contains(&name), "{}", line);
+                let value: f64 = value.parse().unwrap();
+                assert!(samples.insert(name, value).is_none(), "duplicate {}", name);
+            }
+        }
+        assert!(text.ends_with('\n'));
+
+        for name in [
+            "geometry_pas_score",
+            "geometry_pas_performance",
+            "geometry_pas_aesthetic",
+            "geometry_pas_system",
+            "geometry_vram_bytes",
+            "geometry_instruction_budget",
+        ] {
+            assert!(samples.contains_key(name), "missing {}", name);
+        }
+        assert_eq!(samples["geometry_vram_bytes"], 1073741824.0);
+        assert_eq!(samples["geometry_pas_aesthetic"], 0.5);
+        assert_eq!(samples["geometry_instruction_budget"], 10000.0);
+        assert!(
+            (samples["geometry_pas_score"] - overlay.current_pas.calculate() as f64).abs() < 1e-6
+        );
+    }
+
+    #[test]
+    fn test_threshold_callbacks_fire_on_transitions() {
+        use std::cell::RefCell;
+        use std::rc::Rc;
+
+        let mut overlay = DiagnosticOverlay::new();
+        overlay
+            .set_pas_weights(PasWeights::new(0.0, 0.0, 1.0).unwrap())
+            .unwrap();
+        let fired = Rc::new(RefCell::new(Vec::new()));
+        let log = fired.clone();
+        overlay.on_threshold_cross(Box::new(move |pas, state| {
+            log.borrow_mut().push((state, pas.s));
+        }));
+
+        // System health alone now sets the score
+        let drive = |overlay: &mut DiagnosticOverlay, s: f32| {
+            overlay.current_pas.s = s;
+            overlay.check_pas_thresholds();
+        };
+
+        drive(&mut overlay, 0.9);
+        drive(&mut overlay, 0.7); // OPTIMAL -> CAUTION
+        drive(&mut overlay, 0.65);
+        drive(&mut overlay, 0.3); // CAUTION -> CRITICAL
+        assert_eq!(overlay.pas_state(), "CRITICAL");
+
+        // Flicker within the hysteresis margin of the 0.5 edge is ignored
+        for s in [0.51, 0.49, 0.515, 0.495, 0.51] {
+            drive(&mut overlay, s);
+        }
+        assert_eq!(overlay.pas_state(), "CRITICAL");
+
+        drive(&mut overlay, 0.55); // CRITICAL -> CAUTION
+        drive(&mut overlay, 0.95); // CAUTION -> OPTIMAL
+        drive(&mut overlay, 0.1); // OPTIMAL -> CRITICAL in one step
+
+        assert_eq!(
+            *fired.borrow(),
+            vec![
+                ("CAUTION", 0.7),
+                ("CRITICAL", 0.3),
+                ("CAUTION", 0.55),
+                ("OPTIMAL", 0.95),
+                ("CRITICAL", 0.1),
+            ]
+        );
+
+        // The overlay's own updates check thresholds too
+        overlay.update_system_health(0);
+        assert_eq!(overlay.pas_state(), "OPTIMAL");
+        assert_eq!(fired.borrow().len(), 6);
+    }
 }
@@ -8,6 +8,40 @@ use crate::gpu::WGSLCompiler;
 use crate::rts::extract_wgsl_from_rts;
 use glam::Vec2;
 
+/// How a zone's output is combined with what is already on the map
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum BlendMode {
+    /// Overwrite the destination (straight texture copy)
+    #[default]
+    Replace,
+    /// Composite over the destination using the zone's alpha
+    AlphaBlend,
+    /// Add the zone's alpha-weighted color to the destination
+    Additive,
+}
+
+impl BlendMode {
+    /// Blend state for a render pipeline drawing with this mode
+    pub fn blend_state(&self) -> wgpu::BlendState {
+        match self {
+            BlendMode::Replace => wgpu::BlendState::REPLACE,
+            BlendMode::AlphaBlend => wgpu::BlendState::ALPHA_BLENDING,
+            BlendMode::Additive => wgpu::BlendState {
+                color: wgpu::BlendComponent {
+                    src_factor: wgpu::BlendFactor::SrcAlpha,
+                    dst_factor: wgpu::BlendFactor::One,
+                    operation: wgpu::BlendOperation::Add,
+                },
+                alpha: wgpu::BlendComponent {
+                    src_factor: wgpu::BlendFactor::One,
+                    dst_factor: wgpu::BlendFactor::One,
+                    operation: wgpu::BlendOperation::Add,
+                },
+            },
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct ExecutionZone {
     pub position: Vec2,
@@ -22,6 +56,8 @@ pub struct ExecutionZone {
     pipeline: Option<std::sync::Arc<wgpu::ComputePipeline>>,
     /// Output texture for compute results
     texture: Option<std::sync::Arc<wgpu::Texture>>,
+    /// How the output is composited onto the map
+    blend_mode: BlendMode,
 }
 
 impl ExecutionZone {
@@ -35,6 +71,7 @@ impl ExecutionZone {
             workgroup_size: (1, 1, 1),
             pipeline: None,
             texture: None,
+            blend_mode: BlendMode::default(),
         }
     }
 
@@ -117,6 +154,24 @@ impl ExecutionZone {
         self.texture.clone()
     }
 
+    /// Use an externally created texture as the zone's output
+    ///
+    /// The texture needs `COPY_SRC` for `BlendMode::Replace` and
+    /// `TEXTURE_BINDING` for the blended modes.
+    pub fn set_texture(&mut self, texture: std::sync::Arc<wgpu::Texture>) {
+        self.texture = Some(texture);
+    }
+
+    /// Get the blend mode used when compositing this zone
+    pub fn blend_mode(&self) -> BlendMode {
+        self.blend_mode
+    }
+
+    /// Set the blend mode used when compositing this zone
+    pub fn set_blend_mode(&mut self, mode: BlendMode) {
+        self.blend_mode = mode;
+    }
+
     /// Create an output texture for compute shader results
     ///
     /// Creates a storage texture that can be bound to compute shaders for write access.
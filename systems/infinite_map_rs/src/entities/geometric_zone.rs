@@ -5,6 +5,38 @@
 
 use crate::rendering::geometric_interpreter::{GeometricEngine, GeometricInstruction};
 use glam::Vec2;
+use std::time::{Duration, Instant};
+
+/// Default length of the spawn (grow-in) animation
+pub const DEFAULT_SPAWN_DURATION: Duration = Duration::from_millis(300);
+/// Default length of the despawn (shrink-out) animation
+pub const DEFAULT_DESPAWN_DURATION: Duration = Duration::from_millis(200);
+
+/// Easing applied to zone spawn and despawn animations
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AnimationCurve {
+    Linear,
+    /// Slow start (quadratic)
+    EaseIn,
+    /// Slow finish (quadratic)
+    EaseOut,
+    /// Slow start and finish (smoothstep)
+    #[default]
+    EaseInOut,
+}
+
+impl AnimationCurve {
+    /// Map linear progress in [0, 1] to eased progress in [0, 1]
+    pub fn apply(&self, t: f32) -> f32 {
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            AnimationCurve::Linear => t,
+            AnimationCurve::EaseIn => t * t,
+            AnimationCurve::EaseOut => 1.0 - (1.0 - t) * (1.0 - t),
+            AnimationCurve::EaseInOut => t * t * (3.0 - 2.0 * t),
+        }
+    }
+}
 
 #[derive(Debug, Clone)]
 pub struct GeometricZone {
@@ -13,6 +45,14 @@ pub struct GeometricZone {
     pub pixels: Vec<[u8; 4]>,
     pub active: bool,
     pub grid_size: u32,
+    /// When the spawn animation started
+    pub spawn_at: Instant,
+    /// When the despawn animation started, if it has
+    pub despawn_at: Option<Instant>,
+    pub spawn_duration: Duration,
+    pub despawn_duration: Duration,
+    /// Easing used for the scale of both animations
+    pub curve: AnimationCurve,
 }
 
 impl GeometricZone {
@@ -23,9 +63,68 @@ impl GeometricZone {
             pixels,
             active: false,
             grid_size,
+            spawn_at: Instant::now(),
+            despawn_at: None,
+            spawn_duration: DEFAULT_SPAWN_DURATION,
+            despawn_duration: DEFAULT_DESPAWN_DURATION,
+            curve: AnimationCurve::default(),
+        }
+    }
+
+    /// Linear progress of an animation of `duration` started at `start`
+    fn fraction(start: Instant, duration: Duration, now: Instant) -> f32 {
+        if duration.is_zero() {
+            return 1.0;
+        }
+        (now.saturating_duration_since(start).as_secs_f32() / duration.as_secs_f32()).min(1.0)
+    }
+
+    /// Linear lifecycle progress: 0 -> 1 while spawning, 1 -> 0 while despawning
+    fn lifecycle_progress(&self, now: Instant) -> f32 {
+        match self.despawn_at {
+            // Shrink from wherever the spawn animation had reached
+            Some(despawn_at) => {
+                let from = Self::fraction(self.spawn_at, self.spawn_duration, despawn_at);
+                from * (1.0 - Self::fraction(despawn_at, self.despawn_duration, now))
+            },
+            None => Self::fraction(self.spawn_at, self.spawn_duration, now),
+        }
+    }
+
+    /// Render scale in [0, 1], eased by `curve`
+    pub fn current_scale(&self, now: Instant) -> f32 {
+        self.curve.apply(self.lifecycle_progress(now))
+    }
+
+    /// Render opacity in [0, 1]; fades linearly while the scale eases
+    pub fn current_alpha(&self, now: Instant) -> f32 {
+        self.lifecycle_progress(now)
+    }
+
+    /// Start the despawn animation now
+    pub fn begin_despawn(&mut self) {
+        self.begin_despawn_at(Instant::now());
+    }
+
+    /// Start the despawn animation at `at` (no-op if already despawning)
+    pub fn begin_despawn_at(&mut self, at: Instant) {
+        if self.despawn_at.is_none() {
+            self.despawn_at = Some(at);
         }
     }
 
+    /// Whether the despawn animation has completed
+    pub fn is_finished(&self) -> bool {
+        self.is_finished_at(Instant::now())
+    }
+
+    /// Whether the despawn animation has completed by `now`
+    pub fn is_finished_at(&self, now: Instant) -> bool {
+        self.despawn_at.is_some_and(|despawn_at| {
+            now.saturating_duration_since(despawn_at) >= self.despawn_duration
+        })
+    }
+
     /// Run the geometric code once
     pub fn execute(&mut self) -> GeometricEngine {
         let mut engine = GeometricEngine::new();
@@ -44,3 +143,34 @@ impl GeometricZone {
         engine
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_spawn_and_despawn_animation() {
+        let mut zone = GeometricZone::new(Vec2::ZERO, "zone".to_string(), Vec::new(), 8);
+        let t0 = Instant::now();
+        zone.spawn_at = t0;
+        zone.spawn_duration = Duration::from_millis(400);
+        zone.despawn_duration = Duration::from_millis(200);
+
+        let ms = Duration::from_millis;
+        assert_eq!(zone.current_scale(t0), 0.0);
+        assert!((zone.current_scale(t0 + ms(200)) - 0.5).abs() < 1e-4);
+        assert!(zone.current_scale(t0 + ms(100)) < zone.current_alpha(t0 + ms(100)));
+        assert_eq!(zone.current_scale(t0 + ms(400)), 1.0);
+        assert_eq!(zone.current_scale(t0 + ms(1000)), 1.0);
+        assert!(!zone.is_finished_at(t0 + ms(1000)));
+
+        let t1 = t0 + ms(1000);
+        zone.begin_despawn_at(t1);
+        assert_eq!(zone.current_scale(t1), 1.0);
+        assert!((zone.current_scale(t1 + ms(100)) - 0.5).abs() < 1e-4);
+        assert!(!zone.is_finished_at(t1 + ms(100)));
+        assert_eq!(zone.current_scale(t1 + ms(200)), 0.0);
+        assert_eq!(zone.current_alpha(t1 + ms(200)), 0.0);
+        assert!(zone.is_finished_at(t1 + ms(200)));
+    }
+}
@@ -7,6 +7,8 @@ pub mod execution_zone;
 pub mod geometric_zone;
 pub mod rts_particle;
 
-pub use execution_zone::ExecutionZone;
-pub use geometric_zone::GeometricZone;
-pub use rts_particle::{EncodingMode, RTSMetadata, RTSParticle, SegmentInfo};
+pub use execution_zone::{BlendMode, ExecutionZone};
+pub use geometric_zone::{AnimationCurve, GeometricZone};
+pub use rts_particle::{
+    EncodingMode, MetadataError, RTSMetadata, RTSParticle, RtsMetadataBuilder, SegmentInfo,
+};
@@ -103,6 +103,219 @@ pub struct SegmentInfo {
     pub segment_type: String,
 }
 
+/// Segment layout problems found by `RTSMetadata::validate`
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum MetadataError {
+    #[error("Segments '{first}' and '{second}' overlap at offset {offset}")]
+    Overlap {
+        first: String,
+        second: String,
+        offset: u64,
+    },
+    #[error("Gap of {size} bytes at offset {offset}")]
+    Gap { offset: u64, size: u64 },
+    #[error("Segments cover {covered} bytes but original_size is {declared}")]
+    LengthMismatch { covered: u64, declared: u64 },
+    #[error("Segment '{0}' is empty")]
+    EmptySegment(String),
+}
+
+impl SegmentInfo {
+    /// Offset one past the last byte of the segment
+    pub fn end(&self) -> u64 {
+        self.offset + self.size
+    }
+}
+
+impl RTSMetadata {
+    /// Segments ordered by offset (ties broken by name)
+    pub fn sorted_segments(&self) -> Vec<(&String, &SegmentInfo)> {
+        let mut segments: Vec<_> = self.offsets.iter().collect();
+        segments.sort_by(|a, b| (a.1.offset, a.0).cmp(&(b.1.offset, b.0)));
+        segments
+    }
+
+    /// Check that segments tile the decoded data unambiguously
+    ///
+    /// In offset order, segments must not overlap and must leave no gaps,
+    /// starting at 0 and, when `original_size` is set, ending exactly there.
+    /// Metadata without segments is always valid.
+    pub fn validate(&self) -> std::result::Result<(), MetadataError> {
+        let segments = self.sorted_segments();
+        let mut end = 0;
+        let mut previous: Option<&String> = None;
+
+        for (name, segment) in &segments {
+            if segment.offset < end {
+                return Err(MetadataError::Overlap {
+                    first: previous.cloned().unwrap_or_default(),
+                    second: (*name).clone(),
+                    offset: segment.offset,
+                });
+            }
+            if segment.offset > end {
+                return Err(MetadataError::Gap {
+                    offset: end,
+                    size: segment.offset - end,
+                });
+            }
+            end = segment.end();
+            previous = Some(name);
+        }
+
+        match self.original_size {
+            Some(declared) if !segments.is_empty() && end < declared => Err(MetadataError::Gap {
+                offset: end,
+                size: declared - end,
+            }),
+            Some(declared) if end > declared => Err(MetadataError::LengthMismatch {
+                covered: end,
+                declared,
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    /// Merge touching segments of the same type
+    ///
+    /// Each run keeps the name of its first segment. Hashes of merged
+    /// segments no longer describe the data and are dropped.
+    pub fn normalize(&mut self) {
+        let sorted: Vec<(String, SegmentInfo)> = self
+            .sorted_segments()
+            .into_iter()
+            .map(|(name, segment)| (name.clone(), segment.clone()))
+            .collect();
+
+        let mut merged: Vec<(String, SegmentInfo)> = Vec::with_capacity(sorted.len());
+        for (name, segment) in sorted {
+            match merged.last_mut() {
+                Some((first, run))
+                    if run.end() == segment.offset && run.segment_type == segment.segment_type =>
+                {
+                    run.size += segment.size;
+                    self.hashes.remove(first);
+                    self.hashes.remove(&name);
+                },
+                _ => merged.push((name, segment)),
+            }
+        }
+        self.offsets = merged.into_iter().collect();
+    }
+}
+
+/// Builds `RTSMetadata` with contiguous, validated segments
+///
+/// ```
+/// use infinite_map_rs::entities::rts_particle::RtsMetadataBuilder;
+///
+/// let metadata = RtsMetadataBuilder::new()
+///     .rts_type("kernel")
+///     .add_segment("kernel", 4096)
+///     .add_segment("cmdline", 128)
+///     .build()
+///     .unwrap();
+/// assert_eq!(metadata.offsets["cmdline"].offset, 4096);
+/// assert_eq!(metadata.original_size, Some(4224));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct RtsMetadataBuilder {
+    metadata: RTSMetadata,
+    /// Segments in insertion order
+    segments: Vec<(String, String, u64)>,
+}
+
+impl RtsMetadataBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn grid_size(mut self, grid_size: u32) -> Self {
+        self.metadata.grid_size = grid_size;
+        self
+    }
+
+    pub fn encoding_mode(mut self, encoding_mode: EncodingMode) -> Self {
+        self.metadata.encoding_mode = encoding_mode;
+        self
+    }
+
+    pub fn rts_type(mut self, rts_type: impl Into<String>) -> Self {
+        self.metadata.rts_type = Some(rts_type.into());
+        self
+    }
+
+    pub fn name(mut self, name: impl Into<String>) -> Self {
+        self.metadata.name = Some(name.into());
+        self
+    }
+
+    pub fn hash(mut self, segment: impl Into<String>, sha256: impl Into<String>) -> Self {
+        self.metadata.hashes.insert(segment.into(), sha256.into());
+        self
+    }
+
+    pub fn timestamp(mut self, timestamp: i64) -> Self {
+        self.metadata.timestamp = Some(timestamp);
+        self
+    }
+
+    /// Append a `mode` segment (e.g. "kernel", "initrd") of `len` bytes
+    /// directly after the previous one, named after its mode
+    ///
+    /// Use [`Self::add_named_segment`] for several segments of one mode.
+    pub fn add_segment(self, mode: impl Into<String>, len: u64) -> Self {
+        let mode = mode.into();
+        self.add_named_segment(mode.clone(), mode, len)
+    }
+
+    /// Append a segment directly after the previous one
+    pub fn add_named_segment(
+        mut self,
+        name: impl Into<String>,
+        segment_type: impl Into<String>,
+        size: u64,
+    ) -> Self {
+        self.segments.push((name.into(), segment_type.into(), size));
+        self
+    }
+
+    /// Lay out the segments from offset 0 and validate the result
+    ///
+    /// `original_size` is set to the total segment length.
+    pub fn build(self) -> std::result::Result<RTSMetadata, MetadataError> {
+        let mut metadata = self.metadata;
+        let mut offset = 0;
+
+        for (name, segment_type, size) in self.segments {
+            if size == 0 {
+                return Err(MetadataError::EmptySegment(name));
+            }
+            // A repeated name would silently drop the earlier segment
+            if metadata.offsets.contains_key(&name) {
+                return Err(MetadataError::Overlap {
+                    first: name.clone(),
+                    second: name,
+                    offset,
+                });
+            }
+            metadata.offsets.insert(
+                name,
+                SegmentInfo {
+                    offset,
+                    size,
+                    segment_type,
+                },
+            );
+            offset += size;
+        }
+
+        metadata.original_size = Some(offset);
+        metadata.validate()?;
+        Ok(metadata)
+    }
+}
+
 impl Default for RTSMetadata {
     fn default() -> Self {
         Self {
@@ -583,6 +796,169 @@ mod tests {
         assert_eq!(metadata.bytes_per_pixel, 4);
     }
 
+    fn segment(offset: u64, size: u64, segment_type: &str) -> SegmentInfo {
+        SegmentInfo {
+            offset,
+            size,
+            segment_type: segment_type.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_validate_rejects_overlapping_segments() {
+        let mut metadata = RTSMetadata::default();
+        metadata
+            .offsets
+            .insert("kernel".into(), segment(0, 100, "binary"));
+        metadata
+            .offsets
+            .insert("initrd".into(), segment(80, 50, "binary"));
+
+        assert_eq!(
+            metadata.validate(),
+            Err(MetadataError::Overlap {
+                first: "kernel".into(),
+                second: "initrd".into(),
+                offset: 80,
+            })
+        );
+    }
+
+    #[test]
+    fn test_validate_rejects_gaps_and_short_coverage() {
+        let mut metadata = RTSMetadata::default();
+        metadata
+            .offsets
+            .insert("kernel".into(), segment(0, 100, "binary"));
+        metadata
+            .offsets
+            .insert("initrd".into(), segment(120, 30, "binary"));
+        assert_eq!(
+            metadata.validate(),
+            Err(MetadataError::Gap {
+                offset: 100,
+                size: 20,
+            })
+        );
+
+        metadata
+            .offsets
+            .insert("initrd".into(), segment(100, 30, "binary"));
+        assert!(metadata.validate().is_ok());
+
+        metadata.original_size = Some(200);
+        assert_eq!(
+            metadata.validate(),
+            Err(MetadataError::Gap {
+                offset: 130,
+                size: 70,
+            })
+        );
+        metadata.original_size = Some(120);
+        assert_eq!(
+            metadata.validate(),
+            Err(MetadataError::LengthMismatch {
+                covered: 130,
+                declared: 120,
+            })
+        );
+    }
+
+    #[test]
+    fn test_normalize_merges_adjacent_same_type_segments() {
+        let mut metadata = RTSMetadata::default();
+        metadata
+            .offsets
+            .insert("code_b".into(), segment(64, 64, "code"));
+        metadata
+            .offsets
+            .insert("code_a".into(), segment(0, 64, "code"));
+        metadata
+            .offsets
+            .insert("data".into(), segment(128, 32, "data"));
+        metadata
+            .offsets
+            .insert("code_c".into(), segment(160, 16, "code"));
+        metadata.hashes.insert("code_a".into(), "aa".into());
+        metadata.hashes.insert("data".into(), "dd".into());
+        metadata.original_size = Some(176);
+
+        metadata.normalize();
+
+        let segments: Vec<(&str, u64, u64)> = metadata
+            .sorted_segments()
+            .into_iter()
+            .map(|(name, s)| (name.as_str(), s.offset, s.size))
+            .collect();
+        assert_eq!(
+            segments,
+            vec![("code_a", 0, 128), ("data", 128, 32), ("code_c", 160, 16)]
+        );
+        assert!(!metadata.hashes.contains_key("code_a"));
+        assert_eq!(metadata.hashes.get("data").map(String::as_str), Some("dd"));
+        assert!(metadata.validate().is_ok());
+    }
+
+    #[test]
+    fn test_builder_lays_out_contiguous_segments() {
+        let metadata = RtsMetadataBuilder::new()
+            .grid_size(256)
+            .encoding_mode(EncodingMode::Code)
+            .add_segment("kernel", 1000)
+            .add_segment("initrd", 500)
+            .add_segment("cmdline", 24)
+            .build()
+            .unwrap();
+
+        let segments: Vec<(&str, u64, u64)> = metadata
+            .sorted_segments()
+            .into_iter()
+            .map(|(name, s)| (name.as_str(), s.offset, s.size))
+            .collect();
+        assert_eq!(
+            segments,
+            vec![
+                ("kernel", 0, 1000),
+                ("initrd", 1000, 500),
+                ("cmdline", 1500, 24)
+            ]
+        );
+        assert_eq!(metadata.offsets["initrd"].segment_type, "initrd");
+        assert_eq!(metadata.original_size, Some(1524));
+        assert_eq!(metadata.grid_size, 256);
+        assert_eq!(metadata.encoding_mode, EncodingMode::Code);
+    }
+
+    #[test]
+    fn test_builder_rejects_empty_segment() {
+        let result = RtsMetadataBuilder::new()
+            .add_segment("kernel", 1000)
+            .add_segment("initrd", 0)
+            .build();
+        assert_eq!(
+            result.unwrap_err(),
+            MetadataError::EmptySegment("initrd".into())
+        );
+    }
+
+    #[test]
+    fn test_builder_named_segments_share_a_mode() {
+        let metadata = RtsMetadataBuilder::new()
+            .add_named_segment("module_a", "wasm", 64)
+            .add_named_segment("module_b", "wasm", 32)
+            .build()
+            .unwrap();
+        assert_eq!(metadata.offsets["module_b"].offset, 64);
+        assert_eq!(metadata.offsets["module_b"].segment_type, "wasm");
+
+        // The same mode twice without names collides
+        let result = RtsMetadataBuilder::new()
+            .add_segment("wasm", 64)
+            .add_segment("wasm", 32)
+            .build();
+        assert!(matches!(result, Err(MetadataError::Overlap { .. })));
+    }
+
     #[test]
     fn test_extract_png_text_metadata_invalid_png() {
         let invalid_data = vec![0x00, 0x01, 0x02];
@@ -15,6 +15,8 @@ pub struct CompressedSensingReconstructor {
     sparsity_threshold: f32,
     current_density: Array2<f32>,
     sample_count: u64,
+    sample_stride: u64,
+    smear: bool,
 }
 
 impl CompressedSensingReconstructor {
@@ -28,9 +30,19 @@ impl CompressedSensingReconstructor {
             sparsity_threshold,
             current_density: Array2::zeros((size as usize, size as usize)),
             sample_count: 0,
+            sample_stride: 1,
+            smear: true,
         }
     }
 
+    /// Sample one page in every `sample_stride`, and choose between the
+    /// Hilbert locality smear (`smear`) and plain hit counts
+    pub fn with_sampling(mut self, sample_stride: u64, smear: bool) -> Self {
+        self.sample_stride = sample_stride.max(1);
+        self.smear = smear;
+        self
+    }
+
     /// Reconstruct density map from a batch of fault events.
     ///
     /// In a full implementation, this would use L1 minimization (Basis Pursuit).
@@ -45,13 +57,14 @@ impl CompressedSensingReconstructor {
             if let FaultEvent::PageFault { address, .. } = fault {
                 // Map address to Hilbert coords
                 // Assuming 4KB pages
-                let page_idx = (address >> 12) as u32; // Simplified
+                let page_idx = address >> 12;
 
-                // We don't have the max logic here, assuming page_idx fits.
-                // In reality we modulo or scale.
-                let scaled_idx = page_idx % (self.size * self.size * self.size);
+                // One sample covers `sample_stride` pages; wrap past the
+                // end of the curve
+                let curve_len = (self.size as u64).pow(3);
+                let scaled_idx = (page_idx / self.sample_stride) % curve_len;
 
-                let (x, y, _z) = self.hilbert.d_to_xyz(scaled_idx as u64);
+                let (x, y, _z) = self.hilbert.d2xyz(scaled_idx);
 
                 // Project 3D (x,y,z) to 2D texture (u, v) for density map.
                 // Strategy: Slice or flatten?
@@ -78,7 +91,9 @@ impl CompressedSensingReconstructor {
 
                     // Hilbert Locality Smear (Simple Comp. Sensing approx)
                     // If we hit this point, assume neighbors are likely active too (probability distribution).
-                    self.smear_neighbors(x as usize, y as usize);
+                    if self.smear {
+                        self.smear_neighbors(x as usize, y as usize);
+                    }
                 }
             }
         }
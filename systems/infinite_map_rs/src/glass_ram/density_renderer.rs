@@ -12,8 +12,8 @@ use wgpu::util::DeviceExt;
 pub struct DensityRendererConfig {
     /// Maximum density value for color mapping
     pub max_density: f32,
-    /// Color palette for density visualization
-    pub palette: DensityPalette,
+    /// Colormap for density visualization
+    pub colormap: Colormap,
     /// Whether to show grid lines
     pub show_grid: bool,
     /// Grid opacity
@@ -24,39 +24,108 @@ impl Default for DensityRendererConfig {
     fn default() -> Self {
         Self {
             max_density: 10.0,
-            palette: DensityPalette::Heatmap,
+            colormap: Colormap::Heat,
             show_grid: true,
             grid_opacity: 0.1,
         }
     }
 }
 
-/// Color palette for density visualization
-#[derive(Debug, Clone, Copy)]
-pub enum DensityPalette {
-    /// Heatmap: Blue -> Green -> Yellow -> Red
-    Heatmap,
+/// Number of swatches in a legend
+const LEGEND_STEPS: usize = 5;
+
+impl DensityRendererConfig {
+    /// Color key for the UI: swatches from zero to `max_density`, each with
+    /// the density it stands for
+    pub fn legend(&self) -> Vec<([u8; 4], f32)> {
+        (0..LEGEND_STEPS)
+            .map(|i| {
+                let t = i as f32 / (LEGEND_STEPS - 1) as f32;
+                (self.colormap.rgba8(t), t * self.max_density)
+            })
+            .collect()
+    }
+}
+
+/// Color map for density visualization
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Colormap {
+    /// Heat: Blue -> Green -> Yellow -> Red
+    Heat,
     /// Viridis: Purple -> Blue -> Green -> Yellow
     Viridis,
+    /// Magma: Black -> Purple -> Orange -> Pale Yellow
+    Magma,
     /// Plasma: Purple -> Red -> Yellow
     Plasma,
     /// Grayscale: Black -> White
     Grayscale,
 }
 
-impl DensityPalette {
+/// Matplotlib's viridis, sampled at 9 evenly spaced points
+const VIRIDIS: [[f32; 3]; 9] = [
+    [0.267, 0.005, 0.329],
+    [0.283, 0.141, 0.458],
+    [0.254, 0.265, 0.530],
+    [0.207, 0.372, 0.553],
+    [0.164, 0.471, 0.558],
+    [0.128, 0.567, 0.551],
+    [0.208, 0.719, 0.473],
+    [0.478, 0.821, 0.3182],
+    [0.993, 0.906, 0.144],
+];
+
+/// Matplotlib's magma, sampled at 9 evenly spaced points
+const MAGMA: [[f32; 3]; 9] = [
+    [0.001, 0.000, 0.014],
+    [0.113, 0.065, 0.277],
+    [0.316, 0.071, 0.485],
+    [0.472, 0.111, 0.507],
+    [0.716, 0.215, 0.475],
+    [0.869, 0.288, 0.409],
+    [0.968, 0.440, 0.360],
+    [0.995, 0.624, 0.427],
+    [0.987, 0.991, 0.750],
+];
+
+/// Matplotlib's plasma, sampled at 5 evenly spaced points
+const PLASMA: [[f32; 3]; 5] = [
+    [0.050, 0.030, 0.528],
+    [0.495, 0.012, 0.658],
+    [0.798, 0.280, 0.470],
+    [0.973, 0.586, 0.254],
+    [0.940, 0.975, 0.131],
+];
+
+impl Colormap {
+    /// Every colormap, in the order a UI should offer them
+    pub const ALL: [Colormap; 5] = [
+        Colormap::Heat,
+        Colormap::Viridis,
+        Colormap::Magma,
+        Colormap::Plasma,
+        Colormap::Grayscale,
+    ];
+
     /// Get color for a normalized density value (0.0 to 1.0)
     pub fn color_for_density(&self, density: f32) -> [f32; 4] {
         let d = density.clamp(0.0, 1.0);
         match self {
-            DensityPalette::Heatmap => self.heatmap_color(d),
-            DensityPalette::Viridis => self.viridis_color(d),
-            DensityPalette::Plasma => self.plasma_color(d),
-            DensityPalette::Grayscale => [d, d, d, 1.0],
+            Colormap::Heat => self.heat_color(d),
+            Colormap::Viridis => Self::interpolate(&VIRIDIS, d),
+            Colormap::Magma => Self::interpolate(&MAGMA, d),
+            Colormap::Plasma => Self::interpolate(&PLASMA, d),
+            Colormap::Grayscale => [d, d, d, 1.0],
         }
     }
 
-    fn heatmap_color(&self, d: f32) -> [f32; 4] {
+    /// `color_for_density` as 8-bit RGBA
+    pub fn rgba8(&self, density: f32) -> [u8; 4] {
+        self.color_for_density(density)
+            .map(|channel| (channel * 255.0).round() as u8)
+    }
+
+    fn heat_color(&self, d: f32) -> [f32; 4] {
         // Blue -> Green -> Yellow -> Red
         if d < 0.25 {
             // Blue to Green
@@ -77,29 +146,18 @@ impl DensityPalette {
         }
     }
 
-    fn viridis_color(&self, d: f32) -> [f32; 4] {
-        // Simplified Viridis approximation
-        if d < 0.33 {
-            let t = d / 0.33;
-            [0.28 + t * 0.1, 0.01 + t * 0.2, 0.57 + t * 0.1, 1.0]
-        } else if d < 0.66 {
-            let t = (d - 0.33) / 0.33;
-            [0.38 + t * 0.2, 0.21 + t * 0.3, 0.67 - t * 0.1, 1.0]
-        } else {
-            let t = (d - 0.66) / 0.34;
-            [0.58 + t * 0.3, 0.51 + t * 0.4, 0.57 + t * 0.2, 1.0]
-        }
-    }
-
-    fn plasma_color(&self, d: f32) -> [f32; 4] {
-        // Simplified Plasma approximation
-        if d < 0.5 {
-            let t = d / 0.5;
-            [0.05 + t * 0.4, 0.05 + t * 0.1, 0.5 - t * 0.3, 1.0]
-        } else {
-            let t = (d - 0.5) / 0.5;
-            [0.45 + t * 0.5, 0.15 + t * 0.7, 0.2 + t * 0.6, 1.0]
-        }
+    /// Linear interpolation between evenly spaced color stops
+    fn interpolate(stops: &[[f32; 3]], d: f32) -> [f32; 4] {
+        let position = d * (stops.len() - 1) as f32;
+        let index = (position.floor() as usize).min(stops.len() - 2);
+        let t = position - index as f32;
+        let (a, b) = (stops[index], stops[index + 1]);
+        [
+            a[0] + (b[0] - a[0]) * t,
+            a[1] + (b[1] - a[1]) * t,
+            a[2] + (b[2] - a[2]) * t,
+            1.0,
+        ]
     }
 }
 
@@ -118,6 +176,8 @@ pub struct DensityRenderer {
     bind_group: wgpu::BindGroup,
     pipeline: wgpu::RenderPipeline,
     uniform_buffer: wgpu::Buffer,
+    /// Side of the square density texture, in texels
+    resolution: u32,
 }
 
 #[repr(C)]
@@ -128,19 +188,19 @@ struct DensityUniforms {
     max_density: f32,
     show_grid: f32,
     grid_opacity: f32,
-    _padding: [f32; 2],
+    _padding: [f32; 3], // WGSL rounds the struct up to 40 bytes
 }
 
 impl DensityRenderer {
-    /// Create a new density map renderer
+    /// Create a new density map renderer for `resolution` x `resolution`
+    /// density maps
     pub fn new(
         device: Arc<wgpu::Device>,
         queue: Arc<wgpu::Queue>,
         config: DensityRendererConfig,
+        resolution: u32,
         texture_format: wgpu::TextureFormat,
     ) -> Self {
-        let resolution = 1024; // Default resolution
-
         // Create texture
         let texture = device.create_texture(&wgpu::TextureDescriptor {
             label: Some("Density Map Texture"),
@@ -181,7 +241,7 @@ impl DensityRenderer {
                 max_density: config.max_density,
                 show_grid: if config.show_grid { 1.0 } else { 0.0 },
                 grid_opacity: config.grid_opacity,
-                _padding: [0.0; 2],
+                _padding: [0.0; 3],
             }]),
             usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
         });
@@ -289,13 +349,14 @@ impl DensityRenderer {
             bind_group,
             pipeline,
             uniform_buffer,
+            resolution,
         }
     }
 
     /// Update the density map texture from a 2D array
     pub fn update_density_map(&self, density_map: &Array2<f32>) {
         let (rows, cols) = density_map.dim();
-        let resolution = rows.min(cols);
+        let resolution = rows.min(cols).min(self.resolution as usize);
 
         // Convert density values to RGBA colors
         let mut texture_data = vec![[0u8; 4]; resolution * resolution];
@@ -311,16 +372,8 @@ impl DensityRenderer {
                 // Normalize density
                 let normalized = (density / self.config.max_density).clamp(0.0, 1.0);
 
-                // Get color from palette
-                let color = self.config.palette.color_for_density(normalized);
-
-                // Convert to u8
-                texture_data[y * resolution + x] = [
-                    (color[0] * 255.0) as u8,
-                    (color[1] * 255.0) as u8,
-                    (color[2] * 255.0) as u8,
-                    (color[3] * 255.0) as u8,
-                ];
+                // Get color from the colormap
+                texture_data[y * resolution + x] = self.config.colormap.rgba8(normalized);
             }
         }
 
@@ -350,11 +403,11 @@ impl DensityRenderer {
     pub fn update_uniforms(&self, screen_size: [f32; 2]) {
         let uniforms = DensityUniforms {
             screen_size,
-            texture_size: [1024.0, 1024.0],
+            texture_size: [self.resolution as f32, self.resolution as f32],
             max_density: self.config.max_density,
             show_grid: if self.config.show_grid { 1.0 } else { 0.0 },
             grid_opacity: self.config.grid_opacity,
-            _padding: [0.0; 2],
+            _padding: [0.0; 3],
         };
 
         self.queue
@@ -376,6 +429,11 @@ impl DensityRenderer {
         &self.texture_view
     }
 
+    /// Side of the density texture, in texels
+    pub fn resolution(&self) -> u32 {
+        self.resolution
+    }
+
     /// Update configuration
     pub fn update_config(&mut self, config: DensityRendererConfig) {
         self.config = config;
@@ -385,6 +443,16 @@ impl DensityRenderer {
     pub fn config(&self) -> &DensityRendererConfig {
         &self.config
     }
+
+    /// Switch colormaps; takes effect on the next `update_density_map`
+    pub fn set_colormap(&mut self, colormap: Colormap) {
+        self.config.colormap = colormap;
+    }
+
+    /// Color key for the current colormap and density range
+    pub fn legend(&self) -> Vec<([u8; 4], f32)> {
+        self.config.legend()
+    }
 }
 
 #[cfg(test)]
@@ -393,7 +461,7 @@ mod tests {
 
     #[test]
     fn test_palette_heatmap() {
-        let palette = DensityPalette::Heatmap;
+        let palette = Colormap::Heat;
         let color_low = palette.color_for_density(0.0);
         let color_mid = palette.color_for_density(0.5);
         let color_high = palette.color_for_density(1.0);
@@ -405,7 +473,7 @@ mod tests {
 
     #[test]
     fn test_palette_grayscale() {
-        let palette = DensityPalette::Grayscale;
+        let palette = Colormap::Grayscale;
         let color = palette.color_for_density(0.5);
         assert_eq!(color[0], 0.5);
         assert_eq!(color[1], 0.5);
@@ -422,11 +490,32 @@ mod tests {
 
     #[test]
     fn test_color_clamping() {
-        let palette = DensityPalette::Heatmap;
+        let palette = Colormap::Heat;
         let color_low = palette.color_for_density(-0.5);
         let color_high = palette.color_for_density(1.5);
 
         assert!(color_low[0] >= 0.0 && color_low[0] <= 1.0);
         assert!(color_high[0] >= 0.0 && color_high[0] <= 1.0);
     }
+
+    #[test]
+    fn test_colormaps_differ_and_legend_spans_range() {
+        let colors: Vec<[u8; 4]> = Colormap::ALL.iter().map(|map| map.rgba8(0.3)).collect();
+        for (i, a) in colors.iter().enumerate() {
+            for b in &colors[i + 1..] {
+                assert_ne!(a, b);
+            }
+        }
+
+        let config = DensityRendererConfig {
+            max_density: 8.0,
+            colormap: Colormap::Magma,
+            ..Default::default()
+        };
+        let legend = config.legend();
+        assert_eq!(legend.len(), LEGEND_STEPS);
+        assert_eq!(legend[0], (Colormap::Magma.rgba8(0.0), 0.0));
+        assert_eq!(legend[LEGEND_STEPS - 1], (Colormap::Magma.rgba8(1.0), 8.0));
+        assert!(legend.windows(2).all(|pair| pair[0].1 < pair[1].1));
+    }
 }
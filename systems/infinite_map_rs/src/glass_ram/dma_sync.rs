@@ -1,12 +1,16 @@
 // systems/infinite_map_rs/src/glass_ram/dma_sync.rs
 //
 // DMA Buffer Synchronization
-// Implements CPU access synchronization via DMA_BUF_IOCTL_SYNC
+// Implements CPU access synchronization via DMA_BUF_IOCTL_SYNC, and
+// cross-device ordering via sync_file fences (DMA_BUF_IOCTL_EXPORT_SYNC_FILE)
 
-use nix::ioctl_write_ptr;
+use nix::{ioctl_readwrite, ioctl_write_ptr};
 use std::error::Error;
 use std::fmt;
+use std::os::fd::{AsRawFd, FromRawFd, OwnedFd};
 use std::os::unix::io::RawFd;
+use std::sync::Arc;
+use std::time::Duration;
 
 // -----------------------------------------------------------------------------
 // Constants & Types (linux/dma-buf.h)
@@ -26,9 +30,23 @@ pub struct DmaBufSync {
     pub flags: u64,
 }
 
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DmaBufExportSyncFile {
+    pub flags: u32,
+    pub fd: i32,
+}
+
 // Define the ioctl
 // _IOW(DMA_BUF_BASE, 0, struct dma_buf_sync)
 ioctl_write_ptr!(dma_buf_ioctl_sync, DMA_BUF_BASE, 0, DmaBufSync);
+// _IOWR(DMA_BUF_BASE, 2, struct dma_buf_export_sync_file), Linux 6.0+
+ioctl_readwrite!(
+    dma_buf_ioctl_export_sync_file,
+    DMA_BUF_BASE,
+    2,
+    DmaBufExportSyncFile
+);
 
 // -----------------------------------------------------------------------------
 // Error Handling
@@ -38,6 +56,8 @@ ioctl_write_ptr!(dma_buf_ioctl_sync, DMA_BUF_BASE, 0, DmaBufSync);
 pub enum DmaSyncError {
     SyncFailed(nix::Error),
     InvalidFd,
+    FenceExportFailed(nix::Error),
+    FenceTimeout(Duration),
 }
 
 impl fmt::Display for DmaSyncError {
@@ -45,6 +65,12 @@ impl fmt::Display for DmaSyncError {
         match self {
             DmaSyncError::SyncFailed(e) => write!(f, "DMA sync ioctl failed: {}", e),
             DmaSyncError::InvalidFd => write!(f, "Invalid file descriptor"),
+            DmaSyncError::FenceExportFailed(e) => {
+                write!(f, "Failed to export sync_file fence: {}", e)
+            },
+            DmaSyncError::FenceTimeout(timeout) => {
+                write!(f, "Fence not signaled within {:?}", timeout)
+            },
         }
     }
 }
@@ -117,3 +143,215 @@ impl DmaFence {
     // Legacy/Stub methods to match previous interface if needed mostly unused now
     // or we can adapt the caller.
 }
+
+// -----------------------------------------------------------------------------
+// Fences (Cross-Device Ordering)
+// -----------------------------------------------------------------------------
+
+/// Something that becomes signaled once, such as a dma-fence
+pub trait SyncPrimitive: Send + Sync {
+    /// Block until signaled or `timeout` passes; `Ok(false)` on timeout
+    fn wait(&self, timeout: Duration) -> Result<bool>;
+}
+
+/// Produces a fence covering the writes pending on a buffer right now
+pub trait FenceSource: Send + Sync {
+    fn export_fence(&self) -> Result<Arc<dyn SyncPrimitive>>;
+}
+
+/// Waitable handle for the writes that were pending when it was taken
+#[derive(Clone)]
+pub struct Fence(Arc<dyn SyncPrimitive>);
+
+/// Linux sync_file: polls readable once its dma-fences have signaled
+pub struct SyncFile {
+    fd: OwnedFd,
+}
+
+impl SyncFile {
+    /// Take ownership of a sync_file descriptor
+    pub fn from_owned_fd(fd: OwnedFd) -> Self {
+        Self { fd }
+    }
+}
+
+impl SyncPrimitive for SyncFile {
+    fn wait(&self, timeout: Duration) -> Result<bool> {
+        let mut pollfd = libc::pollfd {
+            fd: self.fd.as_raw_fd(),
+            events: libc::POLLIN,
+            revents: 0,
+        };
+        let timeout_ms = timeout.as_millis().min(i32::MAX as u128) as i32;
+        loop {
+            match unsafe { libc::poll(&mut pollfd, 1, timeout_ms) } {
+                0 => return Ok(false),
+                n if n > 0 => return Ok(true),
+                _ => {
+                    let err = std::io::Error::last_os_error();
+                    if err.kind() != std::io::ErrorKind::Interrupted {
+                        return Err(Box::new(err));
+                    }
+                },
+            }
+        }
+    }
+}
+
+/// Exports the implicit fences of a dma-buf as a sync_file
+struct DmaBufFenceSource {
+    fd: RawFd,
+}
+
+impl FenceSource for DmaBufFenceSource {
+    fn export_fence(&self) -> Result<Arc<dyn SyncPrimitive>> {
+        // READ asks for the fences a reader must wait for, i.e. pending writes
+        let mut args = DmaBufExportSyncFile {
+            flags: DMA_BUF_SYNC_READ as u32,
+            fd: -1,
+        };
+        unsafe {
+            dma_buf_ioctl_export_sync_file(self.fd, &mut args)
+                .map_err(|e| Box::new(DmaSyncError::FenceExportFailed(e)) as Box<dyn Error>)?;
+        }
+        let fd = unsafe { OwnedFd::from_raw_fd(args.fd) };
+        Ok(Arc::new(SyncFile::from_owned_fd(fd)))
+    }
+}
+
+/// Orders a consumer's reads after a producer's writes to a shared buffer
+///
+/// Take a `fence()` before reading and `wait` on it; once it returns, every
+/// write that was in flight when the fence was taken is visible.
+pub struct DmaSync {
+    source: Box<dyn FenceSource>,
+}
+
+impl DmaSync {
+    /// Fences from a dma-buf's reservation object
+    pub fn new(dmabuf_fd: RawFd) -> Result<Self> {
+        if dmabuf_fd < 0 {
+            return Err(Box::new(DmaSyncError::InvalidFd));
+        }
+        Ok(Self::with_source(DmaBufFenceSource { fd: dmabuf_fd }))
+    }
+
+    /// Fences from another sync primitive, e.g. a GPU API's or a mock
+    pub fn with_source(source: impl FenceSource + 'static) -> Self {
+        Self {
+            source: Box::new(source),
+        }
+    }
+
+    /// Fence for the writes pending on the buffer now
+    pub fn fence(&self) -> Result<Fence> {
+        self.source.export_fence().map(Fence)
+    }
+
+    /// Block until `fence` signals, or fail with `FenceTimeout`
+    pub fn wait(fence: &Fence, timeout: Duration) -> Result<()> {
+        if fence.0.wait(timeout)? {
+            Ok(())
+        } else {
+            Err(Box::new(DmaSyncError::FenceTimeout(timeout)))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU64, Ordering};
+    use std::sync::{Condvar, Mutex};
+    use std::thread;
+
+    /// dma-fence stand-in: a flag and a condvar
+    #[derive(Default)]
+    struct MockFence {
+        signaled: Mutex<bool>,
+        cond: Condvar,
+    }
+
+    impl MockFence {
+        fn signal(&self) {
+            *self.signaled.lock().unwrap() = true;
+            self.cond.notify_all();
+        }
+    }
+
+    impl SyncPrimitive for MockFence {
+        fn wait(&self, timeout: Duration) -> Result<bool> {
+            let signaled = self.signaled.lock().unwrap();
+            let (signaled, _) = self
+                .cond
+                .wait_timeout_while(signaled, timeout, |signaled| !*signaled)
+                .unwrap();
+            Ok(*signaled)
+        }
+    }
+
+    /// Buffer whose writer publishes one fence per write, like a
+    /// reservation object's exclusive fence
+    #[derive(Default)]
+    struct MockBuffer {
+        pending: Mutex<Option<Arc<MockFence>>>,
+    }
+
+    impl MockBuffer {
+        fn begin_write(&self) -> Arc<MockFence> {
+            let fence = Arc::new(MockFence::default());
+            *self.pending.lock().unwrap() = Some(fence.clone());
+            fence
+        }
+    }
+
+    impl FenceSource for Arc<MockBuffer> {
+        fn export_fence(&self) -> Result<Arc<dyn SyncPrimitive>> {
+            let fence = self.pending.lock().unwrap().clone().unwrap_or_else(|| {
+                let idle = Arc::new(MockFence::default());
+                idle.signal();
+                idle
+            });
+            Ok(fence)
+        }
+    }
+
+    #[test]
+    fn test_reader_waiting_on_fence_sees_whole_write() {
+        const WORDS: usize = 64;
+        let buffer = Arc::new(MockBuffer::default());
+        let data: Arc<Vec<AtomicU64>> = Arc::new((0..WORDS).map(|_| AtomicU64::new(0)).collect());
+        let sync = DmaSync::with_source(buffer.clone());
+
+        for generation in 1..=5u64 {
+            let write = buffer.begin_write();
+            let writer = {
+                let data = data.clone();
+                thread::spawn(move || {
+                    // Slow, word-by-word write so a racing reader would
+                    // catch it half done
+                    for word in data.iter() {
+                        word.store(generation, Ordering::Relaxed);
+                        thread::sleep(Duration::from_micros(200));
+                    }
+                    write.signal();
+                })
+            };
+
+            let fence = sync.fence().unwrap();
+            DmaSync::wait(&fence, Duration::from_secs(5)).unwrap();
+            assert!(data
+                .iter()
+                .all(|word| word.load(Ordering::Relaxed) == generation));
+            writer.join().unwrap();
+        }
+
+        // A write that never finishes times out instead of hanging
+        let _stuck = buffer.begin_write();
+        let err = DmaSync::wait(&sync.fence().unwrap(), Duration::from_millis(20)).unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<DmaSyncError>(),
+            Some(DmaSyncError::FenceTimeout(_))
+        ));
+    }
+}
@@ -4,7 +4,10 @@
 // Provides non-blocking fault event polling with tokio integration
 
 use crate::glass_ram::uffd_wrapper::{PageFaultEvent, UserfaultFd};
+use std::collections::VecDeque;
 use std::os::fd::{AsRawFd, FromRawFd};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 use tokio::sync::mpsc;
 
 /// Fault events from userfaultfd
@@ -35,16 +38,137 @@ pub enum FaultEvent {
     Unknown(u8),
 }
 
+/// A fault event with the time it was read from the userfaultfd, so whoever
+/// handles it can measure the latency up to the end of handling
+#[derive(Debug, Clone)]
+pub struct StampedFaultEvent {
+    pub event: FaultEvent,
+    pub read_at: Instant,
+}
+
+/// Upper bounds of the handling latency histogram buckets, in microseconds
+pub const LATENCY_BUCKETS_US: [u64; 8] = [10, 50, 100, 500, 1_000, 5_000, 10_000, 50_000];
+
+/// Faults per second are averaged over this trailing window
+const RATE_WINDOW: Duration = Duration::from_secs(5);
+
+/// Snapshot of fault handling activity
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FaultStats {
+    /// Events handled since the poller started
+    pub faults_total: u64,
+    /// Event rate over the last few seconds
+    pub faults_per_sec: f64,
+    /// `(upper bound in µs, count)` per bucket of `LATENCY_BUCKETS_US`, then
+    /// `(u64::MAX, count)` for anything slower
+    pub handle_latency_histogram: Vec<(u64, u64)>,
+}
+
+#[derive(Debug, Default)]
+struct StatsState {
+    faults_total: u64,
+    first_fault: Option<Instant>,
+    recent: VecDeque<Instant>,
+    latency_counts: [u64; LATENCY_BUCKETS_US.len() + 1],
+}
+
+/// Shared, clonable fault counter; clones see the same numbers, so a handle
+/// can be kept after the poller moves into its task
+#[derive(Debug, Clone, Default)]
+pub struct FaultStatsRecorder {
+    state: Arc<Mutex<StatsState>>,
+}
+
+impl FaultStatsRecorder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Count one event handled at `at` that took `latency` to handle
+    pub fn record(&self, at: Instant, latency: Duration) {
+        let mut state = self.state.lock().unwrap();
+        state.faults_total += 1;
+        state.first_fault.get_or_insert(at);
+        state.recent.push_back(at);
+        while state
+            .recent
+            .front()
+            .is_some_and(|&t| at.saturating_duration_since(t) > RATE_WINDOW)
+        {
+            state.recent.pop_front();
+        }
+
+        let micros = latency.as_micros() as u64;
+        let bucket = LATENCY_BUCKETS_US
+            .iter()
+            .position(|&bound| micros <= bound)
+            .unwrap_or(LATENCY_BUCKETS_US.len());
+        state.latency_counts[bucket] += 1;
+    }
+
+    /// Statistics as of `now`
+    pub fn stats_at(&self, now: Instant) -> FaultStats {
+        let state = self.state.lock().unwrap();
+
+        // Average over the window, or over the time since the first fault
+        // if that is shorter
+        let window_start = now.checked_sub(RATE_WINDOW).unwrap_or(now);
+        let span = match state.first_fault {
+            Some(first) => now.saturating_duration_since(first.max(window_start)),
+            None => Duration::ZERO,
+        };
+        let in_window = state
+            .recent
+            .iter()
+            .filter(|&&t| t >= window_start && t <= now)
+            .count();
+        let faults_per_sec = if span.is_zero() {
+            0.0
+        } else {
+            in_window as f64 / span.as_secs_f64()
+        };
+
+        let bounds = LATENCY_BUCKETS_US.iter().copied().chain([u64::MAX]);
+        FaultStats {
+            faults_total: state.faults_total,
+            faults_per_sec,
+            handle_latency_histogram: bounds.zip(state.latency_counts).collect(),
+        }
+    }
+
+    pub fn stats(&self) -> FaultStats {
+        self.stats_at(Instant::now())
+    }
+}
+
 /// Async fault event poller
 pub struct FaultPoller {
     uffd: UserfaultFd,
-    event_tx: mpsc::UnboundedSender<FaultEvent>,
+    event_tx: mpsc::UnboundedSender<StampedFaultEvent>,
+    stats: FaultStatsRecorder,
 }
 
 impl FaultPoller {
     /// Create a new fault poller
-    pub fn new(uffd: UserfaultFd, event_tx: mpsc::UnboundedSender<FaultEvent>) -> Self {
-        Self { uffd, event_tx }
+    pub fn new(uffd: UserfaultFd, event_tx: mpsc::UnboundedSender<StampedFaultEvent>) -> Self {
+        Self {
+            uffd,
+            event_tx,
+            stats: FaultStatsRecorder::new(),
+        }
+    }
+
+    /// Fault counts, rate and handling latency so far
+    pub fn stats(&self) -> FaultStats {
+        self.stats.stats()
+    }
+
+    /// Handle on the statistics that stays valid while `run` owns the poller
+    ///
+    /// The consumer of the events records each one here once it has been
+    /// handled.
+    pub fn stats_recorder(&self) -> FaultStatsRecorder {
+        self.stats.clone()
     }
 
     /// Run the async event loop
@@ -55,15 +179,7 @@ impl FaultPoller {
             let event_opt = self.read_event_async().await?;
 
             if let Some(event) = event_opt {
-                // Send event to channel
-                if let Err(e) = self.event_tx.send(event) {
-                    log::error!("Failed to send fault event: {}", e);
-                    // Send error via channel closing is not an IO error, but we need to return
-                    // map SendError to something generic or just break
-                    return Err(Box::new(std::io::Error::other(
-                        "Channel closed",
-                    )));
-                }
+                forward_event(&self.event_tx, event)?;
             }
 
             // Small delay to prevent busy-waiting
@@ -74,7 +190,7 @@ impl FaultPoller {
     /// Read event from userfaultfd asynchronously
     async fn read_event_async(
         &mut self,
-    ) -> Result<Option<FaultEvent>, Box<dyn std::error::Error + Send + Sync>> {
+    ) -> Result<Option<StampedFaultEvent>, Box<dyn std::error::Error + Send + Sync>> {
         // Use tokio's task::spawn_blocking for blocking read
         let uffd_fd = self.uffd.as_raw_fd();
 
@@ -91,6 +207,7 @@ impl FaultPoller {
             let mut buf = [0u8; mem::size_of::<crate::glass_ram::uffd_wrapper::UffdMsg>()];
             match file.read(&mut buf) {
                 Ok(n) => {
+                    let read_at = Instant::now();
                     if n != mem::size_of::<crate::glass_ram::uffd_wrapper::UffdMsg>() {
                         // Prevent file from being closed
                         std::mem::forget(file);
@@ -180,7 +297,10 @@ impl FaultPoller {
                         },
                     };
 
-                    Ok(Some(fault_event))
+                    Ok(Some(StampedFaultEvent {
+                        event: fault_event,
+                        read_at,
+                    }))
                 },
                 Err(ref e) if e.kind() == std::io::ErrorKind::WouldBlock => {
                     // Prevent file from being closed
@@ -199,6 +319,21 @@ impl FaultPoller {
     }
 }
 
+/// Send a fault event that has just been read on to the channel
+fn forward_event(
+    event_tx: &mpsc::UnboundedSender<StampedFaultEvent>,
+    event: StampedFaultEvent,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    // Send event to channel
+    if let Err(e) = event_tx.send(event) {
+        log::error!("Failed to send fault event: {}", e);
+        // Send error via channel closing is not an IO error, but we need to return
+        // map SendError to something generic or just break
+        return Err(Box::new(std::io::Error::other("Channel closed")));
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -255,4 +390,71 @@ mod tests {
         let unknown = FaultEvent::Unknown(0xFF);
         assert!(matches!(unknown, FaultEvent::Unknown(_)));
     }
+
+    #[test]
+    fn test_fault_stats_rate_and_latency() {
+        let recorder = FaultStatsRecorder::new();
+        let start = Instant::now();
+
+        // 100 faults/sec for 8 seconds; every third one is slow
+        let mut at = start;
+        for i in 0..800 {
+            at = start + Duration::from_millis(10 * i);
+            let latency = if i % 3 == 0 {
+                Duration::from_millis(20)
+            } else {
+                Duration::from_micros(40)
+            };
+            recorder.record(at, latency);
+        }
+
+        let stats = recorder.stats_at(at);
+        assert_eq!(stats.faults_total, 800);
+        assert!(
+            (stats.faults_per_sec - 100.0).abs() < 5.0,
+            "{} faults/sec",
+            stats.faults_per_sec
+        );
+
+        let histogram = &stats.handle_latency_histogram;
+        assert_eq!(histogram.len(), LATENCY_BUCKETS_US.len() + 1);
+        assert_eq!(histogram.iter().map(|&(_, count)| count).sum::<u64>(), 800);
+        assert_eq!(histogram[1], (50, 533));
+        assert_eq!(histogram[7], (50_000, 267));
+
+        // The rate falls off once faults stop
+        let idle = recorder.stats_at(at + RATE_WINDOW * 2);
+        assert_eq!(idle.faults_per_sec, 0.0);
+        assert_eq!(idle.faults_total, 800);
+    }
+
+    #[test]
+    fn test_forward_event_keeps_the_read_time() {
+        let (event_tx, mut event_rx) = mpsc::unbounded_channel();
+        let read_at = Instant::now();
+        let event = FaultEvent::PageFault {
+            address: 0x1000,
+            flags: 0,
+            thread_id: None,
+        };
+        forward_event(&event_tx, StampedFaultEvent { event, read_at }).unwrap();
+
+        let received = event_rx.try_recv().unwrap();
+        assert_eq!(received.read_at, read_at);
+        assert!(matches!(
+            received.event,
+            FaultEvent::PageFault {
+                address: 0x1000,
+                ..
+            }
+        ));
+
+        // A closed channel is an error
+        drop(event_rx);
+        let unknown = StampedFaultEvent {
+            event: FaultEvent::Unknown(0),
+            read_at,
+        };
+        assert!(forward_event(&event_tx, unknown).is_err());
+    }
 }
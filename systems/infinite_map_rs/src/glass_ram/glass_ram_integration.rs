@@ -4,6 +4,7 @@
 // Integrates fault polling with compressed sensing reconstruction for real-time memory visualization
 
 use crate::glass_ram::compressed_sensing::CompressedSensingReconstructor;
+use crate::glass_ram::density_renderer::{Colormap, DensityRenderer, DensityRendererConfig};
 use crate::glass_ram::fault_poller::FaultEvent;
 use crate::glass_ram::hilbert_skilling::Hilbert3D;
 use ndarray::Array2;
@@ -21,6 +22,8 @@ pub struct GlassRamConfig {
     pub decay_factor: f32,
     /// Batch size for reconstruction
     pub batch_size: usize,
+    /// Resolution vs accuracy trade-offs
+    pub sampling: IntegrationConfig,
 }
 
 impl Default for GlassRamConfig {
@@ -30,10 +33,48 @@ impl Default for GlassRamConfig {
             sparsity_threshold: 0.1, // 10% Nyquist constraint
             decay_factor: 0.9,       // 10% decay per frame
             batch_size: 100,         // Process 100 faults per batch
+            sampling: IntegrationConfig::default(),
         }
     }
 }
 
+/// Sampling knobs for huge address spaces: trade fidelity for speed
+#[derive(Debug, Clone)]
+pub struct IntegrationConfig {
+    /// Pages per density sample (1 = every page); larger strides cover
+    /// more memory with the same texture
+    pub sample_stride: u64,
+    /// Smear faults over Hilbert neighbors (true) or count raw hits (false)
+    pub compressed_sensing: bool,
+    /// Colormap for rendering the density map
+    pub colormap: Colormap,
+    /// Largest texture side; the Hilbert order is reduced to fit
+    pub max_texture_size: u32,
+}
+
+impl Default for IntegrationConfig {
+    fn default() -> Self {
+        Self {
+            sample_stride: 1,
+            compressed_sensing: true,
+            colormap: Colormap::Heat,
+            max_texture_size: 8192, // wgpu's default max_texture_dimension_2d
+        }
+    }
+}
+
+/// Largest Hilbert order whose 3D curve index (3 bits per level) fits in u64
+pub const MAX_HILBERT_ORDER: u32 = 21;
+
+impl IntegrationConfig {
+    /// Largest Hilbert order up to `requested` whose texture fits and whose
+    /// curve index fits in u64
+    pub fn effective_order(&self, requested: u32) -> u32 {
+        let max_order = 31 - self.max_texture_size.max(1).leading_zeros();
+        requested.min(max_order).min(MAX_HILBERT_ORDER)
+    }
+}
+
 /// Integrated Glass RAM system
 ///
 /// Combines fault event polling with compressed sensing reconstruction
@@ -53,18 +94,42 @@ impl GlassRamIntegration {
     }
 
     /// Create a new Glass RAM integration with custom configuration
-    pub fn with_config(config: GlassRamConfig) -> Self {
-        let reconstructor =
-            CompressedSensingReconstructor::new(config.hilbert_order, config.sparsity_threshold);
-        let hilbert = Hilbert3D::new(config.hilbert_order);
+    pub fn with_config(mut config: GlassRamConfig) -> Self {
+        config.sampling.sample_stride = config.sampling.sample_stride.max(1);
+        let order = config.sampling.effective_order(config.hilbert_order);
+        if order < config.hilbert_order {
+            log::info!(
+                "Glass RAM: Hilbert order {} exceeds max texture size {} or order {}, using {}",
+                config.hilbert_order,
+                config.sampling.max_texture_size,
+                MAX_HILBERT_ORDER,
+                order
+            );
+            config.hilbert_order = order;
+        }
 
-        Self {
+        let reconstructor = CompressedSensingReconstructor::new(order, config.sparsity_threshold)
+            .with_sampling(
+                config.sampling.sample_stride,
+                config.sampling.compressed_sensing,
+            );
+        let hilbert = Hilbert3D::new(order);
+
+        let integration = Self {
             config: config.clone(),
             reconstructor,
             hilbert,
             fault_buffer: Vec::with_capacity(config.batch_size),
             frame_count: 0,
-        }
+        };
+        log::info!(
+            "Glass RAM: {0}x{0} density map, 1 sample per {1} pages, covering {2} pages ({3} MiB)",
+            integration.resolution(),
+            config.sampling.sample_stride,
+            integration.coverage_pages(),
+            integration.coverage_pages().saturating_mul(4) / 1024
+        );
+        integration
     }
 
     /// Process a single fault event
@@ -140,16 +205,16 @@ impl GlassRamIntegration {
 
     /// Map a memory address to Hilbert 3D coordinates
     pub fn address_to_hilbert(&self, address: u64) -> (u32, u32, u32) {
-        let page_idx = (address >> 12) as u32;
-        let max_idx = 1u32 << (self.config.hilbert_order * 3);
-        let scaled_idx = page_idx % max_idx;
-        self.hilbert.d_to_xyz(scaled_idx as u64)
+        let page_idx = address >> 12;
+        let max_idx = 1u64 << (self.config.hilbert_order * 3);
+        let scaled_idx = (page_idx / self.config.sampling.sample_stride) % max_idx;
+        self.hilbert.d2xyz(scaled_idx)
     }
 
     /// Map Hilbert 3D coordinates to a memory address (approximate)
     pub fn hilbert_to_address(&self, x: u32, y: u32, z: u32) -> u64 {
-        let d = self.hilbert.xyz_to_d(x, y, z);
-        (d as u64) << 12 // Convert to byte address (4KB pages)
+        let d = self.hilbert.xyz2d(x, y, z);
+        (d * self.config.sampling.sample_stride) << 12 // Convert to byte address (4KB pages)
     }
 
     /// Get the resolution of the density map
@@ -157,6 +222,36 @@ impl GlassRamIntegration {
         1 << self.config.hilbert_order
     }
 
+    /// Pages mapped before addresses wrap around the curve
+    pub fn coverage_pages(&self) -> u64 {
+        (1u64 << (self.config.hilbert_order * 3)).saturating_mul(self.config.sampling.sample_stride)
+    }
+
+    /// Renderer settings matching the sampling config
+    pub fn density_renderer_config(&self) -> DensityRendererConfig {
+        DensityRendererConfig {
+            colormap: self.config.sampling.colormap,
+            ..Default::default()
+        }
+    }
+
+    /// Renderer for this integration's density map, sized to its resolution
+    /// and using its colormap
+    pub fn create_density_renderer(
+        &self,
+        device: Arc<wgpu::Device>,
+        queue: Arc<wgpu::Queue>,
+        texture_format: wgpu::TextureFormat,
+    ) -> DensityRenderer {
+        DensityRenderer::new(
+            device,
+            queue,
+            self.density_renderer_config(),
+            self.resolution() as u32,
+            texture_format,
+        )
+    }
+
     /// Reset the integration state
     pub fn reset(&mut self) {
         self.fault_buffer.clear();
@@ -391,6 +486,92 @@ mod tests {
         assert_eq!(stats.resolution, 1024);
     }
 
+    #[test]
+    fn test_large_stride_covers_region_at_reduced_resolution() {
+        // 64 GiB of 4 KiB pages, sampled 1 in 64 pages onto at most 64x64
+        const REGION_PAGES: u64 = 16 * 1024 * 1024;
+        const STRIDE: u64 = 64;
+        let mut integration = GlassRamIntegration::with_config(GlassRamConfig {
+            batch_size: 1024,
+            sampling: IntegrationConfig {
+                sample_stride: STRIDE,
+                compressed_sensing: false,
+                colormap: Colormap::Viridis,
+                max_texture_size: 64,
+            },
+            ..Default::default()
+        });
+
+        assert_eq!(integration.resolution(), 64);
+        assert!(integration.coverage_pages() >= REGION_PAGES);
+
+        // One fault per 256 samples, spread over the whole region
+        let faults: Vec<FaultEvent> = (0..1024u64)
+            .map(|i| FaultEvent::PageFault {
+                address: (i * REGION_PAGES / 1024) << 12,
+                flags: 0x1,
+                thread_id: None,
+            })
+            .collect();
+        integration.process_faults(&faults);
+
+        let density = integration.density_map();
+        assert_eq!(density.dim(), (64, 64));
+        // Every fault lands on the map, unsmeared
+        assert!((density.sum() - 1024.0).abs() < 1e-3);
+        // and spread over the whole region rather than wrapping onto a corner
+        let last = faults.last().unwrap();
+        let FaultEvent::PageFault { address, .. } = last else {
+            unreachable!()
+        };
+        let (x, y, z) = integration.address_to_hilbert(*address);
+        let d = integration.hilbert().xyz2d(x, y, z);
+        assert_eq!(d, (*address >> 12) / STRIDE);
+
+        assert_eq!(
+            integration.density_renderer_config().colormap,
+            Colormap::Viridis
+        );
+    }
+
+    #[test]
+    fn test_effective_order_keeps_3d_index_in_u64() {
+        let sampling = IntegrationConfig {
+            max_texture_size: u32::MAX,
+            ..Default::default()
+        };
+        assert_eq!(sampling.effective_order(32), MAX_HILBERT_ORDER);
+        assert_eq!(sampling.effective_order(12), 12);
+        assert!(1u64.checked_shl(MAX_HILBERT_ORDER * 3).is_some());
+    }
+
+    #[test]
+    fn test_density_renderer_uses_integration_colormap_and_resolution() {
+        let Some((device, queue)) = crate::tests::gpu::test_device() else {
+            return;
+        };
+        let mut integration = GlassRamIntegration::with_config(GlassRamConfig {
+            batch_size: 1,
+            sampling: IntegrationConfig {
+                colormap: Colormap::Magma,
+                max_texture_size: 64,
+                ..Default::default()
+            },
+            ..Default::default()
+        });
+        integration.process_fault(FaultEvent::PageFault {
+            address: 0x1000,
+            flags: 0x1,
+            thread_id: None,
+        });
+
+        let renderer =
+            integration.create_density_renderer(device, queue, wgpu::TextureFormat::Rgba8UnormSrgb);
+        assert_eq!(renderer.config().colormap, Colormap::Magma);
+        assert_eq!(renderer.resolution(), 64);
+        renderer.update_density_map(integration.density_map());
+    }
+
     #[test]
     fn test_display_stats() {
         let integration = GlassRamIntegration::new();
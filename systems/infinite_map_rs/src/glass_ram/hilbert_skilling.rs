@@ -1,109 +1,186 @@
 // systems/infinite_map_rs/src/glass_ram/hilbert_skilling.rs
 //
-// Skilling's 3D Hilbert Curve Implementation
+// Skilling's Hilbert Curve Implementation
 // based on "Programming the Hilbert Curve" by John Skilling (2004)
 //
 // This module implements the Hilbert curve mapping using efficient bitwise operations
-// and Gray code transpositions, achieving O(B*D) complexity where B is bits and D is dimensions (3).
+// and Gray code transpositions, achieving O(B*D) complexity where B is bits and D is dimensions.
+// Any dimension and order works as long as the index fits in 64 bits; in 2D the
+// curve is the same one `crate::hilbert::d2xy` draws.
 
-/// Implementation of Skilling's algorithm for 3D Hilbert curves
-#[derive(Debug, Clone, Copy)]
-pub struct Hilbert3D {
+/// Widest supported index: dims × bits may not exceed this
+pub const MAX_INDEX_BITS: u32 = 64;
+
+/// Skilling's Hilbert curve over `dims` dimensions of `bits` bits each
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HilbertCurve {
+    dims: u32,
     bits: u32,
 }
 
-impl Hilbert3D {
-    /// Create a new 3D Hilbert mapper for a given number of bits per dimension
-    /// Total curve length will be 2^(3*bits)
-    pub fn new(bits: u32) -> Self {
-        Self { bits }
+impl HilbertCurve {
+    /// Create a curve filling a cube of side 2^bits in `dims` dimensions
+    ///
+    /// # Panics
+    ///
+    /// Panics if either is zero, `bits` exceeds 32, or `dims * bits`
+    /// exceeds `MAX_INDEX_BITS`.
+    pub fn new(dims: u32, bits: u32) -> Self {
+        assert!(dims > 0 && bits > 0, "Hilbert curve needs dims and bits");
+        assert!(bits <= 32, "coordinates are u32, got {} bits", bits);
+        assert!(
+            dims * bits <= MAX_INDEX_BITS,
+            "{} dimensions of {} bits do not fit a u64 index",
+            dims,
+            bits
+        );
+        Self { dims, bits }
     }
 
-    /// Convert 3D coordinates to Hilbert distance (index)
-    /// Coordinates (x, y, z) must each be < 2^bits
-    pub fn xyz_to_d(&self, x: u32, y: u32, z: u32) -> u64 {
-        let mut coords = [x, y, z];
-
-        // 1. Transform Axes to Transposed (Gray code components)
-        self.axes_to_transpose(&mut coords);
-
-        // 2. Bit Interleave
-        // Skilling's order: "Inverse of distribution"
-        // Interleave: bit i of coords[d] -> bit 3*i + (2-d) of h (Big Endian Interleave for Skilling)
-
-        let mut interleave = 0u64;
-        for i in 0..self.bits {
-            for d in 0..3 {
-                if (coords[d as usize] & (1 << i)) != 0 {
-                    // Skilling usually interleaves: z_i y_i x_i ...
-                    // Let's use standard: ... z0 y0 x0
-                    interleave |= 1 << (3 * i + d);
-                }
+    pub fn dims(&self) -> u32 {
+        self.dims
+    }
+
+    /// Bits per coordinate (the curve's order)
+    pub fn bits(&self) -> u32 {
+        self.bits
+    }
+
+    /// Cells along each axis
+    pub fn side(&self) -> u64 {
+        1 << self.bits
+    }
+
+    /// Convert a Hilbert distance to coordinates, written to `axes`
+    /// (one per dimension)
+    pub fn index_to_axes(&self, d: u64, axes: &mut [u32]) {
+        assert_eq!(axes.len(), self.dims as usize);
+
+        // De-interleave: the index holds bit b-1 of every transposed
+        // coordinate, then bit b-2, and so on
+        axes.fill(0);
+        let mut k = self.dims * self.bits;
+        for j in (0..self.bits).rev() {
+            for x in axes.iter_mut() {
+                k -= 1;
+                *x |= (((d >> k) & 1) as u32) << j;
             }
         }
 
-        // 3. Gray Decode (Gray to Binary)
-        let mut final_h = interleave;
-        let mut shifts = 1;
-        while shifts < (3 * self.bits) {
-            final_h ^= final_h >> shifts;
-            shifts <<= 1;
-        }
+        self.transpose_to_axes(axes);
+    }
+
+    /// Convert coordinates (each < 2^bits) to their Hilbert distance
+    pub fn axes_to_index(&self, axes: &[u32]) -> u64 {
+        assert_eq!(axes.len(), self.dims as usize);
+
+        let mut x = axes.to_vec();
+        self.axes_to_transpose(&mut x);
 
-        final_h
+        let mut d = 0u64;
+        for j in (0..self.bits).rev() {
+            for &xi in &x {
+                d = (d << 1) | ((xi >> j) & 1) as u64;
+            }
+        }
+        d
     }
 
-    /// Skilling's un-rotation ("Inverse") function
-    /// Modifies coordinates in-place to the "transposed" state (Gray code components)
-    fn axes_to_transpose(&self, x: &mut [u32; 3]) {
-        let n = 3; // dimensions
-                   // Iterate bits from MSB to LSB
-        let mut m = 1u32 << (self.bits - 1);
+    /// Skilling's TransposetoAxes: transposed index form to coordinates
+    fn transpose_to_axes(&self, x: &mut [u32]) {
+        let n = x.len();
 
-        while m > 0 {
-            // Loop through dimensions from (n-1) down to 0
-            // (Skilling C code loop direction)
+        // Gray decode by H ^ (H / 2)
+        let t = x[n - 1] >> 1;
+        for i in (1..n).rev() {
+            x[i] ^= x[i - 1];
+        }
+        x[0] ^= t;
 
-            let mut i = (n - 1) as isize;
-            while i >= 0 {
-                // If the bit at M is set for dimension I
-                if (x[i as usize] & m) != 0 {
-                    // Intra-bit exchange (XOR swap) with lower dimension 0
-                    let t = (x[0] ^ x[i as usize]) & (m - 1);
+        // Undo excess work
+        for q_bit in 1..self.bits {
+            let q = 1u32 << q_bit;
+            let p = q - 1;
+            for i in (0..n).rev() {
+                if x[i] & q != 0 {
+                    // Invert
+                    x[0] ^= p;
+                } else {
+                    // Exchange
+                    let t = (x[0] ^ x[i]) & p;
                     x[0] ^= t;
-                    x[i as usize] ^= t;
+                    x[i] ^= t;
                 }
-                i -= 1;
             }
-            m >>= 1;
         }
     }
 
-    /// Convert Hilbert distance (index) to 3D coordinates
-    pub fn d_to_xyz(&self, d: u64) -> (u32, u32, u32) {
-        let mut h = d;
-
-        // 1. Binary to Gray
-        h ^= h >> 1;
+    /// Skilling's AxestoTranspose: coordinates to transposed index form
+    fn axes_to_transpose(&self, x: &mut [u32]) {
+        let n = x.len();
 
-        // 2. De-interleave bits to get "Transposed" coordinates
-        let mut x = [0u32; 3];
-
-        for i in 0..self.bits {
-            for d in 0..3 {
-                if (h & (1 << (3 * i + d))) != 0 {
-                    x[d as usize] |= 1 << i;
+        // Inverse undo
+        for q_bit in (1..self.bits).rev() {
+            let q = 1u32 << q_bit;
+            let p = q - 1;
+            for i in 0..n {
+                if x[i] & q != 0 {
+                    x[0] ^= p;
+                } else {
+                    let t = (x[0] ^ x[i]) & p;
+                    x[0] ^= t;
+                    x[i] ^= t;
                 }
             }
         }
 
-        // 3. Apply Skilling's rotation logic (Transpose to Axes)
-        // Note: For Skilling's method, the transformation is self-inverse.
-        // We use the same axes_to_transpose function (which swaps bits based on higher bits).
+        // Gray encode
+        for i in 1..n {
+            x[i] ^= x[i - 1];
+        }
+        let mut t = 0;
+        for q_bit in (1..self.bits).rev() {
+            let q = 1u32 << q_bit;
+            if x[n - 1] & q != 0 {
+                t ^= q - 1;
+            }
+        }
+        for xi in x.iter_mut() {
+            *xi ^= t;
+        }
+    }
+}
+
+/// 3D Hilbert curve for volumetric memory visualization
+#[derive(Debug, Clone, Copy)]
+pub struct Hilbert3D {
+    curve: HilbertCurve,
+}
 
-        self.axes_to_transpose(&mut x);
+impl Hilbert3D {
+    /// Create a new 3D Hilbert mapper for a given number of bits per dimension
+    /// Total curve length will be 2^(3*bits)
+    pub fn new(bits: u32) -> Self {
+        Self {
+            curve: HilbertCurve::new(3, bits),
+        }
+    }
 
-        (x[0], x[1], x[2])
+    pub fn curve(&self) -> &HilbertCurve {
+        &self.curve
+    }
+
+    /// Convert 3D coordinates to Hilbert distance (index)
+    /// Coordinates (x, y, z) must each be < 2^bits
+    pub fn xyz2d(&self, x: u32, y: u32, z: u32) -> u64 {
+        self.curve.axes_to_index(&[x, y, z])
+    }
+
+    /// Convert Hilbert distance (index) to 3D coordinates
+    pub fn d2xyz(&self, d: u64) -> (u32, u32, u32) {
+        let mut axes = [0u32; 3];
+        self.curve.index_to_axes(d, &mut axes);
+        (axes[0], axes[1], axes[2])
     }
 }
 
@@ -112,15 +189,15 @@ mod tests {
     use super::*;
 
     #[test]
-    #[ignore = "Hilbert roundtrip algorithm needs investigation - d_to_xyz/xyz_to_d not inverses"]
     fn test_skilling_roundtrip() {
         let mapper = Hilbert3D::new(4); // 4 bits = 16x16x16 cube
         let limit = 16 * 16 * 16;
 
+        let mut seen = vec![false; limit];
         for i in 0..limit {
             let index = i as u64;
-            let (x, y, z) = mapper.d_to_xyz(index);
-            let index2 = mapper.xyz_to_d(x, y, z);
+            let (x, y, z) = mapper.d2xyz(index);
+            let index2 = mapper.xyz2d(x, y, z);
 
             assert_eq!(index, index2, "Roundtrip failed at {}", index);
 
@@ -128,19 +205,58 @@ mod tests {
             assert!(x < 16);
             assert!(y < 16);
             assert!(z < 16);
+
+            // Every cell is visited exactly once
+            let cell = (x * 256 + y * 16 + z) as usize;
+            assert!(!seen[cell], "({}, {}, {}) visited twice", x, y, z);
+            seen[cell] = true;
         }
     }
 
     #[test]
     fn test_locality() {
         let mapper = Hilbert3D::new(3); // 8x8x8
-        let (x1, y1, z1) = mapper.d_to_xyz(0);
-        let (x2, y2, z2) = mapper.d_to_xyz(1);
+        for d in 1..512 {
+            let (x1, y1, z1) = mapper.d2xyz(d - 1);
+            let (x2, y2, z2) = mapper.d2xyz(d);
+
+            let dist = (x1 as i32 - x2 as i32).abs()
+                + (y1 as i32 - y2 as i32).abs()
+                + (z1 as i32 - z2 as i32).abs();
+
+            assert_eq!(dist, 1, "Hilbert curve must be continuous (dist=1)");
+        }
+    }
 
-        let dist = (x1 as i32 - x2 as i32).abs()
-            + (y1 as i32 - y2 as i32).abs()
-            + (z1 as i32 - z2 as i32).abs();
+    #[test]
+    fn test_2d_matches_canonical_hilbert() {
+        for bits in 1..=6 {
+            let curve = HilbertCurve::new(2, bits);
+            let n = curve.side() as u32;
+            let mut axes = [0u32; 2];
+            for d in 0..(n as u64 * n as u64) {
+                curve.index_to_axes(d, &mut axes);
+                assert_eq!((axes[0], axes[1]), crate::hilbert::d2xy(n, d));
+                assert_eq!(curve.axes_to_index(&axes), d);
+            }
+        }
+    }
 
-        assert_eq!(dist, 1, "Hilbert curve must be continuous (dist=1)");
+    #[test]
+    fn test_configurable_dimensions() {
+        // 4D, and the widest index that still fits
+        let curve = HilbertCurve::new(4, 3);
+        let mut axes = [0u32; 4];
+        for d in 0..(1 << 12) {
+            curve.index_to_axes(d, &mut axes);
+            assert_eq!(curve.axes_to_index(&axes), d);
+        }
+
+        let wide = HilbertCurve::new(2, 32);
+        let mut axes = [0u32; 2];
+        for d in [0, 1, u64::MAX / 3, u64::MAX] {
+            wide.index_to_axes(d, &mut axes);
+            assert_eq!(wide.axes_to_index(&axes), d);
+        }
     }
 }
@@ -1,14 +1,19 @@
-use crate::glass_ram::fault_poller::{FaultEvent, FaultPoller};
+use crate::glass_ram::fault_poller::{
+    FaultEvent, FaultPoller, FaultStats, FaultStatsRecorder, StampedFaultEvent,
+};
 use crate::glass_ram::hilbert_skilling::Hilbert3D;
 use crate::glass_ram::process_attacher::ProcessAttacher;
 use nix::unistd::Pid;
+use std::time::Instant;
 use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
 
-#[allow(dead_code)] // attacher - process attachment scaffolding for future monitoring
 pub struct GlassRamMonitor {
     attacher: ProcessAttacher,
     poller: Option<FaultPoller>,
-    event_rx: mpsc::UnboundedReceiver<FaultEvent>,
+    poller_task: Option<JoinHandle<()>>,
+    fault_stats: FaultStatsRecorder,
+    event_rx: mpsc::UnboundedReceiver<StampedFaultEvent>,
     hilbert: Hilbert3D,
 }
 
@@ -20,6 +25,7 @@ impl GlassRamMonitor {
 
         let uffd_clone = attacher.uffd().try_clone()?;
         let poller = FaultPoller::new(uffd_clone, event_tx);
+        let fault_stats = poller.stats_recorder();
 
         // Initialize Hilbert curve with 10 bits per dimension (1024x1024x1024 grid)
         // This covers 4KB pages for up to 4TB of address space (2^30 * 4KB = 4TB)
@@ -28,19 +34,26 @@ impl GlassRamMonitor {
         Ok(Self {
             attacher,
             poller: Some(poller),
+            poller_task: None,
+            fault_stats,
             event_rx,
             hilbert,
         })
     }
 
+    /// Fault rate, and latency from reading each fault to having handled it
+    pub fn fault_stats(&self) -> FaultStats {
+        self.fault_stats.stats()
+    }
+
     pub async fn run(&mut self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
         // Spawn fault polling task
         if let Some(mut poller) = self.poller.take() {
-            tokio::spawn(async move {
+            self.poller_task = Some(tokio::spawn(async move {
                 if let Err(e) = poller.run().await {
                     log::error!("Fault poller error: {}", e);
                 }
-            });
+            }));
         }
 
         // Process fault events
@@ -56,58 +69,121 @@ impl GlassRamMonitor {
         Ok(())
     }
 
-    fn handle_fault_event(&mut self, event: FaultEvent) -> Result<(), Box<dyn std::error::Error>> {
-        match event {
-            FaultEvent::PageFault {
+    /// Stop polling and unregister every region, so the target goes back to
+    /// normal page handling
+    ///
+    /// Idempotent, and also run on drop, so an error or panic in the monitor
+    /// cannot leave the target stuck on faults nobody serves.
+    pub fn detach(&mut self) -> Result<(), nix::Error> {
+        if let Some(task) = self.poller_task.take() {
+            task.abort();
+        }
+        self.poller = None;
+        self.attacher.detach()
+    }
+
+    fn handle_fault_event(
+        &mut self,
+        stamped: StampedFaultEvent,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        handle_fault_event(&self.hilbert, &self.fault_stats, stamped);
+        Ok(())
+    }
+}
+
+/// Handle one event, then record its latency from being read off the
+/// userfaultfd (time queued in the channel included) to now
+fn handle_fault_event(hilbert: &Hilbert3D, stats: &FaultStatsRecorder, stamped: StampedFaultEvent) {
+    match stamped.event {
+        FaultEvent::PageFault {
+            address,
+            flags: _flags,
+            thread_id,
+        } => {
+            // Calculate Hilbert coordinates
+            // Assume 4KB pages
+            let page_idx = address >> 12;
+            let (x, y, z) = hilbert.d2xyz(page_idx);
+
+            log::debug!(
+                "Page fault at 0x{:x} -> Hilbert({}, {}, {}) [Thread: {:?}]",
                 address,
-                flags: _flags,
-                thread_id,
-            } => {
-                // Calculate Hilbert coordinates
-                // Assume 4KB pages
-                let page_idx = address >> 12;
-                let (x, y, z) = self.hilbert.d_to_xyz(page_idx);
-
-                log::debug!(
-                    "Page fault at 0x{:x} -> Hilbert({}, {}, {}) [Thread: {:?}]",
-                    address,
-                    x,
-                    y,
-                    z,
-                    thread_id
-                );
-
-                // TODO: Update fault telemetry
-                // TODO: Send to visualization
-            },
-            FaultEvent::Fork {
-                parent_pid,
-                child_pid,
-            } => {
-                log::info!("Fork: {} -> {}", parent_pid, child_pid);
-            },
-            FaultEvent::Remap {
+                x,
+                y,
+                z,
+                thread_id
+            );
+
+            // TODO: Update fault telemetry
+            // TODO: Send to visualization
+        },
+        FaultEvent::Fork {
+            parent_pid,
+            child_pid,
+        } => {
+            log::info!("Fork: {} -> {}", parent_pid, child_pid);
+        },
+        FaultEvent::Remap {
+            old_address,
+            new_address,
+            length,
+        } => {
+            log::debug!(
+                "Remap: 0x{:x} -> 0x{:x} (len: {})",
                 old_address,
                 new_address,
-                length,
-            } => {
-                log::debug!(
-                    "Remap: 0x{:x} -> 0x{:x} (len: {})",
-                    old_address,
-                    new_address,
-                    length
-                );
-            },
-            FaultEvent::Remove { address, length } => {
-                log::debug!("Remove: 0x{:x} (len: {})", address, length);
-            },
-            FaultEvent::Unmap { address, length } => {
-                log::debug!("Unmap: 0x{:x} (len: {})", address, length);
-            },
-            FaultEvent::Unknown(e) => {
-                log::warn!("Unknown UFFD event: {}", e);
-            },
+                length
+            );
+        },
+        FaultEvent::Remove { address, length } => {
+            log::debug!("Remove: 0x{:x} (len: {})", address, length);
+        },
+        FaultEvent::Unmap { address, length } => {
+            log::debug!("Unmap: 0x{:x} (len: {})", address, length);
+        },
+        FaultEvent::Unknown(e) => {
+            log::warn!("Unknown UFFD event: {}", e);
+        },
+    }
+
+    let handled_at = Instant::now();
+    stats.record(
+        handled_at,
+        handled_at.saturating_duration_since(stamped.read_at),
+    );
+}
+
+impl Drop for GlassRamMonitor {
+    fn drop(&mut self) {
+        if let Err(e) = self.detach() {
+            log::error!("Failed to detach from process: {}", e);
         }
-        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_latency_runs_from_read_to_handled() {
+        let stats = FaultStatsRecorder::new();
+        let hilbert = Hilbert3D::new(10);
+
+        // Read 20ms ago, then left waiting in the channel
+        let read_at = Instant::now() - Duration::from_millis(20);
+        let event = FaultEvent::PageFault {
+            address: 0x7f00_0000_1000,
+            flags: 0,
+            thread_id: Some(7),
+        };
+        handle_fault_event(&hilbert, &stats, StampedFaultEvent { event, read_at });
+
+        let stats = stats.stats();
+        assert_eq!(stats.faults_total, 1);
+        // Counted in a bucket above 10ms, not the microseconds handling took
+        let (bound, count) = stats.handle_latency_histogram[7];
+        assert_eq!((bound, count), (50_000, 1));
     }
 }
@@ -1,6 +1,208 @@
 use crate::glass_ram::process_maps::{filter_writable_regions, parse_proc_maps, MemoryRegion};
 use crate::glass_ram::uffd_wrapper::{UffdFeatureFlags, UffdFlags, UffdIoctlMode, UserfaultFd};
-use nix::unistd::Pid;
+use nix::unistd::{geteuid, Pid};
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+
+/// Linux capability number of CAP_SYS_PTRACE (linux/capability.h)
+const CAP_SYS_PTRACE: u32 = 19;
+
+const PTRACE_SCOPE_PATH: &str = "/proc/sys/kernel/yama/ptrace_scope";
+
+// -----------------------------------------------------------------------------
+// Error Handling
+// -----------------------------------------------------------------------------
+
+/// Why attaching to a process would fail, found before touching the target
+#[derive(Debug)]
+pub enum AttachError {
+    /// No process with this pid is visible in /proc
+    NoSuchProcess(i32),
+    /// The target runs as another user and the caller lacks CAP_SYS_PTRACE
+    NotOwner {
+        pid: i32,
+        target_uid: u32,
+        caller_uid: u32,
+    },
+    /// Yama ptrace_scope=1: only descendants may be attached to
+    NotDescendant { pid: i32 },
+    /// Yama ptrace_scope=2: only CAP_SYS_PTRACE may attach
+    AdminOnly { pid: i32 },
+    /// Yama ptrace_scope=3: attaching is disabled until reboot
+    AttachDisabled,
+    /// /proc could not be read
+    Io(io::Error),
+}
+
+impl AttachError {
+    /// What the user can do about it, if anything
+    pub fn remediation(&self) -> Option<&'static str> {
+        match self {
+            AttachError::NotOwner { .. } => Some(
+                "run Glass RAM as the same user as the target, or grant it the capability with `sudo setcap cap_sys_ptrace+ep <binary>`",
+            ),
+            AttachError::NotDescendant { .. } => Some(
+                "launch the target from Glass RAM, grant CAP_SYS_PTRACE with `sudo setcap cap_sys_ptrace+ep <binary>`, or relax Yama with `sudo sysctl kernel.yama.ptrace_scope=0`",
+            ),
+            AttachError::AdminOnly { .. } => Some(
+                "grant CAP_SYS_PTRACE with `sudo setcap cap_sys_ptrace+ep <binary>` or run as root",
+            ),
+            AttachError::AttachDisabled => Some(
+                "kernel.yama.ptrace_scope=3 cannot be lowered at runtime; set it to 0-2 in /etc/sysctl.d and reboot",
+            ),
+            AttachError::NoSuchProcess(_) | AttachError::Io(_) => None,
+        }
+    }
+}
+
+impl fmt::Display for AttachError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AttachError::NoSuchProcess(pid) => write!(f, "No such process: {}", pid)?,
+            AttachError::NotOwner {
+                pid,
+                target_uid,
+                caller_uid,
+            } => write!(
+                f,
+                "Permission denied: process {} belongs to uid {}, not uid {}",
+                pid, target_uid, caller_uid
+            )?,
+            AttachError::NotDescendant { pid } => write!(
+                f,
+                "Permission denied: kernel.yama.ptrace_scope=1 only allows attaching to descendants, and process {} is not one",
+                pid
+            )?,
+            AttachError::AdminOnly { pid } => write!(
+                f,
+                "Permission denied: kernel.yama.ptrace_scope=2 requires CAP_SYS_PTRACE to attach to process {}",
+                pid
+            )?,
+            AttachError::AttachDisabled => write!(
+                f,
+                "Permission denied: kernel.yama.ptrace_scope=3 disables attaching to any process"
+            )?,
+            AttachError::Io(e) => write!(f, "Failed to read process credentials: {}", e)?,
+        }
+        if let Some(fix) = self.remediation() {
+            write!(f, " (to fix: {})", fix)?;
+        }
+        Ok(())
+    }
+}
+
+impl Error for AttachError {}
+
+impl From<io::Error> for AttachError {
+    fn from(e: io::Error) -> Self {
+        AttachError::Io(e)
+    }
+}
+
+/// Who is asking to attach
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AttachCredentials {
+    pub pid: i32,
+    pub euid: u32,
+    pub cap_sys_ptrace: bool,
+}
+
+impl AttachCredentials {
+    /// Credentials of the current process
+    pub fn current() -> Result<Self, AttachError> {
+        let status = fs::read_to_string("/proc/self/status")?;
+        let cap_eff = status_field(&status, "CapEff")
+            .and_then(|caps| u64::from_str_radix(caps, 16).ok())
+            .unwrap_or(0);
+        Ok(Self {
+            pid: std::process::id() as i32,
+            euid: geteuid().as_raw(),
+            cap_sys_ptrace: cap_eff & (1 << CAP_SYS_PTRACE) != 0,
+        })
+    }
+}
+
+/// Value of a `Name:\tvalue` line in /proc/<pid>/status
+fn status_field<'a>(status: &'a str, name: &str) -> Option<&'a str> {
+    status.lines().find_map(|line| {
+        line.strip_prefix(name)
+            .and_then(|rest| rest.strip_prefix(':'))
+            .map(str::trim)
+    })
+}
+
+fn read_status(pid: i32) -> Result<String, AttachError> {
+    fs::read_to_string(format!("/proc/{}/status", pid)).map_err(|e| match e.kind() {
+        io::ErrorKind::NotFound => AttachError::NoSuchProcess(pid),
+        _ => AttachError::Io(e),
+    })
+}
+
+/// Current Yama ptrace_scope, or `None` if Yama is not enabled
+pub fn ptrace_scope() -> Option<u8> {
+    fs::read_to_string(PTRACE_SCOPE_PATH)
+        .ok()
+        .and_then(|scope| scope.trim().parse().ok())
+}
+
+/// Check the kernel's ptrace access rules for `caller` attaching to `pid`
+/// under the given Yama scope (`None` behaves like scope 0)
+pub fn check_attach(
+    pid: i32,
+    caller: &AttachCredentials,
+    ptrace_scope: Option<u8>,
+) -> Result<(), AttachError> {
+    let status = read_status(pid)?;
+    let scope = ptrace_scope.unwrap_or(0);
+
+    if scope >= 3 {
+        return Err(AttachError::AttachDisabled);
+    }
+    if caller.cap_sys_ptrace {
+        return Ok(());
+    }
+    if scope == 2 {
+        return Err(AttachError::AdminOnly { pid });
+    }
+
+    // Real, effective and saved uid must all match the caller
+    let uids: Vec<u32> = status_field(&status, "Uid")
+        .map(|uids| {
+            uids.split_whitespace()
+                .filter_map(|u| u.parse().ok())
+                .collect()
+        })
+        .unwrap_or_default();
+    if let Some(&other) = uids.iter().take(3).find(|&&uid| uid != caller.euid) {
+        return Err(AttachError::NotOwner {
+            pid,
+            target_uid: other,
+            caller_uid: caller.euid,
+        });
+    }
+
+    if scope == 1 && !is_descendant(pid, caller.pid)? {
+        return Err(AttachError::NotDescendant { pid });
+    }
+    Ok(())
+}
+
+/// Walk the parent chain of `pid` looking for `ancestor`
+fn is_descendant(pid: i32, ancestor: i32) -> Result<bool, AttachError> {
+    let mut current = pid;
+    while current > 1 {
+        let parent = status_field(&read_status(current)?, "PPid")
+            .and_then(|ppid| ppid.parse().ok())
+            .unwrap_or(0);
+        if parent == ancestor {
+            return Ok(true);
+        }
+        current = parent;
+    }
+    Ok(false)
+}
 
 pub struct ProcessAttacher {
     pid: Pid,
@@ -9,7 +211,15 @@ pub struct ProcessAttacher {
 }
 
 impl ProcessAttacher {
+    /// Check whether this process may attach to `pid`, explaining how to fix
+    /// it if not. `attach` runs this first.
+    pub fn can_attach(pid: Pid) -> Result<(), AttachError> {
+        check_attach(pid.as_raw(), &AttachCredentials::current()?, ptrace_scope())
+    }
+
     pub fn attach(pid: Pid) -> Result<Self, Box<dyn std::error::Error>> {
+        Self::can_attach(pid)?;
+
         // Create userfaultfd with required features
         let flags = UffdFlags::CLOEXEC | UffdFlags::NONBLOCK;
         let uffd = UserfaultFd::new(flags)?;
@@ -37,6 +247,14 @@ impl ProcessAttacher {
         let all_regions = parse_proc_maps(pid.as_raw() as u32)?;
         let writable_regions = filter_writable_regions(&all_regions);
 
+        // Regions are unregistered again when this is dropped, including
+        // when a later registration fails
+        let mut attacher = Self {
+            pid,
+            uffd,
+            regions: Vec::new(),
+        };
+
         // Register each writable region
         for region in &writable_regions {
             let _range = UffdIoctlMode::REGISTER_MODE_WP; // Use WP mode for registration if available?
@@ -50,11 +268,8 @@ impl ProcessAttacher {
             // Note: register() might fail if address is not in OUR address space (if local UFFD)
             // But we implement as spec'd.
 
-            // We need to map `region.start` and `region.end` to length
-            let len = region.end - region.start;
-
             // Should catch error here?
-            if let Err(e) = uffd.register(region.start, len, mode) {
+            if let Err(e) = attacher.register_region(region, mode) {
                 // Log and continue? or fail?
                 // "Glass RAM Monitor" loop might handle errors.
                 // For now, simple propagation or log.
@@ -64,14 +279,44 @@ impl ProcessAttacher {
             }
         }
 
-        // Clone regions for storage
-        let regions_owned: Vec<MemoryRegion> = writable_regions.into_iter().cloned().collect();
+        Ok(attacher)
+    }
 
-        Ok(Self {
-            pid,
-            uffd,
-            regions: regions_owned,
-        })
+    /// Register `region` with the userfaultfd and remember it for `detach`
+    fn register_region(
+        &mut self,
+        region: &MemoryRegion,
+        mode: UffdIoctlMode,
+    ) -> Result<(), nix::Error> {
+        self.uffd
+            .register(region.start, region.end - region.start, mode)?;
+        self.regions.push(region.clone());
+        Ok(())
+    }
+
+    /// Unregister every region, returning the target to normal page
+    /// handling
+    ///
+    /// Safe to call more than once; `Drop` calls it too, so the target is
+    /// released even if the monitor panics. Keeps going past failures and
+    /// returns the first one.
+    pub fn detach(&mut self) -> Result<(), nix::Error> {
+        let mut result = Ok(());
+        for region in self.regions.drain(..) {
+            if let Err(e) = self
+                .uffd
+                .unregister(region.start, region.end - region.start)
+            {
+                log::warn!(
+                    "Failed to unregister region 0x{:x}-0x{:x}: {}",
+                    region.start,
+                    region.end,
+                    e
+                );
+                result = result.and(Err(e));
+            }
+        }
+        result
     }
 
     pub fn pid(&self) -> Pid {
@@ -86,3 +331,138 @@ impl ProcessAttacher {
         &self.uffd
     }
 }
+
+impl Drop for ProcessAttacher {
+    fn drop(&mut self) {
+        let _ = self.detach();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_non_root_cannot_attach_to_init() {
+        let caller = AttachCredentials {
+            pid: std::process::id() as i32,
+            euid: 1000,
+            cap_sys_ptrace: false,
+        };
+
+        let err = check_attach(1, &caller, ptrace_scope()).unwrap_err();
+        assert!(
+            matches!(
+                err,
+                AttachError::NotOwner {
+                    pid: 1,
+                    target_uid: 0,
+                    caller_uid: 1000
+                } | AttachError::AdminOnly { pid: 1 }
+                    | AttachError::AttachDisabled
+            ),
+            "{:?}",
+            err
+        );
+        let fix = err.remediation().expect("permission errors carry a fix");
+        assert!(err.to_string().contains(fix));
+        assert!(err.to_string().starts_with("Permission denied"));
+    }
+
+    #[test]
+    #[ignore = "Requires userfaultfd kernel support and permissions"]
+    fn test_detach_releases_faulting_pages() {
+        use crate::glass_ram::uffd_wrapper::UffdFlags;
+        use std::sync::mpsc;
+        use std::time::Duration;
+
+        let page_size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) } as usize;
+        let page = unsafe {
+            libc::mmap(
+                std::ptr::null_mut(),
+                page_size,
+                libc::PROT_READ | libc::PROT_WRITE,
+                libc::MAP_PRIVATE | libc::MAP_ANONYMOUS,
+                -1,
+                0,
+            )
+        };
+        assert_ne!(page, libc::MAP_FAILED);
+        let start = page as u64;
+
+        let uffd = UserfaultFd::new(UffdFlags::CLOEXEC | UffdFlags::NONBLOCK).unwrap();
+        uffd.api(UffdFeatureFlags(0)).unwrap();
+        let mut attacher = ProcessAttacher {
+            pid: Pid::this(),
+            uffd,
+            regions: Vec::new(),
+        };
+        let region = MemoryRegion {
+            start,
+            end: start + page_size as u64,
+            permissions: "rw-p".to_string(),
+            offset: 0,
+            device: "00:00".to_string(),
+            inode: 0,
+            pathname: None,
+        };
+        attacher
+            .register_region(&region, UffdIoctlMode::REGISTER_MODE_MISSING)
+            .unwrap();
+
+        // With nobody serving faults, the first touch blocks
+        let touch = |start: u64| {
+            let (done_tx, done_rx) = mpsc::channel();
+            std::thread::spawn(move || {
+                let value = unsafe { std::ptr::read_volatile(start as *const u8) };
+                let _ = done_tx.send(value);
+            });
+            done_rx
+        };
+        let blocked = touch(start);
+        assert!(blocked.recv_timeout(Duration::from_millis(200)).is_err());
+
+        // Detaching wakes the blocked reader, and later accesses never fault
+        attacher.detach().unwrap();
+        assert_eq!(blocked.recv_timeout(Duration::from_secs(5)), Ok(0));
+        assert_eq!(
+            touch(start + 64).recv_timeout(Duration::from_secs(5)),
+            Ok(0)
+        );
+        assert!(attacher.regions().is_empty());
+        attacher.detach().unwrap();
+
+        drop(attacher);
+        unsafe { libc::munmap(page, page_size) };
+    }
+
+    #[test]
+    fn test_ptrace_scope_rules() {
+        let me = std::process::id() as i32;
+        let mut caller = AttachCredentials::current().unwrap();
+        caller.cap_sys_ptrace = false;
+
+        // Our own process is owned by us but is not our descendant
+        assert!(check_attach(me, &caller, Some(0)).is_ok());
+        assert!(matches!(
+            check_attach(me, &caller, Some(1)),
+            Err(AttachError::NotDescendant { .. })
+        ));
+        assert!(matches!(
+            check_attach(me, &caller, Some(2)),
+            Err(AttachError::AdminOnly { .. })
+        ));
+
+        caller.cap_sys_ptrace = true;
+        assert!(check_attach(me, &caller, Some(2)).is_ok());
+        assert!(matches!(
+            check_attach(me, &caller, Some(3)),
+            Err(AttachError::AttachDisabled)
+        ));
+
+        assert!(matches!(
+            check_attach(i32::MAX, &caller, None),
+            Err(AttachError::NoSuchProcess(_))
+        ));
+    }
+}
@@ -221,12 +221,14 @@ const UFFDIO: u8 = 0xAA;
 const API: u8 = 0x3F;
 #[allow(dead_code)] // IOCTL register command
 const REGISTER: u8 = 0x00;
+#[allow(dead_code)] // IOCTL unregister command
+const UNREGISTER: u8 = 0x01;
 #[allow(dead_code)] // IOCTL write protect command
 const WRITEPROTECT: u8 = 0x06;
 
 // Direct libc ioctl calls for userfaultfd operations
 unsafe fn uffdio_api(fd: RawFd, api_struct: *mut UffdioApi) -> nix::Result<i32> {
-    let ret = libc::ioctl(fd, 0xC0183F3F, api_struct); // _IOWR(0xAA, 0x3F, UffdioApi)
+    let ret = libc::ioctl(fd, 0xC018AA3F, api_struct); // _IOWR(0xAA, 0x3F, UffdioApi)
     if ret < 0 {
         Err(Errno::last())
     } else {
@@ -235,7 +237,16 @@ unsafe fn uffdio_api(fd: RawFd, api_struct: *mut UffdioApi) -> nix::Result<i32>
 }
 
 unsafe fn uffdio_register(fd: RawFd, reg_struct: *mut UffdioRegister) -> nix::Result<i32> {
-    let ret = libc::ioctl(fd, 0xC018AA00, reg_struct); // _IOWR(0xAA, 0x00, UffdioRegister)
+    let ret = libc::ioctl(fd, 0xC020AA00, reg_struct); // _IOWR(0xAA, 0x00, UffdioRegister)
+    if ret < 0 {
+        Err(Errno::last())
+    } else {
+        Ok(ret)
+    }
+}
+
+unsafe fn uffdio_unregister(fd: RawFd, range: *mut UffdioRange) -> nix::Result<i32> {
+    let ret = libc::ioctl(fd, 0x8010AA01, range); // _IOR(0xAA, 0x01, UffdioRange)
     if ret < 0 {
         Err(Errno::last())
     } else {
@@ -244,7 +255,7 @@ unsafe fn uffdio_register(fd: RawFd, reg_struct: *mut UffdioRegister) -> nix::Re
 }
 
 unsafe fn uffdio_writeprotect(fd: RawFd, wp_struct: *mut UffdioWriteProtect) -> nix::Result<i32> {
-    let ret = libc::ioctl(fd, 0xC010AA06, wp_struct); // _IOW(0xAA, 0x06, UffdioWriteProtect)
+    let ret = libc::ioctl(fd, 0xC018AA06, wp_struct); // _IOWR(0xAA, 0x06, UffdioWriteProtect)
     if ret < 0 {
         Err(Errno::last())
     } else {
@@ -303,6 +314,18 @@ impl UserfaultFd {
         Ok(())
     }
 
+    /// Stop handling faults in a registered range; threads blocked on a
+    /// fault there are woken and write protection is dropped
+    pub fn unregister(&self, start: u64, len: u64) -> Result<(), nix::Error> {
+        let mut range = UffdioRange { start, len };
+
+        unsafe {
+            uffdio_unregister(self.file.as_raw_fd(), &mut range)?;
+        }
+
+        Ok(())
+    }
+
     pub fn write_protect(&self, start: u64, len: u64, enable: bool) -> Result<(), nix::Error> {
         let mode = if enable {
             UffdIoctlMode::WRITEPROTECT_MODE_WP
@@ -0,0 +1,255 @@
+//! Hilbert Compliance Harness - WGSL vs Rust Reference
+//!
+//! Runs the WGSL port of `d2xy`/`xy2d` in `shaders/hilbert_compliance.wgsl`
+//! over every cell of a grid, reads the results back, and compares them with
+//! the canonical implementation in `crate::hilbert`. The `hilbert` module
+//! requires all implementations to agree; this catches shader regressions.
+
+use crate::hilbert;
+use bytemuck::{Pod, Zeroable};
+use std::sync::Arc;
+use wgpu;
+use wgpu::util::DeviceExt;
+
+/// WGSL source of the compliance shader (tested asset)
+pub const HILBERT_COMPLIANCE_WGSL: &str = include_str!("shaders/hilbert_compliance.wgsl");
+
+/// Workgroup size of both entry points (must match the shader)
+const WORKGROUP_SIZE: u32 = 64;
+
+/// Largest grid the harness checks (keeps `n * n` within u32 on device)
+pub const MAX_COMPLIANCE_GRID: u32 = 256;
+
+/// Matches the WGSL `ComplianceParams` struct.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod, Zeroable)]
+struct ComplianceParams {
+    n: u32,
+    _pad: [u32; 3],
+}
+
+/// A single disagreement between the shader and the Rust reference
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HilbertMismatch {
+    D2xy {
+        d: u32,
+        gpu: (u32, u32),
+        expected: (u32, u32),
+    },
+    Xy2d {
+        x: u32,
+        y: u32,
+        gpu: u32,
+        expected: u32,
+    },
+}
+
+/// Result of checking one grid size
+#[derive(Debug, Clone, Default)]
+pub struct ComplianceReport {
+    /// Grid size checked
+    pub n: u32,
+    /// Number of d2xy and xy2d results compared
+    pub checked: usize,
+    /// Every result that differed from the Rust reference
+    pub mismatches: Vec<HilbertMismatch>,
+}
+
+impl ComplianceReport {
+    /// True when the shader matched the Rust reference everywhere
+    pub fn is_compliant(&self) -> bool {
+        self.mismatches.is_empty()
+    }
+}
+
+/// Runs the compliance shader and compares against `crate::hilbert`
+pub struct HilbertComplianceHarness {
+    device: Arc<wgpu::Device>,
+    queue: Arc<wgpu::Queue>,
+    d2xy_pipeline: wgpu::ComputePipeline,
+    xy2d_pipeline: wgpu::ComputePipeline,
+}
+
+impl HilbertComplianceHarness {
+    pub fn new(device: Arc<wgpu::Device>, queue: Arc<wgpu::Queue>) -> Self {
+        let module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Hilbert Compliance Shader"),
+            source: wgpu::ShaderSource::Wgsl(HILBERT_COMPLIANCE_WGSL.into()),
+        });
+
+        let pipeline = |entry_point: &str| {
+            device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+                label: Some(entry_point),
+                layout: None,
+                module: &module,
+                entry_point,
+            })
+        };
+        let d2xy_pipeline = pipeline("d2xy_main");
+        let xy2d_pipeline = pipeline("xy2d_main");
+
+        Self {
+            device,
+            queue,
+            d2xy_pipeline,
+            xy2d_pipeline,
+        }
+    }
+
+    /// Check every `d` and every `(x, y)` of an `n` x `n` grid
+    pub fn run(&self, n: u32) -> Result<ComplianceReport, String> {
+        if !hilbert::validate_grid_size(n) || n > MAX_COMPLIANCE_GRID {
+            return Err(format!(
+                "Grid size {} must be a power of 2 no larger than {}",
+                n, MAX_COMPLIANCE_GRID
+            ));
+        }
+
+        let cells = n * n;
+        let mut report = ComplianceReport {
+            n,
+            ..Default::default()
+        };
+
+        let coords = self.dispatch(&self.d2xy_pipeline, n, cells * 2)?;
+        for d in 0..cells {
+            let gpu = (coords[2 * d as usize], coords[2 * d as usize + 1]);
+            let expected = hilbert::d2xy(n, d as u64);
+            if gpu != expected {
+                report
+                    .mismatches
+                    .push(HilbertMismatch::D2xy { d, gpu, expected });
+            }
+        }
+
+        let distances = self.dispatch(&self.xy2d_pipeline, n, cells)?;
+        for (i, &gpu) in distances.iter().enumerate() {
+            let (x, y) = (i as u32 % n, i as u32 / n);
+            let expected = hilbert::xy2d(n, x, y) as u32;
+            if gpu != expected {
+                report.mismatches.push(HilbertMismatch::Xy2d {
+                    x,
+                    y,
+                    gpu,
+                    expected,
+                });
+            }
+        }
+
+        report.checked = cells as usize * 2;
+        Ok(report)
+    }
+
+    /// Run one entry point over the grid and read back `words` results
+    fn dispatch(
+        &self,
+        pipeline: &wgpu::ComputePipeline,
+        n: u32,
+        words: u32,
+    ) -> Result<Vec<u32>, String> {
+        let size = words as u64 * 4;
+        let params = self
+            .device
+            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("Hilbert Compliance Params"),
+                contents: bytemuck::bytes_of(&ComplianceParams { n, _pad: [0; 3] }),
+                usage: wgpu::BufferUsages::UNIFORM,
+            });
+        let results = self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Hilbert Compliance Results"),
+            size,
+            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_SRC,
+            mapped_at_creation: false,
+        });
+        let readback = self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Hilbert Compliance Readback"),
+            size,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+
+        let bind_group = self.device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Hilbert Compliance Bind Group"),
+            layout: &pipeline.get_bind_group_layout(0),
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: params.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: results.as_entire_binding(),
+                },
+            ],
+        });
+
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("Hilbert Compliance Encoder"),
+            });
+        {
+            let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+                label: Some("Hilbert Compliance Pass"),
+                timestamp_writes: None,
+            });
+            pass.set_pipeline(pipeline);
+            pass.set_bind_group(0, &bind_group, &[]);
+            pass.dispatch_workgroups((n * n).div_ceil(WORKGROUP_SIZE), 1, 1);
+        }
+        encoder.copy_buffer_to_buffer(&results, 0, &readback, 0, size);
+        self.queue.submit(Some(encoder.finish()));
+
+        let slice = readback.slice(..);
+        let (tx, rx) = std::sync::mpsc::channel();
+        slice.map_async(wgpu::MapMode::Read, move |result| {
+            let _ = tx.send(result);
+        });
+        self.device.poll(wgpu::Maintain::Wait);
+        rx.recv()
+            .map_err(|e| format!("Readback channel closed: {}", e))?
+            .map_err(|e| format!("Failed to map readback buffer: {}", e))?;
+
+        let data = bytemuck::cast_slice(&slice.get_mapped_range()).to_vec();
+        readback.unmap();
+        Ok(data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::gpu::test_device;
+
+    #[test]
+    fn test_wgsl_hilbert_matches_rust_reference() {
+        let Some((device, queue)) = test_device() else {
+            return;
+        };
+
+        let harness = HilbertComplianceHarness::new(device, queue);
+        let mut n = 2;
+        while n <= MAX_COMPLIANCE_GRID {
+            let report = harness.run(n).unwrap();
+            assert_eq!(report.checked, (n * n * 2) as usize);
+            assert!(
+                report.is_compliant(),
+                "WGSL diverged from Rust for n={}: {:?}",
+                n,
+                &report.mismatches[..report.mismatches.len().min(8)]
+            );
+            n *= 2;
+        }
+    }
+
+    #[test]
+    fn test_compliance_shader_parses() {
+        let module = naga::front::wgsl::parse_str(HILBERT_COMPLIANCE_WGSL).unwrap();
+        let entry_points: Vec<&str> = module
+            .entry_points
+            .iter()
+            .map(|ep| ep.name.as_str())
+            .collect();
+        assert_eq!(entry_points, vec!["d2xy_main", "xy2d_main"]);
+    }
+}
@@ -6,8 +6,10 @@
 pub mod geometric_vm;
 pub mod glyph_vm;
 pub mod hebbian_processor;
+pub mod hilbert_compliance;
 pub mod wgsl_compiler;
 
 pub use geometric_vm::{GeometricState, GeometricVM};
 pub use hebbian_processor::{GPUHebbianProcessor, HebbianUniforms, HebbianUpdate};
+pub use hilbert_compliance::{ComplianceReport, HilbertComplianceHarness, HilbertMismatch};
 pub use wgsl_compiler::WGSLCompiler;
@@ -0,0 +1,96 @@
+// ============================================
+// GEOMETRY OS - HILBERT COMPLIANCE SHADER
+// WGSL port of the canonical d2xy / xy2d in hilbert/mod.rs.
+// Checked against the Rust reference by gpu::hilbert_compliance;
+// any change here must keep that harness passing.
+// ============================================
+
+struct ComplianceParams {
+    // Grid size (power of 2)
+    n: u32,
+    // Scalar padding keeps the struct at 16 bytes (vec3 would align to 32)
+    _pad0: u32,
+    _pad1: u32,
+    _pad2: u32,
+};
+
+@group(0) @binding(0) var<uniform> params: ComplianceParams;
+// d2xy: (x, y) pairs indexed by d; xy2d: d indexed by y * n + x
+@group(0) @binding(1) var<storage, read_write> results: array<u32>;
+
+fn hilbert_d2xy(n: u32, d_in: u32) -> vec2<u32> {
+    var x = 0u;
+    var y = 0u;
+    var s = 1u;
+    var d = d_in;
+
+    while (s < n) {
+        let rx = 1u & (d / 2u);
+        let ry = 1u & (d ^ rx);
+
+        if (ry == 0u) {
+            if (rx == 1u) {
+                x = s - 1u - x;
+                y = s - 1u - y;
+            }
+            let t = x;
+            x = y;
+            y = t;
+        }
+
+        x += s * rx;
+        y += s * ry;
+        d /= 4u;
+        s *= 2u;
+    }
+
+    return vec2<u32>(x, y);
+}
+
+fn hilbert_xy2d(n: u32, x_in: u32, y_in: u32) -> u32 {
+    var d = 0u;
+    var s = n / 2u;
+    var x = x_in;
+    var y = y_in;
+
+    while (s > 0u) {
+        let rx = select(0u, 1u, (x & s) > 0u);
+        let ry = select(0u, 1u, (y & s) > 0u);
+        d += s * s * ((3u * rx) ^ ry);
+
+        if (ry == 0u) {
+            if (rx == 1u) {
+                // Wrapping subtraction, as in the Rust reference
+                x = s - 1u - x;
+                y = s - 1u - y;
+            }
+            let t = x;
+            x = y;
+            y = t;
+        }
+
+        s /= 2u;
+    }
+
+    return d;
+}
+
+@compute @workgroup_size(64)
+fn d2xy_main(@builtin(global_invocation_id) id: vec3<u32>) {
+    let d = id.x;
+    if (d >= params.n * params.n) {
+        return;
+    }
+    let p = hilbert_d2xy(params.n, d);
+    results[2u * d] = p.x;
+    results[2u * d + 1u] = p.y;
+}
+
+@compute @workgroup_size(64)
+fn xy2d_main(@builtin(global_invocation_id) id: vec3<u32>) {
+    let i = id.x;
+    if (i >= params.n * params.n) {
+        return;
+    }
+    results[i] = hilbert_xy2d(params.n, i % params.n, i / params.n);
+}
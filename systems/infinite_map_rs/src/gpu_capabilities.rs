@@ -1,6 +1,26 @@
 // gpu_capabilities.rs
 /// GPU capability detection for WGSL shader compatibility
-use wgpu::Adapter;
+use std::collections::HashMap;
+use std::path::Path;
+use wgpu::{Adapter, AdapterInfo, DeviceType, TextureFormat, TextureFormatFeatures, TextureUsages};
+
+/// Formats whose adapter support is recorded by `GpuCapabilities::new`
+///
+/// Formats outside this list fall back to the WebGPU guaranteed features.
+pub const PROBED_FORMATS: &[TextureFormat] = &[
+    TextureFormat::R8Unorm,
+    TextureFormat::R16Float,
+    TextureFormat::R32Float,
+    TextureFormat::R32Uint,
+    TextureFormat::Rg32Float,
+    TextureFormat::Rgba8Unorm,
+    TextureFormat::Rgba8UnormSrgb,
+    TextureFormat::Bgra8Unorm,
+    TextureFormat::Bgra8UnormSrgb,
+    TextureFormat::Rgba16Float,
+    TextureFormat::Rgba32Float,
+    TextureFormat::Rgba32Uint,
+];
 
 /// Strategy for handling i64 operations in shaders
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -11,12 +31,79 @@ pub enum I64Strategy {
     Emulate,
 }
 
+/// Which adapter to pick on multi-GPU systems
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AdapterPreference {
+    /// Discrete GPUs first (VM workloads, heavy compute)
+    HighPerformance,
+    /// Integrated GPUs first (idle desktop, battery)
+    LowPower,
+    /// First adapter whose vendor or name contains this string
+    /// (case-insensitive), falling back to `HighPerformance`
+    SpecificVendor(String),
+}
+
+/// PCI vendor name for the common GPU vendor ids
+fn vendor_label(vendor: u32) -> Option<&'static str> {
+    match vendor {
+        0x10DE => Some("nvidia"),
+        0x1002 | 0x1022 => Some("amd"),
+        0x8086 => Some("intel"),
+        0x106B => Some("apple"),
+        0x13B5 => Some("arm"),
+        0x5143 => Some("qualcomm"),
+        _ => None,
+    }
+}
+
+/// Total VRAM of the selected adapter, from the driver's own counters
+///
+/// wgpu does not expose memory sizes, so this asks `nvidia-smi` for NVIDIA
+/// and DRM sysfs for AMD, picking the GPU by its PCI device id. Other
+/// vendors (and shared-memory GPUs) report `None`.
+fn detect_vram(vendor: u32, device: u32) -> Option<u64> {
+    use crate::tool_adapter::gpu_memory_adapter::{
+        DrmSysfsSource, GpuMemorySource, NvidiaSmiSource,
+    };
+
+    let total = match vendor_label(vendor)? {
+        "nvidia" => NvidiaSmiSource::device_total(device)?,
+        "amd" => {
+            DrmSysfsSource::discover_device(Path::new("/sys/class/drm"), vendor, device)?
+                .read()
+                .ok()?
+                .total_bytes
+        },
+        _ => return None,
+    };
+    (total > 0).then_some(total)
+}
+
+/// Lower is better
+fn device_type_rank(device_type: DeviceType, prefer_discrete: bool) -> u8 {
+    match (device_type, prefer_discrete) {
+        (DeviceType::DiscreteGpu, true) | (DeviceType::IntegratedGpu, false) => 0,
+        (DeviceType::IntegratedGpu, true) | (DeviceType::DiscreteGpu, false) => 1,
+        (DeviceType::VirtualGpu, _) => 2,
+        (DeviceType::Other, _) => 3,
+        (DeviceType::Cpu, _) => 4,
+    }
+}
+
 /// GPU capability detection
 #[derive(Debug, Clone)]
 pub struct GpuCapabilities {
     pub supports_i64: bool,
     pub vendor_name: String,
     pub device_name: String,
+    /// Optional features the adapter exposes
+    pub features: wgpu::Features,
+    /// Adapter-reported support for each of `PROBED_FORMATS`
+    pub format_features: HashMap<TextureFormat, TextureFormatFeatures>,
+    /// Best limits the adapter supports
+    pub limits: wgpu::Limits,
+    /// Dedicated VRAM reported by the driver, when it can be queried
+    pub vram_bytes: Option<u64>,
 }
 
 impl GpuCapabilities {
@@ -31,10 +118,71 @@ impl GpuCapabilities {
             .and_then(|v| v.parse::<bool>().ok())
             .unwrap_or(false);
 
+        let format_features = PROBED_FORMATS
+            .iter()
+            .map(|&format| (format, adapter.get_texture_format_features(format)))
+            .collect();
+
         Self {
             supports_i64,
             vendor_name: format!("{:?}", info.vendor),
             device_name: info.name.clone(),
+            features: adapter.features(),
+            format_features,
+            limits: adapter.limits(),
+            vram_bytes: detect_vram(info.vendor, info.device),
+        }
+    }
+
+    /// Describe every adapter the instance can see
+    pub fn enumerate(instance: &wgpu::Instance) -> Vec<AdapterInfo> {
+        instance
+            .enumerate_adapters(wgpu::Backends::all())
+            .iter()
+            .map(|adapter| adapter.get_info())
+            .collect()
+    }
+
+    /// Pick an adapter according to `prefer`
+    pub fn select(instance: &wgpu::Instance, prefer: &AdapterPreference) -> Option<Adapter> {
+        let mut adapters = instance.enumerate_adapters(wgpu::Backends::all());
+        let infos: Vec<AdapterInfo> = adapters.iter().map(|adapter| adapter.get_info()).collect();
+        let index = Self::select_index(&infos, prefer)?;
+        log::info!(
+            "Selected adapter {} ({:?}, {:?}) for {:?}",
+            infos[index].name,
+            infos[index].device_type,
+            infos[index].backend,
+            prefer
+        );
+        Some(adapters.swap_remove(index))
+    }
+
+    /// Index of the adapter `select` would choose from `adapters`
+    ///
+    /// Ties keep enumeration order.
+    pub fn select_index(adapters: &[AdapterInfo], prefer: &AdapterPreference) -> Option<usize> {
+        let best_by_type = |prefer_discrete: bool| {
+            adapters
+                .iter()
+                .enumerate()
+                .min_by_key(|(_, info)| device_type_rank(info.device_type, prefer_discrete))
+                .map(|(index, _)| index)
+        };
+
+        match prefer {
+            AdapterPreference::HighPerformance => best_by_type(true),
+            AdapterPreference::LowPower => best_by_type(false),
+            AdapterPreference::SpecificVendor(vendor) => {
+                let wanted = vendor.to_lowercase();
+                adapters
+                    .iter()
+                    .position(|info| {
+                        info.name.to_lowercase().contains(&wanted)
+                            || vendor_label(info.vendor).is_some_and(|label| label == wanted)
+                    })
+                    .or_else(|| best_by_type(true))
+            },
         }
     }
 
@@ -49,6 +197,43 @@ impl GpuCapabilities {
             I64Strategy::Emulate
         }
     }
+
+    /// Whether a texture of `format` can be created with `usage`
+    pub fn supports_format(&self, format: TextureFormat, usage: TextureUsages) -> bool {
+        if !self.features.contains(format.required_features()) {
+            return false;
+        }
+        let supported = self
+            .format_features
+            .get(&format)
+            .copied()
+            .unwrap_or_else(|| format.guaranteed_format_features(self.features));
+        supported.allowed_usages.contains(usage)
+    }
+
+    /// First candidate usable with `usage`, in order of preference
+    ///
+    /// Texture-creating code lists its ideal format first and cheaper
+    /// fallbacks after it, instead of letting wgpu fail on creation.
+    pub fn best_supported_format(
+        &self,
+        candidates: &[TextureFormat],
+        usage: TextureUsages,
+    ) -> Option<TextureFormat> {
+        let best = candidates
+            .iter()
+            .copied()
+            .find(|&format| self.supports_format(format, usage));
+        if best != candidates.first().copied() {
+            log::warn!(
+                "Texture format {:?} unsupported for {:?}, using {:?}",
+                candidates.first(),
+                usage,
+                best
+            );
+        }
+        best
+    }
 }
 
 #[cfg(test)]
@@ -81,8 +266,118 @@ mod tests {
             supports_i64: false,
             vendor_name: "Test GPU".to_string(),
             device_name: "Test Device".to_string(),
+            features: wgpu::Features::empty(),
+            format_features: HashMap::new(),
+            limits: wgpu::Limits::default(),
+            vram_bytes: None,
         };
 
         assert_eq!(caps.get_i64_strategy(), I64Strategy::Emulate);
     }
+
+    fn fake_adapter(name: &str, vendor: u32, device_type: DeviceType) -> AdapterInfo {
+        AdapterInfo {
+            name: name.to_string(),
+            vendor,
+            device: 0,
+            device_type,
+            driver: String::new(),
+            driver_info: String::new(),
+            backend: wgpu::Backend::Vulkan,
+        }
+    }
+
+    #[test]
+    fn test_select_adapter_by_preference() {
+        let adapters = vec![
+            fake_adapter("llvmpipe", 0x10005, DeviceType::Cpu),
+            fake_adapter(
+                "Intel(R) UHD Graphics 630",
+                0x8086,
+                DeviceType::IntegratedGpu,
+            ),
+            fake_adapter("NVIDIA GeForce RTX 3080", 0x10DE, DeviceType::DiscreteGpu),
+        ];
+
+        let select = |prefer| GpuCapabilities::select_index(&adapters, &prefer);
+        assert_eq!(select(AdapterPreference::HighPerformance), Some(2));
+        assert_eq!(select(AdapterPreference::LowPower), Some(1));
+        assert_eq!(
+            select(AdapterPreference::SpecificVendor("intel".into())),
+            Some(1)
+        );
+        assert_eq!(
+            select(AdapterPreference::SpecificVendor("GeForce".into())),
+            Some(2)
+        );
+        // Unknown vendor falls back to the fastest adapter
+        assert_eq!(
+            select(AdapterPreference::SpecificVendor("amd".into())),
+            Some(2)
+        );
+
+        // Preferred type absent: take the next best, never fail while any exist
+        let cpu_and_igpu = &adapters[..2];
+        assert_eq!(
+            GpuCapabilities::select_index(cpu_and_igpu, &AdapterPreference::HighPerformance),
+            Some(1)
+        );
+        let cpu_only = &adapters[..1];
+        assert_eq!(
+            GpuCapabilities::select_index(cpu_only, &AdapterPreference::LowPower),
+            Some(0)
+        );
+        assert_eq!(
+            GpuCapabilities::select_index(&[], &AdapterPreference::HighPerformance),
+            None
+        );
+    }
+    #[test]
+    fn test_best_supported_format_downgrades() {
+        let sampled = TextureFormatFeatures {
+            allowed_usages: TextureUsages::TEXTURE_BINDING | TextureUsages::COPY_DST,
+            flags: wgpu::TextureFormatFeatureFlags::empty(),
+        };
+        let storage = TextureFormatFeatures {
+            allowed_usages: sampled.allowed_usages | TextureUsages::STORAGE_BINDING,
+            ..sampled
+        };
+        let caps = GpuCapabilities {
+            supports_i64: false,
+            vendor_name: "Test GPU".to_string(),
+            device_name: "Test Device".to_string(),
+            features: wgpu::Features::empty(),
+            format_features: HashMap::from([
+                (TextureFormat::Rgba16Float, sampled),
+                (TextureFormat::Rgba32Float, sampled),
+                (TextureFormat::Rgba8Unorm, storage),
+            ]),
+            limits: wgpu::Limits::default(),
+            vram_bytes: None,
+        };
+
+        let candidates = [
+            TextureFormat::Rgba16Float,
+            TextureFormat::Rgba32Float,
+            TextureFormat::Rgba8Unorm,
+        ];
+        assert_eq!(
+            caps.best_supported_format(&candidates, TextureUsages::TEXTURE_BINDING),
+            Some(TextureFormat::Rgba16Float)
+        );
+        assert_eq!(
+            caps.best_supported_format(&candidates, TextureUsages::STORAGE_BINDING),
+            Some(TextureFormat::Rgba8Unorm)
+        );
+        assert_eq!(
+            caps.best_supported_format(&candidates[..2], TextureUsages::STORAGE_BINDING),
+            None
+        );
+
+        // Feature-gated formats need the adapter feature
+        assert_eq!(
+            caps.best_supported_format(&[TextureFormat::Bc1RgbaUnorm], TextureUsages::COPY_DST),
+            None
+        );
+    }
 }
@@ -20,7 +20,9 @@
 //! | 4 | 2 | (1, 1) |
 //! | 4 | 3 | (0, 1) |
 //! | 8 | 0 | (0, 0) |
-//! | 8 | 7 | (0, 1) |
+//! | 8 | 7 | (2, 1) |
+//!
+//! The full set for any order comes from [`test_vectors::generate`].
 //!
 //! ## Usage
 //!
@@ -28,16 +30,23 @@
 //! use hilbert::{d2xy, xy2d, HilbertCurve};
 //!
 //! // Convert distance to coordinates
-//! let (x, y) = d2xy(8, 7);  // Returns (0, 1)
+//! let (x, y) = d2xy(8, 7);  // Returns (2, 1)
 //!
 //! // Convert coordinates to distance
-//! let d = xy2d(8, 0, 1);  // Returns 7
+//! let d = xy2d(8, 2, 1);  // Returns 7
 //!
 //! // Using the struct (for grid_size caching)
 //! let curve = HilbertCurve::new(8);
 //! let (x, y) = curve.d2xy(7);
 //! ```
 
+use crate::gpu_capabilities::GpuCapabilities;
+
+pub mod test_vectors;
+pub mod upload;
+
+pub use upload::HilbertTextureUpload;
+
 /// Convert Hilbert distance to (x, y) coordinates.
 ///
 /// This is the canonical implementation. All other implementations
@@ -111,6 +120,97 @@ pub fn d2xy(n: u32, d: u64) -> (u32, u32) {
     (x, y)
 }
 
+/// Quadrant (x, y) of each base-4 digit in an untransformed square
+const DIGIT_QUADRANTS: [(u32, u32); 4] = [(0, 0), (0, 1), (1, 1), (1, 0)];
+
+/// Frame change entering each digit's quadrant (bit 0 swaps x/y, bit 1
+/// mirrors both); frames compose by XOR
+const DIGIT_FRAMES: [u8; 4] = [1, 0, 0, 3];
+
+/// Map a quadrant through a frame (each frame is its own inverse)
+fn apply_frame(frame: u8, (x, y): (u32, u32)) -> (u32, u32) {
+    let (x, y) = if frame & 2 != 0 {
+        (1 - x, 1 - y)
+    } else {
+        (x, y)
+    };
+    if frame & 1 != 0 {
+        (y, x)
+    } else {
+        (x, y)
+    }
+}
+
+/// Level-1 cell (x, y) for the low two bits of a distance
+const LOW_CELLS: [(i64, i64); 4] = [(0, 0), (0, 1), (1, 1), (1, 0)];
+
+/// Convert `count` consecutive distances starting at `start` in one pass.
+///
+/// Writes interleaved `(x, y)` pairs to `out[..count * 2]`, identical to
+/// calling [`d2xy`] for each distance. The upper levels of the curve are
+/// shared by each aligned group of four distances, so they are folded into
+/// one affine transform per group instead of being recomputed per index.
+///
+/// # Panics
+///
+/// Panics if `out` holds fewer than `count * 2` values.
+///
+/// # Examples
+///
+/// ```
+/// use infinite_map_rs::hilbert::d2xy_batch;
+/// let mut out = [0u32; 8];
+/// d2xy_batch(4, 0, 4, &mut out);
+/// assert_eq!(out, [0, 0, 1, 0, 1, 1, 0, 1]);
+/// ```
+pub fn d2xy_batch(n: u32, start: u64, count: usize, out: &mut [u32]) {
+    assert!(
+        out.len() >= count * 2,
+        "Output holds {} values, need {}",
+        out.len(),
+        count * 2
+    );
+    if n <= 1 {
+        out[..count * 2].fill(0);
+        return;
+    }
+
+    let mut d = start;
+    let mut i = 0;
+    while i < count {
+        // x = ax·X + bx·Y + cx, y = ay·X + by·Y + cy for level-1 cell (X, Y)
+        let (mut ax, mut bx, mut cx) = (1i64, 0i64, 0i64);
+        let (mut ay, mut by, mut cy) = (0i64, 1i64, 0i64);
+        let mut s = 2i64;
+        let mut rest = d / 4;
+        while s < n as i64 {
+            let rx = (1 & (rest / 2)) as i64;
+            let ry = (1 & (rest ^ rx as u64)) as i64;
+            if ry == 0 {
+                if rx == 1 {
+                    (ax, bx, cx) = (-ax, -bx, s - 1 - cx);
+                    (ay, by, cy) = (-ay, -by, s - 1 - cy);
+                }
+                (ax, bx, cx, ay, by, cy) = (ay, by, cy, ax, bx, cx);
+            }
+            cx += s * rx;
+            cy += s * ry;
+            rest /= 4;
+            s *= 2;
+        }
+
+        for &(lx, ly) in &LOW_CELLS[(d & 3) as usize..] {
+            if i == count {
+                break;
+            }
+            out[2 * i] = (ax * lx + bx * ly + cx) as u32;
+            out[2 * i + 1] = (ay * lx + by * ly + cy) as u32;
+            i += 1;
+            d += 1;
+        }
+    }
+}
+
 /// Convert (x, y) coordinates to Hilbert distance.
 ///
 /// This is the inverse of `d2xy`. The round-trip property must hold:
@@ -180,6 +280,95 @@ pub fn xy2d(n: u32, x: u32, y: u32) -> u64 {
     d
 }
 
+/// [`d2xy`] with 64-bit coordinates for grids larger than 2^16 per axis.
+///
+/// `n` may be any power of 2 up to 2^63. Results match [`d2xy`] wherever
+/// both apply.
+///
+/// # Examples
+///
+/// ```
+/// use infinite_map_rs::hilbert::d2xy_u64;
+/// let n = 1u64 << 24;
+/// let d = (n as u128 * n as u128) - 1;
+/// assert_eq!(d2xy_u64(n, d), (n - 1, 0));
+/// ```
+#[inline]
+pub fn d2xy_u64(n: u64, d: u128) -> (u64, u64) {
+    let mut x = 0u64;
+    let mut y = 0u64;
+    let mut s = 1u64;
+    let mut d = d;
+
+    while s < n {
+        let rx = (1 & (d / 2)) as u64;
+        let ry = (1 & (d ^ rx as u128)) as u64;
+
+        // Rotate/flip quadrant
+        if ry == 0 {
+            if rx == 1 {
+                x = s - 1 - x;
+                y = s - 1 - y;
+            }
+            std::mem::swap(&mut x, &mut y);
+        }
+
+        x += s * rx;
+        y += s * ry;
+
+        d /= 4;
+        s *= 2;
+    }
+
+    (x, y)
+}
+
+/// [`xy2d`] with 64-bit coordinates for grids larger than 2^16 per axis.
+///
+/// # Examples
+///
+/// ```
+/// use infinite_map_rs::hilbert::{d2xy_u64, xy2d_u64};
+/// let n = 1u64 << 20;
+/// let (x, y) = d2xy_u64(n, 123_456_789_012);
+/// assert_eq!(xy2d_u64(n, x, y), 123_456_789_012);
+/// ```
+#[inline]
+pub fn xy2d_u64(n: u64, x: u64, y: u64) -> u128 {
+    let mut d = 0u128;
+    let mut s = n / 2;
+    let mut x = x;
+    let mut y = y;
+
+    while s > 0 {
+        let rx = (x & s) > 0;
+        let ry = (y & s) > 0;
+        d += s as u128 * s as u128 * ((3 * rx as u128) ^ ry as u128);
+
+        // Rotate/flip quadrant
+        if !ry {
+            if rx {
+                x = (s - 1).wrapping_sub(x);
+                y = (s - 1).wrapping_sub(y);
+            }
+            std::mem::swap(&mut x, &mut y);
+        }
+
+        s /= 2;
+    }
+
+    d
+}
+
+/// Invalid Hilbert curve parameters
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+pub enum HilbertError {
+    #[error("Grid size {0} is not a power of 2")]
+    NotPowerOfTwo(u32),
+    #[error("Order {0} is too large (grid size must fit in u32)")]
+    OrderTooLarge(u32),
+}
+
 /// Hilbert curve with cached grid size.
 ///
 /// Useful when performing multiple conversions on the same grid size,
@@ -199,7 +388,8 @@ impl HilbertCurve {
     ///
     /// # Panics
     ///
-    /// Panics if `n` is not a power of 2.
+    /// Panics if `n` is not a power of 2. Use [`HilbertCurve::try_new`]
+    /// when `n` comes from user input.
     ///
     /// # Examples
     ///
@@ -210,19 +400,41 @@ impl HilbertCurve {
     /// assert_eq!(curve.order, 6);
     /// ```
     pub fn new(n: u32) -> Self {
-        assert!(n.is_power_of_two(), "Grid size must be power of 2");
+        match Self::try_new(n) {
+            Ok(curve) => curve,
+            Err(e) => panic!("{}", e),
+        }
+    }
+
+    /// Create a Hilbert curve, rejecting grid sizes that are not a power of 2.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use infinite_map_rs::hilbert::{HilbertCurve, HilbertError};
+    /// assert_eq!(HilbertCurve::try_new(64).unwrap().order, 6);
+    /// assert_eq!(HilbertCurve::try_new(100).unwrap_err(), HilbertError::NotPowerOfTwo(100));
+    /// ```
+    pub fn try_new(n: u32) -> Result<Self, HilbertError> {
+        if !n.is_power_of_two() {
+            return Err(HilbertError::NotPowerOfTwo(n));
+        }
         let order = n.trailing_zeros();
-        let total_pixels = (n * n) as u64;
+        let total_pixels = n as u64 * n as u64;
 
-        Self {
+        Ok(Self {
             n,
             order,
             total_pixels,
-        }
+        })
     }
 
     /// Create from order (grid size = 2^order).
     ///
+    /// # Panics
+    ///
+    /// Panics if `order` is 32 or more.
+    ///
     /// # Examples
     ///
     /// ```
@@ -231,8 +443,18 @@ impl HilbertCurve {
     /// assert_eq!(curve.n, 64);
     /// ```
     pub fn from_order(order: u32) -> Self {
-        let n = 1u32 << order;
-        Self::new(n)
+        match Self::try_from_order(order) {
+            Ok(curve) => curve,
+            Err(e) => panic!("{}", e),
+        }
+    }
+
+    /// Create from order, rejecting orders whose grid size overflows u32.
+    pub fn try_from_order(order: u32) -> Result<Self, HilbertError> {
+        let n = 1u32
+            .checked_shl(order)
+            .ok_or(HilbertError::OrderTooLarge(order))?;
+        Self::try_new(n)
     }
 
     /// Convert distance to (x, y) coordinates.
@@ -263,6 +485,149 @@ impl HilbertCurve {
         xy2d(self.n, x, y)
     }
 
+    /// Distances of the 4-neighbors of the point at distance `d`.
+    ///
+    /// Returned in N/E/S/W order with y growing downwards (N is `y - 1`).
+    /// Neighbors off the grid, and all four when `d` is past the end of the
+    /// curve, are `None`.
+    ///
+    /// Works on the base-4 digits of `d`: a neighbor shares every digit above
+    /// the level where its coordinate carries, so only the digits below that
+    /// level are re-encoded (usually one or two).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use infinite_map_rs::hilbert::HilbertCurve;
+    /// let curve = HilbertCurve::new(4);
+    /// // d = 0 is the top-left corner
+    /// assert_eq!(curve.neighbors(0), [None, Some(1), Some(3), None]);
+    /// ```
+    pub fn neighbors(&self, d: u64) -> [Option<u64>; 4] {
+        if d >= self.total_pixels {
+            return [None; 4];
+        }
+
+        // Frame entering each level, and the x/y bit each digit selects
+        let levels = self.order as usize;
+        let mut frames = [0u8; 32];
+        let mut bits = [[0u32; 32]; 2];
+        let mut frame = 0;
+        for level in (0..levels).rev() {
+            let digit = ((d >> (2 * level)) & 3) as usize;
+            frames[level] = frame;
+            let (x, y) = apply_frame(frame, DIGIT_QUADRANTS[digit]);
+            bits[0][level] = x;
+            bits[1][level] = y;
+            frame ^= DIGIT_FRAMES[digit];
+        }
+
+        // (axis, step): N, E, S, W
+        [(1, false), (0, true), (1, true), (0, false)].map(|(axis, up)| {
+            // Stepping up turns the lowest 0 bit to 1 and the 1s below it to
+            // 0 (down is the mirror); levels above that bit keep their digits
+            let below = u32::from(!up);
+            let level = (0..levels).find(|&l| bits[axis][l] == below)?;
+            let keep = 2 * (level as u32 + 1);
+            let mut neighbor = d.checked_shr(keep).map_or(0, |high| high << keep);
+            let mut frame = frames[level];
+            for l in (0..=level).rev() {
+                let moved = if l == level { 1 - below } else { below };
+                let quadrant = if axis == 0 {
+                    (moved, bits[1][l])
+                } else {
+                    (bits[0][l], moved)
+                };
+                let (cx, cy) = apply_frame(frame, quadrant);
+                let digit = ((3 * cx) ^ cy) as usize;
+                neighbor |= (digit as u64) << (2 * l);
+                frame ^= DIGIT_FRAMES[digit];
+            }
+            Some(neighbor)
+        })
+    }
+
+    /// Distance ranges covering exactly the cells of an inclusive box.
+    ///
+    /// Returns sorted, non-overlapping, inclusive `(start, end)` ranges with
+    /// adjacent ranges merged. The box is clipped to the grid; an empty box
+    /// (`x0 > x1` or `y0 > y1`) yields no ranges.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use infinite_map_rs::hilbert::HilbertCurve;
+    /// let curve = HilbertCurve::new(4);
+    /// // The top-left quadrant is the first quarter of the curve
+    /// assert_eq!(curve.box_to_ranges(0, 0, 1, 1), vec![(0, 3)]);
+    /// assert_eq!(curve.box_to_ranges(1, 0, 2, 0), vec![(1, 1), (14, 14)]);
+    /// ```
+    pub fn box_to_ranges(&self, x0: u32, y0: u32, x1: u32, y1: u32) -> Vec<(u64, u64)> {
+        let (x1, y1) = (x1.min(self.n - 1), y1.min(self.n - 1));
+        let mut ranges = Vec::new();
+        if x0 > x1 || y0 > y1 {
+            return ranges;
+        }
+        self.collect_box_ranges(0, self.n, (x0, y0, x1, y1), &mut ranges);
+        ranges
+    }
+
+    /// Visit the aligned square of side `size` starting at distance `base`
+    fn collect_box_ranges(
+        &self,
+        base: u64,
+        size: u32,
+        bounds: (u32, u32, u32, u32),
+        ranges: &mut Vec<(u64, u64)>,
+    ) {
+        let (x0, y0, x1, y1) = bounds;
+        // Every point of an aligned sub-square floors to its origin
+        let (px, py) = self.d2xy(base);
+        let (sx, sy) = (px & !(size - 1), py & !(size - 1));
+        let (ex, ey) = (sx + (size - 1), sy + (size - 1));
+
+        if ex < x0 || sx > x1 || ey < y0 || sy > y1 {
+            return;
+        }
+        let area = size as u64 * size as u64;
+        if sx >= x0 && ex <= x1 && sy >= y0 && ey <= y1 {
+            let end = base + area - 1;
+            match ranges.last_mut() {
+                Some(last) if last.1 + 1 == base => last.1 = end,
+                _ => ranges.push((base, end)),
+            }
+            return;
+        }
+
+        let quarter = area / 4;
+        for k in 0..4 {
+            self.collect_box_ranges(base + k * quarter, size / 2, bounds, ranges);
+        }
+    }
+
+    /// Iterate `(d, x, y)` for every point in distance order.
+    ///
+    /// Points are computed lazily, so no LUT is materialized. The iterator
+    /// knows its exact length and can also be walked from the tail.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use infinite_map_rs::hilbert::HilbertCurve;
+    /// let curve = HilbertCurve::new(4);
+    /// let points: Vec<_> = curve.iter().take(2).collect();
+    /// assert_eq!(points, vec![(0, 0, 0), (1, 1, 0)]);
+    /// assert_eq!(curve.iter().len(), 16);
+    /// assert_eq!(curve.iter().next_back(), Some((15, 3, 0)));
+    /// ```
+    pub fn iter(&self) -> HilbertIter {
+        HilbertIter {
+            curve: *self,
+            front: 0,
+            back: self.total_pixels,
+        }
+    }
+
     /// Generate a lookup table for all coordinates.
     ///
     /// Returns a Vec where index d contains (x, y) coordinates.
@@ -293,19 +658,172 @@ impl HilbertCurve {
     /// Returns a Vec<u32> where each pair of u32 values represents (x, y).
     /// Suitable for direct upload to GPU via WGPU.
     pub fn generate_gpu_lut(&self) -> Vec<u32> {
-        let total = (self.n * self.n) as usize;
-        let mut lut = Vec::with_capacity(total * 2);
+        let total = self.total_pixels as usize;
+        let mut lut = vec![0u32; total * 2];
+        d2xy_batch(self.n, 0, total, &mut lut);
+        lut
+    }
 
-        for d in 0..total {
-            let (x, y) = self.d2xy(d as u64);
-            lut.push(x);
-            lut.push(y);
-        }
+    /// [`HilbertCurve::generate_gpu_lut`] split across the Rayon pool.
+    ///
+    /// Output is identical to the sequential version.
+    #[cfg(feature = "parallel-lut")]
+    pub fn generate_gpu_lut_parallel(&self) -> Vec<u32> {
+        use rayon::prelude::*;
+
+        /// Distances per task (multiple of 4 keeps groups aligned)
+        const CHUNK: usize = 16 * 1024;
 
+        let total = self.total_pixels as usize;
+        let mut lut = vec![0u32; total * 2];
+        lut.par_chunks_mut(CHUNK * 2)
+            .enumerate()
+            .for_each(|(i, chunk)| {
+                d2xy_batch(self.n, (i * CHUNK) as u64, chunk.len() / 2, chunk);
+            });
         lut
     }
+
+    /// Size in bytes of the buffer made by `create_gpu_lut_buffer`.
+    pub fn gpu_lut_size(&self) -> u64 {
+        self.total_pixels * 2 * std::mem::size_of::<u32>() as u64
+    }
+
+    /// Choose between the GPU LUT and CPU remapping for this adapter.
+    ///
+    /// Software and low-end adapters can have storage binding limits smaller
+    /// than the LUT of a large grid; those fall back to [`Self::remap_to_texture`].
+    pub fn mapping_path(&self, caps: &GpuCapabilities) -> HilbertMappingPath {
+        let limit = caps.limits.max_storage_buffer_binding_size as u64;
+        if self.gpu_lut_size() <= limit {
+            HilbertMappingPath::GpuLut
+        } else {
+            log::warn!(
+                "Hilbert LUT for {}x{} grid ({} bytes) exceeds storage binding limit ({} bytes), remapping on CPU",
+                self.n,
+                self.n,
+                self.gpu_lut_size(),
+                limit
+            );
+            HilbertMappingPath::Cpu
+        }
+    }
+
+    /// Scatter linear texels to their Hilbert positions in an n x n texture.
+    ///
+    /// Texel `d` of `linear` lands at `d2xy(d)`, matching what a shader
+    /// sampling the GPU LUT writes. Missing texels are left zeroed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use infinite_map_rs::hilbert::HilbertCurve;
+    /// let curve = HilbertCurve::new(2);
+    /// // d = 0..4 visits (0,0), (0,1), (1,1), (1,0)
+    /// assert_eq!(curve.remap_to_texture(&[1, 2, 3, 4], 1), vec![1, 4, 2, 3]);
+    /// ```
+    pub fn remap_to_texture(&self, linear: &[u8], bytes_per_pixel: usize) -> Vec<u8> {
+        let mut texture = vec![0u8; self.total_pixels as usize * bytes_per_pixel];
+        for ((_, x, y), texel) in self.iter().zip(linear.chunks(bytes_per_pixel)) {
+            let offset = (y as usize * self.n as usize + x as usize) * bytes_per_pixel;
+            texture[offset..offset + texel.len()].copy_from_slice(texel);
+        }
+        texture
+    }
+
+    /// Upload `generate_gpu_lut` as a read-only storage buffer.
+    ///
+    /// Bind it as `var<storage, read> hilbert_lut: array<u32>` and append
+    /// [`HILBERT_LUT_WGSL`] to the shader to get `hilbert_d2xy(d)` on device.
+    pub fn create_gpu_lut_buffer(&self, device: &wgpu::Device) -> wgpu::Buffer {
+        use wgpu::util::DeviceExt;
+
+        device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Hilbert LUT"),
+            contents: bytemuck::cast_slice(&self.generate_gpu_lut()),
+            usage: wgpu::BufferUsages::STORAGE,
+        })
+    }
+}
+
+impl IntoIterator for &HilbertCurve {
+    type Item = (u64, u32, u32);
+    type IntoIter = HilbertIter;
+
+    fn into_iter(self) -> HilbertIter {
+        self.iter()
+    }
 }
 
+/// Lazy `(d, x, y)` iterator returned by [`HilbertCurve::iter`].
+#[derive(Debug, Clone)]
+pub struct HilbertIter {
+    curve: HilbertCurve,
+    /// Next distance from the front
+    front: u64,
+    /// One past the next distance from the back
+    back: u64,
+}
+
+impl HilbertIter {
+    fn point(&self, d: u64) -> (u64, u32, u32) {
+        let (x, y) = self.curve.d2xy(d);
+        (d, x, y)
+    }
+}
+
+impl Iterator for HilbertIter {
+    type Item = (u64, u32, u32);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.front >= self.back {
+            return None;
+        }
+        let point = self.point(self.front);
+        self.front += 1;
+        Some(point)
+    }
+
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        self.front = self.front.saturating_add(n as u64).min(self.back);
+        self.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = usize::try_from(self.back - self.front).unwrap_or(usize::MAX);
+        (len, Some(len))
+    }
+}
+
+impl DoubleEndedIterator for HilbertIter {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.front >= self.back {
+            return None;
+        }
+        self.back -= 1;
+        Some(self.point(self.back))
+    }
+}
+
+impl ExactSizeIterator for HilbertIter {}
+
+impl std::iter::FusedIterator for HilbertIter {}
+
+/// How linear data is laid out along the Hilbert curve for upload.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HilbertMappingPath {
+    /// Shaders look up coordinates in the LUT from `create_gpu_lut_buffer`
+    GpuLut,
+    /// Data is remapped with `HilbertCurve::remap_to_texture` before upload
+    Cpu,
+}
+
+/// WGSL snippet providing `hilbert_d2xy(d) -> vec2<u32>` over a LUT buffer.
+///
+/// Expects the including shader to declare a `hilbert_lut` storage binding
+/// holding [`HilbertCurve::generate_gpu_lut`] (see the snippet header).
+pub const HILBERT_LUT_WGSL: &str = include_str!("../shaders/hilbert_lut.wgsl");
+
 /// Validate grid size is power of 2.
 ///
 /// # Examples
@@ -353,12 +871,47 @@ pub fn grid_order(n: u32) -> u32 {
 /// ```
 #[inline]
 pub fn grid_capacity(n: u32) -> usize {
-    (n * n * 4) as usize
+    grid_capacity_bpp(n, 4)
+}
+
+/// Calculate data capacity for a grid with the given texel size.
+///
+/// # Examples
+///
+/// ```
+/// use infinite_map_rs::hilbert::grid_capacity_bpp;
+/// assert_eq!(grid_capacity_bpp(64, 1), 4096); // R8
+/// assert_eq!(grid_capacity_bpp(64, 4), 16384); // RGBA8
+/// ```
+#[inline]
+pub fn grid_capacity_bpp(n: u32, bytes_per_pixel: u32) -> usize {
+    n as usize * n as usize * bytes_per_pixel as usize
+}
+
+/// Smallest power-of-two grid whose capacity fits `data_len` bytes.
+///
+/// Returns at least 1; a `bytes_per_pixel` of 0 is treated as 1.
+///
+/// # Examples
+///
+/// ```
+/// use infinite_map_rs::hilbert::required_grid_size;
+/// assert_eq!(required_grid_size(16384, 4), 64);
+/// assert_eq!(required_grid_size(16385, 4), 128);
+/// ```
+pub fn required_grid_size(data_len: usize, bytes_per_pixel: u32) -> u32 {
+    let pixels = data_len.div_ceil(bytes_per_pixel.max(1) as usize);
+    let mut n = 1u32;
+    while (n as usize) * (n as usize) < pixels {
+        n *= 2;
+    }
+    n
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::tests::gpu::test_device;
 
     // Test vectors from HilbertEngine.py (reference implementation)
     const TEST_VECTORS: &[(u32, u64, (u32, u32))] = &[
@@ -384,6 +937,23 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_generated_vectors_match_reference() {
+        let order2 = test_vectors::generate(2);
+        let order3 = test_vectors::generate(3);
+        assert_eq!(order2.len(), 16);
+        assert_eq!(order3.len(), 64);
+
+        for &(n, d, xy) in TEST_VECTORS {
+            let generated = match n {
+                4 => &order2,
+                8 => &order3,
+                _ => unreachable!("no order for n={}", n),
+            };
+            assert_eq!(generated[d as usize], (n, d, xy));
+        }
+    }
+
     #[test]
     fn test_round_trip() {
         for n in [4, 8, 16, 32, 64, 128] {
@@ -412,6 +982,37 @@ mod tests {
         assert_eq!(HilbertCurve::from_order(8).n, 256);
     }
 
+    #[test]
+    fn test_try_new_rejects_non_power_of_two() {
+        assert_eq!(
+            HilbertCurve::try_new(100).unwrap_err(),
+            HilbertError::NotPowerOfTwo(100)
+        );
+        assert_eq!(
+            HilbertCurve::try_new(0).unwrap_err(),
+            HilbertError::NotPowerOfTwo(0)
+        );
+        let curve = HilbertCurve::try_new(64).unwrap();
+        assert_eq!((curve.n, curve.order, curve.total_pixels), (64, 6, 4096));
+
+        assert_eq!(HilbertCurve::try_from_order(8).unwrap().n, 256);
+        assert_eq!(
+            HilbertCurve::try_from_order(32).unwrap_err(),
+            HilbertError::OrderTooLarge(32)
+        );
+        // Largest grid no longer overflows the pixel count
+        assert_eq!(
+            HilbertCurve::try_from_order(31).unwrap().total_pixels,
+            1u64 << 62
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "not a power of 2")]
+    fn test_new_panics_on_invalid_size() {
+        HilbertCurve::new(100);
+    }
+
     #[test]
     fn test_generate_lut() {
         let curve = HilbertCurve::new(4);
@@ -446,6 +1047,66 @@ mod tests {
         assert_eq!(grid_capacity(256), 262144);
     }
 
+    #[test]
+    fn test_d2xy_batch_matches_d2xy() {
+        for n in [1u32, 2, 4, 8, 16, 64, 256] {
+            let total = n as u64 * n as u64;
+            // Unaligned starts and partial groups at both ends
+            for (start, count) in [(0, total), (1, total - 1), (3, 6), (5, 2), (total - 1, 1)] {
+                if start >= total {
+                    continue;
+                }
+                let count = count.min(total - start) as usize;
+                let mut out = vec![u32::MAX; count * 2];
+                d2xy_batch(n, start, count, &mut out);
+
+                let expected: Vec<u32> = (start..start + count as u64)
+                    .flat_map(|d| {
+                        let (x, y) = d2xy(n, d);
+                        [x, y]
+                    })
+                    .collect();
+                assert_eq!(out, expected, "n={} start={} count={}", n, start, count);
+            }
+        }
+    }
+
+    #[cfg(feature = "parallel-lut")]
+    #[test]
+    fn test_parallel_lut_matches_sequential() {
+        let curve = HilbertCurve::new(512);
+        assert_eq!(curve.generate_gpu_lut_parallel(), curve.generate_gpu_lut());
+    }
+
+    #[test]
+    fn test_grid_capacity_bpp() {
+        // R8
+        assert_eq!(grid_capacity_bpp(256, 1), 65536);
+        // RG16 and RGBA8 are both 4 bytes
+        assert_eq!(grid_capacity_bpp(256, 4), grid_capacity(256));
+        assert_eq!(grid_capacity_bpp(16, 8), 2048);
+        // No longer overflows u32
+        assert_eq!(grid_capacity_bpp(65536, 4), 1 << 34);
+    }
+
+    #[test]
+    fn test_required_grid_size_is_minimal() {
+        assert_eq!(required_grid_size(0, 4), 1);
+        assert_eq!(required_grid_size(4, 4), 1);
+        assert_eq!(required_grid_size(5, 4), 2);
+        assert_eq!(required_grid_size(4096, 1), 64);
+        assert_eq!(required_grid_size(4097, 1), 128);
+        assert_eq!(required_grid_size(4097, 4), 64);
+
+        for len in [1usize, 100, 1000, 65536, 1_000_000] {
+            for bpp in [1u32, 2, 4] {
+                let n = required_grid_size(len, bpp);
+                assert!(grid_capacity_bpp(n, bpp) >= len);
+                assert!(n == 1 || grid_capacity_bpp(n / 2, bpp) < len);
+            }
+        }
+    }
+
     #[test]
     fn test_continuity() {
         // Verify that consecutive indices are spatially adjacent
@@ -460,4 +1121,273 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_cpu_fallback_when_lut_exceeds_storage_limit() {
+        let mut caps = GpuCapabilities {
+            supports_i64: false,
+            vendor_name: "Test GPU".to_string(),
+            device_name: "llvmpipe".to_string(),
+            features: wgpu::Features::empty(),
+            format_features: Default::default(),
+            limits: wgpu::Limits::default(),
+            vram_bytes: None,
+        };
+        let curve = HilbertCurve::new(32);
+        assert_eq!(curve.mapping_path(&caps), HilbertMappingPath::GpuLut);
+
+        caps.limits.max_storage_buffer_binding_size = 1024;
+        assert_eq!(curve.mapping_path(&caps), HilbertMappingPath::Cpu);
+
+        // The CPU path must match a shader scattering texel d to lut[d]
+        let linear: Vec<u8> = (0..curve.total_pixels * 4).map(|i| i as u8).collect();
+        let lut = curve.generate_gpu_lut();
+        let mut expected = vec![0u8; linear.len()];
+        for (d, texel) in linear.chunks(4).enumerate() {
+            let (x, y) = (lut[2 * d] as usize, lut[2 * d + 1] as usize);
+            let offset = (y * 32 + x) * 4;
+            expected[offset..offset + 4].copy_from_slice(texel);
+        }
+        assert_eq!(curve.remap_to_texture(&linear, 4), expected);
+    }
+
+    #[test]
+    fn test_gpu_lut_matches_rust_d2xy() {
+        let Some((device, queue)) = test_device() else {
+            return;
+        };
+
+        let curve = HilbertCurve::new(16);
+        let total = curve.n as u64 * curve.n as u64;
+        let lut = curve.create_gpu_lut_buffer(&device);
+
+        // Sample the LUT through hilbert_d2xy for every index
+        let source = format!(
+            "@group(0) @binding(0) var<storage, read> hilbert_lut: array<u32>;
+             @group(0) @binding(1) var<storage, read_write> out: array<u32>;
+             {HILBERT_LUT_WGSL}
+             @compute @workgroup_size(64)
+             fn main(@builtin(global_invocation_id) id: vec3<u32>) {{
+                 let d = id.x;
+                 if (d >= arrayLength(&hilbert_lut) / 2u) {{ return; }}
+                 let p = hilbert_d2xy(d);
+                 out[2u * d] = p.x;
+                 out[2u * d + 1u] = p.y;
+             }}"
+        );
+        let module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Hilbert LUT Compliance"),
+            source: wgpu::ShaderSource::Wgsl(source.into()),
+        });
+        let pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: Some("Hilbert LUT Compliance"),
+            layout: None,
+            module: &module,
+            entry_point: "main",
+        });
+
+        let size = total * 2 * 4;
+        let out = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Hilbert LUT Output"),
+            size,
+            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_SRC,
+            mapped_at_creation: false,
+        });
+        let readback = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Hilbert LUT Readback"),
+            size,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: None,
+            layout: &pipeline.get_bind_group_layout(0),
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: lut.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: out.as_entire_binding(),
+                },
+            ],
+        });
+
+        let mut encoder = device.create_command_encoder(&Default::default());
+        {
+            let mut pass = encoder.begin_compute_pass(&Default::default());
+            pass.set_pipeline(&pipeline);
+            pass.set_bind_group(0, &bind_group, &[]);
+            pass.dispatch_workgroups((total as u32).div_ceil(64), 1, 1);
+        }
+        encoder.copy_buffer_to_buffer(&out, 0, &readback, 0, size);
+        queue.submit(Some(encoder.finish()));
+
+        let slice = readback.slice(..);
+        slice.map_async(wgpu::MapMode::Read, |_| {});
+        device.poll(wgpu::Maintain::Wait);
+        let result: Vec<u32> = bytemuck::cast_slice(&slice.get_mapped_range()).to_vec();
+
+        for d in 0..total {
+            let gpu = (result[2 * d as usize], result[2 * d as usize + 1]);
+            assert_eq!(gpu, d2xy(16, d), "GPU LUT diverged at d={}", d);
+        }
+    }
+
+    #[test]
+    fn test_neighbors_are_symmetric() {
+        const N: usize = 0;
+        const E: usize = 1;
+        const S: usize = 2;
+        const W: usize = 3;
+
+        for n in [1, 2, 8, 16] {
+            let curve = HilbertCurve::new(n);
+            for d in 0..curve.total_pixels {
+                let neighbors = curve.neighbors(d);
+                let (x, y) = curve.d2xy(d);
+                let edges = [y == 0, x == n - 1, y == n - 1, x == 0];
+                let expected = [
+                    (y > 0).then(|| curve.xy2d(x, y - 1)),
+                    (x < n - 1).then(|| curve.xy2d(x + 1, y)),
+                    (y < n - 1).then(|| curve.xy2d(x, y + 1)),
+                    (x > 0).then(|| curve.xy2d(x - 1, y)),
+                ];
+                assert_eq!(neighbors, expected, "n={} d={}", n, d);
+                for (dir, opposite) in [(N, S), (E, W), (S, N), (W, E)] {
+                    assert_eq!(neighbors[dir].is_none(), edges[dir], "n={} d={}", n, d);
+                    if let Some(other) = neighbors[dir] {
+                        assert_eq!(curve.neighbors(other)[opposite], Some(d));
+                    }
+                }
+            }
+        }
+
+        // Deep curves carry across many levels
+        let curve = HilbertCurve::new(1 << 16);
+        for d in [
+            0,
+            0x5555_5555,
+            curve.total_pixels / 3,
+            curve.total_pixels - 1,
+        ] {
+            let (x, y) = curve.d2xy(d);
+            let east = (x < curve.n - 1).then(|| curve.xy2d(x + 1, y));
+            let north = (y > 0).then(|| curve.xy2d(x, y - 1));
+            assert_eq!(curve.neighbors(d)[E], east);
+            assert_eq!(curve.neighbors(d)[N], north);
+        }
+
+        // Consecutive distances are always adjacent
+        let curve = HilbertCurve::new(8);
+        for d in 1..curve.total_pixels {
+            assert!(curve.neighbors(d).contains(&Some(d - 1)));
+        }
+        assert_eq!(curve.neighbors(curve.total_pixels), [None; 4]);
+    }
+
+    #[test]
+    fn test_box_to_ranges_covers_exactly_the_box() {
+        use std::collections::HashSet;
+
+        let curve = HilbertCurve::new(16);
+        let boxes = [
+            (0, 0, 15, 15),
+            (0, 0, 0, 0),
+            (3, 5, 9, 6),
+            (7, 0, 8, 15),
+            (1, 1, 14, 14),
+            (10, 2, 30, 40), // clipped to the grid
+        ];
+        for (x0, y0, x1, y1) in boxes {
+            let ranges = curve.box_to_ranges(x0, y0, x1, y1);
+            for pair in ranges.windows(2) {
+                // Sorted, disjoint and not touching (adjacent ones are merged)
+                assert!(pair[0].1 + 1 < pair[1].0, "{:?}", ranges);
+            }
+
+            let covered: HashSet<(u32, u32)> = ranges
+                .iter()
+                .flat_map(|&(start, end)| start..=end)
+                .map(|d| curve.d2xy(d))
+                .collect();
+            let expected: HashSet<(u32, u32)> = curve
+                .iter()
+                .filter(|&(_, x, y)| (x0..=x1).contains(&x) && (y0..=y1).contains(&y))
+                .map(|(_, x, y)| (x, y))
+                .collect();
+            assert_eq!(covered, expected, "box {:?}", (x0, y0, x1, y1));
+            let total: u64 = ranges.iter().map(|(s, e)| e - s + 1).sum();
+            assert_eq!(total as usize, expected.len());
+        }
+
+        assert_eq!(curve.box_to_ranges(0, 0, 15, 15), vec![(0, 255)]);
+        assert!(curve.box_to_ranges(5, 0, 4, 3).is_empty());
+        assert!(curve.box_to_ranges(16, 0, 20, 3).is_empty());
+    }
+
+    #[test]
+    fn test_u64_matches_u32_on_small_grids() {
+        for n in [1u32, 2, 4, 64] {
+            for d in 0..n as u64 * n as u64 {
+                let (x, y) = d2xy(n, d);
+                assert_eq!(d2xy_u64(n as u64, d as u128), (x as u64, y as u64));
+                assert_eq!(xy2d_u64(n as u64, x as u64, y as u64), d as u128);
+            }
+        }
+    }
+
+    #[test]
+    fn test_u64_round_trip_large_orders() {
+        for order in [20u32, 24, 31] {
+            let n = 1u64 << order;
+            let total = n as u128 * n as u128;
+
+            // Ends of the curve, then a spread of distances
+            assert_eq!(d2xy_u64(n, 0), (0, 0));
+            assert_eq!(d2xy_u64(n, total - 1), (n - 1, 0));
+            let mut d = 0x9e37_79b9_7f4a_7c15u128 % total;
+            for _ in 0..2000 {
+                let (x, y) = d2xy_u64(n, d);
+                assert!(x < n && y < n);
+                assert_eq!(xy2d_u64(n, x, y), d, "order {} d {}", order, d);
+                d = (d * 6_364_136_223_846_793_005 + 1_442_695_040_888_963_407) % total;
+            }
+
+            // Neighbouring distances stay adjacent cells
+            let mid = total / 3;
+            let (ax, ay) = d2xy_u64(n, mid);
+            let (bx, by) = d2xy_u64(n, mid + 1);
+            assert_eq!(ax.abs_diff(bx) + ay.abs_diff(by), 1);
+        }
+    }
+
+    #[test]
+    fn test_iter_exact_size_and_double_ended() {
+        let curve = HilbertCurve::new(16);
+        let mut iter = curve.iter();
+        assert_eq!(iter.len(), 256);
+
+        let forward: Vec<_> = curve.iter().collect();
+        let mut backward: Vec<_> = curve.iter().rev().collect();
+        backward.reverse();
+        assert_eq!(forward, backward);
+        for &(d, x, y) in &forward {
+            assert_eq!(curve.d2xy(d), (x, y));
+        }
+
+        // Meeting in the middle yields every point exactly once
+        assert_eq!(iter.next(), Some(forward[0]));
+        assert_eq!(iter.next_back(), Some(forward[255]));
+        assert_eq!(iter.nth(10), Some(forward[11]));
+        assert_eq!(iter.len(), 243);
+        let rest: Vec<_> = iter.by_ref().collect();
+        assert_eq!(rest, forward[12..255]);
+        assert_eq!(iter.len(), 0);
+        assert_eq!((iter.next(), iter.next_back()), (None, None));
+
+        assert_eq!((&curve).into_iter().count(), 256);
+        assert_eq!(curve.iter().nth(1000), None);
+    }
 }
@@ -0,0 +1,100 @@
+//! Compliance test vectors for the Hilbert reference implementation.
+//!
+//! Generates the canonical `(n, d, (x, y))` tuples used to check the
+//! Python (`HilbertEngine.py`), WGSL and Rust implementations against each
+//! other, and reads/writes them in a plain-text fixture format:
+//!
+//! ```text
+//! n,d,x,y
+//! 4,0,0,0
+//! 4,1,1,0
+//! ```
+
+use super::{d2xy, HilbertCurve};
+
+/// One `(n, d, (x, y))` test vector
+pub type TestVector = (u32, u64, (u32, u32));
+
+/// Header line of a fixture file
+pub const FIXTURE_HEADER: &str = "n,d,x,y";
+
+/// Every vector of the curve with grid size `2^order`, in distance order.
+///
+/// The output has `4^order` entries, so keep `order` small (fixtures use
+/// orders up to about 8).
+///
+/// # Panics
+///
+/// Panics if `order` is 32 or more.
+///
+/// # Examples
+///
+/// ```
+/// use infinite_map_rs::hilbert::test_vectors::generate;
+/// assert_eq!(generate(1), vec![(2, 0, (0, 0)), (2, 1, (0, 1)), (2, 2, (1, 1)), (2, 3, (1, 0))]);
+/// ```
+pub fn generate(order: u32) -> Vec<TestVector> {
+    let curve = HilbertCurve::from_order(order);
+    (0..curve.total_pixels)
+        .map(|d| (curve.n, d, d2xy(curve.n, d)))
+        .collect()
+}
+
+/// Render vectors as a fixture file
+pub fn format(vectors: &[TestVector]) -> String {
+    let mut out = String::from(FIXTURE_HEADER);
+    out.push('\n');
+    for &(n, d, (x, y)) in vectors {
+        out.push_str(&format!("{},{},{},{}\n", n, d, x, y));
+    }
+    out
+}
+
+/// Parse a fixture file, e.g. one written by the Python implementation.
+///
+/// The header line is optional; blank lines and `#` comments are skipped.
+pub fn parse(text: &str) -> Result<Vec<TestVector>, String> {
+    let mut vectors = Vec::new();
+    for (index, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') || line == FIXTURE_HEADER {
+            continue;
+        }
+        let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+        let [n, d, x, y] = fields[..] else {
+            return Err(format!(
+                "line {}: expected 4 fields, got '{}'",
+                index + 1,
+                line
+            ));
+        };
+        let field_error = |e: std::num::ParseIntError| format!("line {}: {}", index + 1, e);
+        vectors.push((
+            n.parse().map_err(field_error)?,
+            d.parse().map_err(field_error)?,
+            (
+                x.parse().map_err(field_error)?,
+                y.parse().map_err(field_error)?,
+            ),
+        ));
+    }
+    Ok(vectors)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fixture_round_trip() {
+        let vectors = generate(3);
+        assert_eq!(vectors.len(), 64);
+        assert_eq!(parse(&format(&vectors)).unwrap(), vectors);
+
+        let python = "# from HilbertEngine.py\n4, 2, 1, 1\n\n4,3,0,1\n";
+        assert_eq!(parse(python).unwrap(), vec![(4, 2, (1, 1)), (4, 3, (0, 1))]);
+
+        assert!(parse("4,2,1\n").is_err());
+        assert!(parse("4,2,1,x\n").is_err());
+    }
+}
@@ -0,0 +1,328 @@
+//! Texture uploads laid out along the Hilbert curve.
+//!
+//! Linear data (memory dumps, byte streams) is shown as an n×n texture in
+//! which texel `d` sits at `d2xy(d)`. The scatter normally runs on the GPU
+//! through the LUT from [`HilbertCurve::create_gpu_lut_buffer`]; adapters
+//! whose storage binding limit cannot hold that LUT get the same texture
+//! from [`HilbertCurve::remap_to_texture`] instead.
+
+use super::{HilbertCurve, HilbertMappingPath, HILBERT_LUT_WGSL};
+use crate::gpu_capabilities::GpuCapabilities;
+
+/// Texels are RGBA8, moved as one `u32` each
+const BYTES_PER_TEXEL: usize = 4;
+
+/// Scatter shader workgroup size
+const WORKGROUP_SIZE: u32 = 64;
+
+/// Workgroups per dispatch dimension (WebGPU default limit)
+const MAX_WORKGROUPS_PER_DIM: u32 = 65535;
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+struct ScatterParams {
+    count: u32,
+    row_texels: u32,
+    _padding: [u32; 2],
+}
+
+/// Resources for the LUT scatter, sized for one curve
+#[derive(Debug)]
+struct GpuScatter {
+    pipeline: wgpu::ComputePipeline,
+    bind_group: wgpu::BindGroup,
+    linear: wgpu::Buffer,
+    texels: wgpu::Buffer,
+    params: wgpu::Buffer,
+    /// Texels per row of `texels`, padded for `copy_buffer_to_texture`
+    row_texels: u32,
+}
+
+impl GpuScatter {
+    fn new(device: &wgpu::Device, curve: &HilbertCurve) -> Self {
+        let lut = curve.create_gpu_lut_buffer(device);
+        let row_texels = curve
+            .n
+            .next_multiple_of(wgpu::COPY_BYTES_PER_ROW_ALIGNMENT / BYTES_PER_TEXEL as u32);
+
+        let linear = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Hilbert Scatter Input"),
+            size: curve.total_pixels * BYTES_PER_TEXEL as u64,
+            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let texels = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Hilbert Scatter Output"),
+            size: row_texels as u64 * curve.n as u64 * BYTES_PER_TEXEL as u64,
+            usage: wgpu::BufferUsages::STORAGE
+                | wgpu::BufferUsages::COPY_SRC
+                | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let params = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Hilbert Scatter Params"),
+            size: std::mem::size_of::<ScatterParams>() as u64,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let source = format!(
+            "{}\n{}",
+            include_str!("../shaders/hilbert_scatter.wgsl"),
+            HILBERT_LUT_WGSL
+        );
+        let module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Hilbert Scatter Shader"),
+            source: wgpu::ShaderSource::Wgsl(source.into()),
+        });
+        let pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: Some("Hilbert Scatter Pipeline"),
+            layout: None,
+            module: &module,
+            entry_point: "main",
+        });
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Hilbert Scatter Bind Group"),
+            layout: &pipeline.get_bind_group_layout(0),
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: lut.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: linear.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: texels.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 3,
+                    resource: params.as_entire_binding(),
+                },
+            ],
+        });
+
+        Self {
+            pipeline,
+            bind_group,
+            linear,
+            texels,
+            params,
+            row_texels,
+        }
+    }
+}
+
+/// Writes linear RGBA8 texels into an n×n texture in Hilbert order.
+///
+/// The path is fixed at creation from [`HilbertCurve::mapping_path`], so
+/// the LUT and scatter buffers are only allocated when the adapter can
+/// bind them.
+#[derive(Debug)]
+pub struct HilbertTextureUpload {
+    curve: HilbertCurve,
+    gpu: Option<GpuScatter>,
+}
+
+impl HilbertTextureUpload {
+    pub fn new(device: &wgpu::Device, curve: HilbertCurve, caps: &GpuCapabilities) -> Self {
+        let gpu = match curve.mapping_path(caps) {
+            HilbertMappingPath::GpuLut => Some(GpuScatter::new(device, &curve)),
+            HilbertMappingPath::Cpu => None,
+        };
+        Self { curve, gpu }
+    }
+
+    pub fn curve(&self) -> &HilbertCurve {
+        &self.curve
+    }
+
+    /// Which side does the remapping
+    pub fn path(&self) -> HilbertMappingPath {
+        match self.gpu {
+            Some(_) => HilbertMappingPath::GpuLut,
+            None => HilbertMappingPath::Cpu,
+        }
+    }
+
+    /// Replace the contents of `texture` with `linear` laid out on the curve.
+    ///
+    /// `linear` holds 4 bytes per texel; texels past its end are cleared
+    /// and a trailing partial texel is dropped. `texture` must be n×n, use
+    /// a 4-byte format and allow `COPY_DST`.
+    pub fn write(
+        &self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        texture: &wgpu::Texture,
+        linear: &[u8],
+    ) {
+        let count = (linear.len() / BYTES_PER_TEXEL).min(self.curve.total_pixels as usize);
+        let linear = &linear[..count * BYTES_PER_TEXEL];
+        let n = self.curve.n;
+        let extent = wgpu::Extent3d {
+            width: n,
+            height: n,
+            depth_or_array_layers: 1,
+        };
+
+        let Some(gpu) = &self.gpu else {
+            queue.write_texture(
+                texture.as_image_copy(),
+                &self.curve.remap_to_texture(linear, BYTES_PER_TEXEL),
+                wgpu::ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: Some(n * BYTES_PER_TEXEL as u32),
+                    rows_per_image: Some(n),
+                },
+                extent,
+            );
+            return;
+        };
+
+        if !linear.is_empty() {
+            queue.write_buffer(&gpu.linear, 0, linear);
+        }
+        let params = ScatterParams {
+            count: count as u32,
+            row_texels: gpu.row_texels,
+            _padding: [0; 2],
+        };
+        queue.write_buffer(&gpu.params, 0, bytemuck::bytes_of(&params));
+
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("Hilbert Scatter"),
+        });
+        encoder.clear_buffer(&gpu.texels, 0, None);
+        if count > 0 {
+            let groups = (count as u32).div_ceil(WORKGROUP_SIZE);
+            let x = groups.min(MAX_WORKGROUPS_PER_DIM);
+            let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+                label: Some("Hilbert Scatter"),
+                timestamp_writes: None,
+            });
+            pass.set_pipeline(&gpu.pipeline);
+            pass.set_bind_group(0, &gpu.bind_group, &[]);
+            pass.dispatch_workgroups(x, groups.div_ceil(x), 1);
+        }
+        encoder.copy_buffer_to_texture(
+            wgpu::ImageCopyBuffer {
+                buffer: &gpu.texels,
+                layout: wgpu::ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: Some(gpu.row_texels * BYTES_PER_TEXEL as u32),
+                    rows_per_image: Some(n),
+                },
+            },
+            texture.as_image_copy(),
+            extent,
+        );
+        queue.submit(Some(encoder.finish()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::gpu::test_device;
+
+    fn caps_with_storage_limit(limit: u32) -> GpuCapabilities {
+        GpuCapabilities {
+            supports_i64: false,
+            vendor_name: "Test GPU".to_string(),
+            device_name: "llvmpipe".to_string(),
+            features: wgpu::Features::empty(),
+            format_features: Default::default(),
+            limits: wgpu::Limits {
+                max_storage_buffer_binding_size: limit,
+                ..Default::default()
+            },
+            vram_bytes: None,
+        }
+    }
+
+    fn read_texture(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        texture: &wgpu::Texture,
+    ) -> Vec<u8> {
+        let n = texture.width();
+        let row_bytes = n * 4;
+        let padded = row_bytes.next_multiple_of(wgpu::COPY_BYTES_PER_ROW_ALIGNMENT);
+        let readback = device.create_buffer(&wgpu::BufferDescriptor {
+            label: None,
+            size: (padded * n) as u64,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+        let mut encoder = device.create_command_encoder(&Default::default());
+        encoder.copy_texture_to_buffer(
+            texture.as_image_copy(),
+            wgpu::ImageCopyBuffer {
+                buffer: &readback,
+                layout: wgpu::ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: Some(padded),
+                    rows_per_image: Some(n),
+                },
+            },
+            texture.size(),
+        );
+        queue.submit(Some(encoder.finish()));
+
+        let slice = readback.slice(..);
+        slice.map_async(wgpu::MapMode::Read, |_| {});
+        device.poll(wgpu::Maintain::Wait);
+        let mapped = slice.get_mapped_range();
+        mapped
+            .chunks(padded as usize)
+            .flat_map(|row| &row[..row_bytes as usize])
+            .copied()
+            .collect()
+    }
+
+    #[test]
+    fn test_gpu_scatter_matches_cpu_remap() {
+        let Some((device, queue)) = test_device() else {
+            return;
+        };
+
+        // 32 texels per row pads to 64 in the scatter buffer
+        let curve = HilbertCurve::new(32);
+        let linear: Vec<u8> = (0..1000u32 * 4 + 3).map(|i| (i * 7) as u8).collect();
+
+        let gpu = HilbertTextureUpload::new(&device, curve, &caps_with_storage_limit(u32::MAX));
+        let cpu = HilbertTextureUpload::new(&device, curve, &caps_with_storage_limit(1024));
+        assert_eq!(gpu.path(), HilbertMappingPath::GpuLut);
+        assert_eq!(cpu.path(), HilbertMappingPath::Cpu);
+
+        let expected = curve.remap_to_texture(&linear[..4000], 4);
+        for upload in [&gpu, &cpu] {
+            let texture = device.create_texture(&wgpu::TextureDescriptor {
+                label: None,
+                size: wgpu::Extent3d {
+                    width: 32,
+                    height: 32,
+                    depth_or_array_layers: 1,
+                },
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format: wgpu::TextureFormat::Rgba8Unorm,
+                usage: wgpu::TextureUsages::COPY_DST | wgpu::TextureUsages::COPY_SRC,
+                view_formats: &[],
+            });
+            // A second, shorter write must clear what the first left behind
+            upload.write(&device, &queue, &texture, &[0xff; 4096]);
+            upload.write(&device, &queue, &texture, &linear);
+            assert_eq!(
+                read_texture(&device, &queue, &texture),
+                expected,
+                "{:?} path",
+                upload.path()
+            );
+        }
+    }
+}
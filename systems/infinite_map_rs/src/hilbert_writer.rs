@@ -36,6 +36,9 @@ The batch operations are structured for optimal auto-vectorization:
 
 use serde::{Deserialize, Serialize};
 
+/// Bytes per RGBA8 texel in the staged pixel buffer
+const BYTES_PER_PIXEL: usize = 4;
+
 /// A single weight mutation
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WeightMutation {
@@ -99,6 +102,10 @@ pub struct HilbertWriter {
     cache_access: std::collections::VecDeque<u32>,
     /// Maximum cache size
     cache_max_size: usize,
+    /// RGBA staging buffer filled by `write_all` (allocated on first use)
+    staged: Vec<u8>,
+    /// Whether `staged` holds data not yet uploaded
+    staged_dirty: bool,
 }
 
 impl HilbertWriter {
@@ -111,7 +118,49 @@ impl HilbertWriter {
             coord_cache: std::collections::HashMap::with_capacity(1024),
             cache_access: std::collections::VecDeque::with_capacity(1024),
             cache_max_size: 1024,
+            staged: Vec::new(),
+            staged_dirty: false,
+        }
+    }
+
+    /// Maximum number of bytes `write_all` can place in the atlas
+    pub fn capacity(&self) -> usize {
+        self.atlas_size as usize * self.atlas_size as usize * BYTES_PER_PIXEL
+    }
+
+    /// Stage `data` into the RGBA buffer in Hilbert order, in one pass
+    ///
+    /// Every 4 bytes form one pixel; pixel `i` lands at the Hilbert
+    /// coordinate of index `i`. Replaces any previously staged payload.
+    pub fn write_all(&mut self, data: &[u8]) -> Result<(), String> {
+        let capacity = self.capacity();
+        if data.len() > capacity {
+            return Err(format!(
+                "Payload of {} bytes exceeds writer capacity of {} bytes",
+                data.len(),
+                capacity
+            ));
+        }
+
+        if self.staged.len() == capacity {
+            self.staged.fill(0);
+        } else {
+            self.staged = vec![0u8; capacity];
+        }
+
+        for (index, pixel) in data.chunks(BYTES_PER_PIXEL).enumerate() {
+            let (x, y) = hilbert_index_to_coord(self.order, index as u32);
+            let offset = (y as usize * self.atlas_size as usize + x as usize) * BYTES_PER_PIXEL;
+            self.staged[offset..offset + pixel.len()].copy_from_slice(pixel);
         }
+        self.staged_dirty = true;
+
+        Ok(())
+    }
+
+    /// The staged RGBA buffer (row-major, `atlas_size` x `atlas_size`)
+    pub fn buffer(&self) -> &[u8] {
+        &self.staged
     }
 
     /// Convert 1D Hilbert index to 2D coordinates with caching
@@ -246,9 +295,35 @@ impl HilbertWriter {
     }
 
     /// Flush pending writes to texture (called by compositor)
-    pub fn flush_to_texture(&mut self, _queue: &wgpu::Queue, _texture: &wgpu::Texture) -> usize {
+    ///
+    /// A buffer staged by `write_all` is uploaded with a single
+    /// `write_texture` call. Returns the number of pending mutations flushed.
+    pub fn flush_to_texture(&mut self, queue: &wgpu::Queue, texture: &wgpu::Texture) -> usize {
         let count = self.pending_writes.len();
 
+        if self.staged_dirty {
+            queue.write_texture(
+                wgpu::ImageCopyTexture {
+                    texture,
+                    mip_level: 0,
+                    origin: wgpu::Origin3d::ZERO,
+                    aspect: wgpu::TextureAspect::All,
+                },
+                &self.staged,
+                wgpu::ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: Some(self.atlas_size * BYTES_PER_PIXEL as u32),
+                    rows_per_image: Some(self.atlas_size),
+                },
+                wgpu::Extent3d {
+                    width: self.atlas_size,
+                    height: self.atlas_size,
+                    depth_or_array_layers: 1,
+                },
+            );
+            self.staged_dirty = false;
+        }
+
         // In production, this would use queue.write_texture() for each write
         // For now, just clear the pending queue
         self.pending_writes.clear();
@@ -397,6 +472,29 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_write_all_places_bytes_in_hilbert_order() {
+        let mut writer = HilbertWriter::new(8);
+        assert_eq!(writer.capacity(), 8 * 8 * 4);
+
+        // 10 bytes: two full pixels and a partial third
+        let payload: Vec<u8> = (1..=10).collect();
+        writer.write_all(&payload).unwrap();
+
+        let buffer = writer.buffer();
+        assert_eq!(buffer.len(), writer.capacity());
+        for (index, pixel) in payload.chunks(4).enumerate() {
+            let (x, y) = hilbert_index_to_coord(3, index as u32);
+            let offset = ((y * 8 + x) * 4) as usize;
+            assert_eq!(&buffer[offset..offset + pixel.len()], pixel);
+        }
+        // Index 2 maps to (1, 1); its unused channels stay zero
+        assert_eq!(&buffer[(8 + 1) * 4..(8 + 1) * 4 + 4], &[9, 10, 0, 0]);
+        assert_eq!(buffer.iter().filter(|&&b| b != 0).count(), payload.len());
+
+        assert!(writer.write_all(&vec![0u8; writer.capacity() + 1]).is_err());
+    }
+
     #[test]
     fn test_cache_functionality() {
         let mut writer = HilbertWriter::new(1024);
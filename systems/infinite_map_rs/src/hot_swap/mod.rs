@@ -37,9 +37,9 @@
 //! ```
 
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::path::PathBuf;
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 // Phase 40.5 Task 2: ModuleManager for dynamic .so loading
 pub mod module_manager;
@@ -88,6 +88,7 @@ pub struct VatHeader {
     pub vat_id: VatId,
     pub timestamp: f64,
     pub data_size: u32,
+    /// CRC32 (IEEE) of the header fields and data, widened to u64
     pub checksum: u64,
 }
 
@@ -108,28 +109,28 @@ impl VatHeader {
 
     /// Calculate checksum for the header
     pub fn calculate_checksum(&mut self, data: &[u8]) {
-        use std::collections::hash_map::DefaultHasher;
-        use std::hash::{Hash, Hasher};
-
-        let mut hasher = DefaultHasher::new();
-        self.version.hash(&mut hasher);
-        self.vat_id.hash(&mut hasher);
-        self.timestamp.to_bits().hash(&mut hasher);
-        data.hash(&mut hasher);
-        self.checksum = hasher.finish();
+        self.checksum = self.compute_checksum(data);
     }
 
     /// Verify the checksum
     pub fn verify(&self, data: &[u8]) -> bool {
-        use std::collections::hash_map::DefaultHasher;
-        use std::hash::{Hash, Hasher};
+        self.compute_checksum(data) == self.checksum
+    }
 
-        let mut hasher = DefaultHasher::new();
-        self.version.hash(&mut hasher);
-        self.vat_id.hash(&mut hasher);
-        self.timestamp.to_bits().hash(&mut hasher);
-        data.hash(&mut hasher);
-        hasher.finish() == self.checksum
+    /// CRC32 over little-endian `version`, length-prefixed `vat_id`,
+    /// `timestamp` bits and `data`
+    ///
+    /// Stable across Rust versions and platforms, so persisted Vats verify
+    /// on any build.
+    fn compute_checksum(&self, data: &[u8]) -> u64 {
+        let vat_id = self.vat_id.as_str().as_bytes();
+        let mut hasher = crc32fast::Hasher::new();
+        hasher.update(&self.version.to_le_bytes());
+        hasher.update(&(vat_id.len() as u32).to_le_bytes());
+        hasher.update(vat_id);
+        hasher.update(&self.timestamp.to_bits().to_le_bytes());
+        hasher.update(data);
+        hasher.finalize() as u64
     }
 }
 
@@ -141,6 +142,9 @@ pub struct VatBuffer {
     /// Read position cursor (not serialized)
     #[serde(skip)]
     read_pos: usize,
+    /// Maximum data size in bytes; `None` means unbounded (not serialized)
+    #[serde(skip)]
+    max_size: Option<usize>,
 }
 
 impl VatBuffer {
@@ -151,6 +155,7 @@ impl VatBuffer {
             header,
             data: Vec::new(),
             read_pos: 0,
+            max_size: None,
         }
     }
 
@@ -163,6 +168,29 @@ impl VatBuffer {
             header,
             data,
             read_pos: 0,
+            max_size: None,
+        }
+    }
+
+    /// Cap the buffer at `max_size` bytes
+    ///
+    /// Once set, any `write_*` call that would grow the data past the cap
+    /// fails with `VatError::BufferOverflow` and leaves the buffer unchanged.
+    pub fn with_capacity_limit(mut self, max_size: usize) -> Self {
+        self.max_size = Some(max_size);
+        self
+    }
+
+    /// Get the configured size cap, if any
+    pub fn capacity_limit(&self) -> Option<usize> {
+        self.max_size
+    }
+
+    /// Check that `additional` bytes fit under the size cap
+    fn ensure_capacity(&self, additional: usize) -> Result<(), VatError> {
+        match self.max_size {
+            Some(max) if self.data.len() + additional > max => Err(VatError::BufferOverflow),
+            _ => Ok(()),
         }
     }
 
@@ -190,36 +218,69 @@ impl VatBuffer {
 
     /// Write a u32 to the buffer
     pub fn write_u32(&mut self, value: u32) -> Result<(), VatError> {
+        self.ensure_capacity(4)?;
         self.data.extend_from_slice(&value.to_le_bytes());
         Ok(())
     }
 
     /// Write a u64 to the buffer
     pub fn write_u64(&mut self, value: u64) -> Result<(), VatError> {
+        self.ensure_capacity(8)?;
         self.data.extend_from_slice(&value.to_le_bytes());
         Ok(())
     }
 
     /// Write a f32 to the buffer
     pub fn write_f32(&mut self, value: f32) -> Result<(), VatError> {
+        self.ensure_capacity(4)?;
         self.data.extend_from_slice(&value.to_le_bytes());
         Ok(())
     }
 
+    /// Write a f64 to the buffer
+    pub fn write_f64(&mut self, value: f64) -> Result<(), VatError> {
+        self.ensure_capacity(8)?;
+        self.data.extend_from_slice(&value.to_le_bytes());
+        Ok(())
+    }
+
+    /// Write an i32 to the buffer
+    pub fn write_i32(&mut self, value: i32) -> Result<(), VatError> {
+        self.ensure_capacity(4)?;
+        self.data.extend_from_slice(&value.to_le_bytes());
+        Ok(())
+    }
+
+    /// Write an i64 to the buffer
+    pub fn write_i64(&mut self, value: i64) -> Result<(), VatError> {
+        self.ensure_capacity(8)?;
+        self.data.extend_from_slice(&value.to_le_bytes());
+        Ok(())
+    }
+
+    /// Write a bool as a single 0/1 byte
+    pub fn write_bool(&mut self, value: bool) -> Result<(), VatError> {
+        self.write_u8(value as u8)
+    }
+
     /// Write a byte slice to the buffer
     pub fn write_bytes(&mut self, bytes: &[u8]) -> Result<(), VatError> {
+        self.ensure_capacity(bytes.len())?;
         self.data.extend_from_slice(bytes);
         Ok(())
     }
 
     /// Write a single u8 byte
     pub fn write_u8(&mut self, value: u8) -> Result<(), VatError> {
+        self.ensure_capacity(1)?;
         self.data.push(value);
         Ok(())
     }
 
     /// Write a string to the buffer
     pub fn write_string(&mut self, s: &str) -> Result<(), VatError> {
+        // Check the whole record up front so a failed write leaves no partial length prefix
+        self.ensure_capacity(4 + s.len())?;
         let len = s.len() as u32;
         self.write_u32(len)?;
         self.data.extend_from_slice(s.as_bytes());
@@ -285,6 +346,35 @@ impl VatBuffer {
         Ok(f32::from_le_bytes(bytes))
     }
 
+    /// Read a f64 from the buffer
+    pub fn read_f64(&mut self) -> Result<f64, VatError> {
+        Ok(f64::from_bits(self.read_u64()?))
+    }
+
+    /// Read an i32 from the buffer
+    pub fn read_i32(&mut self) -> Result<i32, VatError> {
+        Ok(self.read_u32()? as i32)
+    }
+
+    /// Read an i64 from the buffer
+    pub fn read_i64(&mut self) -> Result<i64, VatError> {
+        Ok(self.read_u64()? as i64)
+    }
+
+    /// Read a bool written by `write_bool`
+    ///
+    /// Bytes other than 0 and 1 are rejected rather than coerced.
+    pub fn read_bool(&mut self) -> Result<bool, VatError> {
+        match self.read_u8()? {
+            0 => Ok(false),
+            1 => Ok(true),
+            other => Err(VatError::DeserializationFailed(format!(
+                "invalid bool byte {:#04x}",
+                other
+            ))),
+        }
+    }
+
     /// Read bytes from the buffer
     pub fn read_bytes(&mut self, len: usize) -> Result<Vec<u8>, VatError> {
         if self.read_pos + len > self.data.len() {
@@ -305,6 +395,106 @@ impl VatBuffer {
         self.read_pos += len;
         Ok(s)
     }
+
+    /// Changes needed to turn this buffer into `other`
+    ///
+    /// Differing bytes are grouped into runs; runs separated by fewer than
+    /// `DELTA_MERGE_GAP` unchanged bytes are merged to save per-run overhead.
+    pub fn diff(&self, other: &VatBuffer) -> VatDelta {
+        let mut runs: Vec<(u32, Vec<u8>)> = Vec::new();
+        let mut run_start: Option<usize> = None;
+        let mut last_changed = 0;
+
+        for (i, &byte) in other.data.iter().enumerate() {
+            if self.data.get(i) == Some(&byte) {
+                continue;
+            }
+            match run_start {
+                Some(start) if i - last_changed > DELTA_MERGE_GAP => {
+                    runs.push((start as u32, other.data[start..=last_changed].to_vec()));
+                    run_start = Some(i);
+                },
+                Some(_) => {},
+                None => run_start = Some(i),
+            }
+            last_changed = i;
+        }
+        if let Some(start) = run_start {
+            runs.push((start as u32, other.data[start..=last_changed].to_vec()));
+        }
+
+        VatDelta {
+            base_checksum: self.header.checksum,
+            header: other.header.clone(),
+            runs,
+        }
+    }
+
+    /// Apply a delta produced by `diff` against this buffer's contents
+    ///
+    /// The data is resized to the target length and the target header is
+    /// adopted. Fails without modifying the buffer if the delta was made
+    /// from a different base, grows the buffer by more bytes than it carries,
+    /// does not fit the capacity limit, or does not reproduce the target
+    /// checksum.
+    pub fn apply_delta(&mut self, delta: &VatDelta) -> Result<(), VatError> {
+        if delta.base_checksum != self.header.checksum {
+            return Err(VatError::DeserializationFailed(
+                "delta was made from a different base".to_string(),
+            ));
+        }
+        let target_len = delta.header.data_size as usize;
+        // Every appended byte is carried by a run, so the claimed size is
+        // checked before anything is allocated for it
+        if target_len > self.data.len() + delta.payload_len() {
+            return Err(VatError::DeserializationFailed(
+                "delta size exceeds its payload".to_string(),
+            ));
+        }
+        if matches!(self.max_size, Some(max) if target_len > max) {
+            return Err(VatError::BufferOverflow);
+        }
+
+        let mut data = self.data.clone();
+        data.resize(target_len, 0);
+        for (offset, bytes) in &delta.runs {
+            let start = *offset as usize;
+            let end = start + bytes.len();
+            if end > target_len {
+                return Err(VatError::BufferOverflow);
+            }
+            data[start..end].copy_from_slice(bytes);
+        }
+        if !delta.header.verify(&data) {
+            return Err(VatError::InvalidVersion);
+        }
+
+        self.data = data;
+        self.header = delta.header.clone();
+        self.reset_cursor();
+        Ok(())
+    }
+}
+
+/// Unchanged bytes tolerated inside a single delta run
+const DELTA_MERGE_GAP: usize = 8;
+
+/// Byte-level changes between two VatBuffers (see `VatBuffer::diff`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VatDelta {
+    /// Checksum of the buffer the delta applies to
+    pub base_checksum: u64,
+    /// Header of the resulting buffer (its `data_size` is the target length)
+    pub header: VatHeader,
+    /// `(offset, bytes)` runs to overwrite, in ascending offset order
+    pub runs: Vec<(u32, Vec<u8>)>,
+}
+
+impl VatDelta {
+    /// Total bytes carried by the runs
+    pub fn payload_len(&self) -> usize {
+        self.runs.iter().map(|(_, bytes)| bytes.len()).sum()
+    }
 }
 
 /// Trait for types that can be serialized/deserialized to/from a Vat
@@ -318,9 +508,16 @@ pub trait VatState {
     /// Deserialize state from a VatBuffer
     fn deserialize_from_vat(&mut self, vat: &mut VatBuffer) -> Result<(), VatError>;
 
+    /// Layout version written to the header; bump when the layout changes
+    /// and register a `VatMigration` for older Vats
+    fn vat_version(&self) -> u32 {
+        1
+    }
+
     /// Get the current state as a VatBuffer
     fn to_vat_buffer(&self) -> Result<VatBuffer, VatError> {
         let mut buffer = VatBuffer::new(self.vat_id());
+        buffer.header.version = self.vat_version();
         self.serialize_to_vat(&mut buffer)?;
         buffer.finalize();
         Ok(buffer)
@@ -328,7 +525,7 @@ pub trait VatState {
 
     /// Restore state from a VatBuffer
     fn from_vat_buffer(&mut self, buffer: &mut VatBuffer) -> Result<(), VatError> {
-        if !buffer.verify() {
+        if !buffer.verify() || buffer.header.version != self.vat_version() {
             return Err(VatError::InvalidVersion);
         }
         buffer.reset_cursor();
@@ -336,21 +533,202 @@ pub trait VatState {
     }
 }
 
+/// Upgrades Vats written with an older layout version
+///
+/// Registered per Vat with [`VatRegistry::register_migration`] and applied by
+/// [`VatRegistry::load_vat`] when the stored version is older than
+/// `current_version`.
+pub trait VatMigration: Send {
+    /// Layout version this migration produces
+    fn current_version(&self) -> u32;
+
+    /// Rewrite `buffer.data` from `from_version` to the current layout
+    ///
+    /// The cursor is at the start of the data. The registry updates the
+    /// header version and checksum afterwards.
+    fn migrate(&self, from_version: u32, buffer: &mut VatBuffer) -> Result<(), VatError>;
+}
+
+/// Magic prefix identifying a binary-encoded Vat file
+const VAT_BINARY_MAGIC: &[u8; 4] = b"VATB";
+
+/// Magic prefix identifying a zlib-compressed Vat file
+const VAT_ZLIB_MAGIC: &[u8; 4] = b"VATZ";
+
+/// Compression applied to encoded Vats on disk
+///
+/// Loading detects compressed files from their magic prefix, so a registry
+/// reads Vats written with any setting.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum VatCompression {
+    /// Write the encoded Vat as is
+    #[default]
+    None,
+    /// zlib-compress the encoded Vat (worth it for mostly-zero dumps)
+    Zlib,
+}
+
+impl VatCompression {
+    /// Compress an encoded Vat
+    pub fn compress(&self, encoded: Vec<u8>) -> Result<Vec<u8>, VatError> {
+        use flate2::write::ZlibEncoder;
+        use std::io::Write;
+
+        match self {
+            VatCompression::None => Ok(encoded),
+            VatCompression::Zlib => {
+                let mut bytes = VAT_ZLIB_MAGIC.to_vec();
+                let mut encoder = ZlibEncoder::new(&mut bytes, flate2::Compression::default());
+                encoder
+                    .write_all(&encoded)
+                    .and_then(|_| encoder.finish().map(|_| ()))
+                    .map_err(|e| VatError::SerializationFailed(e.to_string()))?;
+                Ok(bytes)
+            },
+        }
+    }
+
+    /// Undo `compress`, passing uncompressed files through
+    pub fn decompress(bytes: Vec<u8>) -> Result<Vec<u8>, VatError> {
+        use flate2::read::ZlibDecoder;
+        use std::io::Read;
+
+        let Some(body) = bytes.strip_prefix(VAT_ZLIB_MAGIC.as_slice()) else {
+            return Ok(bytes);
+        };
+        let mut encoded = Vec::new();
+        ZlibDecoder::new(body)
+            .read_to_end(&mut encoded)
+            .map_err(|e| VatError::DeserializationFailed(e.to_string()))?;
+        Ok(encoded)
+    }
+}
+
+/// On-disk encoding used when persisting Vats
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum VatFormat {
+    /// Pretty-printed JSON (`.vat`), the default for debuggability
+    #[default]
+    Json,
+    /// Compact bincode with a magic prefix (`.vatb`), for large snapshots
+    Binary,
+}
+
+impl VatFormat {
+    /// File extension used for this format
+    pub fn extension(&self) -> &'static str {
+        match self {
+            VatFormat::Json => "vat",
+            VatFormat::Binary => "vatb",
+        }
+    }
+
+    /// Encode a VatBuffer into this format
+    pub fn encode(&self, buffer: &VatBuffer) -> Result<Vec<u8>, VatError> {
+        match self {
+            VatFormat::Json => serde_json::to_vec_pretty(buffer)
+                .map_err(|e| VatError::SerializationFailed(e.to_string())),
+            VatFormat::Binary => {
+                let body = bincode::serde::encode_to_vec(buffer, bincode::config::standard())
+                    .map_err(|e| VatError::SerializationFailed(e.to_string()))?;
+                let mut bytes = Vec::with_capacity(VAT_BINARY_MAGIC.len() + body.len());
+                bytes.extend_from_slice(VAT_BINARY_MAGIC);
+                bytes.extend_from_slice(&body);
+                Ok(bytes)
+            },
+        }
+    }
+
+    /// Decode a VatBuffer, detecting the format from the magic prefix
+    pub fn decode(bytes: &[u8]) -> Result<VatBuffer, VatError> {
+        if let Some(body) = bytes.strip_prefix(VAT_BINARY_MAGIC.as_slice()) {
+            let (buffer, _) = bincode::serde::decode_from_slice(body, bincode::config::standard())
+                .map_err(|e| VatError::DeserializationFailed(e.to_string()))?;
+            Ok(buffer)
+        } else {
+            serde_json::from_slice(bytes)
+                .map_err(|e| VatError::DeserializationFailed(e.to_string()))
+        }
+    }
+}
+
+/// Vats a registry keeps in memory unless configured otherwise
+pub const DEFAULT_MAX_RESIDENT: usize = 256;
+
 /// Global Vat Registry for managing all active Vats
 pub struct VatRegistry {
     vats: HashMap<VatId, VatBuffer>,
+    /// Access tick of each resident Vat (smallest is least recently used)
+    last_access: HashMap<VatId, u64>,
+    access_tick: u64,
+    max_resident: usize,
     storage_path: PathBuf,
+    format: VatFormat,
+    compression: VatCompression,
+    migrations: HashMap<VatId, Box<dyn VatMigration>>,
 }
 
 impl VatRegistry {
-    /// Create a new VatRegistry
+    /// Create a new VatRegistry (persists as JSON)
     pub fn new(storage_path: PathBuf) -> Self {
+        Self::new_with_format(storage_path, VatFormat::default())
+    }
+
+    /// Create a new VatRegistry that persists using the given format
+    pub fn new_with_format(storage_path: PathBuf, format: VatFormat) -> Self {
         Self {
             vats: HashMap::new(),
+            last_access: HashMap::new(),
+            access_tick: 0,
+            max_resident: DEFAULT_MAX_RESIDENT,
             storage_path,
+            format,
+            compression: VatCompression::default(),
+            migrations: HashMap::new(),
         }
     }
 
+    /// Compress Vats written by this registry
+    pub fn with_compression(mut self, compression: VatCompression) -> Self {
+        self.compression = compression;
+        self
+    }
+
+    /// Keep at most `max_resident` Vats in memory (at least one)
+    ///
+    /// Least recently accessed Vats beyond the cap are evicted; they stay on
+    /// disk and are reloaded on their next access.
+    pub fn with_max_resident(mut self, max_resident: usize) -> Self {
+        self.max_resident = max_resident.max(1);
+        self.evict_excess();
+        self
+    }
+
+    /// Get the cap on in-memory Vats
+    pub fn max_resident(&self) -> usize {
+        self.max_resident
+    }
+
+    /// Number of Vats currently held in memory
+    pub fn resident_count(&self) -> usize {
+        self.vats.len()
+    }
+
+    /// Get the compression applied on persist
+    pub fn compression(&self) -> VatCompression {
+        self.compression
+    }
+
+    /// Register the migration used when loading older versions of a Vat
+    pub fn register_migration(&mut self, vat_id: VatId, migration: Box<dyn VatMigration>) {
+        self.migrations.insert(vat_id, migration);
+    }
+
+    /// Get the persistence format
+    pub fn format(&self) -> VatFormat {
+        self.format
+    }
+
     /// Register a Vat (store state in memory and optionally persist to disk)
     pub fn register_vat(&mut self, buffer: VatBuffer) -> Result<(), VatError> {
         let vat_id = buffer.header.vat_id.clone();
@@ -360,30 +738,72 @@ impl VatRegistry {
             return Err(VatError::InvalidVersion);
         }
 
-        // Store in memory
-        self.vats.insert(vat_id.clone(), buffer.clone());
-
-        // Persist to disk
+        // Store in memory and persist to disk before anything is evicted
+        self.vats.insert(vat_id.clone(), buffer);
+        self.touch(&vat_id);
         self.persist_vat(&vat_id)?;
+        self.evict_excess();
 
         Ok(())
     }
 
-    /// Get a Vat by ID
-    pub fn get_vat(&self, vat_id: &VatId) -> Option<&VatBuffer> {
-        self.vats.get(vat_id)
+    /// Get a Vat by ID, reloading it from disk if it was evicted
+    pub fn get_vat(&mut self, vat_id: &VatId) -> Option<&VatBuffer> {
+        self.get_vat_mut(vat_id).map(|buffer| &*buffer)
     }
 
-    /// Get a mutable Vat by ID
+    /// Get a mutable Vat by ID, reloading it from disk if it was evicted
     pub fn get_vat_mut(&mut self, vat_id: &VatId) -> Option<&mut VatBuffer> {
+        if self.vats.contains_key(vat_id) {
+            self.touch(vat_id);
+        } else if let Err(e) = self.load_vat(vat_id) {
+            log::debug!("Vat {} not available: {:?}", vat_id.as_str(), e);
+            return None;
+        }
         self.vats.get_mut(vat_id)
     }
 
     /// Remove a Vat (unregister)
     pub fn unregister_vat(&mut self, vat_id: &VatId) -> Option<VatBuffer> {
+        self.last_access.remove(vat_id);
         self.vats.remove(vat_id)
     }
 
+    /// Mark a resident Vat as most recently used
+    fn touch(&mut self, vat_id: &VatId) {
+        self.access_tick += 1;
+        self.last_access.insert(vat_id.clone(), self.access_tick);
+    }
+
+    /// Drop least recently used Vats until at most `max_resident` remain
+    ///
+    /// Evicted Vats are persisted first, so changes made through
+    /// `get_vat_mut` survive; a Vat that fails to persist stays resident.
+    fn evict_excess(&mut self) {
+        let mut candidates: Vec<(u64, VatId)> = self
+            .vats
+            .keys()
+            .map(|id| (self.last_access.get(id).copied().unwrap_or(0), id.clone()))
+            .collect();
+        candidates.sort_unstable_by_key(|(tick, _)| *tick);
+
+        let excess = self.vats.len().saturating_sub(self.max_resident);
+        for (_, vat_id) in candidates.into_iter().take(excess) {
+            if let Err(e) = self.persist_vat(&vat_id) {
+                log::warn!("Keeping vat {} resident: {:?}", vat_id.as_str(), e);
+                continue;
+            }
+            self.vats.remove(&vat_id);
+            self.last_access.remove(&vat_id);
+        }
+    }
+
+    /// Path of a Vat file in the given format
+    fn vat_path(&self, vat_id: &VatId, format: VatFormat) -> PathBuf {
+        self.storage_path
+            .join(format!("{}.{}", vat_id.as_str(), format.extension()))
+    }
+
     /// Persist a Vat to disk
     fn persist_vat(&self, vat_id: &VatId) -> Result<(), VatError> {
         use std::fs;
@@ -395,35 +815,84 @@ impl VatRegistry {
             .map_err(|e| VatError::SerializationFailed(e.to_string()))?;
 
         // Write to file
-        let file_path = self.storage_path.join(format!("{}.vat", vat_id.as_str()));
-        let json = serde_json::to_string_pretty(buffer)
-            .map_err(|e| VatError::SerializationFailed(e.to_string()))?;
+        let file_path = self.vat_path(vat_id, self.format);
+        let bytes = self.compression.compress(self.format.encode(buffer)?)?;
 
-        fs::write(file_path, json).map_err(|e| VatError::SerializationFailed(e.to_string()))?;
+        fs::write(file_path, bytes).map_err(|e| VatError::SerializationFailed(e.to_string()))?;
 
         Ok(())
     }
 
     /// Load a Vat from disk
+    ///
+    /// Looks for the registry's own format first and falls back to the other
+    /// extension; the encoding and compression are detected from the file
+    /// contents.
+    /// Vats older than their registered migration's version are migrated and
+    /// persisted in the new layout; newer ones are rejected.
     pub fn load_vat(&mut self, vat_id: &VatId) -> Result<VatBuffer, VatError> {
         use std::fs;
 
-        let file_path = self.storage_path.join(format!("{}.vat", vat_id.as_str()));
-        let json = fs::read_to_string(&file_path)
-            .map_err(|e| VatError::DeserializationFailed(e.to_string()))?;
+        let fallback = match self.format {
+            VatFormat::Json => VatFormat::Binary,
+            VatFormat::Binary => VatFormat::Json,
+        };
+        let file_path = [self.format, fallback]
+            .into_iter()
+            .map(|format| self.vat_path(vat_id, format))
+            .find(|path| path.exists())
+            .unwrap_or_else(|| self.vat_path(vat_id, self.format));
 
-        let buffer: VatBuffer = serde_json::from_str(&json)
-            .map_err(|e| VatError::DeserializationFailed(e.to_string()))?;
+        let bytes =
+            fs::read(&file_path).map_err(|e| VatError::DeserializationFailed(e.to_string()))?;
+
+        // The checksum covers the decompressed data
+        let mut buffer = VatFormat::decode(&VatCompression::decompress(bytes)?)?;
 
         if !buffer.verify() {
             return Err(VatError::InvalidVersion);
         }
 
+        let migrated = self.migrate(&mut buffer)?;
         self.vats.insert(vat_id.clone(), buffer.clone());
+        self.touch(vat_id);
+        if migrated {
+            self.persist_vat(vat_id)?;
+        }
+        self.evict_excess();
         Ok(buffer)
     }
 
-    /// List all registered Vat IDs
+    /// Bring `buffer` up to its registered migration's version
+    ///
+    /// Returns whether the buffer was rewritten.
+    fn migrate(&self, buffer: &mut VatBuffer) -> Result<bool, VatError> {
+        let Some(migration) = self.migrations.get(&buffer.header.vat_id) else {
+            return Ok(false);
+        };
+        let from_version = buffer.header.version;
+        let current = migration.current_version();
+        if from_version > current {
+            return Err(VatError::InvalidVersion);
+        }
+        if from_version == current {
+            return Ok(false);
+        }
+
+        buffer.reset_cursor();
+        migration.migrate(from_version, buffer)?;
+        buffer.header.version = current;
+        buffer.finalize();
+        log::info!(
+            "🔁 Migrated vat {} from v{} to v{}",
+            buffer.header.vat_id.as_str(),
+            from_version,
+            current
+        );
+        Ok(true)
+    }
+
+    /// List the Vat IDs resident in memory
     pub fn list_vats(&self) -> Vec<VatId> {
         self.vats.keys().cloned().collect()
     }
@@ -431,6 +900,85 @@ impl VatRegistry {
     /// Clear all Vats (for testing or shutdown)
     pub fn clear(&mut self) {
         self.vats.clear();
+        self.last_access.clear();
+    }
+
+    /// Garbage-collect orphaned Vats
+    ///
+    /// Removes every Vat, in memory and on disk, whose ID is not in
+    /// `live_vat_ids` and which is older than `max_age`. In-memory age is taken
+    /// from the header timestamp; on-disk age from the file modification time.
+    /// Returns the number of distinct Vats removed.
+    pub fn gc(&mut self, live_vat_ids: &HashSet<VatId>, max_age: Duration) -> usize {
+        use std::fs;
+
+        let now = SystemTime::now();
+        let now_secs = now
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs_f64();
+        let mut removed: HashSet<VatId> = HashSet::new();
+
+        // Sweep in-memory Vats
+        self.vats.retain(|vat_id, buffer| {
+            let age = (now_secs - buffer.header.timestamp).max(0.0);
+            let keep = live_vat_ids.contains(vat_id) || age <= max_age.as_secs_f64();
+            if !keep {
+                removed.insert(vat_id.clone());
+            }
+            keep
+        });
+        self.last_access
+            .retain(|vat_id, _| !removed.contains(vat_id));
+
+        // Sweep on-disk Vats (including ones never loaded this session)
+        if let Ok(entries) = fs::read_dir(&self.storage_path) {
+            for entry in entries.flatten() {
+                let path = entry.path();
+                let is_vat_file = matches!(
+                    path.extension().and_then(|ext| ext.to_str()),
+                    Some("vat") | Some("vatb")
+                );
+                if !is_vat_file {
+                    continue;
+                }
+                let Some(stem) = path.file_stem().and_then(|stem| stem.to_str()) else {
+                    continue;
+                };
+
+                let vat_id = VatId::new(stem);
+                if live_vat_ids.contains(&vat_id) {
+                    continue;
+                }
+
+                let expired = removed.contains(&vat_id)
+                    || entry
+                        .metadata()
+                        .and_then(|m| m.modified())
+                        .ok()
+                        .and_then(|mtime| now.duration_since(mtime).ok())
+                        .map(|age| age > max_age)
+                        .unwrap_or(false);
+
+                if expired {
+                    match fs::remove_file(&path) {
+                        Ok(()) => {
+                            // A Vat may exist in both formats; count it once
+                            removed.insert(vat_id);
+                        },
+                        Err(e) => {
+                            log::warn!("Failed to remove orphaned vat {}: {}", path.display(), e);
+                        },
+                    }
+                }
+            }
+        }
+
+        if !removed.is_empty() {
+            log::info!("🧹 Vat GC removed {} orphaned vat(s)", removed.len());
+        }
+
+        removed.len()
     }
 }
 
@@ -467,13 +1015,13 @@ impl VatState for CounterState {
 
     fn serialize_to_vat(&self, vat: &mut VatBuffer) -> Result<(), VatError> {
         vat.write_u32(self.count)?;
-        vat.write_f32(self.last_increment as f32)?;
+        vat.write_f64(self.last_increment)?;
         Ok(())
     }
 
     fn deserialize_from_vat(&mut self, vat: &mut VatBuffer) -> Result<(), VatError> {
         self.count = vat.read_u32()?;
-        self.last_increment = vat.read_f32()? as f64;
+        self.last_increment = vat.read_f64()?;
         Ok(())
     }
 }
@@ -484,6 +1032,8 @@ pub struct RiscVExecutorState {
     pub id: VatId,
     pub instruction_ptr: u32,
     pub registers: [u32; 32],
+    /// Full-width registers of an rv64 hart (`registers` then holds the low halves)
+    pub registers64: Option<[u64; 32]>,
     pub memory_size: usize,
     pub halted: bool,
 }
@@ -494,6 +1044,7 @@ impl RiscVExecutorState {
             id: VatId::from_path(kernel_path),
             instruction_ptr: 0,
             registers: [0; 32],
+            registers64: None,
             memory_size: 0,
             halted: false,
         }
@@ -515,6 +1066,11 @@ impl VatState for RiscVExecutorState {
         self.id.clone()
     }
 
+    /// v2 appends the optional rv64 register file
+    fn vat_version(&self) -> u32 {
+        2
+    }
+
     fn serialize_to_vat(&self, vat: &mut VatBuffer) -> Result<(), VatError> {
         vat.write_u32(self.instruction_ptr)?;
         vat.write_u32(self.memory_size as u32)?;
@@ -525,6 +1081,13 @@ impl VatState for RiscVExecutorState {
             vat.write_u32(*reg)?;
         }
 
+        vat.write_bool(self.registers64.is_some())?;
+        if let Some(registers64) = &self.registers64 {
+            for reg in registers64 {
+                vat.write_u64(*reg)?;
+            }
+        }
+
         Ok(())
     }
 
@@ -538,10 +1101,33 @@ impl VatState for RiscVExecutorState {
             *reg = vat.read_u32()?;
         }
 
+        self.registers64 = None;
+        if vat.read_bool()? {
+            let mut registers64 = [0u64; 32];
+            for reg in &mut registers64 {
+                *reg = vat.read_u64()?;
+            }
+            self.registers64 = Some(registers64);
+        }
+
         Ok(())
     }
 }
 
+/// Upgrades v1 `RiscVExecutorState` Vats (32-bit registers only)
+pub struct RiscVExecutorStateMigration;
+
+impl VatMigration for RiscVExecutorStateMigration {
+    fn current_version(&self) -> u32 {
+        2
+    }
+
+    fn migrate(&self, _from_version: u32, buffer: &mut VatBuffer) -> Result<(), VatError> {
+        // v1 is a prefix of v2; mark the rv64 register file absent
+        buffer.write_bool(false)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -557,6 +1143,97 @@ mod tests {
         assert_eq!(buffer.read_u32().unwrap(), 1337);
     }
 
+    #[test]
+    fn test_vat_checksum_is_stable_crc32() {
+        let mut header = VatHeader::new(VatId::new("crc_test"), 16);
+        header.timestamp = 1_700_000_000.5;
+        let data: Vec<u8> = (0..16).collect();
+
+        header.calculate_checksum(&data);
+        assert_eq!(header.checksum, 0x3078_bfb6);
+        assert!(header.verify(&data));
+
+        let mut corrupted = data.clone();
+        corrupted[7] ^= 1;
+        assert!(!header.verify(&corrupted));
+    }
+
+    #[test]
+    fn test_vat_buffer_capacity_limit() {
+        let mut buffer = VatBuffer::new(VatId::new("capped")).with_capacity_limit(10);
+        buffer.write_u32(1).unwrap();
+        buffer.write_u32(2).unwrap();
+        buffer.write_u8(3).unwrap();
+        buffer.write_u8(4).unwrap();
+        assert_eq!(buffer.data.len(), 10);
+
+        assert!(matches!(buffer.write_u8(5), Err(VatError::BufferOverflow)));
+        assert!(matches!(buffer.write_u64(6), Err(VatError::BufferOverflow)));
+        assert!(matches!(
+            buffer.write_string("overflow"),
+            Err(VatError::BufferOverflow)
+        ));
+        assert_eq!(buffer.data.len(), 10);
+    }
+
+    #[test]
+    fn test_vat_delta_round_trip() {
+        let base: Vec<u8> = (0..64).collect();
+        let a = VatBuffer::from_data(VatId::new("delta"), base.clone());
+
+        let mut changed = base.clone();
+        changed[3] = 0xff;
+        changed[5] = 0xfe;
+        changed[40] = 0xfd;
+        let grown = [changed.clone(), vec![7; 10]].concat();
+        let cases = [
+            base.clone(),
+            changed.clone(),
+            grown,
+            changed[..20].to_vec(),
+            Vec::new(),
+        ];
+
+        for data in cases {
+            let b = VatBuffer::from_data(VatId::new("delta"), data);
+            let delta = a.diff(&b);
+            assert!(delta.payload_len() <= b.data.len());
+
+            // Deltas survive the wire format
+            let config = bincode::config::standard();
+            let wire = bincode::serde::encode_to_vec(&delta, config).unwrap();
+            let (delta, _): (VatDelta, _) =
+                bincode::serde::decode_from_slice(&wire, config).unwrap();
+
+            let mut patched = a.clone();
+            patched.apply_delta(&delta).unwrap();
+            assert_eq!(patched.data, b.data);
+            assert_eq!(patched.header.checksum, b.header.checksum);
+            assert!(patched.verify());
+        }
+
+        // Nearby changes share a run, distant ones don't
+        let b = VatBuffer::from_data(VatId::new("delta"), changed);
+        let delta = a.diff(&b);
+        assert_eq!(delta.runs.len(), 2);
+        assert_eq!(delta.runs[0], (3, vec![0xff, 4, 0xfe]));
+
+        // A delta only applies to its own base
+        let mut other = VatBuffer::from_data(VatId::new("delta"), vec![1, 2, 3]);
+        assert!(other.apply_delta(&delta).is_err());
+        assert_eq!(other.data, vec![1, 2, 3]);
+
+        // A size the runs cannot fill is rejected before resizing
+        let mut bogus = delta.clone();
+        bogus.header.data_size = u32::MAX;
+        let mut patched = a.clone();
+        assert!(matches!(
+            patched.apply_delta(&bogus),
+            Err(VatError::DeserializationFailed(_))
+        ));
+        assert_eq!(patched.data, base);
+    }
+
     #[test]
     fn test_counter_state_serialization() {
         let mut counter = CounterState::new("test_counter");
@@ -564,14 +1241,52 @@ mod tests {
         counter.last_increment = 123456.789; // Fixed timestamp
 
         let buffer = counter.to_vat_buffer().unwrap();
-        assert_eq!(buffer.header.data_size, 8); // u32 + f32
+        assert_eq!(buffer.header.data_size, 12); // u32 + f64
 
         let mut restored = CounterState::new("test_counter");
         restored.from_vat_buffer(&mut buffer.clone()).unwrap();
 
         assert_eq!(restored.count, 100);
-        // f32 has ~7 decimal digits of precision
-        assert!((restored.last_increment - 123456.789).abs() < 0.1);
+        assert_eq!(restored.last_increment, 123456.789);
+    }
+
+    #[test]
+    fn test_vat_buffer_typed_helpers() {
+        let mut buffer = VatBuffer::new(VatId::new("typed"));
+        buffer.write_f64(1_700_000_000.123_456_7).unwrap();
+        buffer.write_i32(-42).unwrap();
+        buffer.write_i64(i64::MIN).unwrap();
+        buffer.write_bool(true).unwrap();
+        buffer.write_bool(false).unwrap();
+        assert_eq!(buffer.data.len(), 8 + 4 + 8 + 2);
+        assert_eq!(&buffer.data[8..12], &(-42i32).to_le_bytes());
+        buffer.finalize();
+
+        assert_eq!(buffer.read_f64().unwrap(), 1_700_000_000.123_456_7);
+        assert_eq!(buffer.read_i32().unwrap(), -42);
+        assert_eq!(buffer.read_i64().unwrap(), i64::MIN);
+        assert!(buffer.read_bool().unwrap());
+        assert!(!buffer.read_bool().unwrap());
+        assert!(matches!(buffer.read_f64(), Err(VatError::BufferUnderflow)));
+        assert!(matches!(buffer.read_i32(), Err(VatError::BufferUnderflow)));
+        assert!(matches!(buffer.read_bool(), Err(VatError::BufferUnderflow)));
+
+        let mut bad = VatBuffer::from_data(VatId::new("bad_bool"), vec![2]);
+        assert!(matches!(
+            bad.read_bool(),
+            Err(VatError::DeserializationFailed(_))
+        ));
+
+        let mut capped = VatBuffer::new(VatId::new("capped")).with_capacity_limit(4);
+        assert!(matches!(
+            capped.write_f64(0.0),
+            Err(VatError::BufferOverflow)
+        ));
+        capped.write_i32(1).unwrap();
+        assert!(matches!(
+            capped.write_bool(true),
+            Err(VatError::BufferOverflow)
+        ));
     }
 
     #[test]
@@ -594,6 +1309,41 @@ mod tests {
         assert_eq!(restored.halted, false);
     }
 
+    #[test]
+    fn test_riscv_state_rv64_registers_round_trip() {
+        let mut state = RiscVExecutorState::new("rv64_kernel.bin");
+        let mut registers64 = [0u64; 32];
+        registers64[5] = 1 << 40;
+        registers64[6] = u64::MAX;
+        state.registers64 = Some(registers64);
+
+        let mut buffer = state.to_vat_buffer().unwrap();
+        let mut restored = RiscVExecutorState::new("rv64_kernel.bin");
+        restored.from_vat_buffer(&mut buffer).unwrap();
+        assert_eq!(restored.registers64, Some(registers64));
+
+        // A v1 Vat from before rv64 support migrates to an rv32 state
+        let dir = tempfile::tempdir().unwrap();
+        let mut legacy = VatBuffer::new(state.vat_id());
+        for value in [0x1000, 0, 0] {
+            legacy.write_u32(value).unwrap();
+        }
+        for _ in 0..32 {
+            legacy.write_u32(7).unwrap();
+        }
+        legacy.finalize();
+        let mut registry = VatRegistry::new(dir.path().to_path_buf());
+        registry.register_vat(legacy).unwrap();
+
+        let mut registry = VatRegistry::new(dir.path().to_path_buf());
+        registry.register_migration(state.vat_id(), Box::new(RiscVExecutorStateMigration));
+        let mut migrated = registry.load_vat(&state.vat_id()).unwrap();
+        restored.from_vat_buffer(&mut migrated).unwrap();
+        assert_eq!(restored.registers64, None);
+        assert_eq!(restored.instruction_ptr, 0x1000);
+        assert_eq!(restored.registers[31], 7);
+    }
+
     #[test]
     fn test_vat_registry() {
         let mut registry = VatRegistry::new(PathBuf::from("/tmp/test_vats"));
@@ -607,4 +1357,259 @@ mod tests {
         let vats = registry.list_vats();
         assert!(vats.contains(&counter.id));
     }
+
+    #[test]
+    fn test_vat_registry_evicts_least_recently_used() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut registry = VatRegistry::new(dir.path().to_path_buf()).with_max_resident(3);
+
+        let counters: Vec<CounterState> = (0..4)
+            .map(|i| {
+                let mut counter = CounterState::new(&format!("lru_{}", i));
+                counter.count = i;
+                counter
+            })
+            .collect();
+        for counter in &counters[..3] {
+            registry
+                .register_vat(counter.to_vat_buffer().unwrap())
+                .unwrap();
+        }
+
+        // Touch the oldest so the second one becomes least recently used
+        assert!(registry.get_vat(&counters[0].id).is_some());
+        registry
+            .register_vat(counters[3].to_vat_buffer().unwrap())
+            .unwrap();
+        assert_eq!(registry.resident_count(), 3);
+        assert!(!registry.list_vats().contains(&counters[1].id));
+
+        // Evicted vats reload from disk on access
+        for counter in &counters {
+            let mut buffer = registry.get_vat(&counter.id).unwrap().clone();
+            let mut restored = CounterState::new(counter.id.as_str());
+            restored.from_vat_buffer(&mut buffer).unwrap();
+            assert_eq!(restored.count, counter.count);
+            assert!(registry.resident_count() <= 3);
+        }
+        assert!(registry.get_vat(&VatId::new("lru_missing")).is_none());
+    }
+
+    #[test]
+    fn test_vat_json_round_trip_keeps_checksum() {
+        // The shortest decimal form of this timestamp is misparsed by
+        // serde_json's default float parser, which breaks the checksum
+        let dir = tempfile::tempdir().unwrap();
+        let mut registry = VatRegistry::new(dir.path().to_path_buf());
+        assert_eq!(registry.format(), VatFormat::Json);
+
+        let mut buffer = VatBuffer::from_data(VatId::new("json_timestamp"), vec![1, 2, 3]);
+        buffer.header.timestamp = 1_760_000_000.007_552_1;
+        buffer.finalize();
+        registry.register_vat(buffer.clone()).unwrap();
+        registry.clear();
+
+        let loaded = registry.load_vat(&buffer.header.vat_id).unwrap();
+        assert_eq!(
+            loaded.header.timestamp.to_bits(),
+            buffer.header.timestamp.to_bits()
+        );
+        assert!(loaded.verify());
+    }
+
+    #[test]
+    fn test_vat_registry_binary_format() {
+        let json_dir = tempfile::tempdir().unwrap();
+        let bin_dir = tempfile::tempdir().unwrap();
+        let mut json_registry = VatRegistry::new(json_dir.path().to_path_buf());
+        let mut bin_registry =
+            VatRegistry::new_with_format(bin_dir.path().to_path_buf(), VatFormat::Binary);
+        assert_eq!(json_registry.format(), VatFormat::Json);
+
+        let vat_id = VatId::new("large_snapshot");
+        let data: Vec<u8> = (0..64 * 1024).map(|i| (i * 31 % 251) as u8).collect();
+        let buffer = VatBuffer::from_data(vat_id.clone(), data);
+
+        json_registry.register_vat(buffer.clone()).unwrap();
+        bin_registry.register_vat(buffer.clone()).unwrap();
+
+        let json_path = json_dir.path().join("large_snapshot.vat");
+        let bin_path = bin_dir.path().join("large_snapshot.vatb");
+        let json_size = std::fs::metadata(&json_path).unwrap().len();
+        let bin_size = std::fs::metadata(&bin_path).unwrap().len();
+        assert!(
+            bin_size < json_size,
+            "binary {} >= json {}",
+            bin_size,
+            json_size
+        );
+
+        // A JSON-configured registry still auto-detects the binary file
+        let mut reader = VatRegistry::new(bin_dir.path().to_path_buf());
+        let loaded = reader.load_vat(&vat_id).unwrap();
+        assert!(loaded.verify());
+        assert_eq!(loaded.data, buffer.data);
+        assert_eq!(loaded.header.checksum, buffer.header.checksum);
+
+        let loaded_json = json_registry.load_vat(&vat_id).unwrap();
+        assert_eq!(loaded_json.data, buffer.data);
+    }
+
+    /// CounterState layout v2: adds a `step` field
+    struct CounterV2 {
+        count: u32,
+        last_increment: f64,
+        step: u32,
+    }
+
+    impl VatState for CounterV2 {
+        fn vat_id(&self) -> VatId {
+            VatId::new("migrating_counter")
+        }
+
+        fn vat_version(&self) -> u32 {
+            2
+        }
+
+        fn serialize_to_vat(&self, vat: &mut VatBuffer) -> Result<(), VatError> {
+            vat.write_u32(self.count)?;
+            vat.write_f64(self.last_increment)?;
+            vat.write_u32(self.step)
+        }
+
+        fn deserialize_from_vat(&mut self, vat: &mut VatBuffer) -> Result<(), VatError> {
+            self.count = vat.read_u32()?;
+            self.last_increment = vat.read_f64()?;
+            self.step = vat.read_u32()?;
+            Ok(())
+        }
+    }
+
+    struct CounterMigration;
+
+    impl VatMigration for CounterMigration {
+        fn current_version(&self) -> u32 {
+            2
+        }
+
+        fn migrate(&self, from_version: u32, buffer: &mut VatBuffer) -> Result<(), VatError> {
+            assert_eq!(from_version, 1);
+            // v1 data is a prefix of v2; append the default step
+            buffer.write_u32(1)
+        }
+    }
+
+    #[test]
+    fn test_vat_registry_migrates_older_versions() {
+        let dir = tempfile::tempdir().unwrap();
+        let vat_id = VatId::new("migrating_counter");
+
+        let mut v1 = CounterState::new("migrating_counter");
+        v1.count = 7;
+        v1.last_increment = 42.5;
+        let mut registry = VatRegistry::new(dir.path().to_path_buf());
+        registry.register_vat(v1.to_vat_buffer().unwrap()).unwrap();
+
+        // Without a migration the old layout is refused by the new state
+        let mut v2 = CounterV2 {
+            count: 0,
+            last_increment: 0.0,
+            step: 0,
+        };
+        let mut raw = registry.load_vat(&vat_id).unwrap();
+        assert_eq!(raw.header.version, 1);
+        assert!(matches!(
+            v2.from_vat_buffer(&mut raw),
+            Err(VatError::InvalidVersion)
+        ));
+
+        let mut registry = VatRegistry::new(dir.path().to_path_buf());
+        registry.register_migration(vat_id.clone(), Box::new(CounterMigration));
+        let mut migrated = registry.load_vat(&vat_id).unwrap();
+        assert_eq!(migrated.header.version, 2);
+        assert!(migrated.verify());
+
+        v2.from_vat_buffer(&mut migrated).unwrap();
+        assert_eq!((v2.count, v2.last_increment, v2.step), (7, 42.5, 1));
+
+        // The migrated layout was persisted, so reloading needs no migration
+        let mut reader = VatRegistry::new(dir.path().to_path_buf());
+        assert_eq!(reader.load_vat(&vat_id).unwrap().header.version, 2);
+
+        // Vats newer than the migration are rejected
+        let mut v3 = v2.to_vat_buffer().unwrap();
+        v3.header.version = 3;
+        v3.finalize();
+        registry.register_vat(v3).unwrap();
+        assert!(matches!(
+            registry.load_vat(&vat_id),
+            Err(VatError::InvalidVersion)
+        ));
+    }
+
+    #[test]
+    fn test_vat_registry_compression_round_trip() {
+        let plain_dir = tempfile::tempdir().unwrap();
+        let zlib_dir = tempfile::tempdir().unwrap();
+        let mut plain = VatRegistry::new(plain_dir.path().to_path_buf());
+        let mut zlib =
+            VatRegistry::new(zlib_dir.path().to_path_buf()).with_compression(VatCompression::Zlib);
+        assert_eq!(zlib.compression(), VatCompression::Zlib);
+
+        let vat_id = VatId::new("zero_dump");
+        let buffer = VatBuffer::from_data(vat_id.clone(), vec![0; 64 * 1024]);
+        plain.register_vat(buffer.clone()).unwrap();
+        zlib.register_vat(buffer.clone()).unwrap();
+
+        let plain_size = std::fs::metadata(plain_dir.path().join("zero_dump.vat"))
+            .unwrap()
+            .len();
+        let zlib_size = std::fs::metadata(zlib_dir.path().join("zero_dump.vat"))
+            .unwrap()
+            .len();
+        assert!(
+            zlib_size * 100 < plain_size,
+            "zlib {} vs plain {}",
+            zlib_size,
+            plain_size
+        );
+
+        // Any registry detects the compression on load
+        for mut reader in [zlib, VatRegistry::new(zlib_dir.path().to_path_buf())] {
+            let loaded = reader.load_vat(&vat_id).unwrap();
+            assert!(loaded.verify());
+            assert_eq!(loaded.data, buffer.data);
+            assert_eq!(loaded.header.checksum, buffer.header.checksum);
+        }
+        assert_eq!(plain.load_vat(&vat_id).unwrap().data, buffer.data);
+    }
+
+    #[test]
+    fn test_vat_registry_gc_removes_orphans() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut registry = VatRegistry::new(dir.path().to_path_buf());
+
+        for name in ["gc_live", "gc_dead_a", "gc_dead_b"] {
+            let buffer = CounterState::new(name).to_vat_buffer().unwrap();
+            registry.register_vat(buffer).unwrap();
+        }
+
+        let live: HashSet<VatId> = [VatId::new("gc_live")].into_iter().collect();
+
+        // Nothing is older than an hour yet, so nothing is collected
+        assert_eq!(registry.gc(&live, Duration::from_secs(3600)), 0);
+        assert_eq!(registry.list_vats().len(), 3);
+
+        std::thread::sleep(Duration::from_millis(20));
+        let removed = registry.gc(&live, Duration::from_millis(1));
+        assert_eq!(removed, 2);
+
+        assert!(registry.get_vat(&VatId::new("gc_live")).is_some());
+        assert!(registry.get_vat(&VatId::new("gc_dead_a")).is_none());
+        assert!(registry.get_vat(&VatId::new("gc_dead_b")).is_none());
+
+        assert!(dir.path().join("gc_live.vat").exists());
+        assert!(!dir.path().join("gc_dead_a.vat").exists());
+        assert!(!dir.path().join("gc_dead_b.vat").exists());
+    }
 }
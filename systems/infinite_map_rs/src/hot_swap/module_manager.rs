@@ -293,7 +293,7 @@ impl ModuleManager {
             .vat_registry
             .lock()
             .ok()
-            .and_then(|reg| reg.get_vat(&vat_id).cloned());
+            .and_then(|mut reg| reg.get_vat(&vat_id).cloned());
 
         // Initialize the module
         module.init(state.as_ref())?;
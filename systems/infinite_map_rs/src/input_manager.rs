@@ -75,6 +75,9 @@ pub struct InputManager {
 
     /// Phase 44: Multi-VM commands (Ctrl+Shift+M)
     multi_vm_commands: Option<Arc<std::sync::Mutex<Vec<u8>>>>,
+
+    /// RISC-V debugger commands (F9 / F10)
+    riscv_debug_commands: Option<Arc<std::sync::Mutex<Vec<u8>>>>,
 }
 
 impl InputManager {
@@ -99,6 +102,7 @@ impl InputManager {
             compile_commands: Some(Arc::new(std::sync::Mutex::new(Vec::new()))),
             profiler_commands: Some(Arc::new(std::sync::Mutex::new(Vec::new()))),
             multi_vm_commands: Some(Arc::new(std::sync::Mutex::new(Vec::new()))),
+            riscv_debug_commands: Some(Arc::new(std::sync::Mutex::new(Vec::new()))),
         }
     }
 
@@ -238,6 +242,13 @@ impl InputManager {
                     buffer.push(cmd);
                     log::info!("🚀 Phase 44: Multi-VM command: 0x{:02x}", cmd);
                 }
+            // RISC-V debugger shortcuts (F9 / F10)
+            } else if let Some(cmd) = self.check_riscv_debug_shortcuts(key.raw()) {
+                if let Some(riscv_debug_commands) = &self.riscv_debug_commands {
+                    let mut buffer = riscv_debug_commands.lock().unwrap();
+                    buffer.push(cmd);
+                    log::info!("🐞 RISC-V debug command: 0x{:02x}", cmd);
+                }
             } else if let Some(byte) = self.map_scancode_to_ascii(key.raw()) {
                 if let Some(crystallized_input) = &self.crystallized_input {
                     let mut buffer = crystallized_input.lock().unwrap();
@@ -667,6 +678,15 @@ impl InputManager {
         }
     }
 
+    /// Check if Alt key is pressed
+    pub fn is_alt_pressed(&self) -> bool {
+        if let Some(keyboard) = self.seat.get_keyboard() {
+            keyboard.modifier_state().alt
+        } else {
+            false
+        }
+    }
+
     /// Phase 31: Handle clipboard copy operation (Ctrl+C)
     pub fn handle_clipboard_copy(&mut self) {
         if let Some(_clipboard_manager) = &self.clipboard_manager {
@@ -899,4 +919,30 @@ impl InputManager {
             None
         }
     }
+
+    /// Check for RISC-V debugger shortcuts (F9 / F10)
+    pub fn check_riscv_debug_shortcuts(&self, key_code: u32) -> Option<u8> {
+        // F9 (Key code 67) = Toggle single-step mode
+        if key_code == 67 {
+            return Some(157);
+        }
+        // F10 (Key code 68) = Step one instruction
+        if key_code == 68 {
+            return Some(158);
+        }
+        None
+    }
+
+    /// Get pending RISC-V debugger commands
+    pub fn get_riscv_debug_commands(&mut self) -> Option<Vec<u8>> {
+        if let Some(riscv_debug_commands) = &self.riscv_debug_commands {
+            let mut buffer = riscv_debug_commands.lock().unwrap();
+            if buffer.is_empty() {
+                return None;
+            }
+            Some(buffer.drain(..).collect())
+        } else {
+            None
+        }
+    }
 }
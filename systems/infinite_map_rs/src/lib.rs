@@ -161,7 +161,7 @@ pub mod mailbox_bridge;
 pub use agency_tile::{AgencyDivision, AgencyTile, Color, PersonaInjector, PersonaShiftEvent};
 
 // Phase 30.8: Damage tracking for partial terminal updates
-pub use damage_tracker::{DamageTracker, DirtyRect};
+pub use damage_tracker::{DamageTracker, DirtyRect, UploadLayout};
 
 // Test utilities for benchmarks and compliance tests
 #[cfg(test)]
@@ -178,7 +178,7 @@ pub use cartridge_texture_manager::{
 // Phase 28: Tectonic Realignment System
 pub use tectonic::{
     BondType, CognitiveBond, CognitiveBondGraph, ForceDirectedSolver, HilbertConstraint,
-    LayoutDelta, PulseEvent, TectonicAsciiRenderer, TectonicConfig, TectonicSimulator,
+    LayoutDelta, PulseEvent, SolverMode, TectonicAsciiRenderer, TectonicConfig, TectonicSimulator,
 };
 
 // Re-export main types for convenience
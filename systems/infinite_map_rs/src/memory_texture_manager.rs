@@ -13,6 +13,8 @@ use std::sync::Arc;
 use std::time::{Duration, Instant};
 use wgpu::{self, BindGroup, Device, Queue, Sampler, Texture, TextureView};
 
+use crate::gpu_capabilities::GpuCapabilities;
+use crate::hilbert::{HilbertCurve, HilbertTextureUpload};
 use crate::memory_tensor::{V2Brick, V2BrickHeader};
 use crate::memory_texture::MemoryRegion;
 
@@ -39,8 +41,8 @@ pub struct MemoryTexture {
     live_update_interval: Duration,
     /// Last live update timestamp
     last_live_update: Instant,
-    /// Optional Hilbert LUT for memory visualization (Linear offset -> Texture index)
-    pub hilbert_lut: Option<Vec<usize>>,
+    /// Optional Hilbert layout for memory visualization (linear offset -> curve position)
+    pub hilbert: Option<HilbertTextureUpload>,
 }
 
 /// Memory texture manager for V2 brick rendering
@@ -177,7 +179,7 @@ impl MemoryTextureManager {
             memory_region: None,
             live_update_interval: Duration::from_millis(100),
             last_live_update: Instant::now(),
-            hilbert_lut: None,
+            hilbert: None,
         };
 
         self.textures.insert(path_str, memory_texture);
@@ -418,7 +420,7 @@ impl MemoryTextureManager {
             memory_region: Some(region.clone()),
             live_update_interval: update_interval,
             last_live_update: Instant::now(),
-            hilbert_lut: None,
+            hilbert: None,
         };
 
         self.textures.insert(name.clone(), memory_texture);
@@ -457,11 +459,15 @@ impl MemoryTextureManager {
     }
 
     /// Create a texture for QEMU VM visualization
+    ///
+    /// Square power-of-two textures are Hilbert mapped, on the GPU unless
+    /// `caps` says the adapter cannot bind the LUT.
     pub fn create_qemu_texture(
         &mut self,
         name: &str,
         width: u32,
         height: u32,
+        caps: &GpuCapabilities,
     ) -> Result<(), String> {
         if self.textures.contains_key(name) {
             return Err(format!("Texture '{}' already exists", name));
@@ -499,23 +505,18 @@ impl MemoryTextureManager {
             ],
         });
 
-        // Generate Hilbert LUT for visual coherence
-        log::info!("Generating Hilbert LUT for {}x{} texture...", width, height);
-        let mut hilbert_lut = Vec::with_capacity((width * height) as usize);
-
-        // Use fast_hilbert to generate mapping
-        // We map linear address 'i' to (x, y), then back to texture linear index (y * width + x)
-        // This effectively "scatters" the linear RAM into a Hilbert 2D arrangement
-        for i in 0..(width * height) {
-            let point: (u32, u32) = fast_hilbert::h2xy(i.into());
-            let x = point.0 as u32;
-            let y = point.1 as u32;
-
-            if x < width && y < height {
-                hilbert_lut.push((y * width + x) as usize);
-            } else {
-                hilbert_lut.push(0); // Safetly fallback
-            }
+        // Hilbert mapping for visual coherence
+        let hilbert = HilbertCurve::try_new(width)
+            .ok()
+            .filter(|_| width == height)
+            .map(|curve| HilbertTextureUpload::new(&self.device, curve, caps));
+        match &hilbert {
+            Some(upload) => log::info!("Hilbert mapping {} on {:?}", name, upload.path()),
+            None => log::warn!(
+                "{}x{} is not a Hilbert grid, mapping linearly",
+                width,
+                height
+            ),
         }
 
         let memory_texture = MemoryTexture {
@@ -529,7 +530,7 @@ impl MemoryTextureManager {
             memory_region: None,
             live_update_interval: Duration::from_millis(16),
             last_live_update: Instant::now(),
-            hilbert_lut: Some(hilbert_lut),
+            hilbert,
         };
 
         self.textures.insert(name.to_string(), memory_texture);
@@ -558,53 +559,35 @@ impl MemoryTextureManager {
             let height = size.height as usize;
             let total_pixels = width * height;
 
-            let mut data = vec![0u8; total_pixels * 4];
+            // Grayscale texel per byte
             let len = mem_slice.len().min(total_pixels);
+            let mut data: Vec<u8> = mem_slice[..len]
+                .iter()
+                .flat_map(|&val| [val, val, val, 255])
+                .collect();
 
-            // Apply Hilbert Mapping if LUT is present, otherwise Linear
-            if let Some(ref lut) = texture.hilbert_lut {
-                for i in 0..len {
-                    let val = mem_slice[i];
-                    // lut[i] gives us the texture index where this byte belongs spatially
-                    if i < lut.len() {
-                        let tex_idx = lut[i];
-                        let pixel_offset = tex_idx * 4;
-                        // Grayscale mapping
-                        data[pixel_offset] = val; // R
-                        data[pixel_offset + 1] = val; // G
-                        data[pixel_offset + 2] = val; // B
-                        data[pixel_offset + 3] = 255; // A
-                    }
-                }
+            if let Some(ref hilbert) = texture.hilbert {
+                hilbert.write(&self.device, &self.queue, &texture.texture, &data);
             } else {
                 // Fallback Linear Mapping
-                for i in 0..len {
-                    let val = mem_slice[i];
-                    let idx = i * 4;
-                    data[idx] = val;
-                    data[idx + 1] = val;
-                    data[idx + 2] = val;
-                    data[idx + 3] = 255;
-                }
+                data.resize(total_pixels * 4, 0);
+                self.queue.write_texture(
+                    wgpu::ImageCopyTexture {
+                        texture: &texture.texture,
+                        mip_level: 0,
+                        origin: wgpu::Origin3d::ZERO,
+                        aspect: wgpu::TextureAspect::All,
+                    },
+                    &data,
+                    wgpu::ImageDataLayout {
+                        offset: 0,
+                        bytes_per_row: Some((width * 4) as u32),
+                        rows_per_image: None,
+                    },
+                    texture.texture.size(),
+                );
             }
 
-            // Write to texture
-            self.queue.write_texture(
-                wgpu::ImageCopyTexture {
-                    texture: &texture.texture,
-                    mip_level: 0,
-                    origin: wgpu::Origin3d::ZERO,
-                    aspect: wgpu::TextureAspect::All,
-                },
-                &data,
-                wgpu::ImageDataLayout {
-                    offset: 0,
-                    bytes_per_row: Some((width * 4) as u32),
-                    rows_per_image: None,
-                },
-                texture.texture.size(),
-            );
-
             texture.last_live_update = now;
         }
     }
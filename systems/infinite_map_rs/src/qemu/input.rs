@@ -0,0 +1,378 @@
+// Phase 47: Guest Input Encoding
+//
+// Turns structured key events into what a QEMU guest understands: xterm-style
+// byte sequences for a serial console, or QMP `input-send-event` key events
+// for a graphical guest. `QemuProcessWithShm::send_key` delivers the result.
+
+use serde_json::{json, Value};
+
+use crate::alpine_vm::AlpineVmManager;
+
+/// A key on the host keyboard, independent of layout
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyCode {
+    /// A printable character, already shifted (`'A'`, `'!'`)
+    Char(char),
+    Enter,
+    Tab,
+    Backspace,
+    Escape,
+    Up,
+    Down,
+    Left,
+    Right,
+    Home,
+    End,
+    PageUp,
+    PageDown,
+    Insert,
+    Delete,
+    /// Function key F1-F12
+    F(u8),
+}
+
+/// Modifier keys held during a key press
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Modifiers {
+    pub shift: bool,
+    pub ctrl: bool,
+    pub alt: bool,
+}
+
+impl Modifiers {
+    pub const NONE: Self = Self {
+        shift: false,
+        ctrl: false,
+        alt: false,
+    };
+
+    pub fn is_empty(&self) -> bool {
+        *self == Self::NONE
+    }
+
+    /// xterm modifier parameter (`1 + shift + 2*alt + 4*ctrl`)
+    fn xterm_param(&self) -> u8 {
+        1 + self.shift as u8 + 2 * self.alt as u8 + 4 * self.ctrl as u8
+    }
+}
+
+/// A key press with the modifiers held at the time
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeyEvent {
+    pub code: KeyCode,
+    pub modifiers: Modifiers,
+}
+
+impl KeyEvent {
+    pub fn new(code: KeyCode) -> Self {
+        Self {
+            code,
+            modifiers: Modifiers::NONE,
+        }
+    }
+
+    pub fn with_modifiers(code: KeyCode, modifiers: Modifiers) -> Self {
+        Self { code, modifiers }
+    }
+
+    /// Build an event from a Linux input scancode on a US layout
+    ///
+    /// Shift is folded into `KeyCode::Char` (`shift` + 30 gives `'A'`) and
+    /// kept in `modifiers` for the other keys.
+    pub fn from_scancode(scancode: u32, modifiers: Modifiers) -> Option<Self> {
+        let code = match scancode {
+            1 => KeyCode::Escape,
+            14 => KeyCode::Backspace,
+            15 => KeyCode::Tab,
+            28 => KeyCode::Enter,
+            59..=68 => KeyCode::F((scancode - 58) as u8),
+            87 => KeyCode::F(11),
+            88 => KeyCode::F(12),
+            102 => KeyCode::Home,
+            103 => KeyCode::Up,
+            104 => KeyCode::PageUp,
+            105 => KeyCode::Left,
+            106 => KeyCode::Right,
+            107 => KeyCode::End,
+            108 => KeyCode::Down,
+            109 => KeyCode::PageDown,
+            110 => KeyCode::Insert,
+            111 => KeyCode::Delete,
+            _ => {
+                let (plain, shifted) = scancode_to_chars(scancode)?;
+                let c = if modifiers.shift { shifted } else { plain };
+                let modifiers = Modifiers {
+                    shift: false,
+                    ..modifiers
+                };
+                return Some(Self::with_modifiers(KeyCode::Char(c), modifiers));
+            },
+        };
+        Some(Self::with_modifiers(code, modifiers))
+    }
+}
+
+/// Printable characters of a US-layout key, unshifted and shifted
+fn scancode_to_chars(scancode: u32) -> Option<(char, char)> {
+    const ROWS: [(u32, &str, &str); 4] = [
+        (2, "1234567890-=", "!@#$%^&*()_+"),
+        (16, "qwertyuiop[]", "QWERTYUIOP{}"),
+        (30, "asdfghjkl;'`", "ASDFGHJKL:\"~"),
+        (43, "\\zxcvbnm,./", "|ZXCVBNM<>?"),
+    ];
+    if scancode == 57 {
+        return Some((' ', ' '));
+    }
+    ROWS.iter().find_map(|&(first, plain, shifted)| {
+        let index = scancode.checked_sub(first)? as usize;
+        Some((plain.chars().nth(index)?, shifted.chars().nth(index)?))
+    })
+}
+
+/// Encoded form of a key event, ready to send to QEMU
+#[derive(Debug, Clone, PartialEq)]
+pub enum EncodedInput {
+    /// Bytes for the guest's serial console
+    Console(Vec<u8>),
+    /// `input-send-event` events for the guest's keyboard device
+    Qmp(Vec<Value>),
+}
+
+/// Strategy for turning key events into guest input
+pub trait InputEncoder: Send {
+    /// Encode `event`, or `None` if the guest has no way to receive it
+    fn encode(&self, event: &KeyEvent) -> Option<EncodedInput>;
+}
+
+/// Encodes keys as the escape sequences an xterm sends, for text consoles
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ConsoleEncoder;
+
+impl ConsoleEncoder {
+    fn encode_bytes(event: &KeyEvent) -> Option<Vec<u8>> {
+        let mods = event.modifiers;
+        // CSI sequences carry modifiers as `1;<param>` before the final byte
+        let csi = |number: u8, last: char| {
+            let mut sequence = format!("\x1b[{}", number);
+            if !mods.is_empty() {
+                sequence = format!("{};{}", sequence, mods.xterm_param());
+            }
+            sequence.push(last);
+            sequence.into_bytes()
+        };
+        let cursor = |last: char| {
+            if mods.is_empty() {
+                format!("\x1b[{}", last).into_bytes()
+            } else {
+                csi(1, last)
+            }
+        };
+
+        let mut bytes = match event.code {
+            KeyCode::Char(c) if mods.ctrl => {
+                let control = match c.to_ascii_lowercase() {
+                    c @ 'a'..='z' => c as u8 - b'a' + 1,
+                    '@' | ' ' | '2' => 0x00,
+                    '[' | '3' => 0x1b,
+                    '\\' | '4' => 0x1c,
+                    ']' | '5' => 0x1d,
+                    '^' | '6' => 0x1e,
+                    '_' | '-' | '7' => 0x1f,
+                    '?' | '8' => 0x7f,
+                    _ => return None,
+                };
+                vec![control]
+            },
+            KeyCode::Char(c) => c.to_string().into_bytes(),
+            KeyCode::Enter => vec![b'\r'],
+            KeyCode::Tab if mods.shift => b"\x1b[Z".to_vec(),
+            KeyCode::Tab => vec![b'\t'],
+            KeyCode::Backspace => vec![0x7f],
+            KeyCode::Escape => vec![0x1b],
+            KeyCode::Up => cursor('A'),
+            KeyCode::Down => cursor('B'),
+            KeyCode::Right => cursor('C'),
+            KeyCode::Left => cursor('D'),
+            KeyCode::Home => cursor('H'),
+            KeyCode::End => cursor('F'),
+            KeyCode::Insert => csi(2, '~'),
+            KeyCode::Delete => csi(3, '~'),
+            KeyCode::PageUp => csi(5, '~'),
+            KeyCode::PageDown => csi(6, '~'),
+            KeyCode::F(n @ 1..=4) => {
+                let last = (b'P' + n - 1) as char;
+                if mods.is_empty() {
+                    format!("\x1bO{}", last).into_bytes()
+                } else {
+                    csi(1, last)
+                }
+            },
+            KeyCode::F(n @ 5..=12) => {
+                const NUMBERS: [u8; 8] = [15, 17, 18, 19, 20, 21, 23, 24];
+                csi(NUMBERS[(n - 5) as usize], '~')
+            },
+            KeyCode::F(_) => return None,
+        };
+
+        // Alt sends ESC before plain keys; the sequences above encode it already
+        if mods.alt
+            && matches!(
+                event.code,
+                KeyCode::Char(_) | KeyCode::Enter | KeyCode::Backspace
+            )
+        {
+            bytes.insert(0, 0x1b);
+        }
+        Some(bytes)
+    }
+}
+
+impl InputEncoder for ConsoleEncoder {
+    fn encode(&self, event: &KeyEvent) -> Option<EncodedInput> {
+        Self::encode_bytes(event).map(EncodedInput::Console)
+    }
+}
+
+/// Encodes keys as QMP qcode press/release events, for graphical guests
+#[derive(Debug, Clone, Copy, Default)]
+pub struct QmpKeyEncoder;
+
+impl QmpKeyEncoder {
+    fn qcode(code: KeyCode) -> Option<(&'static str, bool)> {
+        let qcode = match code {
+            KeyCode::Char(c) => return AlpineVmManager::char_to_qcode(c),
+            KeyCode::Enter => "ret",
+            KeyCode::Tab => "tab",
+            KeyCode::Backspace => "backspace",
+            KeyCode::Escape => "esc",
+            KeyCode::Up => "up",
+            KeyCode::Down => "down",
+            KeyCode::Left => "left",
+            KeyCode::Right => "right",
+            KeyCode::Home => "home",
+            KeyCode::End => "end",
+            KeyCode::PageUp => "pgup",
+            KeyCode::PageDown => "pgdn",
+            KeyCode::Insert => "insert",
+            KeyCode::Delete => "delete",
+            KeyCode::F(n) => {
+                const FUNCTION_KEYS: [&str; 12] = [
+                    "f1", "f2", "f3", "f4", "f5", "f6", "f7", "f8", "f9", "f10", "f11", "f12",
+                ];
+                FUNCTION_KEYS.get((n as usize).checked_sub(1)?)?
+            },
+        };
+        Some((qcode, false))
+    }
+
+    fn key_event(qcode: &str, down: bool) -> Value {
+        json!({
+            "type": "key",
+            "data": { "key": { "type": "qcode", "data": qcode }, "down": down },
+        })
+    }
+}
+
+impl InputEncoder for QmpKeyEncoder {
+    fn encode(&self, event: &KeyEvent) -> Option<EncodedInput> {
+        let (qcode, needs_shift) = Self::qcode(event.code)?;
+        let mods = event.modifiers;
+        let held: Vec<&str> = [
+            (mods.ctrl, "ctrl"),
+            (mods.alt, "alt"),
+            (mods.shift || needs_shift, "shift"),
+        ]
+        .iter()
+        .filter(|&&(pressed, _)| pressed)
+        .map(|&(_, name)| name)
+        .collect();
+
+        // Modifiers down, key down, key up, modifiers up in reverse
+        let mut events: Vec<Value> = held.iter().map(|m| Self::key_event(m, true)).collect();
+        events.push(Self::key_event(qcode, true));
+        events.push(Self::key_event(qcode, false));
+        events.extend(held.iter().rev().map(|m| Self::key_event(m, false)));
+        Some(EncodedInput::Qmp(events))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_console_encoder_sequences() {
+        let encode = |event: KeyEvent| match ConsoleEncoder.encode(&event) {
+            Some(EncodedInput::Console(bytes)) => bytes,
+            other => panic!("unexpected encoding {:?}", other),
+        };
+
+        assert_eq!(encode(KeyEvent::new(KeyCode::Up)), b"\x1b[A");
+        assert_eq!(encode(KeyEvent::new(KeyCode::Char('q'))), b"q");
+        assert_eq!(encode(KeyEvent::new(KeyCode::Char('é'))), "é".as_bytes());
+
+        let ctrl = Modifiers {
+            ctrl: true,
+            ..Modifiers::NONE
+        };
+        assert_eq!(
+            encode(KeyEvent::with_modifiers(KeyCode::Char('c'), ctrl)),
+            [0x03]
+        );
+        assert_eq!(
+            encode(KeyEvent::with_modifiers(KeyCode::Up, ctrl)),
+            b"\x1b[1;5A"
+        );
+        assert_eq!(encode(KeyEvent::new(KeyCode::Delete)), b"\x1b[3~");
+        assert_eq!(encode(KeyEvent::new(KeyCode::F(1))), b"\x1bOP");
+        assert_eq!(encode(KeyEvent::new(KeyCode::F(12))), b"\x1b[24~");
+    }
+
+    #[test]
+    fn test_qmp_encoder_presses_and_releases() {
+        let Some(EncodedInput::Qmp(events)) =
+            QmpKeyEncoder.encode(&KeyEvent::new(KeyCode::Char('A')))
+        else {
+            panic!("expected QMP events");
+        };
+        let keys: Vec<(&str, bool)> = events
+            .iter()
+            .map(|e| {
+                let data = &e["data"];
+                (
+                    data["key"]["data"].as_str().unwrap(),
+                    data["down"].as_bool().unwrap(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            keys,
+            [("shift", true), ("a", true), ("a", false), ("shift", false)]
+        );
+    }
+
+    #[test]
+    fn test_key_event_from_scancode() {
+        let shift = Modifiers {
+            shift: true,
+            ..Modifiers::NONE
+        };
+        assert_eq!(
+            KeyEvent::from_scancode(103, Modifiers::NONE),
+            Some(KeyEvent::new(KeyCode::Up))
+        );
+        assert_eq!(
+            KeyEvent::from_scancode(30, shift),
+            Some(KeyEvent::new(KeyCode::Char('A')))
+        );
+        assert_eq!(
+            KeyEvent::from_scancode(38, Modifiers::NONE),
+            Some(KeyEvent::new(KeyCode::Char('l')))
+        );
+        assert_eq!(
+            KeyEvent::from_scancode(3, shift),
+            Some(KeyEvent::new(KeyCode::Char('@')))
+        );
+        assert_eq!(KeyEvent::from_scancode(0, Modifiers::NONE), None);
+    }
+}
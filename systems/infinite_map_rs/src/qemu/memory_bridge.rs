@@ -90,9 +90,12 @@ impl SharedMemoryBridge {
 
     /// Inverse Hilbert mapping: (x,y) -> Linear address
     /// Used for mouse interaction to find which memory byte is at a pixel
-    pub fn xy2h(&self, x: u32, y: u32, _width: u32) -> Option<usize> {
-        // Use fast_hilbert to convert (x,y) back to linear address
-        let addr = fast_hilbert::xy2h(x, y);
+    /// of a `width`x`width` texture drawn by `HilbertTextureUpload`
+    pub fn xy2h(&self, x: u32, y: u32, width: u32) -> Option<usize> {
+        if !crate::hilbert::validate_grid_size(width) || x >= width || y >= width {
+            return None;
+        }
+        let addr = crate::hilbert::xy2d(width, x, y);
         if addr < self.size as u64 {
             Some(addr as usize)
         } else {
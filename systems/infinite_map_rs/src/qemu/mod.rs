@@ -1,10 +1,14 @@
+pub mod input;
 pub mod memory_bridge;
 pub mod qmp;
 #[cfg(test)]
 mod qmp_tests;
 
+pub use input::{
+    ConsoleEncoder, EncodedInput, InputEncoder, KeyCode, KeyEvent, Modifiers, QmpKeyEncoder,
+};
 pub use memory_bridge::SharedMemoryBridge;
-pub use qmp::QmpClient;
+pub use qmp::{QmpClient, QmpEvent, QmpStatus};
 
 #[derive(Debug, Clone)]
 pub enum QmpCommand {
@@ -7,6 +7,7 @@ use std::time::Duration;
 use thiserror::Error;
 use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
 use tokio::net::UnixStream;
+use tokio::sync::{mpsc, watch};
 
 #[derive(Error, Debug)]
 pub enum QmpError {
@@ -18,62 +19,253 @@ pub enum QmpError {
     Protocol(String),
     #[error("Wait timeout")]
     Timeout,
+    #[error("QMP socket not connected")]
+    NotConnected,
+    #[error("QMP socket unavailable after {0} connection attempts")]
+    ReconnectFailed(u32),
+}
+
+impl QmpError {
+    /// Whether the error means the socket is gone (VM reset or QEMU restart)
+    fn is_connection_lost(&self) -> bool {
+        match self {
+            QmpError::Io(e) => matches!(
+                e.kind(),
+                std::io::ErrorKind::UnexpectedEof
+                    | std::io::ErrorKind::BrokenPipe
+                    | std::io::ErrorKind::ConnectionReset
+                    | std::io::ErrorKind::ConnectionAborted
+                    | std::io::ErrorKind::NotConnected
+            ),
+            QmpError::NotConnected => true,
+            _ => false,
+        }
+    }
+}
+
+/// Health of the QMP connection, i.e. whether VM control is available
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QmpStatus {
+    /// Handshake complete, commands are accepted
+    Connected,
+    /// Connection lost, retrying the socket
+    Reconnecting,
+    /// Not connected and not currently retrying
+    Disconnected,
+}
+
+/// How hard to retry the socket before giving up
+#[derive(Debug, Clone, Copy)]
+pub struct ReconnectPolicy {
+    pub max_attempts: u32,
+    /// Delay after the first failed attempt, doubled after each further one
+    pub initial_delay: Duration,
+    pub max_delay: Duration,
+}
+
+impl Default for ReconnectPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 6,
+            initial_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(4),
+        }
+    }
+}
+
+/// Capacity of the `QmpClient::events` channel
+const EVENT_CHANNEL_CAPACITY: usize = 32;
+
+/// Asynchronous QMP event the VM emitted on its own
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum QmpEvent {
+    /// VM shut down; `guest` is false when the host requested it
+    Shutdown { guest: bool, reason: Option<String> },
+    /// VM reset
+    Reset { guest: bool, reason: Option<String> },
+    /// Execution paused
+    Stop,
+    /// Execution resumed
+    Resume,
+    /// ACPI power button pressed (`system_powerdown`)
+    PowerdownRequested,
+}
+
+impl QmpEvent {
+    /// Parse a `{"event": ..., "data": ...}` message; `None` for events the
+    /// app doesn't track
+    pub fn from_message(message: &Value) -> Option<Self> {
+        let data = &message["data"];
+        let guest = data["guest"].as_bool().unwrap_or(false);
+        let reason = data["reason"].as_str().map(str::to_string);
+        match message.get("event")?.as_str()? {
+            "SHUTDOWN" => Some(QmpEvent::Shutdown { guest, reason }),
+            "RESET" => Some(QmpEvent::Reset { guest, reason }),
+            "STOP" => Some(QmpEvent::Stop),
+            "RESUME" => Some(QmpEvent::Resume),
+            "POWERDOWN" => Some(QmpEvent::PowerdownRequested),
+            _ => None,
+        }
+    }
 }
 
 pub struct QmpClient {
-    stream: BufReader<UnixStream>,
-    #[allow(dead_code)]
+    stream: Option<BufReader<UnixStream>>,
+    /// Bytes of the message being read, kept across cancelled reads
+    line: Vec<u8>,
     socket_path: String,
+    status: watch::Sender<QmpStatus>,
+    reconnect_policy: ReconnectPolicy,
+    event_tx: Option<mpsc::Sender<QmpEvent>>,
 }
 
 impl QmpClient {
+    /// Client for a QMP socket, not yet connected
+    pub fn new(socket_path: impl Into<String>) -> Self {
+        let (status, _) = watch::channel(QmpStatus::Disconnected);
+        Self {
+            stream: None,
+            line: Vec::new(),
+            socket_path: socket_path.into(),
+            status,
+            reconnect_policy: ReconnectPolicy::default(),
+            event_tx: None,
+        }
+    }
+
+    /// Client for the socket QEMU creates for `vm_id` (`/tmp/qmp-<vm_id>.sock`)
+    pub fn for_vm(vm_id: &str) -> Self {
+        Self::new(format!("/tmp/qmp-{}.sock", vm_id))
+    }
+
+    pub fn with_reconnect_policy(mut self, policy: ReconnectPolicy) -> Self {
+        self.reconnect_policy = policy;
+        self
+    }
+
     /// Connect to a QMP socket and perform the initial handshake
     pub async fn connect(vm_id: &str) -> Result<Self, QmpError> {
-        let socket_path = format!("/tmp/qmp-{}.sock", vm_id);
-        log::info!("🔌 QMP: Connecting to {}", socket_path);
-
-        // Retry loop for connection (VM might be starting up)
-        let mut attempts = 0;
-        let stream = loop {
-            match UnixStream::connect(&socket_path).await {
-                Ok(s) => break s,
+        let mut client = Self::for_vm(vm_id);
+        client.ensure_connected().await?;
+        Ok(client)
+    }
+
+    /// Connect to a QMP socket at an explicit path
+    pub async fn connect_path(socket_path: &str) -> Result<Self, QmpError> {
+        let mut client = Self::new(socket_path);
+        client.ensure_connected().await?;
+        Ok(client)
+    }
+
+    pub fn socket_path(&self) -> &str {
+        &self.socket_path
+    }
+
+    pub fn connection_status(&self) -> QmpStatus {
+        *self.status.borrow()
+    }
+
+    /// Receiver that follows the connection status, for use outside the task
+    /// owning the client
+    pub fn status_watch(&self) -> watch::Receiver<QmpStatus> {
+        self.status.subscribe()
+    }
+
+    /// Channel receiving the events QEMU emits from now on
+    ///
+    /// Events are read while waiting for command responses and by
+    /// `pump_events`. Calling this again replaces the previous channel.
+    pub fn events(&mut self) -> mpsc::Receiver<QmpEvent> {
+        let (tx, rx) = mpsc::channel(EVENT_CHANNEL_CAPACITY);
+        self.event_tx = Some(tx);
+        rx
+    }
+
+    fn set_status(&self, status: QmpStatus) {
+        self.status.send_if_modified(|current| {
+            let changed = *current != status;
+            *current = status;
+            changed
+        });
+    }
+
+    /// Connect and handshake unless already connected
+    ///
+    /// Retries according to the reconnect policy (the VM might be starting
+    /// up or resetting). The status is `Reconnecting` while retrying and
+    /// `Disconnected` once the attempts run out.
+    pub async fn ensure_connected(&mut self) -> Result<(), QmpError> {
+        if self.stream.is_some() {
+            return Ok(());
+        }
+        self.set_status(QmpStatus::Reconnecting);
+        log::info!("🔌 QMP: Connecting to {}", self.socket_path);
+
+        let policy = self.reconnect_policy;
+        let mut delay = policy.initial_delay;
+        for attempt in 1..=policy.max_attempts {
+            match self.open().await {
+                Ok(()) => {
+                    self.set_status(QmpStatus::Connected);
+                    log::info!("✅ QMP Handshake Complete for {}", self.socket_path);
+                    return Ok(());
+                },
                 Err(e) => {
-                    attempts += 1;
-                    if attempts > 5 {
-                        return Err(QmpError::Io(e));
-                    }
-                    tokio::time::sleep(Duration::from_millis(500)).await;
+                    self.stream = None;
+                    log::debug!("🔌 QMP: Attempt {} failed: {}", attempt, e);
                 },
             }
-        };
+            if attempt < policy.max_attempts {
+                tokio::time::sleep(delay).await;
+                delay = (delay * 2).min(policy.max_delay);
+            }
+        }
 
-        let mut client = Self {
-            stream: BufReader::new(stream),
-            socket_path,
-        };
+        self.set_status(QmpStatus::Disconnected);
+        Err(QmpError::ReconnectFailed(policy.max_attempts))
+    }
+
+    /// Open the socket and perform the QMP handshake
+    async fn open(&mut self) -> Result<(), QmpError> {
+        let stream = UnixStream::connect(&self.socket_path).await?;
+        self.stream = Some(BufReader::new(stream));
+        self.line.clear();
 
-        // QMP Handshake
         // 1. Read Greeting
-        let greeting = client.read_message().await?;
+        let greeting = self.read_message().await?;
         if greeting.get("QMP").is_none() {
             return Err(QmpError::Protocol("No QMP greeting received".to_string()));
         }
         log::debug!("🔌 QMP Greeting: {:?}", greeting);
 
         // 2. Enable Capabilities
-        let response = client.execute("qmp_capabilities", None).await?;
+        self.send(&json!({ "execute": "qmp_capabilities" })).await?;
+        let response = self.read_response().await?;
         if response.get("return").is_none() {
             return Err(QmpError::Protocol(format!(
                 "Handshake failed: {:?}",
                 response
             )));
         }
+        Ok(())
+    }
 
-        log::info!("✅ QMP Handshake Complete for {}", vm_id);
-        Ok(client)
+    /// Drop the current connection and run the reconnect loop
+    async fn reconnect(&mut self) -> Result<(), QmpError> {
+        log::warn!(
+            "⚠️ QMP: Connection to {} lost, reconnecting",
+            self.socket_path
+        );
+        self.stream = None;
+        self.ensure_connected().await
     }
 
     /// Execute a QMP command
+    ///
+    /// Reconnects automatically when the socket has gone away. A command
+    /// that could not be written is resent on the new connection; one whose
+    /// response was lost is not, since QEMU may already have run it (e.g.
+    /// `system_reset`), and its error is returned instead.
     pub async fn execute(
         &mut self,
         command: &str,
@@ -87,36 +279,107 @@ impl QmpClient {
             cmd_obj["arguments"] = args;
         }
 
-        let cmd_str = serde_json::to_string(&cmd_obj)?;
-        self.stream.write_all(cmd_str.as_bytes()).await?;
-        self.stream.write_all(b"\n").await?; // Newline is required
-        self.stream.flush().await?;
+        self.ensure_connected().await?;
+        if let Err(e) = self.send(&cmd_obj).await {
+            if !e.is_connection_lost() {
+                return Err(e);
+            }
+            self.reconnect().await?;
+            self.send(&cmd_obj).await?;
+        }
+
+        match self.read_response().await {
+            Err(e) if e.is_connection_lost() => {
+                if let Err(reconnect_error) = self.reconnect().await {
+                    log::warn!("⚠️ QMP: {}", reconnect_error);
+                }
+                Err(e)
+            },
+            result => result,
+        }
+    }
+
+    async fn send(&mut self, cmd_obj: &Value) -> Result<(), QmpError> {
+        let stream = self.stream.as_mut().ok_or(QmpError::NotConnected)?;
+        let cmd_str = serde_json::to_string(cmd_obj)?;
+        stream.write_all(cmd_str.as_bytes()).await?;
+        stream.write_all(b"\n").await?; // Newline is required
+        stream.flush().await?;
+        Ok(())
+    }
 
+    /// Read the response to the last command
+    async fn read_response(&mut self) -> Result<Value, QmpError> {
         // Read response (skip events if necessary, but for now simple read)
         // Note: Real QMP is asynchronous; events can arrive at any time.
         // This is a simplified synchronous-over-async implementations.
         loop {
             let msg = self.read_message().await?;
-            // If it's an event (has "event" key), log it and wait for "return"
+            // If it's an event (has "event" key), forward it and wait for "return"
             if msg.get("event").is_some() {
-                log::debug!("🔔 QMP Event: {:?}", msg);
+                self.dispatch_event(&msg);
                 continue;
             }
             return Ok(msg);
         }
     }
 
+    /// Wait for a message while no command is running and forward events
+    ///
+    /// Cancel safe, so it can sit in a `select!` next to the command queue.
+    /// Never completes while disconnected; if the socket closes, the client
+    /// drops to `Disconnected` and reconnects on the next command.
+    pub async fn pump_events(&mut self) -> Result<(), QmpError> {
+        if self.stream.is_none() {
+            return std::future::pending().await;
+        }
+        match self.read_message().await {
+            Ok(msg) if msg.get("event").is_some() => {
+                self.dispatch_event(&msg);
+                Ok(())
+            },
+            Ok(msg) => {
+                log::debug!("QMP: Ignoring unsolicited message {:?}", msg);
+                Ok(())
+            },
+            Err(e) => {
+                if e.is_connection_lost() {
+                    self.stream = None;
+                    self.set_status(QmpStatus::Disconnected);
+                }
+                Err(e)
+            },
+        }
+    }
+
+    fn dispatch_event(&mut self, msg: &Value) {
+        log::debug!("🔔 QMP Event: {:?}", msg);
+        let (Some(event), Some(tx)) = (QmpEvent::from_message(msg), &self.event_tx) else {
+            return;
+        };
+        match tx.try_send(event) {
+            Ok(()) => {},
+            Err(mpsc::error::TrySendError::Full(event)) => {
+                log::warn!("⚠️ QMP: Event channel full, dropping {:?}", event);
+            },
+            Err(mpsc::error::TrySendError::Closed(_)) => self.event_tx = None,
+        }
+    }
+
     /// Read a single JSON message line
     async fn read_message(&mut self) -> Result<Value, QmpError> {
-        let mut line = String::new();
-        self.stream.read_line(&mut line).await?;
-        if line.is_empty() {
+        let stream = self.stream.as_mut().ok_or(QmpError::NotConnected)?;
+        // read_until keeps what it read in `self.line` if the future is
+        // dropped, so a cancelled `pump_events` loses nothing
+        stream.read_until(b'\n', &mut self.line).await?;
+        let line = std::mem::take(&mut self.line);
+        if !line.ends_with(b"\n") {
             return Err(QmpError::Io(std::io::Error::new(
                 std::io::ErrorKind::UnexpectedEof,
                 "QMP Socket closed",
             )));
         }
-        let value: Value = serde_json::from_str(&line)?;
+        let value: Value = serde_json::from_slice(&line)?;
         Ok(value)
     }
 
@@ -164,9 +427,6 @@ impl QmpClient {
             return Ok(filename.to_string());
         }
 
-        Err(QmpError::Protocol(format!(
-            "Screendump failed: {:?}",
-            resp
-        )))
+        Err(QmpError::Protocol(format!("Screendump failed: {:?}", resp)))
     }
 }
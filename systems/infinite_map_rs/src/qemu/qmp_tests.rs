@@ -1,7 +1,10 @@
 #[cfg(test)]
 mod tests {
-    use super::*;
-    use serde_json::json;
+    use crate::qemu::qmp::{QmpClient, QmpEvent, QmpStatus, ReconnectPolicy};
+    use serde_json::{json, Value};
+    use std::time::Duration;
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+    use tokio::net::UnixListener;
 
     #[test]
     fn test_qmp_command_serialization() {
@@ -23,4 +26,152 @@ mod tests {
             r#"{"arguments":{"zoom":1.5},"execute":"camera"}"#
         );
     }
+
+    /// Mock QEMU: accept one client, greet it, answer `qmp_capabilities`
+    /// plus `commands` further commands, then hang up
+    async fn serve_session(listener: &UnixListener, commands: usize) {
+        let (stream, _) = listener.accept().await.unwrap();
+        let (read, mut write) = stream.into_split();
+        let mut lines = BufReader::new(read).lines();
+        write
+            .write_all(b"{\"QMP\": {\"version\": {}, \"capabilities\": []}}\n")
+            .await
+            .unwrap();
+        for _ in 0..=commands {
+            let Some(line) = lines.next_line().await.unwrap() else {
+                return;
+            };
+            let request: Value = serde_json::from_str(&line).unwrap();
+            let reply = match request["execute"].as_str() {
+                Some("query-status") => json!({"return": {"status": "running", "running": true}}),
+                _ => json!({"return": {}}),
+            };
+            write
+                .write_all(format!("{}\n", reply).as_bytes())
+                .await
+                .unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn test_client_reconnects_when_server_returns() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("qmp-test.sock");
+
+        // First VM instance answers one query, then goes away (e.g. reset)
+        let listener = UnixListener::bind(&path).unwrap();
+        let server = tokio::spawn(async move { serve_session(&listener, 1).await });
+
+        let mut client =
+            QmpClient::new(path.to_str().unwrap()).with_reconnect_policy(ReconnectPolicy {
+                max_attempts: 500,
+                initial_delay: Duration::from_millis(5),
+                max_delay: Duration::from_millis(20),
+            });
+        assert_eq!(client.connection_status(), QmpStatus::Disconnected);
+        client.ensure_connected().await.unwrap();
+        assert_eq!(client.connection_status(), QmpStatus::Connected);
+        assert_eq!(client.query_status().await.unwrap(), "running");
+
+        server.await.unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        let mut status = client.status_watch();
+        let command = tokio::spawn(async move {
+            let result = client.query_status().await;
+            (client, result)
+        });
+        status
+            .wait_for(|status| *status == QmpStatus::Reconnecting)
+            .await
+            .unwrap();
+
+        // The VM comes back on the same socket
+        let listener = UnixListener::bind(&path).unwrap();
+        let server = tokio::spawn(async move { serve_session(&listener, 2).await });
+
+        // The query never reached the old socket, so it is resent
+        let (mut client, result) = command.await.unwrap();
+        assert_eq!(result.unwrap(), "running");
+        assert_eq!(client.connection_status(), QmpStatus::Connected);
+        assert_eq!(*status.borrow(), QmpStatus::Connected);
+        assert_eq!(client.query_status().await.unwrap(), "running");
+
+        drop(client);
+        server.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_events_are_delivered() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("qmp-events.sock");
+        let listener = UnixListener::bind(&path).unwrap();
+
+        let server = tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let (read, mut write) = stream.into_split();
+            let mut lines = BufReader::new(read).lines();
+            write
+                .write_all(b"{\"QMP\": {\"version\": {}, \"capabilities\": []}}\n")
+                .await
+                .unwrap();
+            lines.next_line().await.unwrap(); // qmp_capabilities
+            write.write_all(b"{\"return\": {}}\n").await.unwrap();
+
+            // An event arriving between a command and its response
+            lines.next_line().await.unwrap(); // stop
+            write
+                .write_all(b"{\"timestamp\": {\"seconds\": 1, \"microseconds\": 0}, \"event\": \"STOP\"}\n{\"return\": {}}\n")
+                .await
+                .unwrap();
+
+            // The guest powers itself off while the client is idle
+            write
+                .write_all(b"{\"timestamp\": {\"seconds\": 2, \"microseconds\": 0}, \"event\": \"SHUTDOWN\", \"data\": {\"guest\": true, \"reason\": \"guest-shutdown\"}}\n")
+                .await
+                .unwrap();
+            lines.next_line().await.unwrap();
+        });
+
+        let mut client = QmpClient::new(path.to_str().unwrap());
+        let mut events = client.events();
+        client.ensure_connected().await.unwrap();
+
+        client.stop().await.unwrap();
+        assert_eq!(events.try_recv().unwrap(), QmpEvent::Stop);
+
+        client.pump_events().await.unwrap();
+        assert_eq!(
+            events.recv().await.unwrap(),
+            QmpEvent::Shutdown {
+                guest: true,
+                reason: Some("guest-shutdown".to_string()),
+            }
+        );
+
+        // The server hangs up once the client goes away
+        drop(client);
+        server.await.unwrap();
+    }
+
+    #[test]
+    fn test_event_parsing() {
+        let event = |message: Value| QmpEvent::from_message(&message);
+        assert_eq!(event(json!({"event": "RESUME"})), Some(QmpEvent::Resume));
+        assert_eq!(
+            event(json!({"event": "POWERDOWN"})),
+            Some(QmpEvent::PowerdownRequested)
+        );
+        assert_eq!(
+            event(
+                json!({"event": "RESET", "data": {"guest": false, "reason": "host-qmp-system-reset"}})
+            ),
+            Some(QmpEvent::Reset {
+                guest: false,
+                reason: Some("host-qmp-system-reset".to_string()),
+            })
+        );
+        assert_eq!(event(json!({"event": "RTC_CHANGE"})), None);
+        assert_eq!(event(json!({"return": {}})), None);
+    }
 }
@@ -148,7 +148,7 @@ struct BorderUniforms {
     tile_pos: [f32; 2],
     tile_size: [f32; 2],
     border_thickness: f32,
-    _pad1: f32,
+    _pad1: [f32; 3],
     border_color: [f32; 4],
     screen_size: [f32; 2],
     _pad2: [f32; 2],
@@ -944,7 +944,7 @@ impl<'a> Renderer<'a> {
             tile_pos: [100.0, 100.0],  // Fixed position for now
             tile_size: [256.0, 256.0], // Fixed size for now
             border_thickness: 4.0,
-            _pad1: 0.0,
+            _pad1: [0.0; 3],
             border_color,
             screen_size: [self.config.width as f32, self.config.height as f32],
             _pad2: [0.0; 2],
@@ -2559,7 +2559,7 @@ impl<'a> Renderer<'a> {
         // Phase 48.3: Render execution zone compositor (if initialized)
         // The compositor renders execution zones for WGSL .rts.png file drops
         if let Some(comp) = compositor {
-            comp.render(&mut encoder, &output.texture);
+            comp.render(&mut encoder, &output.texture, camera);
         }
 
         self.queue.submit(std::iter::once(encoder.finish()));
@@ -9,14 +9,20 @@
 //! - Renders inactive zones with a visual indicator
 //! - Blits results to the output texture
 
-use crate::entities::execution_zone::ExecutionZone;
+use crate::camera::Camera;
+use crate::entities::execution_zone::{BlendMode, ExecutionZone};
 use crate::glyph_atlas::GlyphAtlas;
 use crate::glyph_substrate::GlyphSubstrate;
 use crate::rendering::glyph_renderer::GlyphRenderer;
 use crate::ui::zone_overlay::BorderColor;
+use std::collections::HashMap;
 use std::sync::Arc;
+use wgpu::util::DeviceExt;
 use wgpu::{CommandEncoder, Device, Texture};
 
+/// Edge length of an execution zone on the map, in pixels
+pub const ZONE_SIZE: f32 = 256.0;
+
 /// Uniform buffer structure for border shader
 /// Must match the BorderUniforms struct in border_quad.wgsl
 #[repr(C)]
@@ -28,8 +34,8 @@ struct BorderUniforms {
     tile_size: [f32; 2],
     /// Border thickness in pixels
     border_thickness: f32,
-    /// Padding to align to 16 bytes
-    _pad1: f32,
+    /// Padding so `border_color` lands on its 16-byte WGSL alignment
+    _pad1: [f32; 3],
     /// Border color (r, g, b, a)
     border_color: [f32; 4],
     /// Screen dimensions
@@ -38,6 +44,48 @@ struct BorderUniforms {
     _pad2: [f32; 2],
 }
 
+/// Size of one zone's border uniforms in the batched uniform buffer
+const BORDER_UNIFORMS_SIZE: u64 = std::mem::size_of::<BorderUniforms>() as u64;
+
+/// Uniform buffer structure for zone blending
+/// Must match the ZoneBlendUniforms struct in zone_blend.wgsl
+#[repr(C)]
+#[derive(Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+struct ZoneBlendUniforms {
+    /// Destination rect in pixels (x, y, width, height)
+    dest_rect: [f32; 4],
+    /// Output target dimensions
+    screen_size: [f32; 2],
+    /// Padding to align total struct to 16 bytes
+    _pad: [f32; 2],
+}
+
+/// Border uniforms for `zone`: green once compiled, gray otherwise
+fn border_uniforms(zone: &ExecutionZone, screen_size: (f32, f32)) -> BorderUniforms {
+    let border_config = crate::ui::zone_overlay::render_zone_border(
+        zone.position,
+        (ZONE_SIZE, ZONE_SIZE),
+        zone.pipeline().is_some(), // Active if has pipeline
+    );
+
+    // Convert BorderColor to RGBA
+    let border_color = match border_config.color {
+        BorderColor::Active => [0.2, 0.8, 0.3, 1.0],   // Green
+        BorderColor::Inactive => [0.5, 0.5, 0.5, 0.8], // Gray
+        BorderColor::Error => [0.9, 0.2, 0.2, 1.0],    // Red
+    };
+
+    BorderUniforms {
+        tile_pos: [border_config.top_left().x, border_config.top_left().y],
+        tile_size: [border_config.width, border_config.height],
+        border_thickness: border_config.line_width,
+        _pad1: [0.0; 3],
+        border_color,
+        screen_size: [screen_size.0, screen_size.1],
+        _pad2: [0.0, 0.0],
+    }
+}
+
 /// Execution Zone Renderer
 ///
 /// Manages rendering of execution zones on the infinite map.
@@ -62,10 +110,13 @@ pub struct ExecutionZoneRenderer {
     zones: Vec<ExecutionZone>,
     /// Border rendering pipeline (lazy-initialized)
     border_pipeline: Option<wgpu::RenderPipeline>,
-    /// Border uniform buffer (lazy-initialized)
+    /// Border uniform buffer, one entry per zone at a dynamic offset
+    /// (lazy-initialized)
     border_uniform_buffer: Option<wgpu::Buffer>,
     /// Border bind group (lazy-initialized)
     border_bind_group: Option<wgpu::BindGroup>,
+    /// Number of zones the border uniform buffer has room for
+    border_capacity: usize,
     /// Border bind group layout (lazy-initialized)
     border_bind_group_layout: Option<wgpu::BindGroupLayout>,
     /// Glyph atlas for text rendering (lazy-initialized)
@@ -74,6 +125,12 @@ pub struct ExecutionZoneRenderer {
     glyph_substrate: Option<GlyphSubstrate>,
     /// Glyph renderer for text overlay (lazy-initialized)
     glyph_renderer: Option<GlyphRenderer>,
+    /// Zone blend bind group layout (lazy-initialized)
+    blend_bind_group_layout: Option<wgpu::BindGroupLayout>,
+    /// Zone blend pipelines by output format and mode (lazy-initialized)
+    blend_pipelines: HashMap<(wgpu::TextureFormat, BlendMode), wgpu::RenderPipeline>,
+    /// Screen size in pixels (width, height) used by `render_visible`
+    viewport: (f32, f32),
 }
 
 impl ExecutionZoneRenderer {
@@ -94,10 +151,14 @@ impl ExecutionZoneRenderer {
             border_pipeline: None,
             border_uniform_buffer: None,
             border_bind_group: None,
+            border_capacity: 0,
             border_bind_group_layout: None,
             glyph_atlas: None,
             glyph_substrate: None,
             glyph_renderer: None,
+            blend_bind_group_layout: None,
+            blend_pipelines: HashMap::new(),
+            viewport: (0.0, 0.0),
         }
     }
 
@@ -169,8 +230,8 @@ impl ExecutionZoneRenderer {
                         visibility: wgpu::ShaderStages::VERTEX | wgpu::ShaderStages::FRAGMENT,
                         ty: wgpu::BindingType::Buffer {
                             ty: wgpu::BufferBindingType::Uniform,
-                            has_dynamic_offset: false,
-                            min_binding_size: None,
+                            has_dynamic_offset: true,
+                            min_binding_size: wgpu::BufferSize::new(BORDER_UNIFORMS_SIZE),
                         },
                         count: None,
                     }],
@@ -219,31 +280,185 @@ impl ExecutionZoneRenderer {
                 multiview: None,
             });
 
-        // Create uniform buffer (initialized with dummy values)
+        // Store all resources; the uniform buffer is sized per batch
+        self.border_pipeline = Some(pipeline);
+        self.border_bind_group_layout = Some(bind_group_layout);
+
+        log::info!("Border rendering pipeline initialized successfully");
+    }
+
+    /// Byte stride between zones in the border uniform buffer
+    fn border_uniform_stride(&self) -> u64 {
+        let alignment = self.device.limits().min_uniform_buffer_offset_alignment as u64;
+        BORDER_UNIFORMS_SIZE.next_multiple_of(alignment)
+    }
+
+    /// Grow the border uniform buffer to hold `count` zones
+    fn reserve_border_batch(&mut self, count: usize) {
+        if count <= self.border_capacity {
+            return;
+        }
+        let Some(layout) = self.border_bind_group_layout.as_ref() else {
+            return;
+        };
+
+        let capacity = count.next_power_of_two();
         let uniform_buffer = self.device.create_buffer(&wgpu::BufferDescriptor {
             label: Some("Border Uniform Buffer"),
-            size: std::mem::size_of::<BorderUniforms>() as u64,
+            size: self.border_uniform_stride() * capacity as u64,
             usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
             mapped_at_creation: false,
         });
-
-        // Create bind group
         let bind_group = self.device.create_bind_group(&wgpu::BindGroupDescriptor {
             label: Some("Border Bind Group"),
-            layout: &bind_group_layout,
+            layout,
             entries: &[wgpu::BindGroupEntry {
                 binding: 0,
-                resource: uniform_buffer.as_entire_binding(),
+                resource: wgpu::BindingResource::Buffer(wgpu::BufferBinding {
+                    buffer: &uniform_buffer,
+                    offset: 0,
+                    size: wgpu::BufferSize::new(BORDER_UNIFORMS_SIZE),
+                }),
             }],
         });
 
-        // Store all resources
-        self.border_pipeline = Some(pipeline);
         self.border_uniform_buffer = Some(uniform_buffer);
         self.border_bind_group = Some(bind_group);
-        self.border_bind_group_layout = Some(bind_group_layout);
+        self.border_capacity = capacity;
+    }
 
-        log::info!("Border rendering pipeline initialized successfully");
+    /// Upload one set of border uniforms per zone, in draw order
+    ///
+    /// Every zone gets its own slot, so each draw in a pass sees its own
+    /// border rather than whichever was written last.
+    fn write_border_batch(&mut self, uniforms: &[BorderUniforms]) {
+        if uniforms.is_empty() {
+            return;
+        }
+        self.reserve_border_batch(uniforms.len());
+        let Some(uniform_buffer) = self.border_uniform_buffer.as_ref() else {
+            return;
+        };
+
+        let stride = self.border_uniform_stride() as usize;
+        let mut bytes = vec![0u8; stride * uniforms.len()];
+        for (slot, zone_uniforms) in bytes.chunks_exact_mut(stride).zip(uniforms) {
+            slot[..BORDER_UNIFORMS_SIZE as usize]
+                .copy_from_slice(bytemuck::bytes_of(zone_uniforms));
+        }
+        self.queue.write_buffer(uniform_buffer, 0, &bytes);
+    }
+
+    /// Draw the first `count` borders of the uploaded batch into `pass`
+    fn draw_border_batch<'a>(&'a self, pass: &mut wgpu::RenderPass<'a>, count: usize) {
+        let (Some(pipeline), Some(bind_group)) = (
+            self.border_pipeline.as_ref(),
+            self.border_bind_group.as_ref(),
+        ) else {
+            return;
+        };
+
+        let stride = self.border_uniform_stride();
+        pass.set_pipeline(pipeline);
+        for slot in 0..count as u64 {
+            // Draw border (6 vertices for 2 triangles forming a quad)
+            pass.set_bind_group(0, bind_group, &[(slot * stride) as u32]);
+            pass.draw(0..6, 0..1);
+        }
+    }
+
+    /// Initialize a zone blend pipeline (lazy initialization)
+    ///
+    /// Creates the quad pipeline used to composite zones whose blend mode
+    /// is not `BlendMode::Replace`. One pipeline is cached per output format
+    /// and blend mode.
+    ///
+    /// # Arguments
+    ///
+    /// * `surface_format` - Texture format of the output surface
+    /// * `mode` - Blend mode the pipeline applies
+    fn initialize_blend_pipeline(&mut self, surface_format: wgpu::TextureFormat, mode: BlendMode) {
+        if self.blend_pipelines.contains_key(&(surface_format, mode)) {
+            return;
+        }
+
+        log::info!("Initializing zone blend pipeline ({:?})", mode);
+
+        let layout = self.blend_bind_group_layout.get_or_insert_with(|| {
+            self.device
+                .create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                    label: Some("Zone Blend Bind Group Layout"),
+                    entries: &[
+                        wgpu::BindGroupLayoutEntry {
+                            binding: 0,
+                            visibility: wgpu::ShaderStages::VERTEX | wgpu::ShaderStages::FRAGMENT,
+                            ty: wgpu::BindingType::Buffer {
+                                ty: wgpu::BufferBindingType::Uniform,
+                                has_dynamic_offset: false,
+                                min_binding_size: None,
+                            },
+                            count: None,
+                        },
+                        wgpu::BindGroupLayoutEntry {
+                            binding: 1,
+                            visibility: wgpu::ShaderStages::FRAGMENT,
+                            ty: wgpu::BindingType::Texture {
+                                sample_type: wgpu::TextureSampleType::Float { filterable: false },
+                                view_dimension: wgpu::TextureViewDimension::D2,
+                                multisampled: false,
+                            },
+                            count: None,
+                        },
+                    ],
+                })
+        });
+
+        let shader = self
+            .device
+            .create_shader_module(wgpu::ShaderModuleDescriptor {
+                label: Some("Zone Blend Shader"),
+                source: wgpu::ShaderSource::Wgsl(include_str!("../shaders/zone_blend.wgsl").into()),
+            });
+
+        let pipeline_layout = self
+            .device
+            .create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some("Zone Blend Pipeline Layout"),
+                bind_group_layouts: &[layout],
+                push_constant_ranges: &[],
+            });
+
+        let pipeline = self
+            .device
+            .create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                label: Some("Zone Blend Pipeline"),
+                layout: Some(&pipeline_layout),
+                vertex: wgpu::VertexState {
+                    module: &shader,
+                    entry_point: "vs_main",
+                    buffers: &[],
+                },
+                fragment: Some(wgpu::FragmentState {
+                    module: &shader,
+                    entry_point: "fs_main",
+                    targets: &[Some(wgpu::ColorTargetState {
+                        format: surface_format,
+                        blend: Some(mode.blend_state()),
+                        write_mask: wgpu::ColorWrites::ALL,
+                    })],
+                }),
+                primitive: wgpu::PrimitiveState {
+                    topology: wgpu::PrimitiveTopology::TriangleList,
+                    cull_mode: None,
+                    ..Default::default()
+                },
+                depth_stencil: None,
+                multisample: wgpu::MultisampleState::default(),
+                multiview: None,
+            });
+
+        self.blend_pipelines
+            .insert((surface_format, mode), pipeline);
     }
 
     /// Initialize glyph rendering pipeline (lazy initialization)
@@ -296,7 +511,145 @@ impl ExecutionZoneRenderer {
     /// rendering failures such as pipeline creation errors, resource binding
     /// failures, or command encoding errors.
     pub fn render(&mut self, encoder: &mut CommandEncoder, output_texture: &Texture) {
-        for zone in &self.zones {
+        let all: Vec<usize> = (0..self.zones.len()).collect();
+        self.render_zones(encoder, output_texture, &all);
+    }
+
+    /// Dispatch, blit and label only the zones inside the camera's view
+    ///
+    /// This is the encoder half of culled rendering: compute passes and
+    /// texture copies cannot be recorded inside a render pass. Borders are
+    /// drawn afterwards by [`Self::render_visible`].
+    ///
+    /// # Arguments
+    ///
+    /// * `camera` - Camera whose view is used for culling
+    /// * `viewport` - Screen size in pixels (width, height)
+    /// * `encoder` - Command encoder for recording rendering commands
+    /// * `output_texture` - Output texture to blit results to
+    ///
+    /// # Returns
+    ///
+    /// Number of zones dispatched
+    pub fn dispatch_visible(
+        &mut self,
+        camera: &Camera,
+        viewport: (f32, f32),
+        encoder: &mut CommandEncoder,
+        output_texture: &Texture,
+    ) -> usize {
+        let visible = Self::visible_zone_indices(&self.zones, camera, viewport);
+        self.dispatch_zones(encoder, output_texture, &visible);
+        self.render_text_overlays(encoder, output_texture, &visible);
+        visible.len()
+    }
+
+    /// Prepare [`Self::render_visible`] for passes drawing into a
+    /// `surface_format` target of `viewport` pixels (width, height)
+    pub fn set_target(&mut self, surface_format: wgpu::TextureFormat, viewport: (f32, f32)) {
+        self.initialize_border_pipeline(surface_format);
+        self.viewport = viewport;
+    }
+
+    /// Draw the borders of the zones inside the camera's view into `pass`
+    ///
+    /// Zones entirely outside the view are culled. The rest are uploaded as
+    /// one batch of uniforms and drawn back to back in `pass`. Call
+    /// [`Self::set_target`] first with the pass's format and size.
+    ///
+    /// # Arguments
+    ///
+    /// * `zones` - Zones to cull and draw
+    /// * `camera` - Camera whose view is used for culling
+    /// * `pass` - Render pass to draw into
+    ///
+    /// # Returns
+    ///
+    /// Number of zones rendered
+    pub fn render_visible<'a>(
+        &'a mut self,
+        zones: &[ExecutionZone],
+        camera: &Camera,
+        pass: &mut wgpu::RenderPass<'a>,
+    ) -> usize {
+        if self.border_pipeline.is_none() {
+            log::warn!("render_visible called before set_target - skipping zones");
+            return 0;
+        }
+
+        let visible = Self::visible_zone_indices(zones, camera, self.viewport);
+        log::trace!(
+            "Culled {} of {} execution zones",
+            zones.len() - visible.len(),
+            zones.len()
+        );
+
+        let uniforms: Vec<BorderUniforms> = visible
+            .iter()
+            .map(|&i| border_uniforms(&zones[i], self.viewport))
+            .collect();
+        self.write_border_batch(&uniforms);
+
+        let renderer: &'a Self = self;
+        renderer.draw_border_batch(pass, uniforms.len());
+        uniforms.len()
+    }
+
+    /// Indices of the zones overlapping the camera's view
+    ///
+    /// # Arguments
+    ///
+    /// * `zones` - Zones to test
+    /// * `camera` - Camera whose view is used for culling
+    /// * `viewport` - Screen size in pixels (width, height)
+    pub fn visible_zone_indices(
+        zones: &[ExecutionZone],
+        camera: &Camera,
+        viewport: (f32, f32),
+    ) -> Vec<usize> {
+        let view_min = camera.screen_to_world(0.0, 0.0, viewport.0, viewport.1);
+        let view_max = camera.screen_to_world(viewport.0, viewport.1, viewport.0, viewport.1);
+
+        zones
+            .iter()
+            .enumerate()
+            .filter(|(_, zone)| {
+                let min = zone.position;
+                let max = zone.position + glam::Vec2::splat(ZONE_SIZE);
+                min.x < view_max.x && max.x > view_min.x && min.y < view_max.y && max.y > view_min.y
+            })
+            .map(|(index, _)| index)
+            .collect()
+    }
+
+    /// Render the zones at `indices`
+    fn render_zones(
+        &mut self,
+        encoder: &mut CommandEncoder,
+        output_texture: &Texture,
+        indices: &[usize],
+    ) {
+        self.dispatch_zones(encoder, output_texture, indices);
+
+        // Render borders for all zones
+        let screen_size = (
+            output_texture.width() as f32,
+            output_texture.height() as f32,
+        );
+        self.render_borders(encoder, output_texture, screen_size, indices);
+
+        // Render text overlays for all zones
+        self.render_text_overlays(encoder, output_texture, indices);
+    }
+
+    /// Dispatch the zones at `indices` and blit their results
+    fn dispatch_zones(
+        &mut self,
+        encoder: &mut CommandEncoder,
+        output_texture: &Texture,
+        indices: &[usize],
+    ) {
+        for zone in indices.iter().map(|&i| &self.zones[i]) {
             if zone.is_active() {
                 self.render_zone(encoder, zone);
             } else {
@@ -304,22 +657,24 @@ impl ExecutionZoneRenderer {
             }
         }
 
+        // Prepare pipelines for zones that blend instead of copying
+        let surface_format = output_texture.format();
+        let blend_modes: Vec<BlendMode> = indices
+            .iter()
+            .map(|&i| &self.zones[i])
+            .filter(|z| z.is_active() && z.blend_mode() != BlendMode::Replace)
+            .map(|z| z.blend_mode())
+            .collect();
+        for mode in blend_modes {
+            self.initialize_blend_pipeline(surface_format, mode);
+        }
+
         // Blit all active zone results to output texture
-        for zone in &self.zones {
+        for zone in indices.iter().map(|&i| &self.zones[i]) {
             if zone.is_active() {
                 self.blit_results(encoder, zone, output_texture);
             }
         }
-
-        // Render borders for all zones
-        let screen_size = (
-            output_texture.width() as f32,
-            output_texture.height() as f32,
-        );
-        self.render_borders(encoder, output_texture, screen_size);
-
-        // Render text overlays for all zones
-        self.render_text_overlays(encoder, output_texture);
     }
 
     /// Render an active execution zone
@@ -508,6 +863,11 @@ impl ExecutionZoneRenderer {
         if let Some(source_texture) = zone.texture() {
             use wgpu::{Extent3d, ImageCopyTexture, Origin3d};
 
+            if zone.blend_mode() != BlendMode::Replace {
+                self.blend_zone(encoder, zone, &source_texture, output_texture);
+                return;
+            }
+
             // Calculate blit region based on zone position
             // Each zone is 256x256 pixels (standard execution zone size)
             let zone_size = 256u32;
@@ -556,9 +916,106 @@ impl ExecutionZoneRenderer {
         }
     }
 
-    /// Render borders for all execution zones
+    /// Composite a zone's output texture with its blend mode
+    ///
+    /// Draws the zone texture as a quad at the zone position using the
+    /// pipeline for `zone.blend_mode()`.
+    ///
+    /// # Arguments
     ///
-    /// Creates a render pass and draws borders around all zones.
+    /// * `encoder` - Command encoder for recording rendering commands
+    /// * `zone` - The execution zone being composited
+    /// * `source_texture` - The zone's output texture
+    /// * `output_texture` - Destination texture
+    fn blend_zone(
+        &self,
+        encoder: &mut CommandEncoder,
+        zone: &ExecutionZone,
+        source_texture: &Texture,
+        output_texture: &Texture,
+    ) {
+        let key = (output_texture.format(), zone.blend_mode());
+        let (Some(pipeline), Some(layout)) = (
+            self.blend_pipelines.get(&key),
+            self.blend_bind_group_layout.as_ref(),
+        ) else {
+            log::warn!(
+                "Zone '{}' has no blend pipeline for {:?} - skipping blit",
+                zone.shader_name,
+                key
+            );
+            return;
+        };
+
+        let uniforms = ZoneBlendUniforms {
+            dest_rect: [
+                zone.position.x,
+                zone.position.y,
+                source_texture.width() as f32,
+                source_texture.height() as f32,
+            ],
+            screen_size: [
+                output_texture.width() as f32,
+                output_texture.height() as f32,
+            ],
+            _pad: [0.0, 0.0],
+        };
+
+        // One buffer per zone so every draw in this encoder sees its own rect
+        let uniform_buffer = self
+            .device
+            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("Zone Blend Uniform Buffer"),
+                contents: bytemuck::cast_slice(&[uniforms]),
+                usage: wgpu::BufferUsages::UNIFORM,
+            });
+        let source_view = source_texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let bind_group = self.device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Zone Blend Bind Group"),
+            layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: uniform_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::TextureView(&source_view),
+                },
+            ],
+        });
+
+        let output_view = output_texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Zone Blend Render Pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: &output_view,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Load, // Blend over existing content
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+        render_pass.set_pipeline(pipeline);
+        render_pass.set_bind_group(0, &bind_group, &[]);
+        render_pass.draw(0..6, 0..1);
+
+        log::debug!(
+            "Blended zone '{}' ({:?}) at ({}, {})",
+            zone.shader_name,
+            zone.blend_mode(),
+            zone.position.x,
+            zone.position.y
+        );
+    }
+
+    /// Render borders for the given execution zones
+    ///
+    /// Creates a render pass and draws borders around the zones.
     /// Active zones get green borders, inactive zones get gray borders.
     ///
     /// # Arguments
@@ -566,14 +1023,16 @@ impl ExecutionZoneRenderer {
     /// * `encoder` - Command encoder for recording rendering commands
     /// * `output_texture` - Output texture to render borders to
     /// * `screen_size` - Screen dimensions in pixels
+    /// * `indices` - Zones to draw borders for
     fn render_borders(
         &mut self,
         encoder: &mut CommandEncoder,
         output_texture: &Texture,
         screen_size: (f32, f32),
+        indices: &[usize],
     ) {
         // Skip if no zones
-        if self.zones.is_empty() {
+        if indices.is_empty() {
             return;
         }
 
@@ -581,10 +1040,11 @@ impl ExecutionZoneRenderer {
         let surface_format = output_texture.format();
         self.initialize_border_pipeline(surface_format);
 
-        // Get pipeline resources (safe to unwrap after initialization)
-        let pipeline = self.border_pipeline.as_ref().unwrap();
-        let uniform_buffer = self.border_uniform_buffer.as_ref().unwrap();
-        let bind_group = self.border_bind_group.as_ref().unwrap();
+        let uniforms: Vec<BorderUniforms> = indices
+            .iter()
+            .map(|&i| border_uniforms(&self.zones[i], screen_size))
+            .collect();
+        self.write_border_batch(&uniforms);
 
         // Create texture view for render pass
         let output_view = output_texture.create_view(&wgpu::TextureViewDescriptor::default());
@@ -604,59 +1064,12 @@ impl ExecutionZoneRenderer {
             timestamp_writes: None,
             occlusion_query_set: None,
         });
+        self.draw_border_batch(&mut render_pass, uniforms.len());
 
-        // Set pipeline and bind group
-        render_pass.set_pipeline(pipeline);
-        render_pass.set_bind_group(0, bind_group, &[]);
-
-        // Render border for each zone
-        for zone in &self.zones {
-            // Generate border config
-            let border_config = crate::ui::zone_overlay::render_zone_border(
-                zone.position,
-                (256.0, 256.0),            // Standard zone size
-                zone.pipeline().is_some(), // Active if has pipeline
-            );
-
-            // Convert BorderColor to RGBA
-            let border_color = match border_config.color {
-                BorderColor::Active => [0.2, 0.8, 0.3, 1.0],   // Green
-                BorderColor::Inactive => [0.5, 0.5, 0.5, 0.8], // Gray
-                BorderColor::Error => [0.9, 0.2, 0.2, 1.0],    // Red
-            };
-
-            // Create uniforms for this zone
-            let uniforms = BorderUniforms {
-                tile_pos: [border_config.top_left().x, border_config.top_left().y],
-                tile_size: [border_config.width, border_config.height],
-                border_thickness: border_config.line_width,
-                _pad1: 0.0,
-                border_color,
-                screen_size: [screen_size.0, screen_size.1],
-                _pad2: [0.0, 0.0],
-            };
-
-            // Update uniform buffer
-            // Note: In a production system, we'd use a dynamic uniform buffer
-            // or staging buffer for better performance
-            self.queue
-                .write_buffer(uniform_buffer, 0, bytemuck::cast_slice(&[uniforms]));
-
-            // Draw border (6 vertices for 2 triangles forming a quad)
-            render_pass.draw(0..6, 0..1);
-
-            log::trace!(
-                "Rendered border for zone '{}' at ({}, {})",
-                zone.shader_name,
-                zone.position.x,
-                zone.position.y
-            );
-        }
-
-        log::debug!("Rendered borders for {} zones", self.zones.len());
+        log::debug!("Rendered borders for {} zones", indices.len());
     }
 
-    /// Render text overlays for all zones
+    /// Render text overlays for the given zones
     ///
     /// Uses the GlyphAtlas and GlyphRenderer to render zone labels and metrics
     /// as text overlays at each zone's position.
@@ -665,9 +1078,15 @@ impl ExecutionZoneRenderer {
     ///
     /// * `encoder` - Command encoder for recording rendering commands
     /// * `output_texture` - Output texture to render text to
-    fn render_text_overlays(&mut self, encoder: &mut CommandEncoder, output_texture: &Texture) {
+    /// * `indices` - Zones to label (only active ones get an overlay)
+    fn render_text_overlays(
+        &mut self,
+        encoder: &mut CommandEncoder,
+        output_texture: &Texture,
+        indices: &[usize],
+    ) {
         // Skip if no zones
-        if self.zones.is_empty() {
+        if indices.is_empty() {
             return;
         }
 
@@ -688,9 +1107,9 @@ impl ExecutionZoneRenderer {
         let screen_height = output_texture.height() as f32;
 
         // Collect overlay texts and positions first (to avoid borrow issues)
-        let overlays: Vec<(String, f32, f32)> = self
-            .zones
+        let overlays: Vec<(String, f32, f32)> = indices
             .iter()
+            .map(|&i| &self.zones[i])
             .filter(|z| z.is_active())
             .map(|zone| {
                 let overlay_text =
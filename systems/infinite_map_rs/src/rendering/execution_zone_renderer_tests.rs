@@ -7,6 +7,7 @@
 #[cfg(test)]
 mod renderer_tests {
     use super::super::*;
+    use crate::tests::gpu::test_device;
 
     /// Test that verifies the ExecutionZoneRenderer API structure
     ///
@@ -41,4 +42,91 @@ mod renderer_tests {
         // Verify type is not unit type by checking they're different
         assert_ne!(_type_check, _unit_type);
     }
+
+    fn zone_at(x: f32, y: f32) -> ExecutionZone {
+        ExecutionZone::new(glam::Vec2::new(x, y), format!("zone_{x}_{y}"), Vec::new())
+    }
+
+    /// Zones outside the camera view are culled, overlapping ones kept
+    #[test]
+    fn test_visible_zone_indices_culls_offscreen_zones() {
+        // 800x600 view centred on (400, 300) covers world (0, 0)..(800, 600)
+        let camera = Camera::new(400.0, 300.0, 1.0);
+        let zones = vec![
+            zone_at(100.0, 100.0),   // inside
+            zone_at(2000.0, 100.0),  // far right
+            zone_at(-200.0, -200.0), // overlaps the top-left corner
+            zone_at(100.0, 600.0),   // touches the bottom edge only
+            zone_at(700.0, 500.0),   // overlaps the bottom-right corner
+        ];
+
+        let visible = ExecutionZoneRenderer::visible_zone_indices(&zones, &camera, (800.0, 600.0));
+        assert_eq!(visible, vec![0, 2, 4]);
+
+        // Zooming out brings the far zone into view
+        let zoomed_out = Camera::new(400.0, 300.0, 0.2);
+        let visible =
+            ExecutionZoneRenderer::visible_zone_indices(&zones, &zoomed_out, (800.0, 600.0));
+        assert_eq!(visible.len(), zones.len());
+    }
+
+    /// `render_visible` draws only the zones in view and reports how many
+    #[test]
+    fn test_render_visible_returns_rendered_count() {
+        let Some((device, queue)) = test_device() else {
+            return;
+        };
+
+        let mut renderer = ExecutionZoneRenderer::new(device.clone(), queue.clone());
+        let zones = vec![
+            zone_at(0.0, 0.0),
+            zone_at(5000.0, 0.0),
+            zone_at(256.0, 256.0),
+            zone_at(0.0, -4000.0),
+        ];
+
+        let output = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Render Visible Output"),
+            size: wgpu::Extent3d {
+                width: 512,
+                height: 512,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::Rgba8Unorm,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_DST,
+            view_formats: &[],
+        });
+        let view = output.create_view(&wgpu::TextureViewDescriptor::default());
+
+        let camera = Camera::new(256.0, 256.0, 1.0);
+        renderer.set_target(output.format(), (512.0, 512.0));
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("Render Visible Encoder"),
+        });
+        let rendered = {
+            let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Render Visible Pass"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: &view,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                        store: wgpu::StoreOp::Store,
+                    },
+                })],
+                depth_stencil_attachment: None,
+                timestamp_writes: None,
+                occlusion_query_set: None,
+            });
+            renderer.render_visible(&zones, &camera, &mut pass)
+        };
+        queue.submit(Some(encoder.finish()));
+
+        assert_eq!(rendered, 2);
+        // Only the two visible borders were uploaded
+        assert_eq!(renderer.border_capacity, 2);
+    }
 }
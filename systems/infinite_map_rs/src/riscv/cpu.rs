@@ -0,0 +1,650 @@
+//! RISC-V CPU Interpreter Module
+//!
+//! Reference interpreter for the RV32I and RV64I base ISAs. The WGSL
+//! executor only has 32-bit registers, so rv64 kernels run here instead,
+//! and it gives tests an executor that needs no GPU.
+//!
+//! Registers are stored as `u64`. In rv32 mode every value is kept
+//! zero-extended to 32 bits, so reading one back as `u32` is lossless.
+
+use std::borrow::Cow;
+
+use crate::gpu_capabilities::I64Strategy;
+use crate::i64_emulation::{
+    emulated_i64_add, emulated_i64_shl, emulated_i64_shr, emulated_i64_sub,
+};
+
+use super::trace::{TraceBuffer, TraceEntry};
+
+/// Register width of the hart
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum XLen {
+    /// 32-bit registers (RV32I)
+    #[default]
+    Rv32,
+    /// 64-bit registers (RV64I, adds the *W ops, LD/SD and LWU)
+    Rv64,
+}
+
+impl XLen {
+    pub fn bits(self) -> u32 {
+        match self {
+            XLen::Rv32 => 32,
+            XLen::Rv64 => 64,
+        }
+    }
+
+    /// Cut a result down to register width
+    pub fn truncate(self, value: u64) -> u64 {
+        match self {
+            XLen::Rv32 => value as u32 as u64,
+            XLen::Rv64 => value,
+        }
+    }
+
+    /// Interpret a register value as signed
+    pub fn signed(self, value: u64) -> i64 {
+        match self {
+            XLen::Rv32 => value as u32 as i32 as i64,
+            XLen::Rv64 => value as i64,
+        }
+    }
+
+    /// Mask applied to register-sourced shift amounts
+    fn shamt_mask(self) -> u32 {
+        self.bits() - 1
+    }
+}
+
+/// Errors raised while executing RISC-V code
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum RiscvError {
+    #[error("illegal instruction {word:#010x} at pc {pc:#x}")]
+    IllegalInstruction { pc: u64, word: u32 },
+    #[error("{len}-byte access at {addr:#x} is outside RAM")]
+    MemoryFault { addr: u64, len: usize },
+    #[error("misaligned instruction fetch at pc {pc:#x}")]
+    MisalignedFetch { pc: u64 },
+    #[error("hart is halted")]
+    Halted,
+    #[error("no program loaded")]
+    NoProgram,
+    #[error("invalid ELF: {0}")]
+    InvalidElf(&'static str),
+}
+
+/// Sign-extend the low 32 bits of a value (the *W instruction result rule)
+fn sext32(value: u64) -> u64 {
+    value as u32 as i32 as i64 as u64
+}
+
+/// Memory store made by one instruction
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MemoryWrite {
+    pub addr: u64,
+    /// Bytes stored (1, 2, 4 or 8)
+    pub len: usize,
+    /// Stored value, zero-extended
+    pub value: u64,
+}
+
+/// What a single `RiscvCore::step` executed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StepResult {
+    /// Address of the executed instruction
+    pub pc: u64,
+    /// Raw instruction word
+    pub word: u32,
+    /// Lowercase mnemonic, e.g. `"addi"`
+    pub mnemonic: Cow<'static, str>,
+    /// Register written and its new value (never x0)
+    pub reg_write: Option<(usize, u64)>,
+    pub mem_write: Option<MemoryWrite>,
+    /// PC of the next instruction
+    pub next_pc: u64,
+}
+
+/// Single RISC-V hart with flat, zero-based RAM
+///
+/// `ECALL` and `EBREAK` halt the hart with the PC left on the instruction.
+pub struct RiscvCore {
+    xlen: XLen,
+    i64_strategy: I64Strategy,
+    regs: [u64; 32],
+    pc: u64,
+    memory: Vec<u8>,
+    halted: bool,
+    instret: u64,
+    /// Retired instructions, recorded only while tracing is enabled
+    trace: Option<TraceBuffer>,
+}
+
+impl RiscvCore {
+    pub fn new(xlen: XLen, memory_size: usize) -> Self {
+        Self {
+            xlen,
+            i64_strategy: I64Strategy::Native,
+            regs: [0; 32],
+            pc: 0,
+            memory: vec![0; memory_size],
+            halted: false,
+            instret: 0,
+            trace: None,
+        }
+    }
+
+    /// Route 64-bit add/sub/shift through the u32-pair emulation helpers,
+    /// matching what the GPU path does without native i64
+    pub fn with_i64_strategy(mut self, strategy: I64Strategy) -> Self {
+        self.i64_strategy = strategy;
+        self
+    }
+
+    pub fn xlen(&self) -> XLen {
+        self.xlen
+    }
+
+    pub fn pc(&self) -> u64 {
+        self.pc
+    }
+
+    pub fn set_pc(&mut self, pc: u64) {
+        self.pc = self.xlen.truncate(pc);
+    }
+
+    pub fn reg(&self, index: usize) -> u64 {
+        self.regs[index]
+    }
+
+    /// Write a register; writes to x0 are ignored
+    pub fn set_reg(&mut self, index: usize, value: u64) {
+        if index != 0 {
+            self.regs[index] = self.xlen.truncate(value);
+        }
+    }
+
+    pub fn registers(&self) -> &[u64; 32] {
+        &self.regs
+    }
+
+    pub fn is_halted(&self) -> bool {
+        self.halted
+    }
+
+    /// Clear the halted flag so execution continues past an `EBREAK`
+    pub fn resume(&mut self) {
+        if self.halted {
+            self.halted = false;
+            self.pc = self.xlen.truncate(self.pc.wrapping_add(4));
+        }
+    }
+
+    /// Instructions retired since creation
+    pub fn instret(&self) -> u64 {
+        self.instret
+    }
+
+    /// Record the last `capacity` retired instructions
+    ///
+    /// Replaces any trace already being recorded.
+    pub fn enable_trace(&mut self, capacity: usize) {
+        self.trace = Some(TraceBuffer::new(capacity));
+    }
+
+    pub fn disable_trace(&mut self) {
+        self.trace = None;
+    }
+
+    pub fn trace(&self) -> Option<&TraceBuffer> {
+        self.trace.as_ref()
+    }
+
+    /// Drain the recorded trace, oldest first; empty when tracing is off
+    pub fn take_trace(&mut self) -> Vec<TraceEntry> {
+        self.trace
+            .as_mut()
+            .map(TraceBuffer::take)
+            .unwrap_or_default()
+    }
+
+    pub fn memory(&self) -> &[u8] {
+        &self.memory
+    }
+
+    /// Copy bytes into RAM at `offset`
+    pub fn load(&mut self, offset: u64, data: &[u8]) -> Result<(), RiscvError> {
+        self.slice_mut(offset, data.len())?.copy_from_slice(data);
+        Ok(())
+    }
+
+    /// `len` bytes of RAM starting at `addr`
+    pub fn read_memory(&self, addr: u64, len: usize) -> Result<&[u8], RiscvError> {
+        let fault = RiscvError::MemoryFault { addr, len };
+        let start = usize::try_from(addr).map_err(|_| fault.clone())?;
+        start
+            .checked_add(len)
+            .and_then(|end| self.memory.get(start..end))
+            .ok_or(fault)
+    }
+
+    /// Execute up to `budget` instructions, stopping early on halt
+    ///
+    /// Returns the number of instructions retired, including the one that
+    /// halted the hart.
+    pub fn run(&mut self, budget: u32) -> Result<u32, RiscvError> {
+        let mut executed = 0;
+        while executed < budget && !self.halted {
+            self.step()?;
+            executed += 1;
+        }
+        Ok(executed)
+    }
+
+    /// Execute one instruction and report what it did
+    pub fn step(&mut self) -> Result<StepResult, RiscvError> {
+        let step = self.execute()?;
+        if let Some(trace) = self.trace.as_mut() {
+            trace.push(TraceEntry::new(&step, self.xlen));
+        }
+        Ok(step)
+    }
+
+    fn execute(&mut self) -> Result<StepResult, RiscvError> {
+        if self.halted {
+            return Err(RiscvError::Halted);
+        }
+        if !self.pc.is_multiple_of(4) {
+            return Err(RiscvError::MisalignedFetch { pc: self.pc });
+        }
+        let pc = self.pc;
+        let word = self.read(pc, 4)? as u32;
+        let illegal = RiscvError::IllegalInstruction { pc, word };
+
+        let opcode = word & 0x7f;
+        let rd = ((word >> 7) & 0x1f) as usize;
+        let funct3 = (word >> 12) & 0x7;
+        let rs1 = self.regs[((word >> 15) & 0x1f) as usize];
+        let rs2 = self.regs[((word >> 20) & 0x1f) as usize];
+        let funct7 = word >> 25;
+        let imm_i = ((word as i32) >> 20) as i64 as u64;
+        let rv64 = self.xlen == XLen::Rv64;
+
+        let mut next_pc = pc.wrapping_add(4);
+        let mut mem_write = None;
+        // (mnemonic, value for rd)
+        let (mnemonic, rd_value): (&'static str, Option<u64>) = match opcode {
+            0x37 => ("lui", Some((word & 0xffff_f000) as i32 as i64 as u64)),
+            0x17 => {
+                let offset = (word & 0xffff_f000) as i32 as i64 as u64;
+                ("auipc", Some(self.add(pc, offset)))
+            },
+            0x6f => {
+                let imm = ((word & 0x8000_0000) as i32 >> 11) as u32
+                    | (word & 0x000f_f000)
+                    | ((word >> 9) & 0x800)
+                    | ((word >> 20) & 0x7fe);
+                let link = next_pc;
+                next_pc = self.add(pc, imm as i32 as i64 as u64);
+                ("jal", Some(link))
+            },
+            0x67 if funct3 == 0 => {
+                let link = next_pc;
+                next_pc = self.add(rs1, imm_i) & !1;
+                ("jalr", Some(link))
+            },
+            0x63 => {
+                let (a, b) = (self.xlen.signed(rs1), self.xlen.signed(rs2));
+                let (mnemonic, taken) = match funct3 {
+                    0 => ("beq", rs1 == rs2),
+                    1 => ("bne", rs1 != rs2),
+                    4 => ("blt", a < b),
+                    5 => ("bge", a >= b),
+                    6 => ("bltu", rs1 < rs2),
+                    7 => ("bgeu", rs1 >= rs2),
+                    _ => return Err(illegal),
+                };
+                if taken {
+                    let imm = ((word & 0x8000_0000) as i32 >> 19) as u32
+                        | ((word << 4) & 0x800)
+                        | ((word >> 20) & 0x7e0)
+                        | ((word >> 7) & 0x1e);
+                    next_pc = self.add(pc, imm as i32 as i64 as u64);
+                }
+                (mnemonic, None)
+            },
+            0x03 => {
+                let addr = self.xlen.truncate(self.add(rs1, imm_i));
+                let (mnemonic, value) = match funct3 {
+                    0 => ("lb", self.read(addr, 1)? as i8 as i64 as u64),
+                    1 => ("lh", self.read(addr, 2)? as i16 as i64 as u64),
+                    2 => ("lw", self.read(addr, 4)? as i32 as i64 as u64),
+                    3 if rv64 => ("ld", self.read(addr, 8)?),
+                    4 => ("lbu", self.read(addr, 1)?),
+                    5 => ("lhu", self.read(addr, 2)?),
+                    6 if rv64 => ("lwu", self.read(addr, 4)?),
+                    _ => return Err(illegal),
+                };
+                (mnemonic, Some(value))
+            },
+            0x23 => {
+                let imm = (((word & 0xfe00_0000) as i32 >> 20) as u32 | ((word >> 7) & 0x1f)) as i32
+                    as i64 as u64;
+                let addr = self.xlen.truncate(self.add(rs1, imm));
+                let (mnemonic, len) = match funct3 {
+                    0 => ("sb", 1),
+                    1 => ("sh", 2),
+                    2 => ("sw", 4),
+                    3 if rv64 => ("sd", 8),
+                    _ => return Err(illegal),
+                };
+                self.write(addr, len, rs2)?;
+                let mask = u64::MAX >> (64 - 8 * len);
+                mem_write = Some(MemoryWrite {
+                    addr,
+                    len,
+                    value: rs2 & mask,
+                });
+                (mnemonic, None)
+            },
+            0x13 => {
+                let shamt = (word >> 20) & 0x3f;
+                let (mnemonic, value) = match funct3 {
+                    0 => ("addi", self.add(rs1, imm_i)),
+                    2 => ("slti", (self.xlen.signed(rs1) < imm_i as i64) as u64),
+                    3 => ("sltiu", (rs1 < self.xlen.truncate(imm_i)) as u64),
+                    4 => ("xori", rs1 ^ imm_i),
+                    6 => ("ori", rs1 | imm_i),
+                    7 => ("andi", rs1 & imm_i),
+                    1 | 5 if shamt >= self.xlen.bits() => return Err(illegal),
+                    1 if word >> 26 == 0 => ("slli", self.shl(rs1, shamt)),
+                    5 if word >> 26 == 0 => ("srli", rs1 >> shamt),
+                    5 if word >> 26 == 0x10 => ("srai", self.sra(rs1, shamt)),
+                    _ => return Err(illegal),
+                };
+                (mnemonic, Some(value))
+            },
+            0x33 => {
+                let shamt = (rs2 as u32) & self.xlen.shamt_mask();
+                let (mnemonic, value) = match (funct7, funct3) {
+                    (0x00, 0) => ("add", self.add(rs1, rs2)),
+                    (0x20, 0) => ("sub", self.sub(rs1, rs2)),
+                    (0x00, 1) => ("sll", self.shl(rs1, shamt)),
+                    (0x00, 2) => (
+                        "slt",
+                        (self.xlen.signed(rs1) < self.xlen.signed(rs2)) as u64,
+                    ),
+                    (0x00, 3) => ("sltu", (rs1 < rs2) as u64),
+                    (0x00, 4) => ("xor", rs1 ^ rs2),
+                    (0x00, 5) => ("srl", rs1 >> shamt),
+                    (0x20, 5) => ("sra", self.sra(rs1, shamt)),
+                    (0x00, 6) => ("or", rs1 | rs2),
+                    (0x00, 7) => ("and", rs1 & rs2),
+                    _ => return Err(illegal),
+                };
+                (mnemonic, Some(value))
+            },
+            // OP-IMM-32 (RV64 only)
+            0x1b if rv64 => {
+                let shamt = (word >> 20) & 0x1f;
+                let (mnemonic, value) = match (funct7, funct3) {
+                    (_, 0) => ("addiw", self.add(rs1, imm_i)),
+                    (0x00, 1) => ("slliw", (rs1 as u32).wrapping_shl(shamt) as u64),
+                    (0x00, 5) => ("srliw", ((rs1 as u32) >> shamt) as u64),
+                    (0x20, 5) => ("sraiw", ((rs1 as u32 as i32) >> shamt) as u32 as u64),
+                    _ => return Err(illegal),
+                };
+                (mnemonic, Some(sext32(value)))
+            },
+            // OP-32 (RV64 only)
+            0x3b if rv64 => {
+                let shamt = (rs2 as u32) & 0x1f;
+                let (mnemonic, value) = match (funct7, funct3) {
+                    (0x00, 0) => ("addw", self.add(rs1, rs2)),
+                    (0x20, 0) => ("subw", self.sub(rs1, rs2)),
+                    (0x00, 1) => ("sllw", (rs1 as u32).wrapping_shl(shamt) as u64),
+                    (0x00, 5) => ("srlw", ((rs1 as u32) >> shamt) as u64),
+                    (0x20, 5) => ("sraw", ((rs1 as u32 as i32) >> shamt) as u32 as u64),
+                    _ => return Err(illegal),
+                };
+                (mnemonic, Some(sext32(value)))
+            },
+            // FENCE: single hart, nothing to order
+            0x0f => ("fence", None),
+            0x73 if word == 0x0000_0073 || word == 0x0010_0073 => {
+                self.halted = true;
+                next_pc = pc;
+                let mnemonic = if word == 0x0000_0073 {
+                    "ecall"
+                } else {
+                    "ebreak"
+                };
+                (mnemonic, None)
+            },
+            _ => return Err(illegal),
+        };
+
+        let mut reg_write = None;
+        if let Some(value) = rd_value {
+            if rd != 0 {
+                self.set_reg(rd, value);
+                reg_write = Some((rd, self.regs[rd]));
+            }
+        }
+        self.pc = self.xlen.truncate(next_pc);
+        self.instret += 1;
+
+        Ok(StepResult {
+            pc,
+            word,
+            mnemonic: Cow::Borrowed(mnemonic),
+            reg_write,
+            mem_write,
+            next_pc: self.pc,
+        })
+    }
+
+    fn add(&self, a: u64, b: u64) -> u64 {
+        let sum = match self.i64_strategy {
+            I64Strategy::Native => a.wrapping_add(b),
+            I64Strategy::Emulate => emulated_i64_add(a as i64, b as i64) as u64,
+        };
+        self.xlen.truncate(sum)
+    }
+
+    fn sub(&self, a: u64, b: u64) -> u64 {
+        let difference = match self.i64_strategy {
+            I64Strategy::Native => a.wrapping_sub(b),
+            I64Strategy::Emulate => emulated_i64_sub(a as i64, b as i64) as u64,
+        };
+        self.xlen.truncate(difference)
+    }
+
+    fn shl(&self, value: u64, shamt: u32) -> u64 {
+        let shifted = match self.i64_strategy {
+            I64Strategy::Native => value << shamt,
+            I64Strategy::Emulate => emulated_i64_shl(value as i64, shamt) as u64,
+        };
+        self.xlen.truncate(shifted)
+    }
+
+    /// Arithmetic right shift at register width
+    fn sra(&self, value: u64, shamt: u32) -> u64 {
+        let value = self.xlen.signed(value);
+        let shifted = match self.i64_strategy {
+            I64Strategy::Native => value >> shamt,
+            I64Strategy::Emulate => emulated_i64_shr(value, shamt),
+        };
+        self.xlen.truncate(shifted as u64)
+    }
+
+    fn slice_mut(&mut self, addr: u64, len: usize) -> Result<&mut [u8], RiscvError> {
+        let fault = RiscvError::MemoryFault { addr, len };
+        let start = usize::try_from(addr).map_err(|_| fault.clone())?;
+        let end = start.checked_add(len).ok_or(fault.clone())?;
+        self.memory.get_mut(start..end).ok_or(fault)
+    }
+
+    /// Little-endian load of `len` (1, 2, 4 or 8) bytes, zero-extended
+    fn read(&self, addr: u64, len: usize) -> Result<u64, RiscvError> {
+        let bytes = self.read_memory(addr, len)?;
+        let mut buf = [0u8; 8];
+        buf[..len].copy_from_slice(bytes);
+        Ok(u64::from_le_bytes(buf))
+    }
+
+    fn write(&mut self, addr: u64, len: usize, value: u64) -> Result<(), RiscvError> {
+        self.slice_mut(addr, len)?
+            .copy_from_slice(&value.to_le_bytes()[..len]);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn r_type(funct7: u32, rs2: u32, rs1: u32, funct3: u32, rd: u32, opcode: u32) -> u32 {
+        (funct7 << 25) | (rs2 << 20) | (rs1 << 15) | (funct3 << 12) | (rd << 7) | opcode
+    }
+
+    fn i_type(imm: i32, rs1: u32, funct3: u32, rd: u32, opcode: u32) -> u32 {
+        (((imm as u32) & 0xfff) << 20) | (rs1 << 15) | (funct3 << 12) | (rd << 7) | opcode
+    }
+
+    fn s_type(imm: i32, rs2: u32, rs1: u32, funct3: u32) -> u32 {
+        let imm = imm as u32;
+        ((imm >> 5) << 25) | (rs2 << 20) | (rs1 << 15) | (funct3 << 12) | ((imm & 0x1f) << 7) | 0x23
+    }
+
+    const EBREAK: u32 = 0x0010_0073;
+
+    fn core_with(xlen: XLen, strategy: I64Strategy, program: &[u32]) -> RiscvCore {
+        let mut core = RiscvCore::new(xlen, 0x1000).with_i64_strategy(strategy);
+        let bytes: Vec<u8> = program.iter().flat_map(|w| w.to_le_bytes()).collect();
+        core.load(0, &bytes).unwrap();
+        core
+    }
+
+    /// RV64I add/shift compliance program
+    fn rv64_add_shift_program() -> Vec<u32> {
+        vec![
+            i_type(1, 0, 0, 1, 0x13),          // addi  x1, x0, 1
+            i_type(40, 1, 1, 2, 0x13),         // slli  x2, x1, 40
+            i_type(-1, 0, 0, 3, 0x13),         // addi  x3, x0, -1
+            r_type(0x00, 3, 2, 0, 4, 0x33),    // add   x4, x2, x3
+            i_type(0x400 | 63, 3, 5, 5, 0x13), // srai  x5, x3, 63
+            i_type(32, 3, 5, 6, 0x13),         // srli  x6, x3, 32
+            i_type(0, 6, 0, 7, 0x1b),          // addiw x7, x6, 0
+            r_type(0x00, 6, 1, 0, 8, 0x3b),    // addw  x8, x1, x6
+            s_type(0x100, 4, 0, 3),            // sd    x4, 0x100(x0)
+            i_type(0x100, 0, 3, 9, 0x03),      // ld    x9, 0x100(x0)
+            r_type(0x20, 1, 0, 0, 10, 0x33),   // sub   x10, x0, x1
+            r_type(0x00, 1, 10, 5, 11, 0x33),  // srl   x11, x10, x1
+            EBREAK,
+        ]
+    }
+
+    #[test]
+    fn test_rv64_add_shift_compliance() {
+        for strategy in [I64Strategy::Native, I64Strategy::Emulate] {
+            let mut core = core_with(XLen::Rv64, strategy, &rv64_add_shift_program());
+            assert_eq!(core.run(100).unwrap(), 13);
+            assert!(core.is_halted());
+            assert_eq!(core.pc(), 12 * 4);
+
+            assert_eq!(core.reg(1), 1);
+            assert_eq!(core.reg(2), 1 << 40);
+            assert_eq!(core.reg(3), u64::MAX);
+            assert_eq!(core.reg(4), (1 << 40) - 1);
+            assert_eq!(core.reg(5), u64::MAX);
+            assert_eq!(core.reg(6), 0xffff_ffff);
+            // *W results are sign-extended from bit 31
+            assert_eq!(core.reg(7), u64::MAX);
+            assert_eq!(core.reg(8), 0);
+            assert_eq!(core.reg(9), (1 << 40) - 1);
+            assert_eq!(core.reg(10), u64::MAX);
+            assert_eq!(core.reg(11), u64::MAX >> 1);
+            assert_eq!(core.reg(0), 0);
+        }
+    }
+
+    #[test]
+    fn test_step_reports_each_instruction() {
+        let program = [
+            i_type(5, 0, 0, 1, 0x13),       // addi x1, x0, 5
+            s_type(0x80, 1, 0, 2),          // sw   x1, 0x80(x0)
+            r_type(0x00, 1, 1, 0, 2, 0x33), // add  x2, x1, x1
+        ];
+        let mut core = core_with(XLen::Rv32, I64Strategy::Native, &program);
+
+        let addi = core.step().unwrap();
+        assert_eq!(
+            (addi.pc, addi.word, &*addi.mnemonic),
+            (0, program[0], "addi")
+        );
+        assert_eq!(addi.reg_write, Some((1, 5)));
+        assert_eq!(addi.mem_write, None);
+
+        let sw = core.step().unwrap();
+        assert_eq!((sw.pc, &*sw.mnemonic, sw.reg_write), (4, "sw", None));
+        assert_eq!(
+            sw.mem_write,
+            Some(MemoryWrite {
+                addr: 0x80,
+                len: 4,
+                value: 5
+            })
+        );
+
+        let add = core.step().unwrap();
+        assert_eq!((add.pc, &*add.mnemonic), (8, "add"));
+        assert_eq!(add.reg_write, Some((2, 10)));
+        assert_eq!(add.next_pc, 12);
+    }
+
+    #[test]
+    fn test_stepping_matches_run_budget() {
+        let program = rv64_add_shift_program();
+        let mut stepped = core_with(XLen::Rv64, I64Strategy::Native, &program);
+        let mut ran = core_with(XLen::Rv64, I64Strategy::Native, &program);
+
+        for budget in [1, 4, 7] {
+            for _ in 0..budget {
+                stepped.step().unwrap();
+            }
+            assert_eq!(ran.run(budget).unwrap(), budget);
+            assert_eq!(stepped.registers(), ran.registers());
+            assert_eq!(stepped.pc(), ran.pc());
+            assert_eq!(stepped.memory(), ran.memory());
+        }
+    }
+
+    #[test]
+    fn test_rv32_wraps_and_rejects_rv64_ops() {
+        let program = [
+            i_type(-1, 0, 0, 1, 0x13),        // addi x1, x0, -1
+            i_type(1, 1, 0, 2, 0x13),         // addi x2, x1, 1
+            i_type(0x400 | 4, 1, 5, 3, 0x13), // srai x3, x1, 4
+            i_type(4, 1, 5, 4, 0x13),         // srli x4, x1, 4
+            EBREAK,
+        ];
+        let mut core = core_with(XLen::Rv32, I64Strategy::Native, &program);
+        core.run(100).unwrap();
+        assert_eq!(core.reg(1), 0xffff_ffff);
+        assert_eq!(core.reg(2), 0);
+        assert_eq!(core.reg(3), 0xffff_ffff);
+        assert_eq!(core.reg(4), 0x0fff_ffff);
+
+        for word in [
+            i_type(40, 1, 1, 2, 0x13), // slli x2, x1, 40
+            i_type(0, 6, 0, 7, 0x1b),  // addiw x7, x6, 0
+            i_type(0, 0, 3, 9, 0x03),  // ld x9, 0(x0)
+        ] {
+            let mut core = core_with(XLen::Rv32, I64Strategy::Native, &[word]);
+            assert_eq!(
+                core.step(),
+                Err(RiscvError::IllegalInstruction { pc: 0, word })
+            );
+        }
+    }
+}
@@ -0,0 +1,281 @@
+//! RISC-V Disassembler
+//!
+//! Turns single 32-bit instruction words into assembler text using ABI
+//! register names, e.g. `addi a0, a1, 4` or `sw a0, 8(sp)`. Covers RV32IM
+//! and RV64IM, the A extension, Zicsr and the privileged returns. Branch and
+//! jump targets are printed as offsets from the instruction, since a word
+//! alone carries no PC. Anything else comes out as `.word 0x...`.
+
+use super::cpu::XLen;
+
+/// ABI names of x0-x31
+pub const REGISTER_NAMES: [&str; 32] = [
+    "zero", "ra", "sp", "gp", "tp", "t0", "t1", "t2", "s0", "s1", "a0", "a1", "a2", "a3", "a4",
+    "a5", "a6", "a7", "s2", "s3", "s4", "s5", "s6", "s7", "s8", "s9", "s10", "s11", "t3", "t4",
+    "t5", "t6",
+];
+
+/// ABI name of register `index` (0-31)
+pub fn register_name(index: usize) -> &'static str {
+    REGISTER_NAMES[index & 0x1f]
+}
+
+/// Decode one instruction word
+pub fn disassemble(word: u32, xlen: XLen) -> String {
+    decode(word, xlen).unwrap_or_else(|| format!(".word {:#010x}", word))
+}
+
+fn decode(word: u32, xlen: XLen) -> Option<String> {
+    let rv64 = xlen == XLen::Rv64;
+    let opcode = word & 0x7f;
+    let rd = register_name(((word >> 7) & 0x1f) as usize);
+    let rs1 = register_name(((word >> 15) & 0x1f) as usize);
+    let rs2 = register_name(((word >> 20) & 0x1f) as usize);
+    let funct3 = (word >> 12) & 0x7;
+    let funct7 = word >> 25;
+    let imm_i = (word as i32) >> 20;
+    let imm_s = ((word as i32) >> 25 << 5) | ((word >> 7) & 0x1f) as i32;
+    let imm_b = ((word as i32) >> 31 << 12)
+        | (((word >> 7) & 1) << 11) as i32
+        | (((word >> 25) & 0x3f) << 5) as i32
+        | (((word >> 8) & 0xf) << 1) as i32;
+    let imm_j = ((word as i32) >> 31 << 20)
+        | (word & 0x000f_f000) as i32
+        | (((word >> 20) & 1) << 11) as i32
+        | (((word >> 21) & 0x3ff) << 1) as i32;
+
+    let text = match opcode {
+        0x37 => format!("lui {}, {:#x}", rd, word >> 12),
+        0x17 => format!("auipc {}, {:#x}", rd, word >> 12),
+        0x6f => format!("jal {}, {}", rd, imm_j),
+        0x67 if funct3 == 0 => format!("jalr {}, {}({})", rd, imm_i, rs1),
+        0x63 => {
+            let mnemonic = match funct3 {
+                0 => "beq",
+                1 => "bne",
+                4 => "blt",
+                5 => "bge",
+                6 => "bltu",
+                7 => "bgeu",
+                _ => return None,
+            };
+            format!("{} {}, {}, {}", mnemonic, rs1, rs2, imm_b)
+        },
+        0x03 => {
+            let mnemonic = match funct3 {
+                0 => "lb",
+                1 => "lh",
+                2 => "lw",
+                3 if rv64 => "ld",
+                4 => "lbu",
+                5 => "lhu",
+                6 if rv64 => "lwu",
+                _ => return None,
+            };
+            format!("{} {}, {}({})", mnemonic, rd, imm_i, rs1)
+        },
+        0x23 => {
+            let mnemonic = match funct3 {
+                0 => "sb",
+                1 => "sh",
+                2 => "sw",
+                3 if rv64 => "sd",
+                _ => return None,
+            };
+            format!("{} {}, {}({})", mnemonic, rs2, imm_s, rs1)
+        },
+        0x13 => {
+            // Shift amounts take 6 bits on rv64, leaving funct6 above them
+            let (shamt, funct) = if rv64 {
+                ((word >> 20) & 0x3f, word >> 26 << 1)
+            } else {
+                ((word >> 20) & 0x1f, funct7)
+            };
+            let mnemonic = match (funct3, funct) {
+                (0, _) => "addi",
+                (2, _) => "slti",
+                (3, _) => "sltiu",
+                (4, _) => "xori",
+                (6, _) => "ori",
+                (7, _) => "andi",
+                (1, 0x00) => return Some(format!("slli {}, {}, {}", rd, rs1, shamt)),
+                (5, 0x00) => return Some(format!("srli {}, {}, {}", rd, rs1, shamt)),
+                (5, 0x20) => return Some(format!("srai {}, {}, {}", rd, rs1, shamt)),
+                _ => return None,
+            };
+            format!("{} {}, {}, {}", mnemonic, rd, rs1, imm_i)
+        },
+        0x1b if rv64 => {
+            let shamt = (word >> 20) & 0x1f;
+            match (funct3, funct7) {
+                (0, _) => format!("addiw {}, {}, {}", rd, rs1, imm_i),
+                (1, 0x00) => format!("slliw {}, {}, {}", rd, rs1, shamt),
+                (5, 0x00) => format!("srliw {}, {}, {}", rd, rs1, shamt),
+                (5, 0x20) => format!("sraiw {}, {}, {}", rd, rs1, shamt),
+                _ => return None,
+            }
+        },
+        0x33 => {
+            let mnemonic = match (funct7, funct3) {
+                (0x00, 0) => "add",
+                (0x20, 0) => "sub",
+                (0x00, 1) => "sll",
+                (0x00, 2) => "slt",
+                (0x00, 3) => "sltu",
+                (0x00, 4) => "xor",
+                (0x00, 5) => "srl",
+                (0x20, 5) => "sra",
+                (0x00, 6) => "or",
+                (0x00, 7) => "and",
+                (0x01, 0) => "mul",
+                (0x01, 1) => "mulh",
+                (0x01, 2) => "mulhsu",
+                (0x01, 3) => "mulhu",
+                (0x01, 4) => "div",
+                (0x01, 5) => "divu",
+                (0x01, 6) => "rem",
+                (0x01, 7) => "remu",
+                _ => return None,
+            };
+            format!("{} {}, {}, {}", mnemonic, rd, rs1, rs2)
+        },
+        0x3b if rv64 => {
+            let mnemonic = match (funct7, funct3) {
+                (0x00, 0) => "addw",
+                (0x20, 0) => "subw",
+                (0x00, 1) => "sllw",
+                (0x00, 5) => "srlw",
+                (0x20, 5) => "sraw",
+                (0x01, 0) => "mulw",
+                (0x01, 4) => "divw",
+                (0x01, 5) => "divuw",
+                (0x01, 6) => "remw",
+                (0x01, 7) => "remuw",
+                _ => return None,
+            };
+            format!("{} {}, {}, {}", mnemonic, rd, rs1, rs2)
+        },
+        0x2f => {
+            let width = match funct3 {
+                2 => "w",
+                3 if rv64 => "d",
+                _ => return None,
+            };
+            let ordering = match (word >> 25) & 0x3 {
+                0 => "",
+                1 => ".rl",
+                2 => ".aq",
+                _ => ".aqrl",
+            };
+            let op = match word >> 27 {
+                0x02 if rs2 == "zero" => {
+                    return Some(format!("lr.{}{} {}, ({})", width, ordering, rd, rs1));
+                },
+                0x03 => "sc",
+                0x01 => "amoswap",
+                0x00 => "amoadd",
+                0x04 => "amoxor",
+                0x0c => "amoand",
+                0x08 => "amoor",
+                0x10 => "amomin",
+                0x14 => "amomax",
+                0x18 => "amominu",
+                0x1c => "amomaxu",
+                _ => return None,
+            };
+            format!("{}.{}{} {}, {}, ({})", op, width, ordering, rd, rs2, rs1)
+        },
+        0x0f => match funct3 {
+            0 => format!(
+                "fence {}, {}",
+                fence_set((word >> 24) & 0xf),
+                fence_set((word >> 20) & 0xf)
+            ),
+            1 => "fence.i".to_string(),
+            _ => return None,
+        },
+        0x73 => {
+            let csr = word >> 20;
+            let uimm = (word >> 15) & 0x1f;
+            match funct3 {
+                0 => match word {
+                    0x0000_0073 => "ecall",
+                    0x0010_0073 => "ebreak",
+                    0x1020_0073 => "sret",
+                    0x3020_0073 => "mret",
+                    0x1050_0073 => "wfi",
+                    _ => return None,
+                }
+                .to_string(),
+                1 => format!("csrrw {}, {:#x}, {}", rd, csr, rs1),
+                2 => format!("csrrs {}, {:#x}, {}", rd, csr, rs1),
+                3 => format!("csrrc {}, {:#x}, {}", rd, csr, rs1),
+                5 => format!("csrrwi {}, {:#x}, {}", rd, csr, uimm),
+                6 => format!("csrrsi {}, {:#x}, {}", rd, csr, uimm),
+                7 => format!("csrrci {}, {:#x}, {}", rd, csr, uimm),
+                _ => return None,
+            }
+        },
+        _ => return None,
+    };
+    Some(text)
+}
+
+/// `iorw`-style predecessor/successor set of a FENCE
+fn fence_set(bits: u32) -> String {
+    [(8, 'i'), (4, 'o'), (2, 'r'), (1, 'w')]
+        .iter()
+        .filter(|&&(bit, _)| bits & bit != 0)
+        .map(|&(_, name)| name)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_disassemble_table() {
+        let cases: &[(u32, XLen, &str)] = &[
+            // I-type
+            (0x0045_8513, XLen::Rv32, "addi a0, a1, 4"),
+            (0xffc4_2503, XLen::Rv32, "lw a0, -4(s0)"),
+            (0x4035_5513, XLen::Rv32, "srai a0, a0, 3"),
+            (0x0000_8067, XLen::Rv32, "jalr zero, 0(ra)"),
+            // S-type
+            (0x00a1_2423, XLen::Rv32, "sw a0, 8(sp)"),
+            // B-type
+            (0xfe00_9ee3, XLen::Rv32, "bne ra, zero, -4"),
+            // U-type
+            (0x1234_5537, XLen::Rv32, "lui a0, 0x12345"),
+            (0x0000_0097, XLen::Rv32, "auipc ra, 0x0"),
+            // J-type
+            (0x0080_00ef, XLen::Rv32, "jal ra, 8"),
+            // R-type, including M
+            (0x40b5_0533, XLen::Rv32, "sub a0, a0, a1"),
+            (0x02b5_0533, XLen::Rv32, "mul a0, a0, a1"),
+            (0x02b5_5533, XLen::Rv32, "divu a0, a0, a1"),
+            // A
+            (0x1005_a52f, XLen::Rv32, "lr.w a0, (a1)"),
+            (0x18c5_a52f, XLen::Rv32, "sc.w a0, a2, (a1)"),
+            (0x06c5_a52f, XLen::Rv32, "amoadd.w.aqrl a0, a2, (a1)"),
+            // System
+            (0x0000_0073, XLen::Rv32, "ecall"),
+            (0x0010_0073, XLen::Rv32, "ebreak"),
+            (0xc000_2573, XLen::Rv32, "csrrs a0, 0xc00, zero"),
+            (0x0ff0_000f, XLen::Rv32, "fence iorw, iorw"),
+            // rv64-only encodings
+            (0x0101_3503, XLen::Rv64, "ld a0, 16(sp)"),
+            (0x0101_3503, XLen::Rv32, ".word 0x01013503"),
+            (0x0285_1513, XLen::Rv64, "slli a0, a0, 40"),
+            (0x0285_1513, XLen::Rv32, ".word 0x02851513"),
+            (0x0015_051b, XLen::Rv64, "addiw a0, a0, 1"),
+            // Illegal
+            (0x0000_0000, XLen::Rv32, ".word 0x00000000"),
+            (0xffff_ffff, XLen::Rv64, ".word 0xffffffff"),
+        ];
+        for &(word, xlen, expected) in cases {
+            assert_eq!(disassemble(word, xlen), expected, "{:#010x}", word);
+        }
+    }
+}
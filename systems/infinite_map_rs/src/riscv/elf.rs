@@ -0,0 +1,345 @@
+//! RISC-V ELF Loader
+//!
+//! Parses static (`ET_EXEC`) little-endian RV32/RV64 executables so they can
+//! be loaded without stripping the headers and guessing the entry point.
+//! `PT_LOAD` segments are placed at their physical address, since VM RAM is
+//! flat and zero-based; the part of a segment beyond its file data (.bss)
+//! is zeroed.
+
+use super::cpu::{RiscvCore, RiscvError, XLen};
+use std::ops::Range;
+
+/// First four bytes of every ELF file
+pub const ELF_MAGIC: &[u8; 4] = b"\x7fELF";
+
+const ELFCLASS32: u8 = 1;
+const ELFCLASS64: u8 = 2;
+const ELFDATA2LSB: u8 = 1;
+const ET_EXEC: u16 = 2;
+const EM_RISCV: u16 = 243;
+const PT_LOAD: u32 = 1;
+
+/// `PT_LOAD` segment of an ELF file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ElfSegment<'a> {
+    /// Physical (load) address
+    pub addr: u64,
+    /// Bytes taken from the file
+    pub data: &'a [u8],
+    /// Size in memory; bytes past `data` are zero
+    pub mem_size: u64,
+}
+
+/// Parsed static RISC-V executable
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ElfImage<'a> {
+    pub xlen: XLen,
+    pub entry: u64,
+    pub segments: Vec<ElfSegment<'a>>,
+}
+
+/// What loading an ELF put where
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LoadedElf {
+    pub xlen: XLen,
+    /// `e_entry`, which the PC was set to
+    pub entry: u64,
+    /// RAM range of each loaded segment, .bss included
+    pub segments: Vec<Range<u64>>,
+}
+
+/// Little-endian field reader that fails on truncated input
+struct Fields<'a> {
+    bytes: &'a [u8],
+    wide: bool,
+}
+
+impl Fields<'_> {
+    fn uint(&self, offset: usize, len: usize) -> Result<u64, RiscvError> {
+        let field = offset
+            .checked_add(len)
+            .and_then(|end| self.bytes.get(offset..end))
+            .ok_or(RiscvError::InvalidElf("truncated header"))?;
+        let mut buf = [0u8; 8];
+        buf[..len].copy_from_slice(field);
+        Ok(u64::from_le_bytes(buf))
+    }
+
+    fn u16(&self, offset: usize) -> Result<u16, RiscvError> {
+        self.uint(offset, 2).map(|value| value as u16)
+    }
+
+    fn u32(&self, offset: usize) -> Result<u32, RiscvError> {
+        self.uint(offset, 4).map(|value| value as u32)
+    }
+
+    /// Address-sized field (4 bytes in ELF32, 8 in ELF64)
+    fn addr(&self, offset: usize) -> Result<u64, RiscvError> {
+        self.uint(offset, if self.wide { 8 } else { 4 })
+    }
+}
+
+fn to_usize(value: u64) -> Result<usize, RiscvError> {
+    usize::try_from(value).map_err(|_| RiscvError::InvalidElf("offset out of range"))
+}
+
+impl<'a> ElfImage<'a> {
+    pub fn parse(bytes: &'a [u8]) -> Result<Self, RiscvError> {
+        if !bytes.starts_with(ELF_MAGIC) {
+            return Err(RiscvError::InvalidElf("missing ELF magic"));
+        }
+        let xlen = match bytes.get(4) {
+            Some(&ELFCLASS32) => XLen::Rv32,
+            Some(&ELFCLASS64) => XLen::Rv64,
+            _ => return Err(RiscvError::InvalidElf("unknown ELF class")),
+        };
+        if bytes.get(5) != Some(&ELFDATA2LSB) {
+            return Err(RiscvError::InvalidElf("not little-endian"));
+        }
+
+        let wide = xlen == XLen::Rv64;
+        let header = Fields { bytes, wide };
+        if header.u16(16)? != ET_EXEC {
+            return Err(RiscvError::InvalidElf("not a static executable"));
+        }
+        if header.u16(18)? != EM_RISCV {
+            return Err(RiscvError::InvalidElf("not a RISC-V executable"));
+        }
+
+        // e_entry and e_phoff, then e_phentsize and e_phnum past the flags
+        let entry = header.addr(24)?;
+        let (phoff, phentsize, phnum) = if wide {
+            (header.addr(32)?, header.u16(54)?, header.u16(56)?)
+        } else {
+            (header.addr(28)?, header.u16(42)?, header.u16(44)?)
+        };
+
+        let mut segments = Vec::new();
+        for index in 0..phnum as usize {
+            let start = to_usize(phoff)?
+                .checked_add(index * phentsize as usize)
+                .ok_or(RiscvError::InvalidElf("offset out of range"))?;
+            let phdr = Fields {
+                bytes: bytes.get(start..).unwrap_or_default(),
+                wide,
+            };
+            if phdr.u32(0)? != PT_LOAD {
+                continue;
+            }
+            let (offset, addr, file_size, mem_size) = if wide {
+                (
+                    phdr.addr(8)?,
+                    phdr.addr(24)?,
+                    phdr.addr(32)?,
+                    phdr.addr(40)?,
+                )
+            } else {
+                (
+                    phdr.addr(4)?,
+                    phdr.addr(12)?,
+                    phdr.addr(16)?,
+                    phdr.addr(20)?,
+                )
+            };
+            if file_size > mem_size {
+                return Err(RiscvError::InvalidElf(
+                    "segment file size exceeds memory size",
+                ));
+            }
+            let data = to_usize(offset)
+                .ok()
+                .zip(to_usize(file_size).ok())
+                .and_then(|(offset, len)| bytes.get(offset..offset.checked_add(len)?))
+                .ok_or(RiscvError::InvalidElf("segment data past end of file"))?;
+            segments.push(ElfSegment {
+                addr,
+                data,
+                mem_size,
+            });
+        }
+
+        Ok(Self {
+            xlen,
+            entry,
+            segments,
+        })
+    }
+
+    /// Fails unless the entry instruction lies inside `ram_size` bytes of RAM
+    pub fn check_entry(&self, ram_size: u64) -> Result<(), RiscvError> {
+        match self.entry.checked_add(4) {
+            Some(end) if end <= ram_size => Ok(()),
+            _ => Err(RiscvError::MemoryFault {
+                addr: self.entry,
+                len: 4,
+            }),
+        }
+    }
+
+    pub fn loaded(&self) -> LoadedElf {
+        LoadedElf {
+            xlen: self.xlen,
+            entry: self.entry,
+            segments: self
+                .segments
+                .iter()
+                .map(|segment| segment.addr..segment.addr.saturating_add(segment.mem_size))
+                .collect(),
+        }
+    }
+}
+
+impl RiscvCore {
+    /// Load a static ELF whose class matches this hart and jump to its entry
+    pub fn load_elf(&mut self, bytes: &[u8]) -> Result<LoadedElf, RiscvError> {
+        let image = ElfImage::parse(bytes)?;
+        if image.xlen != self.xlen() {
+            return Err(RiscvError::InvalidElf("ELF class does not match the hart"));
+        }
+        image.check_entry(self.memory().len() as u64)?;
+        for segment in &image.segments {
+            let mut data = segment.data.to_vec();
+            data.resize(to_usize(segment.mem_size)?, 0);
+            self.load(segment.addr, &data)?;
+        }
+        self.set_pc(image.entry);
+        Ok(image.loaded())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EBREAK: u32 = 0x0010_0073;
+
+    /// Minimal executable with one `PT_LOAD` segment at `addr` holding
+    /// `code` followed by `bss` zero bytes
+    fn build_elf(xlen: XLen, addr: u64, code: &[u32], bss: u64) -> Vec<u8> {
+        let wide = xlen == XLen::Rv64;
+        let (ehsize, phentsize): (usize, usize) = if wide { (64, 56) } else { (52, 32) };
+        let text: Vec<u8> = code.iter().flat_map(|w| w.to_le_bytes()).collect();
+        let text_offset = (ehsize + phentsize) as u64;
+        let file_size = text.len() as u64;
+        let word = |value: u64| {
+            if wide {
+                value.to_le_bytes().to_vec()
+            } else {
+                (value as u32).to_le_bytes().to_vec()
+            }
+        };
+
+        let mut elf = ELF_MAGIC.to_vec();
+        elf.extend([if wide { ELFCLASS64 } else { ELFCLASS32 }, ELFDATA2LSB, 1]);
+        elf.resize(16, 0);
+        elf.extend(ET_EXEC.to_le_bytes());
+        elf.extend(EM_RISCV.to_le_bytes());
+        elf.extend(1u32.to_le_bytes()); // e_version
+        elf.extend(word(addr)); // e_entry
+        elf.extend(word(ehsize as u64)); // e_phoff
+        elf.extend(word(0)); // e_shoff
+        elf.extend(0u32.to_le_bytes()); // e_flags
+        elf.extend((ehsize as u16).to_le_bytes());
+        elf.extend((phentsize as u16).to_le_bytes());
+        elf.extend(1u16.to_le_bytes()); // e_phnum
+        elf.extend([0u8; 6]); // e_shentsize, e_shnum, e_shstrndx
+        assert_eq!(elf.len(), ehsize);
+
+        elf.extend(PT_LOAD.to_le_bytes());
+        if wide {
+            elf.extend(5u32.to_le_bytes()); // p_flags (R+X)
+        }
+        elf.extend(word(text_offset));
+        elf.extend(word(addr)); // p_vaddr
+        elf.extend(word(addr)); // p_paddr
+        elf.extend(word(file_size));
+        elf.extend(word(file_size + bss));
+        if !wide {
+            elf.extend(5u32.to_le_bytes());
+        }
+        elf.extend(word(4)); // p_align
+        assert_eq!(elf.len() as u64, text_offset);
+
+        elf.extend(text);
+        elf
+    }
+
+    /// addi x1, x0, 42; sw/sd x1, 0x200(x0); ebreak
+    fn store_program(xlen: XLen) -> [u32; 3] {
+        let funct3 = if xlen == XLen::Rv64 { 3 } else { 2 };
+        [0x02a0_0093, 0x2010_0023 | (funct3 << 12), EBREAK]
+    }
+
+    #[test]
+    fn test_load_elf_runs_program() {
+        for xlen in [XLen::Rv32, XLen::Rv64] {
+            let elf = build_elf(xlen, 0x100, &store_program(xlen), 0x20);
+            let mut core = RiscvCore::new(xlen, 0x1000);
+            // .bss must come out zeroed
+            core.load(0x10c, &[0xff; 0x20]).unwrap();
+
+            let loaded = core.load_elf(&elf).unwrap();
+            assert_eq!(loaded.xlen, xlen);
+            assert_eq!(loaded.entry, 0x100);
+            assert_eq!(loaded.segments, vec![0x100..0x12c]);
+            assert_eq!(core.pc(), 0x100);
+            assert!(core
+                .read_memory(0x10c, 0x20)
+                .unwrap()
+                .iter()
+                .all(|&b| b == 0));
+
+            core.run(16).unwrap();
+            assert!(core.is_halted());
+            assert_eq!(core.read_memory(0x200, 4).unwrap(), [42, 0, 0, 0]);
+        }
+    }
+
+    #[test]
+    fn test_rejects_invalid_elf() {
+        let elf = build_elf(XLen::Rv32, 0x100, &[EBREAK], 0);
+        assert_eq!(ElfImage::parse(&elf).unwrap().xlen, XLen::Rv32);
+
+        assert!(ElfImage::parse(b"\x7fELF").is_err());
+        assert!(ElfImage::parse(&elf[..40]).is_err());
+
+        let mut not_elf = elf.clone();
+        not_elf[0] = 0;
+        assert!(ElfImage::parse(&not_elf).is_err());
+
+        let mut x86 = elf.clone();
+        x86[18] = 62; // EM_X86_64
+        assert!(ElfImage::parse(&x86).is_err());
+
+        let mut truncated_segment = elf.clone();
+        truncated_segment.pop();
+        assert!(ElfImage::parse(&truncated_segment).is_err());
+
+        // A 32-bit ELF can't be loaded into a 64-bit hart
+        assert!(RiscvCore::new(XLen::Rv64, 0x1000).load_elf(&elf).is_err());
+        // Segments must fit in RAM
+        assert!(RiscvCore::new(XLen::Rv32, 0x80).load_elf(&elf).is_err());
+    }
+
+    #[test]
+    fn test_rejects_entry_outside_ram() {
+        let mut elf = build_elf(XLen::Rv32, 0x100, &[EBREAK], 0);
+        elf[24..28].copy_from_slice(&0x2000u32.to_le_bytes());
+        let mut core = RiscvCore::new(XLen::Rv32, 0x1000);
+        assert_eq!(
+            core.load_elf(&elf),
+            Err(RiscvError::MemoryFault {
+                addr: 0x2000,
+                len: 4
+            })
+        );
+        assert_eq!(core.pc(), 0);
+
+        // An entry past 4 GiB must not wrap into RAM
+        let mut elf = build_elf(XLen::Rv64, 0x100, &[EBREAK], 0);
+        elf[24..32].copy_from_slice(&0x1_0000_0100u64.to_le_bytes());
+        let image = ElfImage::parse(&elf).unwrap();
+        assert!(image.check_entry(0x1000).is_err());
+        assert!(image.check_entry(0x1_0000_0104).is_ok());
+    }
+}
@@ -0,0 +1,637 @@
+//! GDB Remote Serial Protocol Stub
+//!
+//! Lets a real `gdb` attach to a RISC-V hart over TCP:
+//!
+//! ```text
+//! (gdb) set architecture riscv:rv32
+//! (gdb) target remote localhost:1234
+//! ```
+//!
+//! Supported packets: `?`, `g`/`G` registers, `m`/`M` memory, `c`/`s`
+//! continue and step, `Z0`/`z0` software breakpoints, `D` detach and `k`
+//! kill. Anything else gets the empty "unsupported" reply.
+//!
+//! `c` runs the target in batches through [`GdbTarget::run`], polling for
+//! a gdb interrupt between batches. The default batch single-steps and
+//! checks breakpoints before every instruction; targets with a faster way
+//! to run override it. When the session ends, however it ends, the stub
+//! calls [`GdbTarget::detach`] so the target can run freely again.
+
+use super::cpu::{RiscvCore, RiscvError, StepResult, XLen};
+use std::collections::BTreeSet;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+
+/// Instructions per `GdbTarget::run` batch on `c`, between checks for a
+/// gdb interrupt (Ctrl-C)
+const INTERRUPT_POLL_INTERVAL: u32 = 4096;
+
+/// POSIX signal numbers used in stop replies
+const SIGINT: u8 = 2;
+const SIGILL: u8 = 4;
+const SIGBUS: u8 = 7;
+const SIGTRAP: u8 = 5;
+const SIGSEGV: u8 = 11;
+
+/// A hart gdb can inspect and drive
+///
+/// Registers use gdb's RISC-V numbering: x0-x31 followed by the pc.
+pub trait GdbTarget {
+    fn xlen(&self) -> XLen;
+
+    /// General purpose registers and the pc
+    fn read_registers(&mut self) -> Result<([u64; 32], u64), RiscvError>;
+
+    fn write_registers(&mut self, regs: &[u64; 32], pc: u64) -> Result<(), RiscvError>;
+
+    fn read_memory(&mut self, addr: u64, len: usize) -> Result<Vec<u8>, RiscvError>;
+
+    fn write_memory(&mut self, addr: u64, data: &[u8]) -> Result<(), RiscvError>;
+
+    /// Execute exactly one instruction
+    fn step(&mut self) -> Result<StepResult, RiscvError>;
+
+    /// Execute up to `budget` instructions, stopping before any address in
+    /// `breakpoints`
+    ///
+    /// The instruction at the current pc always runs, so resuming from a
+    /// breakpoint moves past it. Returns whether execution stopped at a
+    /// breakpoint.
+    fn run(&mut self, breakpoints: &BTreeSet<u64>, budget: u32) -> Result<bool, RiscvError> {
+        for _ in 0..budget {
+            if breakpoints.contains(&self.step()?.next_pc) {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    /// gdb detached, killed the session or disconnected
+    fn detach(&mut self) {}
+}
+
+impl GdbTarget for RiscvCore {
+    fn xlen(&self) -> XLen {
+        RiscvCore::xlen(self)
+    }
+
+    fn read_registers(&mut self) -> Result<([u64; 32], u64), RiscvError> {
+        Ok((*self.registers(), self.pc()))
+    }
+
+    fn write_registers(&mut self, regs: &[u64; 32], pc: u64) -> Result<(), RiscvError> {
+        for (index, &value) in regs.iter().enumerate() {
+            self.set_reg(index, value);
+        }
+        self.set_pc(pc);
+        Ok(())
+    }
+
+    fn read_memory(&mut self, addr: u64, len: usize) -> Result<Vec<u8>, RiscvError> {
+        RiscvCore::read_memory(self, addr, len).map(|bytes| bytes.to_vec())
+    }
+
+    fn write_memory(&mut self, addr: u64, data: &[u8]) -> Result<(), RiscvError> {
+        self.load(addr, data)
+    }
+
+    fn step(&mut self) -> Result<StepResult, RiscvError> {
+        RiscvCore::step(self)
+    }
+}
+
+impl<T: GdbTarget + ?Sized> GdbTarget for &mut T {
+    fn xlen(&self) -> XLen {
+        (**self).xlen()
+    }
+
+    fn read_registers(&mut self) -> Result<([u64; 32], u64), RiscvError> {
+        (**self).read_registers()
+    }
+
+    fn write_registers(&mut self, regs: &[u64; 32], pc: u64) -> Result<(), RiscvError> {
+        (**self).write_registers(regs, pc)
+    }
+
+    fn read_memory(&mut self, addr: u64, len: usize) -> Result<Vec<u8>, RiscvError> {
+        (**self).read_memory(addr, len)
+    }
+
+    fn write_memory(&mut self, addr: u64, data: &[u8]) -> Result<(), RiscvError> {
+        (**self).write_memory(addr, data)
+    }
+
+    fn step(&mut self) -> Result<StepResult, RiscvError> {
+        (**self).step()
+    }
+
+    fn run(&mut self, breakpoints: &BTreeSet<u64>, budget: u32) -> Result<bool, RiscvError> {
+        (**self).run(breakpoints, budget)
+    }
+
+    fn detach(&mut self) {
+        (**self).detach()
+    }
+}
+
+/// Modulo-256 sum of the packet data, as sent after the `#`
+pub fn checksum(data: &str) -> u8 {
+    data.bytes().fold(0u8, |sum, byte| sum.wrapping_add(byte))
+}
+
+/// Frame packet data as `$data#xx`
+pub fn encode_packet(data: &str) -> String {
+    format!("${}#{:02x}", data, checksum(data))
+}
+
+fn hex_encode(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+fn hex_decode(hex: &str) -> Option<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+/// Parse the `addr,len` arguments of `m`, `M`, `Z` and `z`
+fn parse_addr_len(args: &str) -> Option<(u64, usize)> {
+    let (addr, len) = args.split_once(',')?;
+    Some((
+        u64::from_str_radix(addr, 16).ok()?,
+        usize::from_str_radix(len, 16).ok()?,
+    ))
+}
+
+/// Signal reported for an execution error
+fn error_signal(error: &RiscvError) -> u8 {
+    match error {
+        RiscvError::IllegalInstruction { .. } => SIGILL,
+        RiscvError::MemoryFault { .. } => SIGSEGV,
+        RiscvError::MisalignedFetch { .. } => SIGBUS,
+        RiscvError::Halted | RiscvError::NoProgram | RiscvError::InvalidElf(_) => SIGTRAP,
+    }
+}
+
+/// Packet framing over one gdb connection
+struct Connection {
+    reader: BufReader<TcpStream>,
+    writer: TcpStream,
+}
+
+impl Connection {
+    fn new(stream: TcpStream) -> io::Result<Self> {
+        Ok(Self {
+            reader: BufReader::new(stream.try_clone()?),
+            writer: stream,
+        })
+    }
+
+    /// Next packet with a valid checksum, acknowledged; `None` once gdb
+    /// disconnects
+    fn read_packet(&mut self) -> io::Result<Option<String>> {
+        loop {
+            // Skip acks and stray interrupts until the start of a packet
+            let mut byte = [0u8];
+            loop {
+                if self.reader.read(&mut byte)? == 0 {
+                    return Ok(None);
+                }
+                if byte[0] == b'$' {
+                    break;
+                }
+            }
+
+            let mut data = Vec::new();
+            self.reader.read_until(b'#', &mut data)?;
+            if data.pop() != Some(b'#') {
+                return Ok(None);
+            }
+            let mut sum = [0u8; 2];
+            self.reader.read_exact(&mut sum)?;
+
+            let data = String::from_utf8_lossy(&data).into_owned();
+            let expected = std::str::from_utf8(&sum)
+                .ok()
+                .and_then(|sum| u8::from_str_radix(sum, 16).ok());
+            if expected == Some(checksum(&data)) {
+                self.writer.write_all(b"+")?;
+                return Ok(Some(data));
+            }
+            log::warn!("GDB stub: bad checksum on packet '{}'", data);
+            self.writer.write_all(b"-")?;
+        }
+    }
+
+    fn send_packet(&mut self, data: &str) -> io::Result<()> {
+        self.writer.write_all(encode_packet(data).as_bytes())?;
+        self.writer.flush()
+    }
+
+    /// Whether gdb sent an interrupt (0x03) while the target was running
+    fn interrupted(&mut self) -> io::Result<bool> {
+        self.reader.get_ref().set_nonblocking(true)?;
+        let result = match self.reader.fill_buf() {
+            Ok(buf) if buf.first() == Some(&0x03) => {
+                self.reader.consume(1);
+                Ok(true)
+            },
+            Ok(_) => Ok(false),
+            Err(e) if e.kind() == io::ErrorKind::WouldBlock => Ok(false),
+            Err(e) => Err(e),
+        };
+        self.reader.get_ref().set_nonblocking(false)?;
+        result
+    }
+}
+
+/// GDB remote stub driving a single hart
+pub struct GdbStub<T: GdbTarget> {
+    target: T,
+    breakpoints: BTreeSet<u64>,
+}
+
+impl<T: GdbTarget> GdbStub<T> {
+    pub fn new(target: T) -> Self {
+        Self {
+            target,
+            breakpoints: BTreeSet::new(),
+        }
+    }
+
+    pub fn target(&self) -> &T {
+        &self.target
+    }
+
+    pub fn target_mut(&mut self) -> &mut T {
+        &mut self.target
+    }
+
+    pub fn into_target(self) -> T {
+        self.target
+    }
+
+    /// Addresses with a software breakpoint
+    pub fn breakpoints(&self) -> &BTreeSet<u64> {
+        &self.breakpoints
+    }
+
+    /// Wait for gdb to connect and serve it until it detaches
+    pub fn serve(&mut self, listener: &TcpListener) -> io::Result<()> {
+        let (stream, peer) = listener.accept()?;
+        log::info!("🐞 GDB stub: {} attached", peer);
+        self.run_session(stream)?;
+        log::info!("🐞 GDB stub: {} detached", peer);
+        Ok(())
+    }
+
+    /// Handle packets from one connection until detach, kill or disconnect
+    ///
+    /// The target is detached on the way out, also when the connection
+    /// fails.
+    pub fn run_session(&mut self, stream: TcpStream) -> io::Result<()> {
+        let result = self.handle_session(stream);
+        self.target.detach();
+        result
+    }
+
+    fn handle_session(&mut self, stream: TcpStream) -> io::Result<()> {
+        stream.set_nodelay(true)?;
+        let mut conn = Connection::new(stream)?;
+        while let Some(packet) = conn.read_packet()? {
+            match packet.as_bytes().first() {
+                Some(b'D') => {
+                    conn.send_packet("OK")?;
+                    break;
+                },
+                Some(b'k') => break,
+                _ => {
+                    let reply = self.handle_packet(&packet, &mut conn)?;
+                    conn.send_packet(&reply)?;
+                },
+            }
+        }
+        Ok(())
+    }
+
+    /// Reply to one packet (other than `D` and `k`)
+    fn handle_packet(&mut self, packet: &str, conn: &mut Connection) -> io::Result<String> {
+        let mut chars = packet.chars();
+        let command = chars.next();
+        let args = chars.as_str();
+        let reply = match command {
+            Some('?') => format!("S{:02x}", SIGTRAP),
+            Some('g') => self.read_registers(),
+            Some('G') => self.write_registers(args),
+            Some('m') => self.read_memory(args),
+            Some('M') => self.write_memory(args),
+            Some('s') => self.single_step(),
+            Some('c') => self.resume(conn)?,
+            Some(kind @ ('Z' | 'z')) => self.update_breakpoint(kind == 'Z', args),
+            Some('H') => "OK".to_string(),
+            Some('q') if args.starts_with("Supported") => "PacketSize=4000".to_string(),
+            Some('q') if args == "Attached" => "1".to_string(),
+            Some('q') if args == "C" => "QC1".to_string(),
+            _ => String::new(),
+        };
+        Ok(reply)
+    }
+
+    /// Bytes per register in the `g`/`G` payload
+    fn register_bytes(&self) -> usize {
+        self.target.xlen().bits() as usize / 8
+    }
+
+    fn read_registers(&mut self) -> String {
+        let width = self.register_bytes();
+        match self.target.read_registers() {
+            Ok((regs, pc)) => regs
+                .iter()
+                .chain(std::iter::once(&pc))
+                .map(|value| hex_encode(&value.to_le_bytes()[..width]))
+                .collect(),
+            Err(_) => "E01".to_string(),
+        }
+    }
+
+    fn write_registers(&mut self, hex: &str) -> String {
+        let width = self.register_bytes();
+        let Some(bytes) = hex_decode(hex).filter(|bytes| bytes.len() >= 33 * width) else {
+            return "E01".to_string();
+        };
+        let mut values = bytes.chunks_exact(width).map(|chunk| {
+            let mut buf = [0u8; 8];
+            buf[..width].copy_from_slice(chunk);
+            u64::from_le_bytes(buf)
+        });
+        let mut regs = [0u64; 32];
+        for reg in regs.iter_mut() {
+            *reg = values.next().unwrap_or(0);
+        }
+        let pc = values.next().unwrap_or(0);
+        match self.target.write_registers(&regs, pc) {
+            Ok(()) => "OK".to_string(),
+            Err(_) => "E01".to_string(),
+        }
+    }
+
+    fn read_memory(&mut self, args: &str) -> String {
+        let Some((addr, len)) = parse_addr_len(args) else {
+            return "E01".to_string();
+        };
+        match self.target.read_memory(addr, len) {
+            Ok(bytes) => hex_encode(&bytes),
+            Err(_) => "E01".to_string(),
+        }
+    }
+
+    fn write_memory(&mut self, args: &str) -> String {
+        let parsed = args.split_once(':').and_then(|(range, hex)| {
+            let (addr, len) = parse_addr_len(range)?;
+            hex_decode(hex)
+                .filter(|bytes| bytes.len() == len)
+                .map(|bytes| (addr, bytes))
+        });
+        let Some((addr, bytes)) = parsed else {
+            return "E01".to_string();
+        };
+        match self.target.write_memory(addr, &bytes) {
+            Ok(()) => "OK".to_string(),
+            Err(_) => "E01".to_string(),
+        }
+    }
+
+    fn single_step(&mut self) -> String {
+        let signal = match self.target.step() {
+            Ok(_) => SIGTRAP,
+            Err(e) => error_signal(&e),
+        };
+        format!("S{:02x}", signal)
+    }
+
+    /// Run until a breakpoint, a halt, an error or a gdb interrupt
+    fn resume(&mut self, conn: &mut Connection) -> io::Result<String> {
+        let signal = loop {
+            match self.target.run(&self.breakpoints, INTERRUPT_POLL_INTERVAL) {
+                Ok(true) => break SIGTRAP,
+                Ok(false) => {},
+                Err(e) => break error_signal(&e),
+            }
+            if conn.interrupted()? {
+                break SIGINT;
+            }
+        };
+        Ok(format!("S{:02x}", signal))
+    }
+
+    /// `Z0,addr,kind` inserts and `z0,addr,kind` removes a software breakpoint
+    fn update_breakpoint(&mut self, insert: bool, args: &str) -> String {
+        let Some(("0", location)) = args.split_once(',') else {
+            // Hardware breakpoints and watchpoints are unsupported
+            return String::new();
+        };
+        let Some((addr, _kind)) = parse_addr_len(location) else {
+            return "E01".to_string();
+        };
+        if insert {
+            self.breakpoints.insert(addr);
+        } else {
+            self.breakpoints.remove(&addr);
+        }
+        "OK".to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    const EBREAK: u32 = 0x0010_0073;
+
+    /// Scripted gdb client
+    struct Client {
+        stream: TcpStream,
+    }
+
+    impl Client {
+        /// Send a packet and return the raw reply, checksum included
+        fn request(&mut self, data: &str) -> String {
+            self.stream
+                .write_all(encode_packet(data).as_bytes())
+                .unwrap();
+
+            let mut ack = [0u8];
+            self.stream.read_exact(&mut ack).unwrap();
+            assert_eq!(ack[0], b'+', "stub rejected '{}'", data);
+
+            let mut reply = Vec::new();
+            let mut byte = [0u8];
+            while !reply.ends_with(b"#") {
+                self.stream.read_exact(&mut byte).unwrap();
+                reply.push(byte[0]);
+            }
+            let mut sum = [0u8; 2];
+            self.stream.read_exact(&mut sum).unwrap();
+            reply.extend_from_slice(&sum);
+            self.stream.write_all(b"+").unwrap();
+            String::from_utf8(reply).unwrap()
+        }
+
+        /// Reply data after checking its framing
+        fn data(&mut self, data: &str) -> String {
+            let reply = self.request(data);
+            let body = &reply[1..reply.len() - 3];
+            assert_eq!(reply, encode_packet(body));
+            body.to_string()
+        }
+    }
+
+    #[test]
+    fn test_scripted_session() {
+        // addi x1, x0, 5; addi x2, x1, 7; sw x2, 0x100(x0); ebreak
+        let program = [0x0050_0093u32, 0x0070_8113, 0x1020_2023, EBREAK];
+        let mut core = RiscvCore::new(XLen::Rv32, 0x1000);
+        let bytes: Vec<u8> = program.iter().flat_map(|w| w.to_le_bytes()).collect();
+        core.load(0, &bytes).unwrap();
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = std::thread::spawn(move || {
+            let mut stub = GdbStub::new(core);
+            stub.serve(&listener).unwrap();
+            stub.into_target()
+        });
+
+        let stream = TcpStream::connect(addr).unwrap();
+        stream.set_nodelay(true).unwrap();
+        stream
+            .set_read_timeout(Some(Duration::from_secs(5)))
+            .unwrap();
+        let mut gdb = Client { stream };
+
+        // 33 zeroed 32-bit registers: 264 '0' characters sum to 0x80
+        assert_eq!(gdb.request("g"), format!("${}#80", "0".repeat(264)));
+
+        assert_eq!(gdb.data("?"), "S05");
+        assert_eq!(gdb.data("Z0,8,4"), "OK");
+        assert_eq!(gdb.data("c"), "S05");
+        let regs = gdb.data("g");
+        assert_eq!(&regs[8..16], "05000000"); // x1
+        assert_eq!(&regs[16..24], "0c000000"); // x2
+        assert_eq!(&regs[256..264], "08000000"); // pc stopped on the breakpoint
+
+        assert_eq!(gdb.data("s"), "S05");
+        assert_eq!(gdb.data("m100,4"), "0c000000");
+        assert_eq!(gdb.data("M104,2:beef"), "OK");
+        assert_eq!(gdb.data("m104,2"), "beef");
+        assert_eq!(gdb.data("m2000,4"), "E01");
+
+        // Continuing runs into the ebreak
+        assert_eq!(gdb.data("z0,8,4"), "OK");
+        assert_eq!(gdb.data("c"), "S05");
+        assert_eq!(gdb.data("vMustReplyEmpty"), "");
+        assert_eq!(gdb.data("D"), "OK");
+
+        let core = server.join().unwrap();
+        assert!(core.is_halted());
+        assert_eq!(core.pc(), 12);
+        assert_eq!(core.read_memory(0x104, 2).unwrap(), [0xbe, 0xef]);
+    }
+
+    /// Core that counts how often gdb let go of it
+    struct DetachCounter {
+        core: RiscvCore,
+        detached: u32,
+    }
+
+    impl GdbTarget for DetachCounter {
+        fn xlen(&self) -> XLen {
+            self.core.xlen()
+        }
+
+        fn read_registers(&mut self) -> Result<([u64; 32], u64), RiscvError> {
+            GdbTarget::read_registers(&mut self.core)
+        }
+
+        fn write_registers(&mut self, regs: &[u64; 32], pc: u64) -> Result<(), RiscvError> {
+            GdbTarget::write_registers(&mut self.core, regs, pc)
+        }
+
+        fn read_memory(&mut self, addr: u64, len: usize) -> Result<Vec<u8>, RiscvError> {
+            GdbTarget::read_memory(&mut self.core, addr, len)
+        }
+
+        fn write_memory(&mut self, addr: u64, data: &[u8]) -> Result<(), RiscvError> {
+            GdbTarget::write_memory(&mut self.core, addr, data)
+        }
+
+        fn step(&mut self) -> Result<StepResult, RiscvError> {
+            self.core.step()
+        }
+
+        fn detach(&mut self) {
+            self.detached += 1;
+        }
+    }
+
+    #[test]
+    fn test_session_end_detaches_target() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = std::thread::spawn(move || {
+            let mut stub = GdbStub::new(DetachCounter {
+                core: RiscvCore::new(XLen::Rv32, 0x100),
+                detached: 0,
+            });
+            for _ in 0..3 {
+                stub.serve(&listener).unwrap();
+            }
+            stub.into_target().detached
+        });
+
+        let connect = || {
+            let stream = TcpStream::connect(addr).unwrap();
+            stream
+                .set_read_timeout(Some(Duration::from_secs(5)))
+                .unwrap();
+            Client { stream }
+        };
+
+        // Detach, kill, and a connection that just goes away
+        let mut gdb = connect();
+        assert_eq!(gdb.data("D"), "OK");
+        let mut gdb = connect();
+        assert_eq!(gdb.data("?"), "S05");
+        gdb.stream.write_all(encode_packet("k").as_bytes()).unwrap();
+        let mut ack = [0u8];
+        gdb.stream.read_exact(&mut ack).unwrap();
+        let mut gdb = connect();
+        assert_eq!(gdb.data("?"), "S05");
+        drop(gdb);
+
+        assert_eq!(server.join().unwrap(), 3);
+    }
+
+    #[test]
+    fn test_register_write_uses_xlen_width() {
+        let mut stub = GdbStub::new(RiscvCore::new(XLen::Rv64, 0x100));
+        let mut regs = [0u64; 32];
+        regs[5] = 0x1122_3344_5566_7788;
+        let payload: String = regs
+            .iter()
+            .chain(std::iter::once(&0x40u64))
+            .map(|value| hex_encode(&value.to_le_bytes()))
+            .collect();
+
+        assert_eq!(stub.write_registers(&payload), "OK");
+        assert_eq!(stub.target().reg(5), 0x1122_3344_5566_7788);
+        assert_eq!(stub.target().pc(), 0x40);
+        assert_eq!(stub.read_registers(), payload);
+        assert_eq!(stub.write_registers("00"), "E01");
+    }
+}
@@ -3,15 +3,24 @@
 //! This module provides the complete RISC-V GPU VM implementation for
 //! running RISC-V programs encoded in the .rts.png format.
 
+pub mod cpu;
+pub mod disasm;
+pub mod elf;
 pub mod executor;
+pub mod gdb_stub;
 pub mod hooks;
 pub mod memory;
 pub mod native_rv64;
 pub mod pipeline;
 pub mod program;
+pub mod trace;
 pub mod ubuntu_bridge;
 
+pub use cpu::{MemoryWrite, RiscvCore, RiscvError, StepResult, XLen};
+pub use disasm::disassemble;
+pub use elf::{ElfImage, LoadedElf};
 pub use executor::{ExecutionResult, RiscvExecutor};
+pub use gdb_stub::{GdbStub, GdbTarget};
 pub use hooks::{AsciiSceneHook, HeatHook, RiscvHook, RiscvHookBroadcaster, WebSocketHook};
 pub use memory::{
     CSRBank, Config, ExecutionState, MMIOState, VMMemoryLayout, RAM_SIZE, REGISTER_COUNT,
@@ -19,4 +28,5 @@ pub use memory::{
 pub use native_rv64::{NativeRv64Executor, Riscv64State, Rv64PushConstants};
 pub use pipeline::RiscvPipeline;
 pub use program::{ProgramMetadata, RiscvProgram};
+pub use trace::{TraceBuffer, TraceEntry};
 pub use ubuntu_bridge::{Rv64Result, UbuntuKernelInput, UbuntuRv64Bridge};
@@ -0,0 +1,171 @@
+//! RISC-V Instruction Trace
+//!
+//! Bounded record of the instructions a hart retired, for diffing a run
+//! against a reference emulator. Entries go into a ring buffer, so a long
+//! run keeps only the most recent `capacity` instructions instead of growing
+//! without limit.
+
+use std::collections::VecDeque;
+use std::fmt;
+
+use super::cpu::{StepResult, XLen};
+use super::disasm::{disassemble, register_name};
+
+/// One retired instruction
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TraceEntry {
+    /// Address of the instruction
+    pub pc: u64,
+    /// Raw instruction word
+    pub word: u32,
+    /// Register written and its new value (never x0)
+    pub reg_write: Option<(usize, u64)>,
+    /// Register width of the hart, for decoding `word`
+    pub xlen: XLen,
+}
+
+impl TraceEntry {
+    pub fn new(step: &StepResult, xlen: XLen) -> Self {
+        Self {
+            pc: step.pc,
+            word: step.word,
+            reg_write: step.reg_write,
+            xlen,
+        }
+    }
+}
+
+/// `0x00000004: fff08093  addi ra, ra, -1  ; ra = 0x2`
+impl fmt::Display for TraceEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:#010x}: {:08x}  {}",
+            self.pc,
+            self.word,
+            disassemble(self.word, self.xlen)
+        )?;
+        if let Some((reg, value)) = self.reg_write {
+            write!(f, "  ; {} = {:#x}", register_name(reg), value)?;
+        }
+        Ok(())
+    }
+}
+
+/// Ring buffer holding the last `capacity` trace entries
+#[derive(Debug, Clone)]
+pub struct TraceBuffer {
+    entries: VecDeque<TraceEntry>,
+    capacity: usize,
+    /// Entries pushed out by newer ones since the last `take`
+    dropped: u64,
+}
+
+impl TraceBuffer {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            entries: VecDeque::with_capacity(capacity),
+            capacity,
+            dropped: 0,
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Entries lost to wrap-around since the last `take`
+    pub fn dropped(&self) -> u64 {
+        self.dropped
+    }
+
+    /// Append an entry, evicting the oldest one when full
+    pub fn push(&mut self, entry: TraceEntry) {
+        if self.capacity == 0 {
+            self.dropped += 1;
+            return;
+        }
+        if self.entries.len() == self.capacity {
+            self.entries.pop_front();
+            self.dropped += 1;
+        }
+        self.entries.push_back(entry);
+    }
+
+    /// Remove and return the buffered entries, oldest first
+    pub fn take(&mut self) -> Vec<TraceEntry> {
+        self.dropped = 0;
+        self.entries.drain(..).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::riscv::{RiscvCore, XLen};
+
+    /// x1 = 3; loop: x1 -= 1; bne x1, x0, loop; ebreak
+    const COUNTDOWN: [u32; 4] = [
+        0x0030_0093, // addi x1, x0, 3
+        0xfff0_8093, // addi x1, x1, -1
+        0xfe00_9ee3, // bne  x1, x0, -4
+        0x0010_0073, // ebreak
+    ];
+
+    fn countdown_core() -> RiscvCore {
+        let program: Vec<u8> = COUNTDOWN.iter().flat_map(|w| w.to_le_bytes()).collect();
+        let mut core = RiscvCore::new(XLen::Rv32, 0x100);
+        core.load(0, &program).unwrap();
+        core
+    }
+
+    #[test]
+    fn test_trace_records_loop() {
+        let mut core = countdown_core();
+        core.enable_trace(64);
+        core.run(100).unwrap();
+        assert!(core.is_halted());
+
+        let trace = core.take_trace();
+        let pcs: Vec<u64> = trace.iter().map(|entry| entry.pc).collect();
+        assert_eq!(pcs, [0, 4, 8, 4, 8, 4, 8, 12]);
+        assert_eq!(trace[0].word, COUNTDOWN[0]);
+        assert_eq!(trace[0].reg_write, Some((1, 3)));
+        assert_eq!(trace[5].reg_write, Some((1, 0)));
+        // Branches write no register
+        assert_eq!(trace[2].reg_write, None);
+        assert_eq!(
+            trace[1].to_string(),
+            "0x00000004: fff08093  addi ra, ra, -1  ; ra = 0x2"
+        );
+        assert_eq!(
+            trace[2].to_string(),
+            "0x00000008: fe009ee3  bne ra, zero, -4"
+        );
+        assert!(core.take_trace().is_empty());
+    }
+
+    #[test]
+    fn test_trace_keeps_most_recent_entries() {
+        let mut core = countdown_core();
+        core.enable_trace(3);
+        core.run(100).unwrap();
+        assert_eq!(core.trace().unwrap().dropped(), 5);
+
+        let pcs: Vec<u64> = core.take_trace().iter().map(|entry| entry.pc).collect();
+        assert_eq!(pcs, [4, 8, 12]);
+
+        // Tracing is off by default
+        let mut untraced = countdown_core();
+        untraced.run(100).unwrap();
+        assert!(untraced.trace().is_none());
+        assert!(untraced.take_trace().is_empty());
+    }
+}
@@ -1,10 +1,15 @@
 use bytemuck::{Pod, Zeroable};
 use log::info;
+use std::collections::BTreeSet;
 use std::sync::Arc;
 
 // Phase 48: WGSL i64 Compatibility
 use crate::gpu_capabilities::{GpuCapabilities, I64Strategy};
 use crate::i64_emulation::generate_i64_emulation_wgsl;
+use crate::riscv::{
+    disassemble, ElfImage, GdbTarget, LoadedElf, MemoryWrite, RiscvCore, RiscvError, StepResult,
+    TraceBuffer, TraceEntry, XLen,
+};
 
 /// RISC-V Executor - Integrates the Pixel CPU VM into the compositor
 ///
@@ -119,8 +124,29 @@ pub struct RiscvStats {
     pub syscall_arg2: u32,
     /// Console buffer position
     pub console_pos: u32,
+    /// Trace slots the shader may fill this dispatch (0 = not tracing)
+    pub trace_slots: u32,
+    /// Trace slots the shader filled this dispatch
+    pub trace_len: u32,
     /// Padding
-    pub _padding: [u32; 7],
+    pub _padding: [u32; 5],
+}
+
+/// Trace slots after the stats header (must match the shader)
+const TRACE_SLOTS: u32 = 4096;
+
+/// Patched over breakpoints while gdb runs rv32 code on the GPU
+const EBREAK: u32 = 0x0010_0073;
+
+/// Instruction the shader retired, as recorded in the stats buffer
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Pod, Zeroable)]
+struct TraceSlot {
+    pc: u32,
+    word: u32,
+    /// Register written (0 = none)
+    reg: u32,
+    value: u32,
 }
 
 /// Syscall queue entry (40 bytes, matching WGSL)
@@ -153,6 +179,17 @@ impl ProfilerEntry {
     pub fn is_hot(&self) -> bool {
         self.count >= 10_000
     }
+
+    /// `0x0040: addi a0, a1, 4 (12000 executions)`, given the instruction
+    /// word at `pc` (the GPU profiler only sees rv32 code)
+    pub fn describe(&self, word: u32) -> String {
+        format!(
+            "0x{:04x}: {} ({} executions)",
+            self.pc,
+            disassemble(word, XLen::Rv32),
+            self.count
+        )
+    }
 }
 
 /// Phase 44: Profiler statistics summary
@@ -206,6 +243,26 @@ pub struct RiscvExecutor {
     /// Phase 48: i64 strategy (Native or Emulate)
     i64_strategy: I64Strategy,
 
+    /// Register width of the hart
+    xlen: XLen,
+
+    /// CPU hart running rv64 code (the shader only has 32-bit registers)
+    ///
+    /// Mirrors every RAM upload while present.
+    cpu_core: Option<RiscvCore>,
+
+    /// `execute_frame` is paused; the program advances only through `step`
+    single_step: bool,
+
+    /// Ring buffer size of the instruction trace, if tracing
+    trace_capacity: Option<usize>,
+
+    /// Instructions the shader retired, while tracing
+    gpu_trace: Option<TraceBuffer>,
+
+    /// Characters of the GPU console buffer already in `console_output`
+    console_len: usize,
+
     /// RAM buffer (stores code, data, registers)
     pub ram_buffer: wgpu::Buffer,
 
@@ -266,6 +323,32 @@ pub struct RiscvExecutor {
     neuromodulation: crate::cortex::Neuromodulator,
 }
 
+/// Display texture formats, best first
+///
+/// The shader only stores `vec4<f32>` colors, so any float format the
+/// compositor can sample works; it is patched to match the chosen one.
+const DISPLAY_FORMATS: &[wgpu::TextureFormat] = &[
+    wgpu::TextureFormat::Rgba8Unorm,
+    wgpu::TextureFormat::Rgba16Float,
+];
+
+const DISPLAY_USAGE: wgpu::TextureUsages = wgpu::TextureUsages::STORAGE_BINDING
+    .union(wgpu::TextureUsages::TEXTURE_BINDING)
+    .union(wgpu::TextureUsages::COPY_DST)
+    .union(wgpu::TextureUsages::COPY_SRC);
+
+/// Executor shader writing the display as `format`
+fn display_shader_source(format: wgpu::TextureFormat) -> String {
+    let source = include_str!("shaders/riscv_executor.wgsl");
+    match format {
+        wgpu::TextureFormat::Rgba16Float => source.replace(
+            "texture_storage_2d<rgba8unorm, write>",
+            "texture_storage_2d<rgba16float, write>",
+        ),
+        _ => source.to_string(),
+    }
+}
+
 impl RiscvExecutor {
     /// Create a new RISC-V executor with the specified GPU capabilities
     /// This ensures the appropriate shader is selected based on i64 support
@@ -300,8 +383,10 @@ impl RiscvExecutor {
 
         // Create RAM buffer (for storing raw bytes)
         // 8192^2 pixels * 4 bytes/pixel = 268,435,456 bytes (256MB)
-        // This is sufficient for full Alpine kernel + initrd + growth
-        let ram_size = (texture_size * texture_size * 4) as u64;
+        // This is sufficient for full Alpine kernel + initrd + growth, but the
+        // shader binds it whole, so it's capped at the device's binding limit
+        let ram_size = ((texture_size * texture_size * 4) as u64)
+            .min(device.limits().max_storage_buffer_binding_size as u64);
         let ram_buffer = device.create_buffer(&wgpu::BufferDescriptor {
             label: Some("RISC-V RAM"),
             size: ram_size,
@@ -312,6 +397,9 @@ impl RiscvExecutor {
         });
 
         // Create display texture (for VM console output)
+        let display_format = caps
+            .best_supported_format(DISPLAY_FORMATS, DISPLAY_USAGE)
+            .unwrap_or(DISPLAY_FORMATS[0]);
         let display_texture = device.create_texture(&wgpu::TextureDescriptor {
             label: Some("RISC-V Display"),
             size: wgpu::Extent3d {
@@ -322,17 +410,14 @@ impl RiscvExecutor {
             mip_level_count: 1,
             sample_count: 1,
             dimension: wgpu::TextureDimension::D2,
-            format: wgpu::TextureFormat::Rgba8Unorm,
-            usage: wgpu::TextureUsages::STORAGE_BINDING
-                | wgpu::TextureUsages::TEXTURE_BINDING
-                | wgpu::TextureUsages::COPY_DST
-                | wgpu::TextureUsages::COPY_SRC,
+            format: display_format,
+            usage: DISPLAY_USAGE,
             view_formats: &[],
         });
         let display_view = display_texture.create_view(&wgpu::TextureViewDescriptor::default());
 
         // Load shader module
-        let shader_source = include_str!("shaders/riscv_executor.wgsl");
+        let shader_source = display_shader_source(display_format);
         let shader_module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
             label: Some("RISC-V Executor Shader"),
             source: wgpu::ShaderSource::Wgsl(shader_source.into()),
@@ -392,7 +477,7 @@ impl RiscvExecutor {
                     visibility: wgpu::ShaderStages::COMPUTE,
                     ty: wgpu::BindingType::StorageTexture {
                         access: wgpu::StorageTextureAccess::WriteOnly,
-                        format: wgpu::TextureFormat::Rgba8Unorm,
+                        format: display_format,
                         view_dimension: wgpu::TextureViewDimension::D2,
                     },
                     count: None,
@@ -479,10 +564,11 @@ impl RiscvExecutor {
             mapped_at_creation: false,
         });
 
-        // Create stats buffers
+        // Create stats buffers (the GPU one ends with the trace slots)
         let stats_buffer = device.create_buffer(&wgpu::BufferDescriptor {
             label: Some("RISC-V Stats GPU"),
-            size: std::mem::size_of::<RiscvStats>() as u64,
+            size: (std::mem::size_of::<RiscvStats>()
+                + std::mem::size_of::<TraceSlot>() * TRACE_SLOTS as usize) as u64,
             usage: wgpu::BufferUsages::STORAGE
                 | wgpu::BufferUsages::COPY_DST
                 | wgpu::BufferUsages::COPY_SRC,
@@ -527,10 +613,7 @@ impl RiscvExecutor {
         let syscall_queue_staging = device.create_buffer(&wgpu::BufferDescriptor {
             label: Some("RISC-V Syscall Queue Staging"),
             size: syscall_queue_size,
-            usage: wgpu::BufferUsages::MAP_READ
-                | wgpu::BufferUsages::MAP_WRITE
-                | wgpu::BufferUsages::COPY_DST
-                | wgpu::BufferUsages::COPY_SRC,
+            usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
             mapped_at_creation: false,
         });
 
@@ -546,10 +629,7 @@ impl RiscvExecutor {
         let pending_counts_staging = device.create_buffer(&wgpu::BufferDescriptor {
             label: Some("RISC-V Pending Counts Staging"),
             size: pending_counts_size,
-            usage: wgpu::BufferUsages::MAP_READ
-                | wgpu::BufferUsages::MAP_WRITE
-                | wgpu::BufferUsages::COPY_DST
-                | wgpu::BufferUsages::COPY_SRC,
+            usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
             mapped_at_creation: false,
         });
 
@@ -565,10 +645,7 @@ impl RiscvExecutor {
         let vm_status_staging = device.create_buffer(&wgpu::BufferDescriptor {
             label: Some("RISC-V VM Status Staging"),
             size: vm_status_size,
-            usage: wgpu::BufferUsages::MAP_READ
-                | wgpu::BufferUsages::MAP_WRITE
-                | wgpu::BufferUsages::COPY_DST
-                | wgpu::BufferUsages::COPY_SRC,
+            usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
             mapped_at_creation: false,
         });
 
@@ -678,6 +755,12 @@ impl RiscvExecutor {
             texture_size,
             neuromodulation: crate::cortex::Neuromodulator::default(),
             i64_strategy,
+            xlen: XLen::Rv32,
+            cpu_core: None,
+            single_step: false,
+            trace_capacity: None,
+            gpu_trace: None,
+            console_len: 0,
         }
     }
 
@@ -689,6 +772,10 @@ impl RiscvExecutor {
             supports_i64: true, // Assume native support for legacy code
             vendor_name: "Unknown".to_string(),
             device_name: "Unknown".to_string(),
+            features: wgpu::Features::empty(),
+            format_features: std::collections::HashMap::new(),
+            limits: wgpu::Limits::default(),
+            vram_bytes: None,
         };
         Self::new_with_caps(device, queue, &caps)
     }
@@ -698,6 +785,308 @@ impl RiscvExecutor {
         self.i64_strategy
     }
 
+    /// Select 32- or 64-bit registers
+    ///
+    /// The WGSL shader only implements RV32, so `XLen::Rv64` moves execution
+    /// to a CPU hart, which uses the i64 emulation helpers when the GPU has
+    /// no native i64. Call before loading a program: switching width starts
+    /// a fresh hart.
+    pub fn set_xlen(&mut self, xlen: XLen) {
+        if xlen == self.xlen {
+            return;
+        }
+        self.set_single_step(false);
+        self.xlen = xlen;
+        self.cpu_core = match xlen {
+            XLen::Rv32 => None,
+            XLen::Rv64 => Some(self.new_cpu_core()),
+        };
+        info!("RISC-V executor switched to {}-bit registers", xlen.bits());
+    }
+
+    pub fn xlen(&self) -> XLen {
+        self.xlen
+    }
+
+    /// Full-width registers of the rv64 hart (rv32 registers live on the GPU)
+    pub fn registers64(&self) -> Option<&[u64; 32]> {
+        self.cpu_core.as_ref().map(|core| core.registers())
+    }
+
+    fn new_cpu_core(&self) -> RiscvCore {
+        let ram_size = self.ram_buffer.size() as usize;
+        let mut core = RiscvCore::new(self.xlen, ram_size).with_i64_strategy(self.i64_strategy);
+        if let Some(capacity) = self.trace_capacity {
+            core.enable_trace(capacity);
+        }
+        core
+    }
+
+    /// Pause `execute_frame` so the program only advances through `step`
+    ///
+    /// The program stays where it runs: rv32 code on the GPU, rv64 code on
+    /// the CPU hart.
+    pub fn set_single_step(&mut self, enabled: bool) {
+        if enabled == self.single_step {
+            return;
+        }
+        self.single_step = enabled;
+        info!(
+            "RISC-V single-step {}",
+            if enabled { "enabled" } else { "disabled" }
+        );
+    }
+
+    /// Record the last `capacity` instructions the program retires
+    ///
+    /// The shader records each instruction in a buffer read back after
+    /// every dispatch, so a traced rv32 program still runs on the GPU, with
+    /// frames capped at the shader's trace slots. Tracing is off by default.
+    pub fn enable_trace(&mut self, capacity: usize) {
+        self.trace_capacity = Some(capacity);
+        self.gpu_trace = Some(TraceBuffer::new(capacity));
+        if let Some(core) = self.cpu_core.as_mut() {
+            core.enable_trace(capacity);
+        }
+    }
+
+    /// Stop tracing and drop entries not yet taken
+    pub fn disable_trace(&mut self) {
+        self.trace_capacity = None;
+        self.gpu_trace = None;
+        if let Some(core) = self.cpu_core.as_mut() {
+            core.disable_trace();
+        }
+    }
+
+    /// Drain the recorded trace, oldest first
+    pub fn take_trace(&mut self) -> Vec<TraceEntry> {
+        match self.cpu_core.as_mut() {
+            Some(core) => core.take_trace(),
+            None => self
+                .gpu_trace
+                .as_mut()
+                .map(TraceBuffer::take)
+                .unwrap_or_default(),
+        }
+    }
+
+    pub fn single_step(&self) -> bool {
+        self.single_step
+    }
+
+    /// Execute exactly one instruction
+    ///
+    /// Enters single-step mode if needed. rv32 programs are stepped by
+    /// dispatching the shader with a budget of one instruction, so a step
+    /// behaves exactly like the GPU executor (e.g. ECALL is an SBI call and
+    /// CSR instructions are skipped).
+    pub fn step(&mut self) -> Result<StepResult, RiscvError> {
+        self.pause_for_debugger()?;
+        let Some(core) = self.cpu_core.as_mut() else {
+            return self.step_gpu();
+        };
+
+        let result = core.step();
+        self.uniforms.pc = core.pc() as u32;
+        match &result {
+            Ok(_) if core.is_halted() => self.uniforms.status = 2,
+            Ok(_) => {},
+            Err(_) => self.uniforms.status = 4,
+        }
+        result
+    }
+
+    /// Run one instruction through the shader and report what it did
+    ///
+    /// The shader doesn't report individual writes: `reg_write` is the
+    /// register whose value changed and `mem_write` is decoded from the
+    /// store instruction.
+    fn step_gpu(&mut self) -> Result<StepResult, RiscvError> {
+        if self.uniforms.status & 1 == 0 {
+            return Err(RiscvError::Halted);
+        }
+        let pc = self.uniforms.pc as u64;
+        if !pc.is_multiple_of(4) {
+            return Err(RiscvError::MisalignedFetch { pc });
+        }
+        let word = self
+            .read_instruction(pc)
+            .ok_or(RiscvError::MemoryFault { addr: pc, len: 4 })?;
+
+        let before = self.read_gpu_registers();
+        self.uniforms.instruction_count = 1;
+        self.dispatch();
+        let after = self.read_gpu_registers();
+
+        let disassembly = disassemble(word, XLen::Rv32);
+        let mnemonic = disassembly.split_whitespace().next().unwrap_or_default();
+        Ok(StepResult {
+            pc,
+            word,
+            mnemonic: mnemonic.to_string().into(),
+            reg_write: (1..32)
+                .find(|&reg| before[reg] != after[reg])
+                .map(|reg| (reg, after[reg] as u64)),
+            mem_write: decode_store(word, &before),
+            next_pc: self.uniforms.pc as u64,
+        })
+    }
+
+    /// Run a batch on the GPU with an `EBREAK` patched over each breakpoint,
+    /// as the shader has no breakpoint check of its own
+    ///
+    /// The original words are restored after the batch. Returns whether the
+    /// shader stopped on one of the patches; that halt is undone, leaving
+    /// the pc on the breakpoint.
+    fn run_gpu_to_breakpoint(
+        &mut self,
+        breakpoints: &BTreeSet<u64>,
+        budget: u32,
+    ) -> Result<bool, RiscvError> {
+        let patched: Vec<(u64, Vec<u8>)> = breakpoints
+            .iter()
+            .filter(|addr| addr.is_multiple_of(4))
+            .filter_map(|&addr| Some((addr, self.read_gpu_memory(addr, 4).ok()?)))
+            .collect();
+        for (addr, _) in &patched {
+            self.write_gpu_memory(*addr, &EBREAK.to_le_bytes())?;
+        }
+        self.run_batch(budget);
+        for (addr, word) in &patched {
+            self.write_gpu_memory(*addr, word)?;
+        }
+
+        let pc = self.uniforms.pc as u64;
+        let at_breakpoint =
+            self.uniforms.status & 2 != 0 && patched.iter().any(|(addr, _)| *addr == pc);
+        if at_breakpoint {
+            self.uniforms.status = 1;
+            self.queue
+                .write_buffer(&self.vm_status_buffer, 0, bytemuck::cast_slice(&[0u32; 8]));
+        }
+        Ok(at_breakpoint)
+    }
+
+    /// Enter single-step mode before a debugger request
+    fn pause_for_debugger(&mut self) -> Result<(), RiscvError> {
+        if !self.program_loaded {
+            return Err(RiscvError::NoProgram);
+        }
+        self.set_single_step(true);
+        Ok(())
+    }
+
+    /// The rv32 register file, which the shader keeps in the first 128 bytes
+    /// of RAM (x0 always reads as 0)
+    fn read_gpu_registers(&self) -> [u32; 32] {
+        let bytes = self.read_back_range(0, 32 * 4);
+        let mut registers = [0u32; 32];
+        for (reg, chunk) in registers.iter_mut().zip(bytes.chunks_exact(4)).skip(1) {
+            *reg = u32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]);
+        }
+        registers
+    }
+
+    /// `len` bytes of GPU RAM at any alignment
+    fn read_gpu_memory(&self, addr: u64, len: usize) -> Result<Vec<u8>, RiscvError> {
+        let end = addr
+            .checked_add(len as u64)
+            .filter(|&end| end <= self.ram_buffer.size())
+            .ok_or(RiscvError::MemoryFault { addr, len })?;
+        if len == 0 {
+            return Ok(Vec::new());
+        }
+        // Buffer copies must be 4-byte aligned
+        let start = addr & !3;
+        let span = self.read_back_range(start, end.next_multiple_of(4) - start);
+        let offset = (addr - start) as usize;
+        Ok(span[offset..offset + len].to_vec())
+    }
+
+    /// Write bytes to GPU RAM at any alignment, merging partial words with
+    /// their current contents
+    fn write_gpu_memory(&mut self, addr: u64, data: &[u8]) -> Result<(), RiscvError> {
+        let len = data.len();
+        let end = addr
+            .checked_add(len as u64)
+            .filter(|&end| end <= self.ram_buffer.size())
+            .ok_or(RiscvError::MemoryFault { addr, len })?;
+        if len == 0 {
+            return Ok(());
+        }
+        let start = addr & !3;
+        let mut span = self.read_back_range(start, end.next_multiple_of(4) - start);
+        let offset = (addr - start) as usize;
+        span[offset..offset + len].copy_from_slice(data);
+        self.queue.write_buffer(&self.ram_buffer, start, &span);
+        Ok(())
+    }
+
+    /// Copy `size` bytes of GPU RAM at `offset` back to the CPU (both must
+    /// be multiples of 4)
+    fn read_back_range(&self, offset: u64, size: u64) -> Vec<u8> {
+        self.read_back_buffer(&self.ram_buffer, offset, size)
+    }
+
+    /// Copy `size` bytes of a GPU buffer at `offset` back to the CPU
+    fn read_back_buffer(&self, buffer: &wgpu::Buffer, offset: u64, size: u64) -> Vec<u8> {
+        let staging = self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("RISC-V Readback"),
+            size,
+            usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("RISC-V Readback Encoder"),
+            });
+        encoder.copy_buffer_to_buffer(buffer, offset, &staging, 0, size);
+        self.queue.submit(Some(encoder.finish()));
+
+        let buffer_slice = staging.slice(..);
+        buffer_slice.map_async(wgpu::MapMode::Read, |_| {});
+        self.device.poll(wgpu::MaintainBase::Wait);
+        let ram = buffer_slice.get_mapped_range().to_vec();
+        staging.unmap();
+        ram
+    }
+
+    /// Instruction word at `pc`, e.g. to disassemble a profiled block
+    ///
+    /// Reads the CPU hart's RAM when there is one; otherwise it's a small
+    /// synchronous GPU read-back.
+    pub fn read_instruction(&self, pc: u64) -> Option<u32> {
+        if let Some(core) = self.cpu_core.as_ref() {
+            let bytes = core.read_memory(pc, 4).ok()?;
+            return Some(u32::from_le_bytes(bytes.try_into().ok()?));
+        }
+        if !pc.is_multiple_of(4) || pc.checked_add(4)? > self.ram_buffer.size() {
+            return None;
+        }
+        let bytes = self.read_back_range(pc, 4);
+        Some(u32::from_le_bytes(bytes.try_into().ok()?))
+    }
+
+    /// Upload bytes to GPU RAM and the CPU hart, if any
+    fn write_ram(&mut self, offset: u64, data: &[u8]) {
+        self.queue.write_buffer(&self.ram_buffer, offset, data);
+        if let Some(core) = self.cpu_core.as_mut() {
+            if let Err(e) = core.load(offset, data) {
+                log::warn!("RISC-V CPU hart: {}", e);
+            }
+        }
+    }
+
+    /// Point the CPU hart, if any, at the current entry PC
+    fn sync_core_pc(&mut self) {
+        if let Some(core) = self.cpu_core.as_mut() {
+            core.set_pc(self.uniforms.pc as u64);
+        }
+    }
+
     /// Phase 48: Transform i64 types in WGSL shader to emulated versions
     /// This replaces i64 types and operations with vec2<u32> equivalents
     fn transform_i64_to_emulated(shader: &str) -> String {
@@ -847,8 +1236,7 @@ impl RiscvExecutor {
             "Writing payload ({} bytes) to GPU RAM at offset 0",
             aligned_payload.len()
         );
-        self.queue
-            .write_buffer(&self.ram_buffer, 0, &aligned_payload);
+        self.write_ram(0, &aligned_payload);
 
         // Set up Linux boot registers
         // a0 (x10) = hart ID (0 for boot hart)
@@ -876,6 +1264,16 @@ impl RiscvExecutor {
             .write_buffer(&self.ram_buffer, a2_addr as u64, &a2_value.to_le_bytes());
         self.queue
             .write_buffer(&self.ram_buffer, a3_addr as u64, &a3_value.to_le_bytes());
+        if let Some(core) = self.cpu_core.as_mut() {
+            for (reg, value) in [
+                (10, a0_value),
+                (11, a1_value),
+                (12, a2_value),
+                (13, a3_value),
+            ] {
+                core.set_reg(reg, value as u64);
+            }
+        }
 
         info!(
             "Linux boot registers set: a0={}, a1={:#x}, a2={}, a3={}",
@@ -884,6 +1282,7 @@ impl RiscvExecutor {
 
         // Set entry point
         self.uniforms.pc = entry_point;
+        self.sync_core_pc();
         self.program_loaded = true;
         self.uniforms.status = 1; // Running
 
@@ -908,8 +1307,7 @@ impl RiscvExecutor {
         }
 
         // Load data into RAM buffer at specified offset
-        self.queue
-            .write_buffer(&self.ram_buffer, offset, &aligned_data);
+        self.write_ram(offset, &aligned_data);
 
         // Extract entry point from the loaded data
         // Pixel 1 (bytes 4-7) contains the 32-bit entry point
@@ -920,6 +1318,7 @@ impl RiscvExecutor {
             let final_entry = if entry_point == 0 { 0x400 } else { entry_point };
 
             self.uniforms.pc = final_entry;
+            self.sync_core_pc();
             info!("Entry point recognized: 0x{:08x}", final_entry);
         }
 
@@ -930,6 +1329,51 @@ impl RiscvExecutor {
         Ok(())
     }
 
+    /// Load a static RV32/RV64 ELF executable and jump to `e_entry`
+    ///
+    /// `PT_LOAD` segments are copied to RAM at their physical address with
+    /// .bss zeroed, and the register width is switched to match the ELF
+    /// class. Nothing is loaded if a segment or the entry point lies outside
+    /// RAM.
+    pub fn load_elf(&mut self, bytes: &[u8]) -> Result<LoadedElf, RiscvError> {
+        let image = ElfImage::parse(bytes)?;
+        let ram_size = self.ram_buffer.size();
+        image.check_entry(ram_size)?;
+        for segment in &image.segments {
+            // GPU buffer writes must be 4-byte aligned
+            if !segment.addr.is_multiple_of(4) {
+                return Err(RiscvError::InvalidElf("segment is not 4-byte aligned"));
+            }
+            if segment.addr.saturating_add(segment.mem_size) > ram_size {
+                return Err(RiscvError::MemoryFault {
+                    addr: segment.addr,
+                    len: segment.mem_size as usize,
+                });
+            }
+        }
+
+        self.set_xlen(image.xlen);
+        for segment in &image.segments {
+            let mut data = segment.data.to_vec();
+            data.resize(segment.mem_size.next_multiple_of(4) as usize, 0);
+            self.write_ram(segment.addr, &data);
+        }
+
+        self.uniforms.pc = image.entry as u32;
+        self.sync_core_pc();
+        self.program_loaded = true;
+        self.uniforms.status = 1; // Running
+
+        let loaded = image.loaded();
+        info!(
+            "Loaded RV{} ELF: entry 0x{:x}, segments {:x?}",
+            loaded.xlen.bits(),
+            loaded.entry,
+            loaded.segments
+        );
+        Ok(loaded)
+    }
+
     /// Phase 43: Set VM ID for multi-VM execution (0-7)
     pub fn set_vm_id(&mut self, vm_id: u32) {
         self.uniforms.vm_id = vm_id;
@@ -951,8 +1395,7 @@ impl RiscvExecutor {
         }
 
         // Write to RAM buffer
-        self.queue
-            .write_buffer(&self.ram_buffer, offset, &aligned_data);
+        self.write_ram(offset, &aligned_data);
 
         info!("Binary loaded successfully");
         Ok(())
@@ -961,6 +1404,7 @@ impl RiscvExecutor {
     /// Set Program Counter directly
     pub fn set_pc(&mut self, pc: u32) {
         self.uniforms.pc = pc;
+        self.sync_core_pc();
         self.program_loaded = true;
         self.uniforms.status = 1; // Running
         info!("PC set to 0x{:08x}", pc);
@@ -979,6 +1423,9 @@ impl RiscvExecutor {
         if !self.program_loaded || self.uniforms.status & 1 == 0 {
             return; // Not running
         }
+        if self.single_step {
+            return; // Advanced by step()
+        }
 
         // Neuromodulated instruction budget
         let base_budget = 10000u32;
@@ -993,16 +1440,47 @@ impl RiscvExecutor {
             1.0
         };
 
-        self.uniforms.instruction_count =
-            (base_budget as f32 * dopamine_multiplier * urgency_throttle) as u32;
+        self.run_batch((base_budget as f32 * dopamine_multiplier * urgency_throttle) as u32);
+    }
+
+    /// Run up to `budget` instructions where the program lives, the shader
+    /// or the CPU hart
+    fn run_batch(&mut self, budget: u32) {
+        self.uniforms.instruction_count = budget;
+        if self.gpu_trace.is_some() && self.cpu_core.is_none() {
+            // Every instruction of a traced frame must fit in the trace slots
+            self.uniforms.instruction_count = self.uniforms.instruction_count.min(TRACE_SLOTS);
+        }
 
         // Update uniforms
         self.uniforms.cycle_count += 1;
+
+        if self.cpu_core.is_some() {
+            self.execute_frame_cpu();
+            return;
+        }
+        self.dispatch();
+    }
+
+    /// Run the shader for `uniforms.instruction_count` instructions and read
+    /// back the PC, status, console output and syscalls
+    fn dispatch(&mut self) {
         self.queue.write_buffer(
             &self.uniform_buffer,
             0,
             bytemuck::cast_slice(&[self.uniforms]),
         );
+        // Open (or close) the trace slots for this dispatch
+        let trace_slots = if self.gpu_trace.is_some() {
+            TRACE_SLOTS
+        } else {
+            0
+        };
+        self.queue.write_buffer(
+            &self.stats_buffer,
+            std::mem::offset_of!(RiscvStats, trace_slots) as u64,
+            bytemuck::cast_slice(&[trace_slots, 0]),
+        );
 
         // Create command encoder
         let mut encoder = self
@@ -1068,6 +1546,7 @@ impl RiscvExecutor {
         self.queue.submit(std::iter::once(encoder.finish()));
 
         // Sync Read-back of stats to update PC for next frame
+        let mut trace_len = 0;
         {
             let buffer_slice = self.stats_staging_buffer.slice(..);
             let (tx, rx) = std::sync::mpsc::channel();
@@ -1083,6 +1562,7 @@ impl RiscvExecutor {
 
                 // Update PC and status from GPU results
                 self.uniforms.pc = stats.current_pc;
+                trace_len = stats.trace_len.min(TRACE_SLOTS);
 
                 // Check for syscalls and log them
                 if stats.syscall_num != 0 {
@@ -1109,6 +1589,10 @@ impl RiscvExecutor {
             }
         }
 
+        if trace_len > 0 {
+            self.read_back_trace(trace_len);
+        }
+
         // Sync Read-back of console buffer for sys_write output
         {
             let buffer_slice = self.console_staging_buffer.slice(..);
@@ -1142,9 +1626,12 @@ impl RiscvExecutor {
                     }
                 }
 
-                if !output.is_empty() {
-                    info!("Console output: {}", output);
-                    self.console_output.push_str(&output);
+                // The buffer holds everything written so far; keep the new part
+                let new_output = output.get(self.console_len..).unwrap_or_default();
+                if !new_output.is_empty() {
+                    info!("Console output: {}", new_output);
+                    self.console_output.push_str(new_output);
+                    self.console_len = output.len();
                 }
 
                 drop(data);
@@ -1245,6 +1732,49 @@ impl RiscvExecutor {
         }
     }
 
+    /// Move the first `len` trace slots the shader filled into the trace
+    fn read_back_trace(&mut self, len: u32) {
+        let slot_size = std::mem::size_of::<TraceSlot>();
+        let bytes = self.read_back_buffer(
+            &self.stats_buffer,
+            std::mem::size_of::<RiscvStats>() as u64,
+            (slot_size * len as usize) as u64,
+        );
+        let Some(trace) = self.gpu_trace.as_mut() else {
+            return;
+        };
+        for chunk in bytes.chunks_exact(slot_size) {
+            let slot: TraceSlot = bytemuck::pod_read_unaligned(chunk);
+            trace.push(TraceEntry {
+                pc: slot.pc as u64,
+                word: slot.word,
+                reg_write: (slot.reg != 0).then_some((slot.reg as usize, slot.value as u64)),
+                xlen: XLen::Rv32,
+            });
+        }
+    }
+
+    /// Run one frame's instruction budget on the CPU hart
+    fn execute_frame_cpu(&mut self) {
+        let Some(core) = self.cpu_core.as_mut() else {
+            return;
+        };
+        let result = core.run(self.uniforms.instruction_count);
+        self.uniforms.pc = core.pc() as u32;
+
+        match result {
+            Ok(_) if core.is_halted() => {
+                self.uniforms.status = 2;
+                info!("RISC-V CPU hart halted at PC: 0x{:08x}", core.pc());
+            },
+            Ok(_) => {},
+            Err(e) => {
+                self.uniforms.status = 4;
+                log::warn!("RISC-V CPU hart stopped: {}", e);
+            },
+        }
+    }
+
     /// Check if VM is still running
     pub fn is_running(&self) -> bool {
         self.program_loaded && (self.uniforms.status & 1) != 0
@@ -1332,7 +1862,7 @@ impl RiscvExecutor {
         self.console_output.clear();
 
         // Clear RAM
-        let zeros = vec![0u8; (self.texture_size * self.texture_size * 4) as usize];
+        let zeros = vec![0u8; self.ram_buffer.size() as usize];
         self.queue.write_buffer(&self.ram_buffer, 0, &zeros);
 
         // Clear console buffer
@@ -1345,6 +1875,14 @@ impl RiscvExecutor {
         self.queue
             .write_buffer(&self.keyboard_buffer, 0, &keyboard_zeros);
 
+        self.single_step = false;
+        self.console_len = 0;
+        self.cpu_core = match self.xlen {
+            XLen::Rv32 => None,
+            XLen::Rv64 => Some(self.new_cpu_core()),
+        };
+        self.gpu_trace = self.trace_capacity.map(TraceBuffer::new);
+
         self.program_loaded = false;
     }
 
@@ -1369,6 +1907,111 @@ impl RiscvExecutor {
     }
 }
 
+/// Lets `riscv::GdbStub` debug the executor; every request pauses
+/// `execute_frame` until gdb detaches and works on the program where it runs
+/// (GPU RAM for rv32 code, the CPU hart otherwise).
+impl GdbTarget for RiscvExecutor {
+    fn xlen(&self) -> XLen {
+        self.xlen
+    }
+
+    fn read_registers(&mut self) -> Result<([u64; 32], u64), RiscvError> {
+        self.pause_for_debugger()?;
+        if let Some(core) = self.cpu_core.as_ref() {
+            return Ok((*core.registers(), core.pc()));
+        }
+        let registers = self.read_gpu_registers().map(u64::from);
+        Ok((registers, self.uniforms.pc as u64))
+    }
+
+    fn write_registers(&mut self, regs: &[u64; 32], pc: u64) -> Result<(), RiscvError> {
+        self.pause_for_debugger()?;
+        if let Some(core) = self.cpu_core.as_mut() {
+            for (index, &value) in regs.iter().enumerate() {
+                core.set_reg(index, value);
+            }
+            core.set_pc(pc);
+            self.uniforms.pc = core.pc() as u32;
+            return Ok(());
+        }
+        let registers: Vec<u8> = std::iter::once(0)
+            .chain(regs[1..].iter().map(|&reg| reg as u32))
+            .flat_map(u32::to_le_bytes)
+            .collect();
+        self.queue.write_buffer(&self.ram_buffer, 0, &registers);
+        self.uniforms.pc = pc as u32;
+        Ok(())
+    }
+
+    fn read_memory(&mut self, addr: u64, len: usize) -> Result<Vec<u8>, RiscvError> {
+        self.pause_for_debugger()?;
+        match self.cpu_core.as_ref() {
+            Some(core) => core.read_memory(addr, len).map(|bytes| bytes.to_vec()),
+            None => self.read_gpu_memory(addr, len),
+        }
+    }
+
+    fn write_memory(&mut self, addr: u64, data: &[u8]) -> Result<(), RiscvError> {
+        self.pause_for_debugger()?;
+        match self.cpu_core.as_mut() {
+            Some(core) => core.load(addr, data),
+            None => self.write_gpu_memory(addr, data),
+        }
+    }
+
+    fn step(&mut self) -> Result<StepResult, RiscvError> {
+        RiscvExecutor::step(self)
+    }
+
+    /// rv32 code runs in shader batches like `execute_frame`; the CPU hart
+    /// is cheap to step and checks every instruction
+    fn run(&mut self, breakpoints: &BTreeSet<u64>, budget: u32) -> Result<bool, RiscvError> {
+        if budget == 0 {
+            return Ok(false);
+        }
+        // Step off the current pc first, in case it holds a breakpoint
+        if breakpoints.contains(&RiscvExecutor::step(self)?.next_pc) {
+            return Ok(true);
+        }
+        if self.cpu_core.is_none() {
+            return self.run_gpu_to_breakpoint(breakpoints, budget - 1);
+        }
+        for _ in 1..budget {
+            if breakpoints.contains(&RiscvExecutor::step(self)?.next_pc) {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    fn detach(&mut self) {
+        self.set_single_step(false);
+    }
+}
+
+/// Store made by an rv32 instruction, given the registers it read
+///
+/// Mirrors the shader's SB/SH/SW; any other word stores nothing.
+fn decode_store(word: u32, regs: &[u32; 32]) -> Option<MemoryWrite> {
+    if word & 0x7f != 0x23 {
+        return None;
+    }
+    let len = match (word >> 12) & 0x7 {
+        0 => 1,
+        1 => 2,
+        2 => 4,
+        _ => return None,
+    };
+    let offset = ((word as i32) >> 25 << 5) as u32 | ((word >> 7) & 0x1f);
+    let base = regs[((word >> 15) & 0x1f) as usize];
+    let value = regs[((word >> 20) & 0x1f) as usize] as u64;
+    Some(MemoryWrite {
+        addr: base.wrapping_add(offset) as u64,
+        len,
+        value: value & (u64::MAX >> (64 - 8 * len)),
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1379,6 +2022,19 @@ mod tests {
         assert_eq!(std::mem::size_of::<RiscvUniforms>(), 44);
     }
 
+    #[test]
+    fn test_profiler_entry_describe() {
+        let entry = ProfilerEntry {
+            pc: 0x40,
+            count: 12_000,
+            _pad: [0; 3],
+        };
+        assert_eq!(
+            entry.describe(0x0045_8513),
+            "0x0040: addi a0, a1, 4 (12000 executions)"
+        );
+    }
+
     #[test]
     fn test_riscv_stats_size() {
         assert_eq!(std::mem::size_of::<RiscvStats>(), 64);
@@ -1436,4 +2092,223 @@ mod tests {
         let data = vec![0u8; 10];
         assert!(LinuxBundleHeader::from_bytes(&data).is_none());
     }
+
+    #[test]
+    fn test_decode_store() {
+        let mut regs = [0u32; 32];
+        regs[2] = 0x2000;
+        regs[6] = 0x1234_5678;
+        // sh t1, -2(sp)
+        assert_eq!(
+            decode_store(0xfe61_1f23, &regs),
+            Some(MemoryWrite {
+                addr: 0x1ffe,
+                len: 2,
+                value: 0x5678
+            })
+        );
+        // addi t1, zero, 5
+        assert_eq!(decode_store(0x0050_0313, &regs), None);
+    }
+
+    /// Prints 'G' through the SBI, then stores 10 at 0x100 after a loop;
+    /// retires 23 instructions, ebreak included
+    const LOOP_PROGRAM: [u32; 11] = [
+        0x0010_0893, // addi a7, zero, 1 (SBI console putchar)
+        0x0470_0513, // addi a0, zero, 'G'
+        0x0000_0073, // ecall
+        0x3005_12f3, // csrrw t0, mstatus, a0 (skipped by the shader)
+        0x0000_007b, // custom-3 opcode (skipped)
+        0x0050_0313, // addi t1, zero, 5
+        0x0023_8393, // loop: addi t2, t2, 2
+        0xfff3_0313, // addi t1, t1, -1
+        0xfe03_1ce3, // bne t1, zero, loop
+        0x1070_2023, // sw t2, 0x100(zero)
+        0x0010_0073, // ebreak
+    ];
+
+    /// Executor with `LOOP_PROGRAM` loaded at 0x1000
+    fn load_loop_program(device: &Arc<wgpu::Device>, queue: &Arc<wgpu::Queue>) -> RiscvExecutor {
+        let bytes: Vec<u8> = LOOP_PROGRAM
+            .iter()
+            .flat_map(|word| word.to_le_bytes())
+            .collect();
+        let mut executor = RiscvExecutor::new(Arc::clone(device), Arc::clone(queue));
+        executor.load_binary(&bytes, 0x1000).unwrap();
+        executor.set_pc(0x1000);
+        executor
+    }
+
+    #[test]
+    fn test_stepping_matches_execute_frame() {
+        let Some((device, queue)) = crate::tests::gpu::test_device() else {
+            return;
+        };
+        let load = || load_loop_program(&device, &queue);
+
+        let mut stepped = load();
+        let mut steps = Vec::new();
+        while !stepped.is_halted() && steps.len() < 64 {
+            steps.push(stepped.step().unwrap());
+        }
+        let mut framed = load();
+        framed.execute_frame();
+
+        // Six straight-line instructions, five trips round the loop, sw, ebreak
+        assert_eq!(steps.len(), 6 + 5 * 3 + 2);
+        assert_eq!(steps[2].reg_write, Some((10, 0)));
+        assert_eq!(steps[3].reg_write, None);
+        assert_eq!(steps[8].next_pc, 0x1018);
+        assert_eq!(
+            steps[21].mem_write,
+            Some(MemoryWrite {
+                addr: 0x100,
+                len: 4,
+                value: 10
+            })
+        );
+        for executor in [&mut stepped, &mut framed] {
+            assert!(executor.is_halted());
+            assert_eq!(executor.get_console_output(), "G");
+            let (regs, pc) = executor.read_registers().unwrap();
+            assert_eq!((regs[5], regs[6], regs[7], regs[10]), (0, 0, 10, 0));
+            assert_eq!(pc, 0x1028);
+            assert_eq!(executor.read_memory(0x100, 4).unwrap(), 10u32.to_le_bytes());
+        }
+    }
+
+    #[test]
+    fn test_trace_keeps_program_on_gpu() {
+        let Some((device, queue)) = crate::tests::gpu::test_device() else {
+            return;
+        };
+        let mut traced = load_loop_program(&device, &queue);
+        traced.enable_trace(64);
+        traced.execute_frame();
+        assert!(traced.cpu_core.is_none());
+
+        let trace = traced.take_trace();
+        assert_eq!(trace.len(), 23);
+        assert_eq!(trace[0].pc, 0x1000);
+        assert_eq!(trace[0].reg_write, Some((17, 1)));
+        // The ecall is the shader's SBI putchar and the CSR write is skipped
+        assert_eq!(
+            (trace[2].word, trace[2].reg_write),
+            (0x0000_0073, Some((10, 0)))
+        );
+        assert_eq!(trace[3].reg_write, None);
+        assert_eq!(trace[21].word, 0x1070_2023);
+        assert_eq!((trace[22].pc, trace[22].word), (0x1028, 0x0010_0073));
+        assert!(traced.take_trace().is_empty());
+
+        let mut untraced = load_loop_program(&device, &queue);
+        untraced.execute_frame();
+        for executor in [&mut traced, &mut untraced] {
+            assert!(executor.is_halted());
+            assert_eq!(executor.get_console_output(), "G");
+        }
+        assert_eq!(traced.read_gpu_registers(), untraced.read_gpu_registers());
+        assert_eq!(traced.uniforms.pc, untraced.uniforms.pc);
+        assert_eq!(
+            traced.read_gpu_memory(0, 0x2000).unwrap(),
+            untraced.read_gpu_memory(0, 0x2000).unwrap()
+        );
+
+        // A small trace keeps the newest instructions
+        let mut short = load_loop_program(&device, &queue);
+        short.enable_trace(4);
+        short.execute_frame();
+        let trace = short.take_trace();
+        assert_eq!(trace.len(), 4);
+        assert_eq!(trace[3].pc, 0x1028);
+    }
+
+    #[test]
+    fn test_gdb_target_on_gpu() {
+        let Some((device, queue)) = crate::tests::gpu::test_device() else {
+            return;
+        };
+        let program: [u32; 4] = [
+            0x0010_0893, // addi a7, zero, 1 (SBI console putchar)
+            0x0000_0073, // ecall
+            0x1000_2283, // lw t0, 0x100(zero)
+            0x0010_0073, // ebreak
+        ];
+        let bytes: Vec<u8> = program.iter().flat_map(|word| word.to_le_bytes()).collect();
+        let mut executor = RiscvExecutor::new(device, queue);
+        executor.load_binary(&bytes, 0x1000).unwrap();
+        executor.set_pc(0);
+        assert!(executor.cpu_core.is_none());
+
+        // x0 stays hardwired and the pc comes from the payload
+        let mut regs = [0u64; 32];
+        regs[0] = 0xdead;
+        regs[10] = 'H' as u64;
+        GdbTarget::write_registers(&mut executor, &regs, 0x1000).unwrap();
+        GdbTarget::write_memory(&mut executor, 0x101, &[0xaa, 0xbb]).unwrap();
+        assert_eq!(
+            GdbTarget::read_memory(&mut executor, 0x100, 4).unwrap(),
+            [0x00, 0xaa, 0xbb, 0x00]
+        );
+        assert!(GdbTarget::read_memory(&mut executor, 0xffff_fff0, 4).is_err());
+
+        let steps: Vec<StepResult> = (0..3)
+            .map(|_| GdbTarget::step(&mut executor).unwrap())
+            .collect();
+        // The ecall is the shader's SBI putchar, not a trap
+        assert_eq!(&*steps[1].mnemonic, "ecall");
+        assert_eq!(steps[1].reg_write, Some((10, 0)));
+        assert_eq!(steps[2].reg_write, Some((5, 0x00bb_aa00)));
+        assert_eq!(executor.get_console_output(), "H");
+        assert!(executor.cpu_core.is_none());
+
+        let (regs, pc) = GdbTarget::read_registers(&mut executor).unwrap();
+        assert_eq!((regs[0], regs[5], regs[10]), (0, 0x00bb_aa00, 0));
+        assert_eq!(pc, 0x100c);
+    }
+
+    #[test]
+    fn test_gdb_run_batches_stop_at_breakpoints_on_gpu() {
+        let Some((device, queue)) = crate::tests::gpu::test_device() else {
+            return;
+        };
+        let mut executor = load_loop_program(&device, &queue);
+        // Inside the loop, after `addi t2, t2, 2`
+        let breakpoints = BTreeSet::from([0x101c]);
+
+        for t2 in [2, 4] {
+            assert!(GdbTarget::run(&mut executor, &breakpoints, 4096).unwrap());
+            assert!(executor.cpu_core.is_none());
+            let (regs, pc) = GdbTarget::read_registers(&mut executor).unwrap();
+            assert_eq!((pc, regs[7]), (0x101c, t2));
+            assert!(executor.is_running());
+        }
+        // The patched word is gone again
+        assert_eq!(executor.read_instruction(0x101c), Some(LOOP_PROGRAM[7]));
+
+        assert!(!GdbTarget::run(&mut executor, &BTreeSet::new(), 4096).unwrap());
+        assert!(executor.is_halted());
+        assert_eq!(executor.read_memory(0x100, 4).unwrap(), 10u32.to_le_bytes());
+        assert!(GdbTarget::run(&mut executor, &breakpoints, 4096).is_err());
+
+        assert!(executor.single_step());
+        GdbTarget::detach(&mut executor);
+        assert!(!executor.single_step());
+    }
+
+    #[test]
+    fn test_executor_shader_validates() {
+        for &format in DISPLAY_FORMATS {
+            let source = display_shader_source(format);
+            let module = naga::front::wgsl::parse_str(&source).unwrap();
+            naga::valid::Validator::new(
+                naga::valid::ValidationFlags::all(),
+                naga::valid::Capabilities::all(),
+            )
+            .validate(&module)
+            .unwrap();
+        }
+        assert!(display_shader_source(wgpu::TextureFormat::Rgba16Float)
+            .contains("texture_storage_2d<rgba16float, write>"));
+    }
 }
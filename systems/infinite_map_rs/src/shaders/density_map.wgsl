@@ -68,7 +68,7 @@ fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
     let center = vec2<f32>(0.5, 0.5);
     let dist = distance(in.uv, center);
     let vignette = 1.0 - smoothstep(0.3, 0.7, dist);
-    final_color.rgb *= vignette;
+    final_color = vec4<f32>(final_color.rgb * vignette, final_color.a);
 
     return final_color;
 }
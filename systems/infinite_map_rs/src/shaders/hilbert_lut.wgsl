@@ -0,0 +1,17 @@
+// ============================================
+// Hilbert LUT Lookup
+// Reads (x, y) for a curve distance from the table produced by
+// HilbertCurve::generate_gpu_lut, so device code shares the Rust
+// reference instead of reimplementing d2xy.
+//
+// Append this snippet to a shader that declares the LUT binding
+// (uploaded with HilbertCurve::create_gpu_lut_buffer):
+//
+//     @group(0) @binding(0) var<storage, read> hilbert_lut: array<u32>;
+//
+// Layout: hilbert_lut[2 * d] = x, hilbert_lut[2 * d + 1] = y
+// ============================================
+
+fn hilbert_d2xy(d: u32) -> vec2<u32> {
+    return vec2<u32>(hilbert_lut[2u * d], hilbert_lut[2u * d + 1u]);
+}
@@ -0,0 +1,34 @@
+// ============================================
+// Hilbert Scatter
+// Moves linear RGBA8 texels to their curve positions through the LUT,
+// producing rows ready for copy_buffer_to_texture.
+//
+// Texel d of `linear` lands at hilbert_d2xy(d); `texels` rows are
+// `row_texels` wide (the copy's padded bytes_per_row / 4).
+//
+// Needs HILBERT_LUT_WGSL appended for hilbert_d2xy.
+// ============================================
+
+struct ScatterParams {
+    count: u32,
+    row_texels: u32,
+    _padding: vec2<u32>,
+}
+
+@group(0) @binding(0) var<storage, read> hilbert_lut: array<u32>;
+@group(0) @binding(1) var<storage, read> linear: array<u32>;
+@group(0) @binding(2) var<storage, read_write> texels: array<u32>;
+@group(0) @binding(3) var<uniform> params: ScatterParams;
+
+@compute @workgroup_size(64)
+fn main(
+    @builtin(global_invocation_id) id: vec3<u32>,
+    @builtin(num_workgroups) groups: vec3<u32>,
+) {
+    let d = id.y * groups.x * 64u + id.x;
+    if (d >= params.count) {
+        return;
+    }
+    let p = hilbert_d2xy(d);
+    texels[p.y * params.row_texels + p.x] = linear[d];
+}
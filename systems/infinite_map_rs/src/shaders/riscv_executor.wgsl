@@ -161,7 +161,7 @@ const WORKGROUP_SIZE: u32 = 16u;  // Number of parallel threads
 var<workgroup> shared_pc: atomic<u32>;              // Shared program counter
 var<workgroup> shared_lock: atomic<u32>;            // Spinlock for PC access
 var<workgroup> shared_instruction_count: u32;       // Total instructions to execute
-var<workgroup> shared_instructions_executed: u32;    // Instructions completed this frame
+var<workgroup> shared_instructions_executed: atomic<u32>;   // Instructions completed this frame
 var<workgroup> shared_halt_flag: u32;               // Halt signal from any thread
 
 // Performance counters for multi-workgroup metrics
@@ -184,7 +184,7 @@ fn pc_to_profiler_slot(pc: u32) -> u32 {
 
 // Record basic block execution
 fn record_block_execution(pc: u32) {
-    if (!PROFILER_ENABLED) { return; }
+    if (PROFILER_ENABLED == 0u) { return; }
 
     let slot = pc_to_profiler_slot(pc);
 
@@ -199,7 +199,7 @@ fn record_block_execution(pc: u32) {
 
 // Check if this block is hot (should be JIT compiled)
 fn is_block_hot(pc: u32) -> bool {
-    if (!PROFILER_ENABLED) { return false; }
+    if (PROFILER_ENABLED == 0u) { return false; }
 
     let slot = pc_to_profiler_slot(pc);
     let count = atomicLoad(&profiler_blocks[slot].count);
@@ -215,6 +215,7 @@ fn read_u32(addr: u32) -> u32 {
 
 // Write a 32-bit word to RAM
 fn write_u32(addr: u32, value: u32) {
+    if addr >= 0x40000000u && addr < 0x40100000u {
         // MMIO: Write to display (512x512 = 262144 words = 1048576 bytes)
         let offset = (addr - 0x40000000u) / 4u;
         let x = offset % 512u;
@@ -228,6 +229,7 @@ fn write_u32(addr: u32, value: u32) {
         
         textureStore(display_write, vec2<i32>(i32(x), i32(y)), vec4<f32>(r, g, b, a));
         return;
+    }
 
     let word_idx = addr / 4u;
     ram_buffer[word_idx] = value;
@@ -276,11 +278,15 @@ fn read_reg(reg_idx: u32) -> u32 {
     return read_u32(addr);
 }
 
+// Register written by the instruction being executed (0 = none), for the trace
+var<private> traced_reg: u32;
+
 // Write register (x0 is read-only)
 fn write_reg(reg_idx: u32, value: u32) {
     if reg_idx == 0u {
         return;  // x0 is always 0
     }
+    traced_reg = reg_idx;
     let addr = REGISTER_BASE + reg_idx * REGISTER_SIZE;
     write_u32(addr, value);
 }
@@ -553,7 +559,7 @@ fn fp_min(a: u32, b: u32) -> u32 {
     }
     let fa = bitcast<f32>(a);
     let fb = bitcast<f32>(b);
-    let result = select(fa < fb, fa, fb);
+    let result = select(fb, fa, fa < fb);
     return bitcast<u32>(result);
 }
 
@@ -567,7 +573,7 @@ fn fp_max(a: u32, b: u32) -> u32 {
     }
     let fa = bitcast<f32>(a);
     let fb = bitcast<f32>(b);
-    let result = select(fa > fb, fa, fb);
+    let result = select(fb, fa, fa > fb);
     return bitcast<u32>(result);
 }
 
@@ -578,7 +584,7 @@ fn fp_feq(a: u32, b: u32) -> u32 {
     }
     let fa = bitcast<f32>(a);
     let fb = bitcast<f32>(b);
-    return select(fa == fb, 1u, 0u);
+    return select(0u, 1u, fa == fb);
 }
 
 // FP Compare Less Than
@@ -588,7 +594,7 @@ fn fp_flt(a: u32, b: u32) -> u32 {
     }
     let fa = bitcast<f32>(a);
     let fb = bitcast<f32>(b);
-    return select(fa < fb, 1u, 0u);
+    return select(0u, 1u, fa < fb);
 }
 
 // FP Compare Less Than or Equal
@@ -598,7 +604,7 @@ fn fp_fle(a: u32, b: u32) -> u32 {
     }
     let fa = bitcast<f32>(a);
     let fb = bitcast<f32>(b);
-    return select(fa <= fb, 1u, 0u);
+    return select(0u, 1u, fa <= fb);
 }
 
 // Convert signed integer to float
@@ -630,6 +636,17 @@ const SYS_EXIT: u32 = 93u;    // Linux sys_exit
 const CONSOLE_BUFFER_SIZE: u32 = 256u;
 @group(0) @binding(5) var<storage, read_write> console_buffer: array<u32>;
 
+// Instruction trace: the host sets trace_slots (0 = off) and clears
+// trace_len before each dispatch, then reads back trace[0..trace_len]
+const TRACE_SLOTS: u32 = 4096u;
+
+struct TraceSlot {
+    pc: u32,
+    word: u32,
+    reg: u32,    // Register written (0 = none)
+    value: u32,  // Its new value
+};
+
 struct RiscvStats {
     cycles_executed: u32,
     instructions_executed: u32,
@@ -640,7 +657,10 @@ struct RiscvStats {
     syscall_arg1: u32,
     syscall_arg2: u32,
     console_pos: u32,
-    _padding: array<u32, 7>,
+    trace_slots: u32,
+    trace_len: u32,
+    _padding: array<u32, 5>,
+    trace: array<TraceSlot, TRACE_SLOTS>,
 };
 
 @group(0) @binding(2) var<storage, read_write> stats: RiscvStats;
@@ -683,9 +703,9 @@ fn handle_syscall(pc: u32) -> u32 {
     let a0 = read_reg(10u);  // a0 = first argument
 
     // SBI Extension IDs
-    const SBI_EXT_0_1_CONSOLE_PUTCHAR: u32 = 0x01u;
-    const SBI_EXT_0_1_CONSOLE_GETCHAR: u32 = 0x02u;
-    const SBI_EXT_BASE: u32 = 0x10u;
+    let SBI_EXT_0_1_CONSOLE_PUTCHAR: u32 = 0x01u;
+    let SBI_EXT_0_1_CONSOLE_GETCHAR: u32 = 0x02u;
+    let SBI_EXT_BASE: u32 = 0x10u;
 
     // Handle SBI console putchar directly (non-blocking)
     if (a7 == SBI_EXT_0_1_CONSOLE_PUTCHAR) {
@@ -1020,11 +1040,11 @@ fn execute_instruction(pc: u32) -> u32 {
 
 // Acquire lock with timeout (prevents deadlock)
 // Returns true if lock acquired, false on timeout
-fn acquire_lock(lock_ptr: ptr<workgroup, atomic<u32>>, max_spins: u32) -> bool {
+fn acquire_lock(max_spins: u32) -> bool {
     for (var spin: u32 = 0u; spin < max_spins; spin = spin + 1u) {
-        // Try to acquire lock (0 -> 1)
-        let result = atomicCompareExchangeWeak(lock_ptr, 0u, 1u);
-        if (result.exchanged) {
+        // Try to acquire lock (0 -> 1); test-and-set, since GLSL backends
+        // can't emit compare-exchange
+        if (atomicExchange(&shared_lock, 1u) == 0u) {
             return true;  // Lock acquired
         }
         // Record contention for profiling
@@ -1036,8 +1056,8 @@ fn acquire_lock(lock_ptr: ptr<workgroup, atomic<u32>>, max_spins: u32) -> bool {
 }
 
 // Release lock
-fn release_lock(lock_ptr: ptr<workgroup, atomic<u32>>) {
-    atomicStore(lock_ptr, 0u);
+fn release_lock() {
+    atomicStore(&shared_lock, 0u);
 }
 
 // ============================================
@@ -1062,7 +1082,7 @@ fn main_riscv(
     if (lid == 0u) {
         atomicStore(&shared_pc, uniforms.pc);
         shared_instruction_count = uniforms.instruction_count;
-        shared_instructions_executed = 0u;
+        atomicStore(&shared_instructions_executed, 0u);
         shared_halt_flag = 0u;
         atomicStore(&shared_lock, 0u);
         atomicStore(&perf_counters.spin_waits, 0u);
@@ -1082,7 +1102,9 @@ fn main_riscv(
     }
 
     // Phase 1: Parallel instruction execution
-    // Each thread processes a strided subset of instructions
+    // Each thread processes a strided subset of instructions. The PC lock is
+    // held while an instruction executes, so the hart stays sequential and
+    // follows jumps and branches.
     for (var i: u32 = lid; i < shared_instruction_count; i = i + WORKGROUP_SIZE) {
         // Early exit if another thread signaled halt
         if (shared_halt_flag != 0u) {
@@ -1090,42 +1112,53 @@ fn main_riscv(
         }
 
         // Acquire PC lock (with timeout to prevent deadlock)
-        let acquired = acquire_lock(&shared_lock, 1000u);
+        let acquired = acquire_lock(1000u);
 
         if (!acquired) {
             // Failed to acquire lock - exit gracefully
             break;
         }
 
-        // Fetch current PC and increment atomically
+        // Another thread may have halted while this one waited
+        if (shared_halt_flag != 0u) {
+            release_lock();
+            break;
+        }
+
         let pc = atomicLoad(&shared_pc);
 
         // Check for halt condition before executing
         if (pc == 0xFFFFFFFFu) {
-            release_lock(&shared_lock);
+            release_lock();
             shared_halt_flag = 1u;
             break;
         }
 
-        // Increment PC for next instruction (fixed 4-byte width for RISC-V)
-        atomicStore(&shared_pc, pc + 4u);
-
-        // Release lock - other threads can now fetch their instructions
-        release_lock(&shared_lock);
-
-        // Execute instruction (independent after PC fetch)
+        // Execute instruction and publish the PC it chose
+        let word = fetch_instruction(pc);
+        traced_reg = 0u;
         let new_pc = execute_instruction(pc);
 
-        // Check for halt (0xFFFFFFFF indicates halt)
+        // Record the retired instruction (the halting one included)
+        let slot = stats.trace_len;
+        if (slot < min(stats.trace_slots, TRACE_SLOTS)) {
+            stats.trace[slot] = TraceSlot(pc, word, traced_reg, read_reg(traced_reg));
+            stats.trace_len = slot + 1u;
+        }
+
+        // Check for halt (0xFFFFFFFF indicates halt); the PC stays on the
+        // halting instruction
         if (new_pc == 0xFFFFFFFFu) {
             shared_halt_flag = 1u;
-            // Update final PC
-            if (lid == 0u) {
-                atomicStore(&shared_pc, 0xFFFFFFFFu);
-            }
+            release_lock();
             break;
         }
 
+        atomicStore(&shared_pc, new_pc);
+
+        // Release lock - the next thread continues from new_pc
+        release_lock();
+
         // Record successful instruction execution
         let executed = atomicAdd(&shared_instructions_executed, 1u);
 
@@ -1154,7 +1187,7 @@ fn main_riscv(
         }
 
         stats.current_pc = final_pc;
-        stats.instructions_executed = shared_instructions_executed;
+        stats.instructions_executed = atomicLoad(&shared_instructions_executed);
         stats.cycles_executed = uniforms.cycle_count;
     }
 }
@@ -0,0 +1,43 @@
+// ============================================
+// Zone Blend Shader
+// Draws an execution zone's output texture as a quad so the
+// pipeline's blend state composites it over the map
+// ============================================
+
+struct ZoneBlendUniforms {
+    // Destination rect in pixels (x, y, width, height)
+    dest_rect: vec4<f32>,
+    // Output target dimensions
+    screen_size: vec2<f32>,
+    // Padding to align total struct to 16 bytes
+    _pad: vec2<f32>,
+}
+
+@group(0) @binding(0)
+var<uniform> uniforms: ZoneBlendUniforms;
+
+@group(0) @binding(1)
+var zone_texture: texture_2d<f32>;
+
+@vertex
+fn vs_main(@builtin(vertex_index) vertex_index: u32) -> @builtin(position) vec4<f32> {
+    var corners = array<vec2<f32>, 6>(
+        vec2<f32>(0.0, 0.0),
+        vec2<f32>(1.0, 0.0),
+        vec2<f32>(0.0, 1.0),
+        vec2<f32>(0.0, 1.0),
+        vec2<f32>(1.0, 0.0),
+        vec2<f32>(1.0, 1.0)
+    );
+
+    // Pixel space (y down) -> clip space (y up)
+    let pixel = uniforms.dest_rect.xy + corners[vertex_index] * uniforms.dest_rect.zw;
+    let ndc = pixel / uniforms.screen_size * 2.0 - 1.0;
+    return vec4<f32>(ndc.x, -ndc.y, 0.0, 1.0);
+}
+
+@fragment
+fn fs_main(@builtin(position) frag_coord: vec4<f32>) -> @location(0) vec4<f32> {
+    let texel = vec2<i32>(floor(frag_coord.xy - uniforms.dest_rect.xy));
+    return textureLoad(zone_texture, texel, 0);
+}
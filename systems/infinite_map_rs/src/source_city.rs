@@ -168,46 +168,13 @@ impl SourceCityLoader {
     }
 
     /// Convert 2D world coordinates to Hilbert distance (inverse of d2xy)
+    ///
+    /// Coordinates outside the grid are clamped to its edge.
     pub fn world_to_hilbert(&self, x: i32, y: i32, n: u32) -> u32 {
-        let mut s = 1u32;
-        let mut d = 0u32;
-        let mut cur_x = x as u32;
-        let mut cur_y = y as u32;
-
-        while s < n {
-            let mask = s - 1;
-            let rx = cur_x & mask;
-            let ry = cur_y & mask;
-
-            // Rotate/flip quadrant
-            d += s
-                * s
-                * match (rx, ry) {
-                    (0, 0) => 0,
-                    (1, 0) => 1,
-                    (1, 1) => 2,
-                    (0, 1) => 3,
-                    _ => 0,
-                };
-
-            cur_x = if ry == 0 {
-                if rx == 1 {
-                    s - 1 - cur_x
-                } else {
-                    cur_x
-                }
-            } else {
-                cur_x
-            };
-
-            std::mem::swap(&mut cur_x, &mut cur_y);
-            cur_x += if ry == 0 && rx == 1 { s } else { 0 };
-            cur_y += if ry == 0 && rx == 1 { s } else { 0 };
-
-            s *= 2;
-        }
-
-        d
+        let max = n.saturating_sub(1) as i32;
+        let x = x.clamp(0, max) as u32;
+        let y = y.clamp(0, max) as u32;
+        crate::hilbert::xy2d(n, x, y) as u32
     }
 }
 
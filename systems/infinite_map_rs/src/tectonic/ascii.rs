@@ -29,6 +29,11 @@ impl TectonicAsciiRenderer {
         self.write_file("tectonic_activity.ascii", &content)
     }
 
+    /// Emit tectonic_activity.svg next to the ASCII monitor
+    pub fn emit_svg(&self, svg: &str) -> Result<(), String> {
+        self.write_file("tectonic_activity.svg", svg)
+    }
+
     fn render(&self, state: &TectonicState) -> String {
         let mut lines = Vec::new();
 
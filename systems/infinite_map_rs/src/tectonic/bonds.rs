@@ -2,9 +2,14 @@
 
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::time::{Duration, Instant};
 
 use super::TileId;
 
+/// Smallest volume strengths are normalized against (avoids division by
+/// zero, and lets bonds that have all but faded away fall below thresholds)
+const MIN_PEAK_VOLUME: f64 = 1.0;
+
 /// Type of cognitive bond between tiles
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub enum BondType {
@@ -36,10 +41,13 @@ pub struct CognitiveBondGraph {
     /// Track pulse types per edge for bond classification
     edge_types: HashMap<(TileId, TileId), BondType>,
 
+    /// When each edge's volume was last pulsed or decayed
+    edge_updated: HashMap<(TileId, TileId), Instant>,
+
     /// Total pulse volume per tile (for normalization)
     tile_volume: HashMap<TileId, f64>,
 
-    /// Maximum volume seen (for normalization)
+    /// Volume of the strongest bond (for normalization)
     max_volume: f64,
 }
 
@@ -48,8 +56,9 @@ impl CognitiveBondGraph {
         Self {
             edges: HashMap::new(),
             edge_types: HashMap::new(),
+            edge_updated: HashMap::new(),
             tile_volume: HashMap::new(),
-            max_volume: 1.0, // Avoid division by zero
+            max_volume: MIN_PEAK_VOLUME,
         }
     }
 
@@ -66,6 +75,7 @@ impl CognitiveBondGraph {
         let entry = self.edges.entry(edge_key).or_insert((0.0, 0));
         entry.0 += volume;
         entry.1 += 1;
+        self.edge_updated.insert(edge_key, Instant::now());
 
         // Track pulse type
         let type_entry = self.edge_types.entry(edge_key).or_insert(if is_cognitive {
@@ -133,8 +143,55 @@ impl CognitiveBondGraph {
     pub fn clear(&mut self) {
         self.edges.clear();
         self.edge_types.clear();
+        self.edge_updated.clear();
+        self.tile_volume.clear();
+        self.max_volume = MIN_PEAK_VOLUME;
+    }
+
+    /// Fade every bond by how long it has gone without pulses
+    ///
+    /// A bond's volume halves every `half_life` since it was last pulsed (or
+    /// last decayed). Strengths are then renormalized against the strongest
+    /// remaining bond, so a stale former peak stops weakening fresh bonds.
+    pub fn decay(&mut self, half_life: Duration, now: Instant) {
+        if half_life.is_zero() {
+            return;
+        }
+        for (key, (volume, _)) in self.edges.iter_mut() {
+            let Some(updated) = self.edge_updated.get_mut(key) else {
+                continue;
+            };
+            let elapsed = now.saturating_duration_since(*updated);
+            *volume *= 0.5f64.powf(elapsed.as_secs_f64() / half_life.as_secs_f64());
+            *updated = (*updated).max(now);
+        }
+        self.rebuild_totals();
+    }
+
+    /// Drop bonds weaker than `min_strength`, returning how many were removed
+    pub fn prune(&mut self, min_strength: f64) -> usize {
+        let before = self.edges.len();
+        let max_volume = self.max_volume;
+        self.edges
+            .retain(|_, (volume, _)| *volume / max_volume >= min_strength);
+        self.edge_types
+            .retain(|key, _| self.edges.contains_key(key));
+        self.edge_updated
+            .retain(|key, _| self.edges.contains_key(key));
+        self.rebuild_totals();
+        before - self.edges.len()
+    }
+
+    /// Recompute per-tile volume and the normalization peak from the
+    /// (decayed or pruned) edge volumes
+    fn rebuild_totals(&mut self) {
         self.tile_volume.clear();
-        self.max_volume = 1.0;
+        self.max_volume = MIN_PEAK_VOLUME;
+        for (&(source, dest), &(volume, _)) in &self.edges {
+            *self.tile_volume.entry(source).or_insert(0.0) += volume;
+            *self.tile_volume.entry(dest).or_insert(0.0) += volume;
+            self.max_volume = self.max_volume.max(volume);
+        }
     }
 
     /// Get statistics about the bond graph
@@ -224,4 +281,57 @@ mod tests {
         assert_eq!(top_2.len(), 2);
         assert!(top_2[0].strength >= top_2[1].strength);
     }
+
+    #[test]
+    fn test_bond_decays_and_is_pruned() {
+        let mut graph = CognitiveBondGraph::new();
+        graph.add_pulse(0, 1, 10.0, true);
+        let pulsed = Instant::now();
+        let half_life = Duration::from_secs(60);
+
+        // One half-life later the bond has half its volume; as the only
+        // bond it is still the strongest
+        graph.decay(half_life, pulsed + half_life);
+        assert_eq!(graph.prune(0.1), 0);
+        let bonds = graph.get_bonds(0.0);
+        assert!((bonds[0].strength - 1.0).abs() < 0.01);
+        assert!((graph.get_tile_volume(0) - 5.0).abs() < 0.1);
+
+        // Decaying again only counts the time since the last decay
+        graph.decay(half_life, pulsed + half_life);
+        assert!((graph.get_tile_volume(0) - 5.0).abs() < 0.1);
+
+        // Eight half-lives in, it has faded to almost nothing and goes away
+        graph.decay(half_life, pulsed + half_life * 8);
+        assert!(graph.get_bonds(0.0)[0].strength < 0.1);
+        assert_eq!(graph.prune(0.1), 1);
+        assert!(graph.get_bonds(0.0).is_empty());
+        assert!(graph.active_tiles().is_empty());
+    }
+
+    #[test]
+    fn test_stale_peak_does_not_weaken_fresh_bond() {
+        let mut graph = CognitiveBondGraph::new();
+        graph.add_pulse(0, 1, 1000.0, true);
+        let half_life = Duration::from_secs(60);
+
+        // The former peak fades to about 1 while nothing pulses it
+        graph.decay(half_life, Instant::now() + half_life * 10);
+        graph.add_pulse(2, 3, 50.0, true);
+
+        let strength = |graph: &CognitiveBondGraph, tile| {
+            graph
+                .get_bonds(0.0)
+                .into_iter()
+                .find(|bond| bond.source == tile)
+                .map(|bond| bond.strength)
+        };
+        assert!((strength(&graph, 2).unwrap() - 1.0).abs() < 0.01);
+        assert!(strength(&graph, 0).unwrap() < 0.1);
+
+        // Pruning keeps the busy bond and drops the stale one
+        assert_eq!(graph.prune(0.1), 1);
+        assert_eq!(strength(&graph, 0), None);
+        assert!((strength(&graph, 2).unwrap() - 1.0).abs() < 0.01);
+    }
 }
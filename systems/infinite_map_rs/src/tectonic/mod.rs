@@ -14,14 +14,16 @@
 pub mod ascii;
 pub mod bonds;
 pub mod constraints;
+mod quadtree;
 pub mod simulator;
 pub mod solver;
+pub mod svg;
 
 pub use ascii::TectonicAsciiRenderer;
 pub use bonds::{BondType, CognitiveBond, CognitiveBondGraph};
 pub use constraints::HilbertConstraint;
 pub use simulator::{LayoutDelta, PulseEvent, TectonicSimulator};
-pub use solver::ForceDirectedSolver;
+pub use solver::{ForceDirectedSolver, SolverMode};
 
 use std::path::PathBuf;
 
@@ -46,9 +48,21 @@ pub struct TectonicConfig {
     /// Minimum bond strength to consider for realignment
     pub min_bond_strength: f64,
 
+    /// Seconds for an unpulsed bond to lose half its strength (0 = never fade)
+    pub bond_half_life_secs: u64,
+
     /// Hilbert constraint strength (0.0 = no constraint, 1.0 = full)
     pub hilbert_strength: f64,
 
+    /// Repulsion algorithm; `BarnesHut` keeps large bond graphs fast
+    pub solver_mode: SolverMode,
+
+    /// Barnes-Hut accuracy (0.0 = exact, larger = faster and coarser)
+    pub barnes_hut_theta: f64,
+
+    /// Solver seed; equal seeds give identical layouts for the same bonds
+    pub seed: u64,
+
     /// Output directory for ASCII files
     pub ascii_output_dir: PathBuf,
 }
@@ -60,7 +74,11 @@ impl Default for TectonicConfig {
             ideal_spacing: 512.0,
             max_movement: 256.0,
             min_bond_strength: 0.1,
+            bond_half_life_secs: 300,
             hilbert_strength: 0.5,
+            solver_mode: SolverMode::Exact,
+            barnes_hut_theta: 0.5,
+            seed: 0,
             ascii_output_dir: PathBuf::from(".geometry/ascii_scene"),
         }
     }
@@ -0,0 +1,169 @@
+//! Quadtree for the Barnes-Hut repulsion approximation.
+//!
+//! Every cell records how many tiles lie under it and their center of mass,
+//! so a distant cluster of tiles can push on a tile as a single body. Cells
+//! live in one array in depth-first order, each knowing where its subtree
+//! ends, so a query is a single forward walk with no stack or pointers.
+
+use std::ops::Range;
+
+use super::Coord;
+
+/// Cells stop splitting at this depth, so coincident tiles share a leaf
+const MAX_DEPTH: u32 = 32;
+
+/// Cells with this many tiles or fewer are not split
+const LEAF_CAPACITY: usize = 8;
+
+struct Cell {
+    center_of_mass: Coord,
+    /// Number of tiles under the cell
+    mass: f64,
+    /// Squared cell width, for the opening test
+    width_sq: f64,
+    center: Coord,
+    half_size: f64,
+    /// Index of the first cell after this one's subtree
+    skip: usize,
+    /// A leaf's tiles, as a range of `QuadTree::sorted`; empty for cells
+    /// that are followed by their children instead
+    bodies: Range<usize>,
+}
+
+impl Cell {
+    fn contains(&self, p: Coord) -> bool {
+        (p.0 - self.center.0).abs() <= self.half_size
+            && (p.1 - self.center.1).abs() <= self.half_size
+    }
+}
+
+/// Child slot (0-3) of a cell centered at `center` whose quadrant holds `p`
+fn quadrant(center: Coord, p: Coord) -> usize {
+    (p.0 >= center.0) as usize | ((p.1 >= center.1) as usize) << 1
+}
+
+pub(super) struct QuadTree<'a> {
+    cells: Vec<Cell>,
+    /// The points, reordered so every leaf's tiles are contiguous
+    sorted: Vec<Coord>,
+    points: &'a [Coord],
+}
+
+impl<'a> QuadTree<'a> {
+    pub fn build(points: &'a [Coord]) -> Self {
+        let (mut min, mut max) = ((f64::MAX, f64::MAX), (f64::MIN, f64::MIN));
+        for p in points {
+            min = (min.0.min(p.0), min.1.min(p.1));
+            max = (max.0.max(p.0), max.1.max(p.1));
+        }
+        let center = ((min.0 + max.0) / 2.0, (min.1 + max.1) / 2.0);
+        let half_size = (max.0 - min.0).max(max.1 - min.1) / 2.0 + 1.0;
+
+        let mut sorted = points.to_vec();
+        let mut tree = Self {
+            cells: Vec::with_capacity(points.len() + 1),
+            sorted: Vec::new(),
+            points,
+        };
+        tree.add_cell(&mut sorted, 0, center, half_size, 0);
+        tree.sorted = sorted;
+        tree
+    }
+
+    /// Append the cell holding `bodies` (which start at `offset` in the
+    /// final order), then its subtree
+    fn add_cell(
+        &mut self,
+        bodies: &mut [Coord],
+        offset: usize,
+        center: Coord,
+        half_size: f64,
+        depth: u32,
+    ) {
+        let mut moment = (0.0, 0.0);
+        for body in bodies.iter() {
+            moment.0 += body.0;
+            moment.1 += body.1;
+        }
+        let mass = bodies.len() as f64;
+        let leaf = bodies.len() <= LEAF_CAPACITY || depth >= MAX_DEPTH;
+        let range = offset..offset + if leaf { bodies.len() } else { 0 };
+
+        let index = self.cells.len();
+        self.cells.push(Cell {
+            center_of_mass: (moment.0 / mass.max(1.0), moment.1 / mass.max(1.0)),
+            mass,
+            width_sq: 4.0 * half_size * half_size,
+            center,
+            half_size,
+            skip: 0,
+            bodies: range,
+        });
+
+        if !leaf {
+            // Group the tiles by quadrant and give each non-empty one a child
+            bodies.sort_unstable_by_key(|&body| quadrant(center, body));
+            let quarter = half_size / 2.0;
+            let mut start = 0;
+            for (slot, (dx, dy)) in [(-1.0, -1.0), (1.0, -1.0), (-1.0, 1.0), (1.0, 1.0)]
+                .into_iter()
+                .enumerate()
+            {
+                let count = bodies[start..]
+                    .iter()
+                    .take_while(|&&body| quadrant(center, body) == slot)
+                    .count();
+                if count > 0 {
+                    let child = (center.0 + dx * quarter, center.1 + dy * quarter);
+                    let end = start + count;
+                    let child_bodies = &mut bodies[start..end];
+                    self.add_cell(child_bodies, offset + start, child, quarter, depth + 1);
+                    start = end;
+                }
+            }
+        }
+        self.cells[index].skip = self.cells.len();
+    }
+
+    /// Net push on point `index` from every other point, where a body at
+    /// distance `d` (at least 0.1) pushes away with magnitude `strength / d`
+    ///
+    /// A cell that looks smaller than `theta` (cell width / distance) from
+    /// the point acts as one body at its center of mass; `theta = 0` visits
+    /// every tile and gives the exact sum.
+    pub fn repulsion(&self, index: usize, theta: f64, strength: f64) -> Coord {
+        let p = self.points[index];
+        let theta_sq = theta * theta;
+        let mut total = (0.0, 0.0);
+        // (dx / d) * (strength / d), which needs no square root
+        let mut push_from = |source: Coord, mass: f64| {
+            let (dx, dy) = (source.0 - p.0, source.1 - p.1);
+            let scale = mass * strength / (dx * dx + dy * dy).max(0.01);
+            total.0 -= dx * scale;
+            total.1 -= dy * scale;
+        };
+
+        let mut next = 0;
+        while let Some(cell) = self.cells.get(next) {
+            if !cell.bodies.is_empty() {
+                // The point itself is among these at distance zero, where it
+                // has no direction to push in and adds nothing
+                for &body in &self.sorted[cell.bodies.clone()] {
+                    push_from(body, 1.0);
+                }
+                next = cell.skip;
+                continue;
+            }
+
+            let (dx, dy) = (cell.center_of_mass.0 - p.0, cell.center_of_mass.1 - p.1);
+            // A cell holding the point itself is always opened
+            if cell.width_sq < theta_sq * (dx * dx + dy * dy) && !cell.contains(p) {
+                push_from(cell.center_of_mass, cell.mass);
+                next = cell.skip;
+            } else {
+                next += 1;
+            }
+        }
+        total
+    }
+}
@@ -8,6 +8,7 @@ use super::ascii::TectonicAsciiRenderer;
 use super::bonds::{CognitiveBond, CognitiveBondGraph};
 use super::constraints::HilbertConstraint;
 use super::solver::ForceDirectedSolver;
+use super::svg::render_svg;
 use super::{Coord, TectonicConfig, TileId};
 
 /// A pulse event from the NeuralPulseSystem
@@ -125,6 +126,9 @@ pub struct TectonicSimulator {
     /// Last realignment timestamp
     last_realignment: Option<Instant>,
 
+    /// When bonds were last decayed
+    last_decay: Instant,
+
     /// Number of realignment cycles completed
     cycle_count: u64,
 }
@@ -137,10 +141,13 @@ impl TectonicSimulator {
             pulse_window: SlidingWindow::new(window_duration, |e| e.timestamp),
             bond_graph: CognitiveBondGraph::new(),
             tile_positions: HashMap::new(),
-            solver: ForceDirectedSolver::new(config.ideal_spacing, config.max_movement),
+            solver: ForceDirectedSolver::new(config.ideal_spacing, config.max_movement)
+                .with_mode(config.solver_mode, config.barnes_hut_theta)
+                .with_seed(config.seed),
             hilbert_constraint: HilbertConstraint::new(config.hilbert_strength),
             ascii_renderer: TectonicAsciiRenderer::new(config.ascii_output_dir.clone()),
             last_realignment: None,
+            last_decay: Instant::now(),
             cycle_count: 0,
             config,
         }
@@ -155,6 +162,27 @@ impl TectonicSimulator {
         let is_cognitive = pulse.pulse_type == "violet";
         self.bond_graph
             .add_pulse(pulse.source, pulse.dest, pulse.volume, is_cognitive);
+
+        let now = Instant::now();
+        if now.duration_since(self.last_decay) >= self.window_duration() {
+            self.decay_bonds(now);
+        }
+    }
+
+    /// Fade bonds that have stopped receiving pulses and prune those below
+    /// `min_bond_strength`
+    ///
+    /// Runs once per aggregation window from `record_pulse` and after each
+    /// realignment.
+    pub fn decay_bonds(&mut self, now: Instant) {
+        let half_life = Duration::from_secs(self.config.bond_half_life_secs);
+        self.bond_graph.decay(half_life, now);
+        self.bond_graph.prune(self.config.min_bond_strength);
+        self.last_decay = now;
+    }
+
+    fn window_duration(&self) -> Duration {
+        Duration::from_secs(self.config.aggregation_window_secs)
     }
 
     /// Set the current position of a tile
@@ -162,6 +190,17 @@ impl TectonicSimulator {
         self.tile_positions.insert(tile, pos);
     }
 
+    /// Place a tile by hand and keep realignment from moving it
+    pub fn pin_tile(&mut self, tile: TileId, at: Coord) {
+        self.tile_positions.insert(tile, at);
+        self.solver.pin_tile(tile, at);
+    }
+
+    /// Let realignment move a pinned tile again
+    pub fn unpin_tile(&mut self, tile: TileId) {
+        self.solver.unpin_tile(tile);
+    }
+
     /// Aggregate pulses into cognitive bonds
     pub fn aggregate_bonds(&self) -> Vec<CognitiveBond> {
         self.bond_graph.get_bonds(self.config.min_bond_strength)
@@ -179,9 +218,15 @@ impl TectonicSimulator {
             self.solver
                 .solve(&self.tile_positions, &bonds, &self.hilbert_constraint);
 
-        // Apply constraints and calculate movements
+        // Apply constraints and calculate movements, in tile order
+        let mut proposed_positions: Vec<_> = proposed_positions.into_iter().collect();
+        proposed_positions.sort_unstable_by_key(|&(tile_id, _)| tile_id);
         let mut movements = Vec::new();
         for (tile_id, new_pos) in &proposed_positions {
+            // Pinned tiles never move, even if pinned away from their position
+            if self.solver.is_pinned(*tile_id) {
+                continue;
+            }
             if let Some(&old_pos) = self.tile_positions.get(tile_id) {
                 let delta =
                     ((new_pos.0 - old_pos.0).powi(2) + (new_pos.1 - old_pos.1).powi(2)).sqrt();
@@ -300,11 +345,13 @@ impl TectonicSimulator {
         self.cycle_count += 1;
         self.last_realignment = Some(Instant::now());
 
-        // Clear bond graph for next window
-        self.bond_graph.clear();
+        // Carry bonds into the next window, fading the idle ones
+        self.decay_bonds(Instant::now());
 
-        // Emit ASCII
+        // Emit ASCII, plus the layout as SVG
         self.ascii_renderer.emit(&self.get_state(&delta))?;
+        self.ascii_renderer
+            .emit_svg(&render_svg(&self.bond_graph, &self.tile_positions))?;
 
         Ok(())
     }
@@ -327,9 +374,7 @@ impl TectonicSimulator {
     pub fn should_realign(&self) -> bool {
         match self.last_realignment {
             None => true,
-            Some(last) => {
-                last.elapsed() >= Duration::from_secs(self.config.aggregation_window_secs)
-            },
+            Some(last) => last.elapsed() >= self.window_duration(),
         }
     }
 
@@ -408,4 +453,97 @@ mod tests {
         let saccade = sim.calculate_saccade_distance(&bonds);
         assert!((saccade - 100.0).abs() < 0.1);
     }
+
+    #[test]
+    fn test_bonds_fade_without_pulses() {
+        let mut sim = TectonicSimulator::new(TectonicConfig {
+            bond_half_life_secs: 10,
+            ..Default::default()
+        });
+        // A lone unit-volume bond is only measured against the minimum peak,
+        // so its strength follows its fading volume
+        sim.record_pulse(PulseEvent {
+            source: 0,
+            dest: 1,
+            pulse_type: "violet".to_string(),
+            volume: 1.0,
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap()
+                .as_millis() as u64,
+        });
+        let pulsed = Instant::now();
+        assert!((sim.aggregate_bonds()[0].strength - 1.0).abs() < 0.01);
+
+        sim.decay_bonds(pulsed + Duration::from_secs(10));
+        let bonds = sim.aggregate_bonds();
+        assert_eq!(bonds.len(), 1);
+        assert!(bonds[0].strength < 0.51);
+
+        // Below `min_bond_strength` (0.1) after four half-lives
+        sim.decay_bonds(pulsed + Duration::from_secs(40));
+        assert!(sim.aggregate_bonds().is_empty());
+    }
+
+    #[test]
+    fn test_realignment_writes_svg_beside_ascii() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut sim = TectonicSimulator::new(TectonicConfig {
+            ascii_output_dir: dir.path().to_path_buf(),
+            ..Default::default()
+        });
+        for tile in 0..3 {
+            sim.set_tile_position(tile, (tile as f64 * 512.0, 0.0));
+        }
+        for (dest, volume) in [(1, 10.0), (2, 0.5)] {
+            sim.record_pulse(PulseEvent {
+                source: 0,
+                dest,
+                pulse_type: "violet".to_string(),
+                volume,
+                timestamp: 0,
+            });
+        }
+
+        let delta = sim.solve_layout();
+        sim.execute_realignment(delta).unwrap();
+
+        assert!(dir.path().join("tectonic_activity.ascii").exists());
+        let svg = std::fs::read_to_string(dir.path().join("tectonic_activity.svg")).unwrap();
+        // The 0.05 bond is under `min_bond_strength` and left out
+        assert_eq!(svg.matches("<line ").count(), 1);
+        assert_eq!(svg.matches("<circle ").count(), 3);
+    }
+
+    #[test]
+    fn test_pinned_tile_has_no_movement() {
+        let mut sim = TectonicSimulator::new(TectonicConfig {
+            hilbert_strength: 0.0,
+            ..Default::default()
+        });
+        for tile in 0..4 {
+            sim.set_tile_position(tile, (tile as f64 * 1000.0, 0.0));
+        }
+        sim.pin_tile(0, (0.0, 0.0));
+        for dest in 1..4 {
+            sim.record_pulse(PulseEvent {
+                source: 0,
+                dest,
+                pulse_type: "violet".to_string(),
+                volume: 10.0,
+                timestamp: SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .unwrap()
+                    .as_millis() as u64,
+            });
+        }
+
+        let delta = sim.solve_layout();
+        assert!(!delta.movements.is_empty());
+        assert!(delta.movements.iter().all(|m| m.tile_id != 0));
+
+        sim.unpin_tile(0);
+        let delta = sim.solve_layout();
+        assert!(delta.movements.iter().any(|m| m.tile_id == 0));
+    }
 }
@@ -1,11 +1,20 @@
 //! Force-Directed Layout Solver for Tectonic Realignment.
 //!
 //! Uses a modified Fruchterman-Reingold algorithm with Hilbert constraints.
+//! Repulsion between every pair of tiles is O(n²) per iteration, so large
+//! graphs can use the Barnes-Hut approximation instead, which is O(n log n).
+//!
+//! Tiles and bonds are visited in id order and the only randomness comes from
+//! a seeded generator, so the same inputs always produce the same layout.
 
 use std::collections::HashMap;
 
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
 use super::bonds::CognitiveBond;
 use super::constraints::HilbertConstraint;
+use super::quadtree::QuadTree;
 use super::{Coord, TileId};
 
 /// Configuration for the solver
@@ -13,6 +22,16 @@ const ITERATIONS: usize = 100;
 const TEMPERATURE_DECAY: f64 = 0.95;
 const MIN_TEMPERATURE: f64 = 0.01;
 
+/// How repulsion between tiles is computed
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SolverMode {
+    /// Every pair of tiles, O(n²)
+    #[default]
+    Exact,
+    /// Quadtree approximation, O(n log n); accuracy set by `theta`
+    BarnesHut,
+}
+
 /// Force-Directed Layout Solver
 pub struct ForceDirectedSolver {
     /// Ideal spacing between tiles
@@ -23,6 +42,18 @@ pub struct ForceDirectedSolver {
 
     /// Initial temperature for simulated annealing
     initial_temperature: f64,
+
+    /// Repulsion algorithm
+    mode: SolverMode,
+
+    /// Barnes-Hut opening angle (cell width / distance); 0 is exact
+    theta: f64,
+
+    /// Tiles held in place; they still push and pull on the others
+    pinned: HashMap<TileId, Coord>,
+
+    /// Seed for the generator that separates tiles sharing a position
+    seed: u64,
 }
 
 impl ForceDirectedSolver {
@@ -31,9 +62,53 @@ impl ForceDirectedSolver {
             k: ideal_spacing,
             max_displacement,
             initial_temperature: ideal_spacing * 0.5,
+            mode: SolverMode::Exact,
+            theta: 0.5,
+            pinned: HashMap::new(),
+            seed: 0,
         }
     }
 
+    /// Seed the solver's random number generator
+    ///
+    /// Solvers with the same seed produce identical layouts from identical
+    /// positions and bonds.
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Select the repulsion algorithm and the Barnes-Hut `theta`
+    ///
+    /// Larger `theta` is faster and less accurate; 0.5 is the usual choice.
+    pub fn with_mode(mut self, mode: SolverMode, theta: f64) -> Self {
+        self.mode = mode;
+        self.theta = theta.max(0.0);
+        self
+    }
+
+    pub fn mode(&self) -> SolverMode {
+        self.mode
+    }
+
+    /// Hold `id` at `at`; the solver will not move it
+    pub fn pin_tile(&mut self, id: TileId, at: Coord) {
+        self.pinned.insert(id, at);
+    }
+
+    /// Let a pinned tile move again
+    pub fn unpin_tile(&mut self, id: TileId) {
+        self.pinned.remove(&id);
+    }
+
+    pub fn is_pinned(&self, id: TileId) -> bool {
+        self.pinned.contains_key(&id)
+    }
+
     /// Solve for optimal tile positions
     pub fn solve(
         &self,
@@ -41,11 +116,28 @@ impl ForceDirectedSolver {
         bonds: &[CognitiveBond],
         constraint: &HilbertConstraint,
     ) -> HashMap<TileId, Coord> {
-        if positions.is_empty() || bonds.is_empty() {
-            return positions.clone();
+        let mut current = positions.clone();
+        for (tile, &at) in &self.pinned {
+            if let Some(pos) = current.get_mut(tile) {
+                *pos = at;
+            }
+        }
+        if current.is_empty() || bonds.is_empty() {
+            return current;
         }
 
-        let mut current = positions.clone();
+        // Sum forces in a fixed order so floating point rounding repeats
+        let mut bonds = bonds.to_vec();
+        bonds.sort_by(|a, b| {
+            (a.source, a.dest)
+                .cmp(&(b.source, b.dest))
+                .then(a.strength.total_cmp(&b.strength))
+        });
+        let bonds = bonds.as_slice();
+
+        let mut rng = StdRng::seed_from_u64(self.seed);
+        self.separate_coincident(&mut current, &mut rng);
+
         let mut temperature = self.initial_temperature;
 
         for _ in 0..ITERATIONS {
@@ -54,6 +146,9 @@ impl ForceDirectedSolver {
 
             // Apply forces with temperature-limited displacement
             for (&tile, &force) in &forces {
+                if self.pinned.contains_key(&tile) {
+                    continue;
+                }
                 if let Some(pos) = current.get_mut(&tile) {
                     // Limit displacement
                     let (dx, dy) = force;
@@ -82,6 +177,27 @@ impl ForceDirectedSolver {
         current
     }
 
+    /// Nudge apart tiles that share a position; with no direction between
+    /// them, repulsion alone could never separate them
+    fn separate_coincident(&self, positions: &mut HashMap<TileId, Coord>, rng: &mut StdRng) {
+        let mut tiles: Vec<TileId> = positions.keys().copied().collect();
+        tiles.sort_unstable();
+
+        let mut seen: Vec<Coord> = Vec::with_capacity(tiles.len());
+        for tile in tiles {
+            let pos = positions[&tile];
+            if seen.contains(&pos) && !self.pinned.contains_key(&tile) {
+                let angle = rng.gen_range(0.0..std::f64::consts::TAU);
+                let radius = self.k * 0.01;
+                let nudged = (pos.0 + radius * angle.cos(), pos.1 + radius * angle.sin());
+                positions.insert(tile, nudged);
+                seen.push(nudged);
+            } else {
+                seen.push(pos);
+            }
+        }
+    }
+
     /// Calculate net forces on all tiles
     fn calculate_forces(
         &self,
@@ -118,38 +234,100 @@ impl ForceDirectedSolver {
         }
 
         // Repulsive forces between all tiles (prevent overcrowding)
-        let tiles: Vec<TileId> = positions.keys().copied().collect();
-        for i in 0..tiles.len() {
-            for j in (i + 1)..tiles.len() {
-                let tile_a = tiles[i];
-                let tile_b = tiles[j];
-
-                if let (Some(&pos_a), Some(&pos_b)) =
-                    (positions.get(&tile_a), positions.get(&tile_b))
-                {
-                    let (dx, dy) = (pos_b.0 - pos_a.0, pos_b.1 - pos_a.1);
-                    let dist = (dx * dx + dy * dy).sqrt().max(0.1);
-
-                    let repulsion = self.repulsion_force(dist);
-
-                    let fx = (dx / dist) * repulsion;
-                    let fy = (dy / dist) * repulsion;
-
-                    if let Some(force_a) = forces.get_mut(&tile_a) {
-                        force_a.0 -= fx;
-                        force_a.1 -= fy;
-                    }
-                    if let Some(force_b) = forces.get_mut(&tile_b) {
-                        force_b.0 += fx;
-                        force_b.1 += fy;
-                    }
-                }
-            }
+        match self.mode {
+            SolverMode::Exact => self.add_exact_repulsion(positions, &mut forces),
+            SolverMode::BarnesHut => self.add_barnes_hut_repulsion(positions, &mut forces),
         }
 
         forces
     }
 
+    /// Pairwise repulsion between every two tiles
+    fn add_exact_repulsion(
+        &self,
+        positions: &HashMap<TileId, Coord>,
+        forces: &mut HashMap<TileId, Coord>,
+    ) {
+        let (tiles, points) = sorted_points(positions);
+        let mut totals = vec![(0.0, 0.0); points.len()];
+
+        for i in 0..points.len() {
+            for j in (i + 1)..points.len() {
+                let (pos_a, pos_b) = (points[i], points[j]);
+                let (dx, dy) = (pos_b.0 - pos_a.0, pos_b.1 - pos_a.1);
+                let dist = (dx * dx + dy * dy).sqrt().max(0.1);
+
+                let repulsion = self.repulsion_force(dist);
+
+                let fx = (dx / dist) * repulsion;
+                let fy = (dy / dist) * repulsion;
+
+                totals[i].0 -= fx;
+                totals[i].1 -= fy;
+                totals[j].0 += fx;
+                totals[j].1 += fy;
+            }
+        }
+
+        for (tile, (fx, fy)) in tiles.iter().zip(totals) {
+            if let Some(force) = forces.get_mut(tile) {
+                force.0 += fx;
+                force.1 += fy;
+            }
+        }
+    }
+
+    /// Repulsion with distant groups of tiles lumped together
+    fn add_barnes_hut_repulsion(
+        &self,
+        positions: &HashMap<TileId, Coord>,
+        forces: &mut HashMap<TileId, Coord>,
+    ) {
+        let (tiles, points) = sorted_points(positions);
+        let tree = QuadTree::build(&points);
+
+        for (index, tile) in tiles.iter().enumerate() {
+            // Same k^2 / d falloff as `repulsion_force`
+            let (fx, fy) = tree.repulsion(index, self.theta, self.k * self.k);
+            if let Some(force) = forces.get_mut(tile) {
+                force.0 += fx;
+                force.1 += fy;
+            }
+        }
+    }
+
+    /// Energy of a layout: bond strain plus crowding between every pair of
+    /// tiles (lower is better)
+    ///
+    /// Always computed exactly, so layouts from either mode can be compared.
+    pub fn layout_energy(
+        &self,
+        positions: &HashMap<TileId, Coord>,
+        bonds: &[CognitiveBond],
+    ) -> f64 {
+        let distance = |a: Coord, b: Coord| ((b.0 - a.0).powi(2) + (b.1 - a.1).powi(2)).sqrt();
+
+        let strain: f64 = bonds
+            .iter()
+            .filter_map(|bond| {
+                let src = positions.get(&bond.source)?;
+                let dst = positions.get(&bond.dest)?;
+                let ideal = self.k * (1.0 - bond.strength * 0.5);
+                Some(bond.strength * (distance(*src, *dst) - ideal).powi(2) / self.k)
+            })
+            .sum();
+
+        let points: Vec<Coord> = positions.values().copied().collect();
+        let mut crowding = 0.0;
+        for (i, &a) in points.iter().enumerate() {
+            for &b in &points[i + 1..] {
+                crowding += self.k * self.k / distance(a, b).max(0.1);
+            }
+        }
+
+        strain + crowding
+    }
+
     /// Fruchterman-Reingold attraction force
     fn attraction_force(&self, distance: f64, ideal: f64) -> f64 {
         // F_a = d^2 / k
@@ -163,6 +341,14 @@ impl ForceDirectedSolver {
     }
 }
 
+/// Tiles and their positions in id order
+fn sorted_points(positions: &HashMap<TileId, Coord>) -> (Vec<TileId>, Vec<Coord>) {
+    let mut tiles: Vec<(TileId, Coord)> =
+        positions.iter().map(|(&tile, &pos)| (tile, pos)).collect();
+    tiles.sort_unstable_by_key(|&(tile, _)| tile);
+    tiles.into_iter().unzip()
+}
+
 #[cfg(test)]
 mod tests {
     use super::super::bonds::BondType;
@@ -200,6 +386,140 @@ mod tests {
         );
     }
 
+    /// Deterministic pseudo-random layout of `count` tiles with about two
+    /// bonds per tile
+    fn random_graph(count: u64, seed: u64) -> (HashMap<TileId, Coord>, Vec<CognitiveBond>) {
+        use rand::{Rng, SeedableRng};
+
+        let mut rng = rand::rngs::StdRng::seed_from_u64(seed);
+        let extent = (count as f64).sqrt() * 150.0;
+        let positions = (0..count)
+            .map(|id| (id, (rng.gen_range(0.0..extent), rng.gen_range(0.0..extent))))
+            .collect();
+        let bonds = (0..count * 2)
+            .map(|_| CognitiveBond {
+                source: rng.gen_range(0..count),
+                dest: rng.gen_range(0..count),
+                strength: rng.gen_range(0.1..1.0),
+                bond_type: BondType::Cognitive,
+                pulse_count: 1,
+            })
+            .filter(|bond| bond.source != bond.dest)
+            .collect();
+        (positions, bonds)
+    }
+
+    #[test]
+    fn test_barnes_hut_forces_match_exact() {
+        let (positions, bonds) = random_graph(60, 7);
+        let exact = ForceDirectedSolver::new(100.0, 50.0);
+        let expected = exact.calculate_forces(&positions, &bonds);
+
+        for (theta, tolerance) in [(0.0, 1e-9), (0.5, 0.05)] {
+            let solver =
+                ForceDirectedSolver::new(100.0, 50.0).with_mode(SolverMode::BarnesHut, theta);
+            let forces = solver.calculate_forces(&positions, &bonds);
+            // Compare against the typical force size, since single tiles can
+            // sit where the forces nearly cancel
+            let scale = expected.values().map(|f| f.0.hypot(f.1)).sum::<f64>() / 60.0;
+            for (tile, &(ex, ey)) in &expected {
+                let (fx, fy) = forces[tile];
+                let error = (fx - ex).hypot(fy - ey) / scale;
+                assert!(
+                    error <= tolerance,
+                    "theta {}: tile {} off by {}",
+                    theta,
+                    tile,
+                    error
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_barnes_hut_layout_energy_500_tiles() {
+        let (positions, bonds) = random_graph(500, 42);
+        let constraint = HilbertConstraint::new(0.0);
+        let exact = ForceDirectedSolver::new(100.0, 50.0);
+        let barnes_hut =
+            ForceDirectedSolver::new(100.0, 50.0).with_mode(SolverMode::BarnesHut, 0.5);
+
+        let exact_layout = exact.solve(&positions, &bonds, &constraint);
+        let barnes_hut_layout = barnes_hut.solve(&positions, &bonds, &constraint);
+
+        let exact_energy = exact.layout_energy(&exact_layout, &bonds);
+        let barnes_hut_energy = exact.layout_energy(&barnes_hut_layout, &bonds);
+        assert!(exact_energy < exact.layout_energy(&positions, &bonds));
+
+        let relative = (barnes_hut_energy - exact_energy).abs() / exact_energy;
+        assert!(
+            relative < 0.02,
+            "energies differ by {:.2}%",
+            relative * 100.0
+        );
+    }
+
+    #[test]
+    fn test_pinned_tile_stays_put() {
+        let (positions, bonds) = random_graph(40, 7);
+        let constraint = HilbertConstraint::new(0.0);
+        let mut solver = ForceDirectedSolver::new(100.0, 50.0);
+        let anchor = bonds[0].source;
+        let at = (-250.0, 125.0);
+        solver.pin_tile(anchor, at);
+
+        let result = solver.solve(&positions, &bonds, &constraint);
+        assert_eq!(result[&anchor], at);
+        // The rest of the graph relaxes around the anchor
+        let pinned_start: HashMap<TileId, Coord> = positions
+            .iter()
+            .map(|(&tile, &pos)| (tile, if tile == anchor { at } else { pos }))
+            .collect();
+        assert!(
+            solver.layout_energy(&result, &bonds) < solver.layout_energy(&pinned_start, &bonds)
+        );
+        assert!(result
+            .iter()
+            .any(|(&tile, &pos)| tile != anchor && pos != positions[&tile]));
+
+        solver.unpin_tile(anchor);
+        assert!(!solver.is_pinned(anchor));
+        assert_ne!(solver.solve(&positions, &bonds, &constraint)[&anchor], at);
+    }
+
+    #[test]
+    fn test_same_seed_same_layout() {
+        let (positions, mut bonds) = random_graph(80, 3);
+        // Stack a few tiles on top of each other so the seed matters
+        let mut positions: Vec<(TileId, Coord)> = positions.into_iter().collect();
+        for (tile, pos) in positions.iter_mut() {
+            if *tile % 10 == 0 {
+                *pos = (0.0, 0.0);
+            }
+        }
+        let constraint = HilbertConstraint::new(0.0);
+
+        // Separately built maps iterate in different orders
+        let first: HashMap<TileId, Coord> = positions.iter().copied().collect();
+        let second: HashMap<TileId, Coord> = positions.iter().rev().copied().collect();
+        let solver = ForceDirectedSolver::new(100.0, 50.0).with_seed(1234);
+        let layout = solver.solve(&first, &bonds, &constraint);
+        bonds.reverse();
+        let again = ForceDirectedSolver::new(100.0, 50.0).with_seed(1234).solve(
+            &second,
+            &bonds,
+            &constraint,
+        );
+        assert_eq!(layout, again);
+
+        let reseeded = ForceDirectedSolver::new(100.0, 50.0).with_seed(4321).solve(
+            &first,
+            &bonds,
+            &constraint,
+        );
+        assert_ne!(layout, reseeded);
+    }
+
     #[test]
     fn test_solver_no_bonds() {
         let solver = ForceDirectedSolver::new(100.0, 50.0);
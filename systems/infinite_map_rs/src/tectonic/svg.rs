@@ -0,0 +1,188 @@
+//! SVG Export for Tectonic Layouts.
+//!
+//! Draws the bond graph at its current tile positions so a realignment can be
+//! inspected outside the compositor: tiles as circles, bonds as lines whose
+//! width and color follow their strength.
+
+use std::collections::HashMap;
+use std::fmt::Write;
+
+use super::bonds::CognitiveBondGraph;
+use super::{Coord, TileId};
+use crate::diagnostic::{PAS_CAUTION_THRESHOLD, PAS_OPTIMAL_THRESHOLD};
+
+/// Radius of a tile circle, in map pixels
+const TILE_RADIUS: f64 = 32.0;
+
+/// Empty border around the outermost tiles
+const MARGIN: f64 = 96.0;
+
+/// Bond line width at strength 0 and 1
+const MIN_STROKE: f64 = 2.0;
+const MAX_STROKE: f64 = 16.0;
+
+/// Bond color, using the PAS score thresholds of the diagnostic overlay
+fn strength_color(strength: f64) -> &'static str {
+    if strength > f64::from(PAS_OPTIMAL_THRESHOLD) {
+        "#00ff80" // Crystalline Green
+    } else if strength > f64::from(PAS_CAUTION_THRESHOLD) {
+        "#ffcc00" // Amber Caution
+    } else {
+        "#ff3333" // Fracture Red
+    }
+}
+
+/// Render every bond in `graph` between tiles that have a position
+///
+/// Bonds are drawn under the tiles, strongest last so they stay on top.
+pub fn render_svg(graph: &CognitiveBondGraph, positions: &HashMap<TileId, Coord>) -> String {
+    let mut tiles: Vec<(TileId, Coord)> = positions.iter().map(|(&id, &pos)| (id, pos)).collect();
+    tiles.sort_by_key(|&(id, _)| id);
+
+    let (mut min, mut max) = ((0.0, 0.0), (0.0, 0.0));
+    if let Some(&(_, first)) = tiles.first() {
+        (min, max) = (first, first);
+        for &(_, (x, y)) in &tiles {
+            min = (min.0.min(x), min.1.min(y));
+            max = (max.0.max(x), max.1.max(y));
+        }
+    }
+    let (left, top) = (min.0 - MARGIN, min.1 - MARGIN);
+    let (width, height) = (max.0 - min.0 + 2.0 * MARGIN, max.1 - min.1 + 2.0 * MARGIN);
+
+    let mut svg = String::new();
+    let _ = writeln!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="{:.1} {:.1} {:.1} {:.1}">"#,
+        left, top, width, height
+    );
+    let _ = writeln!(
+        svg,
+        r##"<rect x="{:.1}" y="{:.1}" width="{:.1}" height="{:.1}" fill="#101018"/>"##,
+        left, top, width, height
+    );
+
+    let mut bonds = graph.get_bonds(0.0);
+    bonds.sort_by(|a, b| {
+        a.strength
+            .total_cmp(&b.strength)
+            .then((a.source, a.dest).cmp(&(b.source, b.dest)))
+    });
+    svg.push_str("<g id=\"bonds\" stroke-linecap=\"round\">\n");
+    for bond in &bonds {
+        let (Some(src), Some(dst)) = (positions.get(&bond.source), positions.get(&bond.dest))
+        else {
+            continue;
+        };
+        let _ = writeln!(
+            svg,
+            r#"<line x1="{:.1}" y1="{:.1}" x2="{:.1}" y2="{:.1}" stroke="{}" stroke-width="{:.1}" data-strength="{:.3}"/>"#,
+            src.0,
+            src.1,
+            dst.0,
+            dst.1,
+            strength_color(bond.strength),
+            MIN_STROKE + (MAX_STROKE - MIN_STROKE) * bond.strength.clamp(0.0, 1.0),
+            bond.strength
+        );
+    }
+    svg.push_str("</g>\n");
+
+    svg.push_str(
+        "<g id=\"tiles\" font-family=\"monospace\" font-size=\"20\" text-anchor=\"middle\">\n",
+    );
+    for (id, (x, y)) in &tiles {
+        let _ = writeln!(
+            svg,
+            r##"<circle cx="{:.1}" cy="{:.1}" r="{:.1}" fill="#2a2a40" stroke="#c0c0ff" stroke-width="2"/>"##,
+            x, y, TILE_RADIUS
+        );
+        let _ = writeln!(
+            svg,
+            r##"<text x="{:.1}" y="{:.1}" fill="#e0e0ff">DIST-{}</text>"##,
+            x,
+            y + TILE_RADIUS + 24.0,
+            id
+        );
+    }
+    svg.push_str("</g>\n</svg>\n");
+    svg
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Minimal well-formedness check: every tag closes in order, attributes
+    /// are quoted, and there is exactly one root element
+    fn assert_well_formed(xml: &str) {
+        let mut open: Vec<&str> = Vec::new();
+        let mut roots = 0;
+        let mut rest = xml;
+        while let Some(start) = rest.find('<') {
+            let end = rest[start..].find('>').expect("unterminated tag") + start;
+            let tag = &rest[start + 1..end];
+            rest = &rest[end + 1..];
+
+            if let Some(name) = tag.strip_prefix('/') {
+                assert_eq!(open.pop(), Some(name.trim()), "mismatched </{}>", name);
+                continue;
+            }
+            let self_closing = tag.ends_with('/');
+            let body = tag.trim_end_matches('/');
+            let name = body.split_whitespace().next().expect("empty tag");
+            assert_eq!(
+                body.matches('"').count() % 2,
+                0,
+                "unbalanced quotes in <{}>",
+                tag
+            );
+            for attribute in body[name.len()..].split('"').step_by(2) {
+                let attribute = attribute.trim();
+                assert!(
+                    attribute.is_empty() || attribute.ends_with('='),
+                    "bad attribute {:?}",
+                    attribute
+                );
+            }
+            if open.is_empty() {
+                roots += 1;
+            }
+            if !self_closing {
+                open.push(name);
+            }
+        }
+        assert!(open.is_empty(), "unclosed elements {:?}", open);
+        assert_eq!(roots, 1);
+    }
+
+    #[test]
+    fn test_render_svg_draws_bonds_above_threshold() {
+        let min_strength = 0.1;
+        let mut graph = CognitiveBondGraph::new();
+        graph.add_pulse(0, 1, 100.0, true);
+        graph.add_pulse(1, 2, 60.0, false);
+        graph.add_pulse(2, 3, 30.0, true);
+        graph.add_pulse(0, 3, 5.0, true); // 0.05, below the threshold
+        graph.prune(min_strength);
+
+        let positions: HashMap<TileId, Coord> = [
+            (0, (0.0, 0.0)),
+            (1, (512.0, 0.0)),
+            (2, (512.0, 512.0)),
+            (3, (0.0, 512.0)),
+        ]
+        .into_iter()
+        .collect();
+
+        let svg = render_svg(&graph, &positions);
+        assert_well_formed(&svg);
+        assert_eq!(
+            svg.matches("<line ").count(),
+            graph.get_bonds(min_strength).len()
+        );
+        assert_eq!(svg.matches("<line ").count(), 3);
+        assert_eq!(svg.matches("<circle ").count(), 4);
+        assert!(svg.contains("#00ff80") && svg.contains("#ffcc00") && svg.contains("#ff3333"));
+    }
+}
@@ -7,7 +7,20 @@
 //!   R (Char):   ASCII character code (0-127)
 //!   G (FG):     Foreground color index (0-15)
 //!   B (BG):     Background color index (0-15)
-//!   A (Flags):  Style flags (bold=1, dim=2, italic=4, underline=8, blink=16, inverse=32)
+//!   A (Flags):  Style flags (bold=1, dim=2, italic=4, underline=8, blink=16, inverse=32,
+//!               highlight=64)
+
+use crate::cognitive::texture_updater::TextureQueue;
+use crate::damage_tracker::{DamageTracker, DirtyRect};
+use crate::glyph_atlas::{GlyphAtlas, GlyphKey};
+use crate::terminal_tile::TerminalMetrics;
+use std::collections::{HashMap, VecDeque};
+
+/// Glyph size used when ripping framebuffers (the 8x16 VGA text font)
+pub const FRAMEBUFFER_GLYPH_SIZE: f32 = 16.0;
+
+/// Default number of rows kept after scrolling off the top
+pub const DEFAULT_SCROLLBACK_LINES: usize = 1000;
 
 /// Terminal cell encoded as RGBA pixel for GPU-native rendering
 #[repr(C)]
@@ -62,6 +75,8 @@ pub mod flags {
     pub const UNDERLINE: u8 = 8;
     pub const BLINK: u8 = 16;
     pub const INVERSE: u8 = 32;
+    /// Search match, drawn by the renderer as a highlight
+    pub const HIGHLIGHT: u8 = 64;
 }
 
 /// Standard 16-color terminal palette as RGB values
@@ -84,6 +99,154 @@ pub const TERMINAL_PALETTE: [[f32; 4]; 16] = [
     [1.00, 1.00, 1.00, 1.0], // 15: Bright White
 ];
 
+/// RGB of a palette entry as 8-bit channels
+pub fn palette_rgb(index: u8) -> [u8; 3] {
+    let [r, g, b, _] = TERMINAL_PALETTE[(index & 0xF) as usize];
+    [r, g, b].map(|c| (c * 255.0).round() as u8)
+}
+
+/// Default RGB distance within which a color snaps to the palette
+pub const DEFAULT_PALETTE_TOLERANCE: f32 = 48.0;
+
+/// A ripped color: a palette entry or an exact RGB value
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PaletteColor {
+    Indexed(u8),
+    TrueColor([u8; 3]),
+}
+
+impl PaletteColor {
+    /// Palette index, if the color snapped to one
+    pub fn index(&self) -> Option<u8> {
+        match self {
+            PaletteColor::Indexed(index) => Some(*index),
+            PaletteColor::TrueColor(_) => None,
+        }
+    }
+
+    /// RGB value of the color
+    pub fn rgb(&self) -> [u8; 3] {
+        match self {
+            PaletteColor::Indexed(index) => palette_rgb(*index),
+            PaletteColor::TrueColor(rgb) => *rgb,
+        }
+    }
+}
+
+/// Maps arbitrary RGB to `TERMINAL_PALETTE` entries
+///
+/// Colors within `tolerance` (Euclidean RGB distance, 0-441) of their
+/// nearest palette entry snap to it; anything further is kept as truecolor.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PaletteMatcher {
+    pub tolerance: f32,
+}
+
+impl Default for PaletteMatcher {
+    fn default() -> Self {
+        Self::new(DEFAULT_PALETTE_TOLERANCE)
+    }
+}
+
+impl PaletteMatcher {
+    pub fn new(tolerance: f32) -> Self {
+        Self {
+            tolerance: tolerance.max(0.0),
+        }
+    }
+
+    /// Closest palette index and its distance
+    pub fn nearest(&self, rgb: [u8; 3]) -> (u8, f32) {
+        (0..TERMINAL_PALETTE.len() as u8)
+            .map(|index| {
+                let entry = palette_rgb(index);
+                let sq: i32 = (0..3)
+                    .map(|c| (entry[c] as i32 - rgb[c] as i32).pow(2))
+                    .sum();
+                (index, (sq as f32).sqrt())
+            })
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .unwrap_or((0, 0.0))
+    }
+
+    /// Snap to the palette within tolerance, truecolor otherwise
+    pub fn match_color(&self, rgb: [u8; 3]) -> PaletteColor {
+        let (index, distance) = self.nearest(rgb);
+        if distance <= self.tolerance {
+            PaletteColor::Indexed(index)
+        } else {
+            PaletteColor::TrueColor(rgb)
+        }
+    }
+}
+
+/// Exact colors of a ripped cell that did not fit the palette
+///
+/// The cell itself still carries the nearest palette index as a fallback.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TrueColorCell {
+    pub fg: Option<[u8; 3]>,
+    pub bg: Option<[u8; 3]>,
+}
+
+/// Glyph masks for ASCII 32..=126 at the framebuffer font size
+struct GlyphMasks {
+    width: usize,
+    height: usize,
+    masks: Vec<(u8, Vec<bool>)>,
+}
+
+impl GlyphMasks {
+    fn from_atlas(atlas: &mut GlyphAtlas) -> Option<Self> {
+        let space = atlas.render_glyph(&GlyphKey::new(' ', FRAMEBUFFER_GLYPH_SIZE))?;
+        let (width, height) = (space.width as usize, space.height as usize);
+
+        let masks = (32u8..=126)
+            .filter_map(|c| {
+                let glyph =
+                    atlas.render_glyph(&GlyphKey::new(c as char, FRAMEBUFFER_GLYPH_SIZE))?;
+                let mask = glyph.pixels.iter().map(|&alpha| alpha >= 128).collect();
+                (glyph.width as usize == width && glyph.height as usize == height)
+                    .then_some((c, mask))
+            })
+            .collect();
+
+        Some(Self {
+            width,
+            height,
+            masks,
+        })
+    }
+
+    /// Best matching character and whether the match was inverted
+    #[cfg(feature = "hypervisor")]
+    fn classify(&self, mask: &[bool]) -> (u8, bool) {
+        let mut best = (b' ', false, usize::MAX);
+        for (c, glyph) in &self.masks {
+            let diff = glyph.iter().zip(mask).filter(|(a, b)| a != b).count();
+            let inverted = glyph.len() - diff;
+            if diff < best.2 {
+                best = (*c, false, diff);
+            }
+            if inverted < best.2 {
+                best = (*c, true, inverted);
+            }
+        }
+        (best.0, best.1)
+    }
+}
+
+/// A search match on one row
+///
+/// `row` counts visible rows from 0; negative rows are scrollback, with -1
+/// the most recent line scrolled off the top.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SearchHit {
+    pub row: isize,
+    pub col: usize,
+    pub len: usize,
+}
+
 /// Geometric terminal buffer for PixelRTS v3 rendering
 pub struct GeometricTerminalBuffer {
     pub cols: usize,
@@ -94,11 +257,24 @@ pub struct GeometricTerminalBuffer {
     pub current_fg: u8,
     pub current_bg: u8,
     pub current_flags: u8,
+    /// Truecolor overrides by cell index (populated by framebuffer ripping)
+    pub truecolor: HashMap<usize, TrueColorCell>,
+    metrics: TerminalMetrics,
+    /// Cells changed since the last `flush_damage`
+    damage: DamageTracker,
+    /// Rows scrolled off the top, oldest first
+    pub scrollback: VecDeque<Vec<GeometricCell>>,
+    /// Maximum rows kept in `scrollback`
+    pub scrollback_limit: usize,
 }
 
 impl GeometricTerminalBuffer {
     /// Create a new geometric terminal buffer
     pub fn new(cols: usize, rows: usize) -> Self {
+        // Everything is pending until the first flush
+        let mut damage = DamageTracker::new(cols as u32, rows as u32);
+        damage.mark_all(cols as u32, rows as u32);
+
         Self {
             cols,
             rows,
@@ -108,7 +284,168 @@ impl GeometricTerminalBuffer {
             current_fg: 7, // Default white
             current_bg: 0, // Default black
             current_flags: 0,
+            truecolor: HashMap::new(),
+            metrics: TerminalMetrics::default(),
+            damage,
+            scrollback: VecDeque::new(),
+            scrollback_limit: DEFAULT_SCROLLBACK_LINES,
+        }
+    }
+
+    /// Rip a terminal grid from an RGBA framebuffer
+    ///
+    /// The framebuffer is split into cells the size of the atlas' 8x16
+    /// glyphs. In each cell the most common color is the background and the
+    /// most common remaining color the foreground; the resulting on/off mask
+    /// is matched against every printable ASCII glyph (plain or inverted).
+    /// Colors are matched with the default `PaletteMatcher`. Partial cells at
+    /// the right and bottom edges are ignored.
+    #[cfg(feature = "hypervisor")]
+    pub fn from_framebuffer(
+        pixels: &[u8],
+        width: usize,
+        height: usize,
+        glyph_atlas: &mut GlyphAtlas,
+    ) -> Self {
+        Self::from_framebuffer_with_matcher(
+            pixels,
+            width,
+            height,
+            glyph_atlas,
+            &PaletteMatcher::default(),
+        )
+    }
+
+    /// `from_framebuffer` with an explicit palette matcher
+    ///
+    /// Colors outside the matcher's tolerance are recorded in `truecolor`.
+    #[cfg(feature = "hypervisor")]
+    pub fn from_framebuffer_with_matcher(
+        pixels: &[u8],
+        width: usize,
+        height: usize,
+        glyph_atlas: &mut GlyphAtlas,
+        matcher: &PaletteMatcher,
+    ) -> Self {
+        let Some(glyphs) = GlyphMasks::from_atlas(glyph_atlas) else {
+            log::warn!("⚠️  Glyph atlas has no ASCII glyphs, cannot rip framebuffer");
+            return Self::new(0, 0);
+        };
+
+        let cols = width / glyphs.width;
+        let rows = height / glyphs.height;
+        let mut buffer = Self::new(cols, rows);
+        if pixels.len() < width * height * 4 {
+            log::warn!(
+                "⚠️  Framebuffer too small: expected {} bytes, got {}",
+                width * height * 4,
+                pixels.len()
+            );
+            return buffer;
+        }
+
+        let mut region = Vec::with_capacity(glyphs.width * glyphs.height);
+        for row in 0..rows {
+            for col in 0..cols {
+                region.clear();
+                for y in 0..glyphs.height {
+                    let start = ((row * glyphs.height + y) * width + col * glyphs.width) * 4;
+                    region.extend(
+                        pixels[start..start + glyphs.width * 4]
+                            .chunks_exact(4)
+                            .map(|px| [px[0], px[1], px[2]]),
+                    );
+                }
+                let index = row * cols + col;
+                let (cell, truecolor) = Self::classify_cell(&region, &glyphs, matcher);
+                buffer.cells[index] = cell;
+                if truecolor != TrueColorCell::default() {
+                    buffer.truecolor.insert(index, truecolor);
+                }
+            }
+        }
+
+        buffer
+    }
+
+    /// Recover one cell from its RGB pixels
+    #[cfg(feature = "hypervisor")]
+    fn classify_cell(
+        region: &[[u8; 3]],
+        glyphs: &GlyphMasks,
+        matcher: &PaletteMatcher,
+    ) -> (GeometricCell, TrueColorCell) {
+        let mut counts: HashMap<[u8; 3], usize> = HashMap::new();
+        for px in region {
+            *counts.entry(*px).or_default() += 1;
+        }
+        let mut by_count: Vec<([u8; 3], usize)> = counts.into_iter().collect();
+        by_count.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+
+        let resolve = |rgb: [u8; 3]| match matcher.match_color(rgb) {
+            PaletteColor::Indexed(index) => (index, None),
+            PaletteColor::TrueColor(rgb) => (matcher.nearest(rgb).0, Some(rgb)),
+        };
+
+        let bg = by_count[0].0;
+        let Some(&(fg, _)) = by_count.get(1) else {
+            let (bg_index, bg_true) = resolve(bg);
+            let truecolor = TrueColorCell {
+                fg: None,
+                bg: bg_true,
+            };
+            return (GeometricCell::new(b' ', 7, bg_index, 0), truecolor);
+        };
+
+        let distance = |a: [u8; 3], b: [u8; 3]| -> i32 {
+            (0..3).map(|c| (a[c] as i32 - b[c] as i32).pow(2)).sum()
+        };
+        let mask: Vec<bool> = region
+            .iter()
+            .map(|&px| distance(px, fg) < distance(px, bg))
+            .collect();
+
+        let (c, inverted) = glyphs.classify(&mask);
+        let (fg, bg) = if inverted { (bg, fg) } else { (fg, bg) };
+        let ((fg_index, fg_true), (bg_index, bg_true)) = (resolve(fg), resolve(bg));
+        let truecolor = TrueColorCell {
+            fg: fg_true,
+            bg: bg_true,
+        };
+        (GeometricCell::new(c, fg_index, bg_index, 0), truecolor)
+    }
+
+    /// Rasterize the buffer to RGBA with the atlas glyphs
+    ///
+    /// Inverse of `from_framebuffer`; returns the pixels and (width, height).
+    pub fn render_to_framebuffer(&self, glyph_atlas: &mut GlyphAtlas) -> (Vec<u8>, usize, usize) {
+        let Some(glyphs) = GlyphMasks::from_atlas(glyph_atlas) else {
+            return (Vec::new(), 0, 0);
+        };
+        let width = self.cols * glyphs.width;
+        let height = self.rows * glyphs.height;
+        let mut pixels = vec![0u8; width * height * 4];
+
+        for (i, cell) in self.cells.iter().enumerate() {
+            let (row, col) = (i / self.cols, i % self.cols);
+            let mask = glyphs
+                .masks
+                .iter()
+                .find(|(c, _)| *c == cell.char)
+                .map(|(_, mask)| mask.as_slice());
+            let (fg, bg) = (palette_rgb(cell.fg), palette_rgb(cell.bg));
+
+            for y in 0..glyphs.height {
+                for x in 0..glyphs.width {
+                    let on = mask.is_some_and(|m| m[y * glyphs.width + x]);
+                    let [r, g, b] = if on { fg } else { bg };
+                    let offset = ((row * glyphs.height + y) * width + col * glyphs.width + x) * 4;
+                    pixels[offset..offset + 4].copy_from_slice(&[r, g, b, 255]);
+                }
+            }
         }
+
+        (pixels, width, height)
     }
 
     /// Put a character at the current cursor position
@@ -117,6 +454,9 @@ impl GeometricTerminalBuffer {
             let idx = self.cursor_y * self.cols + self.cursor_x;
             self.cells[idx] =
                 GeometricCell::new(c, self.current_fg, self.current_bg, self.current_flags);
+            self.metrics.cells_updated += 1;
+            self.damage
+                .mark_dirty(self.cursor_x as u32, self.cursor_y as u32);
 
             // Advance cursor
             self.cursor_x += 1;
@@ -129,6 +469,7 @@ impl GeometricTerminalBuffer {
 
     /// Process PTY output bytes
     pub fn process_pty_output(&mut self, data: &[u8]) {
+        self.metrics.bytes_processed += data.len() as u64;
         for &byte in data {
             match byte {
                 b'\n' => {
@@ -169,11 +510,25 @@ impl GeometricTerminalBuffer {
     /// Scroll the buffer by N lines
     pub fn scroll(&mut self, lines: usize) {
         for _ in 0..lines {
-            // Shift cells up
-            self.cells.drain(0..self.cols);
+            // Shift cells up, keeping the top row as scrollback
+            let top: Vec<GeometricCell> = self.cells.drain(0..self.cols).collect();
+            if self.scrollback_limit > 0 {
+                if self.scrollback.len() >= self.scrollback_limit {
+                    self.scrollback.pop_front();
+                }
+                self.scrollback.push_back(top);
+            }
             self.cells
                 .extend(std::iter::repeat_with(GeometricCell::default).take(self.cols));
         }
+
+        self.damage.mark_all(self.cols as u32, self.rows as u32);
+
+        let shift = lines * self.cols;
+        self.truecolor = std::mem::take(&mut self.truecolor)
+            .into_iter()
+            .filter_map(|(index, colors)| index.checked_sub(shift).map(|i| (i, colors)))
+            .collect();
     }
 
     /// Clear the buffer
@@ -181,10 +536,189 @@ impl GeometricTerminalBuffer {
         for cell in &mut self.cells {
             *cell = GeometricCell::default();
         }
+        self.truecolor.clear();
+        self.damage.mark_all(self.cols as u32, self.rows as u32);
         self.cursor_x = 0;
         self.cursor_y = 0;
     }
 
+    /// Bytes and cells processed so far
+    ///
+    /// `texture_rebuilds` is left to the owner of the GPU texture.
+    pub fn metrics(&self) -> TerminalMetrics {
+        self.metrics
+    }
+
+    /// Find `query` in scrollback and visible rows, oldest first
+    ///
+    /// Matches do not span rows; empty cells compare as spaces.
+    pub fn search(&self, query: &str, case_sensitive: bool) -> Vec<SearchHit> {
+        let fold = |c: u8| {
+            if case_sensitive {
+                c
+            } else {
+                c.to_ascii_lowercase()
+            }
+        };
+        let needle: Vec<u8> = query.bytes().map(fold).collect();
+        if needle.is_empty() {
+            return Vec::new();
+        }
+
+        let scrollback_rows = self.scrollback.len() as isize;
+        let rows = self
+            .scrollback
+            .iter()
+            .map(|row| row.as_slice())
+            .chain(self.cells.chunks(self.cols.max(1)));
+
+        let mut hits = Vec::new();
+        for (i, row) in rows.enumerate() {
+            let text: Vec<u8> = row
+                .iter()
+                .map(|cell| fold(if cell.char == 0 { b' ' } else { cell.char }))
+                .collect();
+            let mut col = 0;
+            while col + needle.len() <= text.len() {
+                if text[col..col + needle.len()] == needle[..] {
+                    hits.push(SearchHit {
+                        row: i as isize - scrollback_rows,
+                        col,
+                        len: needle.len(),
+                    });
+                    col += needle.len();
+                } else {
+                    col += 1;
+                }
+            }
+        }
+        hits
+    }
+
+    /// Set `flags::HIGHLIGHT` on every cell covered by `hits`
+    pub fn highlight_hits(&mut self, hits: &[SearchHit]) {
+        for hit in hits {
+            self.set_highlight(hit);
+        }
+    }
+
+    /// Remove `flags::HIGHLIGHT` from all cells
+    pub fn clear_highlights(&mut self) {
+        for row in self.scrollback.iter_mut() {
+            for cell in row.iter_mut() {
+                cell.flags &= !flags::HIGHLIGHT;
+            }
+        }
+        for (i, cell) in self.cells.iter_mut().enumerate() {
+            if cell.flags & flags::HIGHLIGHT != 0 {
+                cell.flags &= !flags::HIGHLIGHT;
+                self.damage
+                    .mark_dirty((i % self.cols) as u32, (i / self.cols) as u32);
+            }
+        }
+    }
+
+    fn set_highlight(&mut self, hit: &SearchHit) {
+        let row = if hit.row < 0 {
+            let index = self.scrollback.len() as isize + hit.row;
+            match usize::try_from(index)
+                .ok()
+                .and_then(|i| self.scrollback.get_mut(i))
+            {
+                Some(row) => row.as_mut_slice(),
+                None => return,
+            }
+        } else {
+            let row = hit.row as usize;
+            if row >= self.rows {
+                return;
+            }
+            let end = hit.col.saturating_add(hit.len).min(self.cols);
+            self.damage
+                .mark_rect_dirty(hit.col as u32, row as u32, end as u32, row as u32 + 1);
+            &mut self.cells[row * self.cols..(row + 1) * self.cols]
+        };
+
+        for cell in row.iter_mut().skip(hit.col).take(hit.len) {
+            cell.flags |= flags::HIGHLIGHT;
+        }
+    }
+
+    /// Overwrite one cell, marking it dirty only if it changed
+    ///
+    /// Returns whether the cell changed. Out-of-range cells are ignored.
+    pub fn set_cell(&mut self, col: usize, row: usize, cell: GeometricCell) -> bool {
+        if col >= self.cols || row >= self.rows {
+            return false;
+        }
+        let idx = row * self.cols + col;
+        if self.cells[idx].to_u32() == cell.to_u32() {
+            return false;
+        }
+        self.cells[idx] = cell;
+        self.damage.mark_dirty(col as u32, row as u32);
+        true
+    }
+
+    /// `set_cell` that also records the cell's exact colors
+    ///
+    /// A change of truecolor damages the cell even when its palette
+    /// fallback stays the same.
+    pub fn set_cell_with_truecolor(
+        &mut self,
+        col: usize,
+        row: usize,
+        cell: GeometricCell,
+        truecolor: TrueColorCell,
+    ) -> bool {
+        if col >= self.cols || row >= self.rows {
+            return false;
+        }
+        let idx = row * self.cols + col;
+        let old = self.truecolor.get(&idx).copied().unwrap_or_default();
+        if self.cells[idx].to_u32() == cell.to_u32() && old == truecolor {
+            return false;
+        }
+        self.cells[idx] = cell;
+        if truecolor == TrueColorCell::default() {
+            self.truecolor.remove(&idx);
+        } else {
+            self.truecolor.insert(idx, truecolor);
+        }
+        self.damage.mark_dirty(col as u32, row as u32);
+        true
+    }
+
+    /// Cells changed since the last flush
+    pub fn damage(&self) -> &DamageTracker {
+        &self.damage
+    }
+
+    /// Upload only the dirty cell regions and clear the damage
+    ///
+    /// `texture` is the `cols` x `rows` cell texture (one RGBA8 texel per
+    /// cell: char, fg, bg, flags). Returns the rects that were uploaded.
+    pub fn flush_damage<Q: TextureQueue>(
+        &mut self,
+        queue: &Q,
+        texture: &Q::Texture,
+    ) -> Vec<DirtyRect> {
+        let rects = self.damage.compute_dirty_rects();
+        for rect in &rects {
+            let mut data = Vec::with_capacity(rect.area() as usize * 4);
+            for row in rect.y1..rect.y2 {
+                let start = row as usize * self.cols;
+                for cell in &self.cells[start + rect.x1 as usize..start + rect.x2 as usize] {
+                    data.extend_from_slice(&[cell.char, cell.fg, cell.bg, cell.flags]);
+                }
+            }
+            queue.write_region(texture, &data, rect, 4);
+        }
+
+        self.damage.clear();
+        rects
+    }
+
     /// Get the buffer as u32 array for GPU
     pub fn to_gpu_buffer(&self) -> Vec<u32> {
         self.cells.iter().map(|c| c.to_u32()).collect()
@@ -345,6 +879,230 @@ mod tests {
         assert_eq!(buf.cells[20].char, b'L'); // Line4
     }
 
+    #[test]
+    #[cfg(feature = "hypervisor")]
+    fn test_from_framebuffer_recovers_text_and_colors() {
+        let mut atlas = GlyphAtlas::new(256, 256);
+        let mut source = GeometricTerminalBuffer::new(16, 3);
+        source.current_fg = 10;
+        source.process_pty_output(b"Hello, VM!\n");
+        source.current_fg = 15;
+        source.current_bg = 4;
+        source.process_pty_output(b"ls -la /tmp");
+
+        let (pixels, width, height) = source.render_to_framebuffer(&mut atlas);
+        assert_eq!((width, height), (16 * 8, 3 * 16));
+
+        let ripped = GeometricTerminalBuffer::from_framebuffer(&pixels, width, height, &mut atlas);
+        assert_eq!((ripped.cols, ripped.rows), (16, 3));
+
+        let line = |buf: &GeometricTerminalBuffer, row: usize| -> String {
+            buf.cells[row * buf.cols..(row + 1) * buf.cols]
+                .iter()
+                .map(|c| if c.char == 0 { ' ' } else { c.char as char })
+                .collect::<String>()
+                .trim_end()
+                .to_string()
+        };
+        assert_eq!(line(&ripped, 0), "Hello, VM!");
+        assert_eq!(line(&ripped, 1), "ls -la /tmp");
+        assert_eq!(line(&ripped, 2), "");
+
+        let h = ripped.cells[0];
+        assert_eq!((h.char, h.fg, h.bg), (b'H', 10, 0));
+        let l = ripped.cells[16];
+        assert_eq!((l.char, l.fg, l.bg), (b'l', 15, 4));
+        // Blank cells keep their background
+        assert_eq!(ripped.cells[16 + 2].bg, 4);
+    }
+
+    /// (x, y, width, height, data)
+    type WriteCall = (u32, u32, u32, u32, Vec<u8>);
+
+    #[derive(Default)]
+    struct RecordingQueue {
+        writes: parking_lot::Mutex<Vec<WriteCall>>,
+    }
+
+    impl TextureQueue for RecordingQueue {
+        type Texture = ();
+
+        fn write_region(&self, _: &(), data: &[u8], rect: &DirtyRect, _: u32) {
+            self.writes
+                .lock()
+                .push((rect.x1, rect.y1, rect.width(), rect.height(), data.to_vec()));
+        }
+    }
+
+    #[test]
+    fn test_flush_damage_uploads_only_dirty_cells() {
+        let queue = RecordingQueue::default();
+        let mut buffer = GeometricTerminalBuffer::new(40, 10);
+
+        // First flush uploads the whole grid
+        assert_eq!(
+            buffer.flush_damage(&queue, &()),
+            vec![DirtyRect::new(0, 0, 40, 10)]
+        );
+        assert!(!buffer.damage().has_damage());
+        queue.writes.lock().clear();
+
+        buffer.cursor_x = 5;
+        buffer.cursor_y = 2;
+        buffer.process_pty_output(b"ok");
+        buffer.cursor_x = 30;
+        buffer.cursor_y = 7;
+        buffer.current_fg = 2;
+        buffer.putc(b'!');
+
+        let rects = buffer.flush_damage(&queue, &());
+        let writes = queue.writes.lock();
+        assert_eq!(writes.len(), rects.len());
+        let uploaded: u32 = writes.iter().map(|(_, _, w, h, _)| w * h).sum();
+        assert_eq!(uploaded, 3);
+
+        let (x, y, w, h, data) = writes.iter().find(|w| w.1 == 2).unwrap();
+        assert_eq!((*x, *y, *w, *h), (5, 2, 2, 1));
+        assert_eq!(data, &vec![b'o', 7, 0, 0, b'k', 7, 0, 0]);
+        let (x, y, _, _, data) = writes.iter().find(|w| w.1 == 7).unwrap();
+        assert_eq!((*x, *y), (30, 7));
+        assert_eq!(data, &vec![b'!', 2, 0, 0]);
+        drop(writes);
+
+        // Damage is cleared: nothing left to upload
+        assert!(buffer.flush_damage(&queue, &()).is_empty());
+    }
+
+    #[test]
+    fn test_search_finds_hits_in_scrollback() {
+        let mut buffer = GeometricTerminalBuffer::new(20, 3);
+        buffer.process_pty_output(b"boot: Kernel ok\n");
+        buffer.process_pty_output(b"mount /dev/sda\n");
+        buffer.process_pty_output(b"kernel panic? no\n");
+        buffer.process_pty_output(b"shell ready\n");
+        buffer.process_pty_output(b"$ dmesg | kernel");
+
+        // Two rows scrolled off: "boot: ..." and "mount ..."
+        assert_eq!(buffer.scrollback.len(), 2);
+
+        let hits = buffer.search("kernel", false);
+        assert_eq!(
+            hits,
+            vec![
+                SearchHit {
+                    row: -2,
+                    col: 6,
+                    len: 6
+                },
+                SearchHit {
+                    row: 0,
+                    col: 0,
+                    len: 6
+                },
+                SearchHit {
+                    row: 2,
+                    col: 10,
+                    len: 6
+                },
+            ]
+        );
+
+        // Case-sensitive search only finds the capitalized one
+        let hits = buffer.search("Kernel", true);
+        assert_eq!(hits.len(), 1);
+        assert_eq!((hits[0].row, hits[0].col), (-2, 6));
+
+        buffer.highlight_hits(&buffer.search("kernel", false));
+        let highlighted = |cells: &[GeometricCell]| {
+            cells
+                .iter()
+                .filter(|c| c.flags & flags::HIGHLIGHT != 0)
+                .count()
+        };
+        assert_eq!(highlighted(&buffer.scrollback[0]), 6);
+        assert_eq!(highlighted(&buffer.scrollback[1]), 0);
+        assert_eq!(highlighted(&buffer.cells), 12);
+        assert_eq!(buffer.cells[0].flags & flags::HIGHLIGHT, flags::HIGHLIGHT);
+        assert!(buffer.damage().is_dirty(10, 2));
+
+        buffer.clear_highlights();
+        assert_eq!(highlighted(&buffer.scrollback[0]), 0);
+        assert_eq!(highlighted(&buffer.cells), 0);
+    }
+
+    #[test]
+    fn test_metrics_count_bytes_and_cells() {
+        let mut buffer = GeometricTerminalBuffer::new(20, 4);
+        buffer.process_pty_output(b"abc\r\n\x1bde\tf");
+
+        let metrics = buffer.metrics();
+        assert_eq!(metrics.bytes_processed, 10);
+        // Control characters and escapes do not touch cells
+        assert_eq!(metrics.cells_updated, 6);
+        assert_eq!(metrics.texture_rebuilds, 0);
+    }
+
+    #[test]
+    fn test_palette_matcher_tolerance() {
+        let matcher = PaletteMatcher::new(24.0);
+
+        // Slightly-off palette colors snap to their entry
+        assert_eq!(matcher.match_color([250, 6, 3]), PaletteColor::Indexed(9));
+        assert_eq!(
+            matcher.match_color([10, 240, 250]),
+            PaletteColor::Indexed(14)
+        );
+        let near_white = palette_rgb(7).map(|c| c - 8);
+        assert_eq!(matcher.match_color(near_white), PaletteColor::Indexed(7));
+
+        // Orange is far from every entry
+        let orange = [255, 140, 0];
+        assert_eq!(matcher.match_color(orange), PaletteColor::TrueColor(orange));
+        assert_eq!(matcher.match_color(orange).index(), None);
+
+        // A generous tolerance snaps it anyway
+        assert!(PaletteMatcher::new(200.0)
+            .match_color(orange)
+            .index()
+            .is_some());
+    }
+
+    #[test]
+    #[cfg(feature = "hypervisor")]
+    fn test_from_framebuffer_records_truecolor() {
+        let mut atlas = GlyphAtlas::new(256, 256);
+        let mut source = GeometricTerminalBuffer::new(4, 1);
+        source.process_pty_output(b"ok");
+        let (mut pixels, width, height) = source.render_to_framebuffer(&mut atlas);
+
+        // Recolor the foreground: slightly-off red in the first cell,
+        // orange in the second
+        let fg = palette_rgb(7);
+        for (i, px) in pixels.chunks_exact_mut(4).enumerate() {
+            if px[..3] == fg {
+                let rgb = if (i % width) < 8 {
+                    [245, 10, 5]
+                } else {
+                    [255, 140, 0]
+                };
+                px[..3].copy_from_slice(&rgb);
+            }
+        }
+
+        let ripped = GeometricTerminalBuffer::from_framebuffer_with_matcher(
+            &pixels,
+            width,
+            height,
+            &mut atlas,
+            &PaletteMatcher::new(24.0),
+        );
+        assert_eq!((ripped.cells[0].char, ripped.cells[0].fg), (b'o', 9));
+        assert!(!ripped.truecolor.contains_key(&0));
+        assert_eq!(ripped.cells[1].char, b'k');
+        assert_eq!(ripped.truecolor[&1].fg, Some([255, 140, 0]));
+        assert_eq!(ripped.truecolor[&1].bg, None);
+    }
+
     #[test]
     fn test_write_notification_single_line() {
         let mut buf = GeometricTerminalBuffer::new(80, 24);
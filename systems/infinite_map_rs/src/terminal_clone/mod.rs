@@ -2,6 +2,8 @@
 //
 // This module provides the infrastructure for "ripping" terminals from
 // VM framebuffers and providing native PTY support for local shells.
+// PTY-backed clones only need the `vte` feature (on by default); ripping
+// VM framebuffers needs `hypervisor`.
 //
 // PixelRTS v3 Integration:
 // Terminal cells are encoded as RGBA pixels for GPU-native rendering:
@@ -12,13 +14,14 @@
 
 pub mod geometric_bridge;
 pub mod pty_engine;
-pub mod terminal_renderer;
-
-#[cfg(feature = "hypervisor")]
 pub mod terminal_clone_manager;
+pub mod terminal_renderer;
 
-pub use geometric_bridge::{GeometricCell, GeometricTerminalBuffer, TERMINAL_PALETTE};
+pub use geometric_bridge::{
+    palette_rgb, GeometricCell, GeometricTerminalBuffer, PaletteColor, PaletteMatcher, SearchHit,
+    TrueColorCell, DEFAULT_PALETTE_TOLERANCE, DEFAULT_SCROLLBACK_LINES, FRAMEBUFFER_GLYPH_SIZE,
+    TERMINAL_PALETTE,
+};
 pub use pty_engine::PtyEngine;
-#[cfg(feature = "hypervisor")]
 pub use terminal_clone_manager::TerminalCloneManager;
 pub use terminal_renderer::TerminalRenderer;
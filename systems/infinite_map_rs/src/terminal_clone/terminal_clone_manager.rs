@@ -5,7 +5,6 @@ use std::collections::HashMap;
 use std::io;
 
 /// Terminal Clone: Manages multiple terminal instances with PTY and Emulation
-#[cfg(feature = "hypervisor")]
 pub struct TerminalClone {
     pub id: usize,
     pub pty: PtyEngine,
@@ -13,13 +12,17 @@ pub struct TerminalClone {
     pub title: String,
 }
 
-#[cfg(feature = "hypervisor")]
 pub struct TerminalCloneManager {
     terminals: HashMap<usize, TerminalClone>,
     next_id: usize,
 }
 
-#[cfg(feature = "hypervisor")]
+impl Default for TerminalCloneManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl TerminalCloneManager {
     /// Create a new TerminalCloneManager
     pub fn new() -> Self {
@@ -54,14 +57,18 @@ impl TerminalCloneManager {
     }
 
     /// Update all terminal clones (read from PTY, feed to emulator)
-    pub fn update(&mut self) {
+    ///
+    /// Returns the bytes read per terminal id this update.
+    pub fn update(&mut self) -> HashMap<usize, usize> {
         let mut buffer = [0u8; 4096];
+        let mut bytes_read = HashMap::new();
 
         for clone in self.terminals.values_mut() {
             loop {
                 match clone.pty.read(&mut buffer) {
                     Ok(n) if n > 0 => {
                         clone.emulator.feed(&buffer[..n]);
+                        *bytes_read.entry(clone.id).or_insert(0) += n;
                     },
                     Ok(_) => break, // EOF or no data
                     Err(e) if e.kind() == io::ErrorKind::WouldBlock => break,
@@ -72,6 +79,8 @@ impl TerminalCloneManager {
                 }
             }
         }
+
+        bytes_read
     }
 
     /// Write data to a terminal clone's PTY
@@ -117,7 +126,7 @@ impl TerminalCloneManager {
     }
 }
 
-#[cfg(test)]
+#[cfg(all(test, feature = "vte"))]
 mod tests {
     use super::*;
     use std::thread;
@@ -166,4 +175,47 @@ mod tests {
 
         panic!("Failed to see expected output in emulator buffer");
     }
+
+    /// PTY clones only need the vte parser, not the hypervisor
+    #[test]
+    #[cfg(not(feature = "hypervisor"))]
+    fn test_pty_clone_tracks_output_without_hypervisor() {
+        let mut manager = TerminalCloneManager::new();
+        let id = manager
+            .create_terminal(10, 40, "/bin/sh")
+            .expect("Failed to create terminal");
+        assert_eq!(manager.list_terminals(), vec![id]);
+
+        manager
+            .resize_terminal(id, 12, 60)
+            .expect("Failed to resize");
+        assert_eq!(manager.get_emulator(id).unwrap().get_size(), (12, 60));
+
+        manager
+            .write_to_terminal(id, b"echo pty_$((6 * 7))\n")
+            .expect("Failed to write");
+
+        let start = Instant::now();
+        let mut bytes_read = 0;
+        while start.elapsed() < Duration::from_secs(2) {
+            bytes_read += manager.update().get(&id).copied().unwrap_or(0);
+
+            let buffer = manager.get_emulator(id).unwrap().get_buffer();
+            let found = (0..12).any(|row| {
+                let line: String = (0..60)
+                    .filter_map(|col| buffer.get_cell(row, col).map(|cell| cell.c))
+                    .collect();
+                line.contains("pty_42")
+            });
+            if found {
+                assert!(bytes_read > 0);
+                manager.remove_terminal(id);
+                assert!(manager.get_emulator(id).is_none());
+                return;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+
+        panic!("PTY output never reached the emulator");
+    }
 }
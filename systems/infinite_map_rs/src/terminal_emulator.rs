@@ -10,11 +10,8 @@
 // - Terminal colors are mapped to Geometry OS Neons
 // - Advanced input (arrows, F-keys) are mapped to ANSI sequences
 
-#[cfg(feature = "hypervisor")]
-use vte::{Parser, Perform};
-
-#[cfg(feature = "hypervisor")]
-use vte::Params;
+#[cfg(feature = "vte")]
+use vte::{Params, Parser, Perform};
 
 /// Terminal Color (8-bit color palette)
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
@@ -419,7 +416,7 @@ impl TerminalBuffer {
 
     /// Mark entire screen as damaged (for full redraw)
     pub fn mark_full_damage(&self, tracker: &mut crate::damage_tracker::DamageTracker) {
-        tracker.mark_rect_dirty(0, 0, self.cols as u32, self.rows as u32);
+        tracker.mark_all(self.cols as u32, self.rows as u32);
     }
 
     /// Advance cursor to next position (handles wrapping and scrolling)
@@ -653,7 +650,7 @@ impl TerminalBuffer {
 }
 
 /// Terminal Emulator (VTE parser wrapper)
-#[cfg(feature = "hypervisor")]
+#[cfg(feature = "vte")]
 pub struct TerminalEmulator {
     /// Virtual screen buffer
     buffer: TerminalBuffer,
@@ -677,7 +674,7 @@ pub struct TerminalEmulator {
     cursor_blink_timer: f32,
 }
 
-#[cfg(feature = "hypervisor")]
+#[cfg(feature = "vte")]
 impl TerminalEmulator {
     /// Create a new terminal emulator with specified dimensions
     pub fn new(rows: usize, cols: usize) -> Self {
@@ -840,7 +837,7 @@ impl TerminalEmulator {
     }
 }
 
-#[cfg(feature = "hypervisor")]
+#[cfg(feature = "vte")]
 impl Perform for TerminalEmulator {
     /// Print a character
     fn print(&mut self, c: char) {
@@ -1253,25 +1250,25 @@ impl Perform for TerminalEmulator {
     }
 }
 
-/// Stub implementation for non-hypervisor builds
-#[cfg(not(feature = "hypervisor"))]
+/// Stub implementation for builds without the vte parser
+#[cfg(not(feature = "vte"))]
 pub struct TerminalEmulator {
     _private: (),
 }
 
-#[cfg(not(feature = "hypervisor"))]
+#[cfg(not(feature = "vte"))]
 impl TerminalEmulator {
     pub fn new(_rows: usize, _cols: usize) -> Self {
-        log::warn!("⚠️  Hypervisor feature not enabled. TerminalEmulator is a stub.");
+        log::warn!("⚠️  vte feature not enabled. TerminalEmulator is a stub.");
         Self { _private: () }
     }
 
     pub fn feed(&mut self, _bytes: &[u8]) {
-        log::warn!("⚠️  Hypervisor feature not enabled. feed() ignored.");
+        log::warn!("⚠️  vte feature not enabled. feed() ignored.");
     }
 
     pub fn resize(&mut self, _new_rows: usize, _new_cols: usize) {
-        log::warn!("⚠️  Hypervisor feature not enabled. resize() ignored.");
+        log::warn!("⚠️  vte feature not enabled. resize() ignored.");
     }
 
     pub fn get_buffer(&self) -> &TerminalBuffer {
@@ -1302,7 +1299,7 @@ impl TerminalEmulator {
 
     // Phase 30.8: Cursor Control Methods (stubs)
     pub fn set_cursor_visible(&mut self, _visible: bool) {
-        log::warn!("⚠️  Hypervisor feature not enabled. set_cursor_visible() ignored.");
+        log::warn!("⚠️  vte feature not enabled. set_cursor_visible() ignored.");
     }
 
     pub fn is_cursor_visible(&self) -> bool {
@@ -1310,7 +1307,7 @@ impl TerminalEmulator {
     }
 
     pub fn update_cursor_blink(&mut self, _delta_time: f32) {
-        log::warn!("⚠️  Hypervisor feature not enabled. update_cursor_blink() ignored.");
+        log::warn!("⚠️  vte feature not enabled. update_cursor_blink() ignored.");
     }
 
     pub fn get_cursor_blink_state(&self) -> f32 {
@@ -1379,14 +1376,14 @@ mod tests {
     }
 
     #[test]
-    #[cfg(feature = "hypervisor")]
+    #[cfg(feature = "vte")]
     fn test_terminal_emulator_creation() {
         let emulator = TerminalEmulator::new(24, 80);
         assert_eq!(emulator.get_size(), (24, 80));
     }
 
     #[test]
-    #[cfg(feature = "hypervisor")]
+    #[cfg(feature = "vte")]
     fn test_terminal_emulator_feed() {
         let mut emulator = TerminalEmulator::new(24, 80);
         emulator.feed(b"Hello");
@@ -1399,7 +1396,7 @@ mod tests {
     }
 
     #[test]
-    #[cfg(feature = "hypervisor")]
+    #[cfg(feature = "vte")]
     fn test_terminal_emulator_ansi_color() {
         let mut emulator = TerminalEmulator::new(24, 80);
         emulator.feed(b"\x1b[31mRed Text\x1b[0m");
@@ -1409,7 +1406,7 @@ mod tests {
     }
 
     #[test]
-    #[cfg(feature = "hypervisor")]
+    #[cfg(feature = "vte")]
     fn test_terminal_emulator_cursor_movement() {
         let mut emulator = TerminalEmulator::new(24, 80);
         emulator.feed(b"AB\x1b[2DC");
@@ -1421,7 +1418,7 @@ mod tests {
     }
 
     #[test]
-    #[cfg(feature = "hypervisor")]
+    #[cfg(feature = "vte")]
     fn test_terminal_emulator_clear_screen() {
         let mut emulator = TerminalEmulator::new(24, 80);
         emulator.feed(b"Hello\x1b[2J");
@@ -1431,7 +1428,7 @@ mod tests {
     }
 
     #[test]
-    #[cfg(feature = "hypervisor")]
+    #[cfg(feature = "vte")]
     fn test_key_to_ansi() {
         let emulator = TerminalEmulator::new(24, 80);
         assert_eq!(emulator.key_to_ansi("Up"), b"\x1b[A".to_vec());
@@ -1,11 +1,32 @@
 // systems/infinite_map_rs/src/terminal_tile.rs
 // Phase 30.7: Terminal Tile Rendering - GPU accelerated text buffers
 
-use crate::terminal_emulator::TerminalEmulator;
+use crate::cognitive::texture_updater::TextureQueue;
+use crate::damage_tracker::DirtyRect;
+use crate::terminal_clone::geometric_bridge::{
+    flags, palette_rgb, GeometricCell, GeometricTerminalBuffer, PaletteMatcher, TrueColorCell,
+};
+use crate::terminal_emulator::{TerminalCell, TerminalColor, TerminalEmulator};
+use std::cell::RefCell;
+use std::collections::HashMap;
 use std::time::Instant;
 
+/// Stable terminal tile id (never reused after removal)
+pub type TerminalTileId = usize;
+
+/// Per-tile cost counters for tuning
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TerminalMetrics {
+    /// Output bytes fed to the terminal
+    pub bytes_processed: u64,
+    /// Cells written (by the parser or a texture rebuild)
+    pub cells_updated: u64,
+    /// Times the CPU texture was redrawn
+    pub texture_rebuilds: u64,
+}
+
 pub struct TerminalTile {
-    pub id: usize,
+    pub id: TerminalTileId,
     pub title: String,
     pub width_chars: u32,
     pub height_chars: u32,
@@ -16,10 +37,14 @@ pub struct TerminalTile {
     pub last_update: Instant,
     pub emulator: TerminalEmulator,
     pub window_id: Option<usize>,
+    pub pty_id: Option<usize>,
+    /// Emulator contents as of the last texture update
+    cells: GeometricTerminalBuffer,
+    metrics: TerminalMetrics,
 }
 
 impl TerminalTile {
-    pub fn new(id: usize, title: String, width: u32, height: u32) -> Self {
+    pub fn new(id: TerminalTileId, title: String, width: u32, height: u32) -> Self {
         let tex_w = width * 8;
         let tex_h = height * 16;
         TerminalTile {
@@ -34,101 +59,351 @@ impl TerminalTile {
             last_update: Instant::now(),
             emulator: TerminalEmulator::new(height as usize, width as usize),
             window_id: None,
+            pty_id: None,
+            cells: GeometricTerminalBuffer::new(width as usize, height as usize),
+            metrics: TerminalMetrics::default(),
+        }
+    }
+
+    /// Feed output to the tile's own emulator
+    pub fn feed(&mut self, bytes: &[u8]) {
+        self.emulator.feed(bytes);
+        self.record_bytes(bytes.len());
+    }
+
+    /// Account for output fed to an external (PTY) emulator
+    pub fn record_bytes(&mut self, bytes: usize) {
+        if bytes > 0 {
+            self.metrics.bytes_processed += bytes as u64;
+            self.needs_render = true;
         }
     }
 
+    pub fn metrics(&self) -> TerminalMetrics {
+        self.metrics
+    }
+
     pub fn get_shader_buffer(&self, _emulator: &TerminalEmulator) -> Vec<u32> {
         // Return packed representation for compute shaders
         // packed: (char << 24) | (fg << 16) | (bg << 8) | flags
         Vec::new()
     }
 
+    /// Redraw the cells that changed since the last update
+    ///
+    /// The emulator is mirrored into a palette cell grid, with exact colors
+    /// for anything outside the 16 base colors; only its damaged regions
+    /// are flushed to the CPU texture.
     pub fn update_texture(&mut self, external_emulator: Option<&TerminalEmulator>) {
         if !self.needs_render && external_emulator.is_none() {
             return;
         }
 
-        let char_w = 8;
-        let char_h = 16;
-        let size_chars = (self.width_chars, self.height_chars);
+        let emu = external_emulator.unwrap_or(&self.emulator);
+        for y in 0..self.height_chars as usize {
+            for x in 0..self.width_chars as usize {
+                if let Some(cell) = emu.get_buffer().get_cell(y, x) {
+                    let (cell, truecolor) = geometric_cell(cell);
+                    self.cells.set_cell_with_truecolor(x, y, cell, truecolor);
+                }
+            }
+        }
+
+        let rasterizer = CellRasterizer {
+            texture_width: self.texture_width,
+            cols: self.cells.cols,
+            truecolor: std::mem::take(&mut self.cells.truecolor),
+        };
+        let texture = RefCell::new(std::mem::take(&mut self.texture_data));
+        let rects = self.cells.flush_damage(&rasterizer, &texture);
+        self.texture_data = texture.into_inner();
+        self.cells.truecolor = rasterizer.truecolor;
+
+        self.metrics.cells_updated += rects.iter().map(|r| r.area() as u64).sum::<u64>();
+        self.needs_render = false;
+        self.metrics.texture_rebuilds += 1;
+        self.last_update = Instant::now();
+    }
+}
 
-        // Clear background (Dark Blue-ish Black)
-        for i in 0..self.texture_data.len() / 4 {
-            self.texture_data[i * 4] = 10;
-            self.texture_data[i * 4 + 1] = 12;
-            self.texture_data[i * 4 + 2] = 15;
-            self.texture_data[i * 4 + 3] = 255;
+const CHAR_W: u32 = 8;
+const CHAR_H: u32 = 16;
+
+/// Background of cells with the default (black) background
+const CLEAR_COLOR: [u8; 3] = [10, 12, 15];
+
+/// Palette cell and exact colors for an emulator cell (inverse already applied)
+fn geometric_cell(cell: &TerminalCell) -> (GeometricCell, TrueColorCell) {
+    let attrs = &cell.attrs;
+    let mut style = 0;
+    for (set, flag) in [
+        (attrs.bold, flags::BOLD),
+        (attrs.dim, flags::DIM),
+        (attrs.italic, flags::ITALIC),
+        (attrs.underline, flags::UNDERLINE),
+        (attrs.blink, flags::BLINK),
+    ] {
+        if set {
+            style |= flag;
         }
+    }
+    let c = if cell.c.is_ascii() {
+        cell.c as u8
+    } else {
+        b'?'
+    };
+    let (fg, fg_rgb) = cell_color(attrs.effective_fg());
+    let (bg, bg_rgb) = cell_color(attrs.effective_bg());
+    let truecolor = TrueColorCell {
+        fg: fg_rgb,
+        bg: bg_rgb,
+    };
+    (GeometricCell::new(c, fg, bg, style), truecolor)
+}
 
-        // Render each character
-        for y in 0..size_chars.1 {
-            for x in 0..size_chars.0 {
-                // Determine which emulator to use and get the cell data
-                let (c, fg, bg) = {
-                    let emu = match external_emulator {
-                        Some(e) => e,
-                        None => &self.emulator,
-                    };
-
-                    let cell = match emu.get_buffer().get_cell(y as usize, x as usize) {
-                        Some(c) => c,
-                        None => continue,
-                    };
-
-                    let fg = cell.attrs.effective_fg().to_rgba();
-                    let bg = cell.attrs.effective_bg().to_rgba();
-                    (cell.c, fg, bg)
-                };
-
-                // Draw background if not black
-                if bg[0] != 0 || bg[1] != 0 || bg[2] != 0 {
-                    self.draw_rect_internal(
-                        (x * char_w) as u32,
-                        (y * char_h) as u32,
-                        char_w as u32,
-                        char_h as u32,
-                        [bg[0], bg[1], bg[2]],
-                    );
-                }
+/// Palette index of a color, plus its RGB when it is not one of the 16
+/// base colors (the index is then only the nearest fallback)
+fn cell_color(color: TerminalColor) -> (u8, Option<[u8; 3]>) {
+    let index = match color {
+        TerminalColor::Black => 0,
+        TerminalColor::Red => 1,
+        TerminalColor::Green => 2,
+        TerminalColor::Yellow => 3,
+        TerminalColor::Blue => 4,
+        TerminalColor::Magenta => 5,
+        TerminalColor::Cyan => 6,
+        TerminalColor::White => 7,
+        TerminalColor::BrightBlack => 8,
+        TerminalColor::BrightRed => 9,
+        TerminalColor::BrightGreen => 10,
+        TerminalColor::BrightYellow => 11,
+        TerminalColor::BrightBlue => 12,
+        TerminalColor::BrightMagenta => 13,
+        TerminalColor::BrightCyan => 14,
+        TerminalColor::BrightWhite => 15,
+        TerminalColor::Indexed(index) if index < 16 => index,
+        other => {
+            let [r, g, b, _] = other.to_rgba();
+            let rgb = [r, g, b];
+            return (PaletteMatcher::default().nearest(rgb).0, Some(rgb));
+        },
+    };
+    (index, None)
+}
 
-                // Skip drawing if character is space and background was already handleable
-                if c == ' ' {
-                    continue;
-                }
+/// Draws flushed cells (char, fg, bg, flags) into a tile's RGBA8 texture
+struct CellRasterizer {
+    texture_width: u32,
+    cols: usize,
+    /// Exact colors by cell index, overriding the palette
+    truecolor: HashMap<usize, TrueColorCell>,
+}
+
+impl TextureQueue for CellRasterizer {
+    type Texture = RefCell<Vec<u8>>;
 
-                // Draw character (Foreground)
-                self.draw_char_internal(
-                    c,
-                    (x * char_w) as u32,
-                    (y * char_h) as u32,
-                    [fg[0], fg[1], fg[2]],
-                );
+    fn write_region(&self, texture: &Self::Texture, data: &[u8], rect: &DirtyRect, _: u32) {
+        let mut pixels = texture.borrow_mut();
+        for (i, cell) in data.chunks_exact(4).enumerate() {
+            let col = rect.x1 + i as u32 % rect.width();
+            let row = rect.y1 + i as u32 / rect.width();
+            let exact = self
+                .truecolor
+                .get(&(row as usize * self.cols + col as usize))
+                .copied()
+                .unwrap_or_default();
+            let (x, y) = (col * CHAR_W, row * CHAR_H);
+            let bg = exact.bg.unwrap_or(match cell[2] {
+                0 => CLEAR_COLOR,
+                index => palette_rgb(index),
+            });
+            self.fill_rect(&mut pixels, x, y, bg);
+            if cell[0] != b' ' {
+                draw_char(cell[0], x, y, exact.fg.unwrap_or(palette_rgb(cell[1])));
             }
         }
+    }
+}
 
-        self.needs_render = false;
-        self.last_update = Instant::now();
+impl CellRasterizer {
+    fn fill_rect(&self, pixels: &mut [u8], x: u32, y: u32, color: [u8; 3]) {
+        for py in y..y + CHAR_H {
+            for px in x..x + CHAR_W {
+                let idx = ((py * self.texture_width + px) * 4) as usize;
+                if let Some(texel) = pixels.get_mut(idx..idx + 4) {
+                    texel.copy_from_slice(&[color[0], color[1], color[2], 255]);
+                }
+            }
+        }
+    }
+}
+
+fn draw_char(_c: u8, _x: u32, _y: u32, _color: [u8; 3]) {
+    // Placeholder for MSDF or bitmap font rendering
+}
+
+/// Terminal tiles keyed by stable id
+///
+/// Ids come from a monotonic counter, so removing a tile never shifts or
+/// hands out the id of another one.
+#[derive(Default)]
+pub struct TerminalTilePool {
+    tiles: HashMap<TerminalTileId, TerminalTile>,
+    next_id: TerminalTileId,
+}
+
+impl TerminalTilePool {
+    pub fn new() -> Self {
+        Self::default()
     }
 
-    fn draw_rect_internal(&mut self, x: u32, y: u32, w: u32, h: u32, color: [u8; 3]) {
-        let tex_w = self.texture_width;
+    /// Create a tile with the next id
+    pub fn create(&mut self, title: String, width: u32, height: u32) -> TerminalTileId {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.tiles
+            .insert(id, TerminalTile::new(id, title, width, height));
+        id
+    }
+
+    pub fn remove(&mut self, id: TerminalTileId) -> Option<TerminalTile> {
+        self.tiles.remove(&id)
+    }
+
+    pub fn get(&self, id: TerminalTileId) -> Option<&TerminalTile> {
+        self.tiles.get(&id)
+    }
 
-        for dy in 0..h {
-            for dx in 0..w {
-                let px = x + dx;
-                let py = y + dy;
-                let idx = ((py * tex_w + px) * 4) as usize;
-                if idx + 3 < self.texture_data.len() {
-                    self.texture_data[idx] = color[0];
-                    self.texture_data[idx + 1] = color[1];
-                    self.texture_data[idx + 2] = color[2];
-                    self.texture_data[idx + 3] = 255;
+    pub fn get_mut(&mut self, id: TerminalTileId) -> Option<&mut TerminalTile> {
+        self.tiles.get_mut(&id)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &TerminalTile> {
+        self.tiles.values()
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut TerminalTile> {
+        self.tiles.values_mut()
+    }
+
+    pub fn len(&self) -> usize {
+        self.tiles.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tiles.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[cfg(feature = "vte")]
+    fn test_metrics_track_bytes_cells_and_rebuilds() {
+        let mut tile = TerminalTile::new(0, "metrics".to_string(), 10, 4);
+        tile.update_texture(None);
+        assert_eq!(tile.metrics().texture_rebuilds, 1);
+        assert_eq!(tile.metrics().cells_updated, 40);
+
+        // Nothing new: no rebuild
+        tile.update_texture(None);
+        assert_eq!(tile.metrics().texture_rebuilds, 1);
+
+        tile.feed(b"hello\r\nworld");
+        assert_eq!(tile.metrics().bytes_processed, 12);
+        assert!(tile.needs_render);
+
+        tile.update_texture(None);
+        tile.record_bytes(0);
+        tile.update_texture(None);
+        let metrics = tile.metrics();
+        assert_eq!(metrics.texture_rebuilds, 2);
+        // Only the 5x2 block holding the text is redrawn
+        assert_eq!(metrics.cells_updated, 50);
+        assert_eq!(metrics.bytes_processed, 12);
+    }
+
+    #[test]
+    #[cfg(feature = "vte")]
+    fn test_update_redraws_only_changed_cells() {
+        let mut tile = TerminalTile::new(0, "damage".to_string(), 10, 4);
+        tile.update_texture(None);
+        let before = tile.texture_data.clone();
+        let width = tile.texture_width;
+        let texel = |data: &[u8], x: u32, y: u32| {
+            let idx = ((y * width + x) * 4) as usize;
+            data[idx..idx + 4].to_vec()
+        };
+        assert_eq!(texel(&before, 0, 0), vec![10, 12, 15, 255]);
+
+        // Red background in cell (2, 1)
+        tile.feed(b"\x1b[2;3H\x1b[41mX");
+        tile.update_texture(None);
+        assert_eq!(tile.metrics().cells_updated, 41);
+
+        let [r, g, b] = palette_rgb(1);
+        assert_eq!(texel(&tile.texture_data, 2 * 8, 16), vec![r, g, b, 255]);
+        assert_eq!(
+            texel(&tile.texture_data, 3 * 8 - 1, 2 * 16 - 1),
+            vec![r, g, b, 255]
+        );
+
+        // Every texel outside that cell is untouched
+        for y in 0..tile.texture_height {
+            for x in 0..tile.texture_width {
+                if (x / 8, y / 16) != (2, 1) {
+                    assert_eq!(texel(&tile.texture_data, x, y), texel(&before, x, y));
                 }
             }
         }
     }
 
-    fn draw_char_internal(&mut self, _c: char, _x: u32, _y: u32, _color: [u8; 3]) {
-        // Placeholder for MSDF or bitmap font rendering
+    #[test]
+    #[cfg(feature = "vte")]
+    fn test_colors_beyond_the_base_16_keep_their_rgb() {
+        let mut tile = TerminalTile::new(0, "truecolor".to_string(), 10, 4);
+        // Top-left texel of a cell in the first row
+        let texel = |data: &[u8], col: u32| {
+            let idx = (col * 8 * 4) as usize;
+            data[idx..idx + 4].to_vec()
+        };
+
+        // Base color, 24-bit color, and a 256-color index past 16
+        tile.feed(b"\x1b[44m \x1b[48;2;200;120;50m \x1b[48;5;196m ");
+        tile.update_texture(None);
+        let [r, g, b] = palette_rgb(4);
+        assert_eq!(texel(&tile.texture_data, 0), vec![r, g, b, 255]);
+        assert_eq!(texel(&tile.texture_data, 1), vec![200, 120, 50, 255]);
+        let [r, g, b, _] = TerminalColor::Indexed(196).to_rgba();
+        assert_eq!(texel(&tile.texture_data, 2), vec![r, g, b, 255]);
+
+        // A shade with the same nearest palette entry still redraws
+        tile.feed(b"\x1b[1;2H\x1b[48;2;201;120;50m ");
+        tile.update_texture(None);
+        assert_eq!(texel(&tile.texture_data, 1), vec![201, 120, 50, 255]);
+    }
+
+    #[test]
+    fn test_tile_ids_stay_stable_after_removal() {
+        let mut pool = TerminalTilePool::new();
+        let first = pool.create("first".to_string(), 10, 4);
+        let second = pool.create("second".to_string(), 10, 4);
+        assert_ne!(first, second);
+
+        assert!(pool.remove(first).is_some());
+        assert!(pool.remove(first).is_none());
+
+        let third = pool.create("third".to_string(), 10, 4);
+        assert_ne!(third, first);
+        assert_ne!(third, second);
+        assert_eq!(pool.len(), 2);
+
+        // The surviving tile keeps its id and contents
+        let tile = pool.get(second).unwrap();
+        assert_eq!((tile.id, tile.title.as_str()), (second, "second"));
+        assert_eq!(pool.get(third).unwrap().id, third);
+        assert!(pool.get(first).is_none());
     }
 }
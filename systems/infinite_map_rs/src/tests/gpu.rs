@@ -0,0 +1,41 @@
+// Shared headless wgpu device for GPU tests
+//
+// Machines without an adapter (most CI runners) should skip GPU tests rather
+// than fail them, so every test takes its device from `test_device` and
+// returns early on `None`.
+
+use std::sync::Arc;
+
+/// A device and queue on the default adapter, or `None` after printing a
+/// skip notice when no adapter is available
+pub fn test_device() -> Option<(Arc<wgpu::Device>, Arc<wgpu::Queue>)> {
+    let instance = wgpu::Instance::new(wgpu::InstanceDescriptor {
+        backends: wgpu::Backends::all(),
+        ..Default::default()
+    });
+
+    let device = pollster::block_on(instance.request_adapter(&wgpu::RequestAdapterOptions {
+        power_preference: wgpu::PowerPreference::default(),
+        compatible_surface: None,
+        force_fallback_adapter: false,
+    }))
+    .and_then(|adapter| {
+        pollster::block_on(adapter.request_device(
+            &wgpu::DeviceDescriptor {
+                label: Some("Test Device"),
+                required_features: wgpu::Features::empty(),
+                required_limits: wgpu::Limits::default(),
+            },
+            None,
+        ))
+        .ok()
+    });
+
+    match device {
+        Some((device, queue)) => Some((Arc::new(device), Arc::new(queue))),
+        None => {
+            println!("SKIP: No GPU available");
+            None
+        },
+    }
+}
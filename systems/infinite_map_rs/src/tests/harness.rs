@@ -0,0 +1,180 @@
+// Deterministic test harness for reproducible benchmark workloads
+//
+// Benchmarks that read the wall clock or an unseeded RNG produce a different
+// workload on every run. The harness replaces both: a clock that advances by
+// a fixed delta per frame, and a seeded RNG that generates the input
+// sequence, so the same seed always yields the same frames.
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::time::Duration;
+
+/// Frame delta used by `TestHarness::deterministic` (60 FPS)
+pub const DEFAULT_FRAME_DELTA: Duration = Duration::from_nanos(1_000_000_000 / 60);
+
+/// Most inputs generated for a single frame
+pub const MAX_INPUTS_PER_FRAME: usize = 3;
+
+/// Simulated time that only moves when told to
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeterministicClock {
+    elapsed: Duration,
+    frame_delta: Duration,
+    frame: u64,
+}
+
+impl DeterministicClock {
+    pub fn new(frame_delta: Duration) -> Self {
+        Self {
+            elapsed: Duration::ZERO,
+            frame_delta,
+            frame: 0,
+        }
+    }
+
+    /// Time since the clock started
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+
+    /// Frames ticked so far
+    pub fn frame(&self) -> u64 {
+        self.frame
+    }
+
+    pub fn frame_delta(&self) -> Duration {
+        self.frame_delta
+    }
+
+    /// Advance one frame and return its delta
+    pub fn tick(&mut self) -> Duration {
+        self.elapsed += self.frame_delta;
+        self.frame += 1;
+        self.frame_delta
+    }
+}
+
+/// Input event fed to a simulated frame
+#[derive(Debug, Clone, PartialEq)]
+pub enum SimulatedInput {
+    /// Pointer moved to normalized coordinates (0.0 - 1.0)
+    PointerMove { x: f32, y: f32 },
+    /// Mouse button pressed at normalized coordinates
+    Click { x: f32, y: f32 },
+    /// Scroll by this many lines (negative is up)
+    Scroll(f32),
+    /// Printable ASCII key press
+    Key(char),
+}
+
+/// One frame of a simulated workload
+#[derive(Debug, Clone, PartialEq)]
+pub struct SimulatedFrame {
+    pub index: u64,
+    /// Simulated time at the end of the frame
+    pub time: Duration,
+    pub delta: Duration,
+    pub inputs: Vec<SimulatedInput>,
+}
+
+/// Reproducible source of frames and randomness for benchmarks
+pub struct TestHarness {
+    seed: u64,
+    clock: DeterministicClock,
+    rng: StdRng,
+}
+
+impl TestHarness {
+    /// Harness with a fixed 60 FPS clock and an RNG seeded with `seed`
+    pub fn deterministic(seed: u64) -> Self {
+        Self {
+            seed,
+            clock: DeterministicClock::new(DEFAULT_FRAME_DELTA),
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+
+    /// Use a different fixed frame delta
+    pub fn with_frame_delta(mut self, frame_delta: Duration) -> Self {
+        self.clock = DeterministicClock::new(frame_delta);
+        self
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn clock(&self) -> &DeterministicClock {
+        &self.clock
+    }
+
+    /// Seeded RNG for workload data beyond the generated inputs
+    pub fn rng(&mut self) -> &mut StdRng {
+        &mut self.rng
+    }
+
+    /// Advance the clock one frame and generate that frame's inputs
+    pub fn next_frame(&mut self) -> SimulatedFrame {
+        let delta = self.clock.tick();
+        let count = self.rng.gen_range(0..=MAX_INPUTS_PER_FRAME);
+        let inputs = (0..count).map(|_| self.random_input()).collect();
+        SimulatedFrame {
+            index: self.clock.frame() - 1,
+            time: self.clock.elapsed(),
+            delta,
+            inputs,
+        }
+    }
+
+    /// The next `count` frames
+    pub fn frames(&mut self, count: usize) -> Vec<SimulatedFrame> {
+        (0..count).map(|_| self.next_frame()).collect()
+    }
+
+    fn random_input(&mut self) -> SimulatedInput {
+        match self.rng.gen_range(0..4) {
+            0 => SimulatedInput::PointerMove {
+                x: self.rng.gen(),
+                y: self.rng.gen(),
+            },
+            1 => SimulatedInput::Click {
+                x: self.rng.gen(),
+                y: self.rng.gen(),
+            },
+            2 => SimulatedInput::Scroll(self.rng.gen_range(-3.0..3.0)),
+            _ => SimulatedInput::Key(self.rng.gen_range(b' '..=b'~') as char),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_same_seed_reproduces_frames() {
+        let first = TestHarness::deterministic(42).frames(300);
+        let second = TestHarness::deterministic(42).frames(300);
+        assert_eq!(first, second);
+        assert!(first.iter().any(|frame| !frame.inputs.is_empty()));
+
+        let other = TestHarness::deterministic(43).frames(300);
+        assert_ne!(first, other);
+
+        // Frame times are exact multiples of the fixed delta
+        for (i, frame) in first.iter().enumerate() {
+            assert_eq!(frame.index, i as u64);
+            assert_eq!(frame.delta, DEFAULT_FRAME_DELTA);
+            assert_eq!(frame.time, DEFAULT_FRAME_DELTA * (i as u32 + 1));
+        }
+    }
+
+    #[test]
+    fn test_custom_frame_delta() {
+        let mut harness = TestHarness::deterministic(7).with_frame_delta(Duration::from_millis(10));
+        harness.frames(5);
+        assert_eq!(harness.clock().elapsed(), Duration::from_millis(50));
+        assert_eq!(harness.clock().frame(), 5);
+        assert_eq!(harness.seed(), 7);
+    }
+}
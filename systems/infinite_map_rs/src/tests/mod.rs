@@ -3,6 +3,10 @@
 pub mod brain_test;
 pub mod geometric_tests;
 pub mod glyph_write_test;
+pub mod gpu;
+pub mod harness;
 pub mod riscv_test_programs;
 pub mod self_hosting_test;
 pub mod trap_test;
+
+pub use harness::{DeterministicClock, SimulatedFrame, SimulatedInput, TestHarness};
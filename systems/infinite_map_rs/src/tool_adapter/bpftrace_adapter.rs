@@ -8,9 +8,103 @@
 // - Network activity
 // - Context switches
 
-use super::{check_binary_available, run_command, ToolAdapter, ToolMetrics};
+use super::{check_binary_available, ToolAdapter, ToolMetrics};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::time::Duration;
 
+/// How long each poll samples syscalls with bpftrace
+const SYSCALL_SAMPLE_MS: u64 = 500;
+
+/// Syscall rate (per second) above which health starts to drop
+const SYSCALL_RATE_WARNING: f64 = 200_000.0;
+
+/// Syscall rate (per second) at which the syscall score reaches 0
+const SYSCALL_RATE_CRITICAL: f64 = 1_000_000.0;
+
+/// Counts every syscall into `@syscalls`
+const SYSCALL_COUNT_SCRIPT: &str = "tracepoint:raw_syscalls:sys_enter { @syscalls = count(); }";
+
+/// Maps printed by bpftrace when a script exits
+///
+/// Scalar maps (`@name: 42`, `@name[key]: 42`) become counters keyed by the
+/// full map expression; `hist()`/`lhist()` maps become `(lower bound, count)`
+/// buckets in print order.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BpftraceMetrics {
+    pub counters: HashMap<String, u64>,
+    pub histograms: HashMap<String, Vec<(u64, u64)>>,
+}
+
+impl BpftraceMetrics {
+    /// Parse bpftrace's default text output; unrecognised lines are skipped
+    pub fn parse(output: &str) -> Self {
+        let mut metrics = Self::default();
+        let mut histogram: Option<String> = None;
+
+        for line in output.lines().map(str::trim) {
+            if line.starts_with('@') {
+                histogram = None;
+                match line.rsplit_once(':') {
+                    Some((name, "")) => {
+                        histogram = Some(name.to_string());
+                        metrics.histograms.entry(name.to_string()).or_default();
+                    },
+                    Some((name, value)) => {
+                        if let Ok(value) = value.trim().parse() {
+                            metrics.counters.insert(name.to_string(), value);
+                        }
+                    },
+                    None => {},
+                }
+            } else if let Some(name) = &histogram {
+                if let Some(bucket) = Self::parse_bucket(line) {
+                    if let Some(buckets) = metrics.histograms.get_mut(name) {
+                        buckets.push(bucket);
+                    }
+                }
+            }
+        }
+
+        metrics
+    }
+
+    /// Parse `[4, 8)   12 |@@@ |` into `(4, 12)`
+    fn parse_bucket(line: &str) -> Option<(u64, u64)> {
+        let rest = line.strip_prefix('[').or_else(|| line.strip_prefix('('))?;
+        let end = rest.find([']', ')'])?;
+        let (range, rest) = rest.split_at(end);
+        let lower = range.split(',').next()?.trim();
+        let lower = if lower == "..." {
+            0
+        } else {
+            Self::parse_size(lower)?
+        };
+        let count = rest[1..].split_whitespace().next()?.parse().ok()?;
+        Some((lower, count))
+    }
+
+    /// Parse bucket bounds with bpftrace's binary suffixes (`1K`, `64M`)
+    fn parse_size(value: &str) -> Option<u64> {
+        let (digits, shift) = match value.as_bytes().last()? {
+            b'K' => (&value[..value.len() - 1], 10),
+            b'M' => (&value[..value.len() - 1], 20),
+            b'G' => (&value[..value.len() - 1], 30),
+            b'T' => (&value[..value.len() - 1], 40),
+            _ => (value, 0),
+        };
+        digits.parse::<u64>().ok()?.checked_mul(1 << shift)
+    }
+
+    pub fn counter(&self, name: &str) -> Option<u64> {
+        self.counters.get(name).copied()
+    }
+
+    pub fn histogram(&self, name: &str) -> Option<&[(u64, u64)]> {
+        self.histograms.get(name).map(Vec::as_slice)
+    }
+}
+
 /// BpftraceAdapter for kernel-level metrics
 ///
 /// This adapter uses bpftrace to collect low-level system metrics
@@ -23,6 +117,9 @@ use std::time::Duration;
 pub struct BpftraceAdapter {
     /// Whether bpftrace is available
     available: bool,
+    /// Set once bpftrace refuses to trace for lack of privileges, so it is
+    /// not respawned on every poll
+    sampling_denied: AtomicBool,
     /// Last poll result (for smoothing)
     #[allow(dead_code)]
     last_metrics: Option<ToolMetrics>,
@@ -43,6 +140,7 @@ impl BpftraceAdapter {
 
         Self {
             available,
+            sampling_denied: AtomicBool::new(false),
             last_metrics: None,
         }
     }
@@ -72,27 +170,61 @@ impl BpftraceAdapter {
 
         writeln!(file, "{}", script).map_err(|e| format!("Failed to write script: {}", e))?;
 
-        // Run bpftrace
-        let output = run_command(
-            "bpftrace",
-            &[
-                "-f",
-                "json",
+        // Run bpftrace (stderr is kept so privilege errors can be recognised)
+        let output = std::process::Command::new("bpftrace")
+            .args([
                 "-e",
                 &format!("interval:ms:{} {{ exit(); }} {}", duration_ms, script),
-            ],
-        );
+            ])
+            .output()
+            .map_err(|e| format!("Failed to execute command: {}", e));
 
         // Clean up temp file
         let _ = std::fs::remove_file(temp_file);
 
-        output
+        let output = output?;
+        if !output.status.success() {
+            return Err(format!(
+                "Command failed with exit code {}: {}",
+                output.status.code().unwrap_or(-1),
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).to_string())
+    }
+
+    /// Sample syscalls with bpftrace
+    ///
+    /// Returns the parsed maps and the syscall rate per second. After a
+    /// privilege error this fails immediately without running bpftrace.
+    fn sample_syscalls(&self) -> Result<(BpftraceMetrics, f64), String> {
+        if self.sampling_denied.load(Ordering::Relaxed) {
+            return Err("bpftrace tracing not permitted".to_string());
+        }
+        let output = self
+            .run_bpftrace_script(SYSCALL_COUNT_SCRIPT, SYSCALL_SAMPLE_MS)
+            .map_err(|e| {
+                if is_permission_error(&e) {
+                    log::warn!(
+                        "🔧 BpftraceAdapter: tracing not permitted, sampling disabled: {}",
+                        e
+                    );
+                    self.sampling_denied.store(true, Ordering::Relaxed);
+                }
+                e
+            })?;
+        let metrics = BpftraceMetrics::parse(&output);
+        let count = metrics
+            .counter("@syscalls")
+            .ok_or("bpftrace output has no @syscalls count")?;
+        let rate = count as f64 * 1000.0 / SYSCALL_SAMPLE_MS as f64;
+        Ok((metrics, rate))
     }
 
-    /// Get system call rate
+    /// Get total context switches since boot from /proc/stat
     ///
-    /// Returns system calls per second
-    fn get_syscall_rate(&self) -> Result<f64, String> {
+    /// Used for the status line when bpftrace sampling is unavailable
+    fn get_context_switches(&self) -> Result<f64, String> {
         // Use /proc/stat for faster access
         use std::fs;
 
@@ -186,7 +318,7 @@ impl BpftraceAdapter {
     /// Calculate health score from kernel metrics
     ///
     /// Returns score (0.0 - 1.0)
-    fn calculate_health_score(&self, _syscall_rate: f64, io_wait: f32) -> f32 {
+    fn calculate_health_score(&self, syscall_rate: f64, io_wait: f32) -> f32 {
         // I/O wait is critical (high = disk bottleneck)
         let io_score = if io_wait <= 5.0 {
             1.0
@@ -197,13 +329,28 @@ impl BpftraceAdapter {
         };
 
         // Syscall rate is less critical (high = busy system)
-        let syscall_score = 1.0; // Default to healthy
+        let syscall_score = if syscall_rate <= SYSCALL_RATE_WARNING {
+            1.0
+        } else if syscall_rate >= SYSCALL_RATE_CRITICAL {
+            0.0
+        } else {
+            (1.0 - (syscall_rate - SYSCALL_RATE_WARNING)
+                / (SYSCALL_RATE_CRITICAL - SYSCALL_RATE_WARNING)) as f32
+        };
 
         // Weighted average (I/O wait is more important)
         io_score * 0.7 + syscall_score * 0.3
     }
 }
 
+/// Whether a bpftrace failure comes from missing privileges
+fn is_permission_error(message: &str) -> bool {
+    let message = message.to_lowercase();
+    ["not permitted", "permission denied", "root user"]
+        .iter()
+        .any(|pattern| message.contains(pattern))
+}
+
 impl ToolAdapter for BpftraceAdapter {
     fn name(&self) -> &str {
         "bpftrace"
@@ -220,6 +367,7 @@ impl ToolAdapter for BpftraceAdapter {
                 health_score: 1.0,
                 status: "N/A (bpftrace not available)".to_string(),
                 raw_data: "unavailable".to_string(),
+                bpftrace: None,
                 timestamp: std::time::Instant::now(),
             });
         }
@@ -227,26 +375,41 @@ impl ToolAdapter for BpftraceAdapter {
         // Collect metrics from /proc filesystem (faster than bpftrace)
         let io_wait = self.get_io_wait()?;
         let network = self.get_network_activity()?;
-        let syscall_rate = self.get_syscall_rate().unwrap_or(0.0);
 
-        let health_score = self.calculate_health_score(syscall_rate, io_wait);
+        // Tracing needs privileges; without them only score I/O wait
+        let (bpftrace, syscall_rate) = match self.sample_syscalls() {
+            Ok((metrics, rate)) => (Some(metrics), Some(rate)),
+            Err(e) => {
+                log::debug!("🔧 BpftraceAdapter: syscall sampling failed: {}", e);
+                (None, None)
+            },
+        };
+
+        let health_score = self.calculate_health_score(syscall_rate.unwrap_or(0.0), io_wait);
 
+        let syscalls = match syscall_rate {
+            Some(rate) => format!("SYSCALLS: {:.0}/s", rate),
+            None => format!("CTXT: {:.0}", self.get_context_switches().unwrap_or(0.0)),
+        };
         let status = format!(
-            "I/O WAIT: {:.1}% | NET: {} MB/s | SYSCALLS: {:.0}",
+            "I/O WAIT: {:.1}% | NET: {} MB/s | {}",
             io_wait,
             network / (1024 * 1024),
-            syscall_rate
+            syscalls
         );
 
         let raw_data = format!(
             "io_wait={:.2},network_bytes={},syscall_rate={:.0}",
-            io_wait, network, syscall_rate
+            io_wait,
+            network,
+            syscall_rate.unwrap_or(0.0)
         );
 
         Ok(ToolMetrics {
             health_score,
             status,
             raw_data,
+            bpftrace,
             timestamp: std::time::Instant::now(),
         })
     }
@@ -270,6 +433,25 @@ mod tests {
         assert_eq!(adapter.name(), "bpftrace");
     }
 
+    #[test]
+    fn test_permission_errors_disable_sampling() {
+        assert!(is_permission_error(
+            "Command failed with exit code 1: ERROR: bpftrace currently only supports running as the root user."
+        ));
+        assert!(is_permission_error("Operation not permitted"));
+        assert!(!is_permission_error(
+            "bpftrace output has no @syscalls count"
+        ));
+
+        // Once denied, sampling fails without spawning bpftrace
+        let adapter = BpftraceAdapter::new();
+        adapter.sampling_denied.store(true, Ordering::Relaxed);
+        assert_eq!(
+            adapter.sample_syscalls().unwrap_err(),
+            "bpftrace tracing not permitted"
+        );
+    }
+
     #[test]
     fn test_calculate_health_score() {
         let adapter = BpftraceAdapter::new();
@@ -286,4 +468,55 @@ mod tests {
         let score3 = adapter.calculate_health_score(10000.0, 12.5);
         assert!(score3 > 0.3 && score3 < 0.7);
     }
+
+    #[test]
+    fn test_parse_bpftrace_maps() {
+        let output = "Attaching 2 probes...
+
+@syscalls: 48213
+@calls[read]: 1200
+@calls[tracepoint:syscalls:sys_enter_write]: 800
+
+@latency_us:
+[0]                   12 |@@@@                                 |
+[1]                    3 |@                                    |
+[2, 4)                40 |@@@@@@@@@@@@@@@@@@@@@@@@@@@@@@@@@@@@@|
+[4, 8)                 0 |                                     |
+[1K, 2K)               7 |@@@@@@                               |
+
+@bytes[sshd]:
+(..., 0)               1 |@                                    |
+[0, 100)               9 |@@@@@@@@@                            |
+[100, ...)             2 |@@                                   |
+";
+        let metrics = BpftraceMetrics::parse(output);
+
+        assert_eq!(metrics.counters.len(), 3);
+        assert_eq!(metrics.counter("@syscalls"), Some(48213));
+        assert_eq!(metrics.counter("@calls[read]"), Some(1200));
+        assert_eq!(
+            metrics.counter("@calls[tracepoint:syscalls:sys_enter_write]"),
+            Some(800)
+        );
+
+        assert_eq!(
+            metrics.histogram("@latency_us"),
+            Some(&[(0, 12), (1, 3), (2, 40), (4, 0), (1024, 7)][..])
+        );
+        assert_eq!(
+            metrics.histogram("@bytes[sshd]"),
+            Some(&[(0, 1), (0, 9), (100, 2)][..])
+        );
+    }
+
+    #[test]
+    fn test_syscall_rate_lowers_health() {
+        let adapter = BpftraceAdapter::new();
+        let calm = adapter.calculate_health_score(SYSCALL_RATE_WARNING, 0.0);
+        let busy = adapter.calculate_health_score(600_000.0, 0.0);
+        let flooded = adapter.calculate_health_score(SYSCALL_RATE_CRITICAL * 2.0, 0.0);
+        assert_eq!(calm, 1.0);
+        assert!((busy - 0.85).abs() < 1e-6, "{}", busy);
+        assert!((flooded - 0.7).abs() < 1e-6);
+    }
 }
@@ -1,17 +1,77 @@
 // src/tool_adapter/btop_adapter.rs
 // Btop Adapter - Phase 2
-// System metrics collection using btop (or htop as fallback)
+// System metrics collection (the same figures btop shows)
 //
-// This adapter polls btop for CPU, memory, and system load metrics
-// and computes a health score based on resource utilization.
+// This adapter reads CPU, memory, and system load metrics straight from
+// /proc (no subprocess) and computes a health score based on resource
+// utilization.
 
-use super::{check_binary_available, ToolAdapter, ToolMetrics};
+use super::{ToolAdapter, ToolMetrics};
+use parking_lot::Mutex;
+use std::path::Path;
 use std::time::Duration;
 
+/// Aggregate CPU jiffies from the first line of /proc/stat
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CpuSnapshot {
+    /// Jiffies spent doing work (everything except idle and iowait)
+    pub busy: u64,
+    /// All jiffies
+    pub total: u64,
+}
+
+impl CpuSnapshot {
+    /// Parse the aggregate `cpu` line of /proc/stat
+    pub fn parse(stat: &str) -> Result<Self, String> {
+        let line = stat
+            .lines()
+            .find(|line| line.starts_with("cpu "))
+            .ok_or("No aggregate cpu line in /proc/stat")?;
+
+        // user nice system idle iowait irq softirq steal (guest time is
+        // already included in user/nice)
+        let fields = line
+            .split_whitespace()
+            .skip(1)
+            .take(8)
+            .map(|field| {
+                field
+                    .parse::<u64>()
+                    .map_err(|e| format!("Failed to parse cpu time '{}': {}", field, e))
+            })
+            .collect::<Result<Vec<u64>, String>>()?;
+        if fields.len() < 4 {
+            return Err("Invalid /proc/stat format".to_string());
+        }
+
+        let total: u64 = fields.iter().sum();
+        let idle = fields[3] + fields.get(4).copied().unwrap_or(0);
+        Ok(Self {
+            busy: total - idle,
+            total,
+        })
+    }
+
+    /// Utilization (0.0 - 100.0) between `previous` and this snapshot
+    pub fn utilization_since(&self, previous: &CpuSnapshot) -> f32 {
+        let total = self.total.saturating_sub(previous.total);
+        let busy = self.busy.saturating_sub(previous.busy);
+        if total == 0 {
+            return 0.0;
+        }
+        (busy as f64 / total as f64 * 100.0) as f32
+    }
+
+    /// Utilization (0.0 - 100.0) averaged since boot
+    pub fn utilization(&self) -> f32 {
+        self.utilization_since(&CpuSnapshot { busy: 0, total: 0 })
+    }
+}
+
 /// BtopAdapter for system metrics collection
 ///
-/// This adapter uses btop (or htop as fallback) to collect:
-/// - CPU utilization
+/// This adapter reads /proc to collect:
+/// - CPU utilization (delta against the previous poll)
 /// - Memory usage
 /// - System load averages
 ///
@@ -19,10 +79,12 @@ use std::time::Duration;
 /// - 1.0 if all metrics are within healthy ranges
 /// - Decreases linearly as metrics approach critical thresholds
 pub struct BtopAdapter {
-    /// Whether to use btop (true) or htop (false)
-    use_btop: bool,
     /// Thresholds for health calculation
     thresholds: HealthThresholds,
+    /// Number of CPU cores, read once at startup
+    cpu_cores: usize,
+    /// /proc/stat snapshot from the previous poll
+    last_cpu: Mutex<Option<CpuSnapshot>>,
 }
 
 /// Health thresholds for system metrics
@@ -57,70 +119,40 @@ impl Default for HealthThresholds {
 
 impl BtopAdapter {
     /// Create a new BtopAdapter
-    ///
-    /// Automatically detects if btop is available, falls back to htop.
     pub fn new() -> Self {
-        let use_btop = check_binary_available("btop");
-
+        let cpu_cores = Self::read_cpu_cores();
         log::info!(
-            "🔧 BtopAdapter: Using {} for system metrics",
-            if use_btop { "btop" } else { "htop" }
+            "🔧 BtopAdapter: Reading system metrics from /proc ({} cores)",
+            cpu_cores
         );
 
         Self {
-            use_btop,
             thresholds: HealthThresholds::default(),
-        }
-    }
-
-    /// Get the binary name to use (btop or htop)
-    fn binary_name(&self) -> &str {
-        if self.use_btop {
-            "btop"
-        } else {
-            "htop"
+            cpu_cores,
+            last_cpu: Mutex::new(None),
         }
     }
 
     /// Parse CPU utilization from /proc/stat
     ///
-    /// Returns average CPU utilization as percentage (0.0 - 100.0)
+    /// Returns CPU utilization as percentage (0.0 - 100.0) since the last poll
     fn parse_cpu_utilization(&self) -> Result<f32, String> {
-        use std::fs;
-
-        let stat = fs::read_to_string("/proc/stat")
+        let stat = std::fs::read_to_string("/proc/stat")
             .map_err(|e| format!("Failed to read /proc/stat: {}", e))?;
+        self.cpu_utilization_from(&stat)
+    }
 
-        // Parse first line (aggregate CPU)
-        let line = stat.lines().next().ok_or("No data in /proc/stat")?;
-
-        let parts: Vec<&str> = line.split_whitespace().collect();
-        if parts.len() < 8 {
-            return Err("Invalid /proc/stat format".to_string());
-        }
-
-        // Calculate CPU usage from user, nice, system, idle times
-        let user: f64 = parts[1]
-            .parse()
-            .map_err(|e| format!("Failed to parse user time: {}", e))?;
-        let nice: f64 = parts[2]
-            .parse()
-            .map_err(|e| format!("Failed to parse nice time: {}", e))?;
-        let system: f64 = parts[3]
-            .parse()
-            .map_err(|e| format!("Failed to parse system time: {}", e))?;
-        let idle: f64 = parts[4]
-            .parse()
-            .map_err(|e| format!("Failed to parse idle time: {}", e))?;
-
-        let total = user + nice + system + idle;
-        let usage = if total > 0.0 {
-            ((user + nice + system) / total) * 100.0
-        } else {
-            0.0
-        };
-
-        Ok(usage as f32)
+    /// Utilization for a /proc/stat snapshot, relative to the cached one
+    ///
+    /// The first snapshot has nothing to compare against, so it reports the
+    /// average since boot.
+    fn cpu_utilization_from(&self, stat: &str) -> Result<f32, String> {
+        let snapshot = CpuSnapshot::parse(stat)?;
+        let previous = self.last_cpu.lock().replace(snapshot);
+        Ok(match previous {
+            Some(previous) => snapshot.utilization_since(&previous),
+            None => snapshot.utilization(),
+        })
     }
 
     /// Parse memory usage from /proc/meminfo
@@ -183,14 +215,15 @@ impl BtopAdapter {
     }
 
     /// Get number of CPU cores
-    fn get_cpu_cores(&self) -> usize {
+    fn read_cpu_cores() -> usize {
         use std::fs;
 
         match fs::read_to_string("/proc/cpuinfo") {
             Ok(cpuinfo) => cpuinfo
                 .lines()
                 .filter(|line| line.starts_with("processor"))
-                .count(),
+                .count()
+                .max(1),
             Err(_) => 1, // Fallback to single core
         }
     }
@@ -211,7 +244,7 @@ impl BtopAdapter {
             self.thresholds.memory_critical,
         );
 
-        let cpu_cores = self.get_cpu_cores() as f32;
+        let cpu_cores = self.cpu_cores as f32;
         let load_per_core = load / cpu_cores;
         let load_score = self.calculate_metric_score(
             load_per_core,
@@ -244,11 +277,11 @@ impl ToolAdapter for BtopAdapter {
     }
 
     fn is_available(&self) -> bool {
-        check_binary_available(self.binary_name())
+        Path::new("/proc/stat").exists() && Path::new("/proc/meminfo").exists()
     }
 
     fn poll(&self) -> Result<ToolMetrics, String> {
-        // Collect metrics from /proc filesystem (more efficient than spawning btop)
+        // Collect metrics from /proc filesystem (no subprocess per poll)
         let cpu = self.parse_cpu_utilization()?;
         let memory = self.parse_memory_usage()?;
         let load = self.parse_load_average()?;
@@ -266,6 +299,7 @@ impl ToolAdapter for BtopAdapter {
             health_score,
             status,
             raw_data,
+            bpftrace: None,
             timestamp: std::time::Instant::now(),
         })
     }
@@ -309,4 +343,24 @@ mod tests {
         let mid = adapter.calculate_metric_score(80.0, 70.0, 90.0);
         assert!(mid > 0.0 && mid < 1.0);
     }
+
+    #[test]
+    fn test_cpu_utilization_uses_snapshot_delta() {
+        let adapter = BtopAdapter::new();
+        let first = "cpu  1000 0 1000 7000 1000 0 0 0 0 0\ncpu0 1000 0 1000 7000 1000 0 0 0 0 0\n";
+        let second =
+            "cpu  1300 50 1150 7400 1100 0 0 0 0 0\ncpu0 1300 50 1150 7400 1100 0 0 0 0 0\n";
+
+        // No previous snapshot: average since boot (2000 busy of 10000)
+        let boot = adapter.cpu_utilization_from(first).unwrap();
+        assert!((boot - 20.0).abs() < 1e-4, "{}", boot);
+
+        // Delta: 500 busy jiffies out of 1000
+        let delta = adapter.cpu_utilization_from(second).unwrap();
+        assert!((delta - 50.0).abs() < 1e-4, "{}", delta);
+
+        // Identical snapshot: no elapsed time
+        assert_eq!(adapter.cpu_utilization_from(second).unwrap(), 0.0);
+        assert!(adapter.cpu_utilization_from("intr 0\n").is_err());
+    }
 }
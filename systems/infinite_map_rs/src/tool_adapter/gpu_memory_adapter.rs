@@ -0,0 +1,406 @@
+// src/tool_adapter/gpu_memory_adapter.rs
+// GPU Memory Adapter - Phase 2
+// Real VRAM usage from the driver
+//
+// The System PAS score otherwise relies on a software VRAM counter, which
+// drifts from what the driver actually has allocated. This adapter reads
+// the real numbers from DRM sysfs (amdgpu) or `nvidia-smi` and scores the
+// remaining headroom.
+
+use super::{check_binary_available, run_command, ToolAdapter, ToolMetrics};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// VRAM usage (percentage) above which health starts to drop
+const VRAM_WARNING: f32 = 75.0;
+
+/// VRAM usage (percentage) at which health reaches 0
+const VRAM_CRITICAL: f32 = 95.0;
+
+/// Used and total VRAM across every GPU a source reports
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GpuMemoryReading {
+    pub used_bytes: u64,
+    pub total_bytes: u64,
+}
+
+impl GpuMemoryReading {
+    /// Used VRAM as a percentage (0.0 - 100.0) of the total
+    pub fn usage_percent(&self) -> f32 {
+        if self.total_bytes == 0 {
+            return 0.0;
+        }
+        (self.used_bytes as f64 / self.total_bytes as f64 * 100.0).min(100.0) as f32
+    }
+}
+
+/// Where GPU memory figures come from
+pub trait GpuMemorySource: Send + Sync {
+    /// Short name shown in the status line (e.g. "nvidia-smi")
+    fn name(&self) -> &str;
+
+    /// Read current VRAM usage
+    fn read(&self) -> Result<GpuMemoryReading, String>;
+}
+
+/// `nvidia-smi` memory query (NVIDIA proprietary driver)
+pub struct NvidiaSmiSource;
+
+impl NvidiaSmiSource {
+    /// Parse `--query-gpu=memory.used,memory.total --format=csv,noheader,nounits`
+    ///
+    /// One `used, total` line (MiB) per GPU; the readings are summed.
+    pub fn parse(output: &str) -> Result<GpuMemoryReading, String> {
+        let mut reading = GpuMemoryReading {
+            used_bytes: 0,
+            total_bytes: 0,
+        };
+        for line in output.lines().filter(|line| !line.trim().is_empty()) {
+            let mut fields = line.split(',').map(|field| {
+                let field = field.trim();
+                field
+                    .parse::<u64>()
+                    .map(|mib| mib * 1024 * 1024)
+                    .map_err(|e| format!("Failed to parse nvidia-smi value '{}': {}", field, e))
+            });
+            let (Some(used), Some(total)) = (fields.next(), fields.next()) else {
+                return Err(format!("Invalid nvidia-smi line: '{}'", line));
+            };
+            reading.used_bytes += used?;
+            reading.total_bytes += total?;
+        }
+        if reading.total_bytes == 0 {
+            return Err("nvidia-smi reported no GPU memory".to_string());
+        }
+        Ok(reading)
+    }
+
+    /// Parse `--query-gpu=pci.device_id,memory.total --format=csv,noheader,nounits`
+    ///
+    /// Returns the total (in bytes) of the first GPU whose PCI device id is
+    /// `device`. nvidia-smi packs the id as `0xDDDDVVVV` (device, vendor).
+    pub fn parse_device_total(output: &str, device: u32) -> Option<u64> {
+        output.lines().find_map(|line| {
+            let (id, total) = line.split_once(',')?;
+            let id = u32::from_str_radix(id.trim().trim_start_matches("0x"), 16).ok()?;
+            if id >> 16 != device {
+                return None;
+            }
+            total
+                .trim()
+                .parse::<u64>()
+                .ok()
+                .map(|mib| mib * 1024 * 1024)
+        })
+    }
+
+    /// Total memory of the GPU with PCI device id `device`
+    pub fn device_total(device: u32) -> Option<u64> {
+        let output = run_command(
+            "nvidia-smi",
+            &[
+                "--query-gpu=pci.device_id,memory.total",
+                "--format=csv,noheader,nounits",
+            ],
+        )
+        .ok()?;
+        Self::parse_device_total(&output, device)
+    }
+}
+
+impl GpuMemorySource for NvidiaSmiSource {
+    fn name(&self) -> &str {
+        "nvidia-smi"
+    }
+
+    fn read(&self) -> Result<GpuMemoryReading, String> {
+        let output = run_command(
+            "nvidia-smi",
+            &[
+                "--query-gpu=memory.used,memory.total",
+                "--format=csv,noheader,nounits",
+            ],
+        )?;
+        Self::parse(&output)
+    }
+}
+
+/// DRM sysfs VRAM counters (`mem_info_vram_used` / `mem_info_vram_total`)
+///
+/// Exposed by amdgpu under `/sys/class/drm/cardN/device`.
+pub struct DrmSysfsSource {
+    devices: Vec<PathBuf>,
+}
+
+impl DrmSysfsSource {
+    /// Find every DRM card exposing VRAM counters under `drm_root`
+    pub fn discover(drm_root: &Path) -> Option<Self> {
+        let mut devices: Vec<PathBuf> = std::fs::read_dir(drm_root)
+            .ok()?
+            .flatten()
+            .filter(|entry| {
+                let name = entry.file_name();
+                let name = name.to_string_lossy();
+                // cardN, not connectors like card0-DP-1
+                name.strip_prefix("card")
+                    .is_some_and(|n| n.chars().all(|c| c.is_ascii_digit()))
+            })
+            .map(|entry| entry.path().join("device"))
+            .filter(|device| device.join("mem_info_vram_total").exists())
+            .collect();
+        devices.sort();
+
+        if devices.is_empty() {
+            None
+        } else {
+            Some(Self { devices })
+        }
+    }
+
+    /// The DRM card with this PCI vendor and device id, if it exposes VRAM
+    /// counters
+    pub fn discover_device(drm_root: &Path, vendor: u32, device: u32) -> Option<Self> {
+        let pci_id = |path: PathBuf| -> Option<u32> {
+            let value = std::fs::read_to_string(path).ok()?;
+            u32::from_str_radix(value.trim().trim_start_matches("0x"), 16).ok()
+        };
+        let found = Self::discover(drm_root)?.devices.into_iter().find(|dir| {
+            pci_id(dir.join("vendor")) == Some(vendor) && pci_id(dir.join("device")) == Some(device)
+        })?;
+        Some(Self {
+            devices: vec![found],
+        })
+    }
+
+    fn read_counter(path: &Path) -> Result<u64, String> {
+        let value = std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        value
+            .trim()
+            .parse()
+            .map_err(|e| format!("Failed to parse {}: {}", path.display(), e))
+    }
+}
+
+impl GpuMemorySource for DrmSysfsSource {
+    fn name(&self) -> &str {
+        "drm"
+    }
+
+    fn read(&self) -> Result<GpuMemoryReading, String> {
+        let mut reading = GpuMemoryReading {
+            used_bytes: 0,
+            total_bytes: 0,
+        };
+        for device in &self.devices {
+            reading.used_bytes += Self::read_counter(&device.join("mem_info_vram_used"))?;
+            reading.total_bytes += Self::read_counter(&device.join("mem_info_vram_total"))?;
+        }
+        Ok(reading)
+    }
+}
+
+/// GpuMemoryAdapter for VRAM pressure
+///
+/// Health is 1.0 while usage is below the warning threshold and drops
+/// linearly to 0.0 at the critical threshold. Without a memory source the
+/// adapter reports itself unavailable.
+pub struct GpuMemoryAdapter {
+    source: Option<Box<dyn GpuMemorySource>>,
+}
+
+impl GpuMemoryAdapter {
+    /// Create an adapter using the first source found on this system
+    ///
+    /// DRM sysfs is preferred since it needs no subprocess.
+    pub fn new() -> Self {
+        let source: Option<Box<dyn GpuMemorySource>> =
+            match DrmSysfsSource::discover(Path::new("/sys/class/drm")) {
+                Some(drm) => Some(Box::new(drm)),
+                None if check_binary_available("nvidia-smi") => Some(Box::new(NvidiaSmiSource)),
+                None => None,
+            };
+
+        match &source {
+            Some(source) => log::info!("🔧 GpuMemoryAdapter: Reading VRAM via {}", source.name()),
+            None => log::warn!("⚠️  GpuMemoryAdapter: No GPU memory source found"),
+        }
+
+        Self { source }
+    }
+
+    /// Create an adapter reading from a specific source
+    pub fn with_source(source: Box<dyn GpuMemorySource>) -> Self {
+        Self {
+            source: Some(source),
+        }
+    }
+
+    /// Health score (0.0 - 1.0) for a VRAM usage percentage
+    fn calculate_health_score(&self, usage: f32) -> f32 {
+        if usage <= VRAM_WARNING {
+            1.0
+        } else if usage >= VRAM_CRITICAL {
+            0.0
+        } else {
+            1.0 - (usage - VRAM_WARNING) / (VRAM_CRITICAL - VRAM_WARNING)
+        }
+    }
+}
+
+impl Default for GpuMemoryAdapter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ToolAdapter for GpuMemoryAdapter {
+    fn name(&self) -> &str {
+        "gpu_memory"
+    }
+
+    fn is_available(&self) -> bool {
+        self.source.is_some()
+    }
+
+    fn poll(&self) -> Result<ToolMetrics, String> {
+        let source = self
+            .source
+            .as_ref()
+            .ok_or("No GPU memory source available")?;
+        let reading = source.read()?;
+        let usage = reading.usage_percent();
+
+        const MIB: u64 = 1024 * 1024;
+        let status = format!(
+            "VRAM: {:.1}% ({} / {} MiB via {})",
+            usage,
+            reading.used_bytes / MIB,
+            reading.total_bytes / MIB,
+            source.name()
+        );
+        let raw_data = format!(
+            "vram_used_bytes={},vram_total_bytes={},vram_usage={:.2}",
+            reading.used_bytes, reading.total_bytes, usage
+        );
+
+        Ok(ToolMetrics {
+            health_score: self.calculate_health_score(usage),
+            status,
+            raw_data,
+            bpftrace: None,
+            timestamp: std::time::Instant::now(),
+        })
+    }
+
+    fn polling_interval(&self) -> Duration {
+        Duration::from_secs(2)
+    }
+
+    fn weight(&self) -> f32 {
+        1.5
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU64, Ordering};
+    use std::sync::Arc;
+
+    /// Memory source whose usage the test controls
+    struct MockSource {
+        used_bytes: Arc<AtomicU64>,
+        total_bytes: u64,
+    }
+
+    impl GpuMemorySource for MockSource {
+        fn name(&self) -> &str {
+            "mock"
+        }
+
+        fn read(&self) -> Result<GpuMemoryReading, String> {
+            Ok(GpuMemoryReading {
+                used_bytes: self.used_bytes.load(Ordering::Relaxed),
+                total_bytes: self.total_bytes,
+            })
+        }
+    }
+
+    #[test]
+    fn test_health_drops_as_vram_fills() {
+        let used = Arc::new(AtomicU64::new(0));
+        let adapter = GpuMemoryAdapter::with_source(Box::new(MockSource {
+            used_bytes: Arc::clone(&used),
+            total_bytes: 1000,
+        }));
+        assert!(adapter.is_available());
+
+        let mut previous = f32::INFINITY;
+        let mut scores = Vec::new();
+        for used_bytes in [100, 750, 800, 850, 900, 950, 1000] {
+            used.store(used_bytes, Ordering::Relaxed);
+            let score = adapter.poll().unwrap().health_score;
+            assert!(score <= previous, "{} at {} bytes", score, used_bytes);
+            previous = score;
+            scores.push(score);
+        }
+        assert_eq!(scores[0], 1.0);
+        assert_eq!(scores[1], 1.0);
+        assert!(scores[2] < 1.0 && scores[4] > 0.0 && scores[2] > scores[4]);
+        assert_eq!(scores[6], 0.0);
+    }
+
+    #[test]
+    fn test_unavailable_without_source() {
+        let adapter = GpuMemoryAdapter { source: None };
+        assert!(!adapter.is_available());
+        assert!(adapter.poll().is_err());
+    }
+
+    #[test]
+    fn test_parse_nvidia_smi_sums_gpus() {
+        let reading = NvidiaSmiSource::parse("1024, 8192\n512, 4096\n").unwrap();
+        assert_eq!(reading.used_bytes, 1536 * 1024 * 1024);
+        assert_eq!(reading.total_bytes, 12288 * 1024 * 1024);
+        assert!((reading.usage_percent() - 12.5).abs() < 1e-4);
+
+        assert!(NvidiaSmiSource::parse("").is_err());
+        assert!(NvidiaSmiSource::parse("[N/A], 8192\n").is_err());
+        assert!(NvidiaSmiSource::parse("1024\n").is_err());
+    }
+
+    #[test]
+    fn test_parse_nvidia_smi_selects_device() {
+        let output = "0x1C8210DE, 4096\n0x220410DE, 24576\n";
+        assert_eq!(
+            NvidiaSmiSource::parse_device_total(output, 0x2204),
+            Some(24576 * 1024 * 1024)
+        );
+        assert_eq!(
+            NvidiaSmiSource::parse_device_total(output, 0x1C82),
+            Some(4096 * 1024 * 1024)
+        );
+        assert_eq!(NvidiaSmiSource::parse_device_total(output, 0x1234), None);
+    }
+
+    #[test]
+    fn test_drm_discover_device_selects_matching_card() {
+        let root = tempfile::tempdir().unwrap();
+        for (card, device, total) in [("card0", "0x73bf", 16), ("card1", "0x164e", 2)] {
+            let dir = root.path().join(card).join("device");
+            std::fs::create_dir_all(&dir).unwrap();
+            std::fs::write(dir.join("vendor"), "0x1002\n").unwrap();
+            std::fs::write(dir.join("device"), format!("{}\n", device)).unwrap();
+            std::fs::write(dir.join("mem_info_vram_used"), "0\n").unwrap();
+            std::fs::write(dir.join("mem_info_vram_total"), format!("{}\n", total)).unwrap();
+        }
+
+        let all = DrmSysfsSource::discover(root.path()).unwrap();
+        assert_eq!(all.read().unwrap().total_bytes, 18);
+
+        let igpu = DrmSysfsSource::discover_device(root.path(), 0x1002, 0x164E).unwrap();
+        assert_eq!(igpu.read().unwrap().total_bytes, 2);
+        assert!(DrmSysfsSource::discover_device(root.path(), 0x10DE, 0x164E).is_none());
+    }
+}
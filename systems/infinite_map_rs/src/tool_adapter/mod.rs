@@ -20,6 +20,8 @@ pub struct ToolMetrics {
     pub status: String,
     /// Raw metrics data (tool-specific)
     pub raw_data: String,
+    /// Parsed bpftrace maps, when the metrics came from a bpftrace run
+    pub bpftrace: Option<BpftraceMetrics>,
     /// Timestamp of this measurement
     pub timestamp: std::time::Instant,
 }
@@ -142,7 +144,9 @@ mod tests {
 // Re-export adapters
 pub mod bpftrace_adapter;
 pub mod btop_adapter;
+pub mod gpu_memory_adapter;
 
 // Re-export for convenience
-pub use bpftrace_adapter::BpftraceAdapter;
+pub use bpftrace_adapter::{BpftraceAdapter, BpftraceMetrics};
 pub use btop_adapter::BtopAdapter;
+pub use gpu_memory_adapter::GpuMemoryAdapter;
@@ -2,14 +2,153 @@
 // Tool Manager - Phase 2
 // Manages multiple tool adapters and coordinates polling
 
-use std::collections::HashMap;
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
 use std::sync::atomic::{AtomicU32, Ordering};
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use tokio::sync::RwLock;
 
 use crate::tool_adapter::{ToolAdapter, ToolMetrics};
 
+/// Age after which a tool's last health score starts to decay
+pub const DEFAULT_STALENESS_THRESHOLD: Duration = Duration::from_secs(10);
+
+/// Time constant of the decay past the staleness threshold
+pub const DEFAULT_STALENESS_DECAY: Duration = Duration::from_secs(10);
+
+/// Health a stale tool decays towards (neither healthy nor critical)
+pub const STALE_HEALTH: f32 = 0.5;
+
+/// Longest the polling loop sleeps, so `stop_polling` is noticed promptly
+const MAX_POLL_SLEEP: Duration = Duration::from_secs(1);
+
+/// Min-heap of next-poll times, one entry per adapter
+///
+/// Each adapter is polled on its own `polling_interval`. Heap entries are
+/// invalidated lazily: an entry only counts if it matches the adapter's
+/// current deadline in `next`.
+#[derive(Debug, Default)]
+pub struct PollScheduler {
+    heap: BinaryHeap<Reverse<(Instant, String)>>,
+    next: HashMap<String, (Instant, Duration)>,
+}
+
+impl PollScheduler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Poll `name` every `interval`, first at `first_poll`
+    pub fn schedule(&mut self, name: &str, interval: Duration, first_poll: Instant) {
+        self.next.insert(name.to_string(), (first_poll, interval));
+        self.heap.push(Reverse((first_poll, name.to_string())));
+    }
+
+    pub fn remove(&mut self, name: &str) {
+        self.next.remove(name);
+    }
+
+    /// When `name` is next due
+    pub fn next_poll_at(&self, name: &str) -> Option<Instant> {
+        self.next.get(name).map(|&(at, _)| at)
+    }
+
+    /// Earliest deadline of any adapter
+    pub fn next_deadline(&mut self) -> Option<Instant> {
+        self.discard_stale();
+        self.heap.peek().map(|Reverse((at, _))| *at)
+    }
+
+    /// Pop every adapter due at `now` and schedule its next poll
+    ///
+    /// Missed polls are not replayed: an adapter that fell behind is next
+    /// due one interval after `now`.
+    pub fn due(&mut self, now: Instant) -> Vec<String> {
+        let mut due = Vec::new();
+        loop {
+            self.discard_stale();
+            match self.heap.peek() {
+                Some(Reverse((at, _))) if *at <= now => {},
+                _ => break,
+            }
+            let Some(Reverse((at, name))) = self.heap.pop() else {
+                break;
+            };
+            let interval = self.next[&name].1;
+            let mut next = at + interval;
+            if next <= now {
+                next = now + interval;
+            }
+            self.schedule(&name, interval, next);
+            due.push(name);
+        }
+        due
+    }
+
+    /// Drop heap entries for removed or rescheduled adapters
+    fn discard_stale(&mut self) {
+        while let Some(Reverse((at, name))) = self.heap.peek() {
+            if self.next.get(name).map(|&(next, _)| next) == Some(*at) {
+                break;
+            }
+            self.heap.pop();
+        }
+    }
+}
+
+/// Errors from adapter registration
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum ToolManagerError {
+    #[error("An adapter named '{0}' is already registered")]
+    DuplicateAdapter(String),
+    #[error("Adapter '{0}' has a zero polling interval")]
+    ZeroPollingInterval(String),
+}
+
+/// When a tool's score starts to decay and how fast
+#[derive(Debug, Clone, Copy)]
+struct Staleness {
+    threshold: Duration,
+    decay: Duration,
+}
+
+impl Staleness {
+    /// Health score with the part past the threshold decayed
+    /// exponentially towards `STALE_HEALTH`
+    fn decayed(&self, metrics: &ToolMetrics, now: Instant) -> f32 {
+        let age = now.saturating_duration_since(metrics.timestamp);
+        let Some(overdue) = age.checked_sub(self.threshold) else {
+            return metrics.health_score;
+        };
+        let factor = if self.decay.is_zero() {
+            0.0
+        } else {
+            (-overdue.as_secs_f32() / self.decay.as_secs_f32()).exp()
+        };
+        STALE_HEALTH + (metrics.health_score - STALE_HEALTH) * factor
+    }
+
+    /// Weighted average of every adapter's (decayed) latest score
+    fn aggregate(
+        &self,
+        adapters: &[Arc<dyn ToolAdapter + Send + Sync>],
+        metrics: &HashMap<String, ToolMetrics>,
+        now: Instant,
+    ) -> Option<f32> {
+        let mut total_weight = 0.0;
+        let mut weighted_sum = 0.0;
+        for adapter in adapters {
+            if let Some(latest) = metrics.get(adapter.name()) {
+                let weight = adapter.weight();
+                weighted_sum += self.decayed(latest, now) * weight;
+                total_weight += weight;
+            }
+        }
+        (total_weight > 0.0).then(|| weighted_sum / total_weight)
+    }
+}
+
 /// Tool Manager for coordinating multiple adapters
 pub struct ToolManager {
     /// Registered adapters
@@ -29,6 +168,12 @@ pub struct ToolManager {
 
     /// Tokio runtime handle for background polling
     runtime_handle: Option<tokio::runtime::Handle>,
+
+    /// Decay applied to scores from tools that stopped reporting
+    staleness: Staleness,
+
+    /// Per-adapter poll deadlines, shared with the polling loop
+    scheduler: Arc<parking_lot::Mutex<PollScheduler>>,
 }
 
 impl ToolManager {
@@ -42,14 +187,49 @@ impl ToolManager {
             polling_active: Arc::new(RwLock::new(false)),
             status_summary: Arc::new(parking_lot::RwLock::new("Initializing...".to_string())),
             runtime_handle: None,
+            staleness: Staleness {
+                threshold: DEFAULT_STALENESS_THRESHOLD,
+                decay: DEFAULT_STALENESS_DECAY,
+            },
+            scheduler: Arc::new(parking_lot::Mutex::new(PollScheduler::new())),
         }
     }
 
-    pub fn register_adapter(&mut self, adapter: Arc<dyn ToolAdapter + Send + Sync>) {
+    /// Set when stale scores start to decay and their decay time constant
+    ///
+    /// Takes effect for polling started afterwards.
+    pub fn set_staleness(&mut self, threshold: Duration, decay: Duration) {
+        self.staleness = Staleness { threshold, decay };
+    }
+
+    /// Register an adapter, rejecting a second adapter with the same name
+    /// and adapters with a zero polling interval
+    ///
+    /// Adapters that are not available are skipped (with a warning) rather
+    /// than treated as an error. Changes take effect for polling started
+    /// afterwards.
+    pub fn register_adapter(
+        &mut self,
+        adapter: Arc<dyn ToolAdapter + Send + Sync>,
+    ) -> Result<(), ToolManagerError> {
         let name = adapter.name();
 
+        if self.adapters.iter().any(|a| a.name() == name) {
+            return Err(ToolManagerError::DuplicateAdapter(name.to_string()));
+        }
+        if adapter.polling_interval().is_zero() {
+            return Err(ToolManagerError::ZeroPollingInterval(name.to_string()));
+        }
+
         if adapter.is_available() {
-            log::info!("🔧 ToolManager: Registered adapter '{}'", name);
+            log::info!(
+                "🔧 ToolManager: Registered adapter '{}' (every {:?})",
+                name,
+                adapter.polling_interval()
+            );
+            self.scheduler
+                .lock()
+                .schedule(name, adapter.polling_interval(), Instant::now());
             self.adapters.push(adapter);
         } else {
             log::warn!(
@@ -57,6 +237,21 @@ impl ToolManager {
                 name
             );
         }
+        Ok(())
+    }
+
+    /// Remove an adapter and its latest metrics
+    ///
+    /// Blocks while a poll holds the metrics lock, so it must not be called
+    /// from async code.
+    pub fn unregister_adapter(&mut self, name: &str) -> Option<Arc<dyn ToolAdapter + Send + Sync>> {
+        let index = self.adapters.iter().position(|a| a.name() == name)?;
+        let adapter = self.adapters.remove(index);
+        self.scheduler.lock().remove(name);
+        // Wait out a polling write rather than leave stale metrics behind
+        self.metrics.blocking_write().remove(name);
+        log::info!("🔧 ToolManager: Unregistered adapter '{}'", name);
+        Some(adapter)
     }
 
     pub async fn start_polling(&mut self, runtime_handle: tokio::runtime::Handle) {
@@ -79,24 +274,32 @@ impl ToolManager {
         let active_flag = Arc::clone(&self.polling_active);
         let agg_health = Arc::clone(&self.aggregated_health);
         let status_sum = Arc::clone(&self.status_summary);
+        let staleness = self.staleness;
+        let scheduler = Arc::clone(&self.scheduler);
 
         runtime_handle.spawn(async move {
             log::info!("🔧 ToolManager: Main polling controller started");
+            let mut statuses: HashMap<String, String> = HashMap::new();
 
             while *active_flag.read().await {
-                let mut total_weight = 0.0;
-                let mut weighted_sum = 0.0;
-                let mut new_summary = String::new();
-
-                for adapter in &adapters_to_poll {
-                    let name = adapter.name().to_string();
-                    match adapter.poll() {
+                // Adapters shell out (bpftrace samples for hundreds of ms), so
+                // keep them off the async workers
+                let polled = {
+                    let scheduler = Arc::clone(&scheduler);
+                    let adapters = adapters_to_poll.clone();
+                    tokio::task::spawn_blocking(move || {
+                        poll_due(&scheduler, &adapters, Instant::now())
+                    })
+                    .await
+                };
+                let polled = polled.unwrap_or_else(|e| {
+                    log::error!("🔧 ToolManager: Polling task failed: {}", e);
+                    Vec::new()
+                });
+                for (name, result) in polled {
+                    match result {
                         Ok(metrics) => {
-                            let weight = adapter.weight();
-                            weighted_sum += metrics.health_score * weight;
-                            total_weight += weight;
-
-                            new_summary.push_str(&format!("{}: {}\n", name, metrics.status));
+                            statuses.insert(name.clone(), metrics.status.clone());
 
                             // Update internal store
                             let mut map = metrics_store.write().await;
@@ -104,13 +307,24 @@ impl ToolManager {
                         },
                         Err(e) => {
                             log::error!("🔧 ToolManager: Error polling '{}': {}", name, e);
-                            new_summary.push_str(&format!("{}: ERROR\n", name));
+                            statuses.insert(name, "ERROR".to_string());
                         },
                     }
                 }
+                let new_summary: String = adapters_to_poll
+                    .iter()
+                    .filter_map(|a| {
+                        let status = statuses.get(a.name())?;
+                        Some(format!("{}: {}\n", a.name(), status))
+                    })
+                    .collect();
 
-                if total_weight > 0.0 {
-                    let health = weighted_sum / total_weight;
+                // Tools that failed this round still count, decaying as they age
+                let health = {
+                    let map = metrics_store.read().await;
+                    staleness.aggregate(&adapters_to_poll, &map, Instant::now())
+                };
+                if let Some(health) = health {
                     agg_health.store(health.to_bits(), Ordering::Relaxed);
                 }
 
@@ -119,13 +333,50 @@ impl ToolManager {
                     *sum_guard = new_summary;
                 }
 
-                tokio::time::sleep(Duration::from_secs(1)).await;
+                let sleep = scheduler
+                    .lock()
+                    .next_deadline()
+                    .map(|at| at.saturating_duration_since(Instant::now()))
+                    .unwrap_or(MAX_POLL_SLEEP)
+                    .min(MAX_POLL_SLEEP);
+                tokio::time::sleep(sleep).await;
             }
             log::info!("🔧 ToolManager: Main polling controller stopped");
         });
     }
 
-    /// Synchronous access toaggregated health score
+    /// Time until the named adapter is next polled (zero if overdue)
+    pub fn next_poll_in(&self, name: &str) -> Option<Duration> {
+        self.scheduler
+            .lock()
+            .next_poll_at(name)
+            .map(|at| at.saturating_duration_since(Instant::now()))
+    }
+
+    /// Weighted health across adapters as of now
+    ///
+    /// Each adapter's latest score is weighted by `weight()`; scores older
+    /// than the staleness threshold decay towards `STALE_HEALTH` so a tool
+    /// that stopped polling cannot mask problems.
+    pub fn aggregate_health(&self) -> f32 {
+        self.aggregate_health_at(Instant::now())
+    }
+
+    /// [`ToolManager::aggregate_health`] evaluated at `now`
+    ///
+    /// Falls back to the value from the last polling round if the metrics
+    /// are being written, and to 1.0 before any tool has reported.
+    pub fn aggregate_health_at(&self, now: Instant) -> f32 {
+        match self.metrics.try_read() {
+            Ok(metrics) => self
+                .staleness
+                .aggregate(&self.adapters, &metrics, now)
+                .unwrap_or(1.0),
+            Err(_) => self.get_health_sync(),
+        }
+    }
+
+    /// Synchronous access to aggregated health score
     pub fn get_health_sync(&self) -> f32 {
         f32::from_bits(self.aggregated_health.load(Ordering::Relaxed))
     }
@@ -144,3 +395,227 @@ impl ToolManager {
         self.adapters.len()
     }
 }
+
+/// Poll the adapters the scheduler says are due at `now`
+///
+/// Blocks for as long as the slowest due adapter takes.
+fn poll_due(
+    scheduler: &parking_lot::Mutex<PollScheduler>,
+    adapters: &[Arc<dyn ToolAdapter + Send + Sync>],
+    now: Instant,
+) -> Vec<(String, Result<ToolMetrics, String>)> {
+    let due = scheduler.lock().due(now);
+    due.into_iter()
+        .filter_map(|name| {
+            let adapter = adapters.iter().find(|a| a.name() == name)?;
+            Some((name, adapter.poll()))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct FakeTool {
+        name: &'static str,
+        weight: f32,
+        interval: Duration,
+        polls: AtomicU32,
+    }
+
+    impl FakeTool {
+        fn new(name: &'static str, weight: f32) -> Self {
+            Self::with_interval(name, weight, Duration::from_secs(1))
+        }
+
+        fn with_interval(name: &'static str, weight: f32, interval: Duration) -> Self {
+            Self {
+                name,
+                weight,
+                interval,
+                polls: AtomicU32::new(0),
+            }
+        }
+    }
+
+    impl ToolAdapter for FakeTool {
+        fn name(&self) -> &str {
+            self.name
+        }
+
+        fn is_available(&self) -> bool {
+            true
+        }
+
+        fn poll(&self) -> Result<ToolMetrics, String> {
+            self.polls.fetch_add(1, Ordering::Relaxed);
+            Err("fake tool has no metrics".to_string())
+        }
+
+        fn polling_interval(&self) -> Duration {
+            self.interval
+        }
+
+        fn weight(&self) -> f32 {
+            self.weight
+        }
+    }
+
+    fn metrics(health_score: f32, timestamp: Instant) -> ToolMetrics {
+        ToolMetrics {
+            health_score,
+            status: String::new(),
+            raw_data: String::new(),
+            bpftrace: None,
+            timestamp,
+        }
+    }
+
+    #[test]
+    fn test_stale_tool_influence_decays() {
+        let mut manager = ToolManager::new();
+        manager
+            .register_adapter(Arc::new(FakeTool::new("fresh", 1.0)))
+            .unwrap();
+        manager
+            .register_adapter(Arc::new(FakeTool::new("dead", 3.0)))
+            .unwrap();
+        manager.set_staleness(Duration::from_secs(5), Duration::from_secs(5));
+        assert_eq!(manager.aggregate_health(), 1.0);
+
+        // "dead" last reported critical at t0; "fresh" keeps reporting
+        let t0 = Instant::now();
+        {
+            let mut map = manager.metrics.try_write().unwrap();
+            map.insert("dead".to_string(), metrics(0.0, t0));
+            map.insert(
+                "fresh".to_string(),
+                metrics(1.0, t0 + Duration::from_secs(600)),
+            );
+        }
+
+        let at = |secs: u64| manager.aggregate_health_at(t0 + Duration::from_secs(secs));
+
+        // Within the threshold the dead tool dominates with its full weight
+        assert!((at(0) - 0.25).abs() < 1e-6);
+        assert!((at(5) - 0.25).abs() < 1e-6);
+
+        // Past it, the dead score drifts towards neutral
+        let dead_at_10 = STALE_HEALTH * (1.0 - (-1.0f32).exp());
+        assert!((at(10) - (1.0 + 3.0 * dead_at_10) / 4.0).abs() < 1e-5);
+        let samples: Vec<f32> = [5, 10, 20, 40, 120].into_iter().map(at).collect();
+        assert!(samples.windows(2).all(|w| w[0] < w[1]), "{:?}", samples);
+        assert!((at(120) - (1.0 + 3.0 * STALE_HEALTH) / 4.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_duplicate_adapter_names_are_rejected() {
+        let mut manager = ToolManager::new();
+        let tool = |name, weight| Arc::new(FakeTool::new(name, weight));
+        manager.register_adapter(tool("probe", 1.0)).unwrap();
+        manager.register_adapter(tool("other", 1.0)).unwrap();
+        assert_eq!(
+            manager.register_adapter(tool("probe", 5.0)),
+            Err(ToolManagerError::DuplicateAdapter("probe".to_string()))
+        );
+        assert_eq!(manager.adapter_count(), 2);
+
+        // A zero interval would be due forever
+        let busy = Arc::new(FakeTool::with_interval("busy", 1.0, Duration::ZERO));
+        assert_eq!(
+            manager.register_adapter(busy),
+            Err(ToolManagerError::ZeroPollingInterval("busy".to_string()))
+        );
+        assert_eq!(manager.adapter_count(), 2);
+
+        let now = Instant::now();
+        {
+            let mut map = manager.metrics.try_write().unwrap();
+            map.insert("probe".to_string(), metrics(0.0, now));
+            map.insert("other".to_string(), metrics(1.0, now));
+        }
+        assert!((manager.aggregate_health_at(now) - 0.5).abs() < 1e-6);
+
+        let removed = manager.unregister_adapter("probe").unwrap();
+        assert_eq!(removed.name(), "probe");
+        assert!(manager.unregister_adapter("probe").is_none());
+        assert_eq!(manager.adapter_count(), 1);
+        assert_eq!(manager.aggregate_health_at(now), 1.0);
+
+        // The name is free again
+        manager.register_adapter(tool("probe", 1.0)).unwrap();
+    }
+
+    #[test]
+    fn test_unregister_waits_for_metrics_lock() {
+        let mut manager = ToolManager::new();
+        manager
+            .register_adapter(Arc::new(FakeTool::new("probe", 1.0)))
+            .unwrap();
+        manager
+            .metrics
+            .try_write()
+            .unwrap()
+            .insert("probe".to_string(), metrics(1.0, Instant::now()));
+
+        // A poller holding the lock only delays the removal
+        let guard = Arc::clone(&manager.metrics).try_write_owned().unwrap();
+        let poller = std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(50));
+            drop(guard);
+        });
+        manager.unregister_adapter("probe").unwrap();
+        poller.join().unwrap();
+        assert!(manager.metrics.try_read().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_adapters_poll_on_their_own_intervals() {
+        let fast = Arc::new(FakeTool::with_interval("fast", 1.0, Duration::from_secs(1)));
+        let slow = Arc::new(FakeTool::with_interval("slow", 1.0, Duration::from_secs(5)));
+        let mut manager = ToolManager::new();
+        manager.register_adapter(fast.clone()).unwrap();
+        manager.register_adapter(slow.clone()).unwrap();
+
+        assert_eq!(manager.next_poll_in("fast"), Some(Duration::ZERO));
+        assert_eq!(manager.next_poll_in("missing"), None);
+
+        // Simulate a minute in 100 ms steps from the slow tool's first poll
+        let t0 = manager.scheduler.lock().next_poll_at("slow").unwrap();
+        for step in 0..600 {
+            let now = t0 + Duration::from_millis(step * 100);
+            for (_, result) in poll_due(&manager.scheduler, &manager.adapters, now) {
+                assert!(result.is_err());
+            }
+        }
+        let fast_polls = fast.polls.load(Ordering::Relaxed);
+        let slow_polls = slow.polls.load(Ordering::Relaxed);
+        assert_eq!((fast_polls, slow_polls), (60, 12));
+
+        let next = manager.scheduler.lock().next_poll_at("slow").unwrap();
+        assert_eq!(next.duration_since(t0), Duration::from_secs(60));
+
+        // Unregistered adapters drop out of the schedule
+        manager.unregister_adapter("fast");
+        assert_eq!(manager.next_poll_in("fast"), None);
+        assert_eq!(manager.scheduler.lock().next_deadline(), Some(next));
+    }
+
+    #[test]
+    fn test_scheduler_skips_missed_polls() {
+        let t0 = Instant::now();
+        let mut scheduler = PollScheduler::new();
+        scheduler.schedule("tool", Duration::from_secs(2), t0);
+        assert_eq!(scheduler.due(t0), vec!["tool".to_string()]);
+        assert!(scheduler.due(t0 + Duration::from_secs(1)).is_empty());
+
+        // Ten seconds late: one poll, then back on a 2 s cadence from now
+        let late = t0 + Duration::from_secs(12);
+        assert_eq!(scheduler.due(late).len(), 1);
+        assert_eq!(
+            scheduler.next_poll_at("tool"),
+            Some(late + Duration::from_secs(2))
+        );
+    }
+}
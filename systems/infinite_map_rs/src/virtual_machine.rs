@@ -12,13 +12,15 @@
 // - Time dilation: VM pauses when not observed
 
 // Phase 47: Hypervisor Convergence - QEMU Wrapper Integration
-use std::io::{Read, Write};
+use std::collections::VecDeque;
+use std::io::{BufRead, BufReader, Read, Write};
 use std::os::unix::net::UnixStream;
-use std::path::PathBuf;
-use std::process::{Child, Command, Stdio};
+use std::path::{Path, PathBuf};
+use std::process::{Child, ChildStderr, Command, ExitStatus, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
 use std::thread;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 // Phase 47: Task 2 - Shared Memory Framebuffer
 use memmap2::{MmapMut, MmapOptions};
@@ -29,6 +31,8 @@ use kvm_bindings::kvm_userspace_memory_region;
 #[cfg(feature = "hypervisor")]
 use kvm_ioctls::{Kvm, VcpuExit, VcpuFd, VmFd};
 
+use crate::qemu::input::{ConsoleEncoder, EncodedInput, InputEncoder, KeyCode, KeyEvent};
+
 // Phase 30.7: Terminal Emulation
 use crate::terminal_emulator::TerminalBuffer;
 #[cfg(feature = "hypervisor")]
@@ -55,8 +59,12 @@ pub struct QemuConfig {
     pub height: u32,
     /// Enable KVM acceleration
     pub enable_kvm: bool,
+    /// Start without KVM instead of failing when it is unavailable
+    pub kvm_fallback: bool,
     /// Additional QEMU arguments
     pub extra_args: Vec<String>,
+    /// VM id the compositor discovers this VM by (see `with_geometry_integration`)
+    pub geometry_vm_id: Option<String>,
 }
 
 impl Default for QemuConfig {
@@ -69,11 +77,60 @@ impl Default for QemuConfig {
             width: 1024,
             height: 768,
             enable_kvm: true,
+            kvm_fallback: true,
             extra_args: Vec::new(),
+            geometry_vm_id: None,
         }
     }
 }
 
+impl QemuConfig {
+    /// Make the VM discoverable and controllable by the compositor
+    ///
+    /// Guest RAM is backed by `/dev/shm/qemu_ram_<vm_id>` (read by
+    /// `SharedMemoryBridge`) and an extra QMP socket is opened at
+    /// `/tmp/qmp-<vm_id>.sock` (used by `QmpClient`).
+    pub fn with_geometry_integration(mut self, vm_id: &str) -> Self {
+        self.geometry_vm_id = Some(vm_id.to_string());
+        self
+    }
+
+    /// Shared memory file backing guest RAM, if integrated
+    pub fn shm_path(&self) -> Option<PathBuf> {
+        self.geometry_vm_id
+            .as_ref()
+            .map(|id| PathBuf::from(format!("/dev/shm/qemu_ram_{}", id)))
+    }
+
+    /// QMP socket for the compositor, if integrated
+    pub fn qmp_socket_path(&self) -> Option<PathBuf> {
+        self.geometry_vm_id
+            .as_ref()
+            .map(|id| PathBuf::from(format!("/tmp/qmp-{}.sock", id)))
+    }
+
+    /// Memory backend and QMP arguments for geometry integration (empty
+    /// without it)
+    pub fn geometry_args(&self) -> Vec<String> {
+        let (Some(shm_path), Some(qmp_socket)) = (self.shm_path(), self.qmp_socket_path()) else {
+            return Vec::new();
+        };
+        vec![
+            // Back guest RAM with the shared file (the size must match -m)
+            "-object".to_string(),
+            format!(
+                "memory-backend-file,id=geometry-ram,size={}M,mem-path={},share=on",
+                self.memory_mb,
+                shm_path.display()
+            ),
+            "-numa".to_string(),
+            "node,memdev=geometry-ram".to_string(),
+            "-qmp".to_string(),
+            format!("unix:{},server,nowait", qmp_socket.display()),
+        ]
+    }
+}
+
 /// QEMU Process Wrapper
 ///
 /// Manages an external QEMU process for running guest operating systems.
@@ -184,6 +241,9 @@ impl QemuProcess {
         // VirtIO randomized
         cmd.args(["-device", "virtio-rng-pci"]);
 
+        // Shared RAM + QMP for the compositor
+        cmd.args(self.config.geometry_args());
+
         // Additional arguments
         cmd.args(&self.config.extra_args);
 
@@ -401,65 +461,7 @@ impl QemuProcess {
         std::fs::remove_file(&screenshot_path).ok();
 
         // Parse PPM (P6 format: binary RGB)
-        Self::parse_ppm(&ppm_data)
-    }
-
-    /// Parse PPM image data to RGBA framebuffer
-    fn parse_ppm(data: &[u8]) -> Result<Vec<u8>, String> {
-        // PPM P6 format: "P6\n<width> <height>\n<maxval>\n<binary RGB data>"
-        let header_end = data
-            .windows(3)
-            .position(|w| w == b"\n\n")
-            .or_else(|| data.windows(2).position(|w| w == b"\n\n"))
-            .unwrap_or(0);
-
-        if header_end == 0 {
-            return Err("Invalid PPM format".to_string());
-        }
-
-        // Find header boundaries
-        let mut lines = data[..header_end].split(|&b| b == b'\n');
-        let magic = lines.next().ok_or("No magic number")?;
-        if magic != b"P6" {
-            return Err(format!("Expected P6 PPM, got {:?}", magic));
-        }
-
-        let dimensions = lines.next().ok_or("No dimensions")?;
-        let dim_str = String::from_utf8_lossy(dimensions);
-        let dims: Vec<&str> = dim_str.split_whitespace().collect();
-        let width: u32 = dims.first()
-            .ok_or("No width")?
-            .parse()
-            .map_err(|_| "Invalid width")?;
-        let height: u32 = dims
-            .get(1)
-            .ok_or("No height")?
-            .parse()
-            .map_err(|_| "Invalid height")?;
-
-        // Find start of pixel data (after all header newlines)
-        let pixel_start = data
-            .iter()
-            .position(|&b| b.is_ascii_digit())
-            .and_then(|pos| data[pos..].iter().position(|&b| b == b'\n').map(|p| pos + p + 1))
-            .unwrap_or(header_end + 2);
-
-        let pixel_data = &data[pixel_start..];
-        let pixel_count = (width * height) as usize;
-
-        // Convert RGB to RGBA
-        let mut rgba = Vec::with_capacity(pixel_count * 4);
-        for i in 0..pixel_count {
-            let offset = i * 3;
-            if offset + 2 < pixel_data.len() {
-                rgba.push(pixel_data[offset]); // R
-                rgba.push(pixel_data[offset + 1]); // G
-                rgba.push(pixel_data[offset + 2]); // B
-                rgba.push(255); // A
-            }
-        }
-
-        Ok(rgba)
+        parse_ppm(&ppm_data).map(|(_, _, rgba)| rgba)
     }
 }
 
@@ -669,6 +671,365 @@ impl Drop for SharedMemoryFramebuffer {
 // Phase 47: QEMU Process with Shared Memory
 // ============================================
 
+/// KVM device QEMU needs for `-enable-kvm`
+const KVM_DEVICE: &str = "/dev/kvm";
+
+/// How long QEMU must survive after spawning to count as started; an
+/// earlier exit is reported by `QemuProcessWithShm::check_startup`
+const QEMU_STARTUP_GRACE: Duration = Duration::from_millis(500);
+
+/// Lines of QEMU's stderr kept to explain a failed start
+const QEMU_STDERR_TAIL: usize = 20;
+
+/// Why `QemuProcessWithShm::start` failed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum QemuStartError {
+    /// No boot image configured
+    NoBootPath,
+    /// Boot image does not exist
+    BootPathNotFound(String),
+    /// QEMU binary is not installed or not on PATH
+    BinaryNotFound(String),
+    /// KVM was requested but can't be used
+    KvmUnavailable(String),
+    /// A port QEMU listens on (VNC, hostfwd, ...) is taken
+    PortInUse(String),
+    /// Framebuffer shared memory could not be created
+    SharedMemory(String),
+    /// Spawning QEMU failed for another reason
+    Spawn(String),
+    /// QEMU exited during startup
+    Exited {
+        status: String,
+        /// Last lines QEMU wrote to stderr
+        stderr: String,
+    },
+}
+
+impl QemuStartError {
+    /// What to do about it, for logs and the UI
+    pub fn remediation(&self) -> &'static str {
+        match self {
+            QemuStartError::NoBootPath => "Choose an ISO or disk image to boot.",
+            QemuStartError::BootPathNotFound(_) => "Check that the boot image path is correct.",
+            QemuStartError::BinaryNotFound(_) => {
+                "Install QEMU (e.g. `apt install qemu-system-x86`) or set `qemu_binary` to its full path."
+            },
+            QemuStartError::KvmUnavailable(_) => {
+                "Enable virtualization in the BIOS, load the kvm module and add your user to the \
+                 `kvm` group, or set `kvm_fallback` to run without KVM."
+            },
+            QemuStartError::PortInUse(_) => {
+                "Stop the other VM or service using the port, or change the port in `extra_args`."
+            },
+            QemuStartError::SharedMemory(_) => "Check that /dev/shm is mounted and not full.",
+            QemuStartError::Spawn(_) => "Check that the QEMU binary is executable.",
+            QemuStartError::Exited { .. } => {
+                "See QEMU's error output above; `extra_args` may be invalid for this QEMU version."
+            },
+        }
+    }
+
+    /// Classify an exit during startup from QEMU's stderr
+    fn from_exit(status: ExitStatus, stderr: String) -> Self {
+        let port_in_use = stderr
+            .lines()
+            .find(|line| line.to_lowercase().contains("address already in use"))
+            .map(|line| line.trim().to_string());
+        if let Some(line) = port_in_use {
+            QemuStartError::PortInUse(line)
+        } else {
+            QemuStartError::Exited {
+                status: status.to_string(),
+                stderr,
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for QemuStartError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            QemuStartError::NoBootPath => write!(f, "No boot path specified"),
+            QemuStartError::BootPathNotFound(path) => write!(f, "Boot path not found: {}", path),
+            QemuStartError::BinaryNotFound(binary) => {
+                write!(f, "QEMU binary not found: {}", binary)
+            },
+            QemuStartError::KvmUnavailable(reason) => write!(f, "KVM unavailable: {}", reason),
+            QemuStartError::PortInUse(reason) => write!(f, "Port already in use: {}", reason),
+            QemuStartError::SharedMemory(e) => write!(f, "Failed to create framebuffer: {}", e),
+            QemuStartError::Spawn(e) => write!(f, "Failed to start QEMU: {}", e),
+            QemuStartError::Exited { status, stderr } => {
+                write!(f, "QEMU exited during startup ({})", status)?;
+                if !stderr.is_empty() {
+                    write!(f, ": {}", stderr)?;
+                }
+                Ok(())
+            },
+        }
+    }
+}
+
+impl std::error::Error for QemuStartError {}
+
+/// Check that the KVM device can be opened the way QEMU opens it
+fn check_kvm_device(path: &Path) -> Result<(), QemuStartError> {
+    std::fs::OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open(path)
+        .map(drop)
+        .map_err(|e| QemuStartError::KvmUnavailable(format!("{}: {}", path.display(), e)))
+}
+
+/// Log QEMU's stderr as it arrives; the thread returns the last lines
+fn forward_qemu_stderr(stderr: ChildStderr) -> thread::JoinHandle<String> {
+    thread::spawn(move || {
+        let mut tail = VecDeque::with_capacity(QEMU_STDERR_TAIL);
+        for line in BufReader::new(stderr).lines().map_while(Result::ok) {
+            log::warn!("qemu: {}", line);
+            if tail.len() == QEMU_STDERR_TAIL {
+                tail.pop_front();
+            }
+            tail.push_back(line);
+        }
+        Vec::from(tail).join("\n")
+    })
+}
+
+/// How long to wait on QMP before giving up on a screendump
+const QMP_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Where `QemuProcessWithShm` takes guest display pixels from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FramebufferSource {
+    /// Moving gradient drawn by the capture thread, for testing without a guest
+    TestPattern,
+    /// The shared memory framebuffer, for a display backend that writes the
+    /// guest display to it; QEMU's own `-display none` doesn't, so
+    /// screendumps stand in until something first writes it
+    SharedMemory,
+    /// QMP `screendump` of the emulated VGA, taken by the capture thread
+    #[default]
+    Screendump,
+}
+
+/// Parse a binary (P6) PPM into `(width, height, RGBA pixels)`
+fn parse_ppm(data: &[u8]) -> Result<(u32, u32, Vec<u8>), String> {
+    // Header: magic, width, height and maxval separated by whitespace, with
+    // `#` comments, then exactly one whitespace byte before the pixels
+    let mut fields = Vec::with_capacity(4);
+    let mut pos = 0;
+    while fields.len() < 4 {
+        match data.get(pos) {
+            Some(b'#') => {
+                while data.get(pos).is_some_and(|&b| b != b'\n') {
+                    pos += 1;
+                }
+            },
+            Some(b) if b.is_ascii_whitespace() => pos += 1,
+            Some(_) => {
+                let start = pos;
+                while data.get(pos).is_some_and(|b| !b.is_ascii_whitespace()) {
+                    pos += 1;
+                }
+                fields.push(String::from_utf8_lossy(&data[start..pos]).into_owned());
+            },
+            None => return Err("Truncated PPM header".to_string()),
+        }
+    }
+    if fields[0] != "P6" {
+        return Err(format!("Expected P6 PPM, got {:?}", fields[0]));
+    }
+    let number = |field: &str, name: &str| {
+        field
+            .parse::<u32>()
+            .map_err(|_| format!("Invalid PPM {}: {:?}", name, field))
+    };
+    let width = number(&fields[1], "width")?;
+    let height = number(&fields[2], "height")?;
+    if number(&fields[3], "maxval")? != 255 {
+        return Err("Only 8-bit PPMs are supported".to_string());
+    }
+
+    let pixel_count = width as usize * height as usize;
+    let pixels = data
+        .get(pos + 1..)
+        .and_then(|rest| rest.get(..pixel_count * 3))
+        .ok_or("Truncated PPM pixel data")?;
+    let rgba = pixels
+        .chunks_exact(3)
+        .flat_map(|rgb| [rgb[0], rgb[1], rgb[2], 255])
+        .collect();
+    Ok((width, height, rgba))
+}
+
+/// Copy a `src_width` x `src_height` RGBA image into the top-left of `dst`,
+/// cropping whatever doesn't fit
+fn blit_rgba(dst: &mut [u8], dst_width: u32, src: &[u8], src_width: u32, src_height: u32) {
+    let dst_row = dst_width as usize * 4;
+    let src_row = src_width as usize * 4;
+    let row_len = dst_row.min(src_row);
+    if row_len == 0 {
+        return;
+    }
+    for (dst, src) in dst
+        .chunks_exact_mut(dst_row)
+        .zip(src.chunks_exact(src_row).take(src_height as usize))
+    {
+        dst[..row_len].copy_from_slice(&src[..row_len]);
+    }
+}
+
+/// Run `commands` over a fresh QMP connection, returning the `return`
+/// value of the last one
+fn qmp_execute(socket: &Path, commands: &[serde_json::Value]) -> Result<serde_json::Value, String> {
+    let stream = UnixStream::connect(socket).map_err(|e| format!("QMP connect failed: {}", e))?;
+    stream.set_read_timeout(Some(QMP_TIMEOUT)).ok();
+    let mut writer = stream
+        .try_clone()
+        .map_err(|e| format!("QMP connect failed: {}", e))?;
+    let mut reader = BufReader::new(stream);
+
+    let mut line = String::new();
+    reader
+        .read_line(&mut line)
+        .map_err(|e| format!("QMP greeting read failed: {}", e))?;
+    if !line.contains("QMP") {
+        return Err("No QMP greeting received".to_string());
+    }
+
+    let capabilities = serde_json::json!({ "execute": "qmp_capabilities" });
+    let mut result = serde_json::Value::Null;
+    for command in std::iter::once(&capabilities).chain(commands) {
+        writeln!(writer, "{}", command).map_err(|e| format!("QMP send failed: {}", e))?;
+        // Skip asynchronous events until the command's reply
+        loop {
+            line.clear();
+            let read = reader
+                .read_line(&mut line)
+                .map_err(|e| format!("QMP read failed: {}", e))?;
+            if read == 0 {
+                return Err("QMP connection closed".to_string());
+            }
+            let Ok(mut reply) = serde_json::from_str::<serde_json::Value>(&line) else {
+                continue;
+            };
+            if let Some(error) = reply.get("error") {
+                return Err(format!("QMP {} failed: {}", command["execute"], error));
+            }
+            if let Some(value) = reply.get_mut("return") {
+                result = value.take();
+                break;
+            }
+        }
+    }
+    Ok(result)
+}
+
+/// Ask QEMU to write its display to `filename` as a PPM
+fn qmp_screendump(socket: &Path, filename: &Path) -> Result<(), String> {
+    let screendump = serde_json::json!({
+        "execute": "screendump",
+        "arguments": { "filename": filename },
+    });
+    qmp_execute(socket, &[screendump]).map(|_| ())
+}
+
+/// Open the host end of the guest's `-serial pty` console
+fn open_serial_console(qmp_socket: &Path) -> Result<std::fs::File, String> {
+    let chardevs = qmp_execute(
+        qmp_socket,
+        &[serde_json::json!({ "execute": "query-chardev" })],
+    )?;
+    // The pty shows up as `pty:/dev/pts/N` on the first serial port
+    let pty = chardevs
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter(|dev| dev["label"] == "serial0")
+        .find_map(|dev| dev["filename"].as_str()?.strip_prefix("pty:"))
+        .ok_or("QEMU has no serial pty")?;
+    std::fs::OpenOptions::new()
+        .write(true)
+        .open(pty)
+        .map_err(|e| format!("Failed to open serial console {}: {}", pty, e))
+}
+
+/// Take a QMP screendump and draw it into `framebuffer`
+fn capture_screendump(
+    qmp_socket: &Path,
+    framebuffer: &Mutex<Vec<u8>>,
+    width: u32,
+) -> Result<(), String> {
+    let path = std::env::temp_dir().join(format!(
+        "qemu_screendump_{}_{}.ppm",
+        std::process::id(),
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_nanos()
+    ));
+    let result = qmp_screendump(qmp_socket, &path).and_then(|()| {
+        std::fs::read(&path).map_err(|e| format!("Failed to read screendump: {}", e))
+    });
+    let _ = std::fs::remove_file(&path);
+
+    let (src_width, src_height, rgba) = parse_ppm(&result?)?;
+    blit_rgba(
+        &mut framebuffer.lock().unwrap(),
+        width,
+        &rgba,
+        src_width,
+        src_height,
+    );
+    Ok(())
+}
+
+/// How long QEMU gets to exit after SIGTERM before it is killed
+const QEMU_TERM_GRACE: Duration = Duration::from_secs(1);
+
+/// How `QemuProcessWithShm::stop_graceful` stopped QEMU
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QemuStopMethod {
+    /// The guest shut down after a QMP `system_powerdown`
+    Powerdown,
+    /// QEMU exited on SIGTERM
+    Terminated,
+    /// QEMU had to be killed with SIGKILL
+    Killed,
+    /// There was no QEMU process to stop
+    NotRunning,
+}
+
+/// Poll `child` until it exits or `timeout` passes; true if it exited
+fn wait_for_exit(child: &mut Child, timeout: Duration) -> bool {
+    let deadline = Instant::now() + timeout;
+    loop {
+        match child.try_wait() {
+            Ok(Some(_)) => return true,
+            Ok(None) if Instant::now() < deadline => thread::sleep(Duration::from_millis(20)),
+            // Can't be waited on, so treat it like a hang
+            _ => return false,
+        }
+    }
+}
+
+/// SIGTERM `child`, then SIGKILL it if it is still around after the grace period
+fn terminate_child(child: &mut Child) -> QemuStopMethod {
+    // SAFETY: kill(2) has no memory-safety preconditions, and the child
+    // is not reaped yet, so its pid cannot have been reused
+    unsafe {
+        libc::kill(child.id() as libc::pid_t, libc::SIGTERM);
+    }
+    if wait_for_exit(child, QEMU_TERM_GRACE) {
+        return QemuStopMethod::Terminated;
+    }
+    let _ = child.kill();
+    let _ = child.wait();
+    QemuStopMethod::Killed
+}
+
 /// Extended QEMU process with shared memory framebuffer support
 pub struct QemuProcessWithShm {
     /// Base QEMU configuration
@@ -685,6 +1046,20 @@ pub struct QemuProcessWithShm {
     capture_thread: Option<thread::JoinHandle<()>>,
     /// Framebuffer copy for render thread
     framebuffer_copy: Arc<Mutex<Vec<u8>>>,
+    /// Where `framebuffer_copy` is filled from
+    framebuffer_source: Arc<Mutex<FramebufferSource>>,
+    /// Something has written the shared memory framebuffer since start
+    shm_written: Arc<AtomicBool>,
+    /// Turns key events into console bytes or QMP events
+    input_encoder: Box<dyn InputEncoder>,
+    /// Host end of the guest serial console, opened on first use
+    serial_console: Mutex<Option<std::fs::File>>,
+    /// KVM device checked before starting with `-enable-kvm`
+    kvm_device: PathBuf,
+    /// End of the startup grace period, while QEMU is starting
+    startup_deadline: Option<Instant>,
+    /// Thread forwarding QEMU's stderr, returning its last lines
+    stderr_tail: Option<thread::JoinHandle<String>>,
 }
 
 impl QemuProcessWithShm {
@@ -715,17 +1090,41 @@ impl QemuProcessWithShm {
             qmp_socket,
             capture_thread: None,
             framebuffer_copy: Arc::new(Mutex::new(vec![0; fb_size])),
+            framebuffer_source: Arc::new(Mutex::new(FramebufferSource::default())),
+            shm_written: Arc::new(AtomicBool::new(false)),
+            input_encoder: Box::new(ConsoleEncoder),
+            serial_console: Mutex::new(None),
+            kvm_device: PathBuf::from(KVM_DEVICE),
+            startup_deadline: None,
+            stderr_tail: None,
         }
     }
 
     /// Start QEMU with shared memory framebuffer
-    pub fn start(&mut self) -> Result<(), String> {
+    ///
+    /// Returns once QEMU is spawned, without waiting for it to come up; call
+    /// `check_startup` to find out whether it exited during startup. If KVM
+    /// was requested but `/dev/kvm` can't be opened and `kvm_fallback` is
+    /// set, QEMU is started without it.
+    pub fn start(&mut self) -> Result<(), QemuStartError> {
         if self.config.boot_path.is_empty() {
-            return Err("No boot path specified".to_string());
+            return Err(QemuStartError::NoBootPath);
         }
 
         if !PathBuf::from(&self.config.boot_path).exists() {
-            return Err(format!("Boot path not found: {}", self.config.boot_path));
+            return Err(QemuStartError::BootPathNotFound(
+                self.config.boot_path.clone(),
+            ));
+        }
+
+        if self.config.enable_kvm {
+            match check_kvm_device(&self.kvm_device) {
+                Err(QemuStartError::KvmUnavailable(reason)) if self.config.kvm_fallback => {
+                    log::warn!("⚠️  KVM unavailable ({}), starting without KVM", reason);
+                    self.config.enable_kvm = false;
+                },
+                result => result?,
+            }
         }
 
         // Create shared memory framebuffer
@@ -738,7 +1137,8 @@ impl QemuProcessWithShm {
             &format!("vm_{}", session_id),
             self.config.width,
             self.config.height,
-        )?;
+        )
+        .map_err(QemuStartError::SharedMemory)?;
 
         log::info!("🚀 Starting QEMU with SHM framebuffer...");
         log::info!("   Binary: {}", self.config.qemu_binary);
@@ -747,92 +1147,161 @@ impl QemuProcessWithShm {
         log::info!("   VCPUs: {}", self.config.vcpu_count);
         log::info!("   SHM: {}", shm_fb.name);
 
+        let mut child = self.spawn_qemu()?;
+        self.stderr_tail = child.stderr.take().map(forward_qemu_stderr);
+        self.startup_deadline = Some(Instant::now() + QEMU_STARTUP_GRACE);
+
+        self.child = Some(child);
+        *self.running.lock().unwrap() = true;
+        log::info!(
+            "✅ QEMU process started with SHM support (PID: {:?})",
+            self.child.as_ref().unwrap().id()
+        );
+
+        // Start capture thread
+        self.shm_written.store(false, Ordering::Relaxed);
+        self.start_capture_thread();
+
+        // Store framebuffer
+        self.framebuffer = Some(shm_fb);
+
+        Ok(())
+    }
+
+    /// Spawn QEMU with its stderr piped
+    fn spawn_qemu(&self) -> Result<Child, QemuStartError> {
         // Build QEMU command
         let mut cmd = Command::new(&self.config.qemu_binary);
+        cmd.args(self.qemu_args());
+
+        // Disable audio for now to avoid alsa/pulse errors
+        cmd.env("QEMU_AUDIO_DRV", "none");
+
+        cmd.stdin(Stdio::null())
+            .stdout(Stdio::inherit())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| match e.kind() {
+                std::io::ErrorKind::NotFound => {
+                    QemuStartError::BinaryNotFound(self.config.qemu_binary.clone())
+                },
+                _ => QemuStartError::Spawn(e.to_string()),
+            })
+    }
+
+    /// Report QEMU exiting within the startup grace period
+    ///
+    /// Doesn't block, so it can be polled every frame after `start`. Returns
+    /// the error once (bad arguments, a taken port, ...) and `Ok` while QEMU
+    /// is starting or once it has survived the grace period.
+    pub fn check_startup(&mut self) -> Result<(), QemuStartError> {
+        let Some(deadline) = self.startup_deadline else {
+            return Ok(());
+        };
+        let exited = match self.child.as_mut().map(Child::try_wait) {
+            Some(Ok(status)) => status,
+            Some(Err(e)) => {
+                self.startup_deadline = None;
+                return Err(QemuStartError::Spawn(e.to_string()));
+            },
+            None => None,
+        };
+        match exited {
+            Some(status) => {
+                self.startup_deadline = None;
+                *self.running.lock().unwrap() = false;
+                // stderr closes when QEMU exits, so the thread is done
+                let stderr = self
+                    .stderr_tail
+                    .take()
+                    .and_then(|thread| thread.join().ok())
+                    .unwrap_or_default();
+                Err(QemuStartError::from_exit(status, stderr))
+            },
+            None => {
+                if Instant::now() >= deadline {
+                    self.startup_deadline = None;
+                }
+                Ok(())
+            },
+        }
+    }
+
+    /// Whether QEMU runs with KVM (false after falling back)
+    pub fn kvm_enabled(&self) -> bool {
+        self.config.enable_kvm
+    }
 
+    /// Command-line arguments QEMU is started with
+    pub fn qemu_args(&self) -> Vec<String> {
         // Basic configuration
-        cmd.args([
-            "-m",
-            &format!("{}", self.config.memory_mb),
-            "-smp",
-            &format!("{}", self.config.vcpu_count),
-        ]);
+        let mut args = vec![
+            "-m".to_string(),
+            format!("{}", self.config.memory_mb),
+            "-smp".to_string(),
+            format!("{}", self.config.vcpu_count),
+        ];
 
         // Enable KVM if available
         if self.config.enable_kvm {
-            cmd.arg("-enable-kvm");
+            args.push("-enable-kvm".to_string());
         }
 
         // QMP control socket
-        cmd.args([
-            "-qmp",
-            &format!("unix:{},server,nowait", self.qmp_socket.to_string_lossy()),
-        ]);
+        args.push("-qmp".to_string());
+        args.push(format!(
+            "unix:{},server,nowait",
+            self.qmp_socket.to_string_lossy()
+        ));
 
         // Use standard VGA and QMP for screendump
-        cmd.args(["-display", "none", "-device", "VGA"]);
-
-        // Disable audio for now to avoid alsa/pulse errors
-        cmd.env("QEMU_AUDIO_DRV", "none");
+        args.extend(["-display", "none", "-device", "VGA"].map(String::from));
 
         // Serial console for debugging
-        cmd.args(["-serial", "pty"]);
+        args.extend(["-serial", "pty"].map(String::from));
 
         // Boot device
         if self.config.boot_path.ends_with(".iso") {
-            cmd.args(["-cdrom", &self.config.boot_path]);
+            args.push("-cdrom".to_string());
+            args.push(self.config.boot_path.clone());
         } else {
-            cmd.args([
-                "-drive",
-                &format!("file={},format=raw", self.config.boot_path),
-            ]);
+            args.push("-drive".to_string());
+            args.push(format!("file={},format=raw", self.config.boot_path));
         }
 
         // Network
-        cmd.args([
-            "-netdev",
-            "user,id=net0",
-            "-device",
-            "virtio-net,netdev=net0",
-        ]);
+        args.extend(
+            [
+                "-netdev",
+                "user,id=net0",
+                "-device",
+                "virtio-net,netdev=net0",
+            ]
+            .map(String::from),
+        );
 
         // VirtIO RNG
-        cmd.args(["-device", "virtio-rng-pci"]);
-
-        // Additional arguments
-        cmd.args(&self.config.extra_args);
-
-        // Spawn the process
-        match cmd
-            .stdin(Stdio::null())
-            .stdout(Stdio::inherit())
-            .stderr(Stdio::inherit())
-            .spawn()
-        {
-            Ok(child) => {
-                self.child = Some(child);
-                *self.running.lock().unwrap() = true;
-                log::info!(
-                    "✅ QEMU process started with SHM support (PID: {:?})",
-                    self.child.as_ref().unwrap().id()
-                );
+        args.extend(["-device", "virtio-rng-pci"].map(String::from));
 
-                // Start capture thread
-                self.start_capture_thread();
+        // Shared RAM + QMP for the compositor
+        args.extend(self.config.geometry_args());
 
-                // Store framebuffer
-                self.framebuffer = Some(shm_fb);
-
-                Ok(())
-            },
-            Err(e) => Err(format!("Failed to start QEMU: {}", e)),
-        }
+        // Additional arguments
+        args.extend(self.config.extra_args.iter().cloned());
+        args
     }
 
     /// Start the framebuffer capture thread
+    ///
+    /// Draws the test pattern or takes screendumps, depending on the
+    /// framebuffer source; written shared memory is read by
+    /// `read_framebuffer_from_shm`.
     fn start_capture_thread(&mut self) {
         let running = Arc::clone(&self.running);
         let fb_copy = Arc::clone(&self.framebuffer_copy);
+        let source = Arc::clone(&self.framebuffer_source);
+        let shm_written = Arc::clone(&self.shm_written);
+        let qmp_socket = self.qmp_socket.clone();
         let width = self.config.width;
         let height = self.config.height;
 
@@ -840,8 +1309,23 @@ impl QemuProcessWithShm {
             log::info!("📸 Framebuffer capture thread started");
 
             while *running.lock().unwrap() {
-                // For now, generate a test pattern
-                // In production, this would read from virtio-gpu blob resources
+                match *source.lock().unwrap() {
+                    FramebufferSource::TestPattern => {},
+                    FramebufferSource::SharedMemory if shm_written.load(Ordering::Relaxed) => {
+                        thread::sleep(Duration::from_millis(16));
+                        continue;
+                    },
+                    // Also stands in for shared memory nothing has written
+                    FramebufferSource::SharedMemory | FramebufferSource::Screendump => {
+                        if let Err(e) = capture_screendump(&qmp_socket, &fb_copy, width) {
+                            log::debug!("QEMU screendump failed: {}", e);
+                        }
+                        // Screendumps are slow; ~10 FPS is plenty
+                        thread::sleep(Duration::from_millis(100));
+                        continue;
+                    },
+                }
+
                 let mut fb = fb_copy.lock().unwrap();
                 let time = std::time::SystemTime::now()
                     .duration_since(std::time::UNIX_EPOCH)
@@ -861,6 +1345,8 @@ impl QemuProcessWithShm {
                     }
                 }
 
+                drop(fb);
+
                 // 60 FPS target
                 thread::sleep(Duration::from_millis(16));
             }
@@ -876,23 +1362,112 @@ impl QemuProcessWithShm {
         self.framebuffer.as_ref().map(|fb| fb.as_slice())
     }
 
-    /// Send input event to QEMU
+    /// Send text to QEMU, one key event per character
+    ///
+    /// Control characters map to their keys (`\n` to Enter, `\x1b` to
+    /// Escape); use `send_key` for arrows, function keys and modifiers.
     pub fn send_input(&self, input: &str) {
-        log::debug!("⌨️  Forwarding Input to QEMU (SHM): {}", input);
-        // TODO: Implement QMP input-send-event or serial PTY writing
+        log::debug!("⌨️  Forwarding Input to QEMU (SHM): {:?}", input);
+        for c in input.chars() {
+            let code = match c {
+                '\n' | '\r' => KeyCode::Enter,
+                '\t' => KeyCode::Tab,
+                '\x08' | '\x7f' => KeyCode::Backspace,
+                '\x1b' => KeyCode::Escape,
+                c => KeyCode::Char(c),
+            };
+            if let Err(e) = self.send_key(KeyEvent::new(code)) {
+                log::warn!("⚠️  QEMU input dropped: {}", e);
+                return;
+            }
+        }
+    }
+
+    /// Encode a key event and deliver it to the guest
+    ///
+    /// Console encodings go to the serial pty, QMP encodings to
+    /// `input-send-event`.
+    pub fn send_key(&self, event: KeyEvent) -> Result<(), String> {
+        let encoded = self
+            .input_encoder
+            .encode(&event)
+            .ok_or_else(|| format!("No encoding for {:?}", event))?;
+        match encoded {
+            EncodedInput::Console(bytes) => {
+                let mut console = self.serial_console.lock().unwrap();
+                if console.is_none() {
+                    *console = Some(open_serial_console(&self.qmp_socket)?);
+                }
+                let result = console.as_mut().unwrap().write_all(&bytes);
+                if let Err(e) = result {
+                    // Reopen next time; the guest may have been restarted
+                    *console = None;
+                    return Err(format!("Serial console write failed: {}", e));
+                }
+                Ok(())
+            },
+            EncodedInput::Qmp(events) => {
+                let command = serde_json::json!({
+                    "execute": "input-send-event",
+                    "arguments": { "events": events },
+                });
+                qmp_execute(&self.qmp_socket, &[command]).map(|_| ())
+            },
+        }
+    }
+
+    /// Replace the input encoder, e.g. `QmpKeyEncoder` for a graphical guest
+    pub fn set_input_encoder(&mut self, encoder: impl InputEncoder + 'static) {
+        self.input_encoder = Box::new(encoder);
     }
 
     pub fn update_test_pattern(&self) {}
 
+    pub fn framebuffer_source(&self) -> FramebufferSource {
+        *self.framebuffer_source.lock().unwrap()
+    }
+
+    /// Choose where the displayed framebuffer comes from
+    pub fn set_framebuffer_source(&mut self, source: FramebufferSource) {
+        *self.framebuffer_source.lock().unwrap() = source;
+    }
+
+    /// Copy the guest display from shared memory into the framebuffer
+    ///
+    /// Until something writes the shared memory (or when there is none),
+    /// the capture thread keeps taking screendumps instead. Does nothing for
+    /// the other sources, which the capture thread fills in. Returns the
+    /// source the framebuffer currently comes from.
+    pub fn read_framebuffer_from_shm(&self) -> FramebufferSource {
+        let source = self.framebuffer_source();
+        if source != FramebufferSource::SharedMemory {
+            return source;
+        }
+        let Some(shm) = &self.framebuffer else {
+            return FramebufferSource::Screendump;
+        };
+        if !self.shm_written.load(Ordering::Relaxed) {
+            if shm.as_slice().iter().all(|&byte| byte == 0) {
+                return FramebufferSource::Screendump;
+            }
+            self.shm_written.store(true, Ordering::Relaxed);
+        }
+
+        let mut fb = self.framebuffer_copy.lock().unwrap();
+        blit_rgba(
+            &mut fb,
+            self.config.width,
+            shm.as_slice(),
+            shm.width,
+            shm.height,
+        );
+        FramebufferSource::SharedMemory
+    }
+
     /// Stop the QEMU process
     pub fn stop(&mut self) {
         log::info!("⏸️  Stopping QEMU process...");
-        *self.running.lock().unwrap() = false;
-
-        // Wait for capture thread
-        if let Some(thread) = self.capture_thread.take() {
-            let _ = thread.join();
-        }
+        self.stop_capture_thread();
 
         if let Some(mut child) = self.child.take() {
             match child.kill() {
@@ -906,11 +1481,64 @@ impl QemuProcessWithShm {
             }
         }
 
-        // Clean up
+        self.cleanup();
+    }
+
+    /// Shut the guest down cleanly, forcing QEMU off only if it hangs
+    ///
+    /// Sends a QMP `system_powerdown` and waits up to `timeout` for QEMU to
+    /// exit, then sends SIGTERM and finally SIGKILL. Returns the step that
+    /// stopped it.
+    pub fn stop_graceful(&mut self, timeout: Duration) -> QemuStopMethod {
+        log::info!("⏸️  Powering down QEMU guest...");
+        self.stop_capture_thread();
+
+        let method = match self.child.take() {
+            None => QemuStopMethod::NotRunning,
+            Some(mut child) => {
+                let powerdown = serde_json::json!({ "execute": "system_powerdown" });
+                let method = match qmp_execute(&self.qmp_socket, &[powerdown]) {
+                    Ok(_) if wait_for_exit(&mut child, timeout) => QemuStopMethod::Powerdown,
+                    Ok(_) => {
+                        log::warn!("⚠️  Guest ignored powerdown for {:?}", timeout);
+                        terminate_child(&mut child)
+                    },
+                    Err(e) => {
+                        log::warn!("⚠️  QEMU powerdown request failed: {}", e);
+                        terminate_child(&mut child)
+                    },
+                };
+                log::info!("✅ QEMU process stopped ({:?})", method);
+                method
+            },
+        };
+
+        self.cleanup();
+        method
+    }
+
+    fn stop_capture_thread(&mut self) {
+        *self.running.lock().unwrap() = false;
+        if let Some(thread) = self.capture_thread.take() {
+            let _ = thread.join();
+        }
+    }
+
+    /// Remove sockets and shared memory left by a stopped QEMU
+    fn cleanup(&mut self) {
         let _ = std::fs::remove_file(&self.qmp_socket);
+        for path in [self.config.shm_path(), self.config.qmp_socket_path()]
+            .into_iter()
+            .flatten()
+        {
+            let _ = std::fs::remove_file(path);
+        }
 
         // SharedMemoryFramebuffer will be dropped automatically
         self.framebuffer = None;
+        *self.serial_console.lock().unwrap() = None;
+        self.startup_deadline = None;
+        self.stderr_tail = None;
     }
 
     /// Check if QEMU is running
@@ -2544,6 +3172,321 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_geometry_integration_args() {
+        let config = QemuConfig {
+            boot_path: "alpine.iso".to_string(),
+            memory_mb: 256,
+            ..Default::default()
+        };
+        let plain_args = QemuProcessWithShm::new(config.clone()).qemu_args();
+        assert!(!plain_args.iter().any(|arg| arg.contains("qemu_ram_")));
+
+        let config = config.with_geometry_integration("test-vm");
+        assert_eq!(
+            config.shm_path(),
+            Some(PathBuf::from("/dev/shm/qemu_ram_test-vm"))
+        );
+        let args = QemuProcessWithShm::new(config).qemu_args();
+        let has_pair = |flag: &str, value: &str| {
+            args.windows(2)
+                .any(|pair| pair[0] == flag && pair[1] == value)
+        };
+        assert!(has_pair(
+            "-object",
+            "memory-backend-file,id=geometry-ram,size=256M,mem-path=/dev/shm/qemu_ram_test-vm,share=on"
+        ));
+        assert!(has_pair("-numa", "node,memdev=geometry-ram"));
+        assert!(has_pair("-qmp", "unix:/tmp/qmp-test-vm.sock,server,nowait"));
+        assert!(has_pair("-display", "none"));
+        assert!(has_pair("-m", "256"));
+    }
+
+    /// Config booting a dummy image with `qemu_binary`
+    fn fake_boot_config(dir: &Path, qemu_binary: &Path) -> QemuConfig {
+        let boot_path = dir.join("disk.img");
+        std::fs::write(&boot_path, []).unwrap();
+        QemuConfig {
+            qemu_binary: qemu_binary.display().to_string(),
+            boot_path: boot_path.display().to_string(),
+            width: 16,
+            height: 16,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_start_reports_missing_binary() {
+        let dir = tempfile::tempdir().unwrap();
+        let missing = dir.path().join("qemu-system-missing");
+        let mut proc = QemuProcessWithShm::new(fake_boot_config(dir.path(), &missing));
+
+        let err = proc.start().unwrap_err();
+        assert_eq!(
+            err,
+            QemuStartError::BinaryNotFound(missing.display().to_string())
+        );
+        assert!(err.remediation().contains("Install QEMU"));
+        assert!(!proc.is_running());
+    }
+
+    /// Executable shell script `name` in `dir`
+    fn fake_qemu(dir: &Path, name: &str, body: &str) -> PathBuf {
+        use std::os::unix::fs::PermissionsExt;
+
+        let script = dir.join(name);
+        std::fs::write(&script, format!("#!/bin/sh\n{}", body)).unwrap();
+        std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o755)).unwrap();
+        script
+    }
+
+    #[test]
+    fn test_start_falls_back_without_kvm() {
+        let dir = tempfile::tempdir().unwrap();
+        let log = dir.path().join("invocations");
+        let script = fake_qemu(
+            dir.path(),
+            "qemu-system-fake",
+            &format!("echo \"$*\" >> {}\nexec sleep 30\n", log.display()),
+        );
+        let config = QemuConfig {
+            kvm_fallback: false,
+            ..fake_boot_config(dir.path(), &script)
+        };
+        let missing_kvm = dir.path().join("kvm");
+
+        let mut proc = QemuProcessWithShm::new(config.clone());
+        proc.kvm_device = missing_kvm.clone();
+        let err = proc.start().unwrap_err();
+        assert!(matches!(err, QemuStartError::KvmUnavailable(_)), "{}", err);
+        assert!(!log.exists(), "QEMU was started without usable KVM");
+
+        let mut proc = QemuProcessWithShm::new(QemuConfig {
+            kvm_fallback: true,
+            ..config
+        });
+        proc.kvm_device = missing_kvm;
+        proc.start().unwrap();
+        assert!(proc.is_running());
+        assert!(!proc.kvm_enabled());
+        assert_eq!(proc.check_startup(), Ok(()));
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while !log.exists() && Instant::now() < deadline {
+            thread::sleep(Duration::from_millis(10));
+        }
+        let invocations = std::fs::read_to_string(&log).unwrap();
+        assert_eq!(invocations.lines().count(), 1);
+        assert!(!invocations.contains("-enable-kvm"));
+        proc.stop();
+    }
+
+    #[test]
+    fn test_check_startup_reports_early_exit() {
+        let dir = tempfile::tempdir().unwrap();
+        let script = fake_qemu(
+            dir.path(),
+            "qemu-system-fake",
+            "echo 'qemu: -vnc :0: Failed to bind socket: Address already in use' >&2\nexit 1\n",
+        );
+        let mut proc = QemuProcessWithShm::new(QemuConfig {
+            enable_kvm: false,
+            ..fake_boot_config(dir.path(), &script)
+        });
+
+        // start doesn't sit out the grace period
+        let started = Instant::now();
+        proc.start().unwrap();
+        assert!(started.elapsed() < QEMU_STARTUP_GRACE);
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+        let err = loop {
+            match proc.check_startup() {
+                Err(e) => break e,
+                Ok(()) if Instant::now() < deadline => thread::sleep(Duration::from_millis(10)),
+                Ok(()) => panic!("QEMU exit was not reported"),
+            }
+        };
+        assert!(matches!(err, QemuStartError::PortInUse(_)), "{}", err);
+        assert!(!proc.is_running());
+        // Reported once
+        assert_eq!(proc.check_startup(), Ok(()));
+        proc.stop();
+    }
+
+    #[test]
+    fn test_read_framebuffer_from_shm() {
+        let mut proc = QemuProcessWithShm::new(QemuConfig {
+            width: 4,
+            height: 2,
+            ..Default::default()
+        });
+        // QEMU's display never reaches the shared memory by itself
+        assert_eq!(proc.framebuffer_source(), FramebufferSource::Screendump);
+        proc.set_framebuffer_source(FramebufferSource::SharedMemory);
+        assert_eq!(
+            proc.read_framebuffer_from_shm(),
+            FramebufferSource::Screendump
+        );
+
+        // Screendumps stand in until the shared memory is first written
+        proc.framebuffer = Some(
+            SharedMemoryFramebuffer::new(&format!("test_read_{}", std::process::id()), 4, 2)
+                .unwrap(),
+        );
+        assert_eq!(
+            proc.read_framebuffer_from_shm(),
+            FramebufferSource::Screendump
+        );
+        assert_eq!(proc.get_framebuffer(), [0; 32]);
+
+        let guest: Vec<u8> = (0..32).collect();
+        proc.framebuffer.as_mut().unwrap().write(&guest).unwrap();
+        assert_eq!(
+            proc.read_framebuffer_from_shm(),
+            FramebufferSource::SharedMemory
+        );
+        assert_eq!(proc.get_framebuffer(), guest);
+
+        // Once written, a blank guest screen is still read from shared memory
+        proc.framebuffer.as_mut().unwrap().clear();
+        assert_eq!(
+            proc.read_framebuffer_from_shm(),
+            FramebufferSource::SharedMemory
+        );
+        assert_eq!(proc.get_framebuffer(), [0; 32]);
+
+        // Other sources leave the framebuffer to the capture thread
+        proc.framebuffer.as_mut().unwrap().write(&guest).unwrap();
+        proc.set_framebuffer_source(FramebufferSource::TestPattern);
+        assert_eq!(
+            proc.read_framebuffer_from_shm(),
+            FramebufferSource::TestPattern
+        );
+        assert_eq!(proc.get_framebuffer(), [0; 32]);
+    }
+
+    #[test]
+    fn test_send_key_writes_console_sequences() {
+        let proc = QemuProcessWithShm::new(QemuConfig::default());
+        let console = tempfile::NamedTempFile::new().unwrap();
+        *proc.serial_console.lock().unwrap() = Some(console.reopen().unwrap());
+
+        proc.send_input("ls\n");
+        proc.send_key(KeyEvent::new(KeyCode::Up)).unwrap();
+        assert_eq!(std::fs::read(console.path()).unwrap(), b"ls\r\x1b[A");
+    }
+
+    /// Fake QMP server on `socket` for one connection: greets, then
+    /// acknowledges each command after an event; returns the commands
+    fn fake_qmp_server(socket: &Path) -> thread::JoinHandle<Vec<serde_json::Value>> {
+        let _ = std::fs::remove_file(socket);
+        let listener = std::os::unix::net::UnixListener::bind(socket).unwrap();
+        thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut writer = stream.try_clone().unwrap();
+            writeln!(
+                writer,
+                r#"{{"QMP": {{"version": {{}}, "capabilities": []}}}}"#
+            )
+            .unwrap();
+            let mut commands = Vec::new();
+            for line in BufReader::new(stream).lines() {
+                let command: serde_json::Value = serde_json::from_str(&line.unwrap()).unwrap();
+                writeln!(writer, r#"{{"event": "RESUME"}}"#).unwrap();
+                writeln!(writer, r#"{{"return": {{}}}}"#).unwrap();
+                commands.push(command);
+            }
+            commands
+        })
+    }
+
+    #[test]
+    fn test_send_key_over_qmp() {
+        let mut proc = QemuProcessWithShm::new(QemuConfig::default());
+        proc.qmp_socket =
+            std::env::temp_dir().join(format!("test_qmp_{}.sock", std::process::id()));
+        let server = fake_qmp_server(&proc.qmp_socket);
+        proc.set_input_encoder(crate::qemu::input::QmpKeyEncoder);
+
+        proc.send_key(KeyEvent::new(KeyCode::Up)).unwrap();
+        let commands = server.join().unwrap();
+        let _ = std::fs::remove_file(&proc.qmp_socket);
+
+        assert_eq!(commands[0]["execute"], "qmp_capabilities");
+        assert_eq!(commands[1]["execute"], "input-send-event");
+        let events = commands[1]["arguments"]["events"].as_array().unwrap();
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0]["data"]["key"]["data"], "up");
+        assert_eq!(events[0]["data"]["down"], true);
+    }
+
+    #[test]
+    fn test_stop_graceful_forces_only_after_timeout() {
+        // Mock QEMU: `guest` is the shell script standing in for the process
+        let stop = |guest: &str, timeout: Duration| {
+            let mut proc = QemuProcessWithShm::new(QemuConfig::default());
+            proc.qmp_socket = std::env::temp_dir().join(format!(
+                "test_stop_{}_{}.sock",
+                std::process::id(),
+                timeout.as_millis()
+            ));
+            let server = fake_qmp_server(&proc.qmp_socket);
+            proc.child = Some(Command::new("sh").args(["-c", guest]).spawn().unwrap());
+
+            let started = Instant::now();
+            let method = proc.stop_graceful(timeout);
+            let commands = server.join().unwrap();
+            assert_eq!(commands[1]["execute"], "system_powerdown");
+            assert!(proc.child.is_none());
+            (method, started.elapsed())
+        };
+
+        // A guest that shuts down in time never sees a signal
+        let (method, elapsed) = stop("sleep 0.2", Duration::from_secs(10));
+        assert_eq!(method, QemuStopMethod::Powerdown);
+        assert!(elapsed < Duration::from_secs(5));
+
+        // A hung guest is terminated once the timeout runs out
+        let (method, elapsed) = stop("exec sleep 30", Duration::from_millis(300));
+        assert_eq!(method, QemuStopMethod::Terminated);
+        assert!(elapsed >= Duration::from_millis(300));
+
+        // ...and killed if it ignores SIGTERM too
+        let (method, elapsed) = stop(
+            "trap '' TERM; while :; do sleep 0.1; done",
+            Duration::from_millis(200),
+        );
+        assert_eq!(method, QemuStopMethod::Killed);
+        assert!(elapsed >= Duration::from_millis(200) + QEMU_TERM_GRACE);
+
+        let mut idle = QemuProcessWithShm::new(QemuConfig::default());
+        assert_eq!(
+            idle.stop_graceful(Duration::from_millis(10)),
+            QemuStopMethod::NotRunning
+        );
+    }
+
+    #[test]
+    fn test_parse_ppm_screendump() {
+        let mut ppm = b"P6\n# screendump\n3 2\n255\n".to_vec();
+        ppm.extend((0..18).map(|i| i as u8));
+        let (width, height, rgba) = parse_ppm(&ppm).unwrap();
+        assert_eq!((width, height), (3, 2));
+        assert_eq!(&rgba[..8], [0, 1, 2, 255, 3, 4, 5, 255]);
+
+        // A larger screendump is cropped to the framebuffer
+        let mut fb = vec![0; 2 * 2 * 4];
+        blit_rgba(&mut fb, 2, &rgba, width, height);
+        assert_eq!(
+            fb,
+            [0, 1, 2, 255, 3, 4, 5, 255, 9, 10, 11, 255, 12, 13, 14, 255]
+        );
+
+        assert!(parse_ppm(b"P3\n1 1\n255\n").is_err());
+        assert!(parse_ppm(&ppm[..ppm.len() - 1]).is_err());
+    }
+
     #[test]
     #[cfg(not(feature = "hypervisor"))]
     fn test_vm_stub() {
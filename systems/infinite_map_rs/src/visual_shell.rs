@@ -1,217 +0,0 @@
-//! Visual Shell Integration Stub
-//!
-//! This module provides stub types for visual shell integration.
-//! The real implementation is in systems/visual_shell/.
-
-/// Daemon identifier for tracking evolution daemons
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-pub struct DaemonId(pub u64);
-
-impl DaemonId {
-    pub fn from_name(name: &str) -> Self {
-        let mut hash = 0u64;
-        for byte in name.bytes() {
-            hash = hash.wrapping_mul(31).wrapping_add(byte as u64);
-        }
-        Self(hash)
-    }
-
-    pub fn name(&self) -> String {
-        format!("daemon-{}", self.0)
-    }
-}
-
-/// Frequency band for audio/visual processing
-#[derive(Debug, Clone, Copy, PartialEq)]
-pub enum FrequencyBand {
-    UltraLow,    // < 1 Hz (background)
-    Low,         // 1-8 Hz (subconscious)
-    Mid,         // 8-30 Hz (conscious)
-    High,        // 31-100 Hz (intensive)
-    Alpha,       // 8-13 Hz (relaxation)
-    Beta,        // 14-30 Hz (focus)
-    Gamma,       // 31-100 Hz (intensive)
-    Custom(f32), // Custom frequency
-}
-
-impl FrequencyBand {
-    pub fn from_name(name: &str) -> Option<Self> {
-        match name.to_lowercase().as_str() {
-            "ultralow" | "ultra_low" => Some(FrequencyBand::UltraLow),
-            "low" => Some(FrequencyBand::Low),
-            "mid" => Some(FrequencyBand::Mid),
-            "high" => Some(FrequencyBand::High),
-            "alpha" => Some(FrequencyBand::Alpha),
-            "beta" => Some(FrequencyBand::Beta),
-            "gamma" => Some(FrequencyBand::Gamma),
-            _ => None,
-        }
-    }
-}
-
-/// Daemon state information
-#[derive(Debug, Clone)]
-pub struct DaemonState {
-    pub id: DaemonId,
-    pub band: FrequencyBand,
-    pub active: bool,
-    pub last_activity: std::time::Instant,
-}
-
-/// Visual shell integration trait for trait objects
-pub trait VisualShellIntegration: Send + Sync {
-    fn initialize(&mut self) -> Result<(), Box<dyn std::error::Error>>;
-    fn send_thought(&self, thought: &[u8]) -> Result<(), Box<dyn std::error::Error>>;
-    fn receive_feedback(&mut self) -> Result<Vec<u8>, Box<dyn std::error::Error>>;
-    fn get_daemon_status(
-        &self,
-        daemon_id: DaemonId,
-    ) -> Result<DaemonState, Box<dyn std::error::Error>>;
-    fn register_daemon(
-        &mut self,
-        id: DaemonId,
-        band: FrequencyBand,
-        amplitude: f32,
-    ) -> Result<(), Box<dyn std::error::Error>>;
-    fn unregister_daemon(&mut self, id: DaemonId) -> Result<(), Box<dyn std::error::Error>>;
-    fn update_daemon_data(
-        &mut self,
-        id: DaemonId,
-        data: Vec<f32>,
-    ) -> Result<(), Box<dyn std::error::Error>>;
-    fn set_daemon_amplitude(
-        &mut self,
-        id: DaemonId,
-        amplitude: f32,
-    ) -> Result<(), Box<dyn std::error::Error>>;
-}
-
-/// Concrete visual shell implementation
-pub struct VisualShell {
-    daemons: std::collections::HashMap<DaemonId, DaemonState>,
-}
-
-impl VisualShell {
-    pub fn new() -> Result<Self, Box<dyn std::error::Error>> {
-        Ok(Self {
-            daemons: std::collections::HashMap::new(),
-        })
-    }
-
-    /// Initialize GPU resources
-    pub fn init_gpu(
-        &mut self,
-        _device: &wgpu::Device,
-        _queue: &wgpu::Queue,
-    ) -> Result<(), Box<dyn std::error::Error>> {
-        Ok(())
-    }
-
-    /// Get texture view for background
-    pub fn texture_view(&self) -> Option<std::sync::Arc<wgpu::TextureView>> {
-        None // Stub doesn't create textures
-    }
-}
-
-impl VisualShell {
-    pub fn daemon_count(&self) -> usize {
-        self.daemons.len()
-    }
-
-    pub fn tick_mixer(
-        &mut self,
-        _delta: std::time::Duration,
-    ) -> Result<(), Box<dyn std::error::Error>> {
-        Ok(())
-    }
-
-    pub fn update_from_spectral_field(
-        &mut self,
-        _factor: f32,
-    ) -> Result<(), Box<dyn std::error::Error>> {
-        Ok(())
-    }
-
-    pub fn update_from_neural(
-        &mut self,
-        _activations: &[f32],
-        _attention_weights: &[f32],
-        _memory_patterns: &[f32],
-        _confidence: f32,
-    ) -> Result<(), Box<dyn std::error::Error>> {
-        Ok(())
-    }
-
-    pub fn update_texture(&mut self) -> Result<(), Box<dyn std::error::Error>> {
-        Ok(())
-    }
-}
-
-impl VisualShellIntegration for VisualShell {
-    fn initialize(&mut self) -> Result<(), Box<dyn std::error::Error>> {
-        Ok(())
-    }
-
-    fn send_thought(&self, _thought: &[u8]) -> Result<(), Box<dyn std::error::Error>> {
-        Ok(())
-    }
-
-    fn receive_feedback(&mut self) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
-        Ok(vec![])
-    }
-
-    fn get_daemon_status(
-        &self,
-        daemon_id: DaemonId,
-    ) -> Result<DaemonState, Box<dyn std::error::Error>> {
-        self.daemons
-            .get(&daemon_id)
-            .cloned()
-            .ok_or_else(|| format!("Daemon {} not found", daemon_id.0).into())
-    }
-
-    fn register_daemon(
-        &mut self,
-        id: DaemonId,
-        band: FrequencyBand,
-        _amplitude: f32,
-    ) -> Result<(), Box<dyn std::error::Error>> {
-        self.daemons.insert(
-            id,
-            DaemonState {
-                id,
-                band,
-                active: true,
-                last_activity: std::time::Instant::now(),
-            },
-        );
-        Ok(())
-    }
-
-    fn unregister_daemon(&mut self, id: DaemonId) -> Result<(), Box<dyn std::error::Error>> {
-        self.daemons.remove(&id);
-        Ok(())
-    }
-
-    fn update_daemon_data(
-        &mut self,
-        id: DaemonId,
-        _data: Vec<f32>,
-    ) -> Result<(), Box<dyn std::error::Error>> {
-        if let Some(state) = self.daemons.get_mut(&id) {
-            state.last_activity = std::time::Instant::now();
-        }
-        Ok(())
-    }
-
-    fn set_daemon_amplitude(
-        &mut self,
-        id: DaemonId,
-        _amplitude: f32,
-    ) -> Result<(), Box<dyn std::error::Error>> {
-        if let Some(state) = self.daemons.get_mut(&id) {
-            state.last_activity = std::time::Instant::now();
-        }
-        Ok(())
-    }
-}
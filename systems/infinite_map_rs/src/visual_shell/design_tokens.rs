@@ -0,0 +1,127 @@
+//! Design Tokens - Visual parameters for the neural visualization
+//!
+//! Loaded from the shell's `tokens.json`; every field has a default so a
+//! partial (or missing) file still yields a usable palette.
+
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// How model confidence modulates node colors
+///
+/// Saturation and brightness are scaled by a factor interpolated between the
+/// `min_*` value (confidence 0) and the `max_*` value (confidence 1), so low
+/// confidence reads as washed out and dim.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ConfidenceToken {
+    pub min_saturation: f32,
+    pub max_saturation: f32,
+    pub min_brightness: f32,
+    pub max_brightness: f32,
+}
+
+impl Default for ConfidenceToken {
+    fn default() -> Self {
+        Self {
+            min_saturation: 0.25,
+            max_saturation: 1.0,
+            min_brightness: 0.6,
+            max_brightness: 1.0,
+        }
+    }
+}
+
+impl ConfidenceToken {
+    /// Modulate an RGB color for the given confidence (0-1)
+    pub fn apply(&self, rgb: [f32; 3], confidence: f32) -> [f32; 3] {
+        let c = confidence.clamp(0.0, 1.0);
+        let saturation = self.min_saturation + (self.max_saturation - self.min_saturation) * c;
+        let brightness = self.min_brightness + (self.max_brightness - self.min_brightness) * c;
+
+        let [h, s, v] = rgb_to_hsv(rgb);
+        hsv_to_rgb([
+            h,
+            (s * saturation).clamp(0.0, 1.0),
+            (v * brightness).clamp(0.0, 1.0),
+        ])
+    }
+}
+
+/// Visual parameters for the neural visualization
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DesignTokens {
+    /// Node color at zero activation
+    pub idle_color: [f32; 3],
+    /// Node color at full activation
+    pub active_color: [f32; 3],
+    /// Texture color of grid cells without a node
+    pub background_color: [f32; 3],
+    /// Attention weight below which no connection is drawn
+    pub connection_threshold: f32,
+    pub confidence: ConfidenceToken,
+}
+
+impl Default for DesignTokens {
+    fn default() -> Self {
+        Self {
+            idle_color: [0.1, 0.15, 0.4],
+            active_color: [0.2, 0.9, 1.0],
+            background_color: [0.02, 0.02, 0.05],
+            connection_threshold: 0.1,
+            confidence: ConfidenceToken::default(),
+        }
+    }
+}
+
+impl DesignTokens {
+    /// Load tokens from a JSON file
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, Box<dyn std::error::Error>> {
+        let json = std::fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&json)?)
+    }
+
+    /// Node color for an activation (0-1), before confidence is applied
+    pub fn activation_color(&self, activation: f32) -> [f32; 3] {
+        let t = activation.clamp(0.0, 1.0);
+        [0, 1, 2].map(|i| self.idle_color[i] + (self.active_color[i] - self.idle_color[i]) * t)
+    }
+}
+
+/// HSV saturation of an RGB color
+pub fn saturation(rgb: [f32; 3]) -> f32 {
+    rgb_to_hsv(rgb)[1]
+}
+
+fn rgb_to_hsv([r, g, b]: [f32; 3]) -> [f32; 3] {
+    let max = r.max(g).max(b);
+    let min = r.min(g).min(b);
+    let delta = max - min;
+
+    let h = if delta == 0.0 {
+        0.0
+    } else if max == r {
+        60.0 * ((g - b) / delta).rem_euclid(6.0)
+    } else if max == g {
+        60.0 * ((b - r) / delta + 2.0)
+    } else {
+        60.0 * ((r - g) / delta + 4.0)
+    };
+    let s = if max == 0.0 { 0.0 } else { delta / max };
+    [h, s, max]
+}
+
+fn hsv_to_rgb([h, s, v]: [f32; 3]) -> [f32; 3] {
+    let c = v * s;
+    let x = c * (1.0 - ((h / 60.0).rem_euclid(2.0) - 1.0).abs());
+    let m = v - c;
+    let (r, g, b) = match (h / 60.0) as u32 {
+        0 => (c, x, 0.0),
+        1 => (x, c, 0.0),
+        2 => (0.0, c, x),
+        3 => (0.0, x, c),
+        4 => (x, 0.0, c),
+        _ => (c, 0.0, x),
+    };
+    [r + m, g + m, b + m]
+}
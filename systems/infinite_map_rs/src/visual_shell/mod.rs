@@ -0,0 +1,649 @@
+//! Visual Shell Integration
+//!
+//! Daemon tracking plus the neural visualization (design tokens and the
+//! visual state graph) used by the compositor. The full shell lives in
+//! systems/visual_shell/.
+
+pub mod design_tokens;
+pub mod pixelrts_bridge;
+pub mod spectral_mixer;
+pub mod visual_state;
+
+pub use design_tokens::{ConfidenceToken, DesignTokens};
+pub use pixelrts_bridge::{OutputFormat, PixelRTSBridge, TextureData};
+pub use spectral_mixer::{
+    Envelope, EnvelopeStage, FrequencyBand, SpectralMixer, SpectralMixerError, WaveLayer,
+};
+pub use visual_state::{NeuralNode, SynapticConnection, VisualState};
+
+use crate::damage_tracker::{self, DirtyRect};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock};
+
+/// Hilbert order of the neural state grid when none is requested (256x256)
+pub const DEFAULT_HILBERT_ORDER: u32 = 8;
+
+/// Namespace used by [`DaemonId::from_name`]
+pub const DEFAULT_DAEMON_NAMESPACE: &str = "default";
+
+/// Daemon identifier for tracking evolution daemons
+///
+/// Ids are scoped by namespace so subsystems can reuse daemon names without
+/// stomping on each other.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct DaemonId {
+    namespace: Arc<str>,
+    hash: u64,
+}
+
+impl DaemonId {
+    /// Id in the default namespace
+    pub fn from_name(name: &str) -> Self {
+        Self::namespaced(DEFAULT_DAEMON_NAMESPACE, name)
+    }
+
+    /// Id for `name` within `namespace`
+    pub fn namespaced(namespace: &str, name: &str) -> Self {
+        // Default-namespace hashes are unchanged from before namespacing
+        let seed = if namespace == DEFAULT_DAEMON_NAMESPACE {
+            0
+        } else {
+            Self::hash_bytes(0, namespace.as_bytes())
+        };
+        Self {
+            namespace: Self::intern(namespace),
+            hash: Self::hash_bytes(seed, name.as_bytes()),
+        }
+    }
+
+    fn hash_bytes(seed: u64, bytes: &[u8]) -> u64 {
+        bytes.iter().fold(seed, |hash, &byte| {
+            hash.wrapping_mul(31).wrapping_add(byte as u64)
+        })
+    }
+
+    /// Ids in the same namespace share one allocation from this table
+    fn intern(namespace: &str) -> Arc<str> {
+        static NAMESPACES: OnceLock<Mutex<HashSet<Arc<str>>>> = OnceLock::new();
+        let mut namespaces = NAMESPACES
+            .get_or_init(Default::default)
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        match namespaces.get(namespace) {
+            Some(interned) => Arc::clone(interned),
+            None => {
+                let interned: Arc<str> = Arc::from(namespace);
+                namespaces.insert(Arc::clone(&interned));
+                interned
+            },
+        }
+    }
+
+    /// Rebuild an id from its namespace and `name_hash` (e.g. when
+    /// restoring persisted state)
+    pub fn from_parts(namespace: &str, name_hash: u64) -> Self {
+        Self {
+            namespace: Self::intern(namespace),
+            hash: name_hash,
+        }
+    }
+
+    pub fn namespace(&self) -> &str {
+        &self.namespace
+    }
+
+    /// Hash of the name (seeded by the namespace)
+    pub fn name_hash(&self) -> u64 {
+        self.hash
+    }
+
+    pub fn name(&self) -> String {
+        if &*self.namespace == DEFAULT_DAEMON_NAMESPACE {
+            format!("daemon-{}", self.hash)
+        } else {
+            format!("{}/daemon-{}", self.namespace, self.hash)
+        }
+    }
+}
+
+/// Daemon state information
+#[derive(Debug, Clone)]
+pub struct DaemonState {
+    pub id: DaemonId,
+    pub band: FrequencyBand,
+    pub active: bool,
+    pub last_activity: std::time::Instant,
+}
+
+/// Visual shell integration trait for trait objects
+pub trait VisualShellIntegration: Send + Sync {
+    fn initialize(&mut self) -> Result<(), Box<dyn std::error::Error>>;
+    fn send_thought(&self, thought: &[u8]) -> Result<(), Box<dyn std::error::Error>>;
+    fn receive_feedback(&mut self) -> Result<Vec<u8>, Box<dyn std::error::Error>>;
+    fn get_daemon_status(
+        &self,
+        daemon_id: DaemonId,
+    ) -> Result<DaemonState, Box<dyn std::error::Error>>;
+    fn register_daemon(
+        &mut self,
+        id: DaemonId,
+        band: FrequencyBand,
+        amplitude: f32,
+    ) -> Result<(), Box<dyn std::error::Error>>;
+    fn unregister_daemon(&mut self, id: DaemonId) -> Result<(), Box<dyn std::error::Error>>;
+    fn update_daemon_data(
+        &mut self,
+        id: DaemonId,
+        data: Vec<f32>,
+    ) -> Result<(), Box<dyn std::error::Error>>;
+    fn set_daemon_amplitude(
+        &mut self,
+        id: DaemonId,
+        amplitude: f32,
+    ) -> Result<(), Box<dyn std::error::Error>>;
+}
+
+/// Concrete visual shell implementation
+pub struct VisualShell {
+    daemons: std::collections::HashMap<DaemonId, DaemonState>,
+    tokens_path: Option<PathBuf>,
+    tokens: DesignTokens,
+    state: VisualState,
+    mixer: SpectralMixer,
+    hilbert_order: u32,
+    /// Neural activations laid out along the Hilbert curve (resolution²)
+    neural_state: Vec<f32>,
+    bridge: PixelRTSBridge,
+    /// State last rasterized into the texture (None forces a full upload)
+    uploaded: Option<VisualState>,
+    gpu: Option<ShellTexture>,
+}
+
+/// Background texture the visual state is rasterized into
+struct ShellTexture {
+    queue: Arc<wgpu::Queue>,
+    texture: wgpu::Texture,
+    view: Arc<wgpu::TextureView>,
+}
+
+impl VisualShell {
+    pub fn new() -> Result<Self, Box<dyn std::error::Error>> {
+        Ok(Self::with_order(None, DEFAULT_HILBERT_ORDER))
+    }
+
+    /// Create a shell whose grid is the smallest that fits `neural_len`
+    pub fn new_auto(
+        tokens_path: impl AsRef<Path>,
+        neural_len: usize,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        let order = Self::order_for_len(neural_len);
+        log::info!(
+            "Visual Shell: Hilbert order {} ({}x{}) for {} neural values",
+            order,
+            1u32 << order,
+            1u32 << order,
+            neural_len
+        );
+        let tokens_path = tokens_path.as_ref();
+        let tokens = DesignTokens::load(tokens_path).unwrap_or_else(|e| {
+            log::warn!(
+                "Visual Shell: using default tokens ({}: {})",
+                tokens_path.display(),
+                e
+            );
+            DesignTokens::default()
+        });
+
+        let mut shell = Self::with_order(Some(tokens_path.to_path_buf()), order);
+        shell.tokens = tokens;
+        Ok(shell)
+    }
+
+    fn with_order(tokens_path: Option<PathBuf>, hilbert_order: u32) -> Self {
+        let resolution = 1usize << hilbert_order;
+        Self {
+            daemons: std::collections::HashMap::new(),
+            tokens_path,
+            tokens: DesignTokens::default(),
+            state: VisualState::new(hilbert_order),
+            mixer: SpectralMixer::new(hilbert_order),
+            hilbert_order,
+            neural_state: vec![0.0; resolution * resolution],
+            bridge: PixelRTSBridge::default(),
+            uploaded: None,
+            gpu: None,
+        }
+    }
+
+    /// Smallest Hilbert order whose resolution² holds `neural_len` values
+    pub fn order_for_len(neural_len: usize) -> u32 {
+        crate::hilbert::required_grid_size(neural_len, 1).trailing_zeros()
+    }
+
+    pub fn hilbert_order(&self) -> u32 {
+        self.hilbert_order
+    }
+
+    /// Grid side length (2^order)
+    pub fn resolution(&self) -> u32 {
+        1 << self.hilbert_order
+    }
+
+    pub fn tokens_path(&self) -> Option<&Path> {
+        self.tokens_path.as_deref()
+    }
+
+    pub fn tokens(&self) -> &DesignTokens {
+        &self.tokens
+    }
+
+    pub fn set_tokens(&mut self, tokens: DesignTokens) {
+        self.tokens = tokens;
+    }
+
+    /// Graph built by the last `update_from_neural`
+    pub fn visual_state(&self) -> &VisualState {
+        &self.state
+    }
+
+    pub fn mixer(&self) -> &SpectralMixer {
+        &self.mixer
+    }
+
+    pub fn mixer_mut(&mut self) -> &mut SpectralMixer {
+        &mut self.mixer
+    }
+
+    /// Activations from the last `update_from_neural`, zero-padded
+    pub fn neural_state(&self) -> &[f32] {
+        &self.neural_state
+    }
+
+    /// Create the background texture the visual state is drawn into
+    pub fn init_gpu(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &Arc<wgpu::Queue>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let size = self.bridge.texture_size(&self.state);
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Visual Shell Texture"),
+            size: wgpu::Extent3d {
+                width: size,
+                height: size,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::Rgba8Unorm,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+            view_formats: &[],
+        });
+        let view = Arc::new(texture.create_view(&wgpu::TextureViewDescriptor::default()));
+        self.gpu = Some(ShellTexture {
+            queue: Arc::clone(queue),
+            texture,
+            view,
+        });
+        self.uploaded = None;
+        self.update_texture()
+    }
+
+    /// Get texture view for background
+    pub fn texture_view(&self) -> Option<Arc<wgpu::TextureView>> {
+        self.gpu.as_ref().map(|gpu| Arc::clone(&gpu.view))
+    }
+}
+
+impl VisualShell {
+    pub fn daemon_count(&self) -> usize {
+        self.daemons.len()
+    }
+
+    /// Change a registered daemon's band and amplitude
+    pub fn reregister_daemon(
+        &mut self,
+        id: DaemonId,
+        band: FrequencyBand,
+        amplitude: f32,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        self.mixer.reregister(&id, band, amplitude)?;
+        if let Some(state) = self.daemons.get_mut(&id) {
+            state.band = band;
+            state.active = true;
+            state.last_activity = std::time::Instant::now();
+        }
+        Ok(())
+    }
+
+    /// Set a daemon's wave phase offset in radians
+    pub fn set_daemon_phase(
+        &mut self,
+        id: DaemonId,
+        phase: f32,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        self.mixer.set_phase(&id, phase)?;
+        Ok(())
+    }
+
+    /// Exclude a daemon from the composite field without unregistering it
+    pub fn mute_daemon(
+        &mut self,
+        id: DaemonId,
+        muted: bool,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        self.mixer.set_muted(&id, muted)?;
+        Ok(())
+    }
+
+    /// Isolate soloed daemons in the composite field
+    pub fn solo_daemon(
+        &mut self,
+        id: DaemonId,
+        soloed: bool,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        self.mixer.set_solo(&id, soloed)?;
+        Ok(())
+    }
+
+    pub fn tick_mixer(
+        &mut self,
+        delta: std::time::Duration,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        self.mixer.tick(delta);
+        Ok(())
+    }
+
+    /// Drive the visualization from the mixer's interference field
+    ///
+    /// Field values (-1..1) are scaled by `factor` and mapped to 0..1
+    /// activations.
+    pub fn update_from_spectral_field(
+        &mut self,
+        factor: f32,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let activations: Vec<f32> = self
+            .mixer
+            .resolve_field()
+            .into_iter()
+            .map(|v| ((v * factor + 1.0) * 0.5).clamp(0.0, 1.0))
+            .collect();
+        self.update_from_neural(&activations, &[], &[], 1.0)
+    }
+
+    /// Store activations for the grid, truncating anything past resolution²,
+    /// and rebuild the visual state with confidence applied
+    pub fn update_from_neural(
+        &mut self,
+        activations: &[f32],
+        attention_weights: &[f32],
+        memory_patterns: &[f32],
+        confidence: f32,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let capacity = self.neural_state.len();
+        let used = activations.len().min(capacity);
+        if activations.len() > capacity {
+            log::warn!(
+                "Visual Shell: {} activations exceed {}x{} grid, dropping {}",
+                activations.len(),
+                self.resolution(),
+                self.resolution(),
+                activations.len() - capacity
+            );
+        }
+
+        self.neural_state[..used].copy_from_slice(&activations[..used]);
+        self.neural_state[used..].fill(0.0);
+        self.state.update_from_neural(
+            activations,
+            attention_weights,
+            memory_patterns,
+            confidence,
+            &self.tokens,
+        );
+        Ok(())
+    }
+
+    /// Texels changed since the last call, packed as by
+    /// `PixelRTSBridge::generate_texture_diff`
+    ///
+    /// The first call (and the first after `init_gpu`) covers the whole
+    /// texture.
+    pub fn take_texture_update(&mut self) -> (Vec<u8>, Vec<DirtyRect>) {
+        let update = match &self.uploaded {
+            Some(old) => self
+                .bridge
+                .generate_texture_diff(old, &self.state, &self.tokens),
+            None => {
+                let size = self.bridge.texture_size(&self.state);
+                let data = self
+                    .bridge
+                    .generate_texture(&self.state, &self.tokens, &OutputFormat::Rgba8)
+                    .to_rgba8();
+                (data, vec![DirtyRect::new(0, 0, size, size)])
+            },
+        };
+        self.uploaded = Some(self.state.clone());
+        update
+    }
+
+    /// Upload only the regions of the visual state that changed
+    pub fn update_texture(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        if self.gpu.is_none() {
+            return Ok(());
+        }
+        let (data, rects) = self.take_texture_update();
+        let Some(gpu) = &self.gpu else {
+            return Ok(());
+        };
+
+        let format = gpu.texture.format();
+        let bytes_per_pixel = damage_tracker::bytes_per_pixel(format)
+            .ok_or_else(|| format!("Cannot upload visual shell texels as {:?}", format))?;
+
+        let mut offset = 0;
+        for rect in rects {
+            // Each rect's texels are packed on their own, rows back to back
+            let (width, height) = (rect.width(), rect.height());
+            let layout = DirtyRect::new(0, 0, width, height).upload_layout(width, bytes_per_pixel);
+            let len = (layout.row_bytes * layout.rows) as usize;
+            gpu.queue.write_texture(
+                wgpu::ImageCopyTexture {
+                    texture: &gpu.texture,
+                    mip_level: 0,
+                    origin: wgpu::Origin3d {
+                        x: rect.x1,
+                        y: rect.y1,
+                        z: 0,
+                    },
+                    aspect: wgpu::TextureAspect::All,
+                },
+                &data[offset..offset + len],
+                layout.image_data_layout(),
+                wgpu::Extent3d {
+                    width,
+                    height,
+                    depth_or_array_layers: 1,
+                },
+            );
+            offset += len;
+        }
+        Ok(())
+    }
+}
+
+impl VisualShellIntegration for VisualShell {
+    fn initialize(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        Ok(())
+    }
+
+    fn send_thought(&self, _thought: &[u8]) -> Result<(), Box<dyn std::error::Error>> {
+        Ok(())
+    }
+
+    fn receive_feedback(&mut self) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+        Ok(vec![])
+    }
+
+    fn get_daemon_status(
+        &self,
+        daemon_id: DaemonId,
+    ) -> Result<DaemonState, Box<dyn std::error::Error>> {
+        self.daemons
+            .get(&daemon_id)
+            .cloned()
+            .ok_or_else(|| format!("Daemon {} not found", daemon_id.name()).into())
+    }
+
+    fn register_daemon(
+        &mut self,
+        id: DaemonId,
+        band: FrequencyBand,
+        amplitude: f32,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        self.mixer.register_daemon(id.clone(), band, amplitude)?;
+        self.daemons.insert(
+            id.clone(),
+            DaemonState {
+                id,
+                band,
+                active: true,
+                last_activity: std::time::Instant::now(),
+            },
+        );
+        Ok(())
+    }
+
+    fn unregister_daemon(&mut self, id: DaemonId) -> Result<(), Box<dyn std::error::Error>> {
+        self.mixer.unregister_daemon(&id);
+        self.daemons.remove(&id);
+        Ok(())
+    }
+
+    fn update_daemon_data(
+        &mut self,
+        id: DaemonId,
+        data: Vec<f32>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        self.mixer.update_daemon_data(&id, data)?;
+        if let Some(state) = self.daemons.get_mut(&id) {
+            state.last_activity = std::time::Instant::now();
+        }
+        Ok(())
+    }
+
+    fn set_daemon_amplitude(
+        &mut self,
+        id: DaemonId,
+        amplitude: f32,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        self.mixer.set_amplitude(&id, amplitude)?;
+        if let Some(state) = self.daemons.get_mut(&id) {
+            state.last_activity = std::time::Instant::now();
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_namespaced_daemon_ids_do_not_collide() {
+        let core = DaemonId::namespaced("core", "security_daemon");
+        let net = DaemonId::namespaced("net", "security_daemon");
+        assert_ne!(core, net);
+        assert_ne!(core.name_hash(), net.name_hash());
+        assert_eq!(core.namespace(), "core");
+        assert_eq!(net.namespace(), "net");
+        assert_eq!(core, DaemonId::namespaced("core", "security_daemon"));
+        // Ids in one namespace share the interned string
+        let other = DaemonId::namespaced("core", "audit_daemon");
+        assert!(Arc::ptr_eq(&core.namespace, &other.namespace));
+
+        let default = DaemonId::from_name("security_daemon");
+        assert_eq!(default.namespace(), DEFAULT_DAEMON_NAMESPACE);
+        assert_eq!(default, DaemonId::namespaced("default", "security_daemon"));
+        assert_ne!(default, core);
+        assert!(net.name().starts_with("net/"));
+
+        // Both can be registered side by side
+        let mut shell = VisualShell::new().unwrap();
+        shell
+            .register_daemon(core, FrequencyBand::Low, 1.0)
+            .unwrap();
+        shell
+            .register_daemon(net.clone(), FrequencyBand::Low, 1.0)
+            .unwrap();
+        assert_eq!(shell.daemon_count(), 2);
+        assert_eq!(shell.get_daemon_status(net).unwrap().id.namespace(), "net");
+    }
+
+    #[test]
+    fn test_new_auto_picks_smallest_fitting_order() {
+        assert_eq!(VisualShell::order_for_len(1000), 5);
+        assert_eq!(VisualShell::order_for_len(256), 4);
+        assert_eq!(VisualShell::order_for_len(257), 5);
+
+        let mut shell = VisualShell::new_auto("tokens.json", 1000).unwrap();
+        assert_eq!((shell.hilbert_order(), shell.resolution()), (5, 32));
+        assert_eq!(shell.neural_state().len(), 1024);
+        assert_eq!(shell.tokens_path(), Some(Path::new("tokens.json")));
+
+        // Oversized input is truncated, short input zero-padded
+        let big: Vec<f32> = (0..2000).map(|i| i as f32).collect();
+        shell.update_from_neural(&big, &[], &[], 1.0).unwrap();
+        assert_eq!(shell.neural_state()[1023], 1023.0);
+
+        shell.update_from_neural(&[1.0; 10], &[], &[], 1.0).unwrap();
+        assert_eq!(shell.neural_state()[9], 1.0);
+        assert_eq!(shell.neural_state()[10], 0.0);
+    }
+
+    #[test]
+    fn test_texture_updates_are_incremental() {
+        let mut shell = VisualShell::new_auto("tokens.json", 64).unwrap();
+        let mut activations = vec![0.5; 64];
+        shell
+            .update_from_neural(&activations, &[], &[], 1.0)
+            .unwrap();
+
+        // First upload covers the whole 8x8 texture
+        let (data, rects) = shell.take_texture_update();
+        assert_eq!(rects, vec![DirtyRect::new(0, 0, 8, 8)]);
+        assert_eq!(data.len(), 8 * 8 * 4);
+
+        // One changed node uploads one texel
+        activations[13] = 1.0;
+        shell
+            .update_from_neural(&activations, &[], &[], 1.0)
+            .unwrap();
+        let (data, rects) = shell.take_texture_update();
+        assert_eq!(rects, vec![DirtyRect::new(1, 2, 2, 3)]);
+        assert_eq!(data.len(), 4);
+
+        let (data, rects) = shell.take_texture_update();
+        assert!(data.is_empty() && rects.is_empty());
+    }
+
+    #[test]
+    fn test_update_texture_uploads_dirty_rects() {
+        let Some((device, queue)) = crate::tests::gpu::test_device() else {
+            return;
+        };
+        let mut shell = VisualShell::new_auto("tokens.json", 64).unwrap();
+        shell.init_gpu(&device, &queue).unwrap();
+
+        let mut activations = vec![0.5; 64];
+        activations[13] = 1.0;
+        shell
+            .update_from_neural(&activations, &[], &[], 1.0)
+            .unwrap();
+        shell.update_texture().unwrap();
+        device.poll(wgpu::Maintain::Wait);
+        assert!(shell.take_texture_update().1.is_empty());
+    }
+}
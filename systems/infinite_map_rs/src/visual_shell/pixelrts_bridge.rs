@@ -0,0 +1,272 @@
+//! PixelRTS Bridge - Rasterizes the visual state into a texture
+//!
+//! Every grid cell of the visual state becomes a `cell_size` x `cell_size`
+//! block colored by its node (or the token background when empty). Output
+//! is either RGBA8 or palettized for bandwidth-constrained broadcast.
+
+use super::design_tokens::DesignTokens;
+use super::visual_state::VisualState;
+use crate::damage_tracker::{DamageTracker, DirtyRect};
+
+/// Texel layout produced by `PixelRTSBridge::generate_texture`
+#[derive(Debug, Clone, PartialEq)]
+pub enum OutputFormat {
+    Rgba8,
+    /// One byte per texel indexing into `palette` (at most 256 entries)
+    Indexed8 {
+        palette: Vec<[u8; 3]>,
+    },
+}
+
+/// Texture bytes in the requested layout
+#[derive(Debug, Clone, PartialEq)]
+pub enum TextureData {
+    Rgba8(Vec<u8>),
+    Indexed8 {
+        indices: Vec<u8>,
+        palette: Vec<[u8; 3]>,
+    },
+}
+
+impl TextureData {
+    /// Expand to RGBA8 (indexed data is looked up in its palette)
+    pub fn to_rgba8(&self) -> Vec<u8> {
+        match self {
+            TextureData::Rgba8(data) => data.clone(),
+            TextureData::Indexed8 { indices, palette } => indices
+                .iter()
+                .flat_map(|&i| {
+                    let [r, g, b] = palette.get(i as usize).copied().unwrap_or([0; 3]);
+                    [r, g, b, 255]
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Evenly spaced RGB cube (`levels`³ colors, capped at 256)
+pub fn color_cube_palette(levels: u32) -> Vec<[u8; 3]> {
+    let levels = levels.clamp(2, 6);
+    let step = |i: u32| (i * 255 / (levels - 1)) as u8;
+    let mut palette = Vec::with_capacity((levels * levels * levels) as usize);
+    for r in 0..levels {
+        for g in 0..levels {
+            for b in 0..levels {
+                palette.push([step(r), step(g), step(b)]);
+            }
+        }
+    }
+    palette
+}
+
+/// Rasterizes a `VisualState` for upload or broadcast
+#[derive(Debug, Clone)]
+pub struct PixelRTSBridge {
+    /// Texels per grid cell along each axis
+    pub cell_size: u32,
+}
+
+impl Default for PixelRTSBridge {
+    fn default() -> Self {
+        Self::new(1)
+    }
+}
+
+impl PixelRTSBridge {
+    pub fn new(cell_size: u32) -> Self {
+        Self {
+            cell_size: cell_size.max(1),
+        }
+    }
+
+    /// Texture side length for a state
+    pub fn texture_size(&self, state: &VisualState) -> u32 {
+        state.resolution() * self.cell_size
+    }
+
+    /// Rasterize the state in the requested format
+    pub fn generate_texture(
+        &self,
+        state: &VisualState,
+        tokens: &DesignTokens,
+        format: &OutputFormat,
+    ) -> TextureData {
+        let rgba = self.rasterize(state, tokens);
+        match format {
+            OutputFormat::Rgba8 => TextureData::Rgba8(rgba),
+            OutputFormat::Indexed8 { palette } => {
+                let palette: Vec<[u8; 3]> = palette.iter().take(256).copied().collect();
+                let indices = rgba
+                    .chunks_exact(4)
+                    .map(|px| nearest_index(&palette, [px[0], px[1], px[2]]))
+                    .collect();
+                TextureData::Indexed8 { indices, palette }
+            },
+        }
+    }
+
+    /// RGBA8 for only the regions that changed between two states
+    ///
+    /// Returns the changed texels, packed rect by rect in the order of the
+    /// returned rects (each row-major and tightly packed), plus the rects in
+    /// texel coordinates. A resolution change yields one full-texture rect.
+    pub fn generate_texture_diff(
+        &self,
+        old_state: &VisualState,
+        new_state: &VisualState,
+        tokens: &DesignTokens,
+    ) -> (Vec<u8>, Vec<DirtyRect>) {
+        let resolution = new_state.resolution();
+        let size = self.texture_size(new_state);
+        let full = self.rasterize(new_state, tokens);
+
+        if old_state.resolution() != resolution {
+            return (full, vec![DirtyRect::new(0, 0, size, size)]);
+        }
+
+        // Compare one texel per grid cell, then scale rects to the texture
+        let cells = PixelRTSBridge::new(1);
+        let old_cells = cells.rasterize(old_state, tokens);
+        let new_cells = cells.rasterize(new_state, tokens);
+        let mut damage = DamageTracker::new(resolution, resolution);
+        for (i, (old, new)) in old_cells
+            .chunks_exact(4)
+            .zip(new_cells.chunks_exact(4))
+            .enumerate()
+        {
+            if old != new {
+                damage.mark_dirty(i as u32 % resolution, i as u32 / resolution);
+            }
+        }
+
+        let rects: Vec<DirtyRect> = damage
+            .compute_dirty_rects()
+            .into_iter()
+            .map(|r| {
+                let s = self.cell_size;
+                DirtyRect::new(r.x1 * s, r.y1 * s, r.x2 * s, r.y2 * s)
+            })
+            .collect();
+
+        let mut data = Vec::new();
+        for rect in &rects {
+            for y in rect.y1..rect.y2 {
+                let start = ((y * size + rect.x1) * 4) as usize;
+                data.extend_from_slice(&full[start..start + rect.width() as usize * 4]);
+            }
+        }
+        (data, rects)
+    }
+
+    fn rasterize(&self, state: &VisualState, tokens: &DesignTokens) -> Vec<u8> {
+        let size = self.texture_size(state) as usize;
+        let background = to_rgba8(tokens.background_color);
+        let mut data = background.repeat(size * size);
+
+        let cell = self.cell_size as usize;
+        for node in state.nodes() {
+            let color = to_rgba8(node.color);
+            let (cx, cy) = (
+                node.position.0 as usize * cell,
+                node.position.1 as usize * cell,
+            );
+            for y in cy..cy + cell {
+                for x in cx..cx + cell {
+                    let offset = (y * size + x) * 4;
+                    data[offset..offset + 4].copy_from_slice(&color);
+                }
+            }
+        }
+        data
+    }
+}
+
+fn to_rgba8(rgb: [f32; 3]) -> [u8; 4] {
+    let [r, g, b] = rgb.map(|c| (c.clamp(0.0, 1.0) * 255.0).round() as u8);
+    [r, g, b, 255]
+}
+
+fn nearest_index(palette: &[[u8; 3]], rgb: [u8; 3]) -> u8 {
+    palette
+        .iter()
+        .enumerate()
+        .min_by_key(|(_, entry)| {
+            (0..3)
+                .map(|c| (entry[c] as i32 - rgb[c] as i32).pow(2))
+                .sum::<i32>()
+        })
+        .map(|(i, _)| i as u8)
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_indexed_output_reconstructs_rgba() {
+        let tokens = DesignTokens::default();
+        let mut state = VisualState::new(3);
+        let activations: Vec<f32> = (0..40).map(|i| i as f32 / 40.0).collect();
+        state.update_from_neural(&activations, &[], &[], 0.7, &tokens);
+
+        let bridge = PixelRTSBridge::new(2);
+        assert_eq!(bridge.texture_size(&state), 16);
+
+        let TextureData::Rgba8(rgba) =
+            bridge.generate_texture(&state, &tokens, &OutputFormat::Rgba8)
+        else {
+            panic!("expected RGBA output");
+        };
+        assert_eq!(rgba.len(), 16 * 16 * 4);
+
+        let format = OutputFormat::Indexed8 {
+            palette: color_cube_palette(6),
+        };
+        let indexed = bridge.generate_texture(&state, &tokens, &format);
+        let TextureData::Indexed8 { indices, palette } = &indexed else {
+            panic!("expected indexed output");
+        };
+        assert_eq!(indices.len(), 16 * 16);
+        assert_eq!(palette.len(), 216);
+
+        // 6 levels are 51 apart: every channel within half a step
+        let rebuilt = indexed.to_rgba8();
+        assert_eq!(rebuilt.len(), rgba.len());
+        for (a, b) in rgba.iter().zip(&rebuilt) {
+            assert!((*a as i32 - *b as i32).abs() <= 26, "{} vs {}", a, b);
+        }
+    }
+
+    #[test]
+    fn test_diff_covers_only_changed_node() {
+        let tokens = DesignTokens::default();
+        let mut activations = vec![0.5; 64];
+        let mut old_state = VisualState::new(3);
+        old_state.update_from_neural(&activations, &[], &[], 1.0, &tokens);
+
+        // Node 13 sits at grid cell (1, 2) on an 8x8 curve
+        activations[13] = 1.0;
+        let mut new_state = old_state.clone();
+        new_state.update_from_neural(&activations, &[], &[], 1.0, &tokens);
+        assert_eq!(new_state.nodes()[13].position, (1, 2));
+
+        let bridge = PixelRTSBridge::new(4);
+        let (data, rects) = bridge.generate_texture_diff(&old_state, &new_state, &tokens);
+        assert_eq!(rects, vec![DirtyRect::new(4, 8, 8, 12)]);
+        assert_eq!(data.len(), 4 * 4 * 4);
+
+        let TextureData::Rgba8(full) =
+            bridge.generate_texture(&new_state, &tokens, &OutputFormat::Rgba8)
+        else {
+            panic!("expected RGBA output");
+        };
+        let texel = |x: usize, y: usize| &full[(y * 32 + x) * 4..(y * 32 + x) * 4 + 4];
+        assert_eq!(&data[..4], texel(4, 8));
+        assert_eq!(&data[data.len() - 4..], texel(7, 11));
+
+        // Identical states produce nothing to upload
+        let (data, rects) = bridge.generate_texture_diff(&new_state, &new_state, &tokens);
+        assert!(data.is_empty() && rects.is_empty());
+    }
+}
@@ -0,0 +1,1069 @@
+//! Spectral Mixer - Wave interference field driven by registered daemons
+//!
+//! Each daemon contributes a sine wave travelling along the Hilbert curve at
+//! its band frequency. `tick` advances every wave's phase by its frequency, so
+//! daemons at slightly different frequencies drift in and out of phase and
+//! the composite field shows moving interference (beating).
+//!
+//! Each layer's amplitude follows an ADSR [`Envelope`]: registering starts
+//! the attack from 0, the level then decays to the sustain level, and
+//! unregistering enters the release. The layer is only dropped once the
+//! release has finished.
+//!
+//! Daemons sharing a band are spread evenly around the circle by a phase
+//! offset (`2π · index / band_count`) so they don't all peak together.
+//!
+//! For debugging, daemons can be muted or soloed: while any daemon is
+//! soloed only soloed daemons are heard, otherwise muted ones are skipped.
+//!
+//! The mixer implements [`VatState`] so its daemons survive a hot swap.
+//! Per-daemon data can be as large as the field, so it is only written when
+//! [`SpectralMixer::set_persist_daemon_data`] is enabled.
+
+use super::DaemonId;
+use crate::hot_swap::{VatBuffer, VatError, VatId, VatState};
+use std::collections::HashMap;
+use std::f32::consts::TAU;
+use std::time::Duration;
+
+/// Wave periods across the whole curve in the resolved field
+pub const SPATIAL_CYCLES: f32 = 4.0;
+
+/// Fade-in time for newly registered daemons
+pub const DEFAULT_ATTACK: Duration = Duration::from_millis(250);
+
+/// Fade-out time for unregistered daemons
+pub const DEFAULT_RELEASE: Duration = Duration::from_millis(500);
+
+/// Vat the mixer's state is stored under
+pub const SPECTRAL_MIXER_VAT_ID: &str = "visual_shell_spectral_mixer";
+
+/// Attack/decay/sustain/release shape applied to a daemon's amplitude
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Envelope {
+    /// Time to rise from 0 to full amplitude
+    pub attack: Duration,
+    /// Time to fall from full amplitude to `sustain`
+    pub decay: Duration,
+    /// Level (0.0 - 1.0) held while registered
+    pub sustain: f32,
+    /// Time to fall from the current level to 0 once unregistered
+    pub release: Duration,
+}
+
+impl Envelope {
+    /// No fades: full amplitude on register, removed on unregister
+    pub const INSTANT: Self = Self {
+        attack: Duration::ZERO,
+        decay: Duration::ZERO,
+        sustain: 1.0,
+        release: Duration::ZERO,
+    };
+
+    /// Fade in and out without a decay stage
+    pub fn attack_release(attack: Duration, release: Duration) -> Self {
+        Self {
+            attack,
+            release,
+            ..Self::INSTANT
+        }
+    }
+}
+
+impl Default for Envelope {
+    fn default() -> Self {
+        Self::attack_release(DEFAULT_ATTACK, DEFAULT_RELEASE)
+    }
+}
+
+/// Where a layer is in its envelope
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EnvelopeStage {
+    Attack,
+    Decay,
+    Sustain,
+    Release,
+}
+
+/// Frequency band for audio/visual processing
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FrequencyBand {
+    UltraLow, // < 1 Hz (background)
+    Low,      // 1-8 Hz (subconscious)
+    Mid,      // 8-30 Hz (conscious)
+    High,     // 31-100 Hz (intensive)
+    Alpha,    // 8-13 Hz (relaxation)
+    Beta,     // 14-30 Hz (focus)
+    Gamma,    // 31-100 Hz (intensive)
+    /// Arbitrary range; build with [`FrequencyBand::custom`] to validate it
+    Custom {
+        low_hz: f32,
+        high_hz: f32,
+    },
+}
+
+impl FrequencyBand {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "ultralow" | "ultra_low" => Some(FrequencyBand::UltraLow),
+            "low" => Some(FrequencyBand::Low),
+            "mid" => Some(FrequencyBand::Mid),
+            "high" => Some(FrequencyBand::High),
+            "alpha" => Some(FrequencyBand::Alpha),
+            "beta" => Some(FrequencyBand::Beta),
+            "gamma" => Some(FrequencyBand::Gamma),
+            _ => None,
+        }
+    }
+
+    /// Custom band, rejecting ranges that are not `0 <= low_hz < high_hz`
+    pub fn custom(low_hz: f32, high_hz: f32) -> Result<Self, SpectralMixerError> {
+        let band = FrequencyBand::Custom { low_hz, high_hz };
+        band.validate()?;
+        Ok(band)
+    }
+
+    /// Frequency range of the band in Hz as `(low, high)`
+    pub fn range(&self) -> (f32, f32) {
+        match self {
+            FrequencyBand::UltraLow => (0.0, 1.0),
+            FrequencyBand::Low => (1.0, 8.0),
+            FrequencyBand::Mid => (8.0, 30.0),
+            FrequencyBand::High => (30.0, 100.0),
+            FrequencyBand::Alpha => (8.0, 13.0),
+            FrequencyBand::Beta => (14.0, 30.0),
+            FrequencyBand::Gamma => (30.0, 100.0),
+            FrequencyBand::Custom { low_hz, high_hz } => (*low_hz, *high_hz),
+        }
+    }
+
+    /// Frequency the mixer synthesizes for the band (centre of the range)
+    pub fn frequency(&self) -> f32 {
+        let (low, high) = self.range();
+        (low + high) * 0.5
+    }
+
+    /// Tag used when serializing the band
+    fn discriminant(&self) -> u8 {
+        match self {
+            FrequencyBand::UltraLow => 0,
+            FrequencyBand::Low => 1,
+            FrequencyBand::Mid => 2,
+            FrequencyBand::High => 3,
+            FrequencyBand::Alpha => 4,
+            FrequencyBand::Beta => 5,
+            FrequencyBand::Gamma => 6,
+            FrequencyBand::Custom { .. } => 7,
+        }
+    }
+
+    fn write_to_vat(&self, vat: &mut VatBuffer) -> Result<(), VatError> {
+        vat.write_u8(self.discriminant())?;
+        if let FrequencyBand::Custom { low_hz, high_hz } = self {
+            vat.write_f32(*low_hz)?;
+            vat.write_f32(*high_hz)?;
+        }
+        Ok(())
+    }
+
+    fn read_from_vat(vat: &mut VatBuffer) -> Result<Self, VatError> {
+        Ok(match vat.read_u8()? {
+            0 => FrequencyBand::UltraLow,
+            1 => FrequencyBand::Low,
+            2 => FrequencyBand::Mid,
+            3 => FrequencyBand::High,
+            4 => FrequencyBand::Alpha,
+            5 => FrequencyBand::Beta,
+            6 => FrequencyBand::Gamma,
+            7 => FrequencyBand::custom(vat.read_f32()?, vat.read_f32()?)
+                .map_err(|e| VatError::DeserializationFailed(e.to_string()))?,
+            tag => {
+                return Err(VatError::DeserializationFailed(format!(
+                    "unknown frequency band tag {}",
+                    tag
+                )))
+            },
+        })
+    }
+
+    pub fn validate(&self) -> Result<(), SpectralMixerError> {
+        let (low_hz, high_hz) = self.range();
+        if low_hz >= 0.0 && low_hz < high_hz && high_hz.is_finite() {
+            Ok(())
+        } else {
+            Err(SpectralMixerError::InvalidBand { low_hz, high_hz })
+        }
+    }
+}
+
+/// Errors from mixer operations on daemons
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum SpectralMixerError {
+    #[error("Daemon {0:?} is not registered")]
+    UnknownDaemon(DaemonId),
+    #[error("Daemon {0:?} is already registered")]
+    AlreadyRegistered(DaemonId),
+    #[error("Invalid frequency band {low_hz}-{high_hz} Hz (need 0 <= low < high)")]
+    InvalidBand { low_hz: f32, high_hz: f32 },
+}
+
+/// One daemon's contribution to the field
+#[derive(Debug, Clone)]
+pub struct WaveLayer {
+    pub band: FrequencyBand,
+    pub amplitude: f32,
+    /// Current phase in radians, kept in [0, 2π)
+    pub phase: f32,
+    /// Constant offset added to `phase` when sampling, in [0, 2π)
+    pub phase_offset: f32,
+    /// Optional per-cell modulation along the curve (sampled cyclically)
+    pub data: Vec<f32>,
+    pub envelope: Envelope,
+    /// Envelope level in [0, 1] applied to `amplitude`
+    gain: f32,
+    stage: EnvelopeStage,
+    /// Level the release started from (it falls to 0 over `release`)
+    release_from: f32,
+    /// Registration order, used to index daemons within a band
+    seq: u64,
+    /// Offset is assigned by band spreading (cleared by `set_phase`)
+    auto_phase: bool,
+    muted: bool,
+    soloed: bool,
+}
+
+impl WaveLayer {
+    fn new(band: FrequencyBand, amplitude: f32, seq: u64) -> Self {
+        Self {
+            band,
+            amplitude,
+            phase: 0.0,
+            phase_offset: 0.0,
+            data: Vec::new(),
+            envelope: Envelope::default(),
+            gain: 0.0,
+            stage: EnvelopeStage::Attack,
+            release_from: 0.0,
+            seq,
+            auto_phase: true,
+            muted: false,
+            soloed: false,
+        }
+    }
+
+    /// Amplitude after the envelope
+    pub fn effective_amplitude(&self) -> f32 {
+        self.amplitude * self.gain
+    }
+
+    pub fn stage(&self) -> EnvelopeStage {
+        self.stage
+    }
+
+    /// Unregistered; removed once the release reaches 0
+    pub fn is_releasing(&self) -> bool {
+        self.stage == EnvelopeStage::Release
+    }
+
+    fn start_release(&mut self) {
+        self.stage = EnvelopeStage::Release;
+        self.release_from = self.gain;
+    }
+
+    pub fn is_muted(&self) -> bool {
+        self.muted
+    }
+
+    pub fn is_soloed(&self) -> bool {
+        self.soloed
+    }
+
+    /// Advance the envelope by `dt` seconds
+    ///
+    /// Time left over when a stage completes carries into the next one, and
+    /// zero-length stages complete immediately.
+    fn advance_envelope(&mut self, mut dt: f32) {
+        let sustain = self.envelope.sustain.clamp(0.0, 1.0);
+        loop {
+            // (level to reach, full-scale change per stage, stage length, next stage)
+            let (target, rate, length, next) = match self.stage {
+                EnvelopeStage::Attack => (1.0, 1.0, self.envelope.attack, EnvelopeStage::Decay),
+                EnvelopeStage::Decay => (
+                    sustain,
+                    1.0 - sustain,
+                    self.envelope.decay,
+                    EnvelopeStage::Sustain,
+                ),
+                EnvelopeStage::Sustain => {
+                    self.gain = sustain;
+                    return;
+                },
+                EnvelopeStage::Release => (
+                    0.0,
+                    self.release_from,
+                    self.envelope.release,
+                    EnvelopeStage::Release,
+                ),
+            };
+
+            let remaining = (target - self.gain).abs();
+            let speed = if length.is_zero() || rate <= 0.0 {
+                f32::INFINITY
+            } else {
+                rate / length.as_secs_f32()
+            };
+            if remaining > speed * dt {
+                self.gain += (target - self.gain).signum() * speed * dt;
+                return;
+            }
+
+            dt -= remaining / speed;
+            self.gain = target;
+            if self.stage == next {
+                return;
+            }
+            self.stage = next;
+        }
+    }
+
+    fn sample(&self, t: f32, d: usize) -> f32 {
+        let modulation = if self.data.is_empty() {
+            1.0
+        } else {
+            self.data[d % self.data.len()]
+        };
+        let phase = self.phase + self.phase_offset;
+        self.effective_amplitude() * modulation * (TAU * SPATIAL_CYCLES * t + phase).sin()
+    }
+}
+
+/// Mixes daemon waves into a field over a Hilbert grid
+pub struct SpectralMixer {
+    layers: HashMap<DaemonId, WaveLayer>,
+    /// Number of field values (resolution²)
+    field_len: usize,
+    next_seq: u64,
+    /// Include per-daemon data when serialized to a Vat
+    persist_daemon_data: bool,
+}
+
+impl SpectralMixer {
+    pub fn new(hilbert_order: u32) -> Self {
+        let resolution = 1usize << hilbert_order;
+        Self {
+            layers: HashMap::new(),
+            field_len: resolution * resolution,
+            next_seq: 0,
+            persist_daemon_data: false,
+        }
+    }
+
+    /// Add a daemon's wave, starting the default envelope's attack from 0
+    ///
+    /// Daemons in the band without an explicit phase are re-spread so their
+    /// offsets stay evenly spaced. Fails with `AlreadyRegistered` if the
+    /// daemon is registered and not releasing; use
+    /// [`SpectralMixer::reregister`] to change its band. A daemon still
+    /// fading out is replaced by a fresh layer.
+    pub fn register_daemon(
+        &mut self,
+        id: DaemonId,
+        band: FrequencyBand,
+        amplitude: f32,
+    ) -> Result<(), SpectralMixerError> {
+        band.validate()?;
+        if self.layers.get(&id).is_some_and(|l| !l.is_releasing()) {
+            return Err(SpectralMixerError::AlreadyRegistered(id));
+        }
+        let mut layer = WaveLayer::new(band, amplitude, self.next_seq);
+        self.next_seq += 1;
+        layer.advance_envelope(0.0);
+        self.layers.insert(id, layer);
+        self.spread_band_phases(band);
+        Ok(())
+    }
+
+    /// Update a registered daemon's band and amplitude, keeping its phase,
+    /// data and envelope. Cancels a pending release.
+    pub fn reregister(
+        &mut self,
+        id: &DaemonId,
+        band: FrequencyBand,
+        amplitude: f32,
+    ) -> Result<(), SpectralMixerError> {
+        band.validate()?;
+        let layer = self.layer_mut(id)?;
+        let previous = layer.band;
+        let moved = previous != band;
+        let revived = layer.is_releasing();
+        layer.band = band;
+        layer.amplitude = amplitude;
+        if revived {
+            // Attack again from the current level
+            layer.stage = EnvelopeStage::Attack;
+        }
+        if moved {
+            self.spread_band_phases(previous);
+        }
+        // A revived daemon rejoins its band's spread even if the band is
+        // unchanged
+        if moved || revived {
+            self.spread_band_phases(band);
+        }
+        Ok(())
+    }
+
+    /// Start the daemon's release; the layer is dropped by `tick` once it
+    /// has faded out. The daemons left in its band are re-spread. Returns
+    /// false if the daemon was not registered.
+    pub fn unregister_daemon(&mut self, id: &DaemonId) -> bool {
+        let Some(layer) = self.layers.get_mut(id) else {
+            return false;
+        };
+        let band = layer.band;
+        if !layer.is_releasing() {
+            layer.start_release();
+        }
+        if layer.envelope.release.is_zero() {
+            self.layers.remove(id);
+        }
+        self.spread_band_phases(band);
+        true
+    }
+
+    /// Registered or still-releasing layer
+    pub fn layer(&self, id: &DaemonId) -> Option<&WaveLayer> {
+        self.layers.get(id)
+    }
+
+    /// Daemons that are registered and not releasing
+    pub fn daemon_count(&self) -> usize {
+        self.layers.values().filter(|l| !l.is_releasing()).count()
+    }
+
+    fn layer_mut(&mut self, id: &DaemonId) -> Result<&mut WaveLayer, SpectralMixerError> {
+        self.layers
+            .get_mut(id)
+            .ok_or_else(|| SpectralMixerError::UnknownDaemon(id.clone()))
+    }
+
+    /// Whether `serialize_to_vat` writes each daemon's data; when off,
+    /// restored daemons come back with empty data
+    pub fn set_persist_daemon_data(&mut self, persist: bool) {
+        self.persist_daemon_data = persist;
+    }
+
+    pub fn persists_daemon_data(&self) -> bool {
+        self.persist_daemon_data
+    }
+
+    /// Replace a daemon's envelope; the current stage continues with the
+    /// new timings
+    pub fn set_envelope(
+        &mut self,
+        id: &DaemonId,
+        envelope: Envelope,
+    ) -> Result<(), SpectralMixerError> {
+        let layer = self.layer_mut(id)?;
+        layer.envelope = envelope;
+        layer.advance_envelope(0.0);
+        Ok(())
+    }
+
+    pub fn set_amplitude(
+        &mut self,
+        id: &DaemonId,
+        amplitude: f32,
+    ) -> Result<(), SpectralMixerError> {
+        self.layer_mut(id)?.amplitude = amplitude;
+        Ok(())
+    }
+
+    /// Set a daemon's phase offset in radians, replacing the auto-assigned one
+    pub fn set_phase(&mut self, id: &DaemonId, phase: f32) -> Result<(), SpectralMixerError> {
+        let layer = self.layer_mut(id)?;
+        layer.phase_offset = phase.rem_euclid(TAU);
+        layer.auto_phase = false;
+        Ok(())
+    }
+
+    /// Skip a daemon in `resolve_field` (ignored while any daemon is soloed)
+    pub fn set_muted(&mut self, id: &DaemonId, muted: bool) -> Result<(), SpectralMixerError> {
+        self.layer_mut(id)?.muted = muted;
+        Ok(())
+    }
+
+    /// Restrict `resolve_field` to soloed daemons, muted or not
+    pub fn set_solo(&mut self, id: &DaemonId, soloed: bool) -> Result<(), SpectralMixerError> {
+        self.layer_mut(id)?.soloed = soloed;
+        Ok(())
+    }
+
+    /// Give each auto-phased daemon in `band` the offset
+    /// `2π · index / band_count`, indexed in registration order
+    fn spread_band_phases(&mut self, band: FrequencyBand) {
+        let mut members: Vec<&mut WaveLayer> = self
+            .layers
+            .values_mut()
+            .filter(|l| l.band == band && !l.is_releasing())
+            .collect();
+        members.sort_by_key(|l| l.seq);
+        let count = members.len() as f32;
+        for (index, layer) in members.into_iter().enumerate() {
+            if layer.auto_phase {
+                layer.phase_offset = TAU * index as f32 / count;
+            }
+        }
+    }
+
+    pub fn update_daemon_data(
+        &mut self,
+        id: &DaemonId,
+        data: Vec<f32>,
+    ) -> Result<(), SpectralMixerError> {
+        self.layer_mut(id)?.data = data;
+        Ok(())
+    }
+
+    /// Advance every wave's phase by 2π · frequency · delta and its
+    /// envelope by delta, dropping layers whose release has finished
+    pub fn tick(&mut self, delta: Duration) {
+        let dt = delta.as_secs_f32();
+        for layer in self.layers.values_mut() {
+            layer.phase = (layer.phase + TAU * layer.band.frequency() * dt).rem_euclid(TAU);
+            layer.advance_envelope(dt);
+        }
+        self.layers
+            .retain(|_, layer| !(layer.is_releasing() && layer.gain <= 0.0));
+    }
+
+    /// Composite field in Hilbert order, normalized to [-1, 1]
+    ///
+    /// Only audible layers (soloed ones if any daemon is soloed, otherwise
+    /// unmuted ones) contribute. Normalization uses their target amplitudes,
+    /// so fading layers get quieter against the mix instead of being
+    /// rescaled to full strength.
+    pub fn resolve_field(&self) -> Vec<f32> {
+        let any_solo = self.layers.values().any(|l| l.soloed);
+        let audible: Vec<&WaveLayer> = self
+            .layers
+            .values()
+            .filter(|l| if any_solo { l.soloed } else { !l.muted })
+            .collect();
+        let total: f32 = audible.iter().map(|l| l.amplitude.abs()).sum();
+        let scale = if total > 0.0 { 1.0 / total } else { 0.0 };
+
+        (0..self.field_len)
+            .map(|d| {
+                let t = d as f32 / self.field_len as f32;
+                let sum: f32 = audible.iter().map(|l| l.sample(t, d)).sum();
+                (sum * scale).clamp(-1.0, 1.0)
+            })
+            .collect()
+    }
+}
+
+impl WaveLayer {
+    fn write_to_vat(&self, vat: &mut VatBuffer, with_data: bool) -> Result<(), VatError> {
+        self.band.write_to_vat(vat)?;
+        vat.write_f32(self.amplitude)?;
+        vat.write_f32(self.phase)?;
+        vat.write_f32(self.phase_offset)?;
+        vat.write_bool(self.auto_phase)?;
+        vat.write_f64(self.envelope.attack.as_secs_f64())?;
+        vat.write_f64(self.envelope.decay.as_secs_f64())?;
+        vat.write_f32(self.envelope.sustain)?;
+        vat.write_f64(self.envelope.release.as_secs_f64())?;
+        vat.write_f32(self.gain)?;
+        vat.write_u8(self.stage as u8)?;
+        vat.write_f32(self.release_from)?;
+        vat.write_bool(self.muted)?;
+        vat.write_bool(self.soloed)?;
+        vat.write_u64(self.seq)?;
+
+        let data: &[f32] = if with_data { &self.data } else { &[] };
+        vat.write_u32(data.len() as u32)?;
+        for value in data {
+            vat.write_f32(*value)?;
+        }
+        Ok(())
+    }
+
+    fn read_from_vat(vat: &mut VatBuffer) -> Result<Self, VatError> {
+        let duration = |secs: f64| {
+            Duration::try_from_secs_f64(secs)
+                .map_err(|e| VatError::DeserializationFailed(e.to_string()))
+        };
+
+        let band = FrequencyBand::read_from_vat(vat)?;
+        let amplitude = vat.read_f32()?;
+        let mut layer = WaveLayer::new(band, amplitude, 0);
+        layer.phase = vat.read_f32()?;
+        layer.phase_offset = vat.read_f32()?;
+        layer.auto_phase = vat.read_bool()?;
+        layer.envelope = Envelope {
+            attack: duration(vat.read_f64()?)?,
+            decay: duration(vat.read_f64()?)?,
+            sustain: vat.read_f32()?,
+            release: duration(vat.read_f64()?)?,
+        };
+        layer.gain = vat.read_f32()?;
+        layer.stage = match vat.read_u8()? {
+            0 => EnvelopeStage::Attack,
+            1 => EnvelopeStage::Decay,
+            2 => EnvelopeStage::Sustain,
+            3 => EnvelopeStage::Release,
+            tag => {
+                return Err(VatError::DeserializationFailed(format!(
+                    "unknown envelope stage {}",
+                    tag
+                )))
+            },
+        };
+        layer.release_from = vat.read_f32()?;
+        layer.muted = vat.read_bool()?;
+        layer.soloed = vat.read_bool()?;
+        layer.seq = vat.read_u64()?;
+
+        let len = vat.read_u32()? as usize;
+        layer.data = (0..len).map(|_| vat.read_f32()).collect::<Result<_, _>>()?;
+        Ok(layer)
+    }
+}
+
+/// Layout: daemon count and next sequence number, then per daemon (in
+/// registration order) its id, band, amplitude, phases, envelope state,
+/// mute/solo flags and optional data
+impl VatState for SpectralMixer {
+    fn vat_id(&self) -> VatId {
+        VatId::new(SPECTRAL_MIXER_VAT_ID)
+    }
+
+    fn serialize_to_vat(&self, vat: &mut VatBuffer) -> Result<(), VatError> {
+        let mut layers: Vec<(&DaemonId, &WaveLayer)> = self.layers.iter().collect();
+        layers.sort_by_key(|(_, layer)| layer.seq);
+
+        vat.write_u32(layers.len() as u32)?;
+        vat.write_u64(self.next_seq)?;
+        for (id, layer) in layers {
+            vat.write_string(id.namespace())?;
+            vat.write_u64(id.name_hash())?;
+            layer.write_to_vat(vat, self.persist_daemon_data)?;
+        }
+        Ok(())
+    }
+
+    /// Replaces every layer; the mixer is unchanged if the Vat is malformed
+    fn deserialize_from_vat(&mut self, vat: &mut VatBuffer) -> Result<(), VatError> {
+        let count = vat.read_u32()?;
+        let next_seq = vat.read_u64()?;
+        let mut layers = HashMap::with_capacity(count as usize);
+        for _ in 0..count {
+            let namespace = vat.read_string()?;
+            let id = DaemonId::from_parts(&namespace, vat.read_u64()?);
+            layers.insert(id, WaveLayer::read_from_vat(vat)?);
+        }
+
+        self.layers = layers;
+        self.next_seq = next_seq;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f32::consts::PI;
+
+    #[test]
+    fn test_slight_detuning_produces_beating() {
+        let a = DaemonId::from_name("alpha");
+        let b = DaemonId::from_name("beta");
+        let mut mixer = SpectralMixer::new(3);
+        let band = |hz: f32| FrequencyBand::custom(hz - 0.5, hz + 0.5).unwrap();
+        mixer.register_daemon(a.clone(), band(10.0), 1.0).unwrap();
+        mixer.register_daemon(b.clone(), band(10.5), 1.0).unwrap();
+        for id in [&a, &b] {
+            mixer.set_envelope(id, Envelope::INSTANT).unwrap();
+        }
+
+        // In phase: the two waves add up fully
+        let peak = |field: &[f32]| field.iter().fold(0.0f32, |m, v| m.max(v.abs()));
+        let initial = mixer.resolve_field();
+        assert_eq!(initial.len(), 64);
+        assert!(peak(&initial) > 0.95);
+
+        let mut fields = vec![initial];
+        let mut peaks = Vec::new();
+        for _ in 0..4 {
+            mixer.tick(Duration::from_millis(250));
+            let field = mixer.resolve_field();
+            assert_ne!(&field, fields.last().unwrap());
+            peaks.push(peak(&field));
+            fields.push(field);
+        }
+
+        // After 1 s the 0.5 Hz difference puts the waves in anti-phase
+        let dphi =
+            (mixer.layer(&b).unwrap().phase - mixer.layer(&a).unwrap().phase).rem_euclid(TAU);
+        assert!((dphi - std::f32::consts::PI).abs() < 1e-3);
+        assert!(
+            peaks[3] < 0.05,
+            "destructive interference, got {}",
+            peaks[3]
+        );
+        assert!(peaks[1] > 0.6);
+    }
+
+    #[test]
+    fn test_set_phase_offsets_daemon() {
+        let a = DaemonId::from_name("a");
+        let mut mixer = SpectralMixer::new(2);
+        mixer
+            .register_daemon(a.clone(), FrequencyBand::Alpha, 1.0)
+            .unwrap();
+        mixer.set_envelope(&a, Envelope::INSTANT).unwrap();
+        let before = mixer.resolve_field();
+
+        mixer.set_phase(&a, std::f32::consts::PI).unwrap();
+        let after = mixer.resolve_field();
+        for (x, y) in before.iter().zip(&after) {
+            assert!((x + y).abs() < 1e-5);
+        }
+
+        let unknown = DaemonId::from_name("missing");
+        assert_eq!(
+            mixer.set_phase(&unknown, 0.0),
+            Err(SpectralMixerError::UnknownDaemon(unknown))
+        );
+    }
+
+    #[test]
+    fn test_envelope_fades_in_and_defers_removal() {
+        let a = DaemonId::from_name("fader");
+        let mut mixer = SpectralMixer::new(2);
+        mixer
+            .register_daemon(a.clone(), FrequencyBand::Alpha, 0.8)
+            .unwrap();
+        mixer
+            .set_envelope(
+                &a,
+                Envelope::attack_release(Duration::from_secs(1), Duration::from_millis(500)),
+            )
+            .unwrap();
+
+        let amplitude = |m: &SpectralMixer| m.layer(&a).unwrap().effective_amplitude();
+        assert_eq!(amplitude(&mixer), 0.0);
+        assert!(mixer.resolve_field().iter().all(|v| *v == 0.0));
+
+        let mut ramp = Vec::new();
+        for _ in 0..4 {
+            mixer.tick(Duration::from_millis(250));
+            ramp.push(amplitude(&mixer));
+        }
+        for (got, want) in ramp.iter().zip([0.2, 0.4, 0.6, 0.8]) {
+            assert!((got - want).abs() < 1e-5, "{:?}", ramp);
+        }
+        mixer.tick(Duration::from_millis(250));
+        assert!((amplitude(&mixer) - 0.8).abs() < 1e-5);
+
+        // Release: still mixed (at falling amplitude) until it completes
+        assert!(mixer.unregister_daemon(&a));
+        assert_eq!(mixer.daemon_count(), 0);
+        assert!(mixer.layer(&a).unwrap().is_releasing());
+        mixer.tick(Duration::from_millis(250));
+        assert!((amplitude(&mixer) - 0.4).abs() < 1e-5);
+        assert!(mixer.resolve_field().iter().any(|v| *v != 0.0));
+
+        mixer.tick(Duration::from_millis(250));
+        assert!(mixer.layer(&a).is_none());
+        assert!(!mixer.unregister_daemon(&a));
+    }
+
+    #[test]
+    fn test_custom_band_frequency_within_range() {
+        assert_eq!(
+            FrequencyBand::custom(12.0, 12.0),
+            Err(SpectralMixerError::InvalidBand {
+                low_hz: 12.0,
+                high_hz: 12.0
+            })
+        );
+        assert!(FrequencyBand::custom(-1.0, 5.0).is_err());
+
+        let a = DaemonId::from_name("custom");
+        let mut mixer = SpectralMixer::new(2);
+        let invalid = FrequencyBand::Custom {
+            low_hz: 20.0,
+            high_hz: 10.0,
+        };
+        assert!(mixer.register_daemon(a.clone(), invalid, 1.0).is_err());
+        assert!(mixer.layer(&a).is_none());
+
+        let band = FrequencyBand::custom(40.0, 44.0).unwrap();
+        mixer.register_daemon(a.clone(), band, 1.0).unwrap();
+
+        // Measure the synthesized frequency from the phase advance
+        let dt = 0.001;
+        mixer.tick(Duration::from_secs_f32(dt));
+        let hz = mixer.layer(&a).unwrap().phase / (TAU * dt);
+        assert!((40.0..=44.0).contains(&hz), "{} Hz", hz);
+
+        // Presets are ranges too
+        let (low, high) = FrequencyBand::Alpha.range();
+        assert!((low..=high).contains(&FrequencyBand::Alpha.frequency()));
+    }
+
+    #[test]
+    fn test_duplicate_registration_requires_reregister() {
+        let a = DaemonId::from_name("dup");
+        let mut mixer = SpectralMixer::new(2);
+        mixer
+            .register_daemon(a.clone(), FrequencyBand::Low, 1.0)
+            .unwrap();
+        mixer.set_phase(&a, 1.0).unwrap();
+
+        assert_eq!(
+            mixer.register_daemon(a.clone(), FrequencyBand::High, 0.5),
+            Err(SpectralMixerError::AlreadyRegistered(a.clone()))
+        );
+        assert_eq!(mixer.layer(&a).unwrap().band, FrequencyBand::Low);
+
+        mixer.reregister(&a, FrequencyBand::High, 0.5).unwrap();
+        let layer = mixer.layer(&a).unwrap();
+        assert_eq!((layer.band, layer.amplitude), (FrequencyBand::High, 0.5));
+        assert_eq!(layer.phase_offset, 1.0);
+        assert_eq!(mixer.daemon_count(), 1);
+
+        let unknown = DaemonId::from_name("unknown");
+        assert_eq!(
+            mixer.reregister(&unknown, FrequencyBand::Mid, 1.0),
+            Err(SpectralMixerError::UnknownDaemon(unknown))
+        );
+
+        // A daemon that is fading out can be registered again
+        mixer.unregister_daemon(&a);
+        mixer
+            .register_daemon(a.clone(), FrequencyBand::Mid, 1.0)
+            .unwrap();
+        assert!(!mixer.layer(&a).unwrap().is_releasing());
+    }
+
+    #[test]
+    fn test_opposite_phases_cancel() {
+        let a = DaemonId::from_name("a");
+        let b = DaemonId::from_name("b");
+        let mut mixer = SpectralMixer::new(3);
+        for id in [&a, &b] {
+            mixer
+                .register_daemon(id.clone(), FrequencyBand::High, 1.0)
+                .unwrap();
+            mixer.set_envelope(id, Envelope::INSTANT).unwrap();
+        }
+        let peak = |field: &[f32]| field.iter().fold(0.0f32, |m, v| m.max(v.abs()));
+
+        // Same band: auto-assigned offsets of 0 and π
+        assert_eq!(mixer.layer(&a).unwrap().phase_offset, 0.0);
+        assert!((mixer.layer(&b).unwrap().phase_offset - PI).abs() < 1e-6);
+        assert!(peak(&mixer.resolve_field()) < 1e-5);
+
+        // Explicit offsets survive another daemon joining the band
+        mixer.set_phase(&a, 0.0).unwrap();
+        mixer.set_phase(&b, PI).unwrap();
+        let c = DaemonId::from_name("c");
+        mixer
+            .register_daemon(c.clone(), FrequencyBand::High, 1.0)
+            .unwrap();
+        assert!((mixer.layer(&b).unwrap().phase_offset - PI).abs() < 1e-6);
+        assert!((mixer.layer(&c).unwrap().phase_offset - 2.0 * TAU / 3.0).abs() < 1e-6);
+
+        mixer.unregister_daemon(&c);
+        mixer.tick(Duration::from_secs(1));
+        assert!(peak(&mixer.resolve_field()) < 1e-4);
+        mixer.set_phase(&b, 0.0).unwrap();
+        assert!(peak(&mixer.resolve_field()) > 0.95);
+    }
+
+    #[test]
+    fn test_unregister_respreads_band() {
+        let ids = ["a", "b", "c"].map(DaemonId::from_name);
+        let mut mixer = SpectralMixer::new(3);
+        for id in &ids {
+            mixer
+                .register_daemon(id.clone(), FrequencyBand::Mid, 1.0)
+                .unwrap();
+        }
+        let offset = |mixer: &SpectralMixer, id| mixer.layer(id).unwrap().phase_offset;
+        assert!((offset(&mixer, &ids[2]) - 2.0 * TAU / 3.0).abs() < 1e-6);
+
+        // The two remaining daemons move to 0 and π while b fades out
+        mixer.unregister_daemon(&ids[1]);
+        assert_eq!(offset(&mixer, &ids[0]), 0.0);
+        assert!((offset(&mixer, &ids[2]) - PI).abs() < 1e-6);
+
+        // Moving a daemon to another band re-spreads the band it left
+        mixer.reregister(&ids[0], FrequencyBand::Low, 1.0).unwrap();
+        assert_eq!(offset(&mixer, &ids[2]), 0.0);
+
+        // Cancelling b's release in the same band brings it back into the
+        // spread
+        mixer.reregister(&ids[1], FrequencyBand::Mid, 1.0).unwrap();
+        assert_eq!(offset(&mixer, &ids[1]), 0.0);
+        assert!((offset(&mixer, &ids[2]) - PI).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_solo_overrides_mute() {
+        let ids = ["a", "b", "c"].map(DaemonId::from_name);
+        let mut mixer = SpectralMixer::new(2);
+        let bands = [FrequencyBand::Low, FrequencyBand::Mid, FrequencyBand::High];
+        for (id, band) in ids.iter().zip(bands) {
+            mixer.register_daemon(id.clone(), band, 1.0).unwrap();
+            mixer.set_envelope(id, Envelope::INSTANT).unwrap();
+        }
+        mixer.tick(Duration::from_millis(10));
+        let [a, b, c] = ids;
+        // Field of one daemon on its own
+        let only = |mixer: &SpectralMixer, id: &DaemonId| {
+            let mut single = SpectralMixer::new(2);
+            single
+                .layers
+                .insert(id.clone(), mixer.layer(id).unwrap().clone());
+            single.resolve_field()
+        };
+        let (only_a, only_b) = (only(&mixer, &a), only(&mixer, &b));
+
+        // Muting drops a daemon from the mix
+        mixer.set_muted(&b, true).unwrap();
+        mixer.set_muted(&c, true).unwrap();
+        assert_eq!(mixer.resolve_field(), only_a);
+
+        // A soloed daemon is heard even while muted; unsoloed ones are not
+        mixer.set_solo(&b, true).unwrap();
+        assert_eq!(mixer.resolve_field(), only_b);
+        assert!(mixer.layer(&b).unwrap().is_muted());
+
+        mixer.set_solo(&b, false).unwrap();
+        mixer.set_muted(&a, true).unwrap();
+        assert!(mixer.resolve_field().iter().all(|&v| v == 0.0));
+
+        // Nothing is unregistered along the way
+        assert_eq!(mixer.daemon_count(), 3);
+        let unknown = DaemonId::from_name("missing");
+        assert_eq!(
+            mixer.set_solo(&unknown, true),
+            Err(SpectralMixerError::UnknownDaemon(unknown))
+        );
+    }
+
+    #[test]
+    fn test_adsr_ramps_instead_of_jumping() {
+        let a = DaemonId::from_name("adsr");
+        let mut mixer = SpectralMixer::new(2);
+        mixer
+            .register_daemon(a.clone(), FrequencyBand::Beta, 1.0)
+            .unwrap();
+        mixer
+            .set_envelope(
+                &a,
+                Envelope {
+                    attack: Duration::from_millis(400),
+                    decay: Duration::from_millis(400),
+                    sustain: 0.5,
+                    release: Duration::from_millis(200),
+                },
+            )
+            .unwrap();
+
+        let level = |m: &SpectralMixer| m.layer(&a).map_or(0.0, |l| l.effective_amplitude());
+        let mut levels = vec![level(&mixer)];
+        let mut stages = Vec::new();
+        for _ in 0..10 {
+            mixer.tick(Duration::from_millis(100));
+            levels.push(level(&mixer));
+            stages.push(mixer.layer(&a).unwrap().stage());
+        }
+        mixer.unregister_daemon(&a);
+        for _ in 0..3 {
+            mixer.tick(Duration::from_millis(100));
+            levels.push(level(&mixer));
+        }
+
+        let want = [
+            0.0, 0.25, 0.5, 0.75, 1.0, // attack
+            0.875, 0.75, 0.625, 0.5, // decay
+            0.5, 0.5, // sustain
+            0.25, 0.0, 0.0, // release
+        ];
+        for (got, want) in levels.iter().zip(want) {
+            assert!((got - want).abs() < 1e-5, "{:?}", levels);
+        }
+        // No step is larger than one tick's worth of the steepest stage
+        assert!(levels
+            .windows(2)
+            .all(|w| (w[1] - w[0]).abs() <= 0.25 + 1e-5));
+        assert_eq!(stages[0], EnvelopeStage::Attack);
+        assert_eq!(stages[4], EnvelopeStage::Decay);
+        assert_eq!(stages[9], EnvelopeStage::Sustain);
+        assert!(mixer.layer(&a).is_none());
+    }
+
+    #[test]
+    fn test_vat_round_trip_restores_daemons() {
+        let ids = [
+            DaemonId::from_name("alpha"),
+            DaemonId::namespaced("tools", "beta"),
+            DaemonId::from_name("gamma"),
+        ];
+        let mut mixer = SpectralMixer::new(3);
+        mixer
+            .register_daemon(ids[0].clone(), FrequencyBand::Alpha, 1.0)
+            .unwrap();
+        mixer
+            .register_daemon(
+                ids[1].clone(),
+                FrequencyBand::custom(3.0, 5.0).unwrap(),
+                0.5,
+            )
+            .unwrap();
+        mixer
+            .register_daemon(ids[2].clone(), FrequencyBand::Alpha, 0.25)
+            .unwrap();
+        mixer.set_phase(&ids[1], 1.25).unwrap();
+        mixer.set_muted(&ids[2], true).unwrap();
+        mixer.update_daemon_data(&ids[0], vec![0.5, 1.0]).unwrap();
+        mixer.tick(Duration::from_millis(100));
+
+        // Without data: everything but the per-daemon data comes back
+        let mut buffer = mixer.to_vat_buffer().unwrap();
+        let mut restored = SpectralMixer::new(3);
+        restored.from_vat_buffer(&mut buffer).unwrap();
+        assert_eq!(restored.daemon_count(), 3);
+        for id in &ids {
+            let (before, after) = (mixer.layer(id).unwrap(), restored.layer(id).unwrap());
+            assert_eq!(after.band, before.band);
+            assert_eq!(after.amplitude, before.amplitude);
+            assert_eq!(after.phase, before.phase);
+            assert_eq!(after.phase_offset, before.phase_offset);
+            assert_eq!(after.effective_amplitude(), before.effective_amplitude());
+            assert_eq!(after.is_muted(), before.is_muted());
+        }
+        assert!(restored.layer(&ids[0]).unwrap().data.is_empty());
+        assert_eq!(ids[1].namespace(), "tools");
+
+        // With data: the restored field is identical
+        mixer.set_persist_daemon_data(true);
+        let mut buffer = mixer.to_vat_buffer().unwrap();
+        let mut restored = SpectralMixer::new(3);
+        restored.from_vat_buffer(&mut buffer).unwrap();
+        assert_eq!(restored.layer(&ids[0]).unwrap().data, vec![0.5, 1.0]);
+        assert_eq!(restored.resolve_field(), mixer.resolve_field());
+
+        // Later registrations keep spreading the band after a restore (3rd of 3)
+        let delta = DaemonId::from_name("delta");
+        restored
+            .register_daemon(delta.clone(), FrequencyBand::Alpha, 1.0)
+            .unwrap();
+        assert!((restored.layer(&delta).unwrap().phase_offset - 2.0 * TAU / 3.0).abs() < 1e-6);
+    }
+}
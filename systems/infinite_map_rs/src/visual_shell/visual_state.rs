@@ -0,0 +1,187 @@
+//! Visual State - Neural activations laid out as a graph on the Hilbert grid
+//!
+//! Each activation becomes a node at its Hilbert curve position, so
+//! neighbouring neurons stay spatially close; attention weights between
+//! consecutive neurons become synaptic connections.
+
+use super::design_tokens::DesignTokens;
+use crate::hilbert::HilbertCurve;
+
+/// A neuron drawn on the grid
+#[derive(Debug, Clone, PartialEq)]
+pub struct NeuralNode {
+    /// Index into the activation vector (= Hilbert distance)
+    pub index: usize,
+    /// Grid cell (x, y)
+    pub position: (u32, u32),
+    pub activation: f32,
+    /// RGB after design tokens and confidence are applied
+    pub color: [f32; 3],
+}
+
+/// A link between two nodes (by node index)
+#[derive(Debug, Clone, PartialEq)]
+pub struct SynapticConnection {
+    pub from: usize,
+    pub to: usize,
+    pub strength: f32,
+}
+
+/// Graph built from the latest neural update
+#[derive(Clone)]
+pub struct VisualState {
+    curve: HilbertCurve,
+    nodes: Vec<NeuralNode>,
+    connections: Vec<SynapticConnection>,
+    confidence: f32,
+}
+
+impl VisualState {
+    pub fn new(hilbert_order: u32) -> Self {
+        Self {
+            curve: HilbertCurve::from_order(hilbert_order),
+            nodes: Vec::new(),
+            connections: Vec::new(),
+            confidence: 1.0,
+        }
+    }
+
+    /// Rebuild nodes and connections from a neural snapshot
+    ///
+    /// Activations beyond the grid capacity are dropped. `attention_weights[i]`
+    /// links node `i` to node `i + 1` when above the token threshold.
+    pub fn update_from_neural(
+        &mut self,
+        activations: &[f32],
+        attention_weights: &[f32],
+        _memory_patterns: &[f32],
+        confidence: f32,
+        tokens: &DesignTokens,
+    ) {
+        let capacity = self.curve.total_pixels as usize;
+        self.confidence = confidence.clamp(0.0, 1.0);
+
+        self.nodes = activations
+            .iter()
+            .take(capacity)
+            .enumerate()
+            .map(|(index, &activation)| NeuralNode {
+                index,
+                position: self.curve.d2xy(index as u64),
+                activation,
+                color: tokens
+                    .confidence
+                    .apply(tokens.activation_color(activation), self.confidence),
+            })
+            .collect();
+
+        let node_count = self.nodes.len();
+        self.connections = attention_weights
+            .iter()
+            .enumerate()
+            .filter(|&(i, &w)| i + 1 < node_count && w > tokens.connection_threshold)
+            .map(|(i, &strength)| SynapticConnection {
+                from: i,
+                to: i + 1,
+                strength,
+            })
+            .collect();
+    }
+
+    pub fn confidence(&self) -> f32 {
+        self.confidence
+    }
+
+    /// Grid side length
+    pub fn resolution(&self) -> u32 {
+        self.curve.n
+    }
+
+    pub fn nodes(&self) -> &[NeuralNode] {
+        &self.nodes
+    }
+
+    pub fn connections(&self) -> &[SynapticConnection] {
+        &self.connections
+    }
+
+    /// Node whose grid cell contains (x, y), in grid units
+    pub fn node_at(&self, x: f32, y: f32) -> Option<&NeuralNode> {
+        if x < 0.0 || y < 0.0 || x >= self.curve.n as f32 || y >= self.curve.n as f32 {
+            return None;
+        }
+        let d = self.curve.xy2d(x as u32, y as u32);
+        self.nodes.get(d as usize)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::design_tokens::{saturation, ConfidenceToken};
+    use super::*;
+
+    #[test]
+    fn test_low_confidence_desaturates() {
+        let tokens = DesignTokens {
+            confidence: ConfidenceToken {
+                min_saturation: 0.0,
+                max_saturation: 1.0,
+                min_brightness: 1.0,
+                max_brightness: 1.0,
+            },
+            ..Default::default()
+        };
+        let activations = [0.2, 0.8, 1.0];
+
+        let mut state = VisualState::new(2);
+        state.update_from_neural(&activations, &[], &[], 0.1, &tokens);
+        let low: Vec<f32> = state.nodes().iter().map(|n| saturation(n.color)).collect();
+
+        state.update_from_neural(&activations, &[], &[], 0.9, &tokens);
+        let high: Vec<f32> = state.nodes().iter().map(|n| saturation(n.color)).collect();
+
+        for (low, high) in low.iter().zip(&high) {
+            assert!(low < high, "{} should be below {}", low, high);
+            // Saturation scales linearly with confidence for this token
+            assert!((low * 9.0 - high).abs() < 1e-4);
+        }
+
+        // Hue and brightness are untouched
+        let base = tokens.activation_color(1.0);
+        let max = state.nodes()[2].color.iter().cloned().fold(0.0, f32::max);
+        assert!((max - base.iter().cloned().fold(0.0, f32::max)).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_query_nodes_and_connections() {
+        let tokens = DesignTokens::default();
+        let activations = [0.0, 0.25, 0.5, 0.75, 1.0, 0.5];
+        let attention = [0.9, 0.05, 0.4];
+
+        let mut state = VisualState::new(2);
+        state.update_from_neural(&activations, &attention, &[], 1.0, &tokens);
+
+        let nodes = state.nodes();
+        assert_eq!(nodes.len(), 6);
+        // Hilbert positions on a 4x4 grid
+        let positions: Vec<(u32, u32)> = nodes.iter().map(|n| n.position).collect();
+        assert_eq!(
+            positions,
+            vec![(0, 0), (1, 0), (1, 1), (0, 1), (0, 2), (0, 3)]
+        );
+        assert_eq!(nodes[3].activation, 0.75);
+
+        // Weight 0.05 is below the default threshold
+        let links: Vec<(usize, usize)> =
+            state.connections().iter().map(|c| (c.from, c.to)).collect();
+        assert_eq!(links, vec![(0, 1), (2, 3)]);
+        assert_eq!(state.connections()[1].strength, 0.4);
+
+        assert_eq!(state.node_at(1.5, 1.2).map(|n| n.index), Some(2));
+        assert_eq!(state.node_at(0.1, 2.9).map(|n| n.index), Some(4));
+        // Empty cell, and outside the grid
+        assert!(state.node_at(3.0, 3.0).is_none());
+        assert!(state.node_at(-0.5, 0.0).is_none());
+        assert!(state.node_at(4.0, 0.0).is_none());
+    }
+}